@@ -1,4 +1,7 @@
-use std::path::Path;
+use std::{
+    env,
+    path::{Path, PathBuf},
+};
 
 fn main() -> anyhow::Result<()> {
     let proto_path = Path::new("proto/geyser.proto");
@@ -8,8 +11,13 @@ fn main() -> anyhow::Result<()> {
         .parent()
         .expect("proto file should reside in a directory");
 
+    // Used by the gRPC reflection service so that clients like grpcurl
+    // can explore the API without the proto files
+    let out_dir = PathBuf::from(env::var("OUT_DIR")?);
+
     tonic_build::configure()
         .protoc_arg("--experimental_allow_proto3_optional")
+        .file_descriptor_set_path(out_dir.join("geyser_descriptor.bin"))
         .compile(&[proto_path], &[proto_dir])?;
 
     Ok(())
@@ -2,6 +2,10 @@
 
 pub mod geyser {
     tonic::include_proto!("geyser");
+
+    /// Encoded descriptors of the geyser and solana storage protos.
+    pub const FILE_DESCRIPTOR_SET: &[u8] =
+        tonic::include_file_descriptor_set!("geyser_descriptor");
 }
 
 pub mod solana {
@@ -1,11 +1,15 @@
-use std::collections::HashSet;
+use std::{collections::HashSet, time::Duration};
 
 use conjunto_transwise::AccountChainSnapshotShared;
 use futures_util::future::BoxFuture;
 use magicblock_account_dumper::AccountDumperError;
 use magicblock_account_fetcher::AccountFetcherError;
 use magicblock_account_updates::AccountUpdatesError;
-use magicblock_core::magic_program;
+use magicblock_core::{
+    error_code::{HasErrorCode, MagicErrorCode},
+    magic_program,
+};
+use magicblock_telemetry::TraceContext;
 use solana_sdk::{clock::Slot, pubkey::Pubkey, signature::Signature};
 use thiserror::Error;
 use tokio::sync::oneshot::Sender;
@@ -13,7 +17,9 @@ use tokio::sync::oneshot::Sender;
 #[derive(Debug, Clone, Error)]
 pub enum AccountClonerError {
     #[error(transparent)]
-    SendError(#[from] tokio::sync::mpsc::error::SendError<Pubkey>),
+    SendError(
+        #[from] tokio::sync::mpsc::error::SendError<(Pubkey, TraceContext)>,
+    ),
 
     #[error(transparent)]
     RecvError(#[from] tokio::sync::oneshot::error::RecvError),
@@ -32,10 +38,35 @@ pub enum AccountClonerError {
 
     #[error("FailedToFetchSatisfactorySlot")]
     FailedToFetchSatisfactorySlot,
+
+    #[error("CloneWorkerStopped")]
+    CloneWorkerStopped,
+
+    #[error("ValidatorShuttingDown")]
+    ValidatorShuttingDown,
 }
 
 pub type AccountClonerResult<T> = Result<T, AccountClonerError>;
 
+impl HasErrorCode for AccountClonerError {
+    fn error_code(&self) -> MagicErrorCode {
+        use AccountClonerError::*;
+        match self {
+            SendError(_)
+            | RecvError(_)
+            | AccountUpdatesError(_)
+            | CloneWorkerStopped => MagicErrorCode::Internal,
+            AccountFetcherError(err) => err.error_code(),
+            AccountDumperError(err) => err.error_code(),
+            ProgramDataDoesNotExist => MagicErrorCode::CloneProgramDataNotFound,
+            FailedToFetchSatisfactorySlot => {
+                MagicErrorCode::CloneSlotNotReached
+            }
+            ValidatorShuttingDown => MagicErrorCode::RpcShuttingDown,
+        }
+    }
+}
+
 pub type AccountClonerListeners =
     Vec<Sender<AccountClonerResult<AccountClonerOutput>>>;
 
@@ -54,13 +85,109 @@ pub enum AccountClonerUnclonableReason {
     DelegatedAccountsNotClonedWhileHydrating,
 }
 
+impl HasErrorCode for AccountClonerUnclonableReason {
+    fn error_code(&self) -> MagicErrorCode {
+        use AccountClonerUnclonableReason::*;
+        match self {
+            AlreadyLocallyOverriden => MagicErrorCode::CloneLocallyOverridden,
+            NoCloningAllowed => MagicErrorCode::CloneNotAllowed,
+            IsBlacklisted => MagicErrorCode::CloneBlocklisted,
+            IsNotAnAllowedProgram => MagicErrorCode::CloneProgramNotAllowed,
+            DoesNotAllowFeePayerAccount => {
+                MagicErrorCode::CloneFeePayerNotAllowed
+            }
+            DoesNotAllowUndelegatedAccount => {
+                MagicErrorCode::CloneUndelegatedNotAllowed
+            }
+            DoesNotAllowDelegatedAccount => {
+                MagicErrorCode::CloneDelegatedNotAllowed
+            }
+            DoesNotAllowProgramAccount => {
+                MagicErrorCode::CloneProgramAccountNotAllowed
+            }
+            DelegatedAccountsNotClonedWhileHydrating => {
+                MagicErrorCode::CloneWhileHydrating
+            }
+        }
+    }
+}
+
+/// Which kinds of accounts may be cloned from the remote cluster.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClonePolicy {
+    pub feepayer_accounts: bool,
+    pub undelegated_accounts: bool,
+    pub delegated_accounts: bool,
+    pub program_accounts: bool,
+}
+
+impl ClonePolicy {
+    pub fn all() -> Self {
+        Self {
+            feepayer_accounts: true,
+            undelegated_accounts: true,
+            delegated_accounts: true,
+            program_accounts: true,
+        }
+    }
+
+    pub fn programs_only() -> Self {
+        Self {
+            program_accounts: true,
+            ..Self::none()
+        }
+    }
+
+    pub fn none() -> Self {
+        Self {
+            feepayer_accounts: false,
+            undelegated_accounts: false,
+            delegated_accounts: false,
+            program_accounts: false,
+        }
+    }
+
+    pub fn allows_any(&self) -> bool {
+        self.feepayer_accounts
+            || self.undelegated_accounts
+            || self.delegated_accounts
+            || self.program_accounts
+    }
+}
+
+/// How accounts that were cloned before are kept up to date with the
+/// remote cluster.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RefreshPolicy {
+    /// Accounts are cloned once and never refreshed
+    Off,
+    /// Accounts are monitored via websocket subscriptions and cloned again
+    /// once they changed on chain
+    Websocket,
+    /// Non-delegated accounts are fetched again once their clone is older
+    /// than [interval], for remotes that don't support websocket
+    /// subscriptions.
+    /// Delegated accounts are never refreshed since changes of their
+    /// delegation can only be learned about via subscriptions.
+    Poll { interval: Duration },
+}
+
+impl RefreshPolicy {
+    pub fn is_off(&self) -> bool {
+        matches!(self, RefreshPolicy::Off)
+    }
+
+    pub fn is_websocket(&self) -> bool {
+        matches!(self, RefreshPolicy::Websocket)
+    }
+}
+
+/// What the cloner is allowed to do, which accounts it clones is decided
+/// independently of how it keeps them up to date.
 #[derive(Debug, Clone)]
 pub struct AccountClonerPermissions {
-    pub allow_cloning_refresh: bool,
-    pub allow_cloning_feepayer_accounts: bool,
-    pub allow_cloning_undelegated_accounts: bool,
-    pub allow_cloning_delegated_accounts: bool,
-    pub allow_cloning_program_accounts: bool,
+    pub clone: ClonePolicy,
+    pub refresh: RefreshPolicy,
 }
 
 #[derive(Debug, Clone)]
@@ -69,6 +196,12 @@ pub enum AccountClonerOutput {
         account_chain_snapshot: AccountChainSnapshotShared,
         signature: Signature,
     },
+    /// The account no longer exists on chain, thus the state we cloned
+    /// before was removed from the bank.
+    ClonedAsClosed {
+        account_chain_snapshot: AccountChainSnapshotShared,
+        signature: Signature,
+    },
     Unclonable {
         pubkey: Pubkey,
         reason: AccountClonerUnclonableReason,
@@ -81,6 +214,10 @@ pub trait AccountCloner {
         &self,
         pubkey: &Pubkey,
     ) -> BoxFuture<AccountClonerResult<AccountClonerOutput>>;
+
+    /// Registers accounts whose state was provided locally, i.e. imported
+    /// from another ephemeral, such that they are never cloned again.
+    fn register_local_overrides(&self, pubkeys: &[Pubkey]);
 }
 
 pub fn standard_blacklisted_accounts(
@@ -8,7 +8,8 @@ use magicblock_account_fetcher::AccountFetcherError;
 use solana_sdk::pubkey::Pubkey;
 
 use crate::{
-    AccountCloner, AccountClonerError, AccountClonerOutput, AccountClonerResult,
+    AccountCloner, AccountClonerError, AccountClonerOutput,
+    AccountClonerResult, AccountClonerUnclonableReason,
 };
 
 #[derive(Debug, Clone, Default)]
@@ -43,4 +44,18 @@ impl AccountCloner for AccountClonerStub {
             ));
         Box::pin(ready(output))
     }
+
+    fn register_local_overrides(&self, pubkeys: &[Pubkey]) {
+        for pubkey in pubkeys {
+            self.set(
+                pubkey,
+                AccountClonerOutput::Unclonable {
+                    pubkey: *pubkey,
+                    reason:
+                        AccountClonerUnclonableReason::AlreadyLocallyOverriden,
+                    at_slot: u64::MAX,
+                },
+            );
+        }
+    }
 }
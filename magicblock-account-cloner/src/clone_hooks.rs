@@ -0,0 +1,177 @@
+use std::{
+    fmt,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use futures_util::future::{ready, BoxFuture};
+use log::*;
+use magicblock_core::robust_lock::RobustRwLock;
+use magicblock_metrics::metrics;
+use solana_sdk::{account::Account, pubkey::Pubkey, signature::Signature};
+
+/// How long a hook may take before cloning continues without waiting for it
+pub const DEFAULT_CLONE_HOOK_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Callbacks invoked around dumping the accounts cloned from the remote
+/// cluster into the bank, i.e. to apply custom policies to cloned accounts
+/// or notify an external service about them.
+///
+/// They are only invoked for the undelegated and delegated accounts that
+/// are actually dumped, neither for programs and fee payers nor for accounts
+/// which are reused since they did not change since they were cloned.
+pub trait AccountCloneHook: Send + Sync {
+    /// Invoked before the [account] with [pubkey] is dumped into the bank.
+    /// The account is dumped as modified by the hook, i.e. with its data
+    /// re-encrypted for the ephemeral.
+    fn before_dump<'a>(
+        &'a self,
+        _pubkey: &'a Pubkey,
+        _account: &'a mut Account,
+    ) -> BoxFuture<'a, ()> {
+        Box::pin(ready(()))
+    }
+
+    /// Invoked after the [account] with [pubkey] was dumped into the bank by
+    /// the transaction with [signature].
+    fn after_dump<'a>(
+        &'a self,
+        _pubkey: &'a Pubkey,
+        _account: &'a Account,
+        _signature: &'a Signature,
+    ) -> BoxFuture<'a, ()> {
+        Box::pin(ready(()))
+    }
+}
+
+#[derive(Clone)]
+struct RegisteredHook {
+    name: String,
+    hook: Arc<dyn AccountCloneHook>,
+}
+
+/// The hooks invoked by the [crate::RemoteAccountClonerWorker] when it
+/// dumps cloned accounts.
+///
+/// Hooks are invoked one after the other in the order they were registered.
+/// Each of them is given the configured timeout after which cloning
+/// continues without it, thus a slow hook delays but never stalls cloning.
+///
+/// Clones share the same hooks such that they can be registered after the
+/// worker was created.
+#[derive(Clone)]
+pub struct AccountCloneHooks {
+    hooks: Arc<RwLock<Vec<RegisteredHook>>>,
+    timeout: Duration,
+}
+
+impl Default for AccountCloneHooks {
+    fn default() -> Self {
+        Self::new(DEFAULT_CLONE_HOOK_TIMEOUT)
+    }
+}
+
+impl fmt::Debug for AccountCloneHooks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AccountCloneHooks")
+            .field("hooks", &self.hook_names())
+            .field("timeout", &self.timeout)
+            .finish()
+    }
+}
+
+impl AccountCloneHooks {
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            hooks: Default::default(),
+            timeout,
+        }
+    }
+
+    /// Registers the [hook] to be invoked after all hooks that were
+    /// registered before it, the [name] identifies it in logs and metrics.
+    pub fn register(&self, name: &str, hook: Arc<dyn AccountCloneHook>) {
+        self.hooks.write_robust().push(RegisteredHook {
+            name: name.to_string(),
+            hook,
+        });
+    }
+
+    /// The names of the registered hooks in the order they are invoked
+    pub fn hook_names(&self) -> Vec<String> {
+        self.hooks
+            .read_robust()
+            .iter()
+            .map(|registered| registered.name.clone())
+            .collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hooks.read_robust().is_empty()
+    }
+
+    /// Returns the [account] as modified by the hooks.
+    /// Each hook works on a copy which only replaces the account once the
+    /// hook finished, thus a hook that times out cannot leave it partially
+    /// modified.
+    pub(crate) async fn before_dump(
+        &self,
+        pubkey: &Pubkey,
+        account: &Account,
+    ) -> Account {
+        let mut account = account.clone();
+        for registered in self.registered_hooks() {
+            let mut modified = account.clone();
+            if self
+                .invoke(
+                    &registered.name,
+                    "before_dump",
+                    registered.hook.before_dump(pubkey, &mut modified),
+                )
+                .await
+            {
+                account = modified;
+            }
+        }
+        account
+    }
+
+    pub(crate) async fn after_dump(
+        &self,
+        pubkey: &Pubkey,
+        account: &Account,
+        signature: &Signature,
+    ) {
+        for registered in self.registered_hooks() {
+            self.invoke(
+                &registered.name,
+                "after_dump",
+                registered.hook.after_dump(pubkey, account, signature),
+            )
+            .await;
+        }
+    }
+
+    /// The hooks are copied such that the lock isn't held while invoking them
+    fn registered_hooks(&self) -> Vec<RegisteredHook> {
+        self.hooks.read_robust().clone()
+    }
+
+    /// Returns `false` if the [hook] did not finish in time.
+    async fn invoke(
+        &self,
+        name: &str,
+        stage: &str,
+        hook: BoxFuture<'_, ()>,
+    ) -> bool {
+        if tokio::time::timeout(self.timeout, hook).await.is_err() {
+            warn!(
+                "Account clone hook '{}' did not finish {} within {:?}, continuing without it",
+                name, stage, self.timeout
+            );
+            metrics::inc_clone_hook_timeout(name, stage);
+            return false;
+        }
+        true
+    }
+}
@@ -0,0 +1,47 @@
+use std::{collections::HashMap, sync::RwLock};
+
+use magicblock_core::robust_lock::RobustRwLock;
+use solana_sdk::{clock::Slot, pubkey::Pubkey};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CachedDelegationRecord {
+    /// The delegation record account of the delegated account
+    pub delegation_record_pubkey: Pubkey,
+    /// The slot at which the delegation record was fetched
+    pub at_slot: Slot,
+}
+
+/// Caches the delegation records of the delegated accounts we cloned, keyed
+/// by the delegated account.
+/// We subscribe to the delegation record accounts, thus an entry stays valid
+/// until an update to its delegation record is pushed via the websocket, i.e.
+/// when the account is undelegated or re-delegated.
+/// Until then the delegation of the account is known to be unchanged and no
+/// chain fetch is needed to decide if it should be re-cloned.
+#[derive(Debug, Default)]
+pub struct DelegationRecordCache {
+    records: RwLock<HashMap<Pubkey, CachedDelegationRecord>>,
+}
+
+impl DelegationRecordCache {
+    pub fn get(
+        &self,
+        delegated_account: &Pubkey,
+    ) -> Option<CachedDelegationRecord> {
+        self.records.read_robust().get(delegated_account).cloned()
+    }
+
+    pub fn insert(
+        &self,
+        delegated_account: Pubkey,
+        record: CachedDelegationRecord,
+    ) {
+        self.records
+            .write_robust()
+            .insert(delegated_account, record);
+    }
+
+    pub fn remove(&self, delegated_account: &Pubkey) {
+        self.records.write_robust().remove(delegated_account);
+    }
+}
@@ -1,9 +1,14 @@
 mod account_cloner;
 mod account_cloner_stub;
+mod clone_hooks;
+mod delegation_record_cache;
+mod mint_authority_override;
 mod remote_account_cloner_client;
 mod remote_account_cloner_worker;
+mod token_extensions;
 
 pub use account_cloner::*;
 pub use account_cloner_stub::*;
+pub use clone_hooks::*;
 pub use remote_account_cloner_client::*;
 pub use remote_account_cloner_worker::*;
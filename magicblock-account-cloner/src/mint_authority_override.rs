@@ -0,0 +1,91 @@
+use solana_sdk::{
+    account::Account, program_error::ProgramError, program_option::COption,
+    program_pack::Pack, pubkey::Pubkey,
+};
+use spl_token::state::Mint;
+
+/// Returns `true` if the [account] is owned by one of the SPL token programs.
+pub(crate) fn is_token_program_account(account: &Account) -> bool {
+    account.owner == spl_token::ID || account.owner == spl_token_2022::ID
+}
+
+/// Returns a copy of the [mint] account with its mint authority replaced
+/// by the provided [authority].
+/// Token-2022 mints share the base layout of SPL token mints and store
+/// their extensions after it, thus we only rewrite the base.
+pub(crate) fn override_mint_authority(
+    mint: &Account,
+    authority: &Pubkey,
+) -> Result<Account, ProgramError> {
+    let base = mint
+        .data
+        .get(..Mint::LEN)
+        .ok_or(ProgramError::InvalidAccountData)?;
+    let mut state = Mint::unpack_from_slice(base)?;
+    if !state.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+    state.mint_authority = COption::Some(*authority);
+
+    let mut account = mint.clone();
+    Mint::pack(state, &mut account.data[..Mint::LEN])?;
+    Ok(account)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mint_account(owner: Pubkey, extension_data: &[u8]) -> Account {
+        let mint = Mint {
+            mint_authority: COption::Some(Pubkey::new_unique()),
+            supply: 42,
+            decimals: 6,
+            is_initialized: true,
+            freeze_authority: COption::None,
+        };
+        let mut data = vec![0; Mint::LEN];
+        Mint::pack(mint, &mut data).unwrap();
+        data.extend_from_slice(extension_data);
+        Account {
+            lamports: 1_000_000,
+            data,
+            owner,
+            executable: false,
+            rent_epoch: 0,
+        }
+    }
+
+    #[test]
+    fn test_override_mint_authority_keeps_remaining_state() {
+        let authority = Pubkey::new_unique();
+        for (owner, extension_data) in
+            [(spl_token::ID, vec![]), (spl_token_2022::ID, vec![1, 2, 3])]
+        {
+            let account = mint_account(owner, &extension_data);
+            let overridden =
+                override_mint_authority(&account, &authority).unwrap();
+
+            let mint =
+                Mint::unpack_from_slice(&overridden.data[..Mint::LEN]).unwrap();
+            assert_eq!(mint.mint_authority, COption::Some(authority));
+            assert_eq!(mint.supply, 42);
+            assert_eq!(mint.decimals, 6);
+            assert_eq!(&overridden.data[Mint::LEN..], &extension_data[..]);
+            assert_eq!(overridden.owner, owner);
+            assert_eq!(overridden.lamports, account.lamports);
+        }
+    }
+
+    #[test]
+    fn test_override_mint_authority_rejects_non_mints() {
+        let authority = Pubkey::new_unique();
+        let mut account = mint_account(spl_token::ID, &[]);
+        account.data.truncate(Mint::LEN - 1);
+        assert!(override_mint_authority(&account, &authority).is_err());
+
+        let mut account = mint_account(spl_token::ID, &[]);
+        account.data.fill(0);
+        assert!(override_mint_authority(&account, &authority).is_err());
+    }
+}
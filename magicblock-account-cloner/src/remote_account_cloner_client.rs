@@ -1,5 +1,5 @@
 use std::{
-    collections::{hash_map::Entry, HashMap},
+    collections::{hash_map::Entry, HashMap, HashSet},
     sync::{Arc, RwLock},
 };
 
@@ -11,6 +11,8 @@ use magicblock_account_dumper::AccountDumper;
 use magicblock_account_fetcher::AccountFetcher;
 use magicblock_account_updates::AccountUpdates;
 use magicblock_accounts_api::InternalAccountProvider;
+use magicblock_core::robust_lock::RobustRwLock;
+use magicblock_telemetry::TraceContext;
 use solana_sdk::pubkey::Pubkey;
 use tokio::sync::{mpsc::UnboundedSender, oneshot::channel};
 
@@ -20,8 +22,9 @@ use crate::{
 };
 
 pub struct RemoteAccountClonerClient {
-    clone_request_sender: UnboundedSender<Pubkey>,
+    clone_request_sender: UnboundedSender<(Pubkey, TraceContext)>,
     clone_listeners: Arc<RwLock<HashMap<Pubkey, AccountClonerListeners>>>,
+    local_overrides: Arc<RwLock<HashSet<Pubkey>>>,
 }
 
 impl RemoteAccountClonerClient {
@@ -37,6 +40,7 @@ impl RemoteAccountClonerClient {
         Self {
             clone_request_sender: worker.get_clone_request_sender(),
             clone_listeners: worker.get_clone_listeners(),
+            local_overrides: worker.get_local_overrides(),
         }
     }
 }
@@ -46,25 +50,25 @@ impl AccountCloner for RemoteAccountClonerClient {
         &self,
         pubkey: &Pubkey,
     ) -> BoxFuture<AccountClonerResult<AccountClonerOutput>> {
-        let (should_request_clone, receiver) = match self
-            .clone_listeners
-            .write()
-            .expect("RwLock of RemoteAccountClonerClient.clone_listeners is poisoned")
-            .entry(*pubkey)
-        {
-            Entry::Vacant(entry) => {
-                let (sender, receiver) = channel();
-                entry.insert(vec![sender]);
-                (true, receiver)
-            }
-            Entry::Occupied(mut entry) => {
-                let (sender, receiver) = channel();
-                entry.get_mut().push(sender);
-                (false, receiver)
-            }
-        };
+        let (should_request_clone, receiver) =
+            match self.clone_listeners.write_robust().entry(*pubkey) {
+                Entry::Vacant(entry) => {
+                    let (sender, receiver) = channel();
+                    entry.insert(vec![sender]);
+                    (true, receiver)
+                }
+                Entry::Occupied(mut entry) => {
+                    let (sender, receiver) = channel();
+                    entry.get_mut().push(sender);
+                    (false, receiver)
+                }
+            };
         if should_request_clone {
-            if let Err(error) = self.clone_request_sender.send(*pubkey) {
+            // The clone is traced as part of the request that triggered it
+            if let Err(error) = self
+                .clone_request_sender
+                .send((*pubkey, TraceContext::current()))
+            {
                 return Box::pin(ready(Err(AccountClonerError::SendError(
                     error,
                 ))));
@@ -75,4 +79,10 @@ impl AccountCloner for RemoteAccountClonerClient {
             Err(error) => Err(AccountClonerError::RecvError(error)),
         }))
     }
+
+    fn register_local_overrides(&self, pubkeys: &[Pubkey]) {
+        self.local_overrides
+            .write_robust()
+            .extend(pubkeys.iter().copied());
+    }
 }
@@ -1,41 +1,86 @@
 use std::{
     collections::{hash_map::Entry, HashMap, HashSet},
+    mem,
     sync::{Arc, RwLock},
-    time::Duration,
+    time::{Duration, Instant},
     vec,
 };
 
 use conjunto_transwise::{
     AccountChainSnapshotShared, AccountChainState, DelegationRecord,
 };
+use dlp::pda::delegation_record_pda_from_delegated_account;
 use futures_util::future::join_all;
 use log::*;
 use magicblock_account_dumper::AccountDumper;
-use magicblock_account_fetcher::AccountFetcher;
+use magicblock_account_fetcher::{AccountFetcher, AccountFetcherError};
 use magicblock_account_updates::AccountUpdates;
 use magicblock_accounts_api::InternalAccountProvider;
+use magicblock_core::{
+    allowed_programs::AllowedPrograms,
+    circuit_breaker::CircuitBreaker,
+    hydrate_report::{HydrateFailure, HydrateReport},
+    robust_lock::RobustRwLock,
+};
 use magicblock_metrics::metrics;
 use magicblock_mutator::idl::{get_pubkey_anchor_idl, get_pubkey_shank_idl};
+use magicblock_program::validator::ValidatorStage;
+use magicblock_telemetry::{
+    child_span, in_span, record_error, KeyValue, TraceContext, TraceFutureExt,
+};
 use solana_sdk::{
     account::{Account, ReadableAccount},
     bpf_loader_upgradeable::{self, get_program_data_address},
     clock::Slot,
     pubkey::Pubkey,
     signature::Signature,
+    system_program,
 };
 use tokio::{
-    sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender},
+    sync::{
+        mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender},
+        watch,
+    },
     time::sleep,
 };
 use tokio_util::sync::CancellationToken;
 
 use crate::{
-    AccountClonerError, AccountClonerListeners, AccountClonerOutput,
-    AccountClonerPermissions, AccountClonerResult,
-    AccountClonerUnclonableReason,
+    delegation_record_cache::{CachedDelegationRecord, DelegationRecordCache},
+    mint_authority_override::{
+        is_token_program_account, override_mint_authority,
+    },
+    token_extensions::parse_token_extensions,
+    AccountCloneHooks, AccountClonerError, AccountClonerListeners,
+    AccountClonerOutput, AccountClonerPermissions, AccountClonerResult,
+    AccountClonerUnclonableReason, RefreshPolicy,
 };
 
-pub enum ValidatorStage {
+/// Fails all clones whose results are still awaited, used before the worker
+/// is restarted after it stopped unexpectedly, i.e. when it panicked mid-clone.
+/// Otherwise their listeners are never notified and since a clone is only
+/// requested if none is pending for the account, all later clones of the
+/// same accounts would wait forever.
+pub fn fail_pending_clones(
+    clone_listeners: &RwLock<HashMap<Pubkey, AccountClonerListeners>>,
+) {
+    let pending = mem::take(&mut *clone_listeners.write_robust());
+    for (pubkey, listeners) in pending {
+        warn!(
+            "Failing {} pending clone(s) of {} since the worker stopped",
+            listeners.len(),
+            pubkey
+        );
+        for listener in listeners {
+            let _ = listener.send(Err(AccountClonerError::CloneWorkerStopped));
+        }
+    }
+}
+
+/// The stage of the validator a clone happens in, along with the context
+/// needed to decide how to clone accounts in it.
+/// Only the stages in which accounts are cloned are distinguished.
+pub enum CloneStage {
     Hydrating {
         /// The identity of our validator
         validator_identity: Pubkey,
@@ -49,12 +94,12 @@ pub enum ValidatorStage {
     Running,
 }
 
-impl ValidatorStage {
+impl CloneStage {
     fn should_clone_delegated_account(
         &self,
         record: &DelegationRecord,
     ) -> bool {
-        use ValidatorStage::*;
+        use CloneStage::*;
         match self {
             // If an account is delegated then one of the following is true:
             // a) it is delegated to us and we made changes to it which we should not overwrite
@@ -90,16 +135,36 @@ pub struct RemoteAccountClonerWorker<IAP, AFE, AUP, ADU> {
     account_fetcher: AFE,
     account_updates: AUP,
     account_dumper: ADU,
-    allowed_program_ids: Option<HashSet<Pubkey>>,
+    allowed_programs: AllowedPrograms,
     blacklisted_accounts: HashSet<Pubkey>,
     payer_init_lamports: Option<u64>,
     permissions: AccountClonerPermissions,
     fetch_retries: u64,
-    clone_request_receiver: UnboundedReceiver<Pubkey>,
-    clone_request_sender: UnboundedSender<Pubkey>,
+    clone_request_receiver: UnboundedReceiver<(Pubkey, TraceContext)>,
+    clone_request_sender: UnboundedSender<(Pubkey, TraceContext)>,
     clone_listeners: Arc<RwLock<HashMap<Pubkey, AccountClonerListeners>>>,
     last_clone_output: Arc<RwLock<HashMap<Pubkey, AccountClonerOutput>>>,
+    /// When each account was last cloned, used to poll for changes if
+    /// refreshing via websocket subscriptions is not possible
+    last_clone_instant: Arc<RwLock<HashMap<Pubkey, Instant>>>,
+    /// Accounts whose last clone did not take effect in the bank, i.e. since
+    /// the deferred upgrade of a program failed, which are cloned again the
+    /// next time they are requested instead of using the cache
+    invalidated_clones: Arc<RwLock<HashSet<Pubkey>>>,
+    /// Accounts whose state was provided locally, i.e. imported from another
+    /// ephemeral, which are never cloned again
+    local_overrides: Arc<RwLock<HashSet<Pubkey>>>,
+    delegation_record_cache: DelegationRecordCache,
     validator_identity: Pubkey,
+    circuit_breaker: CircuitBreaker,
+    mint_authority_overrides: HashSet<Pubkey>,
+    /// The IDL accounts to use instead of the ones on chain by program id
+    idl_overrides: HashMap<Pubkey, (Pubkey, Account)>,
+    /// Invoked around dumping cloned accounts into the bank
+    clone_hooks: AccountCloneHooks,
+    /// Observes the validator stage in order to stop cloning once it
+    /// shuts down
+    validator_stage: Option<watch::Receiver<ValidatorStage>>,
 }
 
 impl<IAP, AFE, AUP, ADU> RemoteAccountClonerWorker<IAP, AFE, AUP, ADU>
@@ -130,7 +195,7 @@ where
 
             account_updates,
             account_dumper,
-            allowed_program_ids,
+            allowed_programs: AllowedPrograms::new(allowed_program_ids),
             blacklisted_accounts,
             payer_init_lamports,
             permissions,
@@ -139,11 +204,86 @@ where
             clone_request_sender,
             clone_listeners: Default::default(),
             last_clone_output: Default::default(),
+            last_clone_instant: Default::default(),
+            invalidated_clones: Default::default(),
+            local_overrides: Default::default(),
+            delegation_record_cache: Default::default(),
             validator_identity: validator_authority,
+            circuit_breaker: CircuitBreaker::disabled(),
+            mint_authority_overrides: Default::default(),
+            idl_overrides: Default::default(),
+            clone_hooks: Default::default(),
+            validator_stage: None,
         }
     }
 
-    pub fn get_clone_request_sender(&self) -> UnboundedSender<Pubkey> {
+    /// The mint authority of the provided SPL token mints is replaced by the
+    /// validator identity when they are cloned, which allows minting tokens
+    /// inside the ephemeral for testing.
+    pub fn with_mint_authority_overrides(
+        mut self,
+        mint_authority_overrides: HashSet<Pubkey>,
+    ) -> Self {
+        self.mint_authority_overrides = mint_authority_overrides;
+        self
+    }
+
+    /// The provided IDL accounts, keyed by the program id, are cloned with
+    /// their program instead of the IDL found on chain.
+    pub fn with_idl_overrides(
+        mut self,
+        idl_overrides: HashMap<Pubkey, (Pubkey, Account)>,
+    ) -> Self {
+        self.idl_overrides = idl_overrides;
+        self
+    }
+
+    /// Shares the allow-list with the admin RPC so programs can be allowed
+    /// at runtime, replaces the [allowed_program_ids] passed to [Self::new].
+    pub fn with_allowed_programs(
+        mut self,
+        allowed_programs: AllowedPrograms,
+    ) -> Self {
+        self.allowed_programs = allowed_programs;
+        self
+    }
+
+    /// While the [CircuitBreaker] is open, accounts that were cloned before
+    /// are served from the cache even if they changed on chain since.
+    pub fn with_circuit_breaker(
+        mut self,
+        circuit_breaker: CircuitBreaker,
+    ) -> Self {
+        self.circuit_breaker = circuit_breaker;
+        self
+    }
+
+    /// Replaces the hooks invoked around dumping cloned accounts, i.e. to
+    /// configure their timeout.
+    pub fn with_clone_hooks(mut self, clone_hooks: AccountCloneHooks) -> Self {
+        self.clone_hooks = clone_hooks;
+        self
+    }
+
+    /// Clone requests received once the validator is draining fail instead
+    /// of fetching accounts from chain, clones that are in progress complete.
+    pub fn with_validator_stage(
+        mut self,
+        validator_stage: watch::Receiver<ValidatorStage>,
+    ) -> Self {
+        self.validator_stage = Some(validator_stage);
+        self
+    }
+
+    /// The hooks invoked around dumping cloned accounts, hooks registered
+    /// with the returned clone are invoked as well.
+    pub fn get_clone_hooks(&self) -> AccountCloneHooks {
+        self.clone_hooks.clone()
+    }
+
+    pub fn get_clone_request_sender(
+        &self,
+    ) -> UnboundedSender<(Pubkey, TraceContext)> {
         self.clone_request_sender.clone()
     }
 
@@ -153,6 +293,14 @@ where
         self.clone_listeners.clone()
     }
 
+    pub fn get_invalidated_clones(&self) -> Arc<RwLock<HashSet<Pubkey>>> {
+        self.invalidated_clones.clone()
+    }
+
+    pub fn get_local_overrides(&self) -> Arc<RwLock<HashSet<Pubkey>>> {
+        self.local_overrides.clone()
+    }
+
     pub async fn start_clone_request_processing(
         &mut self,
         cancellation_token: CancellationToken,
@@ -164,7 +312,9 @@ where
                     join_all(
                         requests
                             .into_iter()
-                            .map(|request| self.process_clone_request(request))
+                            .map(|(pubkey, trace_context)| {
+                                self.process_clone_request(pubkey, trace_context)
+                            })
                     ).await;
                 }
                 _ = cancellation_token.cancelled() => {
@@ -174,20 +324,40 @@ where
         }
     }
 
-    async fn process_clone_request(&self, pubkey: Pubkey) {
+    async fn process_clone_request(
+        &self,
+        pubkey: Pubkey,
+        trace_context: TraceContext,
+    ) {
+        let trace_context = child_span(
+            "clone",
+            &trace_context,
+            vec![KeyValue::new("pubkey", pubkey.to_string())],
+        );
         // Actually run the whole cloning process on the bank, yield until done
-        let result = self.do_clone_or_use_cache(&pubkey).await;
+        let result = if self.is_shutting_down() {
+            debug!(
+                "Not cloning {} since the validator is shutting down",
+                pubkey
+            );
+            Err(AccountClonerError::ValidatorShuttingDown)
+        } else {
+            self.do_clone_or_use_cache(&pubkey)
+                .with_context(trace_context.clone())
+                .await
+        };
+        if let Err(err) = &result {
+            record_error(&trace_context, err);
+        }
         // Collecting the list of listeners awaiting for the clone to be done
-        let listeners = match self.clone_listeners
-            .write()
-            .expect(
-                "RwLock of RemoteAccountClonerWorker.clone_listeners is poisoned",
-            )
-            .entry(pubkey)
+        let listeners = match self.clone_listeners.write_robust().entry(pubkey)
         {
             // If the entry didn't exist for some reason, something is very wrong, just fail here
             Entry::Vacant(_) => {
-                return error!("Clone listeners were discarded improperly: {}", pubkey);
+                return error!(
+                    "Clone listeners were discarded improperly: {}",
+                    pubkey
+                );
             }
             // If the entry exists, we want to consume the list of listeners
             Entry::Occupied(entry) => entry.remove(),
@@ -200,23 +370,34 @@ where
         }
     }
 
+    fn is_shutting_down(&self) -> bool {
+        self.validator_stage
+            .as_ref()
+            .is_some_and(|stage| *stage.borrow() >= ValidatorStage::Draining)
+    }
+
     fn can_clone(&self) -> bool {
-        self.permissions.allow_cloning_feepayer_accounts
-            || self.permissions.allow_cloning_undelegated_accounts
-            || self.permissions.allow_cloning_delegated_accounts
-            || self.permissions.allow_cloning_program_accounts
+        self.permissions.clone.allows_any()
     }
 
-    pub async fn hydrate(&self) {
+    /// Clones the accounts found in our bank at startup again such that the
+    /// cache is populated and their state matches the chain, accounts
+    /// delegated to us keep the state of our bank.
+    /// Returns how many accounts were cloned, skipped or failed to clone.
+    pub async fn hydrate(&self) -> HydrateReport {
+        let mut report = HydrateReport::default();
         if !self.can_clone() {
             warn!("Cloning is disabled, no need to hydrate the cache");
-            return;
+            return report;
         }
         let account_keys = self
             .internal_account_provider
             .get_all_accounts()
             .into_iter()
             .filter(|(pubkey, _)| !self.blacklisted_accounts.contains(pubkey))
+            .filter(|(pubkey, _)| {
+                !self.local_overrides.read_robust().contains(pubkey)
+            })
             .filter(|(pubkey, acc)| {
                 // NOTE: there is an account that has ◎18,446,744,073.709553 which is present
                 // at validator start. We already blacklist the faucet and validator authority and
@@ -250,26 +431,39 @@ where
             let res = self
                 .do_clone_and_update_cache(
                     &pubkey,
-                    ValidatorStage::Hydrating {
+                    CloneStage::Hydrating {
                         validator_identity: self.validator_identity,
                         account_owner: owner,
                     },
                 )
                 .await;
             match res {
+                Ok(AccountClonerOutput::Unclonable { reason, .. }) => {
+                    debug!(pubkey:% = pubkey; "Skipped '{}': {:?}", pubkey, reason);
+                    report.skipped += 1;
+                }
                 Ok(output) => {
-                    debug!("Cloned '{}': {:?}", pubkey, output);
+                    debug!(pubkey:% = pubkey; "Cloned '{}': {:?}", pubkey, output);
+                    report.cloned += 1;
                 }
                 Err(err) => {
-                    // TODO: @@@ what to do here?
                     // Even empty accounts should clone fine with 0 lamports which would
                     // cover the case that the account was removed from chain in the meantime
                     // Thus if we encounter an error our validator cannot restore a proper
-                    // clone state and we should probably shut it down.
-                    error!("Failed to clone {} ('{:?}')", pubkey, err);
+                    // clone state, the validator decides if it can start anyways.
+                    error!(pubkey:% = pubkey; "Failed to clone {} ('{:?}')", pubkey, err);
+                    report.failures.push(HydrateFailure {
+                        pubkey,
+                        owner,
+                        reason: err.to_string(),
+                        may_be_delegated: owner != system_program::ID
+                            && owner != dlp::id()
+                            && !is_loader(&owner),
+                    });
                 }
             }
         }
+        report
     }
 
     async fn do_clone_or_use_cache(
@@ -284,6 +478,14 @@ where
                 at_slot: u64::MAX, // we should never try cloning, ever
             });
         }
+        // If the state of the account was provided locally, keep it as is
+        if self.local_overrides.read_robust().contains(pubkey) {
+            return Ok(AccountClonerOutput::Unclonable {
+                pubkey: *pubkey,
+                reason: AccountClonerUnclonableReason::AlreadyLocallyOverriden,
+                at_slot: u64::MAX, // we will never try cloning again
+            });
+        }
         // Check for the latest updates onchain for that account
         let last_known_update_slot = self
             .account_updates
@@ -291,26 +493,62 @@ where
             .unwrap_or(u64::MIN);
         // Check for the happy/fast path, we may already have cloned this account before
         match self.get_last_clone_output(pubkey) {
+            // If the remote cluster is failing, serve what we cloned last
+            Some(last_clone_output) if self.circuit_breaker.is_open() => {
+                Ok(last_clone_output)
+            }
+            // If the previous clone did not take effect, we need to clone it again
+            Some(_)
+                if self.invalidated_clones.write_robust().remove(pubkey) =>
+            {
+                self.do_clone_and_update_cache(pubkey, CloneStage::Running)
+                    .await
+            }
             // If we already cloned this account, check what the output of the clone was
             Some(last_clone_output) => match &last_clone_output {
                 // If the previous clone suceeded, we may be able to re-use it, need to check further
                 AccountClonerOutput::Cloned {
                     account_chain_snapshot: snapshot,
                     ..
+                }
+                | AccountClonerOutput::ClonedAsClosed {
+                    account_chain_snapshot: snapshot,
+                    ..
                 } => {
+                    // If we poll for changes and the clone is too old, fetch it again
+                    if self.is_due_for_poll(pubkey, snapshot) {
+                        self.do_clone_and_update_cache(
+                            pubkey,
+                            CloneStage::Running,
+                        )
+                        .await
+                    }
                     // If the clone output is recent enough, that directly
-                    if snapshot.at_slot >= last_known_update_slot {
+                    else if snapshot.at_slot >= last_known_update_slot {
+                        Ok(last_clone_output)
+                    }
+                    // If a delegated account changed on chain, but its delegation did not, we
+                    // keep our local state as source of truth and don't need to fetch it again
+                    else if self.is_delegation_unchanged(pubkey) {
                         Ok(last_clone_output)
                     }
                     // If the cloned account has been updated since clone, update the cache
                     else {
                         self.do_clone_and_update_cache(
                             pubkey,
-                            ValidatorStage::Running,
+                            CloneStage::Running,
                         )
                         .await
                     }
                 }
+                // If the program was added to the allow-list since, we need to clone it now
+                AccountClonerOutput::Unclonable {
+                    reason: AccountClonerUnclonableReason::IsNotAnAllowedProgram,
+                    ..
+                } if self.allowed_programs.is_allowed(pubkey) => {
+                    self.do_clone_and_update_cache(pubkey, CloneStage::Running)
+                        .await
+                }
                 // If the previous clone marked the account as unclonable, we may be able to re-use that output
                 AccountClonerOutput::Unclonable {
                     at_slot: until_slot,
@@ -324,7 +562,7 @@ where
                     else {
                         self.do_clone_and_update_cache(
                             pubkey,
-                            ValidatorStage::Running,
+                            CloneStage::Running,
                         )
                         .await
                     }
@@ -342,11 +580,8 @@ where
                 }
                 // If we need to clone it for the first time and update the cache
                 else {
-                    self.do_clone_and_update_cache(
-                        pubkey,
-                        ValidatorStage::Running,
-                    )
-                    .await
+                    self.do_clone_and_update_cache(pubkey, CloneStage::Running)
+                        .await
                 }
             }
         }
@@ -355,21 +590,103 @@ where
     async fn do_clone_and_update_cache(
         &self,
         pubkey: &Pubkey,
-        stage: ValidatorStage,
+        stage: CloneStage,
     ) -> AccountClonerResult<AccountClonerOutput> {
         let updated_clone_output = self.do_clone(pubkey, stage).await?;
+        self.update_delegation_record_cache(pubkey, &updated_clone_output)?;
         self.last_clone_output
-            .write()
-            .expect("RwLock of RemoteAccountClonerWorker.last_clone_output is poisoned")
+            .write_robust()
             .insert(*pubkey, updated_clone_output.clone());
+        self.last_clone_instant
+            .write_robust()
+            .insert(*pubkey, Instant::now());
         Ok(updated_clone_output)
     }
 
+    /// Returns `true` if we poll the remote cluster for changes and the
+    /// account was cloned longer than the poll interval ago.
+    /// Delegated accounts are never polled, we either own their state or
+    /// would need to monitor their delegation record to notice changes.
+    fn is_due_for_poll(
+        &self,
+        pubkey: &Pubkey,
+        snapshot: &AccountChainSnapshotShared,
+    ) -> bool {
+        let RefreshPolicy::Poll { interval } = self.permissions.refresh else {
+            return false;
+        };
+        if matches!(snapshot.chain_state, AccountChainState::Delegated { .. }) {
+            return false;
+        }
+        self.last_clone_instant
+            .read_robust()
+            .get(pubkey)
+            .map_or(true, |cloned_at| cloned_at.elapsed() >= interval)
+    }
+
+    /// Returns `true` if we know from the [DelegationRecordCache] that the
+    /// delegation of the account did not change since we last fetched it.
+    fn is_delegation_unchanged(&self, pubkey: &Pubkey) -> bool {
+        let Some(record) = self.delegation_record_cache.get(pubkey) else {
+            return false;
+        };
+        // Updates to the delegation record are only pushed to us after we
+        // subscribed to it, thus we cannot rely on records fetched before
+        let subscribed_before_fetch = self
+            .account_updates
+            .get_first_subscribed_slot(&record.delegation_record_pubkey)
+            .is_some_and(|slot| slot <= record.at_slot);
+        let updated_since_fetch = self
+            .account_updates
+            .get_last_known_update_slot(&record.delegation_record_pubkey)
+            .is_some_and(|slot| slot > record.at_slot);
+        if updated_since_fetch {
+            self.delegation_record_cache.remove(pubkey);
+        }
+        subscribed_before_fetch && !updated_since_fetch
+    }
+
+    fn update_delegation_record_cache(
+        &self,
+        pubkey: &Pubkey,
+        clone_output: &AccountClonerOutput,
+    ) -> AccountClonerResult<()> {
+        if let AccountClonerOutput::Cloned {
+            account_chain_snapshot,
+            ..
+        } = clone_output
+        {
+            if let AccountChainState::Delegated { .. } =
+                &account_chain_snapshot.chain_state
+            {
+                // Without monitoring we'd never learn about delegation changes
+                if self.permissions.refresh.is_websocket() {
+                    let delegation_record_pubkey =
+                        delegation_record_pda_from_delegated_account(pubkey);
+                    self.account_updates
+                        .ensure_account_monitoring(&delegation_record_pubkey)
+                        .map_err(AccountClonerError::AccountUpdatesError)?;
+                    self.delegation_record_cache.insert(
+                        *pubkey,
+                        CachedDelegationRecord {
+                            delegation_record_pubkey,
+                            at_slot: account_chain_snapshot.at_slot,
+                        },
+                    );
+                }
+                return Ok(());
+            }
+        }
+        self.delegation_record_cache.remove(pubkey);
+        Ok(())
+    }
+
     async fn do_clone(
         &self,
         pubkey: &Pubkey,
-        stage: ValidatorStage,
+        stage: CloneStage,
     ) -> AccountClonerResult<AccountClonerOutput> {
+        let clone_started_at = Instant::now();
         // If the account is blacklisted against cloning, no need to do anything anytime
         if self.blacklisted_accounts.contains(pubkey) {
             return Ok(AccountClonerOutput::Unclonable {
@@ -379,7 +696,8 @@ where
             });
         }
         // Get the latest state of the account
-        let account_chain_snapshot = if self.permissions.allow_cloning_refresh {
+        let account_chain_snapshot = if self.permissions.refresh.is_websocket()
+        {
             // Mark the account for monitoring, we want to start to detect futures updates on it
             // since we're cloning it now, it's now part of the validator monitored accounts
             // TODO(vbrunet)
@@ -418,8 +736,16 @@ where
                         }
                     }
                     Err(error) => {
-                        // If we failed to fetch too many time, stop here
-                        if fetch_count >= self.fetch_retries {
+                        // If we failed to fetch too many time or the remote
+                        // cluster is failing, stop here
+                        if fetch_count >= self.fetch_retries
+                            || matches!(
+                                error,
+                                AccountClonerError::AccountFetcherError(
+                                    AccountFetcherError::CircuitBreakerOpen
+                                )
+                            )
+                        {
                             return Err(error);
                         }
                     }
@@ -432,10 +758,29 @@ where
         };
         // Generate cloning transactions
         let signature = match &account_chain_snapshot.chain_state {
+            // If the account no longer exists on chain, but we cloned it with some state before,
+            // we need to remove that state unless the account is delegated to us
+            AccountChainState::FeePayer { lamports, owner }
+                if *lamports == 0
+                    && owner == &system_program::id()
+                    && self.has_cloned_state(pubkey) =>
+            {
+                if let Some(last_clone_output) =
+                    self.get_last_clone_output_if_delegated_to_us(pubkey)
+                {
+                    return Ok(last_clone_output);
+                }
+                let signature =
+                    self.do_clone_closed_account(pubkey, clone_started_at)?;
+                return Ok(AccountClonerOutput::ClonedAsClosed {
+                    account_chain_snapshot,
+                    signature,
+                });
+            }
             // If the account has no data, we can use it for lamport transfers only
             // We'll use the escrowed lamport value rather than its actual on-chain info
             AccountChainState::FeePayer { lamports, owner } => {
-                if !self.permissions.allow_cloning_feepayer_accounts {
+                if !self.permissions.clone.feepayer_accounts {
                     return Ok(AccountClonerOutput::Unclonable {
                         pubkey: *pubkey,
                         reason:
@@ -443,24 +788,26 @@ where
                         at_slot: account_chain_snapshot.at_slot,
                     });
                 }
-                self.do_clone_feepayer_account(pubkey, *lamports, owner)?
+                self.do_clone_feepayer_account(
+                    pubkey,
+                    *lamports,
+                    owner,
+                    clone_started_at,
+                )?
             }
             // If the account is present on-chain, but not delegated, it's just readonly data
             // We need to differenciate between programs and other accounts
             AccountChainState::Undelegated { account, .. } => {
                 // If it's an executable, we may have some special fetching to do
                 if account.executable {
-                    if let Some(allowed_program_ids) = &self.allowed_program_ids
-                    {
-                        if !allowed_program_ids.contains(pubkey) {
-                            return Ok(AccountClonerOutput::Unclonable {
-                                pubkey: *pubkey,
-                                reason: AccountClonerUnclonableReason::IsNotAnAllowedProgram,
-                                at_slot: u64::MAX, // we will never try again
-                            });
-                        }
+                    if !self.allowed_programs.is_allowed(pubkey) {
+                        return Ok(AccountClonerOutput::Unclonable {
+                            pubkey: *pubkey,
+                            reason: AccountClonerUnclonableReason::IsNotAnAllowedProgram,
+                            at_slot: u64::MAX, // we only try again once it is allowed
+                        });
                     }
-                    if !self.permissions.allow_cloning_program_accounts {
+                    if !self.permissions.clone.program_accounts {
                         return Ok(AccountClonerOutput::Unclonable {
                             pubkey: *pubkey,
                             reason: AccountClonerUnclonableReason::DoesNotAllowProgramAccount,
@@ -471,19 +818,25 @@ where
                         pubkey,
                         account,
                         Some(account_chain_snapshot.at_slot),
+                        clone_started_at,
                     )
                     .await?
                 }
                 // If it's not an executble, simpler rules apply
                 else {
-                    if !self.permissions.allow_cloning_undelegated_accounts {
+                    if !self.permissions.clone.undelegated_accounts {
                         return Ok(AccountClonerOutput::Unclonable {
                             pubkey: *pubkey,
                             reason: AccountClonerUnclonableReason::DoesNotAllowUndelegatedAccount,
                             at_slot: account_chain_snapshot.at_slot,
                         });
                     }
-                    self.do_clone_undelegated_account(pubkey, account)?
+                    self.do_clone_undelegated_account(
+                        pubkey,
+                        account,
+                        clone_started_at,
+                    )
+                    .await?
                 }
             }
             // If the account delegated on-chain, we need to apply some overrides
@@ -493,7 +846,7 @@ where
                 delegation_record,
                 ..
             } => {
-                if !self.permissions.allow_cloning_delegated_accounts {
+                if !self.permissions.clone.delegated_accounts {
                     return Ok(AccountClonerOutput::Unclonable {
                         pubkey: *pubkey,
                         reason:
@@ -518,7 +871,9 @@ where
                     account,
                     &delegation_record.owner,
                     delegation_record.delegation_slot,
-                )?
+                    clone_started_at,
+                )
+                .await?
             }
         };
         // Return the result
@@ -533,42 +888,95 @@ where
         pubkey: &Pubkey,
         lamports: u64,
         owner: &Pubkey,
+        clone_started_at: Instant,
     ) -> AccountClonerResult<Signature> {
         let lamports = self.payer_init_lamports.unwrap_or(lamports);
-        self.account_dumper
-            .dump_feepayer_account(pubkey, lamports, owner)
-            .map_err(AccountClonerError::AccountDumperError)
-            .inspect(|_| {
-                metrics::inc_account_clone(metrics::AccountClone::FeePayer {
+        in_span("clone.dump", vec![], || {
+            self.account_dumper
+                .dump_feepayer_account(pubkey, lamports, owner)
+        })
+        .map_err(AccountClonerError::AccountDumperError)
+        .inspect(|_| {
+            metrics::observe_account_clone(
+                metrics::AccountClone::FeePayer {
                     pubkey: &pubkey.to_string(),
-                });
-            })
+                },
+                0,
+                clone_started_at.elapsed(),
+            );
+        })
+    }
+
+    fn do_clone_closed_account(
+        &self,
+        pubkey: &Pubkey,
+        clone_started_at: Instant,
+    ) -> AccountClonerResult<Signature> {
+        // Dumping an empty account removes it from the bank and since the
+        // account can't be delegated anymore we also stop auditing it
+        let account = Account {
+            owner: system_program::id(),
+            ..Default::default()
+        };
+        in_span("clone.dump", vec![], || {
+            self.account_dumper
+                .dump_undelegated_account(pubkey, &account)
+        })
+        .map_err(AccountClonerError::AccountDumperError)
+        .inspect(|_| {
+            metrics::observe_account_clone(
+                metrics::AccountClone::Closed {
+                    pubkey: &pubkey.to_string(),
+                },
+                0,
+                clone_started_at.elapsed(),
+            );
+        })
     }
 
-    fn do_clone_undelegated_account(
+    async fn do_clone_undelegated_account(
         &self,
         pubkey: &Pubkey,
         account: &Account,
+        clone_started_at: Instant,
     ) -> AccountClonerResult<Signature> {
-        self.account_dumper
-            .dump_undelegated_account(pubkey, account)
-            .map_err(AccountClonerError::AccountDumperError)
-            .inspect(|_| {
-                metrics::inc_account_clone(
-                    metrics::AccountClone::Undelegated {
-                        pubkey: &pubkey.to_string(),
-                        owner: &account.owner().to_string(),
-                    },
-                );
-            })
+        if let Some(extensions) = parse_token_extensions(account)
+            .filter(|extensions| !extensions.is_empty())
+        {
+            debug!(
+                "Cloning Token-2022 account '{}' with extensions {:?}",
+                pubkey, extensions
+            );
+        }
+        let overridden_mint = self.try_override_mint_authority(pubkey, account);
+        let account = overridden_mint.as_ref().unwrap_or(account);
+        let account = self.clone_hooks.before_dump(pubkey, account).await;
+        let signature = in_span("clone.dump", vec![], || {
+            self.account_dumper
+                .dump_undelegated_account(pubkey, &account)
+        })
+        .map_err(AccountClonerError::AccountDumperError)?;
+        metrics::observe_account_clone(
+            metrics::AccountClone::Undelegated {
+                pubkey: &pubkey.to_string(),
+                owner: &account.owner().to_string(),
+            },
+            account.data.len(),
+            clone_started_at.elapsed(),
+        );
+        self.clone_hooks
+            .after_dump(pubkey, &account, &signature)
+            .await;
+        Ok(signature)
     }
 
-    fn do_clone_delegated_account(
+    async fn do_clone_delegated_account(
         &self,
         pubkey: &Pubkey,
         account: &Account,
         owner: &Pubkey,
         delegation_slot: Slot,
+        clone_started_at: Instant,
     ) -> AccountClonerResult<Signature> {
         // If we already cloned this account from the same delegation slot
         // Keep the local state as source of truth even if it changed on-chain
@@ -587,15 +995,62 @@ where
             }
         };
         // If its the first time we're seeing this delegated account, dump it to the bank
-        self.account_dumper
-            .dump_delegated_account(pubkey, account, owner)
-            .map_err(AccountClonerError::AccountDumperError)
-            .inspect(|_| {
-                metrics::inc_account_clone(metrics::AccountClone::Delegated {
-                    pubkey: &pubkey.to_string(),
-                    owner: &owner.to_string(),
-                });
-            })
+        let account = self.clone_hooks.before_dump(pubkey, account).await;
+        let signature = in_span("clone.dump", vec![], || {
+            self.account_dumper
+                .dump_delegated_account(pubkey, &account, owner)
+        })
+        .map_err(AccountClonerError::AccountDumperError)?;
+        metrics::observe_account_clone(
+            metrics::AccountClone::Delegated {
+                pubkey: &pubkey.to_string(),
+                owner: &owner.to_string(),
+            },
+            account.data.len(),
+            clone_started_at.elapsed(),
+        );
+        self.clone_hooks
+            .after_dump(pubkey, &account, &signature)
+            .await;
+        Ok(signature)
+    }
+
+    fn try_override_mint_authority(
+        &self,
+        pubkey: &Pubkey,
+        account: &Account,
+    ) -> Option<Account> {
+        if !self.mint_authority_overrides.contains(pubkey)
+            || !is_token_program_account(account)
+        {
+            return None;
+        }
+        match override_mint_authority(account, &self.validator_identity) {
+            Ok(account) => {
+                // The authorities of Token-2022 extensions, i.e. the transfer
+                // fee config, are kept, thus we audit which ones the mint has
+                let extensions = parse_token_extensions(&account)
+                    .map(|extensions| extensions.extension_types)
+                    .unwrap_or_default();
+                info!(
+                    target: "audit",
+                    mint:% = pubkey,
+                    mint_authority:% = self.validator_identity,
+                    extensions:? = extensions;
+                    "Overriding mint authority of cloned mint '{}' with '{}'",
+                    pubkey,
+                    self.validator_identity
+                );
+                Some(account)
+            }
+            Err(err) => {
+                warn!(
+                    "Not overriding mint authority of '{}' since it is not a valid mint: {:?}",
+                    pubkey, err
+                );
+                None
+            }
+        }
     }
 
     async fn do_clone_program_accounts(
@@ -603,6 +1058,7 @@ where
         pubkey: &Pubkey,
         account: &Account,
         min_context_slot: Option<Slot>,
+        clone_started_at: Instant,
     ) -> AccountClonerResult<Signature> {
         let program_id_pubkey = pubkey;
         let program_id_account = account;
@@ -616,11 +1072,12 @@ where
             // clone such programs like normal accounts
             return Err(AccountClonerError::ProgramDataDoesNotExist);
         } else if account.owner == solana_sdk::bpf_loader::ID {
-            let signature =
+            let signature = in_span("clone.dump", vec![], || {
                 self.account_dumper.dump_program_account_with_old_bpf(
                     program_id_pubkey,
                     program_id_account,
-                )?;
+                )
+            })?;
             return Ok(signature);
         }
 
@@ -632,21 +1089,28 @@ where
             .chain_state
             .account()
             .ok_or(AccountClonerError::ProgramDataDoesNotExist)?;
-        self.account_dumper
-            .dump_program_accounts(
+        let program_idl = self
+            .fetch_program_idl(program_id_pubkey, min_context_slot)
+            .await?;
+        in_span("clone.dump", vec![], || {
+            self.account_dumper.dump_program_accounts(
                 program_id_pubkey,
                 program_id_account,
                 program_data_pubkey,
                 program_data_account,
-                self.fetch_program_idl(program_id_pubkey, min_context_slot)
-                    .await?,
+                program_idl,
             )
-            .map_err(AccountClonerError::AccountDumperError)
-            .inspect(|_| {
-                metrics::inc_account_clone(metrics::AccountClone::Program {
+        })
+        .map_err(AccountClonerError::AccountDumperError)
+        .inspect(|_| {
+            metrics::observe_account_clone(
+                metrics::AccountClone::Program {
                     pubkey: &pubkey.to_string(),
-                });
-            })
+                },
+                program_data_account.data.len(),
+                clone_started_at.elapsed(),
+            );
+        })
     }
 
     async fn fetch_program_idl(
@@ -654,6 +1118,10 @@ where
         program_id_pubkey: &Pubkey,
         min_context_slot: Option<Slot>,
     ) -> AccountClonerResult<Option<(Pubkey, Account)>> {
+        // A local IDL takes precedence over the one on chain
+        if let Some(idl_override) = self.idl_overrides.get(program_id_pubkey) {
+            return Ok(Some(idl_override.clone()));
+        }
         // First check if we can find an anchor IDL
         let program_idl_anchor = self
             .try_fetch_program_idl_snapshot(
@@ -709,18 +1177,82 @@ where
     ) -> AccountClonerResult<AccountChainSnapshotShared> {
         self.account_fetcher
             .fetch_account_chain_snapshot(pubkey, min_context_slot)
+            .with_context(child_span(
+                "clone.fetch",
+                &TraceContext::current(),
+                vec![],
+            ))
             .await
             .map_err(AccountClonerError::AccountFetcherError)
     }
 
+    /// Returns `true` if the account was cloned with state that has to be
+    /// removed once the account no longer exists on chain, as opposed to
+    /// fee payers which commonly don't exist on chain.
+    fn has_cloned_state(&self, pubkey: &Pubkey) -> bool {
+        match self.get_last_clone_output(pubkey) {
+            Some(AccountClonerOutput::Cloned {
+                account_chain_snapshot,
+                ..
+            }) => !matches!(
+                account_chain_snapshot.chain_state,
+                AccountChainState::FeePayer { .. }
+            ),
+            Some(AccountClonerOutput::ClonedAsClosed { .. }) => true,
+            Some(AccountClonerOutput::Unclonable { .. }) => false,
+            // While hydrating we only know the state the account has in our bank
+            None => self
+                .internal_account_provider
+                .get_account(pubkey)
+                .is_some_and(|account| {
+                    !account.data().is_empty()
+                        || account.owner() != &system_program::id()
+                }),
+        }
+    }
+
+    /// Returns the last clone output if it shows that the account is delegated
+    /// to our validator, in which case our bank holds its latest state.
+    fn get_last_clone_output_if_delegated_to_us(
+        &self,
+        pubkey: &Pubkey,
+    ) -> Option<AccountClonerOutput> {
+        let last_clone_output = self.get_last_clone_output(pubkey)?;
+        let AccountClonerOutput::Cloned {
+            account_chain_snapshot,
+            ..
+        } = &last_clone_output
+        else {
+            return None;
+        };
+        let AccountChainState::Delegated {
+            delegation_record, ..
+        } = &account_chain_snapshot.chain_state
+        else {
+            return None;
+        };
+        // The authority of older delegation records is not set, in which case
+        // we assume the account is delegated to us since we cloned it as such
+        let authority = delegation_record.authority;
+        (authority == self.validator_identity || authority == Pubkey::default())
+            .then_some(last_clone_output)
+    }
+
     fn get_last_clone_output(
         &self,
         pubkey: &Pubkey,
     ) -> Option<AccountClonerOutput> {
-        self.last_clone_output
-            .read()
-            .expect("RwLock of RemoteAccountClonerWorker.last_clone_output is poisoned")
-            .get(pubkey)
-            .cloned()
+        self.last_clone_output.read_robust().get(pubkey).cloned()
     }
 }
+
+fn is_loader(owner: &Pubkey) -> bool {
+    [
+        solana_sdk::bpf_loader::ID,
+        solana_sdk::bpf_loader_deprecated::ID,
+        bpf_loader_upgradeable::ID,
+        solana_sdk::loader_v4::ID,
+        solana_sdk::native_loader::ID,
+    ]
+    .contains(owner)
+}
@@ -0,0 +1,114 @@
+use solana_sdk::{account::Account, program_pack::Pack};
+use spl_token_2022::{
+    extension::{
+        BaseState, BaseStateWithExtensions, ExtensionType, StateWithExtensions,
+    },
+    state::{Account as TokenAccount, Mint},
+};
+
+/// The Token-2022 extensions of a cloned mint or token account, which are
+/// stored after the base state shared with SPL token accounts.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct TokenExtensions {
+    pub extension_types: Vec<ExtensionType>,
+}
+
+impl TokenExtensions {
+    pub fn is_empty(&self) -> bool {
+        self.extension_types.is_empty()
+    }
+}
+
+/// Parses the extensions of the [account] if it is a Token-2022 mint or
+/// token account, returns `None` for any other account.
+pub(crate) fn parse_token_extensions(
+    account: &Account,
+) -> Option<TokenExtensions> {
+    if account.owner != spl_token_2022::ID {
+        return None;
+    }
+    // Only one of the layouts matches the data, thus we try both
+    parse_extensions::<Mint>(&account.data)
+        .or_else(|| parse_extensions::<TokenAccount>(&account.data))
+}
+
+fn parse_extensions<S: BaseState + Pack>(
+    data: &[u8],
+) -> Option<TokenExtensions> {
+    let state = StateWithExtensions::<S>::unpack(data).ok()?;
+    let extension_types = state.get_extension_types().ok()?;
+    Some(TokenExtensions { extension_types })
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::{program_option::COption, pubkey::Pubkey};
+    use spl_token_2022::extension::{
+        metadata_pointer::MetadataPointer, transfer_fee::TransferFeeConfig,
+        StateWithExtensionsMut,
+    };
+
+    use super::*;
+
+    fn token_2022_account(data: Vec<u8>) -> Account {
+        Account {
+            lamports: 1_000_000,
+            data,
+            owner: spl_token_2022::ID,
+            executable: false,
+            rent_epoch: 0,
+        }
+    }
+
+    fn mint_with_extensions(metadata_address: Pubkey) -> Vec<u8> {
+        let len = ExtensionType::try_calculate_account_len::<Mint>(&[
+            ExtensionType::TransferFeeConfig,
+            ExtensionType::MetadataPointer,
+        ])
+        .unwrap();
+        let mut data = vec![0; len];
+        let mut state =
+            StateWithExtensionsMut::<Mint>::unpack_uninitialized(&mut data)
+                .unwrap();
+        let config = state.init_extension::<TransferFeeConfig>(true).unwrap();
+        config.newer_transfer_fee.transfer_fee_basis_points = 50.into();
+        config.newer_transfer_fee.maximum_fee = 1_000.into();
+        let pointer = state.init_extension::<MetadataPointer>(true).unwrap();
+        pointer.metadata_address = Some(metadata_address).try_into().unwrap();
+        state.base = Mint {
+            mint_authority: COption::Some(Pubkey::new_unique()),
+            decimals: 6,
+            is_initialized: true,
+            ..Default::default()
+        };
+        state.pack_base();
+        state.init_account_type().unwrap();
+        data
+    }
+
+    #[test]
+    fn test_parse_mint_with_extensions() {
+        let account =
+            token_2022_account(mint_with_extensions(Pubkey::new_unique()));
+
+        let extensions = parse_token_extensions(&account).unwrap();
+        assert_eq!(
+            extensions.extension_types,
+            vec![
+                ExtensionType::TransferFeeConfig,
+                ExtensionType::MetadataPointer
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_ignores_non_token_2022_accounts() {
+        let mut account =
+            token_2022_account(mint_with_extensions(Pubkey::new_unique()));
+        account.owner = spl_token::ID;
+        assert_eq!(parse_token_extensions(&account), None);
+
+        let account = token_2022_account(vec![1, 2, 3]);
+        assert_eq!(parse_token_extensions(&account), None);
+    }
+}
@@ -1,20 +1,25 @@
-use std::collections::HashSet;
+use std::{collections::HashSet, panic::AssertUnwindSafe, time::Duration};
 
+use dlp::pda::delegation_record_pda_from_delegated_account;
+use futures_util::FutureExt;
 use magicblock_account_cloner::{
-    standard_blacklisted_accounts, AccountCloner, AccountClonerError,
-    AccountClonerOutput, AccountClonerPermissions,
-    AccountClonerUnclonableReason, RemoteAccountClonerClient,
-    RemoteAccountClonerWorker,
+    fail_pending_clones, standard_blacklisted_accounts, AccountCloner,
+    AccountClonerError, AccountClonerOutput, AccountClonerPermissions,
+    AccountClonerUnclonableReason, ClonePolicy, RefreshPolicy,
+    RemoteAccountClonerClient, RemoteAccountClonerWorker,
 };
 use magicblock_account_dumper::AccountDumperStub;
 use magicblock_account_fetcher::AccountFetcherStub;
 use magicblock_account_updates::AccountUpdatesStub;
 use magicblock_accounts_api::InternalAccountProviderStub;
+use magicblock_core::allowed_programs::AllowedPrograms;
 use magicblock_mutator::idl::{get_pubkey_anchor_idl, get_pubkey_shank_idl};
+use magicblock_program::validator::ValidatorStage;
 use solana_sdk::{
     bpf_loader_upgradeable::get_program_data_address,
     native_token::LAMPORTS_PER_SOL, pubkey::Pubkey, sysvar::clock,
 };
+use tokio::{sync::watch, time::timeout};
 use tokio_util::sync::CancellationToken;
 
 #[allow(clippy::too_many_arguments)]
@@ -82,11 +87,8 @@ fn setup_replica(
             &Pubkey::new_unique(),
         ),
         AccountClonerPermissions {
-            allow_cloning_refresh: false,
-            allow_cloning_feepayer_accounts: true,
-            allow_cloning_undelegated_accounts: true,
-            allow_cloning_delegated_accounts: true,
-            allow_cloning_program_accounts: true,
+            clone: ClonePolicy::all(),
+            refresh: RefreshPolicy::Off,
         },
     )
 }
@@ -113,11 +115,8 @@ fn setup_programs_replica(
             &Pubkey::new_unique(),
         ),
         AccountClonerPermissions {
-            allow_cloning_refresh: false,
-            allow_cloning_feepayer_accounts: false,
-            allow_cloning_undelegated_accounts: false,
-            allow_cloning_delegated_accounts: false,
-            allow_cloning_program_accounts: true,
+            clone: ClonePolicy::programs_only(),
+            refresh: RefreshPolicy::Off,
         },
     )
 }
@@ -144,11 +143,36 @@ fn setup_ephemeral(
             &Pubkey::new_unique(),
         ),
         AccountClonerPermissions {
-            allow_cloning_refresh: true,
-            allow_cloning_feepayer_accounts: true,
-            allow_cloning_undelegated_accounts: true,
-            allow_cloning_delegated_accounts: true,
-            allow_cloning_program_accounts: true,
+            clone: ClonePolicy::all(),
+            refresh: RefreshPolicy::Websocket,
+        },
+    )
+}
+
+fn setup_ephemeral_polling(
+    internal_account_provider: InternalAccountProviderStub,
+    account_fetcher: AccountFetcherStub,
+    account_updates: AccountUpdatesStub,
+    account_dumper: AccountDumperStub,
+    interval: Duration,
+) -> (
+    RemoteAccountClonerClient,
+    CancellationToken,
+    tokio::task::JoinHandle<()>,
+) {
+    setup_custom(
+        internal_account_provider,
+        account_fetcher,
+        account_updates,
+        account_dumper,
+        None,
+        standard_blacklisted_accounts(
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+        ),
+        AccountClonerPermissions {
+            clone: ClonePolicy::all(),
+            refresh: RefreshPolicy::Poll { interval },
         },
     )
 }
@@ -175,11 +199,8 @@ fn setup_offline(
             &Pubkey::new_unique(),
         ),
         AccountClonerPermissions {
-            allow_cloning_refresh: false,
-            allow_cloning_feepayer_accounts: false,
-            allow_cloning_undelegated_accounts: false,
-            allow_cloning_delegated_accounts: false,
-            allow_cloning_program_accounts: false,
+            clone: ClonePolicy::none(),
+            refresh: RefreshPolicy::Off,
         },
     )
 }
@@ -440,6 +461,83 @@ async fn test_clone_program_accounts_when_ephemeral_with_whitelist() {
     assert!(worker_handle.await.is_ok());
 }
 
+#[tokio::test]
+async fn test_clone_program_accounts_when_ephemeral_after_allowing_at_runtime()
+{
+    // Important pubkeys
+    let configured_program_id = Pubkey::new_unique();
+    let program_id = Pubkey::new_unique();
+    // Stubs
+    let internal_account_provider = InternalAccountProviderStub::default();
+    let account_fetcher = AccountFetcherStub::default();
+    let account_updates = AccountUpdatesStub::default();
+    let account_dumper = AccountDumperStub::default();
+    let allowed_programs =
+        AllowedPrograms::new(Some(HashSet::from([configured_program_id])));
+    // Create account cloner worker and client sharing the allow-list
+    let mut cloner_worker = RemoteAccountClonerWorker::new(
+        internal_account_provider.clone(),
+        account_fetcher.clone(),
+        account_updates.clone(),
+        account_dumper.clone(),
+        None,
+        standard_blacklisted_accounts(
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+        ),
+        Some(1_000 * LAMPORTS_PER_SOL),
+        AccountClonerPermissions {
+            clone: ClonePolicy::all(),
+            refresh: RefreshPolicy::Off,
+        },
+        Pubkey::new_unique(),
+    )
+    .with_allowed_programs(allowed_programs.clone());
+    let cloner = RemoteAccountClonerClient::new(&cloner_worker);
+    let cancellation_token = CancellationToken::new();
+    let worker_handle = {
+        let cloner_cancellation_token = cancellation_token.clone();
+        tokio::spawn(async move {
+            cloner_worker
+                .start_clone_request_processing(cloner_cancellation_token)
+                .await
+        })
+    };
+    // Account(s) involved
+    let program_data = get_program_data_address(&program_id);
+    let program_idl = get_pubkey_anchor_idl(&program_id).unwrap();
+    account_updates.set_first_subscribed_slot(program_id, 41);
+    account_updates.set_first_subscribed_slot(program_data, 41);
+    account_updates.set_first_subscribed_slot(program_idl, 41);
+    account_fetcher.set_executable_account(program_id, 42);
+    account_fetcher.set_undelegated_account(program_data, 42);
+    account_fetcher.set_undelegated_account(program_idl, 42);
+    // Run test
+    let result = cloner.clone_account(&program_id).await;
+    // Check expected result
+    assert!(matches!(
+        result,
+        Ok(AccountClonerOutput::Unclonable {
+            reason: AccountClonerUnclonableReason::IsNotAnAllowedProgram,
+            ..
+        })
+    ));
+    assert!(account_dumper.was_untouched(&program_id));
+    // Allow the program without restarting the worker
+    assert!(allowed_programs.add(program_id).unwrap());
+    // Run test
+    let result = cloner.clone_account(&program_id).await;
+    // Check expected result
+    assert!(matches!(result, Ok(AccountClonerOutput::Cloned { .. })));
+    assert_eq!(account_fetcher.get_fetch_count(&program_id), 2);
+    assert!(account_dumper.was_dumped_as_program_id(&program_id));
+    assert!(account_dumper.was_dumped_as_program_data(&program_data));
+    assert!(account_dumper.was_dumped_as_program_idl(&program_idl));
+    // Cleanup everything correctly
+    cancellation_token.cancel();
+    assert!(worker_handle.await.is_ok());
+}
+
 #[tokio::test]
 async fn test_clone_refuse_already_written_in_bank() {
     // Stubs
@@ -476,6 +574,46 @@ async fn test_clone_refuse_already_written_in_bank() {
     assert!(worker_handle.await.is_ok());
 }
 
+#[tokio::test]
+async fn test_clone_refuse_local_override_cloned_before() {
+    // Stubs
+    let internal_account_provider = InternalAccountProviderStub::default();
+    let account_fetcher = AccountFetcherStub::default();
+    let account_updates = AccountUpdatesStub::default();
+    let account_dumper = AccountDumperStub::default();
+    // Create account cloner worker and client
+    let (cloner, cancellation_token, worker_handle) = setup_ephemeral(
+        internal_account_provider.clone(),
+        account_fetcher.clone(),
+        account_updates.clone(),
+        account_dumper.clone(),
+        None,
+    );
+    // Account(s) involved
+    let imported_account = Pubkey::new_unique();
+    account_updates.set_first_subscribed_slot(imported_account, 41);
+    account_fetcher.set_undelegated_account(imported_account, 42);
+    let result = cloner.clone_account(&imported_account).await;
+    assert!(matches!(result, Ok(AccountClonerOutput::Cloned { .. })));
+    // Run test, the account changes on chain after its state was imported
+    cloner.register_local_overrides(&[imported_account]);
+    account_updates.set_last_known_update_slot(imported_account, 55);
+    account_fetcher.set_undelegated_account(imported_account, 55);
+    let result = cloner.clone_account(&imported_account).await;
+    // Check expected result
+    assert!(matches!(
+        result,
+        Ok(AccountClonerOutput::Unclonable {
+            reason: AccountClonerUnclonableReason::AlreadyLocallyOverriden,
+            ..
+        })
+    ));
+    assert_eq!(account_fetcher.get_fetch_count(&imported_account), 1);
+    // Cleanup everything correctly
+    cancellation_token.cancel();
+    assert!(worker_handle.await.is_ok());
+}
+
 #[tokio::test]
 async fn test_clone_refuse_blacklisted_account() {
     // Stubs
@@ -1085,6 +1223,63 @@ async fn test_clone_properly_cached_delegated_account_that_changes_state() {
     assert!(worker_handle.await.is_ok());
 }
 
+#[tokio::test]
+async fn test_clone_delegated_account_uses_delegation_record_cache() {
+    // Stubs
+    let internal_account_provider = InternalAccountProviderStub::default();
+    let account_fetcher = AccountFetcherStub::default();
+    let account_updates = AccountUpdatesStub::default();
+    let account_dumper = AccountDumperStub::default();
+    // Create account cloner worker and client
+    let (cloner, cancellation_token, worker_handle) = setup_ephemeral(
+        internal_account_provider.clone(),
+        account_fetcher.clone(),
+        account_updates.clone(),
+        account_dumper.clone(),
+        None,
+    );
+    // Account(s) involved
+    let delegated_account = Pubkey::new_unique();
+    let delegation_record =
+        delegation_record_pda_from_delegated_account(&delegated_account);
+    account_updates.set_first_subscribed_slot(delegated_account, 41);
+    account_updates.set_first_subscribed_slot(delegation_record, 41);
+    account_fetcher.set_delegated_account(delegated_account, 42, 11);
+    // Run test (we clone the account for the first time as delegated)
+    let result1 = cloner.clone_account(&delegated_account).await;
+    // Check expected result1
+    assert!(matches!(result1, Ok(AccountClonerOutput::Cloned { .. })));
+    assert_eq!(account_fetcher.get_fetch_count(&delegated_account), 1);
+    assert!(account_updates.has_account_monitoring(&delegated_account));
+    assert!(account_updates.has_account_monitoring(&delegation_record));
+    assert!(account_dumper.was_dumped_as_delegated_account(&delegated_account));
+    // Clear dump history
+    account_dumper.clear_history();
+    // The account is now updated remotely (but its delegation record didnt change)
+    account_updates.set_last_known_update_slot(delegated_account, 66);
+    // Run test (we MUST NOT re-fetch since the delegation is unchanged)
+    let result2 = cloner.clone_account(&delegated_account).await;
+    // Check expected result2
+    assert!(matches!(result2, Ok(AccountClonerOutput::Cloned { .. })));
+    assert_eq!(account_fetcher.get_fetch_count(&delegated_account), 1);
+    assert!(account_dumper.was_untouched(&delegated_account));
+    // The account becomes undelegated which updates its delegation record
+    account_updates.set_last_known_update_slot(delegated_account, 77);
+    account_updates.set_last_known_update_slot(delegation_record, 77);
+    account_fetcher.set_undelegated_account(delegated_account, 77);
+    // Run test (now we MUST RE-FETCH and RE-DUMP as an undelegated account)
+    let result3 = cloner.clone_account(&delegated_account).await;
+    // Check expected result3
+    assert!(matches!(result3, Ok(AccountClonerOutput::Cloned { .. })));
+    assert_eq!(account_fetcher.get_fetch_count(&delegated_account), 2);
+    assert!(
+        account_dumper.was_dumped_as_undelegated_account(&delegated_account)
+    );
+    // Cleanup everything correctly
+    cancellation_token.cancel();
+    assert!(worker_handle.await.is_ok());
+}
+
 #[tokio::test]
 async fn test_clone_properly_upgrading_downgrading_when_created_and_deleted() {
     // Stubs
@@ -1164,3 +1359,345 @@ async fn test_clone_properly_upgrading_downgrading_when_created_and_deleted() {
     cancellation_token.cancel();
     assert!(worker_handle.await.is_ok());
 }
+
+#[tokio::test]
+async fn test_clone_account_closed_on_chain_when_ephemeral() {
+    // Stubs
+    let internal_account_provider = InternalAccountProviderStub::default();
+    let account_fetcher = AccountFetcherStub::default();
+    let account_updates = AccountUpdatesStub::default();
+    let account_dumper = AccountDumperStub::default();
+    // Create account cloner worker and client
+    let (cloner, cancellation_token, worker_handle) = setup_ephemeral(
+        internal_account_provider.clone(),
+        account_fetcher.clone(),
+        account_updates.clone(),
+        account_dumper.clone(),
+        None,
+    );
+    // Account(s) involved
+    let undelegated_account = Pubkey::new_unique();
+    let new_wallet = Pubkey::new_unique();
+    account_updates.set_first_subscribed_slot(undelegated_account, 41);
+    account_fetcher.set_undelegated_account(undelegated_account, 42);
+    account_updates.set_first_subscribed_slot(new_wallet, 41);
+    account_fetcher.set_closed_account(new_wallet, 42);
+    // Run test (we clone the account for the first time)
+    let result1 = cloner.clone_account(&undelegated_account).await;
+    // Check expected result1
+    assert!(matches!(result1, Ok(AccountClonerOutput::Cloned { .. })));
+    assert!(
+        account_dumper.was_dumped_as_undelegated_account(&undelegated_account)
+    );
+    // Clear dump history
+    account_dumper.clear_history();
+    // The account is now closed remotely
+    account_fetcher.set_closed_account(undelegated_account, 66);
+    account_updates.set_last_known_update_slot(undelegated_account, 66);
+    // Run test (we re-clone the account and it should remove its state)
+    let result2 = cloner.clone_account(&undelegated_account).await;
+    // Check expected result2
+    assert!(matches!(
+        result2,
+        Ok(AccountClonerOutput::ClonedAsClosed { .. })
+    ));
+    assert_eq!(account_fetcher.get_fetch_count(&undelegated_account), 2);
+    assert!(
+        account_dumper.was_dumped_as_undelegated_account(&undelegated_account)
+    );
+    // Clear dump history
+    account_dumper.clear_history();
+    // Run test (we re-clone the account and it should be in the cache)
+    let result3 = cloner.clone_account(&undelegated_account).await;
+    // Check expected result3
+    assert!(matches!(
+        result3,
+        Ok(AccountClonerOutput::ClonedAsClosed { .. })
+    ));
+    assert_eq!(account_fetcher.get_fetch_count(&undelegated_account), 2);
+    assert!(account_dumper.was_untouched(&undelegated_account));
+    // Run test (an account that never existed is still cloned as fee payer)
+    let result4 = cloner.clone_account(&new_wallet).await;
+    // Check expected result4
+    assert!(matches!(result4, Ok(AccountClonerOutput::Cloned { .. })));
+    assert!(account_dumper.was_dumped_as_feepayer_account(&new_wallet));
+    // Cleanup everything correctly
+    cancellation_token.cancel();
+    assert!(worker_handle.await.is_ok());
+}
+
+#[tokio::test]
+async fn test_clone_polls_undelegated_account_when_ephemeral_polling() {
+    // Stubs
+    let internal_account_provider = InternalAccountProviderStub::default();
+    let account_fetcher = AccountFetcherStub::default();
+    let account_updates = AccountUpdatesStub::default();
+    let account_dumper = AccountDumperStub::default();
+    // Create account cloner worker and client
+    let interval = Duration::from_millis(100);
+    let (cloner, cancellation_token, worker_handle) = setup_ephemeral_polling(
+        internal_account_provider.clone(),
+        account_fetcher.clone(),
+        account_updates.clone(),
+        account_dumper.clone(),
+        interval,
+    );
+    // Account(s) involved
+    let undelegated_account = Pubkey::new_unique();
+    account_fetcher.set_undelegated_account(undelegated_account, 42);
+    // Run test (cloned, then served from the cache until the interval passed)
+    let result = cloner.clone_account(&undelegated_account).await;
+    assert!(matches!(result, Ok(AccountClonerOutput::Cloned { .. })));
+    let result = cloner.clone_account(&undelegated_account).await;
+    assert!(matches!(result, Ok(AccountClonerOutput::Cloned { .. })));
+    assert_eq!(account_fetcher.get_fetch_count(&undelegated_account), 1);
+    tokio::time::sleep(interval).await;
+    let result = cloner.clone_account(&undelegated_account).await;
+    // Check expected result
+    assert!(matches!(result, Ok(AccountClonerOutput::Cloned { .. })));
+    assert_eq!(account_fetcher.get_fetch_count(&undelegated_account), 2);
+    assert!(!account_updates.has_account_monitoring(&undelegated_account));
+    assert!(
+        account_dumper.was_dumped_as_undelegated_account(&undelegated_account)
+    );
+    // Cleanup everything correctly
+    cancellation_token.cancel();
+    assert!(worker_handle.await.is_ok());
+}
+
+#[tokio::test]
+async fn test_clone_does_not_poll_delegated_account_when_ephemeral_polling() {
+    // Stubs
+    let internal_account_provider = InternalAccountProviderStub::default();
+    let account_fetcher = AccountFetcherStub::default();
+    let account_updates = AccountUpdatesStub::default();
+    let account_dumper = AccountDumperStub::default();
+    // Create account cloner worker and client
+    let interval = Duration::from_millis(100);
+    let (cloner, cancellation_token, worker_handle) = setup_ephemeral_polling(
+        internal_account_provider.clone(),
+        account_fetcher.clone(),
+        account_updates.clone(),
+        account_dumper.clone(),
+        interval,
+    );
+    // Account(s) involved
+    let delegated_account = Pubkey::new_unique();
+    account_fetcher.set_delegated_account(delegated_account, 42, 11);
+    // Run test
+    let result = cloner.clone_account(&delegated_account).await;
+    assert!(matches!(result, Ok(AccountClonerOutput::Cloned { .. })));
+    tokio::time::sleep(interval).await;
+    let result = cloner.clone_account(&delegated_account).await;
+    // Check expected result
+    assert!(matches!(result, Ok(AccountClonerOutput::Cloned { .. })));
+    assert_eq!(account_fetcher.get_fetch_count(&delegated_account), 1);
+    assert!(!account_updates.has_account_monitoring(&delegated_account));
+    // Cleanup everything correctly
+    cancellation_token.cancel();
+    assert!(worker_handle.await.is_ok());
+}
+
+#[tokio::test]
+async fn test_clone_same_account_after_worker_panicked_mid_clone() {
+    // Stubs
+    let internal_account_provider = InternalAccountProviderStub::default();
+    let account_fetcher = AccountFetcherStub::default();
+    let account_updates = AccountUpdatesStub::default();
+    let account_dumper = AccountDumperStub::default();
+    // Create account cloner worker and client, restarting the worker after
+    // it panicked the same way the validator supervises it
+    let mut cloner_worker = RemoteAccountClonerWorker::new(
+        internal_account_provider,
+        account_fetcher.clone(),
+        account_updates,
+        account_dumper.clone(),
+        None,
+        standard_blacklisted_accounts(
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+        ),
+        Some(1_000 * LAMPORTS_PER_SOL),
+        AccountClonerPermissions {
+            clone: ClonePolicy::all(),
+            refresh: RefreshPolicy::Off,
+        },
+        Pubkey::new_unique(),
+    );
+    let cloner = RemoteAccountClonerClient::new(&cloner_worker);
+    let clone_listeners = cloner_worker.get_clone_listeners();
+    let cancellation_token = CancellationToken::new();
+    let worker_handle = {
+        let cancellation_token = cancellation_token.clone();
+        tokio::spawn(async move {
+            while AssertUnwindSafe(
+                cloner_worker
+                    .start_clone_request_processing(cancellation_token.clone()),
+            )
+            .catch_unwind()
+            .await
+            .is_err()
+            {
+                fail_pending_clones(&clone_listeners);
+            }
+        })
+    };
+    // Account(s) involved
+    let undelegated_account = Pubkey::new_unique();
+    account_fetcher.set_undelegated_account(undelegated_account, 42);
+    account_fetcher.set_panicking_account(undelegated_account);
+    // Run test (the worker panics mid-clone, then the account is cloned again)
+    let result = timeout(
+        Duration::from_secs(5),
+        cloner.clone_account(&undelegated_account),
+    )
+    .await
+    .expect("pending clone was not failed");
+    assert!(matches!(
+        result,
+        Err(AccountClonerError::CloneWorkerStopped)
+    ));
+    let result = timeout(
+        Duration::from_secs(5),
+        cloner.clone_account(&undelegated_account),
+    )
+    .await
+    .expect("clone after restart did not complete");
+    // Check expected result
+    assert!(matches!(result, Ok(AccountClonerOutput::Cloned { .. })));
+    assert_eq!(account_fetcher.get_fetch_count(&undelegated_account), 2);
+    assert!(
+        account_dumper.was_dumped_as_undelegated_account(&undelegated_account)
+    );
+    // Cleanup everything correctly
+    cancellation_token.cancel();
+    assert!(worker_handle.await.is_ok());
+}
+
+#[tokio::test]
+async fn test_clone_fails_once_validator_is_draining() {
+    // Stubs
+    let internal_account_provider = InternalAccountProviderStub::default();
+    let account_fetcher = AccountFetcherStub::default();
+    let account_updates = AccountUpdatesStub::default();
+    let account_dumper = AccountDumperStub::default();
+    let (stage_sender, stage_receiver) =
+        watch::channel(ValidatorStage::Running);
+    // Create account cloner worker and client
+    let mut cloner_worker = RemoteAccountClonerWorker::new(
+        internal_account_provider,
+        account_fetcher.clone(),
+        account_updates,
+        account_dumper.clone(),
+        None,
+        standard_blacklisted_accounts(
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+        ),
+        Some(1_000 * LAMPORTS_PER_SOL),
+        AccountClonerPermissions {
+            clone: ClonePolicy::all(),
+            refresh: RefreshPolicy::Off,
+        },
+        Pubkey::new_unique(),
+    )
+    .with_validator_stage(stage_receiver);
+    let cloner = RemoteAccountClonerClient::new(&cloner_worker);
+    let cancellation_token = CancellationToken::new();
+    let worker_handle = {
+        let cancellation_token = cancellation_token.clone();
+        tokio::spawn(async move {
+            cloner_worker
+                .start_clone_request_processing(cancellation_token)
+                .await
+        })
+    };
+    // Account(s) involved
+    let running_account = Pubkey::new_unique();
+    let draining_account = Pubkey::new_unique();
+    account_fetcher.set_undelegated_account(running_account, 42);
+    account_fetcher.set_undelegated_account(draining_account, 42);
+    // Run test
+    let result = cloner.clone_account(&running_account).await;
+    assert!(matches!(result, Ok(AccountClonerOutput::Cloned { .. })));
+    stage_sender.send_replace(ValidatorStage::Draining);
+    let result = cloner.clone_account(&draining_account).await;
+    // Check expected result
+    assert!(matches!(
+        result,
+        Err(AccountClonerError::ValidatorShuttingDown)
+    ));
+    assert_eq!(account_fetcher.get_fetch_count(&draining_account), 0);
+    assert!(account_dumper.was_untouched(&draining_account));
+    // Cleanup everything correctly
+    cancellation_token.cancel();
+    assert!(worker_handle.await.is_ok());
+}
+
+#[tokio::test]
+async fn test_clone_program_again_after_its_clone_was_invalidated() {
+    // Stubs
+    let internal_account_provider = InternalAccountProviderStub::default();
+    let account_fetcher = AccountFetcherStub::default();
+    let account_updates = AccountUpdatesStub::default();
+    let account_dumper = AccountDumperStub::default();
+    // Create account cloner worker and client
+    let mut cloner_worker = RemoteAccountClonerWorker::new(
+        internal_account_provider,
+        account_fetcher.clone(),
+        account_updates.clone(),
+        account_dumper.clone(),
+        None,
+        standard_blacklisted_accounts(
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+        ),
+        Some(1_000 * LAMPORTS_PER_SOL),
+        AccountClonerPermissions {
+            clone: ClonePolicy::all(),
+            refresh: RefreshPolicy::Off,
+        },
+        Pubkey::new_unique(),
+    );
+    let cloner = RemoteAccountClonerClient::new(&cloner_worker);
+    let invalidated_clones = cloner_worker.get_invalidated_clones();
+    let cancellation_token = CancellationToken::new();
+    let worker_handle = {
+        let cancellation_token = cancellation_token.clone();
+        tokio::spawn(async move {
+            cloner_worker
+                .start_clone_request_processing(cancellation_token)
+                .await
+        })
+    };
+    // Account(s) involved
+    let program_id = Pubkey::new_unique();
+    let program_data = get_program_data_address(&program_id);
+    let program_anchor = get_pubkey_anchor_idl(&program_id).unwrap();
+    let program_shank = get_pubkey_shank_idl(&program_id).unwrap();
+    account_updates.set_first_subscribed_slot(program_id, 41);
+    account_fetcher.set_executable_account(program_id, 42);
+    account_fetcher.set_undelegated_account(program_data, 42);
+    account_fetcher.set_feepayer_account(program_anchor, 42);
+    account_fetcher.set_feepayer_account(program_shank, 42);
+    // The second clone uses the cache
+    let result = cloner.clone_account(&program_id).await;
+    assert!(matches!(result, Ok(AccountClonerOutput::Cloned { .. })));
+    let result = cloner.clone_account(&program_id).await;
+    assert!(matches!(result, Ok(AccountClonerOutput::Cloned { .. })));
+    assert_eq!(account_fetcher.get_fetch_count(&program_id), 1);
+    // Once the clone is invalidated, i.e. since its upgrade failed, it is
+    // cloned again, but only once
+    invalidated_clones.write().unwrap().insert(program_id);
+    let result = cloner.clone_account(&program_id).await;
+    assert!(matches!(result, Ok(AccountClonerOutput::Cloned { .. })));
+    assert_eq!(account_fetcher.get_fetch_count(&program_id), 2);
+    assert_eq!(account_fetcher.get_fetch_count(&program_data), 2);
+    assert!(account_dumper.was_dumped_as_program_id(&program_id));
+    let result = cloner.clone_account(&program_id).await;
+    assert!(matches!(result, Ok(AccountClonerOutput::Cloned { .. })));
+    assert_eq!(account_fetcher.get_fetch_count(&program_id), 2);
+    // Cleanup everything correctly
+    cancellation_token.cancel();
+    assert!(worker_handle.await.is_ok());
+}
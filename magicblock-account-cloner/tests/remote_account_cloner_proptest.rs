@@ -0,0 +1,424 @@
+use std::collections::HashSet;
+
+use conjunto_transwise::AccountChainState;
+use dlp::pda::delegation_record_pda_from_delegated_account;
+use magicblock_account_cloner::{
+    standard_blacklisted_accounts, AccountCloner, AccountClonerOutput,
+    AccountClonerPermissions, AccountClonerUnclonableReason, ClonePolicy,
+    RefreshPolicy, RemoteAccountClonerClient, RemoteAccountClonerWorker,
+};
+use magicblock_account_dumper::AccountDumperStub;
+use magicblock_account_fetcher::AccountFetcherStub;
+use magicblock_account_updates::AccountUpdatesStub;
+use magicblock_accounts_api::InternalAccountProviderStub;
+use proptest::{collection::vec, prelude::*};
+use solana_sdk::{
+    account::AccountSharedData, clock::Slot, native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+};
+use tokio_util::sync::CancellationToken;
+
+type StubbedWorker = RemoteAccountClonerWorker<
+    InternalAccountProviderStub,
+    AccountFetcherStub,
+    AccountUpdatesStub,
+    AccountDumperStub,
+>;
+
+#[derive(Default)]
+struct Stubs {
+    internal_account_provider: InternalAccountProviderStub,
+    account_fetcher: AccountFetcherStub,
+    account_updates: AccountUpdatesStub,
+    account_dumper: AccountDumperStub,
+}
+
+impl Stubs {
+    fn worker(
+        &self,
+        blacklisted_accounts: HashSet<Pubkey>,
+        validator_identity: Pubkey,
+    ) -> StubbedWorker {
+        RemoteAccountClonerWorker::new(
+            self.internal_account_provider.clone(),
+            self.account_fetcher.clone(),
+            self.account_updates.clone(),
+            self.account_dumper.clone(),
+            None,
+            blacklisted_accounts,
+            Some(1_000 * LAMPORTS_PER_SOL),
+            AccountClonerPermissions {
+                clone: ClonePolicy::all(),
+                refresh: RefreshPolicy::Websocket,
+            },
+            validator_identity,
+        )
+    }
+}
+
+fn runtime() -> tokio::runtime::Runtime {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+}
+
+// -----------------
+// Chain state machine
+// -----------------
+#[derive(Debug, Clone, Copy)]
+enum ChainEvent {
+    Created,
+    Undelegated,
+    Delegated,
+    Updated,
+    Closed,
+}
+
+fn chain_event() -> impl Strategy<Value = ChainEvent> {
+    prop_oneof![
+        Just(ChainEvent::Created),
+        Just(ChainEvent::Undelegated),
+        Just(ChainEvent::Delegated),
+        Just(ChainEvent::Updated),
+        Just(ChainEvent::Closed),
+    ]
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ChainStateKind {
+    FeePayer,
+    Undelegated,
+    Delegated { delegation_slot: Slot },
+    Closed,
+}
+
+impl ChainStateKind {
+    fn matches(&self, chain_state: &AccountChainState) -> bool {
+        match (self, chain_state) {
+            (
+                ChainStateKind::FeePayer,
+                AccountChainState::FeePayer { lamports, .. },
+            ) => *lamports > 0,
+            (
+                ChainStateKind::Closed,
+                AccountChainState::FeePayer { lamports, .. },
+            ) => *lamports == 0,
+            (
+                ChainStateKind::Undelegated,
+                AccountChainState::Undelegated { .. },
+            ) => true,
+            (
+                ChainStateKind::Delegated { delegation_slot },
+                AccountChainState::Delegated {
+                    delegation_record, ..
+                },
+            ) => delegation_record.delegation_slot == *delegation_slot,
+            _ => false,
+        }
+    }
+
+    fn is_delegated(&self) -> bool {
+        matches!(self, ChainStateKind::Delegated { .. })
+    }
+}
+
+/// Tracks the chain state of a single account and mirrors every transition
+/// into the fetcher and updates stubs.
+struct ChainModel {
+    pubkey: Pubkey,
+    kind: Option<ChainStateKind>,
+}
+
+impl ChainModel {
+    fn new(pubkey: Pubkey, stubs: &Stubs) -> Self {
+        stubs.account_updates.set_first_subscribed_slot(pubkey, 1);
+        Self { pubkey, kind: None }
+    }
+
+    fn apply(
+        &mut self,
+        event: ChainEvent,
+        slot: Slot,
+        stubs: &Stubs,
+    ) -> ChainStateKind {
+        let kind = match (event, self.kind) {
+            (ChainEvent::Created, _) | (ChainEvent::Updated, None) => {
+                ChainStateKind::FeePayer
+            }
+            (ChainEvent::Undelegated, _) => ChainStateKind::Undelegated,
+            (ChainEvent::Delegated, _) => ChainStateKind::Delegated {
+                delegation_slot: slot,
+            },
+            (ChainEvent::Updated, Some(kind)) => kind,
+            (ChainEvent::Closed, _) => ChainStateKind::Closed,
+        };
+        let fetcher = &stubs.account_fetcher;
+        match kind {
+            ChainStateKind::FeePayer => {
+                fetcher.set_feepayer_account(self.pubkey, slot)
+            }
+            ChainStateKind::Undelegated => {
+                fetcher.set_undelegated_account(self.pubkey, slot)
+            }
+            ChainStateKind::Delegated { delegation_slot } => fetcher
+                .set_delegated_account(self.pubkey, slot, delegation_slot),
+            ChainStateKind::Closed => {
+                fetcher.set_closed_account(self.pubkey, slot)
+            }
+        }
+        stubs
+            .account_updates
+            .set_last_known_update_slot(self.pubkey, slot);
+        // Any change of the delegation also modifies the delegation record
+        let was_delegated = self.kind.is_some_and(|kind| kind.is_delegated());
+        if self.kind != Some(kind) && (was_delegated || kind.is_delegated()) {
+            stubs.account_updates.set_last_known_update_slot(
+                delegation_record_pda_from_delegated_account(&self.pubkey),
+                slot,
+            );
+        }
+        self.kind = Some(kind);
+        kind
+    }
+}
+
+async fn run_clone_sequence(
+    events: Vec<ChainEvent>,
+) -> Result<(), TestCaseError> {
+    let stubs = Stubs::default();
+    let blacklisted_account = Pubkey::new_unique();
+    let mut blacklisted_accounts = standard_blacklisted_accounts(
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+    );
+    blacklisted_accounts.insert(blacklisted_account);
+    let mut cloner_worker =
+        stubs.worker(blacklisted_accounts, Pubkey::new_unique());
+    let cloner = RemoteAccountClonerClient::new(&cloner_worker);
+    let cancellation_token = CancellationToken::new();
+    let worker_handle = {
+        let cloner_cancellation_token = cancellation_token.clone();
+        tokio::spawn(async move {
+            cloner_worker
+                .start_clone_request_processing(cloner_cancellation_token)
+                .await
+        })
+    };
+
+    let account = Pubkey::new_unique();
+    let mut account_model = ChainModel::new(account, &stubs);
+    let mut blacklisted_model = ChainModel::new(blacklisted_account, &stubs);
+    let mut last_at_slot = 0;
+    let mut has_cloned_state = false;
+    for (idx, event) in events.into_iter().enumerate() {
+        let slot = 10 * (idx as Slot + 1);
+        let previous_kind = account_model.kind;
+        let kind = account_model.apply(event, slot, &stubs);
+        blacklisted_model.apply(event, slot, &stubs);
+        stubs.account_dumper.clear_history();
+
+        let result = cloner.clone_account(&account).await;
+        let (account_chain_snapshot, cloned_as_closed) = match result {
+            Ok(AccountClonerOutput::Cloned {
+                account_chain_snapshot,
+                ..
+            }) => (account_chain_snapshot, false),
+            Ok(AccountClonerOutput::ClonedAsClosed {
+                account_chain_snapshot,
+                ..
+            }) => (account_chain_snapshot, true),
+            _ => {
+                return Err(TestCaseError::fail(format!(
+                    "Expected {:?} to be cloned as {:?} at slot {}, got {:?}",
+                    account, kind, slot, result
+                )));
+            }
+        };
+        // Only accounts that were cloned with some state need to be closed,
+        // others are fee payers which don't need to exist on chain
+        prop_assert_eq!(
+            cloned_as_closed,
+            kind == ChainStateKind::Closed && has_cloned_state
+        );
+        if cloned_as_closed {
+            prop_assert!(stubs
+                .account_dumper
+                .was_dumped_as_undelegated_account(&account));
+        }
+        has_cloned_state = cloned_as_closed
+            || matches!(
+                kind,
+                ChainStateKind::Undelegated | ChainStateKind::Delegated { .. }
+            );
+        // The cached clone output never moves back in time
+        prop_assert!(
+            account_chain_snapshot.at_slot >= last_at_slot,
+            "Clone output went from slot {} back to {}",
+            last_at_slot,
+            account_chain_snapshot.at_slot
+        );
+        prop_assert!(account_chain_snapshot.at_slot <= slot);
+        last_at_slot = account_chain_snapshot.at_slot;
+        prop_assert!(
+            kind.matches(&account_chain_snapshot.chain_state),
+            "Expected {:?}, got {:?}",
+            kind,
+            account_chain_snapshot.chain_state
+        );
+        // The local state of a delegated account is the source of truth
+        // until the account is delegated again
+        if previous_kind == Some(kind) && kind.is_delegated() {
+            prop_assert!(stubs.account_dumper.was_untouched(&account));
+        }
+
+        let result = cloner.clone_account(&blacklisted_account).await;
+        prop_assert!(
+            matches!(
+                result,
+                Ok(AccountClonerOutput::Unclonable {
+                    reason: AccountClonerUnclonableReason::IsBlacklisted,
+                    ..
+                })
+            ),
+            "Blacklisted account was not refused: {:?}",
+            result
+        );
+        prop_assert!(stubs.account_dumper.was_untouched(&blacklisted_account));
+        prop_assert_eq!(
+            stubs.account_fetcher.get_fetch_count(&blacklisted_account),
+            0
+        );
+    }
+
+    cancellation_token.cancel();
+    prop_assert!(worker_handle.await.is_ok());
+    Ok(())
+}
+
+// -----------------
+// Hydration
+// -----------------
+#[derive(Debug, Clone, Copy)]
+enum HydratedAccount {
+    FeePayer,
+    Undelegated,
+    DelegatedToUs,
+    DelegatedToOther,
+    Closed,
+    Blacklisted,
+}
+
+fn hydrated_account() -> impl Strategy<Value = HydratedAccount> {
+    prop_oneof![
+        Just(HydratedAccount::FeePayer),
+        Just(HydratedAccount::Undelegated),
+        Just(HydratedAccount::DelegatedToUs),
+        Just(HydratedAccount::DelegatedToOther),
+        Just(HydratedAccount::Closed),
+        Just(HydratedAccount::Blacklisted),
+    ]
+}
+
+async fn run_hydrate(
+    accounts: Vec<HydratedAccount>,
+) -> Result<(), TestCaseError> {
+    let stubs = Stubs::default();
+    let validator_identity = Pubkey::new_unique();
+    let mut blacklisted_accounts = standard_blacklisted_accounts(
+        &validator_identity,
+        &Pubkey::new_unique(),
+    );
+    let accounts = accounts
+        .into_iter()
+        .map(|kind| (Pubkey::new_unique(), kind))
+        .collect::<Vec<_>>();
+    for (pubkey, kind) in &accounts {
+        // The account was cloned by a previous run of the validator
+        stubs.internal_account_provider.set(
+            *pubkey,
+            AccountSharedData::new(LAMPORTS_PER_SOL, 0, &Pubkey::new_unique()),
+        );
+        stubs.account_updates.set_first_subscribed_slot(*pubkey, 41);
+        let fetcher = &stubs.account_fetcher;
+        match kind {
+            HydratedAccount::FeePayer | HydratedAccount::Blacklisted => {
+                fetcher.set_feepayer_account(*pubkey, 42)
+            }
+            HydratedAccount::Undelegated => {
+                fetcher.set_undelegated_account(*pubkey, 42)
+            }
+            HydratedAccount::DelegatedToUs => fetcher
+                .set_delegated_account_with_authority(
+                    *pubkey,
+                    42,
+                    11,
+                    validator_identity,
+                ),
+            HydratedAccount::DelegatedToOther => {
+                fetcher.set_delegated_account(*pubkey, 42, 11)
+            }
+            HydratedAccount::Closed => fetcher.set_closed_account(*pubkey, 42),
+        }
+        if let HydratedAccount::Blacklisted = kind {
+            blacklisted_accounts.insert(*pubkey);
+        }
+    }
+
+    let report = stubs
+        .worker(blacklisted_accounts, validator_identity)
+        .hydrate()
+        .await;
+    let hydrated = accounts
+        .iter()
+        .filter(|(_, kind)| !matches!(kind, HydratedAccount::Blacklisted))
+        .count();
+    prop_assert_eq!(report.cloned, hydrated);
+    prop_assert_eq!(report.skipped, 0);
+    prop_assert!(report.failures.is_empty(), "{:?}", report.failures);
+
+    let dumper = &stubs.account_dumper;
+    for (pubkey, kind) in &accounts {
+        let dumped_as_expected = match kind {
+            HydratedAccount::FeePayer => {
+                dumper.was_dumped_as_feepayer_account(pubkey)
+            }
+            // The state cloned by the previous run is removed
+            HydratedAccount::Undelegated | HydratedAccount::Closed => {
+                dumper.was_dumped_as_undelegated_account(pubkey)
+            }
+            // Our local state is more recent than the one on chain
+            HydratedAccount::DelegatedToUs => dumper.was_untouched(pubkey),
+            HydratedAccount::DelegatedToOther => {
+                dumper.was_dumped_as_delegated_account(pubkey)
+            }
+            HydratedAccount::Blacklisted => {
+                dumper.was_untouched(pubkey)
+                    && stubs.account_fetcher.get_fetch_count(pubkey) == 0
+            }
+        };
+        prop_assert!(
+            dumped_as_expected,
+            "{:?} account {} was not hydrated as expected",
+            kind,
+            pubkey
+        );
+    }
+    Ok(())
+}
+
+proptest! {
+    #[test]
+    fn test_clone_invariants_hold_for_any_chain_state_sequence(
+        events in vec(chain_event(), 1..24)
+    ) {
+        runtime().block_on(run_clone_sequence(events))?;
+    }
+
+    #[test]
+    fn test_hydrate_never_overwrites_accounts_delegated_to_us(
+        accounts in vec(hydrated_account(), 1..16)
+    ) {
+        runtime().block_on(run_hydrate(accounts))?;
+    }
+}
@@ -1,3 +1,4 @@
+use magicblock_core::error_code::{HasErrorCode, MagicErrorCode};
 use magicblock_mutator::errors::MutatorModificationError;
 use solana_sdk::{account::Account, pubkey::Pubkey, signature::Signature};
 use thiserror::Error;
@@ -13,6 +14,16 @@ pub enum AccountDumperError {
 
 pub type AccountDumperResult<T> = Result<T, AccountDumperError>;
 
+impl HasErrorCode for AccountDumperError {
+    fn error_code(&self) -> MagicErrorCode {
+        use AccountDumperError::*;
+        match self {
+            TransactionError(_) => MagicErrorCode::DumpTransactionFailed,
+            MutatorModificationError(err) => err.error_code(),
+        }
+    }
+}
+
 // TODO - this could probably be deprecated in favor of:
 //  - a TransactionExecutor trait with a service implementation passed as parameter to the AccountCloner
 //  - using the mutator's functionality directly inside of the AccountCloner
@@ -64,3 +75,61 @@ pub trait AccountDumper {
         program_account: &Account,
     ) -> AccountDumperResult<Signature>;
 }
+
+/// Allows to compose the dumper used by the validator at runtime, i.e. the
+/// bank dumper wrapped by the decorators that are enabled.
+pub type AccountDumperStack = Box<dyn AccountDumper + Send + Sync>;
+
+impl<T: AccountDumper + ?Sized> AccountDumper for Box<T> {
+    fn dump_feepayer_account(
+        &self,
+        pubkey: &Pubkey,
+        lamports: u64,
+        owner: &Pubkey,
+    ) -> AccountDumperResult<Signature> {
+        (**self).dump_feepayer_account(pubkey, lamports, owner)
+    }
+
+    fn dump_undelegated_account(
+        &self,
+        pubkey: &Pubkey,
+        account: &Account,
+    ) -> AccountDumperResult<Signature> {
+        (**self).dump_undelegated_account(pubkey, account)
+    }
+
+    fn dump_delegated_account(
+        &self,
+        pubkey: &Pubkey,
+        account: &Account,
+        owner: &Pubkey,
+    ) -> AccountDumperResult<Signature> {
+        (**self).dump_delegated_account(pubkey, account, owner)
+    }
+
+    fn dump_program_accounts(
+        &self,
+        program_id: &Pubkey,
+        program_id_account: &Account,
+        program_data: &Pubkey,
+        program_data_account: &Account,
+        program_idl: Option<(Pubkey, Account)>,
+    ) -> AccountDumperResult<Signature> {
+        (**self).dump_program_accounts(
+            program_id,
+            program_id_account,
+            program_data,
+            program_data_account,
+            program_idl,
+        )
+    }
+
+    fn dump_program_account_with_old_bpf(
+        &self,
+        program_pubkey: &Pubkey,
+        program_account: &Account,
+    ) -> AccountDumperResult<Signature> {
+        (**self)
+            .dump_program_account_with_old_bpf(program_pubkey, program_account)
+    }
+}
@@ -0,0 +1,270 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, Write},
+    path::Path,
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use log::*;
+use magicblock_bank::bank::Bank;
+use magicblock_core::robust_lock::RobustMutex;
+use serde::Serialize;
+use solana_sdk::{
+    account::{Account, ReadableAccount},
+    bpf_loader_upgradeable::get_program_data_address,
+    clock::Slot,
+    hash::{hashv, Hash},
+    pubkey::Pubkey,
+    signature::Signature,
+};
+
+use crate::{AccountDumper, AccountDumperResult};
+
+#[derive(Serialize)]
+struct DumpRecord {
+    timestamp_millis: u128,
+    slot: Slot,
+    kind: &'static str,
+    signature: Option<String>,
+    error: Option<String>,
+    accounts: Vec<DumpedAccount>,
+}
+
+/// The hashes of an account before and after it was dumped, [None] if it
+/// didn't exist.
+#[derive(Serialize)]
+struct DumpedAccount {
+    pubkey: String,
+    before: Option<String>,
+    after: Option<String>,
+}
+
+/// Decorates an [AccountDumper] by appending a JSON line for every dump to
+/// the audit log, recording which accounts were written, when and the hashes
+/// of their state before and after the dump.
+/// This allows to find out exactly what was cloned into the bank when
+/// debugging state corruption.
+pub struct AccountDumperAuditLog<T: AccountDumper> {
+    inner: T,
+    bank: Arc<Bank>,
+    file: Mutex<File>,
+}
+
+impl<T: AccountDumper> AccountDumperAuditLog<T> {
+    pub fn new(inner: T, bank: Arc<Bank>, path: &Path) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            inner,
+            bank,
+            file: Mutex::new(file),
+        })
+    }
+
+    fn audit(
+        &self,
+        kind: &'static str,
+        pubkeys: &[Pubkey],
+        dump: impl FnOnce() -> AccountDumperResult<Signature>,
+    ) -> AccountDumperResult<Signature> {
+        let before = pubkeys
+            .iter()
+            .map(|pubkey| self.account_hash(pubkey))
+            .collect::<Vec<_>>();
+        let result = dump();
+        let accounts = pubkeys
+            .iter()
+            .zip(before)
+            .map(|(pubkey, before)| DumpedAccount {
+                pubkey: pubkey.to_string(),
+                before: before.map(|hash| hash.to_string()),
+                after: self.account_hash(pubkey).map(|hash| hash.to_string()),
+            })
+            .collect();
+        let (signature, error) = match &result {
+            Ok(signature) => (Some(signature.to_string()), None),
+            Err(err) => (None, Some(err.to_string())),
+        };
+        self.write_record(&DumpRecord {
+            timestamp_millis: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis(),
+            slot: self.bank.slot(),
+            kind,
+            signature,
+            error,
+            accounts,
+        });
+        result
+    }
+
+    fn account_hash(&self, pubkey: &Pubkey) -> Option<Hash> {
+        self.bank.get_account(pubkey).map(|account| {
+            hashv(&[
+                &account.lamports().to_le_bytes(),
+                account.owner().as_ref(),
+                &[account.executable() as u8],
+                &account.rent_epoch().to_le_bytes(),
+                account.data(),
+            ])
+        })
+    }
+
+    fn write_record(&self, record: &DumpRecord) {
+        let mut line = match serde_json::to_vec(record) {
+            Ok(line) => line,
+            Err(err) => {
+                error!("Failed to serialize account dump record: {:?}", err);
+                return;
+            }
+        };
+        line.push(b'\n');
+        if let Err(err) = self.file.lock_robust().write_all(&line) {
+            error!("Failed to write account dump audit log: {:?}", err);
+        }
+    }
+}
+
+impl<T: AccountDumper> AccountDumper for AccountDumperAuditLog<T> {
+    fn dump_feepayer_account(
+        &self,
+        pubkey: &Pubkey,
+        lamports: u64,
+        owner: &Pubkey,
+    ) -> AccountDumperResult<Signature> {
+        self.audit("feepayer", &[*pubkey], || {
+            self.inner.dump_feepayer_account(pubkey, lamports, owner)
+        })
+    }
+
+    fn dump_undelegated_account(
+        &self,
+        pubkey: &Pubkey,
+        account: &Account,
+    ) -> AccountDumperResult<Signature> {
+        self.audit("undelegated", &[*pubkey], || {
+            self.inner.dump_undelegated_account(pubkey, account)
+        })
+    }
+
+    fn dump_delegated_account(
+        &self,
+        pubkey: &Pubkey,
+        account: &Account,
+        owner: &Pubkey,
+    ) -> AccountDumperResult<Signature> {
+        self.audit("delegated", &[*pubkey], || {
+            self.inner.dump_delegated_account(pubkey, account, owner)
+        })
+    }
+
+    fn dump_program_accounts(
+        &self,
+        program_id: &Pubkey,
+        program_id_account: &Account,
+        program_data: &Pubkey,
+        program_data_account: &Account,
+        program_idl: Option<(Pubkey, Account)>,
+    ) -> AccountDumperResult<Signature> {
+        let mut pubkeys = vec![*program_id, *program_data];
+        if let Some((program_idl_pubkey, _)) = &program_idl {
+            pubkeys.push(*program_idl_pubkey);
+        }
+        self.audit("program", &pubkeys, || {
+            self.inner.dump_program_accounts(
+                program_id,
+                program_id_account,
+                program_data,
+                program_data_account,
+                program_idl,
+            )
+        })
+    }
+
+    fn dump_program_account_with_old_bpf(
+        &self,
+        program_pubkey: &Pubkey,
+        program_account: &Account,
+    ) -> AccountDumperResult<Signature> {
+        let pubkeys =
+            [*program_pubkey, get_program_data_address(program_pubkey)];
+        self.audit("program_old_bpf", &pubkeys, || {
+            self.inner.dump_program_account_with_old_bpf(
+                program_pubkey,
+                program_account,
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use serde_json::Value;
+    use solana_sdk::{native_token::LAMPORTS_PER_SOL, system_program};
+    use tempfile::NamedTempFile;
+    use test_tools::validator::bank_with_started_validator;
+
+    use super::*;
+    use crate::AccountDumperBank;
+
+    fn read_records(path: &Path) -> Vec<Value> {
+        fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_audit_log_records_dumps_with_hashes() {
+        let bank = bank_with_started_validator();
+        let audit_log = NamedTempFile::new().unwrap();
+        let dumper = AccountDumperAuditLog::new(
+            AccountDumperBank::new(bank.clone(), None),
+            bank,
+            audit_log.path(),
+        )
+        .unwrap();
+
+        let pubkey = Pubkey::new_unique();
+        let account = Account {
+            lamports: LAMPORTS_PER_SOL,
+            owner: system_program::id(),
+            data: vec![1; 8],
+            ..Default::default()
+        };
+        let first = dumper.dump_undelegated_account(&pubkey, &account).unwrap();
+        let second = dumper
+            .dump_undelegated_account(
+                &pubkey,
+                &Account {
+                    data: vec![2; 8],
+                    ..account
+                },
+            )
+            .unwrap();
+
+        let records = read_records(audit_log.path());
+        assert_eq!(records.len(), 2);
+        for (record, signature) in records.iter().zip([first, second]) {
+            assert_eq!(record["kind"], "undelegated");
+            assert_eq!(record["signature"], signature.to_string());
+            assert_eq!(record["error"], Value::Null);
+            assert_eq!(record["accounts"][0]["pubkey"], pubkey.to_string());
+            assert!(record["accounts"][0]["after"].is_string());
+        }
+        // The first dump created the account which the second one modified
+        assert_eq!(records[0]["accounts"][0]["before"], Value::Null);
+        assert_eq!(
+            records[1]["accounts"][0]["before"],
+            records[0]["accounts"][0]["after"]
+        );
+        assert_ne!(
+            records[1]["accounts"][0]["after"],
+            records[1]["accounts"][0]["before"]
+        );
+    }
+}
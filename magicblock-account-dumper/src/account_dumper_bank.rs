@@ -1,6 +1,9 @@
 use std::sync::Arc;
 
-use magicblock_bank::bank::Bank;
+use magicblock_bank::{
+    bank::Bank,
+    program_versions::{PendingProgramUpgrade, ProgramVersion},
+};
 use magicblock_mutator::{
     program::{
         create_program_buffer_modification, create_program_data_modification,
@@ -18,7 +21,10 @@ use solana_sdk::{
     bpf_loader_upgradeable::{
         self, get_program_data_address, UpgradeableLoaderState,
     },
+    clock::Epoch,
+    hash::{hash, Hash},
     pubkey::Pubkey,
+    rent_collector::RENT_EXEMPT_RENT_EPOCH,
     signature::Signature,
     transaction::Transaction,
 };
@@ -28,6 +34,7 @@ use crate::{AccountDumper, AccountDumperError, AccountDumperResult};
 pub struct AccountDumperBank {
     bank: Arc<Bank>,
     transaction_status_sender: Option<TransactionStatusSender>,
+    mainnet_rent_semantics: bool,
 }
 
 impl AccountDumperBank {
@@ -38,9 +45,30 @@ impl AccountDumperBank {
         Self {
             bank,
             transaction_status_sender,
+            mainnet_rent_semantics: false,
         }
     }
 
+    /// Marks rent exempt accounts with the [RENT_EXEMPT_RENT_EPOCH] when
+    /// dumping them, like mainnet does once they are written to.
+    /// Otherwise the rent epoch of the account on chain is kept as is.
+    pub fn with_mainnet_rent_semantics(
+        mut self,
+        mainnet_rent_semantics: bool,
+    ) -> Self {
+        self.mainnet_rent_semantics = mainnet_rent_semantics;
+        self
+    }
+
+    fn rent_epoch_override(&self, account: &Account) -> Option<Epoch> {
+        let is_rent_exempt = account.lamports
+            >= self
+                .bank
+                .get_minimum_balance_for_rent_exemption(account.data.len());
+        (self.mainnet_rent_semantics && is_rent_exempt)
+            .then_some(RENT_EXEMPT_RENT_EPOCH)
+    }
+
     fn execute_transaction(
         &self,
         transaction: Transaction,
@@ -52,6 +80,38 @@ impl AccountDumperBank {
         )
         .map_err(AccountDumperError::TransactionError)
     }
+
+    /// Deploys a program that doesn't exist yet right away, while upgrades
+    /// of deployed programs are deferred to the end of the slot.
+    /// Upgrading a program makes it unusable for the rest of the slot, so
+    /// this way transactions that already run in this slot keep using the
+    /// previous version instead of failing.
+    fn deploy_program(
+        &self,
+        program_id: &Pubkey,
+        needs_upgrade: bool,
+        bytecode_hash: Hash,
+        transaction: Transaction,
+    ) -> AccountDumperResult<Signature> {
+        if needs_upgrade {
+            let signature = transaction.signatures[0];
+            self.bank.queue_program_upgrade(PendingProgramUpgrade {
+                program_id: *program_id,
+                bytecode_hash,
+                transaction,
+            });
+            return Ok(signature);
+        }
+        let signature = self.execute_transaction(transaction)?;
+        self.bank.record_program_version(
+            *program_id,
+            ProgramVersion {
+                effective_slot: self.bank.slot(),
+                bytecode_hash,
+            },
+        );
+        Ok(signature)
+    }
 }
 
 impl AccountDumper for AccountDumperBank {
@@ -61,17 +121,27 @@ impl AccountDumper for AccountDumperBank {
         lamports: u64,
         owner: &Pubkey,
     ) -> AccountDumperResult<Signature> {
+        self.bank
+            .validator_context()
+            .set_account_delegated(pubkey, false);
         let account = Account {
             lamports,
             owner: *owner,
             ..Default::default()
         };
+        let overrides = Some(AccountModification {
+            pubkey: *pubkey,
+            rent_epoch: self.rent_epoch_override(&account),
+            ..Default::default()
+        });
         let transaction = transaction_to_clone_regular_account(
+            self.bank.validator_context(),
             pubkey,
             &account,
-            None,
+            overrides,
             self.bank.last_blockhash(),
-        );
+        )
+        .map_err(AccountDumperError::MutatorModificationError)?;
         self.execute_transaction(transaction)
     }
 
@@ -80,12 +150,24 @@ impl AccountDumper for AccountDumperBank {
         pubkey: &Pubkey,
         account: &Account,
     ) -> AccountDumperResult<Signature> {
+        // The account is no longer delegated to us, so we don't audit it
+        self.bank.set_account_journaled(pubkey, false);
+        self.bank
+            .validator_context()
+            .set_account_delegated(pubkey, false);
+        let overrides = Some(AccountModification {
+            pubkey: *pubkey,
+            rent_epoch: self.rent_epoch_override(account),
+            ..Default::default()
+        });
         let transaction = transaction_to_clone_regular_account(
+            self.bank.validator_context(),
             pubkey,
             account,
-            None,
+            overrides,
             self.bank.last_blockhash(),
-        );
+        )
+        .map_err(AccountDumperError::MutatorModificationError)?;
         self.execute_transaction(transaction)
     }
 
@@ -98,15 +180,24 @@ impl AccountDumper for AccountDumperBank {
         let overrides = Some(AccountModification {
             pubkey: *pubkey,
             owner: Some(*owner),
+            rent_epoch: self.rent_epoch_override(account),
             ..Default::default()
         });
         let transaction = transaction_to_clone_regular_account(
+            self.bank.validator_context(),
             pubkey,
             account,
             overrides,
             self.bank.last_blockhash(),
-        );
-        self.execute_transaction(transaction)
+        )
+        .map_err(AccountDumperError::MutatorModificationError)?;
+        let signature = self.execute_transaction(transaction)?;
+        // Writes to delegated accounts are journaled to allow auditing them
+        self.bank.set_account_journaled(pubkey, true);
+        self.bank
+            .validator_context()
+            .set_account_delegated(pubkey, true);
+        Ok(signature)
     }
 
     fn dump_program_accounts(
@@ -122,6 +213,7 @@ impl AccountDumper for AccountDumperBank {
             program_data_modification,
             program_buffer_modification,
         } = create_program_modifications(
+            &self.bank.validator_context().validator_authority_id(),
             program_id_pubkey,
             program_id_account,
             program_data_pubkey,
@@ -138,14 +230,25 @@ impl AccountDumper for AccountDumperBank {
             });
         let needs_upgrade = self.bank.has_account(program_id_pubkey);
         let transaction = transaction_to_clone_program(
+            self.bank.validator_context(),
             needs_upgrade,
             program_id_modification,
             program_data_modification,
             program_buffer_modification,
             program_idl_modification,
             self.bank.last_blockhash(),
-        );
-        self.execute_transaction(transaction)
+        )
+        .map_err(AccountDumperError::MutatorModificationError)?;
+        // The modifications were only created if the program data contains
+        // the bytecode
+        let program_data_bytecode = &program_data_account.data
+            [UpgradeableLoaderState::size_of_programdata_metadata()..];
+        self.deploy_program(
+            program_id_pubkey,
+            needs_upgrade,
+            hash(program_data_bytecode),
+            transaction,
+        )
     }
 
     fn dump_program_account_with_old_bpf(
@@ -156,9 +259,12 @@ impl AccountDumper for AccountDumperBank {
         // derive program data account address, as expected by upgradeable BPF loader
         let programdata_address = get_program_data_address(program_pubkey);
         let slot = self.bank.slot();
+        let validator_authority =
+            self.bank.validator_context().validator_authority_id();
 
         // we can use the whole data field of program, as it only contains the executable bytecode
         let program_data_modification = create_program_data_modification(
+            &validator_authority,
             &programdata_address,
             &program_account.data,
             slot,
@@ -181,19 +287,117 @@ impl AccountDumper for AccountDumperBank {
             .owner
             .replace(bpf_loader_upgradeable::ID);
 
-        let program_buffer_modification =
-            create_program_buffer_modification(&program_account.data);
+        let program_buffer_modification = create_program_buffer_modification(
+            &validator_authority,
+            &program_account.data,
+        );
 
         let needs_upgrade = self.bank.has_account(program_pubkey);
 
         let transaction = transaction_to_clone_program(
+            self.bank.validator_context(),
             needs_upgrade,
             program_id_modification,
             program_data_modification,
             program_buffer_modification,
             None,
             self.bank.last_blockhash(),
+        )
+        .map_err(AccountDumperError::MutatorModificationError)?;
+        self.deploy_program(
+            program_pubkey,
+            needs_upgrade,
+            hash(&program_account.data),
+            transaction,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::{
+        account::ReadableAccount, native_token::LAMPORTS_PER_SOL,
+        system_program,
+    };
+    use test_tools::validator::bank_with_started_validator;
+
+    use super::*;
+
+    fn dumper_with_mainnet_rent_semantics(
+        mainnet_rent_semantics: bool,
+    ) -> AccountDumperBank {
+        let bank = bank_with_started_validator();
+        AccountDumperBank::new(bank, None)
+            .with_mainnet_rent_semantics(mainnet_rent_semantics)
+    }
+
+    #[test]
+    fn test_dump_undelegated_account_rent_epoch() {
+        let account = Account {
+            lamports: LAMPORTS_PER_SOL,
+            owner: system_program::id(),
+            data: vec![1; 8],
+            rent_epoch: 42,
+            ..Default::default()
+        };
+        for (mainnet_rent_semantics, expected_rent_epoch) in
+            [(false, 42), (true, RENT_EXEMPT_RENT_EPOCH)]
+        {
+            let dumper =
+                dumper_with_mainnet_rent_semantics(mainnet_rent_semantics);
+            let pubkey = Pubkey::new_unique();
+            dumper.dump_undelegated_account(&pubkey, &account).unwrap();
+            assert_eq!(
+                dumper.bank.get_account(&pubkey).unwrap().rent_epoch(),
+                expected_rent_epoch
+            );
+        }
+    }
+
+    #[test]
+    fn test_dump_program_defers_upgrade_to_end_of_slot() {
+        let dumper = dumper_with_mainnet_rent_semantics(false);
+        let program_id = Pubkey::new_unique();
+        let program_account = |bytecode: &[u8]| Account {
+            lamports: LAMPORTS_PER_SOL,
+            owner: bpf_loader_upgradeable::id(),
+            data: bytecode.to_vec(),
+            executable: true,
+            ..Default::default()
+        };
+        let slot = dumper.bank.slot();
+
+        // Programs that don't exist yet are deployed right away
+        dumper
+            .dump_program_account_with_old_bpf(
+                &program_id,
+                &program_account(b"v1"),
+            )
+            .unwrap();
+        let v1 = ProgramVersion {
+            effective_slot: slot,
+            bytecode_hash: hash(b"v1"),
+        };
+        assert_eq!(
+            dumper.bank.get_program_version(&program_id, slot),
+            Some(v1)
         );
-        self.execute_transaction(transaction)
+
+        // Upgrades are queued while the deployed version remains in effect
+        let signature = dumper
+            .dump_program_account_with_old_bpf(
+                &program_id,
+                &program_account(b"v2"),
+            )
+            .unwrap();
+        assert_eq!(
+            dumper.bank.get_program_version(&program_id, slot),
+            Some(v1)
+        );
+        let pending = dumper.bank.take_pending_program_upgrades();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].program_id, program_id);
+        assert_eq!(pending[0].bytecode_hash, hash(b"v2"));
+        assert_eq!(pending[0].transaction.signatures[0], signature);
     }
 }
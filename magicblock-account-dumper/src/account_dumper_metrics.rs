@@ -0,0 +1,98 @@
+use std::time::Instant;
+
+use magicblock_metrics::metrics::{self, Outcome};
+use solana_sdk::{account::Account, pubkey::Pubkey, signature::Signature};
+
+use crate::{AccountDumper, AccountDumperResult};
+
+/// Decorates an [AccountDumper] by tracking the count, outcome and duration
+/// of dumps per kind of dump.
+pub struct AccountDumperMetrics<T: AccountDumper> {
+    inner: T,
+}
+
+impl<T: AccountDumper> AccountDumperMetrics<T> {
+    pub fn new(inner: T) -> Self {
+        Self { inner }
+    }
+
+    fn observe(
+        kind: &str,
+        dump: impl FnOnce() -> AccountDumperResult<Signature>,
+    ) -> AccountDumperResult<Signature> {
+        let start = Instant::now();
+        let result = dump();
+        metrics::observe_account_dump(
+            kind,
+            Outcome::from_success(result.is_ok()),
+            start.elapsed(),
+        );
+        result
+    }
+}
+
+impl<T: AccountDumper> AccountDumper for AccountDumperMetrics<T> {
+    fn dump_feepayer_account(
+        &self,
+        pubkey: &Pubkey,
+        lamports: u64,
+        owner: &Pubkey,
+    ) -> AccountDumperResult<Signature> {
+        Self::observe("feepayer", || {
+            self.inner.dump_feepayer_account(pubkey, lamports, owner)
+        })
+    }
+
+    fn dump_undelegated_account(
+        &self,
+        pubkey: &Pubkey,
+        account: &Account,
+    ) -> AccountDumperResult<Signature> {
+        Self::observe("undelegated", || {
+            self.inner.dump_undelegated_account(pubkey, account)
+        })
+    }
+
+    fn dump_delegated_account(
+        &self,
+        pubkey: &Pubkey,
+        account: &Account,
+        owner: &Pubkey,
+    ) -> AccountDumperResult<Signature> {
+        Self::observe("delegated", || {
+            self.inner.dump_delegated_account(pubkey, account, owner)
+        })
+    }
+
+    fn dump_program_accounts(
+        &self,
+        program_id: &Pubkey,
+        program_id_account: &Account,
+        program_data: &Pubkey,
+        program_data_account: &Account,
+        program_idl: Option<(Pubkey, Account)>,
+    ) -> AccountDumperResult<Signature> {
+        Self::observe("program", || {
+            self.inner.dump_program_accounts(
+                program_id,
+                program_id_account,
+                program_data,
+                program_data_account,
+                program_idl,
+            )
+        })
+    }
+
+    fn dump_program_account_with_old_bpf(
+        &self,
+        program_pubkey: &Pubkey,
+        program_account: &Account,
+    ) -> AccountDumperResult<Signature> {
+        Self::observe("program_old_bpf", || {
+            self.inner.dump_program_account_with_old_bpf(
+                program_pubkey,
+                program_account,
+            )
+        })
+    }
+}
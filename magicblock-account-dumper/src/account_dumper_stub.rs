@@ -1,5 +1,5 @@
 use std::{
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     sync::{Arc, RwLock},
 };
 
@@ -18,6 +18,8 @@ pub struct AccountDumperStub {
     program_ids: Arc<RwLock<HashSet<Pubkey>>>,
     program_datas: Arc<RwLock<HashSet<Pubkey>>>,
     program_idls: Arc<RwLock<HashSet<Pubkey>>>,
+    /// The data of the last dump of each undelegated or delegated account
+    dumped_data: Arc<RwLock<HashMap<Pubkey, Vec<u8>>>>,
 }
 
 impl AccountDumper for AccountDumperStub {
@@ -37,8 +39,9 @@ impl AccountDumper for AccountDumperStub {
     fn dump_undelegated_account(
         &self,
         pubkey: &Pubkey,
-        _account: &Account,
+        account: &Account,
     ) -> AccountDumperResult<Signature> {
+        self.record_dumped_data(pubkey, account);
         self.undelegated_accounts
             .write()
             .expect("RwLock for undelegated_accounts is poisoned")
@@ -49,9 +52,10 @@ impl AccountDumper for AccountDumperStub {
     fn dump_delegated_account(
         &self,
         pubkey: &Pubkey,
-        _account: &Account,
+        account: &Account,
         _owner: &Pubkey,
     ) -> AccountDumperResult<Signature> {
+        self.record_dumped_data(pubkey, account);
         self.delegated_accounts
             .write()
             .expect("RwLock for delegated_accounts is poisoned")
@@ -104,6 +108,17 @@ impl AccountDumper for AccountDumperStub {
 }
 
 impl AccountDumperStub {
+    fn record_dumped_data(&self, pubkey: &Pubkey, account: &Account) {
+        self.dumped_data
+            .write()
+            .expect("RwLock for dumped_data is poisoned")
+            .insert(*pubkey, account.data.clone());
+    }
+
+    pub fn dumped_data(&self, pubkey: &Pubkey) -> Option<Vec<u8>> {
+        self.dumped_data.read().unwrap().get(pubkey).cloned()
+    }
+
     pub fn was_dumped_as_feepayer_account(&self, pubkey: &Pubkey) -> bool {
         self.feepayer_accounts.read().unwrap().contains(pubkey)
     }
@@ -140,5 +155,6 @@ impl AccountDumperStub {
         self.program_ids.write().unwrap().clear();
         self.program_datas.write().unwrap().clear();
         self.program_idls.write().unwrap().clear();
+        self.dumped_data.write().unwrap().clear();
     }
 }
@@ -1,7 +1,11 @@
 mod account_dumper;
+mod account_dumper_audit_log;
 mod account_dumper_bank;
+mod account_dumper_metrics;
 mod account_dumper_stub;
 
 pub use account_dumper::*;
+pub use account_dumper_audit_log::*;
 pub use account_dumper_bank::*;
+pub use account_dumper_metrics::*;
 pub use account_dumper_stub::*;
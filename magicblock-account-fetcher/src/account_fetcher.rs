@@ -1,5 +1,6 @@
 use conjunto_transwise::AccountChainSnapshotShared;
 use futures_util::future::BoxFuture;
+use magicblock_core::error_code::{HasErrorCode, MagicErrorCode};
 use solana_sdk::{clock::Slot, pubkey::Pubkey};
 use thiserror::Error;
 use tokio::sync::oneshot::Sender;
@@ -16,10 +17,24 @@ pub enum AccountFetcherError {
 
     #[error("FailedToFetch '{0}'")]
     FailedToFetch(String),
+
+    #[error("Remote cluster is failing, not fetching until it recovers")]
+    CircuitBreakerOpen,
 }
 
 pub type AccountFetcherResult<T> = Result<T, AccountFetcherError>;
 
+impl HasErrorCode for AccountFetcherError {
+    fn error_code(&self) -> MagicErrorCode {
+        use AccountFetcherError::*;
+        match self {
+            SendError(_) | RecvError(_) => MagicErrorCode::Internal,
+            FailedToFetch(_) => MagicErrorCode::CloneFetchFailed,
+            CircuitBreakerOpen => MagicErrorCode::CloneRemoteUnavailable,
+        }
+    }
+}
+
 pub type AccountFetcherListeners =
     Vec<Sender<AccountFetcherResult<AccountChainSnapshotShared>>>;
 
@@ -30,3 +45,17 @@ pub trait AccountFetcher {
         min_context_slot: Option<Slot>,
     ) -> BoxFuture<AccountFetcherResult<AccountChainSnapshotShared>>;
 }
+
+/// Allows to compose the fetcher used by the validator at runtime, i.e. the
+/// remote fetcher client or the one replaying recorded inputs.
+pub type AccountFetcherStack = Box<dyn AccountFetcher + Send + Sync>;
+
+impl<T: AccountFetcher + ?Sized> AccountFetcher for Box<T> {
+    fn fetch_account_chain_snapshot(
+        &self,
+        pubkey: &Pubkey,
+        min_context_slot: Option<Slot>,
+    ) -> BoxFuture<AccountFetcherResult<AccountChainSnapshotShared>> {
+        (**self).fetch_account_chain_snapshot(pubkey, min_context_slot)
+    }
+}
@@ -1,5 +1,5 @@
 use std::{
-    collections::{hash_map::Entry, HashMap},
+    collections::{hash_map::Entry, HashMap, HashSet},
     sync::{Arc, RwLock},
 };
 
@@ -9,7 +9,9 @@ use conjunto_transwise::{
     CommitFrequency, DelegationInconsistency, DelegationRecord,
 };
 use futures_util::future::{ready, BoxFuture};
-use solana_sdk::{account::Account, clock::Slot, pubkey::Pubkey};
+use solana_sdk::{
+    account::Account, clock::Slot, pubkey::Pubkey, system_program,
+};
 
 use crate::{AccountFetcher, AccountFetcherResult};
 
@@ -19,6 +21,7 @@ enum AccountFetcherStubState {
     Undelegated,
     Delegated { delegation_record: DelegationRecord },
     Executable,
+    Closed,
 }
 
 #[derive(Debug)]
@@ -31,6 +34,7 @@ struct AccountFetcherStubSnapshot {
 pub struct AccountFetcherStub {
     fetched_counters: Arc<RwLock<HashMap<Pubkey, u64>>>,
     known_accounts: Arc<RwLock<HashMap<Pubkey, AccountFetcherStubSnapshot>>>,
+    panicking_accounts: Arc<RwLock<HashSet<Pubkey>>>,
 }
 
 impl AccountFetcherStub {
@@ -80,6 +84,12 @@ impl AccountFetcherStub {
                             delegation_inconsistency: DelegationInconsistency::DelegationRecordNotFound,
                         }
                     }
+                    AccountFetcherStubState::Closed => {
+                        AccountChainState::FeePayer {
+                            lamports: 0,
+                            owner: system_program::ID,
+                        }
+                    }
                 },
             }
             .into()),
@@ -115,6 +125,20 @@ impl AccountFetcherStub {
         pubkey: Pubkey,
         at_slot: Slot,
         delegation_slot: Slot,
+    ) {
+        self.set_delegated_account_with_authority(
+            pubkey,
+            at_slot,
+            delegation_slot,
+            Pubkey::new_unique(),
+        );
+    }
+    pub fn set_delegated_account_with_authority(
+        &self,
+        pubkey: Pubkey,
+        at_slot: Slot,
+        delegation_slot: Slot,
+        authority: Pubkey,
     ) {
         self.insert_known_account(
             pubkey,
@@ -122,7 +146,7 @@ impl AccountFetcherStub {
                 slot: at_slot,
                 state: AccountFetcherStubState::Delegated {
                     delegation_record: DelegationRecord {
-                        authority: Pubkey::new_unique(),
+                        authority,
                         owner: Pubkey::new_unique(),
                         delegation_slot,
                         commit_frequency: CommitFrequency::default(),
@@ -140,6 +164,20 @@ impl AccountFetcherStub {
             },
         );
     }
+    pub fn set_closed_account(&self, pubkey: Pubkey, at_slot: Slot) {
+        self.insert_known_account(
+            pubkey,
+            AccountFetcherStubSnapshot {
+                slot: at_slot,
+                state: AccountFetcherStubState::Closed,
+            },
+        );
+    }
+    /// Makes the next fetch of the account panic, i.e. to simulate a worker
+    /// crashing mid-clone.
+    pub fn set_panicking_account(&self, pubkey: Pubkey) {
+        self.panicking_accounts.write().unwrap().insert(pubkey);
+    }
 
     pub fn get_fetch_count(&self, pubkey: &Pubkey) -> u64 {
         self.fetched_counters
@@ -166,6 +204,9 @@ impl AccountFetcher for AccountFetcherStub {
                 entry.insert(1);
             }
         };
+        if self.panicking_accounts.write().unwrap().remove(pubkey) {
+            panic!("Fetching {} panicked as set up by the test", pubkey);
+        }
         Box::pin(ready(self.generate_account_chain_snapshot(pubkey)))
     }
 }
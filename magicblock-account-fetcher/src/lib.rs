@@ -1,9 +1,11 @@
 mod account_fetcher;
 mod account_fetcher_stub;
+mod missing_accounts_cache;
 mod remote_account_fetcher_client;
 mod remote_account_fetcher_worker;
 
 pub use account_fetcher::*;
 pub use account_fetcher_stub::*;
+pub use missing_accounts_cache::*;
 pub use remote_account_fetcher_client::*;
 pub use remote_account_fetcher_worker::*;
@@ -0,0 +1,97 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use conjunto_transwise::{AccountChainSnapshotShared, AccountChainState};
+use magicblock_core::robust_lock::RobustMutex;
+use solana_sdk::{clock::Slot, pubkey::Pubkey, system_program};
+
+/// Accounts remembered at most, once reached accounts are only remembered
+/// again after the ones that expired were forgotten
+const MAX_ENTRIES: usize = 100_000;
+
+/// Remembers for a short time which accounts don't exist on chain, such that
+/// probing the same nonexistent accounts repeatedly, i.e. PDAs of players
+/// that did not join a game yet, does not ask the remote each time.
+/// Fetches of an account are served from it as long as they don't require a
+/// context slot after the one at which the account was missing.
+#[derive(Debug, Clone)]
+pub struct MissingAccountsCache {
+    ttl: Option<Duration>,
+    missing: Arc<Mutex<HashMap<Pubkey, (AccountChainSnapshotShared, Instant)>>>,
+}
+
+impl MissingAccountsCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl: (!ttl.is_zero()).then_some(ttl),
+            missing: Default::default(),
+        }
+    }
+
+    pub fn disabled() -> Self {
+        Self {
+            ttl: None,
+            missing: Default::default(),
+        }
+    }
+
+    /// Returns the snapshot of the account fetched while it was missing if
+    /// that did not expire and satisfies the [min_context_slot].
+    pub fn get(
+        &self,
+        pubkey: &Pubkey,
+        min_context_slot: Option<Slot>,
+    ) -> Option<AccountChainSnapshotShared> {
+        let ttl = self.ttl?;
+        let mut missing = self.missing.lock_robust();
+        let (snapshot, fetched_at) = missing.get(pubkey)?;
+        if fetched_at.elapsed() >= ttl {
+            missing.remove(pubkey);
+            return None;
+        }
+        if min_context_slot.is_some_and(|slot| slot > snapshot.at_slot) {
+            return None;
+        }
+        Some(snapshot.clone())
+    }
+
+    /// Remembers the account if the snapshot shows it missing on chain and
+    /// forgets it otherwise.
+    pub fn update(&self, snapshot: &AccountChainSnapshotShared) {
+        let Some(ttl) = self.ttl else {
+            return;
+        };
+        let mut missing = self.missing.lock_robust();
+        if !is_missing_on_chain(snapshot) {
+            missing.remove(&snapshot.pubkey);
+            return;
+        }
+        if missing.len() >= MAX_ENTRIES {
+            missing.retain(|_, (_, fetched_at)| fetched_at.elapsed() < ttl);
+            if missing.len() >= MAX_ENTRIES {
+                return;
+            }
+        }
+        missing.insert(snapshot.pubkey, (snapshot.clone(), Instant::now()));
+    }
+
+    pub fn len(&self) -> usize {
+        self.missing.lock_robust().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Accounts that don't exist on chain are fetched as empty fee payers
+fn is_missing_on_chain(snapshot: &AccountChainSnapshotShared) -> bool {
+    matches!(
+        snapshot.chain_state,
+        AccountChainState::FeePayer { lamports: 0, owner }
+            if owner == system_program::ID
+    )
+}
@@ -8,17 +8,19 @@ use futures_util::{
     future::{ready, BoxFuture},
     FutureExt,
 };
+use magicblock_core::robust_lock::RobustMutex;
 use solana_sdk::{clock::Slot, pubkey::Pubkey};
 use tokio::sync::{mpsc::UnboundedSender, oneshot::channel};
 
 use crate::{
     AccountFetcher, AccountFetcherError, AccountFetcherListeners,
-    AccountFetcherResult, RemoteAccountFetcherWorker,
+    AccountFetcherResult, MissingAccountsCache, RemoteAccountFetcherWorker,
 };
 
 pub struct RemoteAccountFetcherClient {
     fetch_request_sender: UnboundedSender<(Pubkey, Option<Slot>)>,
     fetch_listeners: Arc<Mutex<HashMap<Pubkey, AccountFetcherListeners>>>,
+    missing_accounts: MissingAccountsCache,
 }
 
 impl RemoteAccountFetcherClient {
@@ -26,6 +28,7 @@ impl RemoteAccountFetcherClient {
         Self {
             fetch_request_sender: worker.get_fetch_request_sender(),
             fetch_listeners: worker.get_fetch_listeners(),
+            missing_accounts: worker.get_missing_accounts_cache(),
         }
     }
 }
@@ -36,23 +39,26 @@ impl AccountFetcher for RemoteAccountFetcherClient {
         pubkey: &Pubkey,
         min_context_slot: Option<Slot>,
     ) -> BoxFuture<AccountFetcherResult<AccountChainSnapshotShared>> {
-        let (should_request_fetch, receiver) = match self
-            .fetch_listeners
-            .lock()
-            .expect("RwLock of RemoteAccountFetcherClient.fetch_listeners is poisoned")
-            .entry(*pubkey)
+        // Nonexistent accounts that were probed recently don't exist yet
+        if let Some(snapshot) =
+            self.missing_accounts.get(pubkey, min_context_slot)
         {
-            Entry::Vacant(entry) => {
-                let (sender, receiver) = channel();
-                entry.insert(vec![sender]);
-                (true, receiver)
-            }
-            Entry::Occupied(mut entry) => {
-                let (sender, receiver) = channel();
-                entry.get_mut().push(sender);
-                (false, receiver)
-            }
-        };
+            magicblock_metrics::metrics::inc_missing_account_cache_hit();
+            return Box::pin(ready(Ok(snapshot)));
+        }
+        let (should_request_fetch, receiver) =
+            match self.fetch_listeners.lock_robust().entry(*pubkey) {
+                Entry::Vacant(entry) => {
+                    let (sender, receiver) = channel();
+                    entry.insert(vec![sender]);
+                    (true, receiver)
+                }
+                Entry::Occupied(mut entry) => {
+                    let (sender, receiver) = channel();
+                    entry.get_mut().push(sender);
+                    (false, receiver)
+                }
+            };
         // track the number of pending clones, might be helpful to detect memory leaks
         magicblock_metrics::metrics::inc_pending_clone_requests();
         if should_request_fetch {
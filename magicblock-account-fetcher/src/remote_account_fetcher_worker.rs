@@ -1,6 +1,7 @@
 use std::{
     collections::{hash_map::Entry, HashMap},
     sync::{Arc, Mutex},
+    time::{Duration, Instant},
     vec,
 };
 
@@ -10,13 +11,20 @@ use conjunto_transwise::{
 };
 use futures_util::future::join_all;
 use log::*;
+use magicblock_core::{
+    chaos::ChaosInjector, circuit_breaker::CircuitBreaker,
+    robust_lock::RobustMutex,
+};
+use magicblock_metrics::metrics;
 use solana_sdk::{clock::Slot, pubkey::Pubkey};
 use tokio::sync::mpsc::{
     unbounded_channel, UnboundedReceiver, UnboundedSender,
 };
 use tokio_util::sync::CancellationToken;
 
-use crate::{AccountFetcherError, AccountFetcherListeners};
+use crate::{
+    AccountFetcherError, AccountFetcherListeners, MissingAccountsCache,
+};
 
 pub struct RemoteAccountFetcherWorker {
     account_chain_snapshot_provider: AccountChainSnapshotProvider<
@@ -26,10 +34,15 @@ pub struct RemoteAccountFetcherWorker {
     fetch_request_receiver: UnboundedReceiver<(Pubkey, Option<Slot>)>,
     fetch_request_sender: UnboundedSender<(Pubkey, Option<Slot>)>,
     fetch_listeners: Arc<Mutex<HashMap<Pubkey, AccountFetcherListeners>>>,
+    endpoint: String,
+    circuit_breaker: CircuitBreaker,
+    chaos: ChaosInjector,
+    missing_accounts: MissingAccountsCache,
 }
 
 impl RemoteAccountFetcherWorker {
     pub fn new(config: RpcProviderConfig) -> Self {
+        let endpoint = metrics::remote_endpoint(config.url()).to_string();
         let account_chain_snapshot_provider = AccountChainSnapshotProvider::new(
             RpcAccountProvider::new(config),
             DelegationRecordParserImpl,
@@ -41,9 +54,41 @@ impl RemoteAccountFetcherWorker {
             fetch_request_receiver,
             fetch_request_sender,
             fetch_listeners: Default::default(),
+            endpoint,
+            circuit_breaker: CircuitBreaker::disabled(),
+            chaos: ChaosInjector::disabled(),
+            missing_accounts: MissingAccountsCache::disabled(),
         }
     }
 
+    /// Fails fetches right away while the [CircuitBreaker] is open.
+    /// It is shared with the other components talking to the same cluster.
+    pub fn with_circuit_breaker(
+        mut self,
+        circuit_breaker: CircuitBreaker,
+    ) -> Self {
+        self.circuit_breaker = circuit_breaker;
+        self
+    }
+
+    /// Delays fetches as configured in the [ChaosInjector].
+    pub fn with_chaos(mut self, chaos: ChaosInjector) -> Self {
+        self.chaos = chaos;
+        self
+    }
+
+    /// Serves fetches of accounts that were missing on chain without asking
+    /// the remote again until [ttl] passed.
+    /// Needs to be set before clients are created from the worker.
+    pub fn with_missing_accounts_ttl(mut self, ttl: Duration) -> Self {
+        self.missing_accounts = MissingAccountsCache::new(ttl);
+        self
+    }
+
+    pub fn get_missing_accounts_cache(&self) -> MissingAccountsCache {
+        self.missing_accounts.clone()
+    }
+
     pub fn get_fetch_request_sender(
         &self,
     ) -> UnboundedSender<(Pubkey, Option<Slot>)> {
@@ -81,19 +126,10 @@ impl RemoteAccountFetcherWorker {
         let pubkey = request.0;
         let min_context_slot = request.1;
         // Actually fetch the account asynchronously
-        let result = match self
-            .account_chain_snapshot_provider
-            .try_fetch_chain_snapshot_of_pubkey(&pubkey, min_context_slot)
-            .await
-        {
-            Ok(snapshot) => Ok(AccountChainSnapshotShared::from(snapshot)),
-            // LockboxError is unclonable, so we have to downgrade it to a clonable error type
-            Err(error) => {
-                // Log the error now, since we're going to lose the stacktrace after string conversion
-                warn!("Failed to fetch account: {} :{:?}", pubkey, error);
-                // Lose the error full stack trace and create a simplified clonable string version
-                Err(AccountFetcherError::FailedToFetch(error.to_string()))
-            }
+        let result = if self.circuit_breaker.is_open() {
+            Err(AccountFetcherError::CircuitBreakerOpen)
+        } else {
+            self.fetch(&pubkey, min_context_slot).await
         };
         // Log the result for debugging purposes
         debug!(
@@ -101,17 +137,13 @@ impl RemoteAccountFetcherWorker {
             pubkey, min_context_slot, result
         );
         // Collect the listeners waiting for the result
-        let listeners = match self
-            .fetch_listeners
-            .lock()
-            .expect(
-                "Mutex of RemoteAccountFetcherWorker.fetch_listeners is poisoned",
-            )
-            .entry(pubkey)
-        {
+        let listeners = match self.fetch_listeners.lock_robust().entry(pubkey) {
             // If the entry didn't exist for some reason, something is very wrong, just fail here
             Entry::Vacant(_) => {
-                return error!("Fetch listeners were discarded improperly: {}", pubkey);
+                return error!(
+                    "Fetch listeners were discarded improperly: {}",
+                    pubkey
+                );
             }
             // If the entry exists, we want to consume the list of listeners
             Entry::Occupied(entry) => entry.remove(),
@@ -123,4 +155,48 @@ impl RemoteAccountFetcherWorker {
             }
         }
     }
+
+    async fn fetch(
+        &self,
+        pubkey: &Pubkey,
+        min_context_slot: Option<Slot>,
+    ) -> Result<AccountChainSnapshotShared, AccountFetcherError> {
+        let start = Instant::now();
+        if let Some(delay) = self.chaos.rpc_delay() {
+            tokio::time::sleep(delay).await;
+        }
+        let result = self
+            .account_chain_snapshot_provider
+            .try_fetch_chain_snapshot_of_pubkey(pubkey, min_context_slot)
+            .await;
+        metrics::observe_remote_rpc_request(
+            &self.endpoint,
+            "fetch_account",
+            metrics::Outcome::from_success(result.is_ok()),
+            start.elapsed(),
+        );
+        match result {
+            Ok(snapshot) => {
+                self.circuit_breaker.record_success();
+                metrics::set_remote_circuit_open(&self.endpoint, false);
+                let snapshot = AccountChainSnapshotShared::from(snapshot);
+                self.missing_accounts.update(&snapshot);
+                Ok(snapshot)
+            }
+            // LockboxError is unclonable, so we have to downgrade it to a clonable error type
+            Err(error) => {
+                // Log the error now, since we're going to lose the stacktrace after string conversion
+                warn!("Failed to fetch account: {} :{:?}", pubkey, error);
+                if self.circuit_breaker.record_failure() {
+                    warn!(
+                        "Pausing requests to remote cluster '{}' after repeated failures",
+                        self.endpoint
+                    );
+                    metrics::set_remote_circuit_open(&self.endpoint, true);
+                }
+                // Lose the error full stack trace and create a simplified clonable string version
+                Err(AccountFetcherError::FailedToFetch(error.to_string()))
+            }
+        }
+    }
 }
@@ -0,0 +1,97 @@
+use std::time::Duration;
+
+use conjunto_transwise::{
+    AccountChainSnapshot, AccountChainSnapshotShared, AccountChainState,
+    DelegationInconsistency,
+};
+use magicblock_account_fetcher::MissingAccountsCache;
+use solana_sdk::{
+    account::Account, clock::Slot, pubkey::Pubkey, system_program,
+};
+
+const TTL: Duration = Duration::from_millis(50);
+
+fn missing_snapshot(
+    pubkey: Pubkey,
+    at_slot: Slot,
+) -> AccountChainSnapshotShared {
+    AccountChainSnapshot {
+        pubkey,
+        at_slot,
+        chain_state: AccountChainState::FeePayer {
+            lamports: 0,
+            owner: system_program::ID,
+        },
+    }
+    .into()
+}
+
+fn existing_snapshot(
+    pubkey: Pubkey,
+    at_slot: Slot,
+) -> AccountChainSnapshotShared {
+    AccountChainSnapshot {
+        pubkey,
+        at_slot,
+        chain_state: AccountChainState::Undelegated {
+            account: Account {
+                lamports: 42,
+                owner: Pubkey::new_unique(),
+                ..Default::default()
+            },
+            delegation_inconsistency:
+                DelegationInconsistency::DelegationRecordNotFound,
+        },
+    }
+    .into()
+}
+
+#[test]
+fn test_missing_account_is_served_until_it_expires() {
+    let cache = MissingAccountsCache::new(TTL);
+    let pubkey = Pubkey::new_unique();
+
+    cache.update(&missing_snapshot(pubkey, 42));
+    assert_eq!(cache.get(&pubkey, None), Some(missing_snapshot(pubkey, 42)));
+
+    std::thread::sleep(TTL);
+    assert_eq!(cache.get(&pubkey, None), None);
+    assert!(cache.is_empty());
+}
+
+#[test]
+fn test_missing_account_is_not_served_for_later_context_slot() {
+    let cache = MissingAccountsCache::new(TTL);
+    let pubkey = Pubkey::new_unique();
+
+    cache.update(&missing_snapshot(pubkey, 42));
+    assert!(cache.get(&pubkey, Some(42)).is_some());
+    assert_eq!(cache.get(&pubkey, Some(43)), None);
+}
+
+#[test]
+fn test_existing_account_is_not_remembered() {
+    let cache = MissingAccountsCache::new(TTL);
+    let pubkey = Pubkey::new_unique();
+
+    cache.update(&existing_snapshot(pubkey, 42));
+    assert_eq!(cache.get(&pubkey, None), None);
+
+    // Once the account was created it is forgotten right away
+    cache.update(&missing_snapshot(pubkey, 43));
+    assert_eq!(cache.len(), 1);
+    cache.update(&existing_snapshot(pubkey, 44));
+    assert_eq!(cache.get(&pubkey, None), None);
+}
+
+#[test]
+fn test_disabled_cache_remembers_nothing() {
+    let pubkey = Pubkey::new_unique();
+    for cache in [
+        MissingAccountsCache::disabled(),
+        MissingAccountsCache::new(Duration::ZERO),
+    ] {
+        cache.update(&missing_snapshot(pubkey, 42));
+        assert_eq!(cache.get(&pubkey, None), None);
+    }
+}
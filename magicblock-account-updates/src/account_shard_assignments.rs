@@ -0,0 +1,74 @@
+use std::collections::{HashMap, HashSet};
+
+use solana_sdk::pubkey::Pubkey;
+
+/// Tracks which of the websocket connections of a pool monitors which
+/// account, accounts are assigned to the connection monitoring the fewest
+/// accounts.
+#[derive(Debug)]
+pub struct AccountShardAssignments {
+    shards: Vec<HashSet<Pubkey>>,
+    shard_by_account: HashMap<Pubkey, usize>,
+}
+
+impl AccountShardAssignments {
+    pub fn new(shard_count: usize) -> Self {
+        Self {
+            shards: vec![HashSet::new(); shard_count.max(1)],
+            shard_by_account: HashMap::new(),
+        }
+    }
+
+    pub fn shard_count(&self) -> usize {
+        self.shards.len()
+    }
+
+    /// Assigns the account to the least loaded shard unless it is assigned
+    /// already and returns the shard monitoring it.
+    pub fn assign(&mut self, pubkey: Pubkey) -> usize {
+        if let Some(shard) = self.shard_by_account.get(&pubkey) {
+            return *shard;
+        }
+        let shard = self.least_loaded_shard(None);
+        self.shards[shard].insert(pubkey);
+        self.shard_by_account.insert(pubkey, shard);
+        shard
+    }
+
+    /// Moves the accounts of the [failed_shard] to the remaining shards and
+    /// returns where each of them was moved to.
+    /// With a single shard the accounts stay assigned to it so that its
+    /// replacement picks them up.
+    pub fn rebalance_from(
+        &mut self,
+        failed_shard: usize,
+    ) -> Vec<(Pubkey, usize)> {
+        if self.shards.len() < 2 {
+            return vec![];
+        }
+        let pubkeys = std::mem::take(&mut self.shards[failed_shard]);
+        pubkeys
+            .into_iter()
+            .map(|pubkey| {
+                let shard = self.least_loaded_shard(Some(failed_shard));
+                self.shards[shard].insert(pubkey);
+                self.shard_by_account.insert(pubkey, shard);
+                (pubkey, shard)
+            })
+            .collect()
+    }
+
+    pub fn shard_accounts(&self, shard: usize) -> &HashSet<Pubkey> {
+        &self.shards[shard]
+    }
+
+    fn least_loaded_shard(&self, excluded_shard: Option<usize>) -> usize {
+        self.shards
+            .iter()
+            .enumerate()
+            .filter(|(shard, _)| Some(*shard) != excluded_shard)
+            .min_by_key(|(_, pubkeys)| pubkeys.len())
+            .map(|(shard, _)| shard)
+            .unwrap_or_default()
+    }
+}
@@ -17,3 +17,22 @@ pub trait AccountUpdates {
     fn get_first_subscribed_slot(&self, pubkey: &Pubkey) -> Option<Slot>;
     fn get_last_known_update_slot(&self, pubkey: &Pubkey) -> Option<Slot>;
 }
+
+/// Allows to compose the account updates used by the validator at runtime,
+/// i.e. the remote updates client or the one replaying recorded inputs.
+pub type AccountUpdatesStack = Box<dyn AccountUpdates + Send + Sync>;
+
+impl<T: AccountUpdates + ?Sized> AccountUpdates for Box<T> {
+    fn ensure_account_monitoring(
+        &self,
+        pubkey: &Pubkey,
+    ) -> AccountUpdatesResult<()> {
+        (**self).ensure_account_monitoring(pubkey)
+    }
+    fn get_first_subscribed_slot(&self, pubkey: &Pubkey) -> Option<Slot> {
+        (**self).get_first_subscribed_slot(pubkey)
+    }
+    fn get_last_known_update_slot(&self, pubkey: &Pubkey) -> Option<Slot> {
+        (**self).get_last_known_update_slot(pubkey)
+    }
+}
@@ -1,9 +1,11 @@
+mod account_shard_assignments;
 mod account_updates;
 mod account_updates_stub;
 mod remote_account_updates_client;
 mod remote_account_updates_shard;
 mod remote_account_updates_worker;
 
+pub use account_shard_assignments::*;
 pub use account_updates::*;
 pub use account_updates_stub::*;
 pub use remote_account_updates_client::*;
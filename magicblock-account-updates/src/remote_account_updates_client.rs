@@ -3,6 +3,7 @@ use std::{
     sync::{Arc, RwLock},
 };
 
+use magicblock_core::robust_lock::RobustRwLock;
 use solana_sdk::{clock::Slot, pubkey::Pubkey};
 use tokio::sync::mpsc::UnboundedSender;
 
@@ -35,15 +36,13 @@ impl AccountUpdates for RemoteAccountUpdatesClient {
     }
     fn get_first_subscribed_slot(&self, pubkey: &Pubkey) -> Option<Slot> {
         self.first_subscribed_slots
-            .read()
-            .expect("RwLock of RemoteAccountUpdatesClient.first_subscribed_slots poisoned")
+            .read_robust()
             .get(pubkey)
             .cloned()
     }
     fn get_last_known_update_slot(&self, pubkey: &Pubkey) -> Option<Slot> {
         self.last_known_update_slots
-            .read()
-            .expect("RwLock of RemoteAccountUpdatesClient.last_known_update_slots poisoned")
+            .read_robust()
             .get(pubkey)
             .cloned()
     }
@@ -2,19 +2,24 @@ use std::{
     cmp::{max, min},
     collections::{hash_map::Entry, HashMap},
     sync::{Arc, RwLock},
+    time::Instant,
 };
 
 use conjunto_transwise::RpcProviderConfig;
 use futures_util::StreamExt;
 use log::*;
+use magicblock_core::{
+    chain_slot_mapping::ChainSlotMapping, chaos::ChaosInjector,
+    robust_lock::RobustRwLock,
+};
+use magicblock_metrics::metrics;
 use solana_account_decoder::{UiAccountEncoding, UiDataSliceConfig};
 use solana_pubsub_client::nonblocking::pubsub_client::PubsubClient;
-use solana_rpc_client_api::config::RpcAccountInfoConfig;
+use solana_rpc_client_api::{
+    config::RpcAccountInfoConfig, response::SlotUpdate,
+};
 use solana_sdk::{
-    clock::{Clock, Slot},
-    commitment_config::CommitmentConfig,
-    pubkey::Pubkey,
-    sysvar::clock,
+    clock::Slot, commitment_config::CommitmentConfig, pubkey::Pubkey,
 };
 use thiserror::Error;
 use tokio::sync::mpsc::UnboundedReceiver;
@@ -28,6 +33,8 @@ pub enum RemoteAccountUpdatesShardError {
         #[from]
         solana_pubsub_client::nonblocking::pubsub_client::PubsubClientError,
     ),
+    #[error("Websocket connection dropped by chaos injection")]
+    ChaosDroppedConnection,
 }
 
 pub struct RemoteAccountUpdatesShard {
@@ -36,6 +43,9 @@ pub struct RemoteAccountUpdatesShard {
     monitoring_request_receiver: UnboundedReceiver<Pubkey>,
     first_subscribed_slots: Arc<RwLock<HashMap<Pubkey, Slot>>>,
     last_known_update_slots: Arc<RwLock<HashMap<Pubkey, Slot>>>,
+    chain_slot_mapping: ChainSlotMapping,
+    endpoint: String,
+    chaos: ChaosInjector,
 }
 
 impl RemoteAccountUpdatesShard {
@@ -45,13 +55,20 @@ impl RemoteAccountUpdatesShard {
         monitoring_request_receiver: UnboundedReceiver<Pubkey>,
         first_subscribed_slots: Arc<RwLock<HashMap<Pubkey, Slot>>>,
         last_known_update_slots: Arc<RwLock<HashMap<Pubkey, Slot>>>,
+        chain_slot_mapping: ChainSlotMapping,
+        chaos: ChaosInjector,
     ) -> Self {
+        let endpoint =
+            metrics::remote_endpoint(rpc_provider_config.ws_url()).to_string();
         Self {
             shard_id,
             rpc_provider_config,
             monitoring_request_receiver,
             first_subscribed_slots,
             last_known_update_slots,
+            chain_slot_mapping,
+            endpoint,
+            chaos,
         }
     }
 
@@ -61,10 +78,16 @@ impl RemoteAccountUpdatesShard {
     ) -> Result<(), RemoteAccountUpdatesShardError> {
         // Create a pubsub client
         info!("Shard {}: Starting", self.shard_id);
+        let start = Instant::now();
         let pubsub_client =
-            PubsubClient::new(self.rpc_provider_config.ws_url())
-                .await
-                .map_err(RemoteAccountUpdatesShardError::PubsubClientError)?;
+            PubsubClient::new(self.rpc_provider_config.ws_url()).await;
+        self.observe_remote_request(
+            "pubsub_connect",
+            pubsub_client.is_ok(),
+            start,
+        );
+        let pubsub_client = pubsub_client
+            .map_err(RemoteAccountUpdatesShardError::PubsubClientError)?;
         // For every account, we only want the updates, not the actual content of the accounts
         let rpc_account_info_config = Some(RpcAccountInfoConfig {
             commitment: self
@@ -78,28 +101,43 @@ impl RemoteAccountUpdatesShard {
             }),
             min_context_slot: None,
         });
-        // Subscribe to the clock from the RPC (to figure out the latest slot)
-        let (mut clock_stream, clock_unsubscribe) = pubsub_client
-            .account_subscribe(&clock::ID, rpc_account_info_config.clone())
-            .await
+        // Subscribe to the slots of the remote cluster (to figure out the latest
+        // confirmed slot). Plain slot subscriptions report processed slots,
+        // which confirmed fetches using them as min_context_slot would have
+        // to wait for
+        let start = Instant::now();
+        let slot_subscription = pubsub_client.slot_updates_subscribe().await;
+        self.observe_remote_request(
+            "slot_updates_subscribe",
+            slot_subscription.is_ok(),
+            start,
+        );
+        let (mut slot_stream, slot_unsubscribe) = slot_subscription
             .map_err(RemoteAccountUpdatesShardError::PubsubClientError)?;
-        let mut clock_slot = 0;
+        let mut chain_slot = None;
+        // Accounts subscribed before we knew the latest slot, their first
+        // subscribed slot is the first slot we get notified about
+        let mut accounts_subscribed_without_slot = vec![];
         // We'll store useful maps for each of the account subscriptions
         let mut account_streams = StreamMap::new();
         let mut account_unsubscribes = HashMap::new();
         // Loop forever until we stop the worker
         loop {
             tokio::select! {
-                // When we receive a new clock notification
-                Some(clock_update) = clock_stream.next() => {
-                    let clock_data = clock_update.value.data.decode();
-                    trace!("Shard {}: Clock data received: {:?}", self.shard_id, clock_data);
-                    if let Some(clock_data) = clock_data {
-                        let clock_value = bincode::deserialize::<Clock>(&clock_data);
-                        trace!("Shard {}: Clock value received: {:?}", self.shard_id, clock_value);
-                        if let Ok(clock_value) = clock_value {
-                            clock_slot = clock_value.slot;
-                        }
+                // When we receive a new slot notification
+                Some(slot_update) = slot_stream.next() => {
+                    let SlotUpdate::OptimisticConfirmation { slot, .. } = slot_update else {
+                        continue;
+                    };
+                    // Slots of different forks may be confirmed out of order
+                    if chain_slot.map_or(false, |chain_slot| slot <= chain_slot) {
+                        continue;
+                    }
+                    trace!("Shard {}: Confirmed slot received: {}", self.shard_id, slot);
+                    chain_slot = Some(slot);
+                    self.chain_slot_mapping.update_chain_slot(slot);
+                    for pubkey in accounts_subscribed_without_slot.drain(..) {
+                        self.try_to_override_first_subscribed_slot(pubkey, slot);
                     }
                 }
                 // When we receive a message to start monitoring an account
@@ -108,18 +146,28 @@ impl RemoteAccountUpdatesShard {
                         continue;
                     }
                     info!(
-                        "Shard {}: Account monitoring started: {:?}, clock_slot: {:?}",
+                        "Shard {}: Account monitoring started: {:?}, chain_slot: {:?}",
                         self.shard_id,
                         pubkey,
-                        clock_slot
+                        chain_slot
                     );
-                    let (stream, unsubscribe) = pubsub_client
+                    let start = Instant::now();
+                    let subscription = pubsub_client
                         .account_subscribe(&pubkey, rpc_account_info_config.clone())
-                        .await
+                        .await;
+                    self.observe_remote_request(
+                        "account_subscribe",
+                        subscription.is_ok(),
+                        start,
+                    );
+                    let (stream, unsubscribe) = subscription
                         .map_err(RemoteAccountUpdatesShardError::PubsubClientError)?;
                     account_streams.insert(pubkey, stream);
                     account_unsubscribes.insert(pubkey, unsubscribe);
-                    self.try_to_override_first_subscribed_slot(pubkey, clock_slot);
+                    match chain_slot {
+                        Some(chain_slot) => self.try_to_override_first_subscribed_slot(pubkey, chain_slot),
+                        None => accounts_subscribed_without_slot.push(pubkey),
+                    }
                 }
                 // When we receive an update from any account subscriptions
                 Some((pubkey, update)) = account_streams.next() => {
@@ -129,6 +177,11 @@ impl RemoteAccountUpdatesShard {
                         self.shard_id, pubkey, current_update_slot, update.value.data.decode(),
                     );
                     self.try_to_override_last_known_update_slot(pubkey, current_update_slot);
+                    if self.chaos.inject_websocket_drop() {
+                        warn!("Shard {}: Dropping connection due to chaos injection", self.shard_id);
+                        // Bail without unsubscribing like a real connection loss would
+                        return Err(RemoteAccountUpdatesShardError::ChaosDroppedConnection);
+                    }
                 }
                 // When we want to stop the worker (it was cancelled)
                 _ = cancellation_token.cancelled() => {
@@ -144,40 +197,50 @@ impl RemoteAccountUpdatesShard {
             );
             account_unsubscribes().await;
         }
-        clock_unsubscribe().await;
+        slot_unsubscribe().await;
         drop(account_streams);
-        drop(clock_stream);
+        drop(slot_stream);
         pubsub_client.shutdown().await?;
         info!("Shard {}: Stopped", self.shard_id);
         // Done
         Ok(())
     }
 
+    fn observe_remote_request(
+        &self,
+        request: &str,
+        success: bool,
+        start: Instant,
+    ) {
+        metrics::observe_remote_rpc_request(
+            &self.endpoint,
+            request,
+            metrics::Outcome::from_success(success),
+            start.elapsed(),
+        );
+    }
+
     fn try_to_override_first_subscribed_slot(
         &self,
         pubkey: Pubkey,
         subscribed_slot: Slot,
     ) {
         // We don't need to acquire a write lock if we already know the slot is already recent enough
-        let first_subscribed_slot = self.first_subscribed_slots
-                .read()
-                .expect("RwLock of RemoteAccountUpdatesShard.first_subscribed_slots poisoned")
-                .get(&pubkey)
-                .cloned();
+        let first_subscribed_slot = self
+            .first_subscribed_slots
+            .read_robust()
+            .get(&pubkey)
+            .cloned();
         if subscribed_slot < first_subscribed_slot.unwrap_or(u64::MAX) {
             // If the subscribe slot seems to be the oldest one, we need to acquire a write lock to update it
-            match self.first_subscribed_slots
-                    .write()
-                    .expect("RwLock of RemoteAccountUpdatesShard.first_subscribed_slots poisoned")
-                    .entry(pubkey)
-                {
-                    Entry::Vacant(entry) => {
-                        entry.insert(subscribed_slot);
-                    }
-                    Entry::Occupied(mut entry) => {
-                        *entry.get_mut() = min(*entry.get(), subscribed_slot);
-                    }
+            match self.first_subscribed_slots.write_robust().entry(pubkey) {
+                Entry::Vacant(entry) => {
+                    entry.insert(subscribed_slot);
                 }
+                Entry::Occupied(mut entry) => {
+                    *entry.get_mut() = min(*entry.get(), subscribed_slot);
+                }
+            }
         }
     }
 
@@ -187,18 +250,14 @@ impl RemoteAccountUpdatesShard {
         current_update_slot: Slot,
     ) {
         // We don't need to acquire a write lock if we already know the update is too old
-        let last_known_update_slot = self.last_known_update_slots
-            .read()
-            .expect("RwLock of RemoteAccountUpdatesShard.last_known_update_slots poisoned")
+        let last_known_update_slot = self
+            .last_known_update_slots
+            .read_robust()
             .get(&pubkey)
             .cloned();
         if current_update_slot > last_known_update_slot.unwrap_or(u64::MIN) {
             // If the current update seems to be the most recent one, we need to acquire a write lock to update it
-            match self.last_known_update_slots
-                .write()
-                .expect("RwLock of RemoteAccountUpdatesShard.last_known_update_slots poisoned")
-                .entry(pubkey)
-            {
+            match self.last_known_update_slots.write_robust().entry(pubkey) {
                 Entry::Vacant(entry) => {
                     entry.insert(current_update_slot);
                 }
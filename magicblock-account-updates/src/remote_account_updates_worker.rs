@@ -9,16 +9,19 @@ use std::{
 
 use conjunto_transwise::RpcProviderConfig;
 use log::*;
+use magicblock_core::{
+    chain_slot_mapping::ChainSlotMapping, chaos::ChaosInjector,
+};
 use solana_sdk::{clock::Slot, pubkey::Pubkey};
 use thiserror::Error;
 use tokio::{
     sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender},
     task::JoinHandle,
-    time::interval,
+    time::{interval, sleep},
 };
 use tokio_util::sync::CancellationToken;
 
-use crate::RemoteAccountUpdatesShard;
+use crate::{AccountShardAssignments, RemoteAccountUpdatesShard};
 
 #[derive(Debug, Error)]
 pub enum RemoteAccountUpdatesWorkerError {
@@ -31,6 +34,10 @@ pub enum RemoteAccountUpdatesWorkerError {
     SendError(#[from] tokio::sync::mpsc::error::SendError<Pubkey>),
 }
 
+/// How long a shard waits before connecting again after its connection
+/// died, avoids hammering a remote that is down.
+const SHARD_RESTART_DELAY: Duration = Duration::from_secs(1);
+
 #[derive(Debug)]
 struct RemoteAccountUpdatesWorkerRunner {
     id: String,
@@ -39,13 +46,31 @@ struct RemoteAccountUpdatesWorkerRunner {
     join_handle: JoinHandle<()>,
 }
 
+/// The connections to one remote, the monitored accounts are sharded across
+/// them.
+struct RemoteAccountUpdatesWorkerPool {
+    rpc_provider_config: RpcProviderConfig,
+    runners: Vec<RemoteAccountUpdatesWorkerRunner>,
+    assignments: AccountShardAssignments,
+}
+
+/// Identifies the runner whose connection died.
+struct RunnerFailure {
+    pool_index: usize,
+    shard_index: usize,
+    runner_id: String,
+}
+
 pub struct RemoteAccountUpdatesWorker {
     rpc_provider_configs: Vec<RpcProviderConfig>,
     refresh_interval: Duration,
+    shard_count: usize,
     monitoring_request_receiver: UnboundedReceiver<Pubkey>,
     monitoring_request_sender: UnboundedSender<Pubkey>,
     first_subscribed_slots: Arc<RwLock<HashMap<Pubkey, Slot>>>,
     last_known_update_slots: Arc<RwLock<HashMap<Pubkey, Slot>>>,
+    chain_slot_mapping: ChainSlotMapping,
+    chaos: ChaosInjector,
 }
 
 impl RemoteAccountUpdatesWorker {
@@ -58,13 +83,30 @@ impl RemoteAccountUpdatesWorker {
         Self {
             rpc_provider_configs,
             refresh_interval,
+            shard_count: 1,
             monitoring_request_receiver,
             monitoring_request_sender,
             first_subscribed_slots: Default::default(),
             last_known_update_slots: Default::default(),
+            chain_slot_mapping: Default::default(),
+            chaos: ChaosInjector::disabled(),
         }
     }
 
+    /// Shards the monitored accounts across [shard_count] websocket
+    /// connections per remote instead of monitoring all of them via a
+    /// single one.
+    pub fn with_shard_count(mut self, shard_count: usize) -> Self {
+        self.shard_count = shard_count.max(1);
+        self
+    }
+
+    /// Drops websocket connections as configured in the [ChaosInjector].
+    pub fn with_chaos(mut self, chaos: ChaosInjector) -> Self {
+        self.chaos = chaos;
+        self
+    }
+
     pub fn get_monitoring_request_sender(&self) -> UnboundedSender<Pubkey> {
         self.monitoring_request_sender.clone()
     }
@@ -81,53 +123,106 @@ impl RemoteAccountUpdatesWorker {
         self.last_known_update_slots.clone()
     }
 
+    /// The mapping fed with the slots of the remote cluster the runners are
+    /// notified about.
+    pub fn get_chain_slot_mapping(&self) -> ChainSlotMapping {
+        self.chain_slot_mapping.clone()
+    }
+
     pub async fn start_monitoring_request_processing(
         &mut self,
         cancellation_token: CancellationToken,
     ) {
-        // Maintain a runner for each config passed as parameter
-        let mut runners = vec![];
+        // Runners report when their connection died so we can replace them
+        let (runner_failure_sender, mut runner_failure_receiver) =
+            unbounded_channel();
+        // Maintain a pool of runners for each config passed as parameter
+        let mut pools = vec![];
         let mut monitored_accounts = HashSet::new();
         // Initialize all the runners for all configs
-        for (index, rpc_provider_config) in
+        for (pool_index, rpc_provider_config) in
             self.rpc_provider_configs.iter().enumerate()
         {
-            runners.push(self.create_runner_from_config(
-                index,
-                rpc_provider_config.clone(),
-                &monitored_accounts,
-            ));
+            let assignments = AccountShardAssignments::new(self.shard_count);
+            let runners = (0..assignments.shard_count())
+                .map(|shard_index| {
+                    self.create_runner_from_config(
+                        pool_index,
+                        shard_index,
+                        rpc_provider_config.clone(),
+                        &HashSet::new(),
+                        &runner_failure_sender,
+                        Duration::ZERO,
+                    )
+                })
+                .collect();
+            pools.push(RemoteAccountUpdatesWorkerPool {
+                rpc_provider_config: rpc_provider_config.clone(),
+                runners,
+                assignments,
+            });
         }
         // Useful states
+        let runner_count = pools.len() * self.shard_count;
         let mut current_refresh_index = 0;
         let mut refresh_interval = interval(self.refresh_interval);
         refresh_interval.reset();
         // Loop forever until we stop the worker
         loop {
             tokio::select! {
-                // When we receive a message to start monitoring an account, propagate request to all runners
+                // When we receive a message to start monitoring an account, propagate request to one runner of each pool
                 Some(pubkey) = self.monitoring_request_receiver.recv() => {
                     if monitored_accounts.contains(&pubkey) {
                         continue;
                     }
                     monitored_accounts.insert(pubkey);
-                    for runner in runners.iter() {
-                        self.notify_runner_of_monitoring_request(runner, pubkey);
+                    for pool in pools.iter_mut() {
+                        let shard_index = pool.assignments.assign(pubkey);
+                        self.notify_runner_of_monitoring_request(&pool.runners[shard_index], pubkey);
+                    }
+                }
+                // When the connection of a runner died, move its accounts to the other runners of the pool and replace it
+                Some(failure) = runner_failure_receiver.recv() => {
+                    let pool = &mut pools[failure.pool_index];
+                    // The runner may have been replaced by a refresh already
+                    if pool.runners[failure.shard_index].id != failure.runner_id {
+                        continue;
+                    }
+                    let moved_accounts = pool.assignments.rebalance_from(failure.shard_index);
+                    warn!(
+                        "Runner {} failed, moving its {} accounts to the other runners of the pool",
+                        failure.runner_id,
+                        moved_accounts.len()
+                    );
+                    for (pubkey, shard_index) in moved_accounts {
+                        self.notify_runner_of_monitoring_request(&pool.runners[shard_index], pubkey);
                     }
+                    let new_runner = self.create_runner_from_config(
+                        failure.pool_index,
+                        failure.shard_index,
+                        pool.rpc_provider_config.clone(),
+                        pool.assignments.shard_accounts(failure.shard_index),
+                        &runner_failure_sender,
+                        SHARD_RESTART_DELAY,
+                    );
+                    let old_runner = std::mem::replace(&mut pool.runners[failure.shard_index], new_runner);
+                    self.cancel_and_join_runner(old_runner);
                 }
                 // Periodically we refresh runners to keep them fresh
                 _ = refresh_interval.tick() => {
-                    current_refresh_index = (current_refresh_index + 1) % self.rpc_provider_configs.len();
-                    let rpc_provider_config = self.rpc_provider_configs
-                        .get(current_refresh_index)
-                        .unwrap()
-                        .clone();
+                    current_refresh_index = (current_refresh_index + 1) % runner_count;
+                    let pool_index = current_refresh_index / self.shard_count;
+                    let shard_index = current_refresh_index % self.shard_count;
+                    let pool = &mut pools[pool_index];
                     let new_runner = self.create_runner_from_config(
-                        current_refresh_index,
-                        rpc_provider_config,
-                        &monitored_accounts
+                        pool_index,
+                        shard_index,
+                        pool.rpc_provider_config.clone(),
+                        pool.assignments.shard_accounts(shard_index),
+                        &runner_failure_sender,
+                        Duration::ZERO,
                     );
-                    let old_runner = std::mem::replace(&mut runners[current_refresh_index], new_runner);
+                    let old_runner = std::mem::replace(&mut pool.runners[shard_index], new_runner);
                     // We hope it ultimately joins, but we don't care to wait for it, just let it be
                     self.cancel_and_join_runner(old_runner);
                 }
@@ -138,39 +233,61 @@ impl RemoteAccountUpdatesWorker {
             }
         }
         // Cancel all runners one by one when we are done
-        while !runners.is_empty() {
-            let runner = runners.swap_remove(0);
+        for runner in pools.into_iter().flat_map(|pool| pool.runners) {
             self.cancel_and_join_runner(runner);
         }
     }
 
     fn create_runner_from_config(
         &self,
-        index: usize,
+        pool_index: usize,
+        shard_index: usize,
         rpc_provider_config: RpcProviderConfig,
         monitored_accounts: &HashSet<Pubkey>,
+        runner_failure_sender: &UnboundedSender<RunnerFailure>,
+        start_delay: Duration,
     ) -> RemoteAccountUpdatesWorkerRunner {
         let (monitoring_request_sender, monitoring_request_receiver) =
             unbounded_channel();
         let first_subscribed_slots = self.first_subscribed_slots.clone();
         let last_known_update_slots = self.last_known_update_slots.clone();
-        let runner_id = format!("[{}:{:06}]", index, self.generate_runner_id());
+        let chain_slot_mapping = self.chain_slot_mapping.clone();
+        let runner_id = format!(
+            "[{}:{}:{:06}]",
+            pool_index,
+            shard_index,
+            self.generate_runner_id()
+        );
         let cancellation_token = CancellationToken::new();
         let shard_id = runner_id.clone();
         let shard_cancellation_token = cancellation_token.clone();
+        let runner_failure_sender = runner_failure_sender.clone();
+        let chaos = self.chaos.clone();
         let join_handle = tokio::spawn(async move {
+            tokio::select! {
+                _ = sleep(start_delay) => {}
+                _ = shard_cancellation_token.cancelled() => return,
+            }
             let mut shard = RemoteAccountUpdatesShard::new(
                 shard_id.clone(),
                 rpc_provider_config,
                 monitoring_request_receiver,
                 first_subscribed_slots,
                 last_known_update_slots,
+                chain_slot_mapping,
+                chaos,
             );
             if let Err(error) = shard
                 .start_monitoring_request_processing(shard_cancellation_token)
                 .await
             {
                 error!("Runner shard has failed: {}: {:?}", shard_id, error);
+                // The worker is gone if this fails, nothing left to replace
+                let _ = runner_failure_sender.send(RunnerFailure {
+                    pool_index,
+                    shard_index,
+                    runner_id: shard_id,
+                });
             }
         });
         let runner = RemoteAccountUpdatesWorkerRunner {
@@ -179,13 +296,16 @@ impl RemoteAccountUpdatesWorker {
             cancellation_token,
             join_handle,
         };
-        info!("Started new runner {}", runner.id);
+        info!(
+            "Started new runner {} monitoring {} accounts",
+            runner.id,
+            monitored_accounts.len()
+        );
         for pubkey in monitored_accounts.iter() {
             self.notify_runner_of_monitoring_request(&runner, *pubkey);
         }
         runner
     }
-
     fn notify_runner_of_monitoring_request(
         &self,
         runner: &RemoteAccountUpdatesWorkerRunner,
@@ -0,0 +1,57 @@
+use magicblock_account_updates::AccountShardAssignments;
+use solana_sdk::pubkey::Pubkey;
+
+#[test]
+fn test_accounts_are_spread_across_shards() {
+    let mut assignments = AccountShardAssignments::new(3);
+    for _ in 0..9 {
+        assignments.assign(Pubkey::new_unique());
+    }
+    for shard in 0..3 {
+        assert_eq!(assignments.shard_accounts(shard).len(), 3);
+    }
+}
+
+#[test]
+fn test_assigning_the_same_account_twice_keeps_its_shard() {
+    let mut assignments = AccountShardAssignments::new(2);
+    let pubkey = Pubkey::new_unique();
+    let shard = assignments.assign(pubkey);
+    assignments.assign(Pubkey::new_unique());
+    assert_eq!(assignments.assign(pubkey), shard);
+    assert_eq!(assignments.shard_accounts(shard).len(), 1);
+}
+
+#[test]
+fn test_rebalance_moves_accounts_of_failed_shard() {
+    let mut assignments = AccountShardAssignments::new(3);
+    for _ in 0..6 {
+        assignments.assign(Pubkey::new_unique());
+    }
+    let failed_accounts = assignments.shard_accounts(1).clone();
+
+    let moved_accounts = assignments.rebalance_from(1);
+    assert_eq!(moved_accounts.len(), failed_accounts.len());
+    for (pubkey, shard) in moved_accounts {
+        assert!(failed_accounts.contains(&pubkey));
+        assert_ne!(shard, 1);
+        assert!(assignments.shard_accounts(shard).contains(&pubkey));
+        assert_eq!(assignments.assign(pubkey), shard);
+    }
+    assert!(assignments.shard_accounts(1).is_empty());
+    assert_eq!(assignments.shard_accounts(0).len(), 3);
+    assert_eq!(assignments.shard_accounts(2).len(), 3);
+
+    // The replaced shard receives new accounts first
+    assert_eq!(assignments.assign(Pubkey::new_unique()), 1);
+}
+
+#[test]
+fn test_rebalance_keeps_accounts_of_single_shard() {
+    let mut assignments = AccountShardAssignments::new(1);
+    let pubkey = Pubkey::new_unique();
+    assignments.assign(pubkey);
+
+    assert!(assignments.rebalance_from(0).is_empty());
+    assert!(assignments.shard_accounts(0).contains(&pubkey));
+}
@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{collections::HashSet, sync::Arc};
 
 use magicblock_bank::bank::Bank;
 use solana_sdk::{account::AccountSharedData, clock::Slot, pubkey::Pubkey};
@@ -28,4 +28,7 @@ impl InternalAccountProvider for BankAccountProvider {
     fn get_slot(&self) -> Slot {
         self.bank.slot()
     }
+    fn take_dirty_accounts(&self) -> HashSet<Pubkey> {
+        self.bank.take_dirty_accounts()
+    }
 }
@@ -0,0 +1,25 @@
+use solana_sdk::pubkey::Pubkey;
+use thiserror::Error;
+
+pub type AccountsApiResult<T> = std::result::Result<T, AccountsApiError>;
+
+#[derive(Error, Debug)]
+pub enum AccountsApiError {
+    #[error("IdlNotFound for program '{0}'")]
+    IdlNotFound(Pubkey),
+
+    #[error("InvalidIdlAccount '{0}' ('{1}')")]
+    InvalidIdlAccount(Pubkey, String),
+
+    #[error("UnknownAccountType of account '{0}' owned by '{1}'")]
+    UnknownAccountType(Pubkey, Pubkey),
+
+    #[error("UnsupportedIdlType '{0}'")]
+    UnsupportedIdlType(String),
+
+    #[error("InvalidAccountData '{0}'")]
+    InvalidAccountData(String),
+
+    #[error("InvalidIdlFile '{0}' ('{1}')")]
+    InvalidIdlFile(String, String),
+}
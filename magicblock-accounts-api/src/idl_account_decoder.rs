@@ -0,0 +1,449 @@
+use std::{
+    io::{Read, Write},
+    path::Path,
+};
+
+use base64::{prelude::BASE64_STANDARD, Engine};
+use flate2::{read::ZlibDecoder, write::ZlibEncoder, Compression};
+use magicblock_mutator::idl::{get_pubkey_anchor_idl, get_pubkey_shank_idl};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use solana_sdk::{
+    account::{Account, ReadableAccount},
+    clock::Epoch,
+    hash::hashv,
+    pubkey::Pubkey,
+    rent::Rent,
+};
+
+use crate::{
+    errors::{AccountsApiError, AccountsApiResult},
+    InternalAccountProvider,
+};
+
+/// Discriminator (8), authority (32) and length of the compressed IDL (4)
+const IDL_ACCOUNT_HEADER_LEN: usize = 8 + 32 + 4;
+const DISCRIMINATOR_LEN: usize = 8;
+/// Types nested deeper than this are rejected, i.e. recursive types of
+/// an IDL cannot exhaust the stack
+const MAX_TYPE_DEPTH: usize = 64;
+
+/// An account deserialized according to the IDL of the program owning it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IdlDecodedAccount {
+    pub program_id: String,
+    /// The name of the account type in the IDL
+    pub account_type: String,
+    pub data: Value,
+}
+
+/// Deserializes accounts into JSON using the Anchor or Shank IDL of their
+/// owner which was cloned together with the program.
+/// The account type is found by matching the account discriminator, either
+/// the one included in the IDL or the one Anchor derives from the name.
+pub struct IdlAccountDecoder<IAP: InternalAccountProvider> {
+    internal_account_provider: IAP,
+}
+
+impl<IAP: InternalAccountProvider> IdlAccountDecoder<IAP> {
+    pub fn new(internal_account_provider: IAP) -> Self {
+        Self {
+            internal_account_provider,
+        }
+    }
+
+    /// Returns `None` if the account does not exist in the ephemeral.
+    pub fn decode_account(
+        &self,
+        pubkey: &Pubkey,
+    ) -> AccountsApiResult<Option<IdlDecodedAccount>> {
+        let Some(account) = self.internal_account_provider.get_account(pubkey)
+        else {
+            return Ok(None);
+        };
+        let program_id = *account.owner();
+        let idl = self.get_idl(&program_id)?;
+        let (account_type, type_def) = find_account_type(&idl, account.data())
+            .ok_or(AccountsApiError::UnknownAccountType(*pubkey, program_id))?;
+
+        let mut reader = BorshReader::new(&account.data()[DISCRIMINATOR_LEN..]);
+        let data = decode_type_def(&idl, type_def, &mut reader, 0)?;
+        Ok(Some(IdlDecodedAccount {
+            program_id: program_id.to_string(),
+            account_type,
+            data,
+        }))
+    }
+
+    /// Returns the IDL of the [program_id], preferring the Anchor one.
+    pub fn get_idl(&self, program_id: &Pubkey) -> AccountsApiResult<Value> {
+        let idl_account = [
+            get_pubkey_anchor_idl(program_id),
+            get_pubkey_shank_idl(program_id),
+        ]
+        .into_iter()
+        .flatten()
+        .find_map(|idl_pubkey| {
+            self.internal_account_provider
+                .get_account(&idl_pubkey)
+                .map(|account| (idl_pubkey, account))
+        });
+        match idl_account {
+            Some((idl_pubkey, account)) => {
+                parse_idl_account_data(account.data()).map_err(|err| {
+                    AccountsApiError::InvalidIdlAccount(idl_pubkey, err)
+                })
+            }
+            None => Err(AccountsApiError::IdlNotFound(*program_id)),
+        }
+    }
+}
+
+/// Decompresses and parses the IDL stored in an IDL account.
+pub fn parse_idl_account_data(data: &[u8]) -> Result<Value, String> {
+    if data.len() < IDL_ACCOUNT_HEADER_LEN {
+        return Err("account data is too short".to_string());
+    }
+    let len_bytes = &data[IDL_ACCOUNT_HEADER_LEN - 4..IDL_ACCOUNT_HEADER_LEN];
+    let len = u32::from_le_bytes(len_bytes.try_into().unwrap()) as usize;
+    let compressed = data
+        .get(IDL_ACCOUNT_HEADER_LEN..IDL_ACCOUNT_HEADER_LEN + len)
+        .ok_or_else(|| "compressed IDL exceeds account data".to_string())?;
+
+    let mut json = Vec::new();
+    ZlibDecoder::new(compressed)
+        .read_to_end(&mut json)
+        .map_err(|err| format!("failed to decompress IDL: {}", err))?;
+    serde_json::from_slice(&json)
+        .map_err(|err| format!("failed to parse IDL: {}", err))
+}
+
+/// Compresses the [idl] into the data of an Anchor IDL account, the
+/// inverse of [parse_idl_account_data].
+pub fn create_idl_account_data(idl: &Value, authority: &Pubkey) -> Vec<u8> {
+    // Compressing into memory cannot fail
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(idl.to_string().as_bytes()).unwrap();
+    let compressed = encoder.finish().unwrap();
+
+    let mut data = anchor_account_discriminator("IdlAccount").to_vec();
+    data.extend_from_slice(authority.as_ref());
+    data.extend_from_slice(&(compressed.len() as u32).to_le_bytes());
+    data.extend_from_slice(&compressed);
+    data
+}
+
+/// Reads the JSON IDL at [path] and creates the Anchor IDL account of the
+/// [program_id] containing it, such that it is found where the IDL cloned
+/// with the program would be.
+/// Returns the address of the IDL account along with it.
+pub fn read_idl_account(
+    program_id: &Pubkey,
+    path: &Path,
+) -> AccountsApiResult<(Pubkey, Account)> {
+    let invalid_idl_file = |err: String| {
+        AccountsApiError::InvalidIdlFile(path.display().to_string(), err)
+    };
+    let json =
+        std::fs::read(path).map_err(|err| invalid_idl_file(err.to_string()))?;
+    let idl = serde_json::from_slice::<Value>(&json)
+        .map_err(|err| invalid_idl_file(err.to_string()))?;
+    let idl_pubkey = get_pubkey_anchor_idl(program_id).ok_or_else(|| {
+        invalid_idl_file(format!("no IDL address for program {}", program_id))
+    })?;
+    let data = create_idl_account_data(&idl, &Pubkey::default());
+    Ok((
+        idl_pubkey,
+        Account {
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            owner: *program_id,
+            executable: false,
+            rent_epoch: Epoch::MAX,
+        },
+    ))
+}
+
+/// The discriminator Anchor derives for accounts of IDLs which don't
+/// include it.
+pub fn anchor_account_discriminator(name: &str) -> [u8; DISCRIMINATOR_LEN] {
+    let hash = hashv(&[format!("account:{}", name).as_bytes()]);
+    hash.to_bytes()[..DISCRIMINATOR_LEN].try_into().unwrap()
+}
+
+fn find_account_type<'a>(
+    idl: &'a Value,
+    data: &[u8],
+) -> Option<(String, &'a Value)> {
+    let discriminator = data.get(..DISCRIMINATOR_LEN)?;
+    idl.get("accounts")?.as_array()?.iter().find_map(|account| {
+        let name = account.get("name")?.as_str()?;
+        let matches = match account.get("discriminator") {
+            Some(Value::Array(bytes)) => bytes
+                .iter()
+                .map(|byte| byte.as_u64().map(|byte| byte as u8))
+                .eq(discriminator.iter().map(|byte| Some(*byte))),
+            _ => anchor_account_discriminator(name) == discriminator,
+        };
+        if !matches {
+            return None;
+        }
+        // IDLs since Anchor 0.30 define the account type among the types
+        let type_def = account
+            .get("type")
+            .or_else(|| find_defined_type(idl, name))?;
+        Some((name.to_string(), type_def))
+    })
+}
+
+fn find_defined_type<'a>(idl: &'a Value, name: &str) -> Option<&'a Value> {
+    idl.get("types")?
+        .as_array()?
+        .iter()
+        .find(|ty| ty.get("name").and_then(Value::as_str) == Some(name))?
+        .get("type")
+}
+
+// -----------------
+// Borsh Decoding
+// -----------------
+struct BorshReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> BorshReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    fn read_bytes(&mut self, len: usize) -> AccountsApiResult<&'a [u8]> {
+        if self.data.len() < len {
+            return Err(AccountsApiError::InvalidAccountData(format!(
+                "expected {} more bytes, found {}",
+                len,
+                self.data.len()
+            )));
+        }
+        let (bytes, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(bytes)
+    }
+
+    fn read_array<const N: usize>(&mut self) -> AccountsApiResult<[u8; N]> {
+        Ok(self.read_bytes(N)?.try_into().unwrap())
+    }
+
+    fn read_len(&mut self) -> AccountsApiResult<usize> {
+        Ok(u32::from_le_bytes(self.read_array()?) as usize)
+    }
+
+    /// Ensures that [len] elements could be read, each taking at least
+    /// one byte, such that crafted lengths cannot make us loop for long.
+    fn check_elements_len(&self, len: u64) -> AccountsApiResult<()> {
+        if len > self.data.len() as u64 {
+            return Err(AccountsApiError::InvalidAccountData(format!(
+                "length {} exceeds the remaining {} bytes",
+                len,
+                self.data.len()
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Decodes a type definition, i.e. `{ "kind": "struct", "fields": [..] }`.
+fn decode_type_def(
+    idl: &Value,
+    type_def: &Value,
+    reader: &mut BorshReader,
+    depth: usize,
+) -> AccountsApiResult<Value> {
+    match type_def.get("kind").and_then(Value::as_str) {
+        Some("struct") => {
+            decode_fields(idl, type_def.get("fields"), reader, depth)
+        }
+        Some("enum") => {
+            let variants = type_def
+                .get("variants")
+                .and_then(Value::as_array)
+                .ok_or_else(|| unsupported(type_def))?;
+            let [index] = reader.read_array::<1>()?;
+            let variant = variants.get(index as usize).ok_or_else(|| {
+                AccountsApiError::InvalidAccountData(format!(
+                    "invalid enum variant {}",
+                    index
+                ))
+            })?;
+            let name = variant
+                .get("name")
+                .and_then(Value::as_str)
+                .ok_or_else(|| unsupported(variant))?;
+            match variant.get("fields") {
+                Some(fields) => {
+                    let mut map = Map::new();
+                    map.insert(
+                        name.to_string(),
+                        decode_fields(idl, Some(fields), reader, depth)?,
+                    );
+                    Ok(Value::Object(map))
+                }
+                None => Ok(Value::String(name.to_string())),
+            }
+        }
+        Some("type") => {
+            let alias =
+                type_def.get("alias").ok_or_else(|| unsupported(type_def))?;
+            decode_type(idl, alias, reader, depth + 1)
+        }
+        _ => Err(unsupported(type_def)),
+    }
+}
+
+/// Decodes named fields into an object and tuple fields into an array.
+fn decode_fields(
+    idl: &Value,
+    fields: Option<&Value>,
+    reader: &mut BorshReader,
+    depth: usize,
+) -> AccountsApiResult<Value> {
+    let Some(fields) = fields else {
+        return Ok(Value::Object(Map::new()));
+    };
+    let fields = fields.as_array().ok_or_else(|| unsupported(fields))?;
+    let named = fields
+        .first()
+        .map_or(true, |field| field.get("name").is_some());
+    if named {
+        let mut map = Map::new();
+        for field in fields {
+            let (Some(name), Some(ty)) =
+                (field.get("name").and_then(Value::as_str), field.get("type"))
+            else {
+                return Err(unsupported(field));
+            };
+            map.insert(
+                name.to_string(),
+                decode_type(idl, ty, reader, depth + 1)?,
+            );
+        }
+        Ok(Value::Object(map))
+    } else {
+        fields
+            .iter()
+            .map(|ty| decode_type(idl, ty, reader, depth + 1))
+            .collect::<AccountsApiResult<Vec<_>>>()
+            .map(Value::Array)
+    }
+}
+
+/// Decodes a field type, i.e. `"u64"` or `{ "vec": "pubkey" }`.
+/// Integers which don't fit into a JSON number are returned as strings.
+fn decode_type(
+    idl: &Value,
+    ty: &Value,
+    reader: &mut BorshReader,
+    depth: usize,
+) -> AccountsApiResult<Value> {
+    if depth > MAX_TYPE_DEPTH {
+        return Err(AccountsApiError::InvalidAccountData(format!(
+            "types are nested deeper than {}",
+            MAX_TYPE_DEPTH
+        )));
+    }
+    if let Some(primitive) = ty.as_str() {
+        return decode_primitive(primitive, reader);
+    }
+    let ty = ty.as_object().ok_or_else(|| unsupported(ty))?;
+    if let Some(inner) = ty.get("vec") {
+        let len = reader.read_len()?;
+        reader.check_elements_len(len as u64)?;
+        return (0..len)
+            .map(|_| decode_type(idl, inner, reader, depth + 1))
+            .collect::<AccountsApiResult<Vec<_>>>()
+            .map(Value::Array);
+    }
+    if let Some(inner) = ty.get("option").or_else(|| ty.get("coption")) {
+        // COption uses a 4 byte tag and always includes the value
+        let is_coption = ty.contains_key("coption");
+        let is_some = if is_coption {
+            reader.read_array::<4>()? != [0; 4]
+        } else {
+            reader.read_array::<1>()? != [0]
+        };
+        return match (is_some, is_coption) {
+            (true, _) => decode_type(idl, inner, reader, depth + 1),
+            (false, true) => {
+                decode_type(idl, inner, reader, depth + 1)?;
+                Ok(Value::Null)
+            }
+            (false, false) => Ok(Value::Null),
+        };
+    }
+    if let Some(array) = ty.get("array") {
+        let (Some(inner), Some(len)) =
+            (array.get(0), array.get(1).and_then(Value::as_u64))
+        else {
+            return Err(unsupported(array));
+        };
+        reader.check_elements_len(len)?;
+        return (0..len)
+            .map(|_| decode_type(idl, inner, reader, depth + 1))
+            .collect::<AccountsApiResult<Vec<_>>>()
+            .map(Value::Array);
+    }
+    if let Some(defined) = ty.get("defined") {
+        // Legacy IDLs only include the name, newer ones an object
+        let name = defined
+            .as_str()
+            .or_else(|| defined.get("name").and_then(Value::as_str))
+            .ok_or_else(|| unsupported(defined))?;
+        let type_def =
+            find_defined_type(idl, name).ok_or_else(|| unsupported(defined))?;
+        return decode_type_def(idl, type_def, reader, depth + 1);
+    }
+    Err(unsupported(&Value::Object(ty.clone())))
+}
+
+fn decode_primitive(
+    primitive: &str,
+    reader: &mut BorshReader,
+) -> AccountsApiResult<Value> {
+    let value = match primitive {
+        "bool" => Value::Bool(reader.read_array::<1>()? != [0]),
+        "u8" => u8::from_le_bytes(reader.read_array()?).into(),
+        "i8" => i8::from_le_bytes(reader.read_array()?).into(),
+        "u16" => u16::from_le_bytes(reader.read_array()?).into(),
+        "i16" => i16::from_le_bytes(reader.read_array()?).into(),
+        "u32" => u32::from_le_bytes(reader.read_array()?).into(),
+        "i32" => i32::from_le_bytes(reader.read_array()?).into(),
+        "u64" => u64::from_le_bytes(reader.read_array()?).into(),
+        "i64" => i64::from_le_bytes(reader.read_array()?).into(),
+        "u128" => u128::from_le_bytes(reader.read_array()?).to_string().into(),
+        "i128" => i128::from_le_bytes(reader.read_array()?).to_string().into(),
+        "f32" => f32::from_le_bytes(reader.read_array()?).into(),
+        "f64" => f64::from_le_bytes(reader.read_array()?).into(),
+        "pubkey" | "publicKey" => Pubkey::new_from_array(reader.read_array()?)
+            .to_string()
+            .into(),
+        "string" => {
+            let len = reader.read_len()?;
+            let bytes = reader.read_bytes(len)?;
+            String::from_utf8_lossy(bytes).into_owned().into()
+        }
+        "bytes" => {
+            let len = reader.read_len()?;
+            BASE64_STANDARD.encode(reader.read_bytes(len)?).into()
+        }
+        _ => {
+            return Err(AccountsApiError::UnsupportedIdlType(
+                primitive.to_string(),
+            ))
+        }
+    };
+    Ok(value)
+}
+
+fn unsupported(ty: &Value) -> AccountsApiError {
+    AccountsApiError::UnsupportedIdlType(ty.to_string())
+}
@@ -1,3 +1,5 @@
+use std::collections::HashSet;
+
 use solana_sdk::{account::AccountSharedData, clock::Slot, pubkey::Pubkey};
 
 pub trait InternalAccountProvider: Send + Sync {
@@ -5,4 +7,6 @@ pub trait InternalAccountProvider: Send + Sync {
     fn get_account(&self, pubkey: &Pubkey) -> Option<AccountSharedData>;
     fn get_all_accounts(&self) -> Vec<(Pubkey, AccountSharedData)>;
     fn get_slot(&self) -> Slot;
+    /// Takes the accounts written since this was last called.
+    fn take_dirty_accounts(&self) -> HashSet<Pubkey>;
 }
@@ -1,5 +1,5 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     sync::{Arc, RwLock},
 };
 
@@ -11,11 +11,13 @@ use crate::InternalAccountProvider;
 pub struct InternalAccountProviderStub {
     slot: Slot,
     accounts: Arc<RwLock<HashMap<Pubkey, AccountSharedData>>>,
+    dirty_accounts: Arc<RwLock<HashSet<Pubkey>>>,
 }
 
 impl InternalAccountProviderStub {
     pub fn set(&self, pubkey: Pubkey, account: AccountSharedData) {
         self.accounts.write().unwrap().insert(pubkey, account);
+        self.dirty_accounts.write().unwrap().insert(pubkey);
     }
 }
 
@@ -37,4 +39,7 @@ impl InternalAccountProvider for InternalAccountProviderStub {
     fn get_slot(&self) -> Slot {
         self.slot
     }
+    fn take_dirty_accounts(&self) -> HashSet<Pubkey> {
+        std::mem::take(&mut *self.dirty_accounts.write().unwrap())
+    }
 }
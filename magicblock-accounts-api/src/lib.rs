@@ -1,7 +1,10 @@
 mod bank_account_provider;
+pub mod errors;
+mod idl_account_decoder;
 mod internal_account_provider;
 mod internal_account_provider_stub;
 
 pub use bank_account_provider::*;
+pub use idl_account_decoder::*;
 pub use internal_account_provider::*;
 pub use internal_account_provider_stub::*;
@@ -0,0 +1,300 @@
+use std::io::Write;
+
+use flate2::{write::ZlibEncoder, Compression};
+use magicblock_accounts_api::{
+    anchor_account_discriminator, errors::AccountsApiError,
+    parse_idl_account_data, read_idl_account, IdlAccountDecoder,
+    InternalAccountProviderStub,
+};
+use magicblock_mutator::idl::get_pubkey_anchor_idl;
+use serde_json::{json, Value};
+use solana_sdk::{
+    account::{AccountSharedData, ReadableAccount},
+    pubkey::Pubkey,
+};
+
+fn idl_account_data(idl: &Value) -> Vec<u8> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(idl.to_string().as_bytes()).unwrap();
+    let compressed = encoder.finish().unwrap();
+
+    let mut data = vec![0; 8];
+    data.extend_from_slice(Pubkey::new_unique().as_ref());
+    data.extend_from_slice(&(compressed.len() as u32).to_le_bytes());
+    data.extend_from_slice(&compressed);
+    data
+}
+
+fn set_account(
+    stub: &InternalAccountProviderStub,
+    pubkey: Pubkey,
+    owner: &Pubkey,
+    data: Vec<u8>,
+) {
+    let mut account = AccountSharedData::new(1, data.len(), owner);
+    account.set_data_from_slice(&data);
+    stub.set(pubkey, account);
+}
+
+fn game_data(discriminator: &[u8]) -> Vec<u8> {
+    let player = Pubkey::new_from_array([1; 32]);
+    let mut data = discriminator.to_vec();
+    // score: u64
+    data.extend_from_slice(&42u64.to_le_bytes());
+    // player: pubkey
+    data.extend_from_slice(player.as_ref());
+    // name: string
+    data.extend_from_slice(&4u32.to_le_bytes());
+    data.extend_from_slice(b"mage");
+    // winner: option<pubkey>
+    data.push(0);
+    // moves: vec<u8>
+    data.extend_from_slice(&2u32.to_le_bytes());
+    data.extend_from_slice(&[3, 5]);
+    // state: enum
+    data.push(1);
+    data.extend_from_slice(&7u16.to_le_bytes());
+    data
+}
+
+fn expected_game_data() -> Value {
+    json!({
+        "score": 42,
+        "player": Pubkey::new_from_array([1; 32]).to_string(),
+        "name": "mage",
+        "winner": null,
+        "moves": [3, 5],
+        "state": { "Playing": { "round": 7 } },
+    })
+}
+
+#[test]
+fn test_decode_account_with_anchor_idl() {
+    let program_id = Pubkey::new_unique();
+    let discriminator = [1, 2, 3, 4, 5, 6, 7, 8];
+    let idl = json!({
+        "address": program_id.to_string(),
+        "accounts": [{ "name": "Game", "discriminator": discriminator }],
+        "types": [
+            {
+                "name": "Game",
+                "type": {
+                    "kind": "struct",
+                    "fields": [
+                        { "name": "score", "type": "u64" },
+                        { "name": "player", "type": "pubkey" },
+                        { "name": "name", "type": "string" },
+                        { "name": "winner", "type": { "option": "pubkey" } },
+                        { "name": "moves", "type": { "vec": "u8" } },
+                        {
+                            "name": "state",
+                            "type": { "defined": { "name": "GameState" } }
+                        },
+                    ]
+                }
+            },
+            {
+                "name": "GameState",
+                "type": {
+                    "kind": "enum",
+                    "variants": [
+                        { "name": "Waiting" },
+                        {
+                            "name": "Playing",
+                            "fields": [{ "name": "round", "type": "u16" }]
+                        },
+                    ]
+                }
+            }
+        ]
+    });
+
+    let stub = InternalAccountProviderStub::default();
+    let idl_pubkey = get_pubkey_anchor_idl(&program_id).unwrap();
+    set_account(&stub, idl_pubkey, &program_id, idl_account_data(&idl));
+    let game = Pubkey::new_unique();
+    set_account(&stub, game, &program_id, game_data(&discriminator));
+
+    let decoder = IdlAccountDecoder::new(stub);
+    let decoded = decoder.decode_account(&game).unwrap().unwrap();
+    assert_eq!(decoded.program_id, program_id.to_string());
+    assert_eq!(decoded.account_type, "Game");
+    assert_eq!(decoded.data, expected_game_data());
+
+    assert!(decoder
+        .decode_account(&Pubkey::new_unique())
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn test_decode_account_with_legacy_idl() {
+    let program_id = Pubkey::new_unique();
+    let idl = json!({
+        "accounts": [{
+            "name": "Game",
+            "type": {
+                "kind": "struct",
+                "fields": [
+                    { "name": "score", "type": "u64" },
+                    { "name": "player", "type": "publicKey" },
+                    { "name": "name", "type": "string" },
+                    { "name": "winner", "type": { "option": "publicKey" } },
+                    { "name": "moves", "type": { "vec": "u8" } },
+                    { "name": "state", "type": { "defined": "GameState" } },
+                ]
+            }
+        }],
+        "types": [{
+            "name": "GameState",
+            "type": {
+                "kind": "enum",
+                "variants": [
+                    { "name": "Waiting" },
+                    {
+                        "name": "Playing",
+                        "fields": [{ "name": "round", "type": "u16" }]
+                    },
+                ]
+            }
+        }]
+    });
+
+    let stub = InternalAccountProviderStub::default();
+    let idl_pubkey = get_pubkey_anchor_idl(&program_id).unwrap();
+    set_account(&stub, idl_pubkey, &program_id, idl_account_data(&idl));
+    let game = Pubkey::new_unique();
+    let data = game_data(&anchor_account_discriminator("Game"));
+    set_account(&stub, game, &program_id, data);
+    let unknown = Pubkey::new_unique();
+    set_account(&stub, unknown, &program_id, game_data(&[0; 8]));
+
+    let decoder = IdlAccountDecoder::new(stub);
+    let decoded = decoder.decode_account(&game).unwrap().unwrap();
+    assert_eq!(decoded.account_type, "Game");
+    assert_eq!(decoded.data, expected_game_data());
+
+    assert!(matches!(
+        decoder.decode_account(&unknown),
+        Err(AccountsApiError::UnknownAccountType(pubkey, owner))
+            if pubkey == unknown && owner == program_id
+    ));
+}
+
+#[test]
+fn test_decode_account_without_idl() {
+    let program_id = Pubkey::new_unique();
+    let stub = InternalAccountProviderStub::default();
+    let account = Pubkey::new_unique();
+    set_account(&stub, account, &program_id, vec![0; 16]);
+
+    let decoder = IdlAccountDecoder::new(stub);
+    assert!(matches!(
+        decoder.decode_account(&account),
+        Err(AccountsApiError::IdlNotFound(pubkey)) if pubkey == program_id
+    ));
+}
+
+#[test]
+fn test_decode_account_rejects_length_exceeding_data() {
+    let program_id = Pubkey::new_unique();
+    let discriminator = [1; 8];
+    let idl = json!({
+        "accounts": [{
+            "name": "Moves",
+            "discriminator": discriminator,
+            "type": {
+                "kind": "struct",
+                "fields": [{ "name": "moves", "type": { "vec": "u8" } }]
+            }
+        }],
+    });
+
+    let stub = InternalAccountProviderStub::default();
+    let idl_pubkey = get_pubkey_anchor_idl(&program_id).unwrap();
+    set_account(&stub, idl_pubkey, &program_id, idl_account_data(&idl));
+    let moves = Pubkey::new_unique();
+    let mut data = discriminator.to_vec();
+    data.extend_from_slice(&u32::MAX.to_le_bytes());
+    data.extend_from_slice(&[3, 5]);
+    set_account(&stub, moves, &program_id, data);
+
+    let decoder = IdlAccountDecoder::new(stub);
+    assert!(matches!(
+        decoder.decode_account(&moves),
+        Err(AccountsApiError::InvalidAccountData(_))
+    ));
+}
+
+#[test]
+fn test_decode_account_rejects_deeply_nested_types() {
+    let program_id = Pubkey::new_unique();
+    let discriminator = [2; 8];
+    let idl = json!({
+        "accounts": [{ "name": "Node", "discriminator": discriminator }],
+        "types": [{
+            "name": "Node",
+            "type": {
+                "kind": "struct",
+                "fields": [{
+                    "name": "next",
+                    "type": { "option": { "defined": { "name": "Node" } } }
+                }]
+            }
+        }]
+    });
+
+    let stub = InternalAccountProviderStub::default();
+    let idl_pubkey = get_pubkey_anchor_idl(&program_id).unwrap();
+    set_account(&stub, idl_pubkey, &program_id, idl_account_data(&idl));
+    let node = Pubkey::new_unique();
+    // Each node links to the next one
+    let mut data = discriminator.to_vec();
+    data.extend_from_slice(&[1; 10_000]);
+    set_account(&stub, node, &program_id, data);
+
+    let decoder = IdlAccountDecoder::new(stub);
+    assert!(matches!(
+        decoder.decode_account(&node),
+        Err(AccountsApiError::InvalidAccountData(_))
+    ));
+}
+
+#[test]
+fn test_read_idl_account_from_file() {
+    let program_id = Pubkey::new_unique();
+    let idl = json!({
+        "accounts": [{ "name": "Game", "discriminator": [1; 8] }],
+        "types": [],
+    });
+    let path = std::env::temp_dir().join(format!("{}.json", program_id));
+    std::fs::write(&path, idl.to_string()).unwrap();
+
+    let (idl_pubkey, account) = read_idl_account(&program_id, &path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(idl_pubkey, get_pubkey_anchor_idl(&program_id).unwrap());
+    assert_eq!(account.owner, program_id);
+    assert_eq!(parse_idl_account_data(account.data()).unwrap(), idl);
+
+    // Found by the decoder like the IDL cloned with the program
+    let stub = InternalAccountProviderStub::default();
+    stub.set(idl_pubkey, account.into());
+    let decoder = IdlAccountDecoder::new(stub);
+    assert_eq!(decoder.get_idl(&program_id).unwrap(), idl);
+}
+
+#[test]
+fn test_read_idl_account_from_invalid_file() {
+    let program_id = Pubkey::new_unique();
+    let path = std::env::temp_dir().join(format!("{}.json", program_id));
+    std::fs::write(&path, "not an idl").unwrap();
+
+    let result = read_idl_account(&program_id, &path);
+    std::fs::remove_file(&path).unwrap();
+    assert!(matches!(result, Err(AccountsApiError::InvalidIdlFile(..))));
+    // Missing files are reported the same way
+    assert!(matches!(
+        read_idl_account(&program_id, &path),
+        Err(AccountsApiError::InvalidIdlFile(..))
+    ));
+}
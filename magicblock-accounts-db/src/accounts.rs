@@ -1,6 +1,7 @@
 use std::sync::{Arc, Mutex};
 
 use log::debug;
+use magicblock_core::robust_lock::RobustMutex;
 pub use solana_accounts_db::accounts::TransactionLoadResult;
 use solana_frozen_abi_macro::AbiExample;
 use solana_sdk::{
@@ -178,12 +179,9 @@ impl Accounts {
         program_id: &Pubkey,
         config: &solana_accounts_db::accounts_index::ScanConfig,
     ) -> Vec<TransactionAccount> {
-        self.accounts_db.scan_accounts(
-            |_pubkey, account| {
-                Self::load_while_filtering(account, |account| {
-                    account.owner() == program_id
-                })
-            },
+        self.accounts_db.scan_program_accounts(
+            program_id,
+            |_pubkey, account| !account.is_zero_lamport(),
             config,
         )
     }
@@ -197,23 +195,13 @@ impl Accounts {
     where
         F: Fn(&AccountSharedData) -> bool + Send + Sync,
     {
-        self.accounts_db.scan_accounts(
-            |_pubkey, account| {
-                Self::load_while_filtering(account, |account| {
-                    account.owner() == program_id && filter(account)
-                })
-            },
+        self.accounts_db.scan_program_accounts(
+            program_id,
+            |_pubkey, account| !account.is_zero_lamport() && filter(account),
             config,
         )
     }
 
-    fn load_while_filtering<F: Fn(&AccountSharedData) -> bool>(
-        account: AccountSharedData,
-        filter: F,
-    ) -> bool {
-        !account.is_zero_lamport() && filter(&account)
-    }
-
     pub fn load_lookup_table_addresses(
         &self,
         current_slot: Slot,
@@ -296,7 +284,7 @@ impl Accounts {
         &self,
         tx_account_locks_results: Vec<Result<TransactionAccountLocks>>,
     ) -> Vec<Result<()>> {
-        let account_locks = &mut self.account_locks.lock().unwrap();
+        let account_locks = &mut self.account_locks.lock_robust();
         tx_account_locks_results
             .into_iter()
             .map(|tx_account_locks_result| match tx_account_locks_result {
@@ -356,7 +344,7 @@ impl Accounts {
             .filter(|(_, res)| res.is_ok())
             .map(|(tx, _)| tx.get_account_locks_unchecked())
             .collect();
-        let mut account_locks = self.account_locks.lock().unwrap();
+        let mut account_locks = self.account_locks.lock_robust();
         keys.into_iter().for_each(|keys| {
             self.unlock_account(
                 &mut account_locks,
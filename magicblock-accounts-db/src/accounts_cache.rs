@@ -7,6 +7,7 @@ use std::{
 };
 
 use dashmap::DashMap;
+use magicblock_core::robust_lock::RobustRwLock;
 use solana_metrics::datapoint_info;
 use solana_sdk::{
     account::{AccountSharedData, ReadableAccount},
@@ -48,12 +49,12 @@ impl CachedAccountInner {
     }
 
     pub fn hash(&self) -> AccountHash {
-        let hash = *self.hash.read().expect("hash lock poisoned");
+        let hash = *self.hash.read_robust();
         match hash {
             Some(hash) => hash,
             None => {
                 let hash = hash_account(&self.account, &self.pubkey);
-                *self.hash.write().expect("hash lock poisoned") = Some(hash);
+                *self.hash.write_robust() = Some(hash);
                 hash
             }
         }
@@ -1,3 +1,9 @@
+use std::time::Duration;
+
 /// The maximum number our accounts cache can hold to perform scans synchronously.
 /// If it is exceeded, we handle it in parallel across our threadpool.
 pub(crate) const SCAN_SLOT_PAR_ITER_THRESHOLD: usize = 4000;
+
+/// How long storing a single account may take before we consider it slow
+/// and warn about it.
+pub(crate) const SLOW_ACCOUNT_WRITE_BUDGET: Duration = Duration::from_millis(5);
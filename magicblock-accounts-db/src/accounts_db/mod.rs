@@ -5,8 +5,11 @@ use std::{
         atomic::{AtomicU64, Ordering},
         RwLock,
     },
+    time::Instant,
 };
 
+use log::*;
+use magicblock_core::robust_lock::RobustRwLock;
 use rayon::{prelude::*, ThreadPool};
 use solana_measure::measure::Measure;
 use solana_rayon_threadlimit::get_thread_count;
@@ -25,6 +28,7 @@ use crate::{
     accounts_index::ZeroLamport,
     accounts_update_notifier_interface::AccountsUpdateNotifier,
     errors::{AccountsDbError, AccountsDbResult, MatchAccountOwnerError},
+    owner_index::OwnerIndex,
     persist::AccountsPersister,
     storable_accounts::StorableAccounts,
     verify_accounts_hash_in_background::VerifyAccountsHashInBackground,
@@ -36,7 +40,8 @@ mod loaded_account_accessor;
 pub use loaded_account_accessor::LoadedAccountAccessor;
 
 use self::{
-    consts::SCAN_SLOT_PAR_ITER_THRESHOLD, loaded_account::LoadedAccount,
+    consts::{SCAN_SLOT_PAR_ITER_THRESHOLD, SLOW_ACCOUNT_WRITE_BUDGET},
+    loaded_account::LoadedAccount,
 };
 
 pub type StoredMetaWriteVersion = u64;
@@ -79,6 +84,10 @@ pub struct AccountsDb {
     /// The cache of accounts which is the only storage we use at this point
     pub accounts_cache: AccountsCache,
 
+    /// Index of the accounts owned by each program, kept in sync with the
+    /// [Self::accounts_cache] on every store
+    pub owner_index: OwnerIndex,
+
     /// Stats about account stores
     pub stats: AccountsStats,
 
@@ -132,6 +141,7 @@ impl AccountsDb {
         Self {
             cluster_type,
             accounts_cache: AccountsCache::default(),
+            owner_index: OwnerIndex::default(),
             stats: AccountsStats::default(),
             accounts_update_notifier,
             write_version: AtomicU64::default(),
@@ -173,6 +183,19 @@ impl AccountsDb {
         }
     }
 
+    /// Returns the number of files the persisted accounts are stored in
+    pub fn storage_file_count(&self) -> AccountsDbResult<usize> {
+        match self.persister {
+            Some(ref persister) => persister.storage_file_count(),
+            None => Ok(0),
+        }
+    }
+
+    /// Returns the number of accounts we currently hold
+    pub fn accounts_count(&self) -> usize {
+        self.accounts_cache.len()
+    }
+
     pub fn store_cached<'a, T: ReadableAccount + Sync + ZeroLamport + 'a>(
         &self,
         accounts: impl StorableAccounts<'a, T>,
@@ -309,6 +332,7 @@ impl AccountsDb {
         txn_iter
             .enumerate()
             .map(|(i, txn)| {
+                let start = Instant::now();
                 let account = accounts_and_meta_to_store
                     .account_default_if_zero_lamport(i)
                     .map(|account| account.to_account_shared_data())
@@ -326,9 +350,23 @@ impl AccountsDb {
                     write_version_producer,
                 );
 
-                self.accounts_cache
-                    .store(accounts_and_meta_to_store.pubkey(i), account);
+                let pubkey = accounts_and_meta_to_store.pubkey(i);
+                self.owner_index.update(
+                    pubkey,
+                    (account.lamports() > 0).then(|| account.owner()),
+                );
+                let data_len = account.data().len();
+                self.accounts_cache.store(pubkey, account);
                 // NOTE: not sending hash request to sender_bg_hasher
+
+                let elapsed = start.elapsed();
+                if elapsed > SLOW_ACCOUNT_WRITE_BUDGET {
+                    warn!(
+                        "Storing account {} with {} bytes of data took {:?}, exceeding the budget of {:?}",
+                        pubkey, data_len, elapsed, SLOW_ACCOUNT_WRITE_BUDGET
+                    );
+                    magicblock_metrics::metrics::inc_slow_account_write();
+                }
                 account_info
             })
             .collect()
@@ -472,7 +510,7 @@ impl AccountsDb {
                             }
                         })
                         .for_each(|(pubkey, account)| {
-                            collected.write().unwrap().push((pubkey, account))
+                            collected.write_robust().push((pubkey, account))
                         });
                 });
                 collected.into_inner().unwrap()
@@ -499,6 +537,45 @@ impl AccountsDb {
         }
     }
 
+    /// Like [Self::scan_accounts] but only considers accounts owned by the
+    /// [program_id], found via the [Self::owner_index].
+    pub fn scan_program_accounts(
+        &self,
+        program_id: &Pubkey,
+        scan_func: impl Fn(&Pubkey, &AccountSharedData) -> bool,
+        config: &solana_accounts_db::accounts_index::ScanConfig,
+    ) -> Vec<TransactionAccount> {
+        let mut accounts = self
+            .owner_index
+            .pubkeys(program_id)
+            .into_iter()
+            .filter_map(|pubkey| {
+                let account = self.load(&pubkey)?;
+                // The account may have been reassigned since we read the index
+                (account.owner() == program_id && scan_func(&pubkey, &account))
+                    .then_some((pubkey, account))
+            })
+            .collect::<Vec<_>>();
+        if !config.collect_all_unsorted {
+            accounts.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+        }
+        accounts
+    }
+
+    /// Rebuilds the [Self::owner_index] from all stored accounts and returns
+    /// the number of indexed accounts.
+    pub fn rebuild_owner_index(&self) -> usize {
+        let slot_cache = self.accounts_cache.slot_cache();
+        self.owner_index.rebuild(slot_cache.iter().filter_map(
+            |cached_account| {
+                let account = &cached_account.value().account;
+                (account.lamports() > 0)
+                    .then(|| (*cached_account.pubkey(), *account.owner()))
+            },
+        ));
+        self.owner_index.len()
+    }
+
     // -----------------
     // Geyser
     // -----------------
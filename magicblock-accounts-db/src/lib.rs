@@ -5,6 +5,7 @@ pub mod accounts_cache;
 pub mod accounts_db;
 pub mod accounts_update_notifier_interface;
 pub mod errors;
+pub mod owner_index;
 mod persist;
 pub mod verify_accounts_hash_in_background;
 pub use persist::FLUSH_ACCOUNTS_SLOT_FREQ;
@@ -0,0 +1,94 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::RwLock,
+};
+
+use magicblock_core::robust_lock::RobustRwLock;
+use solana_sdk::pubkey::Pubkey;
+
+// -----------------
+// OwnerIndex
+// -----------------
+#[derive(Debug, Default)]
+struct OwnerIndexInner {
+    /// The accounts owned by each program
+    by_owner: HashMap<Pubkey, HashSet<Pubkey>>,
+    /// The owner each account is currently indexed under, needed to remove it
+    /// from its previous owner when it is reassigned
+    owners: HashMap<Pubkey, Pubkey>,
+}
+
+/// Secondary index of the accounts owned by each program which is updated on
+/// every account write, allowing to find the accounts of a program without
+/// scanning all accounts.
+/// Accounts without lamports are not indexed since they don't exist.
+#[derive(Debug, Default)]
+pub struct OwnerIndex {
+    inner: RwLock<OwnerIndexInner>,
+}
+
+impl OwnerIndex {
+    /// Indexes the [pubkey] under the [owner] or removes it from the index if
+    /// no owner is provided, i.e. when the account was closed.
+    pub fn update(&self, pubkey: &Pubkey, owner: Option<&Pubkey>) {
+        let mut inner = self.inner.write_robust();
+        inner.update(pubkey, owner);
+    }
+
+    /// Replaces the entire index with the provided accounts.
+    pub fn rebuild(&self, accounts: impl Iterator<Item = (Pubkey, Pubkey)>) {
+        let mut rebuilt = OwnerIndexInner::default();
+        for (pubkey, owner) in accounts {
+            rebuilt.update(&pubkey, Some(&owner));
+        }
+        *self.inner.write_robust() = rebuilt;
+    }
+
+    /// Returns the accounts currently owned by the [owner] in no particular
+    /// order.
+    pub fn pubkeys(&self, owner: &Pubkey) -> Vec<Pubkey> {
+        self.inner
+            .read_robust()
+            .by_owner
+            .get(owner)
+            .map(|pubkeys| pubkeys.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// Returns the owner the [pubkey] is indexed under, if any.
+    pub fn owner(&self, pubkey: &Pubkey) -> Option<Pubkey> {
+        self.inner.read_robust().owners.get(pubkey).copied()
+    }
+
+    /// The number of indexed accounts.
+    pub fn len(&self) -> usize {
+        self.inner.read_robust().owners.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl OwnerIndexInner {
+    fn update(&mut self, pubkey: &Pubkey, owner: Option<&Pubkey>) {
+        let previous = match owner {
+            Some(owner) => self.owners.insert(*pubkey, *owner),
+            None => self.owners.remove(pubkey),
+        };
+        if previous.as_ref() == owner {
+            return;
+        }
+        if let Some(previous) = previous {
+            if let Some(pubkeys) = self.by_owner.get_mut(&previous) {
+                pubkeys.remove(pubkey);
+                if pubkeys.is_empty() {
+                    self.by_owner.remove(&previous);
+                }
+            }
+        }
+        if let Some(owner) = owner {
+            self.by_owner.entry(*owner).or_default().insert(*pubkey);
+        }
+    }
+}
@@ -9,6 +9,7 @@ use std::{
 };
 
 use log::*;
+use magicblock_metrics::metrics;
 use rand::{thread_rng, Rng};
 use solana_sdk::{
     account::{AccountSharedData, ReadableAccount},
@@ -141,7 +142,9 @@ impl AccountsPersister {
             );
             self.last_storage_cleanup_slot
                 .store(slot, Ordering::Relaxed);
-            Ok(self.delete_storage_entries_older_than(keep_after)?)
+            Ok(metrics::observe_clean_accounts_time(|| {
+                self.delete_storage_entries_older_than(keep_after)
+            })?)
         } else {
             Ok(0)
         }
@@ -433,4 +436,18 @@ impl AccountsPersister {
             None => Ok(0),
         }
     }
+
+    pub(crate) fn storage_file_count(&self) -> AccountsDbResult<usize> {
+        // NOTE: same assumption as for [Self::storage_size]
+        let Some(path) = self.paths.first().filter(|path| path.exists()) else {
+            return Ok(0);
+        };
+        let mut count = 0;
+        for entry in fs::read_dir(path)? {
+            if entry?.metadata()?.is_file() {
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
 }
@@ -7,15 +7,20 @@ use conjunto_transwise::{
 use magicblock_account_cloner::RemoteAccountClonerClient;
 use magicblock_accounts_api::BankAccountProvider;
 use magicblock_bank::bank::Bank;
+use magicblock_core::{
+    chain_slot_mapping::ChainSlotMapping, chaos::ChaosInjector,
+    circuit_breaker::CircuitBreaker,
+};
 use magicblock_transaction_status::TransactionStatusSender;
 use solana_rpc_client::nonblocking::rpc_client::RpcClient;
-use solana_sdk::{commitment_config::CommitmentConfig, signature::Keypair};
+use solana_sdk::commitment_config::CommitmentConfig;
 
 use crate::{
-    config::AccountsConfig, errors::AccountsResult,
-    remote_account_committer::RemoteAccountCommitter,
+    commit_transaction_sender_from_strategy, config::AccountsConfig,
+    errors::AccountsResult, remote_account_committer::RemoteAccountCommitter,
     remote_scheduled_commits_processor::RemoteScheduledCommitsProcessor,
-    utils::try_rpc_cluster_from_cluster, ExternalAccountsManager,
+    utils::try_rpc_cluster_from_cluster, CommitCostTracker, CommitErrorQueue,
+    ExternalAccountsManager, ScheduledCommitPolicy,
 };
 
 pub type AccountsManager = ExternalAccountsManager<
@@ -32,9 +37,11 @@ impl AccountsManager {
         bank: &Arc<Bank>,
         remote_account_cloner_client: RemoteAccountClonerClient,
         transaction_status_sender: Option<TransactionStatusSender>,
-        validator_keypair: Keypair,
         config: AccountsConfig,
+        circuit_breaker: CircuitBreaker,
+        chaos: ChaosInjector,
     ) -> AccountsResult<Self> {
+        let commit_policy = config.commit_policy();
         let remote_cluster = config.remote_cluster;
         let internal_account_provider = BankAccountProvider::new(bank.clone());
         let rpc_cluster = try_rpc_cluster_from_cluster(&remote_cluster)?;
@@ -42,16 +49,31 @@ impl AccountsManager {
             rpc_cluster.url().to_string(),
             CommitmentConfig::confirmed(),
         );
+        let commit_sender = commit_transaction_sender_from_strategy(
+            &config.commit_send_strategy,
+            rpc_cluster.url(),
+            bank.validator_context().clone(),
+        );
+        let commit_cost_tracker = CommitCostTracker::new(config.commit_budget);
         let account_committer = RemoteAccountCommitter::new(
             rpc_client,
-            validator_keypair,
+            bank.validator_context().clone(),
             config.commit_compute_unit_price,
-        );
+            circuit_breaker.clone(),
+            commit_cost_tracker.clone(),
+        )
+        .with_sender(commit_sender)
+        .with_preflight_simulation(config.simulate_commits)
+        .with_commit_proofs(config.commit_proofs)
+        .with_signature_subscriptions(rpc_cluster.ws_url().to_string())
+        .with_chaos(chaos);
 
         let scheduled_commits_processor = RemoteScheduledCommitsProcessor::new(
             remote_cluster,
             bank.clone(),
             transaction_status_sender.clone(),
+            circuit_breaker,
+            config.max_concurrent_commits,
         );
 
         Ok(Self {
@@ -60,9 +82,40 @@ impl AccountsManager {
             account_committer: Arc::new(account_committer),
             transaction_accounts_extractor: TransactionAccountsExtractorImpl,
             transaction_accounts_validator: TransactionAccountsValidatorImpl,
+            commit_policy,
             lifecycle: config.lifecycle,
             scheduled_commits_processor,
             external_commitable_accounts: Default::default(),
+            dirty_commitable_accounts: Default::default(),
+            ephemeral_only_accounts: Default::default(),
+            commit_cost_tracker,
         })
     }
+
+    /// Replaces the [crate::DefaultScheduledCommitPolicy] which decides which
+    /// accounts of a scheduled commit are committed.
+    pub fn with_scheduled_commit_policy(
+        mut self,
+        policy: Arc<dyn ScheduledCommitPolicy>,
+    ) -> Self {
+        self.scheduled_commits_processor.set_policy(policy);
+        self
+    }
+
+    /// Commits that were not sent since their simulation against the remote
+    /// cluster failed.
+    pub fn commit_error_queue(&self) -> &CommitErrorQueue {
+        self.account_committer.commit_error_queue()
+    }
+
+    /// Timestamps the commits with the slot of the base chain at the time
+    /// they were scheduled.
+    pub fn with_chain_slot_mapping(
+        mut self,
+        chain_slot_mapping: ChainSlotMapping,
+    ) -> Self {
+        self.scheduled_commits_processor
+            .set_chain_slot_mapping(chain_slot_mapping);
+        self
+    }
 }
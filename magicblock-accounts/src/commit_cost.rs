@@ -0,0 +1,214 @@
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use log::*;
+use magicblock_core::robust_lock::RobustMutex;
+use magicblock_metrics::metrics;
+use solana_sdk::{
+    account::{AccountSharedData, ReadableAccount},
+    pubkey::Pubkey,
+};
+
+/// Spend is aggregated into buckets of this size which bounds the memory
+/// needed to track the rolling windows.
+const BUCKET_DURATION: Duration = Duration::from_secs(60);
+
+/// Bounds the memory used to track the spend per account, once reached the
+/// least recently committed account is evicted to make room for a new one.
+const MAX_TRACKED_ACCOUNTS: usize = 10_000;
+
+/// Rolling windows for which the spend is exposed via metrics.
+const METRICS_WINDOWS: [(&str, Duration); 2] = [
+    ("1h", Duration::from_secs(60 * 60)),
+    ("24h", Duration::from_secs(24 * 60 * 60)),
+];
+
+/// Maximum amount of lamports the validator authority should spend on
+/// commits within the rolling [Self::window].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CommitBudget {
+    pub max_lamports: u64,
+    pub window: Duration,
+}
+
+#[derive(Debug, Default)]
+struct CommitSpend {
+    /// Start of each bucket and the lamports spent within it, oldest first.
+    buckets: VecDeque<(Instant, u64)>,
+    budget_exceeded: bool,
+    /// Lamports spent per account along with the sequence at which it was
+    /// last committed.
+    /// These are not exposed as metrics since labeling by pubkey would make
+    /// their cardinality unbounded.
+    accounts: HashMap<Pubkey, (u64, u64)>,
+    /// Maps the sequence at which an account was last committed to its pubkey
+    recency: BTreeMap<u64, Pubkey>,
+    next_seq: u64,
+}
+
+impl CommitSpend {
+    fn add(&mut self, now: Instant, lamports: u64) {
+        match self.buckets.back_mut() {
+            Some((start, spent))
+                if now.duration_since(*start) < BUCKET_DURATION =>
+            {
+                *spent = spent.saturating_add(lamports);
+            }
+            _ => self.buckets.push_back((now, lamports)),
+        }
+    }
+
+    fn add_account(&mut self, pubkey: Pubkey, lamports: u64) {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+
+        if let Some((spent, last_seq)) = self.accounts.get_mut(&pubkey) {
+            self.recency.remove(&*last_seq);
+            *spent = spent.saturating_add(lamports);
+            *last_seq = seq;
+        } else {
+            if self.accounts.len() >= MAX_TRACKED_ACCOUNTS {
+                if let Some((_, least_recent)) = self.recency.pop_first() {
+                    self.accounts.remove(&least_recent);
+                }
+            }
+            self.accounts.insert(pubkey, (lamports, seq));
+        }
+        self.recency.insert(seq, pubkey);
+    }
+
+    fn prune(&mut self, now: Instant, max_window: Duration) {
+        while let Some((start, _)) = self.buckets.front() {
+            if now.duration_since(*start) > max_window + BUCKET_DURATION {
+                self.buckets.pop_front();
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn spent_within(&self, now: Instant, window: Duration) -> u64 {
+        self.buckets
+            .iter()
+            .rev()
+            .take_while(|(start, _)| now.duration_since(*start) <= window)
+            .map(|(_, spent)| *spent)
+            .sum()
+    }
+}
+
+/// Tracks the lamports the validator authority spends on commit transactions
+/// and raises an alarm once the configured [CommitBudget] is exceeded.
+/// Clones share the same spend.
+#[derive(Debug, Clone, Default)]
+pub struct CommitCostTracker {
+    budget: Option<CommitBudget>,
+    spend: Arc<Mutex<CommitSpend>>,
+}
+
+impl CommitCostTracker {
+    pub fn new(budget: Option<CommitBudget>) -> Self {
+        Self {
+            budget,
+            spend: Default::default(),
+        }
+    }
+
+    /// Records the [fee_lamports] of a commit transaction, attributing an
+    /// equal share of it to each of the [committees] and their owners.
+    pub fn record(
+        &self,
+        fee_lamports: u64,
+        committees: &[(Pubkey, AccountSharedData)],
+    ) {
+        metrics::inc_commit_cost(fee_lamports);
+        let now = Instant::now();
+        let mut spend = self.lock_spend();
+        if !committees.is_empty() {
+            let share = fee_lamports / committees.len() as u64;
+            let mut per_program = HashMap::<Pubkey, u64>::new();
+            for (pubkey, account) in committees {
+                spend.add_account(*pubkey, share);
+                *per_program.entry(*account.owner()).or_default() += share;
+            }
+            for (program, lamports) in per_program {
+                metrics::inc_program_commit_cost(
+                    &program.to_string(),
+                    lamports,
+                );
+            }
+        }
+
+        spend.add(now, fee_lamports);
+        self.update_windows(&mut spend, now);
+    }
+
+    /// Returns the [limit] accounts the most lamports were spent on
+    /// committing, most expensive first.
+    pub fn top_account_costs(&self, limit: usize) -> Vec<(Pubkey, u64)> {
+        let mut accounts = self
+            .lock_spend()
+            .accounts
+            .iter()
+            .map(|(pubkey, (spent, _))| (*pubkey, *spent))
+            .collect::<Vec<_>>();
+        accounts.sort_by(|a, b| b.1.cmp(&a.1));
+        accounts.truncate(limit);
+        accounts
+    }
+
+    /// Returns `true` if the spend within the budget window exceeds the
+    /// [CommitBudget], in which case non-critical commits should be paused.
+    pub fn is_over_budget(&self) -> bool {
+        if self.budget.is_none() {
+            return false;
+        }
+        let mut spend = self.lock_spend();
+        self.update_windows(&mut spend, Instant::now());
+        spend.budget_exceeded
+    }
+
+    fn update_windows(&self, spend: &mut CommitSpend, now: Instant) {
+        let max_window = METRICS_WINDOWS
+            .iter()
+            .map(|(_, window)| *window)
+            .chain(self.budget.map(|budget| budget.window))
+            .max()
+            .unwrap_or_default();
+        spend.prune(now, max_window);
+
+        for (label, window) in METRICS_WINDOWS {
+            metrics::set_commit_cost_window(
+                label,
+                spend.spent_within(now, window),
+            );
+        }
+
+        let Some(budget) = self.budget else {
+            return;
+        };
+        let spent = spend.spent_within(now, budget.window);
+        let exceeded = spent > budget.max_lamports;
+        if exceeded != spend.budget_exceeded {
+            if exceeded {
+                warn!(
+                    "Commit budget exceeded, spent {} of {} lamports within {:?}. Pausing non-critical commits.",
+                    spent, budget.max_lamports, budget.window
+                );
+            } else {
+                info!(
+                    "Commit spend is back within budget, resuming non-critical commits"
+                );
+            }
+            metrics::set_commit_budget_exceeded(exceeded);
+            spend.budget_exceeded = exceeded;
+        }
+    }
+
+    fn lock_spend(&self) -> std::sync::MutexGuard<'_, CommitSpend> {
+        self.spend.lock_robust()
+    }
+}
@@ -0,0 +1,64 @@
+use std::{
+    collections::{HashSet, VecDeque},
+    sync::{Arc, Mutex},
+    time::SystemTime,
+};
+
+use magicblock_core::robust_lock::RobustMutex;
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+
+/// The number of failed commits kept, older ones are dropped.
+const MAX_FAILED_COMMITS: usize = 1_000;
+
+/// A commit transaction that was not sent since simulating it against the
+/// remote cluster failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FailedCommit {
+    pub signature: Signature,
+    pub undelegated_accounts: HashSet<Pubkey>,
+    pub committed_only_accounts: HashSet<Pubkey>,
+    /// The transaction error returned by the simulation
+    pub error: String,
+    /// The logs of the simulation which usually explain the error, i.e. a
+    /// changed delegation record
+    pub logs: Vec<String>,
+    pub failed_at: SystemTime,
+}
+
+/// Keeps the most recent commits that failed their simulation so they can be
+/// inspected via the `getFailedCommits` admin RPC instead of paying fees to
+/// land them on chain only to fail.
+///
+/// Clones share the same queue.
+#[derive(Debug, Clone, Default)]
+pub struct CommitErrorQueue {
+    failed_commits: Arc<Mutex<VecDeque<FailedCommit>>>,
+}
+
+impl CommitErrorQueue {
+    pub fn push(&self, failed_commit: FailedCommit) {
+        let mut failed_commits = self.failed_commits.lock_robust();
+        if failed_commits.len() >= MAX_FAILED_COMMITS {
+            failed_commits.pop_front();
+        }
+        failed_commits.push_back(failed_commit);
+    }
+
+    /// Returns the failed commits, oldest first.
+    pub fn failed_commits(&self) -> Vec<FailedCommit> {
+        self.failed_commits.lock_robust().iter().cloned().collect()
+    }
+
+    /// Removes and returns the failed commits, oldest first.
+    pub fn drain(&self) -> Vec<FailedCommit> {
+        self.failed_commits.lock_robust().drain(..).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.failed_commits.lock_robust().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
@@ -0,0 +1,117 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+};
+
+use magicblock_core::robust_lock::RobustMutex;
+use solana_sdk::pubkey::Pubkey;
+use tokio::sync::{watch, OwnedSemaphorePermit, Semaphore};
+
+/// Lets commits of unrelated accounts run concurrently while commits which
+/// touch the same account run one after the other in the order they were
+/// registered. At most `max_concurrent_commits` commits run at once.
+#[derive(Debug, Clone)]
+pub struct CommitOrdering {
+    /// The commit registered last for each account, it finished once its
+    /// [watch::Sender] is dropped
+    last_commits: Arc<Mutex<HashMap<Pubkey, watch::Receiver<()>>>>,
+    permits: Arc<Semaphore>,
+}
+
+impl CommitOrdering {
+    pub fn new(max_concurrent_commits: usize) -> Self {
+        Self {
+            last_commits: Default::default(),
+            permits: Arc::new(Semaphore::new(max_concurrent_commits.max(1))),
+        }
+    }
+
+    /// Registers a commit of the [pubkeys] which runs once the returned turn
+    /// is [CommitTurn::wait]ed for.
+    /// Needs to be called in the order the commits were scheduled since that
+    /// is the order in which commits of the same account run.
+    pub fn register(&self, pubkeys: &[Pubkey]) -> CommitTurn {
+        let (done, done_receiver) = watch::channel(());
+        let mut last_commits = self.last_commits.lock_robust();
+        // Forget commits that finished already
+        last_commits.retain(|_, commit| commit.has_changed().is_ok());
+
+        let mut predecessors = Vec::new();
+        for pubkey in pubkeys.iter().collect::<HashSet<_>>() {
+            if let Some(predecessor) =
+                last_commits.insert(*pubkey, done_receiver.clone())
+            {
+                predecessors.push(predecessor);
+            }
+        }
+        CommitTurn {
+            predecessors,
+            done,
+            permits: self.permits.clone(),
+        }
+    }
+
+    /// The number of accounts with commits that did not finish yet
+    pub fn pending_accounts_len(&self) -> usize {
+        self.last_commits
+            .lock_robust()
+            .values()
+            .filter(|commit| commit.has_changed().is_ok())
+            .count()
+    }
+}
+
+/// The turn of a commit registered via [CommitOrdering::register].
+#[derive(Debug)]
+pub struct CommitTurn {
+    predecessors: Vec<watch::Receiver<()>>,
+    done: watch::Sender<()>,
+    permits: Arc<Semaphore>,
+}
+
+impl CommitTurn {
+    /// Waits until the commits of the same accounts registered before
+    /// finished and the concurrency limit allows the commit to run.
+    /// The commit is considered finished once the returned guard is dropped.
+    pub async fn wait(self) -> CommitTurnGuard {
+        let mut waited = false;
+        for mut predecessor in self.predecessors {
+            // The sender is only dropped once the predecessor finished
+            waited |= predecessor.has_changed().is_ok();
+            // Nothing is ever sent, this only returns once the predecessor
+            // finished and dropped its sender
+            let _ = predecessor.changed().await;
+        }
+        let permit = match self.permits.clone().try_acquire_owned() {
+            Ok(permit) => permit,
+            Err(_) => {
+                waited = true;
+                self.permits
+                    .acquire_owned()
+                    .await
+                    .expect("commit permits semaphore is never closed")
+            }
+        };
+        CommitTurnGuard {
+            _permit: permit,
+            _done: self.done,
+            waited,
+        }
+    }
+}
+
+/// Marks the commit as finished once dropped.
+#[derive(Debug)]
+pub struct CommitTurnGuard {
+    _permit: OwnedSemaphorePermit,
+    _done: watch::Sender<()>,
+    waited: bool,
+}
+
+impl CommitTurnGuard {
+    /// Whether the commit had to wait for other commits before it could
+    /// run, i.e. its transactions may have been signed a while ago.
+    pub fn waited(&self) -> bool {
+        self.waited
+    }
+}
@@ -0,0 +1,98 @@
+use std::str::FromStr;
+
+use solana_sdk::{
+    clock::Slot,
+    hash::{hashv, Hash},
+    instruction::Instruction,
+    pubkey,
+    pubkey::Pubkey,
+};
+
+/// The SPL memo program (v3) which we use to attach commit proofs to the
+/// commit transactions on chain.
+pub const MEMO_PROGRAM_ID: Pubkey =
+    pubkey!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
+
+const COMMIT_PROOF_MEMO_PREFIX: &str = "mb-commit:v1";
+
+/// Claim of the ephemeral validator that the committed data of [Self::pubkey]
+/// is the state of the account it executed up to [Self::slot].
+///
+/// It is included as a memo in the commit transaction, so anyone can verify
+/// that the bytes passed to the delegation program match the claim via
+/// [Self::verify].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CommitProof {
+    pub pubkey: Pubkey,
+    pub slot: Slot,
+    pub state_hash: Hash,
+}
+
+impl CommitProof {
+    pub fn new(pubkey: Pubkey, slot: Slot, data: &[u8]) -> Self {
+        Self {
+            pubkey,
+            slot,
+            state_hash: commit_state_hash(&pubkey, slot, data),
+        }
+    }
+
+    /// Returns `true` if the proof was created for [data] of the account at
+    /// [slot].
+    pub fn verify(&self, slot: Slot, data: &[u8]) -> bool {
+        self.slot == slot
+            && self.state_hash == commit_state_hash(&self.pubkey, slot, data)
+    }
+
+    pub fn to_memo(&self) -> String {
+        format!(
+            "{}:{}:{}:{}",
+            COMMIT_PROOF_MEMO_PREFIX, self.pubkey, self.slot, self.state_hash
+        )
+    }
+
+    /// Parses a memo created via [Self::to_memo], returns `None` if the memo
+    /// isn't a commit proof.
+    pub fn from_memo(memo: &str) -> Option<Self> {
+        let rest = memo
+            .strip_prefix(COMMIT_PROOF_MEMO_PREFIX)?
+            .strip_prefix(':')?;
+        let mut parts = rest.split(':');
+        let pubkey = Pubkey::from_str(parts.next()?).ok()?;
+        let slot = parts.next()?.parse().ok()?;
+        let state_hash = Hash::from_str(parts.next()?).ok()?;
+        if parts.next().is_some() {
+            return None;
+        }
+        Some(Self {
+            pubkey,
+            slot,
+            state_hash,
+        })
+    }
+
+    /// Memo instruction to include in the commit transaction.
+    pub fn to_instruction(&self) -> Instruction {
+        Instruction {
+            program_id: MEMO_PROGRAM_ID,
+            accounts: vec![],
+            data: self.to_memo().into_bytes(),
+        }
+    }
+
+    /// Extracts the commit proof from a memo instruction, returns `None` if
+    /// the instruction isn't a commit proof memo.
+    pub fn from_instruction(ix: &Instruction) -> Option<Self> {
+        if ix.program_id != MEMO_PROGRAM_ID {
+            return None;
+        }
+        Self::from_memo(std::str::from_utf8(&ix.data).ok()?)
+    }
+}
+
+/// Hash binding the committed account data to the account and the ephemeral
+/// slot at which it was committed.
+pub fn commit_state_hash(pubkey: &Pubkey, slot: Slot, data: &[u8]) -> Hash {
+    let data_len = (data.len() as u64).to_le_bytes();
+    hashv(&[pubkey.as_ref(), &slot.to_le_bytes(), &data_len, data])
+}
@@ -0,0 +1,342 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use async_trait::async_trait;
+use base64::{prelude::BASE64_STANDARD, Engine};
+use futures_util::future::join_all;
+use log::*;
+use magicblock_program::ValidatorContext;
+use solana_rpc_client::{
+    nonblocking::rpc_client::RpcClient, rpc_client::SerializableTransaction,
+};
+use solana_rpc_client_api::{
+    config::RpcSendTransactionConfig, request::RpcRequest,
+};
+use solana_sdk::{
+    commitment_config::CommitmentConfig, genesis_config::ClusterType, pubkey,
+    pubkey::Pubkey, signature::Signature, signer::Signer, system_instruction,
+    transaction::Transaction,
+};
+use url::Url;
+
+use crate::{Cluster, CommitSendStrategy};
+
+/// The tip accounts of the Jito mainnet block engines.
+pub const JITO_MAINNET_TIP_ACCOUNTS: [Pubkey; 8] = [
+    pubkey!("96gYZGLnJYVFmbjzopPSU6QiEV5fGqZNyN9nmNhvrZU5"),
+    pubkey!("HFqU5x63VTqvQss8hp11i4wVV8bD44PvwucfZ2bU7gRe"),
+    pubkey!("Cw8CFyM9FkoMi7K7Crf6HNQqf4uEMzpKw6QNghXLvLkY"),
+    pubkey!("ADaUMid9yfUytqMBgopwjb2DTLSokTSzL1zt6iGPaS49"),
+    pubkey!("DfXygSm4jCyNCybVYYK6DwvWqjKee8pbDmJGcLWNDXjh"),
+    pubkey!("ADuUkR4vqLUMWXxW9gh6D6L8pMSawimctcNZ5pGwDcEt"),
+    pubkey!("DttWaMuVvTiduZRnguLF7jNxTgiMBZ1hyAumKUiL2KRL"),
+    pubkey!("3AVi9Tg9Uo68tJfuvoKvqKNWKkC5wPdSSdeBnizKZ6jT"),
+];
+
+/// Returns the tip accounts of the Jito block engines of the [cluster], or
+/// `None` if they aren't known and thus need to be configured.
+pub fn default_jito_tip_accounts(cluster: &Cluster) -> Option<Vec<Pubkey>> {
+    match cluster {
+        Cluster::Known(ClusterType::MainnetBeta) => {
+            Some(JITO_MAINNET_TIP_ACCOUNTS.to_vec())
+        }
+        _ => None,
+    }
+}
+
+/// Submits signed commit transactions to the base chain.
+#[async_trait]
+pub trait CommitTransactionSender: Send + Sync {
+    /// Submits the [transaction] and returns its signature once any
+    /// endpoint accepted it.
+    async fn send_commit_transaction(
+        &self,
+        transaction: &Transaction,
+    ) -> Result<Signature, String>;
+
+    /// Lamports paid for each commit in addition to the transaction fee.
+    fn tip_lamports(&self) -> u64 {
+        0
+    }
+}
+
+/// Creates the sender for the [strategy], using the [rpc_url] of the remote
+/// cluster where the strategy sends to it.
+/// Tips are paid by the authority of the validator [context].
+pub fn commit_transaction_sender_from_strategy(
+    strategy: &CommitSendStrategy,
+    rpc_url: &str,
+    context: Arc<ValidatorContext>,
+) -> Box<dyn CommitTransactionSender> {
+    match strategy {
+        CommitSendStrategy::Rpc => {
+            Box::new(RpcCommitTransactionSender::new(rpc_client(rpc_url)))
+        }
+        CommitSendStrategy::Fanout { urls } => {
+            let rpc_clients = std::iter::once(rpc_url)
+                .chain(urls.iter().map(Url::as_str))
+                .map(rpc_client)
+                .collect();
+            Box::new(FanoutCommitTransactionSender::new(rpc_clients))
+        }
+        CommitSendStrategy::JitoBundle {
+            block_engine_url,
+            tip_lamports,
+            tip_accounts,
+        } => Box::new(JitoBundleCommitTransactionSender::new(
+            rpc_client(block_engine_url.as_str()),
+            context,
+            *tip_lamports,
+            tip_accounts.clone(),
+        )),
+    }
+}
+
+fn rpc_client(url: &str) -> RpcClient {
+    RpcClient::new_with_commitment(
+        url.to_string(),
+        CommitmentConfig::confirmed(),
+    )
+}
+
+async fn send_via_rpc(
+    rpc_client: &RpcClient,
+    transaction: &Transaction,
+) -> Result<Signature, String> {
+    rpc_client
+        .send_transaction_with_config(
+            transaction,
+            RpcSendTransactionConfig {
+                skip_preflight: true,
+                ..Default::default()
+            },
+        )
+        .await
+        .map_err(|err| err.to_string())
+}
+
+// -----------------
+// RpcCommitTransactionSender
+// -----------------
+/// Sends commits to a single RPC endpoint.
+pub struct RpcCommitTransactionSender {
+    rpc_client: RpcClient,
+}
+
+impl RpcCommitTransactionSender {
+    pub fn new(rpc_client: RpcClient) -> Self {
+        Self { rpc_client }
+    }
+}
+
+#[async_trait]
+impl CommitTransactionSender for RpcCommitTransactionSender {
+    async fn send_commit_transaction(
+        &self,
+        transaction: &Transaction,
+    ) -> Result<Signature, String> {
+        send_via_rpc(&self.rpc_client, transaction).await
+    }
+}
+
+// -----------------
+// FanoutCommitTransactionSender
+// -----------------
+/// Sends commits to multiple RPC endpoints at once, which raises the chance
+/// that a leader receives them while the cluster is congested.
+pub struct FanoutCommitTransactionSender {
+    rpc_clients: Vec<RpcClient>,
+}
+
+impl FanoutCommitTransactionSender {
+    pub fn new(rpc_clients: Vec<RpcClient>) -> Self {
+        Self { rpc_clients }
+    }
+}
+
+#[async_trait]
+impl CommitTransactionSender for FanoutCommitTransactionSender {
+    async fn send_commit_transaction(
+        &self,
+        transaction: &Transaction,
+    ) -> Result<Signature, String> {
+        let results = join_all(
+            self.rpc_clients
+                .iter()
+                .map(|rpc_client| send_via_rpc(rpc_client, transaction)),
+        )
+        .await;
+        let mut errors = vec![];
+        for result in results {
+            match result {
+                Ok(signature) => return Ok(signature),
+                Err(err) => errors.push(err),
+            }
+        }
+        Err(errors.join(", "))
+    }
+}
+
+// -----------------
+// JitoBundleCommitTransactionSender
+// -----------------
+/// Submits each commit as a bundle to a Jito block engine.
+/// The bundle includes a transaction tipping one of the tip accounts after
+/// the commit, since bundles execute atomically the tip is only paid if the
+/// commit lands.
+pub struct JitoBundleCommitTransactionSender {
+    block_engine: RpcClient,
+    validator_context: Arc<ValidatorContext>,
+    tip_lamports: u64,
+    tip_accounts: Vec<Pubkey>,
+    next_tip_account: AtomicUsize,
+}
+
+impl JitoBundleCommitTransactionSender {
+    pub fn new(
+        block_engine: RpcClient,
+        validator_context: Arc<ValidatorContext>,
+        tip_lamports: u64,
+        tip_accounts: Vec<Pubkey>,
+    ) -> Self {
+        Self {
+            block_engine,
+            validator_context,
+            tip_lamports,
+            tip_accounts,
+            next_tip_account: AtomicUsize::new(0),
+        }
+    }
+
+    /// Rotates through the tip accounts to avoid write lock contention on
+    /// a single one of them.
+    fn tip_account(&self) -> Pubkey {
+        let index = self.next_tip_account.fetch_add(1, Ordering::Relaxed);
+        self.tip_accounts[index % self.tip_accounts.len()]
+    }
+
+    fn tip_transaction(&self, commit_transaction: &Transaction) -> Transaction {
+        let authority = self.validator_context.validator_authority();
+        Transaction::new_signed_with_payer(
+            &[system_instruction::transfer(
+                &authority.pubkey(),
+                &self.tip_account(),
+                self.tip_lamports,
+            )],
+            Some(&authority.pubkey()),
+            &[&authority],
+            commit_transaction.message.recent_blockhash,
+        )
+    }
+}
+
+#[async_trait]
+impl CommitTransactionSender for JitoBundleCommitTransactionSender {
+    async fn send_commit_transaction(
+        &self,
+        transaction: &Transaction,
+    ) -> Result<Signature, String> {
+        let bundle = [transaction.clone(), self.tip_transaction(transaction)]
+            .iter()
+            .map(|tx| {
+                bincode::serialize(tx)
+                    .map(|bytes| BASE64_STANDARD.encode(bytes))
+                    .map_err(|err| err.to_string())
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let bundle_id = self
+            .block_engine
+            .send::<String>(
+                RpcRequest::Custom {
+                    method: "sendBundle",
+                },
+                serde_json::json!([bundle, { "encoding": "base64" }]),
+            )
+            .await
+            .map_err(|err| err.to_string())?;
+        debug!(
+            "Sent commit {} in Jito bundle {}",
+            transaction.get_signature(),
+            bundle_id
+        );
+        Ok(*transaction.get_signature())
+    }
+
+    fn tip_lamports(&self) -> u64 {
+        self.tip_lamports
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::{
+        hash::Hash, message::Message, signature::Keypair,
+        system_instruction::SystemInstruction, system_program,
+    };
+
+    use super::*;
+
+    /// Returns the tip account and lamports if the [transaction] transfers
+    /// to any of the [tip_accounts].
+    fn tip_transfer(
+        transaction: &Transaction,
+        tip_accounts: &[Pubkey],
+    ) -> Option<(Pubkey, u64)> {
+        let message = &transaction.message;
+        message.instructions.iter().find_map(|ix| {
+            if message.account_keys[ix.program_id_index as usize]
+                != system_program::id()
+            {
+                return None;
+            }
+            let SystemInstruction::Transfer { lamports } =
+                bincode::deserialize(&ix.data).ok()?
+            else {
+                return None;
+            };
+            let recipient = message.account_keys[*ix.accounts.get(1)? as usize];
+            tip_accounts
+                .contains(&recipient)
+                .then_some((recipient, lamports))
+        })
+    }
+
+    #[test]
+    fn test_tip_transaction_transfers_to_rotating_tip_accounts() {
+        let authority = Keypair::new();
+        let authority_pubkey = authority.pubkey();
+        let tip_accounts = vec![Pubkey::new_unique(), Pubkey::new_unique()];
+        let sender = JitoBundleCommitTransactionSender::new(
+            rpc_client("http://localhost:8899"),
+            Arc::new(ValidatorContext::new(authority)),
+            10_000,
+            tip_accounts.clone(),
+        );
+        let commit_transaction =
+            Transaction::new_unsigned(Message::new_with_blockhash(
+                &[],
+                Some(&authority_pubkey),
+                &Hash::new_unique(),
+            ));
+        assert_eq!(tip_transfer(&commit_transaction, &tip_accounts), None);
+
+        for tip_account in [tip_accounts[0], tip_accounts[1], tip_accounts[0]] {
+            let tip_transaction = sender.tip_transaction(&commit_transaction);
+            assert!(tip_transaction.verify().is_ok());
+            assert_eq!(
+                tip_transaction.message.account_keys[0],
+                authority_pubkey
+            );
+            assert_eq!(
+                tip_transaction.message.recent_blockhash,
+                commit_transaction.message.recent_blockhash
+            );
+            assert_eq!(
+                tip_transfer(&tip_transaction, &tip_accounts),
+                Some((tip_account, 10_000))
+            );
+        }
+    }
+}
@@ -1,8 +1,13 @@
-use std::collections::HashSet;
+use std::{collections::HashSet, path::PathBuf, time::Duration};
 
-use magicblock_account_cloner::AccountClonerPermissions;
+use magicblock_account_cloner::{
+    AccountClonerPermissions, ClonePolicy, RefreshPolicy,
+};
 use magicblock_mutator::Cluster;
 use solana_sdk::pubkey::Pubkey;
+use url::Url;
+
+use crate::CommitBudget;
 
 #[derive(Debug, PartialEq, Eq)]
 pub struct AccountsConfig {
@@ -11,6 +16,114 @@ pub struct AccountsConfig {
     pub commit_compute_unit_price: u64,
     pub payer_init_lamports: Option<u64>,
     pub allowed_program_ids: Option<HashSet<Pubkey>>,
+    pub commit_budget: Option<CommitBudget>,
+    pub commit_send_strategy: CommitSendStrategy,
+    /// Simulates commits against the remote cluster before sending them
+    pub simulate_commits: bool,
+    /// Attaches a memo proving the committed state to commit transactions
+    pub commit_proofs: bool,
+    /// How many commits run at once, the ones of the same account always
+    /// run in the order they were scheduled
+    pub max_concurrent_commits: usize,
+    /// Polls for changes of cloned accounts instead of monitoring them via
+    /// websocket subscriptions if set
+    pub refresh_poll_interval: Option<Duration>,
+    /// Overrides the policies derived from the [LifecycleMode] where set
+    pub policy_overrides: PolicyOverrides,
+    pub mint_authority_overrides: HashSet<Pubkey>,
+    /// Local IDL files used instead of the IDL on chain by program id
+    pub idl_overrides: Vec<(Pubkey, PathBuf)>,
+    pub mainnet_rent_semantics: bool,
+    /// Appends every account dump to this file if set
+    pub dump_audit_log: Option<PathBuf>,
+    pub dump_metrics: bool,
+}
+
+impl AccountsConfig {
+    pub fn clone_policy(&self) -> ClonePolicy {
+        self.policy_overrides
+            .clone
+            .unwrap_or_else(|| self.lifecycle.to_clone_policy())
+    }
+
+    pub fn refresh_policy(&self) -> RefreshPolicy {
+        if let Some(refresh) = self.policy_overrides.refresh {
+            return refresh;
+        }
+        let refresh = self.lifecycle.to_refresh_policy();
+        // Polling only replaces subscriptions if the lifecycle refreshes clones
+        match self.refresh_poll_interval {
+            Some(interval) if !refresh.is_off() => {
+                RefreshPolicy::Poll { interval }
+            }
+            _ => refresh,
+        }
+    }
+
+    pub fn commit_policy(&self) -> CommitPolicy {
+        self.policy_overrides
+            .commit
+            .unwrap_or_else(|| self.lifecycle.to_commit_policy())
+    }
+
+    pub fn to_account_cloner_permissions(&self) -> AccountClonerPermissions {
+        AccountClonerPermissions {
+            clone: self.clone_policy(),
+            refresh: self.refresh_policy(),
+        }
+    }
+}
+
+/// Replaces individual policies the [LifecycleMode] implies, i.e. to clone
+/// everything but never refresh it for deterministic replays.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PolicyOverrides {
+    pub clone: Option<ClonePolicy>,
+    pub refresh: Option<RefreshPolicy>,
+    pub commit: Option<CommitPolicy>,
+}
+
+/// Whether state changes of delegated accounts are committed to the
+/// remote cluster.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CommitPolicy {
+    /// Commits delegated accounts periodically and on demand
+    pub commit_delegated_accounts: bool,
+    /// Sends the commits scheduled by programs, they are dropped otherwise
+    pub process_scheduled_commits: bool,
+}
+
+impl CommitPolicy {
+    pub fn all() -> Self {
+        Self {
+            commit_delegated_accounts: true,
+            process_scheduled_commits: true,
+        }
+    }
+
+    pub fn none() -> Self {
+        Self {
+            commit_delegated_accounts: false,
+            process_scheduled_commits: false,
+        }
+    }
+}
+
+/// How commit transactions are submitted to the remote cluster.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum CommitSendStrategy {
+    /// Sends to the RPC of the remote cluster
+    #[default]
+    Rpc,
+    /// Sends to the RPC of the remote cluster and the [urls] at once
+    Fanout { urls: Vec<Url> },
+    /// Submits commits as Jito bundles including a tip transaction
+    JitoBundle {
+        block_engine_url: Url,
+        tip_lamports: u64,
+        /// See [crate::default_jito_tip_accounts] for those of known clusters
+        tip_accounts: Vec<Pubkey>,
+    },
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -23,35 +136,34 @@ pub enum LifecycleMode {
 
 impl LifecycleMode {
     pub fn to_account_cloner_permissions(&self) -> AccountClonerPermissions {
+        AccountClonerPermissions {
+            clone: self.to_clone_policy(),
+            refresh: self.to_refresh_policy(),
+        }
+    }
+
+    pub fn to_clone_policy(&self) -> ClonePolicy {
         match self {
-            LifecycleMode::Replica => AccountClonerPermissions {
-                allow_cloning_refresh: false,
-                allow_cloning_feepayer_accounts: true,
-                allow_cloning_undelegated_accounts: true,
-                allow_cloning_delegated_accounts: true,
-                allow_cloning_program_accounts: true,
-            },
-            LifecycleMode::ProgramsReplica => AccountClonerPermissions {
-                allow_cloning_refresh: false,
-                allow_cloning_feepayer_accounts: false,
-                allow_cloning_undelegated_accounts: false,
-                allow_cloning_delegated_accounts: false,
-                allow_cloning_program_accounts: true,
-            },
-            LifecycleMode::Ephemeral => AccountClonerPermissions {
-                allow_cloning_refresh: true,
-                allow_cloning_feepayer_accounts: true,
-                allow_cloning_undelegated_accounts: true,
-                allow_cloning_delegated_accounts: true,
-                allow_cloning_program_accounts: true,
-            },
-            LifecycleMode::Offline => AccountClonerPermissions {
-                allow_cloning_refresh: false,
-                allow_cloning_feepayer_accounts: false,
-                allow_cloning_undelegated_accounts: false,
-                allow_cloning_delegated_accounts: false,
-                allow_cloning_program_accounts: false,
-            },
+            LifecycleMode::Replica => ClonePolicy::all(),
+            LifecycleMode::ProgramsReplica => ClonePolicy::programs_only(),
+            LifecycleMode::Ephemeral => ClonePolicy::all(),
+            LifecycleMode::Offline => ClonePolicy::none(),
+        }
+    }
+
+    pub fn to_refresh_policy(&self) -> RefreshPolicy {
+        match self {
+            LifecycleMode::Replica => RefreshPolicy::Off,
+            LifecycleMode::ProgramsReplica => RefreshPolicy::Off,
+            LifecycleMode::Ephemeral => RefreshPolicy::Websocket,
+            LifecycleMode::Offline => RefreshPolicy::Off,
+        }
+    }
+
+    pub fn to_commit_policy(&self) -> CommitPolicy {
+        match self {
+            LifecycleMode::Offline => CommitPolicy::none(),
+            _ => CommitPolicy::all(),
         }
     }
 
@@ -63,4 +175,10 @@ impl LifecycleMode {
             LifecycleMode::Offline => false,
         }
     }
+
+    /// Offline validators never talk to the remote cluster, i.e. they
+    /// neither clone nor commit accounts.
+    pub fn is_offline(&self) -> bool {
+        matches!(self, LifecycleMode::Offline)
+    }
 }
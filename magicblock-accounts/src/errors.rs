@@ -3,6 +3,7 @@ use std::collections::HashSet;
 use magicblock_account_cloner::{
     AccountClonerError, AccountClonerUnclonableReason,
 };
+use magicblock_core::error_code::{HasErrorCode, MagicErrorCode};
 use solana_sdk::pubkey::Pubkey;
 use thiserror::Error;
 
@@ -40,9 +41,50 @@ pub enum AccountsError {
     #[error("FailedToGetLatestBlockhash '{0}'")]
     FailedToGetLatestBlockhash(String),
 
+    #[error("CommitTransactionTooLarge: {0} bytes committing {1:?}")]
+    CommitTransactionTooLarge(u64, Vec<Pubkey>),
+
     #[error("FailedToSendCommitTransaction '{0}'")]
     FailedToSendCommitTransaction(String, HashSet<Pubkey>, HashSet<Pubkey>),
 
     #[error("Too many committees: {0}")]
     TooManyCommittees(usize),
+
+    #[error("RemoteDisabled: {0} requires the remote cluster, but the validator runs offline")]
+    RemoteDisabled(String),
+}
+
+impl HasErrorCode for AccountsError {
+    fn error_code(&self) -> MagicErrorCode {
+        use AccountsError::*;
+        match self {
+            TranswiseError(_) => MagicErrorCode::CloneValidationFailed,
+            UrlParseError(_)
+            | InvalidRpcUrl(_)
+            | FailedToUpdateUrlScheme
+            | FailedToUpdateUrlPort => MagicErrorCode::RemoteInvalidUrl,
+            TransactionError(_) => MagicErrorCode::CommitTransactionFailed,
+            AccountClonerError(err) => err.error_code(),
+            UnclonableAccountUsedAsWritableInEphemeral(_, reason) => {
+                reason.error_code()
+            }
+            FailedToGetLatestBlockhash(_) => {
+                MagicErrorCode::CommitBlockhashUnavailable
+            }
+            CommitTransactionTooLarge(_, _) => {
+                MagicErrorCode::CommitTransactionTooLarge
+            }
+            // The sender only reports the error message of the remote RPC
+            FailedToSendCommitTransaction(err, _, _)
+                if err.contains("BlockhashNotFound") =>
+            {
+                MagicErrorCode::CommitBlockhashExpired
+            }
+            FailedToSendCommitTransaction(_, _, _) => {
+                MagicErrorCode::CommitSendFailed
+            }
+            TooManyCommittees(_) => MagicErrorCode::CommitTooManyCommittees,
+            RemoteDisabled(_) => MagicErrorCode::RemoteDisabled,
+        }
+    }
 }
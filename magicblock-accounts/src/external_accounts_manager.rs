@@ -1,5 +1,5 @@
 use std::{
-    collections::{hash_map::Entry, HashMap},
+    collections::{hash_map::Entry, HashMap, HashSet},
     sync::{Arc, RwLock},
     time::Duration,
     vec,
@@ -16,7 +16,10 @@ use futures_util::future::{try_join, try_join_all};
 use log::*;
 use magicblock_account_cloner::{AccountCloner, AccountClonerOutput};
 use magicblock_accounts_api::InternalAccountProvider;
-use magicblock_core::magic_program;
+use magicblock_core::{magic_program, robust_lock::RobustRwLock};
+use magicblock_program::{
+    fee_payer_escrow_pda, MagicContext, DEFAULT_FEE_PAYER_ESCROW_INDEX,
+};
 use solana_sdk::{
     account::{AccountSharedData, ReadableAccount},
     hash::Hash,
@@ -29,8 +32,8 @@ use crate::{
     errors::{AccountsError, AccountsResult},
     traits::{AccountCommitter, UndelegationRequest},
     utils::get_epoch,
-    AccountCommittee, CommitAccountsPayload, LifecycleMode,
-    PendingCommitTransaction, ScheduledCommitsProcessor,
+    AccountCommittee, CommitAccountsPayload, CommitCostTracker, CommitPolicy,
+    LifecycleMode, PendingCommitTransaction, ScheduledCommitsProcessor,
     SendableCommitAccountsPayload,
 };
 
@@ -92,8 +95,16 @@ where
     pub transaction_accounts_validator: TAV,
     pub scheduled_commits_processor: SCP,
     pub lifecycle: LifecycleMode,
+    pub commit_policy: CommitPolicy,
     pub external_commitable_accounts:
         RwLock<HashMap<Pubkey, ExternalCommitableAccount>>,
+    /// Commitable accounts that were written since they were last committed,
+    /// only these are considered when committing delegated accounts
+    pub dirty_commitable_accounts: RwLock<HashSet<Pubkey>>,
+    /// Ephemeral-only accounts as found in the MagicContext, reloaded only
+    /// once the MagicContext was written, `None` until it is first loaded
+    pub ephemeral_only_accounts: RwLock<Option<HashSet<Pubkey>>>,
+    pub commit_cost_tracker: CommitCostTracker,
 }
 
 impl<IAP, ACL, ACM, TAE, TAV, SCP>
@@ -110,6 +121,24 @@ where
         &self,
         tx: &SanitizedTransaction,
     ) -> AccountsResult<Vec<Signature>> {
+        // While offline, programs that don't exist locally cannot be cloned,
+        // thus the transaction could never succeed.
+        // Other accounts that don't exist may be created by the transaction.
+        if self.lifecycle.is_offline() {
+            if let Some(program_id) = tx
+                .message()
+                .program_instructions_iter()
+                .map(|(program_id, _)| program_id)
+                .find(|program_id| {
+                    !self.internal_account_provider.has_account(program_id)
+                })
+            {
+                return Err(AccountsError::RemoteDisabled(format!(
+                    "Cloning program '{}'",
+                    program_id
+                )));
+            }
+        }
         // Extract all acounts from the transaction
         let accounts_holder = self
             .transaction_accounts_extractor
@@ -123,6 +152,34 @@ where
         .await
     }
 
+    /// Clones only the fee payer of a transaction together with its default
+    /// escrow, which allows inspecting the escrow before cloning any of the
+    /// other accounts it uses.
+    pub async fn ensure_fee_payer(
+        &self,
+        payer: Pubkey,
+        signature: String,
+    ) -> AccountsResult<Vec<Signature>> {
+        self.ensure_accounts_from_holder(
+            TransactionAccountsHolder {
+                readonly: vec![fee_payer_escrow_pda(
+                    &payer,
+                    DEFAULT_FEE_PAYER_ESCROW_INDEX,
+                )],
+                writable: vec![payer],
+                payer,
+            },
+            signature,
+        )
+        .await
+    }
+
+    /// Keeps the state of the accounts that was provided locally, i.e.
+    /// imported from another ephemeral, instead of cloning them again.
+    pub fn register_local_overrides(&self, pubkeys: &[Pubkey]) {
+        self.account_cloner.register_local_overrides(pubkeys);
+    }
+
     // Direct use for tests only
     pub async fn ensure_accounts_from_holder(
         &self,
@@ -166,7 +223,8 @@ where
             .iter()
             .chain(writable_clone_outputs.iter())
             .filter_map(|clone_output| match clone_output {
-                AccountClonerOutput::Cloned { signature, .. } => {
+                AccountClonerOutput::Cloned { signature, .. }
+                | AccountClonerOutput::ClonedAsClosed { signature, .. } => {
                     Some(*signature)
                 }
                 AccountClonerOutput::Unclonable { .. } => None,
@@ -182,6 +240,10 @@ where
                     AccountClonerOutput::Cloned {
                         account_chain_snapshot,
                         ..
+                    }
+                    | AccountClonerOutput::ClonedAsClosed {
+                        account_chain_snapshot,
+                        ..
                     } => Some(account_chain_snapshot),
                     AccountClonerOutput::Unclonable { .. } => None,
                 })
@@ -189,7 +251,8 @@ where
             // Ephemeral will only work if all writable accounts involved in a transaction are properly cloned
             let writable_snapshots = writable_clone_outputs.into_iter()
                 .map(|clone_output| match clone_output {
-                    AccountClonerOutput::Cloned{account_chain_snapshot, ..} => Ok(account_chain_snapshot),
+                    AccountClonerOutput::Cloned{account_chain_snapshot, ..}
+                    | AccountClonerOutput::ClonedAsClosed{account_chain_snapshot, ..} => Ok(account_chain_snapshot),
                     AccountClonerOutput::Unclonable{ pubkey, reason, ..} => {
                         Err(AccountsError::UnclonableAccountUsedAsWritableInEphemeral(pubkey, reason))
                     }
@@ -224,17 +287,19 @@ where
                 delegation_record, ..
             } = &account_chain_snapshot.chain_state
             {
-                match self.external_commitable_accounts
-                    .write()
-                    .expect(
-                    "RwLock of ExternalAccountsManager.external_commitable_accounts is poisoned",
-                    )
+                match self
+                    .external_commitable_accounts
+                    .write_robust()
                     .entry(account_chain_snapshot.pubkey)
                 {
-                    Entry::Occupied(mut _entry) => {},
+                    Entry::Occupied(mut _entry) => {}
                     Entry::Vacant(entry) => {
-                        entry.insert(ExternalCommitableAccount::new(&account_chain_snapshot.pubkey, &delegation_record.commit_frequency, &get_epoch()));
-                    },
+                        entry.insert(ExternalCommitableAccount::new(
+                            &account_chain_snapshot.pubkey,
+                            &delegation_record.commit_frequency,
+                            &get_epoch(),
+                        ));
+                    }
                 }
             }
         };
@@ -244,21 +309,56 @@ where
     /// which accounts are due to be committed, perform that step for them
     /// and return the signatures of the transactions that were sent to the cluster.
     pub async fn commit_delegated(&self) -> AccountsResult<Vec<Signature>> {
+        if !self.commit_policy.commit_delegated_accounts {
+            return Ok(vec![]);
+        }
         let now = get_epoch();
-        // Find all accounts that are due to be committed let accounts_to_be_committed = self
-        let accounts_to_be_committed = self
-            .external_commitable_accounts
-            .read()
-            .expect(
-                "RwLock of ExternalAccountsManager.external_commitable_accounts is poisoned",
-            )
-            .values()
-            .filter_map(|x| x.needs_commit(&now).then_some((x.pubkey, x.last_commit_hash)))
-            .collect::<Vec<_>>();
+        // Find all accounts that changed and are due to be committed
+        let accounts_to_be_committed = self.take_dirty_accounts_due(&now);
+        if accounts_to_be_committed.is_empty() {
+            return Ok(vec![]);
+        }
+
+        // Frequent commits are not critical, i.e. the accounts are committed
+        // once they are undelegated, thus we skip them while over budget
+        if self.commit_cost_tracker.is_over_budget() {
+            debug!(
+                "Commit budget exceeded, skipping commit of {} delegated accounts",
+                accounts_to_be_committed.len()
+            );
+            self.mark_dirty(
+                accounts_to_be_committed.iter().map(|(pubkey, _)| *pubkey),
+            );
+            return Ok(vec![]);
+        }
+
+        self.commit_accounts(now, accounts_to_be_committed).await
+    }
+
+    /// Commits all delegated accounts that were written since their last
+    /// commit regardless of their commit frequency and the commit budget.
+    /// This is meant for operators that need the chain to reflect the
+    /// current state, i.e. before maintenance.
+    pub async fn commit_all_delegated(&self) -> AccountsResult<Vec<Signature>> {
+        self.ensure_remote_enabled("Committing accounts")?;
+        if !self.commit_policy.commit_delegated_accounts {
+            return Err(AccountsError::RemoteDisabled(
+                "Committing accounts".to_string(),
+            ));
+        }
+        let now = get_epoch();
+        let accounts_to_be_committed = self.take_dirty_accounts(|_| true);
         if accounts_to_be_committed.is_empty() {
             return Ok(vec![]);
         }
+        self.commit_accounts(now, accounts_to_be_committed).await
+    }
 
+    async fn commit_accounts(
+        &self,
+        now: Duration,
+        accounts_to_be_committed: Vec<(Pubkey, Option<Hash>)>,
+    ) -> AccountsResult<Vec<Signature>> {
         // NOTE: the scheduled commits use the slot at which the commit was scheduled
         // However frequent commits run async and could be running before a slot is completed
         // Thus they really commit in between two slots instead of at the end of a particular slot.
@@ -266,30 +366,114 @@ where
         // slot. However since we most likely will phase out frequent commits we accept this
         // inconsistency for now.
         let slot = self.internal_account_provider.get_slot();
-        let commit_infos = self
-            .create_transactions_to_commit_specific_accounts(
-                accounts_to_be_committed,
-                slot,
-                None,
-            )
-            .await?;
-        let sendables = commit_infos
-            .into_iter()
-            .flat_map(|x| match x.transaction {
-                Some(tx) => Some(SendableCommitAccountsPayload {
-                    transaction: tx,
-                    committees: x.committees,
-                }),
-                None => None,
-            })
+        let pubkeys = accounts_to_be_committed
+            .iter()
+            .map(|(pubkey, _)| *pubkey)
             .collect::<Vec<_>>();
-        // NOTE: we ignore the [PendingCommitTransaction::undelegated_accounts] here since for
-        // scheduled commits we never request undelegation
-        self.run_transactions_to_commit_specific_accounts(now, sendables)
-            .await
+        let result = async {
+            let commit_infos = self
+                .create_transactions_to_commit_specific_accounts(
+                    accounts_to_be_committed,
+                    slot,
+                    None,
+                )
+                .await?;
+            let sendables = commit_infos
+                .into_iter()
+                .flat_map(|x| match x.transaction {
+                    Some(tx) => Some(SendableCommitAccountsPayload {
+                        transaction: tx,
+                        committees: x.committees,
+                    }),
+                    None => None,
+                })
+                .collect::<Vec<_>>();
+            // NOTE: we ignore the [PendingCommitTransaction::undelegated_accounts] here since for
+            // scheduled commits we never request undelegation
+            self.run_transactions_to_commit_specific_accounts(now, sendables)
+                .await
+        }
+        .await;
+        // Accounts we failed to commit need to be committed on the next tick
+        if result.is_err() {
+            self.mark_dirty(pubkeys);
+        }
+        result
             .map(|pendings| pendings.into_iter().map(|x| x.signature).collect())
     }
 
+    /// Takes the commitable accounts that were written since their last
+    /// commit and are due to be committed, which keeps the cost of each
+    /// commit tick proportional to the changed accounts instead of all
+    /// delegated accounts.
+    fn take_dirty_accounts_due(
+        &self,
+        now: &Duration,
+    ) -> Vec<(Pubkey, Option<Hash>)> {
+        self.take_dirty_accounts(|acc| acc.needs_commit(now))
+    }
+
+    fn take_dirty_accounts(
+        &self,
+        is_due: impl Fn(&ExternalCommitableAccount) -> bool,
+    ) -> Vec<(Pubkey, Option<Hash>)> {
+        let written_accounts =
+            self.internal_account_provider.take_dirty_accounts();
+        // Ephemeral-only accounts are never committed
+        let ephemeral_only_accounts = self.ephemeral_only_accounts(
+            written_accounts.contains(&magic_program::MAGIC_CONTEXT_PUBKEY),
+        );
+        let commitable_accounts =
+            self.external_commitable_accounts.read_robust();
+        let mut dirty_accounts = self.dirty_commitable_accounts.write_robust();
+        dirty_accounts.extend(
+            written_accounts
+                .into_iter()
+                .filter(|pubkey| commitable_accounts.contains_key(pubkey))
+                .filter(|pubkey| !ephemeral_only_accounts.contains(pubkey)),
+        );
+
+        let mut due = vec![];
+        dirty_accounts.retain(|pubkey| match commitable_accounts.get(pubkey) {
+            Some(acc) if is_due(acc) => {
+                due.push((acc.pubkey, acc.last_commit_hash));
+                false
+            }
+            Some(_) => true,
+            // No longer delegated to us
+            None => false,
+        });
+        due
+    }
+
+    /// Returns the ephemeral-only accounts, only deserializing the
+    /// MagicContext if it was [written] since they were last loaded.
+    fn ephemeral_only_accounts(&self, written: bool) -> HashSet<Pubkey> {
+        let mut ephemeral_only_accounts =
+            self.ephemeral_only_accounts.write_robust();
+        match ephemeral_only_accounts.as_ref() {
+            Some(accounts) if !written => accounts.clone(),
+            _ => {
+                let accounts = self
+                    .internal_account_provider
+                    .get_account(&magic_program::MAGIC_CONTEXT_PUBKEY)
+                    .map(|acc| MagicContext::find_ephemeral_only_accounts(&acc))
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|x| x.account)
+                    .collect::<HashSet<_>>();
+                *ephemeral_only_accounts = Some(accounts.clone());
+                accounts
+            }
+        }
+    }
+
+    fn mark_dirty(&self, pubkeys: impl IntoIterator<Item = Pubkey>) {
+        self.dirty_commitable_accounts
+            .write_robust()
+            .extend(pubkeys);
+    }
+
     async fn create_transactions_to_commit_specific_accounts(
         &self,
         accounts_to_be_committed: Vec<(Pubkey, Option<Hash>)>,
@@ -357,15 +541,11 @@ where
         for (pubkey, hash) in pubkeys_with_hashes {
             if let Some(acc) = self
                 .external_commitable_accounts
-                .write()
-                .expect(
-                "RwLock of ExternalAccountsManager.external_commitable_accounts is poisoned",
-                )
+                .write_robust()
                 .get_mut(&pubkey)
             {
                 acc.mark_as_committed(&now, &hash);
-            }
-            else {
+            } else {
                 // This should never happen
                 error!(
                     "Account '{}' disappeared while being committed",
@@ -379,20 +559,51 @@ where
 
     pub fn last_commit(&self, pubkey: &Pubkey) -> Option<Duration> {
         self.external_commitable_accounts
-            .read()
-            .expect(
-            "RwLock of ExternalAccountsManager.external_commitable_accounts is poisoned",
-            )
+            .read_robust()
             .get(pubkey)
             .map(|x| x.last_committed_at())
     }
 
+    /// Processes the commits scheduled by programs.
+    /// While offline programs cannot schedule commits, however commits
+    /// replayed from the ledger are still accepted. Those as well as the ones
+    /// scheduled while the [CommitPolicy] disallows processing them are
+    /// dropped since they are never sent, which is reported as error to make
+    /// this visible.
     pub async fn process_scheduled_commits(&self) -> AccountsResult<()> {
+        if self.lifecycle.is_offline()
+            || !self.commit_policy.process_scheduled_commits
+        {
+            let scheduled_commits = self.scheduled_commits_len();
+            if scheduled_commits == 0 {
+                return Ok(());
+            }
+            self.clear_scheduled_commits();
+            return Err(AccountsError::RemoteDisabled(format!(
+                "Committing {} scheduled commits",
+                scheduled_commits
+            )));
+        }
+        let commitable_accounts = &self.external_commitable_accounts;
+        let is_delegated = |pubkey: &Pubkey| {
+            commitable_accounts.read_robust().contains_key(pubkey)
+        };
         self.scheduled_commits_processor
-            .process(&self.account_committer, &self.internal_account_provider)
+            .process(
+                &self.account_committer,
+                &self.internal_account_provider,
+                &is_delegated,
+            )
             .await
     }
 
+    fn ensure_remote_enabled(&self, operation: &str) -> AccountsResult<()> {
+        if self.lifecycle.is_offline() {
+            return Err(AccountsError::RemoteDisabled(operation.to_string()));
+        }
+        Ok(())
+    }
+
     pub fn scheduled_commits_len(&self) -> usize {
         self.scheduled_commits_processor.scheduled_commits_len()
     }
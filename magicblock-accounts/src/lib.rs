@@ -1,15 +1,28 @@
 mod accounts_manager;
+mod commit_cost;
+mod commit_error_queue;
+mod commit_ordering;
+mod commit_proof;
+mod commit_sender;
 mod config;
 pub mod errors;
 mod external_accounts_manager;
 mod remote_account_committer;
 mod remote_scheduled_commits_processor;
+mod scheduled_commit_policy;
 mod traits;
 pub mod utils;
 
 pub use accounts_manager::AccountsManager;
+pub use commit_cost::{CommitBudget, CommitCostTracker};
+pub use commit_error_queue::*;
+pub use commit_ordering::*;
+pub use commit_proof::*;
+pub use commit_sender::*;
 pub use config::*;
 pub use external_accounts_manager::ExternalAccountsManager;
 pub use magicblock_mutator::Cluster;
+pub use remote_account_committer::RemoteAccountCommitter;
+pub use scheduled_commit_policy::*;
 pub use traits::*;
 pub use utils::*;
@@ -1,26 +1,38 @@
-use std::collections::HashSet;
+use std::{
+    collections::HashSet,
+    sync::Arc,
+    time::{Duration, Instant, SystemTime},
+};
 
 use async_trait::async_trait;
 use dlp::instruction::{commit_state, finalize, undelegate, CommitAccountArgs};
-use futures_util::future::join_all;
+use futures_util::{future::join_all, Stream, StreamExt};
 use log::*;
+use magicblock_core::{chaos::ChaosInjector, circuit_breaker::CircuitBreaker};
 use magicblock_metrics::metrics;
-use magicblock_program::{validator, Pubkey};
+use magicblock_program::{Pubkey, ValidatorContext};
+use solana_pubsub_client::nonblocking::pubsub_client::PubsubClient;
 use solana_rpc_client::{
     nonblocking::rpc_client::RpcClient, rpc_client::SerializableTransaction,
 };
-use solana_rpc_client_api::config::RpcSendTransactionConfig;
+use solana_rpc_client_api::{
+    config::{RpcSignatureSubscribeConfig, RpcSimulateTransactionConfig},
+    response::{Response, RpcSignatureResult},
+};
 use solana_sdk::{
     account::ReadableAccount, clock::MAX_HASH_AGE_IN_SECONDS,
     commitment_config::CommitmentConfig,
-    compute_budget::ComputeBudgetInstruction, instruction::Instruction,
-    signature::Keypair, signer::Signer, transaction::Transaction,
+    compute_budget::ComputeBudgetInstruction, hash::Hash,
+    instruction::Instruction, packet::PACKET_DATA_SIZE, signature::Signature,
+    signer::Signer, transaction::Transaction,
 };
 
 use crate::{
     errors::{AccountsError, AccountsResult},
     AccountCommittee, AccountCommitter, CommitAccountsPayload,
-    CommitAccountsTransaction, PendingCommitTransaction,
+    CommitAccountsTransaction, CommitCostTracker, CommitErrorQueue,
+    CommitProof, CommitTransactionSender, FailedCommit,
+    PendingCommitTransaction, RpcCommitTransactionSender,
     SendableCommitAccountsPayload, UndelegationRequest,
 };
 
@@ -30,25 +42,194 @@ use crate::{
 const MAX_TRANSACTION_CONFIRMATION_SECS: u64 =
     MAX_HASH_AGE_IN_SECONDS as u64 / 4;
 
+// How often the status of a commit transaction is polled while confirming it
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+// Subscriptions only miss transactions that were confirmed before we
+// subscribed, thus we poll them a lot less often
+const SUBSCRIBED_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+const CONFIRMED_VIA_SUBSCRIPTION: &str = "subscription";
+const CONFIRMED_VIA_POLLING: &str = "polling";
+
+// The base fee charged for each signature of a transaction
+const LAMPORTS_PER_SIGNATURE: u64 = 5_000;
+const MICRO_LAMPORTS_PER_LAMPORT: u64 = 1_000_000;
+
 // -----------------
 // RemoteAccountCommitter
 // -----------------
+/// Commits accounts signing with the current validator authority which may
+/// change when the validator identity is rotated.
 pub struct RemoteAccountCommitter {
     rpc_client: RpcClient,
-    committer_authority: Keypair,
+    validator_context: Arc<ValidatorContext>,
     compute_unit_price: u64,
+    endpoint: String,
+    circuit_breaker: CircuitBreaker,
+    commit_cost_tracker: CommitCostTracker,
+    sender: Box<dyn CommitTransactionSender>,
+    /// Simulates commits against the remote cluster before sending them
+    simulate_commits: bool,
+    /// Attaches a [CommitProof] memo for each committed account
+    commit_proofs: bool,
+    commit_error_queue: CommitErrorQueue,
+    chaos: ChaosInjector,
+    /// Websocket of the remote cluster to confirm commits via signature
+    /// subscriptions instead of polling their status
+    ws_url: Option<String>,
 }
 
 impl RemoteAccountCommitter {
     pub fn new(
         rpc_client: RpcClient,
-        committer_authority: Keypair,
+        validator_context: Arc<ValidatorContext>,
         compute_unit_price: u64,
+        circuit_breaker: CircuitBreaker,
+        commit_cost_tracker: CommitCostTracker,
     ) -> Self {
+        let endpoint = metrics::remote_endpoint(&rpc_client.url()).to_string();
+        let sender = Box::new(RpcCommitTransactionSender::new(
+            RpcClient::new_with_commitment(
+                rpc_client.url(),
+                rpc_client.commitment(),
+            ),
+        ));
         Self {
             rpc_client,
-            committer_authority,
+            validator_context,
             compute_unit_price,
+            endpoint,
+            circuit_breaker,
+            commit_cost_tracker,
+            sender,
+            simulate_commits: false,
+            commit_proofs: false,
+            commit_error_queue: CommitErrorQueue::default(),
+            chaos: ChaosInjector::disabled(),
+            ws_url: None,
+        }
+    }
+
+    /// Replaces the [RpcCommitTransactionSender] sending commits to the RPC
+    /// of the remote cluster.
+    pub fn with_sender(
+        mut self,
+        sender: Box<dyn CommitTransactionSender>,
+    ) -> Self {
+        self.sender = sender;
+        self
+    }
+
+    /// Simulates each commit against the remote cluster before sending it.
+    /// Commits whose simulation fails are not sent but moved to the
+    /// [Self::commit_error_queue] instead.
+    pub fn with_preflight_simulation(mut self, simulate_commits: bool) -> Self {
+        self.simulate_commits = simulate_commits;
+        self
+    }
+
+    /// Attaches a [CommitProof] memo for each committed account to the
+    /// commit transactions. The memos are omitted from transactions that
+    /// would exceed the packet size with them.
+    pub fn with_commit_proofs(mut self, commit_proofs: bool) -> Self {
+        self.commit_proofs = commit_proofs;
+        self
+    }
+
+    pub fn commit_error_queue(&self) -> &CommitErrorQueue {
+        &self.commit_error_queue
+    }
+
+    /// Confirms commits via subscriptions to their signatures on the
+    /// websocket of the remote cluster at [ws_url].
+    /// Their status is only polled frequently if subscribing fails.
+    pub fn with_signature_subscriptions(mut self, ws_url: String) -> Self {
+        self.ws_url = Some(ws_url);
+        self
+    }
+
+    /// Delays and fails commit sends as configured in the [ChaosInjector].
+    pub fn with_chaos(mut self, chaos: ChaosInjector) -> Self {
+        self.chaos = chaos;
+        self
+    }
+
+    /// Records the outcome of a request to the remote cluster in the metrics
+    /// and the [CircuitBreaker] shared with the other remote clients.
+    fn observe_remote_request(
+        &self,
+        request: &str,
+        success: bool,
+        start: Instant,
+    ) {
+        metrics::observe_remote_rpc_request(
+            &self.endpoint,
+            request,
+            metrics::Outcome::from_success(success),
+            start.elapsed(),
+        );
+        if success {
+            self.circuit_breaker.record_success();
+            metrics::set_remote_circuit_open(&self.endpoint, false);
+        } else if self.circuit_breaker.record_failure() {
+            warn!(
+                "Pausing requests to remote cluster '{}' after repeated failures",
+                self.endpoint
+            );
+            metrics::set_remote_circuit_open(&self.endpoint, true);
+        }
+    }
+
+    /// Simulates the commit [transaction] against the remote cluster and
+    /// returns the error and logs if it would fail.
+    /// If the simulation itself fails we cannot tell, thus the commit is
+    /// considered to succeed.
+    async fn latest_blockhash(&self) -> AccountsResult<Hash> {
+        let start = Instant::now();
+        let latest_blockhash = self.rpc_client.get_latest_blockhash().await;
+        self.observe_remote_request(
+            "get_latest_blockhash",
+            latest_blockhash.is_ok(),
+            start,
+        );
+        latest_blockhash.map_err(|err| {
+            AccountsError::FailedToGetLatestBlockhash(err.to_string())
+        })
+    }
+
+    async fn simulate_commit(
+        &self,
+        transaction: &Transaction,
+    ) -> Result<(), (String, Vec<String>)> {
+        let start = Instant::now();
+        let result = self
+            .rpc_client
+            .simulate_transaction_with_config(
+                transaction,
+                RpcSimulateTransactionConfig {
+                    sig_verify: false,
+                    commitment: Some(CommitmentConfig::confirmed()),
+                    ..Default::default()
+                },
+            )
+            .await;
+        self.observe_remote_request("simulate_commit", result.is_ok(), start);
+        match result {
+            Ok(response) => match response.value.err {
+                Some(err) => Err((
+                    err.to_string(),
+                    response.value.logs.unwrap_or_default(),
+                )),
+                None => Ok(()),
+            },
+            Err(err) => {
+                warn!(
+                    "Failed to simulate commit {}: {:?}",
+                    transaction.get_signature(),
+                    err
+                );
+                Ok(())
+            }
         }
     }
 }
@@ -60,13 +241,7 @@ impl AccountCommitter for RemoteAccountCommitter {
         committees: Vec<AccountCommittee>,
     ) -> AccountsResult<CommitAccountsPayload> {
         // Get blockhash once since this is a slow operation
-        let latest_blockhash = self
-            .rpc_client
-            .get_latest_blockhash()
-            .await
-            .map_err(|err| {
-                AccountsError::FailedToGetLatestBlockhash(err.to_string())
-            })?;
+        let latest_blockhash = self.latest_blockhash().await?;
 
         let committee_count: u32 = committees
             .len()
@@ -78,12 +253,15 @@ impl AccountCommitter for RemoteAccountCommitter {
             .count()
             .try_into()
             .map_err(|_| AccountsError::TooManyCommittees(committees.len()))?;
-        let (compute_budget_ix, compute_unit_price_ix) =
-            self.compute_instructions(committee_count, undelegation_count);
+        // Resolve the authority once so all instructions and the signature
+        // use the same one even if it is rotated in the meantime
+        let committer_authority = self.validator_context.validator_authority();
+        let committer = committer_authority.pubkey();
 
         let mut undelegated_accounts = HashSet::new();
         let mut committed_only_accounts = HashSet::new();
-        let mut ixs = vec![compute_budget_ix, compute_unit_price_ix];
+        let mut ixs = vec![];
+        let mut ixs_with_proofs = vec![];
 
         for AccountCommittee {
             pubkey,
@@ -92,39 +270,102 @@ impl AccountCommitter for RemoteAccountCommitter {
             undelegation_request,
         } in committees.iter()
         {
-            let committer = self.committer_authority.pubkey();
+            // The transient state of ephemeral-only accounts is discarded
+            // instead of being committed
+            let refund = undelegation_request
+                .as_ref()
+                .and_then(|request| request.refund);
+            let data = match refund {
+                Some(_) => &[][..],
+                None => account_data.data(),
+            };
+            // Accounts resized inside the ephemeral need no extra
+            // instructions since finalizing the commit resizes the account
+            // on the base layer to the size of the committed data
             let commit_args = CommitAccountArgs {
                 slot: *slot,
                 allow_undelegation: undelegation_request.is_some(),
-                data: account_data.data().to_vec(),
+                data: data.to_vec(),
             };
             let commit_ix = commit_state(committer, *pubkey, commit_args);
 
             let finalize_ix = finalize(committer, *pubkey, committer);
-            ixs.extend(vec![commit_ix, finalize_ix]);
-            if let Some(UndelegationRequest { owner }) = undelegation_request {
-                let undelegate_ix = undelegate(
-                    validator::validator_authority_id(),
-                    *pubkey,
-                    *owner,
-                    validator::validator_authority_id(),
-                );
-                ixs.push(undelegate_ix);
+            let undelegate_ix = undelegation_request.as_ref().map(
+                |UndelegationRequest { owner, .. }| {
+                    // The rent of the delegation record was paid by us, thus
+                    // it is always reimbursed to us
+                    undelegate(committer, *pubkey, *owner, committer)
+                },
+            );
+            if undelegate_ix.is_some() {
                 undelegated_accounts.insert(*pubkey);
             } else {
                 committed_only_accounts.insert(*pubkey);
             }
+
+            if self.commit_proofs {
+                // Allows anyone to verify the committed data against the
+                // slot at which we claim to have executed it
+                let proof_ix =
+                    CommitProof::new(*pubkey, *slot, data).to_instruction();
+                ixs_with_proofs.extend(
+                    [commit_ix.clone(), proof_ix, finalize_ix.clone()]
+                        .into_iter()
+                        .chain(undelegate_ix.clone()),
+                );
+            }
+            ixs.extend(
+                [commit_ix, finalize_ix].into_iter().chain(undelegate_ix),
+            );
         }
 
+        let compute_budget =
+            compute_budget(committee_count, undelegation_count);
+        let (compute_budget_ix, compute_unit_price_ix) =
+            self.compute_instructions(compute_budget);
+        let sign = |ixs: &[Instruction]| {
+            let ixs =
+                [compute_budget_ix.clone(), compute_unit_price_ix.clone()]
+                    .into_iter()
+                    .chain(ixs.iter().cloned())
+                    .collect::<Vec<_>>();
+            Transaction::new_signed_with_payer(
+                &ixs,
+                Some(&committer),
+                &[&committer_authority],
+                latest_blockhash,
+            )
+        };
+        let tx_size =
+            |tx: &Transaction| bincode::serialized_size(tx).unwrap_or(u64::MAX);
+
         // For now we always commit all accounts in one transaction, but
         // in the future we may split them up into batches to avoid running
         // over the max instruction args size
-        let tx = Transaction::new_signed_with_payer(
-            &ixs,
-            Some(&self.committer_authority.pubkey()),
-            &[&self.committer_authority],
-            latest_blockhash,
-        );
+        // The proofs are only a convenience for verifiers, thus we rather
+        // omit them than failing the commit
+        let tx = match self.commit_proofs.then(|| sign(&ixs_with_proofs)) {
+            Some(tx) if tx_size(&tx) <= PACKET_DATA_SIZE as u64 => tx,
+            Some(_) => {
+                debug!(
+                    "Omitting commit proofs since the commit of {} accounts would exceed the packet size with them",
+                    committee_count
+                );
+                sign(&ixs)
+            }
+            None => sign(&ixs),
+        };
+        // The data is committed inline, thus a commit whose transaction
+        // exceeds the packet size could never land
+        let tx_size = tx_size(&tx);
+        if tx_size > PACKET_DATA_SIZE as u64 {
+            return Err(AccountsError::CommitTransactionTooLarge(
+                tx_size,
+                committees.iter().map(|c| c.pubkey).collect(),
+            ));
+        }
+        let fee_lamports =
+            self.fee_lamports(compute_budget, tx.signatures.len() as u64);
         let committees = committees
             .into_iter()
             .map(|c| (c.pubkey, c.account_data))
@@ -135,11 +376,43 @@ impl AccountCommitter for RemoteAccountCommitter {
                 transaction: tx,
                 undelegated_accounts,
                 committed_only_accounts,
+                fee_lamports,
             }),
             committees,
         })
     }
 
+    async fn resign_commit_transactions(
+        &self,
+        payloads: &mut [SendableCommitAccountsPayload],
+    ) -> AccountsResult<()> {
+        if payloads.is_empty() {
+            return Ok(());
+        }
+        let latest_blockhash = self.latest_blockhash().await?;
+        // Identity rotations complete pending commits first, thus the
+        // authority still matches the payer the transactions were built for
+        let committer_authority = self.validator_context.validator_authority();
+        for payload in payloads.iter_mut() {
+            let CommitAccountsTransaction {
+                transaction,
+                undelegated_accounts,
+                committed_only_accounts,
+                ..
+            } = &mut payload.transaction;
+            transaction
+                .try_sign(&[&committer_authority], latest_blockhash)
+                .map_err(|err| {
+                    AccountsError::FailedToSendCommitTransaction(
+                        format!("Failed to sign commit: {}", err),
+                        undelegated_accounts.clone(),
+                        committed_only_accounts.clone(),
+                    )
+                })?;
+        }
+        Ok(())
+    }
+
     async fn send_commit_transactions(
         &self,
         payloads: Vec<SendableCommitAccountsPayload>,
@@ -151,6 +424,7 @@ impl AccountCommitter for RemoteAccountCommitter {
                     transaction,
                     undelegated_accounts,
                     committed_only_accounts,
+                    fee_lamports,
                 },
             committees,
         } in payloads
@@ -191,24 +465,61 @@ impl AccountCommitter for RemoteAccountCommitter {
                 );
             }
 
+            if self.simulate_commits {
+                if let Err((error, logs)) =
+                    self.simulate_commit(&transaction).await
+                {
+                    warn!(
+                        signature:% = tx_sig;
+                        "Not sending commit '{}' since its simulation failed: {}",
+                        tx_sig,
+                        error
+                    );
+                    metrics::inc_commit_simulation_failure();
+                    update_account_commit_metrics(
+                        &undelegated_accounts,
+                        &committed_only_accounts,
+                        metrics::Outcome::Error,
+                        None,
+                    );
+                    self.commit_error_queue.push(FailedCommit {
+                        signature: *tx_sig,
+                        undelegated_accounts,
+                        committed_only_accounts,
+                        error,
+                        logs,
+                        failed_at: SystemTime::now(),
+                    });
+                    continue;
+                }
+            }
+
             let timer = metrics::account_commit_start();
-            let signature = self
-                .rpc_client
-                .send_transaction_with_config(
-                    &transaction,
-                    RpcSendTransactionConfig {
-                        skip_preflight: true,
-                        ..Default::default()
-                    },
+            let start = Instant::now();
+            if let Some(delay) = self.chaos.rpc_delay() {
+                tokio::time::sleep(delay).await;
+            }
+            let signature = if self.chaos.inject_commit_send_failure() {
+                Err("commit send failed due to chaos injection".to_string())
+            } else {
+                self.sender.send_commit_transaction(&transaction).await
+            };
+            self.observe_remote_request(
+                "send_commit",
+                signature.is_ok(),
+                start,
+            );
+            let signature = signature.map_err(|err| {
+                AccountsError::FailedToSendCommitTransaction(
+                    err,
+                    undelegated_accounts.clone(),
+                    committed_only_accounts.clone(),
                 )
-                .await
-                .map_err(|err| {
-                    AccountsError::FailedToSendCommitTransaction(
-                        err.to_string(),
-                        undelegated_accounts.clone(),
-                        committed_only_accounts.clone(),
-                    )
-                })?;
+            })?;
+
+            // The fee is charged once the transaction lands, even if it fails
+            self.commit_cost_tracker
+                .record(fee_lamports + self.sender.tip_lamports(), &committees);
 
             if &signature != tx_sig {
                 error!(
@@ -217,6 +528,7 @@ impl AccountCommitter for RemoteAccountCommitter {
                 );
             }
             debug!(
+                signature:% = signature;
                 "Sent commit for [{}] | signature: '{:?}'",
                 pubkeys_display.unwrap_or_default(),
                 signature
@@ -234,85 +546,270 @@ impl AccountCommitter for RemoteAccountCommitter {
     async fn confirm_pending_commits(
         &self,
         pending_commits: Vec<PendingCommitTransaction>,
-    ) {
-        let mut futures = Vec::new();
-        for pc in pending_commits.into_iter() {
-            let fut = async move {
-                let now = std::time::Instant::now();
-                loop {
-                    match self
-                        .rpc_client
-                        .confirm_transaction_with_commitment(
-                            &pc.signature,
-                            CommitmentConfig::confirmed(),
-                        )
-                        .await
-                    {
-                        Ok(res) => {
-                            // The RPC `confirm_transaction_with_commitment` doesn't provide
-                            // the info to distinguish between a not yet confirmed or
-                            // failed transaction.
-                            // Failed transactions should be rare, so it's ok to check
-                            // them over and over until the timeout is reached.
-                            // If we see that happen a lot we can write our custom confirm method
-                            // that makes this more straightforward.
-                            let confirmed_and_succeeded = res.value;
-                            if confirmed_and_succeeded {
-                                update_account_commit_metrics(
-                                    &pc.undelegated_accounts,
-                                    &pc.committed_only_accounts,
-                                    metrics::Outcome::from_success(res.value),
-                                    Some(pc.timer),
-                                );
-                                break;
-                            } else if now.elapsed().as_secs()
-                                > MAX_TRANSACTION_CONFIRMATION_SECS
-                            {
-                                error!(
-                                    "Timed out confirming commit-transaction success '{:?}': {:?}. This means that the transaction failed or failed to confirm in time.",
-                                    pc.signature, res
-                                );
-                                update_account_commit_metrics(
-                                    &pc.undelegated_accounts,
-                                    &pc.committed_only_accounts,
-                                    metrics::Outcome::Error,
-                                    None,
-                                );
-                                break;
-                            } else {
-                                tokio::time::sleep(
-                                    std::time::Duration::from_millis(50),
-                                )
-                                .await;
-                            }
-                        }
-                        Err(err) => {
-                            error!(
-                                "Failed to confirm commit transaction '{:?}': {:?}",
-                                pc.signature, err
-                            );
-                            update_account_commit_metrics(
-                                &pc.undelegated_accounts,
-                                &pc.committed_only_accounts,
-                                metrics::Outcome::Error,
-                                None,
-                            );
-                            break;
-                        }
+    ) -> Vec<(Signature, bool)> {
+        let pubsub_client = match &self.ws_url {
+            Some(ws_url) if !pending_commits.is_empty() => {
+                self.connect_pubsub(ws_url).await
+            }
+            _ => None,
+        };
+        let futures = pending_commits.into_iter().map(|pc| {
+            let pubsub_client = pubsub_client.as_ref();
+            async move {
+                let now = Instant::now();
+                let signature = pc.signature;
+                let (method, confirmation) =
+                    self.confirm_commit(pubsub_client, &signature).await;
+                let confirmed =
+                    matches!(confirmation, CommitConfirmation::Succeeded);
+                metrics::observe_commit_confirmation(
+                    method,
+                    metrics::Outcome::from_success(confirmed),
+                    now.elapsed(),
+                );
+                match confirmation {
+                    CommitConfirmation::Succeeded => {
+                        update_account_commit_metrics(
+                            &pc.undelegated_accounts,
+                            &pc.committed_only_accounts,
+                            metrics::Outcome::Success,
+                            Some(pc.timer),
+                        );
+                    }
+                    CommitConfirmation::Failed(err) => {
+                        error!(
+                            signature:% = signature;
+                            "Commit transaction '{:?}' failed: {}",
+                            signature, err
+                        );
+                    }
+                    CommitConfirmation::TimedOut => {
+                        error!(
+                            signature:% = signature;
+                            "Timed out confirming commit-transaction success '{:?}'. This means that the transaction failed or failed to confirm in time.",
+                            signature
+                        );
                     }
+                    CommitConfirmation::Error(err) => {
+                        error!(
+                            signature:% = signature;
+                            "Failed to confirm commit transaction '{:?}': {}",
+                            signature, err
+                        );
+                    }
+                }
+                if !confirmed {
+                    update_account_commit_metrics(
+                        &pc.undelegated_accounts,
+                        &pc.committed_only_accounts,
+                        metrics::Outcome::Error,
+                        None,
+                    );
                 }
 
                 if log_enabled!(log::Level::Trace) {
                     trace!(
-                        "Confirmed commit for {:?} in {:?}",
-                        pc.signature,
+                        "Confirmed commit for {:?} via {} in {:?}",
+                        signature,
+                        method,
                         now.elapsed()
                     );
                 }
-            };
-            futures.push(fut);
+                (signature, confirmed)
+            }
+        });
+        let confirmed = join_all(futures).await;
+
+        if let Some(pubsub_client) = pubsub_client {
+            if let Err(err) = pubsub_client.shutdown().await {
+                debug!("Failed to close commit confirmation pubsub: {:?}", err);
+            }
+        }
+        confirmed
+    }
+}
+
+/// The outcome of confirming a commit transaction.
+enum CommitConfirmation {
+    /// The transaction landed and succeeded
+    Succeeded,
+    /// The transaction landed, but failed
+    Failed(String),
+    /// The transaction was not confirmed in time, thus it either failed or
+    /// did not land
+    TimedOut,
+    /// The requests to confirm the transaction failed
+    Error(String),
+}
+
+impl RemoteAccountCommitter {
+    async fn connect_pubsub(&self, ws_url: &str) -> Option<PubsubClient> {
+        let start = Instant::now();
+        let pubsub_client = PubsubClient::new(ws_url).await;
+        self.observe_remote_request(
+            "connect_pubsub",
+            pubsub_client.is_ok(),
+            start,
+        );
+        pubsub_client
+            .inspect_err(|err| {
+                warn!(
+                    "Failed to connect to '{}' to confirm commits, polling their status instead: {:?}",
+                    ws_url, err
+                );
+            })
+            .ok()
+    }
+
+    /// Confirms the commit transaction via a subscription to its signature
+    /// if the [pubsub_client] is provided, otherwise or if the subscription
+    /// fails by polling its status.
+    /// Returns the method that confirmed it together with the outcome.
+    async fn confirm_commit(
+        &self,
+        pubsub_client: Option<&PubsubClient>,
+        signature: &Signature,
+    ) -> (&'static str, CommitConfirmation) {
+        let started = Instant::now();
+        if let Some(pubsub_client) = pubsub_client {
+            match self
+                .confirm_commit_via_subscription(
+                    pubsub_client,
+                    signature,
+                    started,
+                )
+                .await
+            {
+                Ok(confirmation) => {
+                    return (CONFIRMED_VIA_SUBSCRIPTION, confirmation)
+                }
+                Err(err) => warn!(
+                    signature:% = signature;
+                    "Failed to confirm commit '{}' via subscription, polling its status instead: {}",
+                    signature,
+                    err
+                ),
+            }
+        }
+        let confirmation = self
+            .poll_commit_confirmation(signature, started, POLL_INTERVAL)
+            .await;
+        (CONFIRMED_VIA_POLLING, confirmation)
+    }
+
+    async fn confirm_commit_via_subscription(
+        &self,
+        pubsub_client: &PubsubClient,
+        signature: &Signature,
+        started: Instant,
+    ) -> Result<CommitConfirmation, String> {
+        let start = Instant::now();
+        let subscription = pubsub_client
+            .signature_subscribe(
+                signature,
+                Some(RpcSignatureSubscribeConfig {
+                    commitment: Some(CommitmentConfig::confirmed()),
+                    enable_received_notification: Some(false),
+                }),
+            )
+            .await;
+        self.observe_remote_request(
+            "subscribe_commit",
+            subscription.is_ok(),
+            start,
+        );
+        let (notifications, unsubscribe) =
+            subscription.map_err(|err| format!("{:?}", err))?;
+        let confirmation = self
+            .await_subscribed_commit_confirmation(
+                notifications,
+                signature,
+                started,
+            )
+            .await;
+        unsubscribe().await;
+        confirmation
+    }
+
+    /// Waits for the commit transaction to be confirmed via the
+    /// [notifications] of the subscription to its signature.
+    /// Fails if the subscription closes before that.
+    async fn await_subscribed_commit_confirmation(
+        &self,
+        mut notifications: impl Stream<Item = Response<RpcSignatureResult>> + Unpin,
+        signature: &Signature,
+        started: Instant,
+    ) -> Result<CommitConfirmation, String> {
+        let notified = async {
+            while let Some(response) = notifications.next().await {
+                if let RpcSignatureResult::ProcessedSignature(result) =
+                    response.value
+                {
+                    return Some(match result.err {
+                        None => CommitConfirmation::Succeeded,
+                        Some(err) => {
+                            CommitConfirmation::Failed(err.to_string())
+                        }
+                    });
+                }
+            }
+            None
+        };
+        // We are not notified about a transaction that was confirmed before
+        // we subscribed, thus we also poll its status, though less often.
+        // This also times out the confirmation if it never lands.
+        let confirmation = tokio::select! {
+            confirmation = notified => confirmation,
+            confirmation = self.poll_commit_confirmation(
+                signature,
+                started,
+                SUBSCRIBED_POLL_INTERVAL,
+            ) => Some(confirmation),
+        };
+        confirmation.ok_or_else(|| {
+            "subscription closed before the commit was confirmed".to_string()
+        })
+    }
+
+    async fn poll_commit_confirmation(
+        &self,
+        signature: &Signature,
+        started: Instant,
+        interval: Duration,
+    ) -> CommitConfirmation {
+        loop {
+            let start = Instant::now();
+            let res = self
+                .rpc_client
+                .confirm_transaction_with_commitment(
+                    signature,
+                    CommitmentConfig::confirmed(),
+                )
+                .await;
+            self.observe_remote_request("confirm_commit", res.is_ok(), start);
+            match res {
+                Ok(res) => {
+                    // The RPC `confirm_transaction_with_commitment` doesn't provide
+                    // the info to distinguish between a not yet confirmed or
+                    // failed transaction.
+                    // Failed transactions should be rare, so it's ok to check
+                    // them over and over until the timeout is reached.
+                    // Subscriptions notify us about failed transactions right away.
+                    if res.value {
+                        return CommitConfirmation::Succeeded;
+                    }
+                    if started.elapsed().as_secs()
+                        > MAX_TRANSACTION_CONFIRMATION_SECS
+                    {
+                        return CommitConfirmation::TimedOut;
+                    }
+                    tokio::time::sleep(interval).await;
+                }
+                Err(err) => {
+                    return CommitConfirmation::Error(format!("{:?}", err))
+                }
+            }
         }
-        join_all(futures).await;
     }
 }
 
@@ -346,19 +843,8 @@ pub(crate) fn update_account_commit_metrics(
 impl RemoteAccountCommitter {
     fn compute_instructions(
         &self,
-        committee_count: u32,
-        undelegation_count: u32,
+        compute_budget: u32,
     ) -> (Instruction, Instruction) {
-        // TODO(thlorenz): We may need to consider account size as well since
-        // the account is copied which could affect CUs
-        const BASE_COMPUTE_BUDGET: u32 = 50_000;
-        const COMPUTE_BUDGET_PER_COMMITTEE: u32 = 30_000;
-        const COMPUTE_BUDGET_PER_UNDELEGATION: u32 = 30_000;
-
-        let compute_budget = BASE_COMPUTE_BUDGET
-            + (COMPUTE_BUDGET_PER_COMMITTEE * committee_count)
-            + (COMPUTE_BUDGET_PER_UNDELEGATION * undelegation_count);
-
         let compute_budget_ix =
             ComputeBudgetInstruction::set_compute_unit_limit(compute_budget);
         let compute_unit_price_ix =
@@ -367,4 +853,131 @@ impl RemoteAccountCommitter {
             );
         (compute_budget_ix, compute_unit_price_ix)
     }
+
+    /// The base fee for each signature plus the priority fee which is charged
+    /// for the requested compute units, not the ones actually consumed.
+    fn fee_lamports(&self, compute_budget: u32, signature_count: u64) -> u64 {
+        let priority_fee = (compute_budget as u64 * self.compute_unit_price)
+            .div_ceil(MICRO_LAMPORTS_PER_LAMPORT);
+        LAMPORTS_PER_SIGNATURE * signature_count + priority_fee
+    }
+}
+
+fn compute_budget(committee_count: u32, undelegation_count: u32) -> u32 {
+    // TODO(thlorenz): We may need to consider account size as well since
+    // the account is copied which could affect CUs
+    const BASE_COMPUTE_BUDGET: u32 = 50_000;
+    const COMPUTE_BUDGET_PER_COMMITTEE: u32 = 30_000;
+    const COMPUTE_BUDGET_PER_UNDELEGATION: u32 = 30_000;
+    const COMPUTE_BUDGET_PER_COMMIT_PROOF: u32 = 10_000;
+
+    BASE_COMPUTE_BUDGET
+        + (COMPUTE_BUDGET_PER_COMMITTEE * committee_count)
+        + (COMPUTE_BUDGET_PER_COMMIT_PROOF * committee_count)
+        + (COMPUTE_BUDGET_PER_UNDELEGATION * undelegation_count)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use futures_util::stream;
+    use solana_rpc_client_api::{
+        request::RpcRequest,
+        response::{
+            ProcessedSignatureResult, ReceivedSignatureResult,
+            RpcResponseContext,
+        },
+    };
+    use solana_sdk::{signature::Keypair, transaction::TransactionError};
+
+    use super::*;
+
+    fn committer(rpc_client: RpcClient) -> RemoteAccountCommitter {
+        RemoteAccountCommitter::new(
+            rpc_client,
+            Arc::new(ValidatorContext::new(Keypair::new())),
+            0,
+            CircuitBreaker::disabled(),
+            CommitCostTracker::default(),
+        )
+    }
+
+    /// The first status poll finds the transaction unconfirmed, the ones
+    /// after that confirmed.
+    fn unconfirmed_at_first_poll() -> RpcClient {
+        let mocks = HashMap::from([(
+            RpcRequest::GetSignatureStatuses,
+            serde_json::json!({
+                "context": { "slot": 1 },
+                "value": [null],
+            }),
+        )]);
+        RpcClient::new_mock_with_mocks("succeeds".to_string(), mocks)
+    }
+
+    fn notification(value: RpcSignatureResult) -> Response<RpcSignatureResult> {
+        Response {
+            context: RpcResponseContext::new(1),
+            value,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_subscription_closing_early_fails_confirmation() {
+        let committer = committer(unconfirmed_at_first_poll());
+
+        let confirmation = committer
+            .await_subscribed_commit_confirmation(
+                stream::empty(),
+                &Signature::new_unique(),
+                Instant::now(),
+            )
+            .await;
+
+        // Makes the caller fall back to polling
+        assert!(confirmation.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_commit_confirmed_before_subscribing_is_polled() {
+        // Transactions confirmed before subscribing are never notified
+        let committer = committer(RpcClient::new_mock("succeeds".to_string()));
+
+        let confirmation = committer
+            .await_subscribed_commit_confirmation(
+                stream::pending(),
+                &Signature::new_unique(),
+                Instant::now(),
+            )
+            .await;
+
+        assert!(matches!(confirmation, Ok(CommitConfirmation::Succeeded)));
+    }
+
+    #[tokio::test]
+    async fn test_failed_commit_is_notified() {
+        let committer = committer(unconfirmed_at_first_poll());
+        let notifications = stream::iter([
+            notification(RpcSignatureResult::ReceivedSignature(
+                ReceivedSignatureResult::ReceivedSignature,
+            )),
+            notification(RpcSignatureResult::ProcessedSignature(
+                ProcessedSignatureResult {
+                    err: Some(TransactionError::AccountInUse),
+                },
+            )),
+        ])
+        .chain(stream::pending());
+
+        let confirmation = committer
+            .await_subscribed_commit_confirmation(
+                notifications,
+                &Signature::new_unique(),
+                Instant::now(),
+            )
+            .await;
+
+        assert!(matches!(confirmation, Ok(CommitConfirmation::Failed(_))));
+    }
 }
@@ -1,23 +1,44 @@
-use std::{collections::HashSet, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, RwLock},
+};
 
 use async_trait::async_trait;
 use log::*;
 use magicblock_accounts_api::InternalAccountProvider;
 use magicblock_bank::bank::Bank;
-use magicblock_core::debug_panic;
+use magicblock_core::{
+    chain_slot_mapping::ChainSlotMapping, circuit_breaker::CircuitBreaker,
+    debug_panic, robust_lock::RobustRwLock,
+};
 use magicblock_metrics::metrics;
 use magicblock_mutator::Cluster;
 use magicblock_processor::execute_transaction::execute_legacy_transaction;
 use magicblock_program::{
-    register_scheduled_commit_sent, SentCommit, TransactionScheduler,
+    magicblock_instruction::scheduled_commit_confirmed,
+    validator::ValidatorStage, ConfirmedCommit, SentCommit,
+    TransactionScheduler,
+};
+use magicblock_telemetry::{
+    child_span, record_error, take_commit_trace_context, KeyValue, TraceContext,
 };
 use magicblock_transaction_status::TransactionStatusSender;
-use solana_sdk::{pubkey::Pubkey, signature::Signature};
+use solana_sdk::{
+    account::{AccountSharedData, ReadableAccount},
+    clock::Slot,
+    hash::{hash, Hash},
+    pubkey::Pubkey,
+    signature::Signature,
+};
+use tokio::sync::watch;
 
 use crate::{
+    commit_ordering::{CommitOrdering, CommitTurn},
     errors::{AccountsError, AccountsResult},
     remote_account_committer::update_account_commit_metrics,
-    AccountCommittee, AccountCommitter, ScheduledCommitsProcessor,
+    AccountCommittee, AccountCommitter, DefaultScheduledCommitPolicy,
+    ScheduledCommitAccount, ScheduledCommitPolicy,
+    ScheduledCommitPolicyOutcome, ScheduledCommitsProcessor,
     SendableCommitAccountsPayload, UndelegationRequest,
 };
 
@@ -27,6 +48,23 @@ pub struct RemoteScheduledCommitsProcessor {
     bank: Arc<Bank>,
     transaction_status_sender: Option<TransactionStatusSender>,
     transaction_scheduler: TransactionScheduler,
+    /// Hashes of the account data that was last committed for each account
+    /// that is still delegated.
+    /// Used to skip conditional commits of accounts that did not change.
+    committed_data_hashes: Arc<RwLock<HashMap<Pubkey, Hash>>>,
+    /// While open, scheduled commits are left in place until the remote
+    /// cluster recovers.
+    circuit_breaker: CircuitBreaker,
+    /// Decides which accounts of a scheduled commit are committed
+    policy: Arc<dyn ScheduledCommitPolicy>,
+    /// Timestamps commits with the slot of the base chain
+    chain_slot_mapping: ChainSlotMapping,
+    /// Runs commits of unrelated accounts concurrently while the ones of the
+    /// same account run in the order they were scheduled
+    commit_ordering: CommitOrdering,
+    /// Observes the validator stage in order to send delayed commits early
+    /// once it shuts down
+    validator_stage: watch::Receiver<ValidatorStage>,
 }
 
 #[async_trait]
@@ -35,33 +73,102 @@ impl ScheduledCommitsProcessor for RemoteScheduledCommitsProcessor {
         &self,
         committer: &Arc<AC>,
         account_provider: &IAP,
+        is_delegated: &(dyn Fn(&Pubkey) -> bool + Sync),
     ) -> AccountsResult<()>
     where
         AC: AccountCommitter,
         IAP: InternalAccountProvider,
     {
-        let scheduled_commits =
-            self.transaction_scheduler.take_scheduled_commits();
+        if self.circuit_breaker.is_open() {
+            debug!("Remote cluster is failing, holding back scheduled commits");
+            return Ok(());
+        }
+        // Once the validator drains all commits are due since it may not run
+        // again before the delayed ones would become due
+        let due_slot =
+            if *self.validator_stage.borrow() >= ValidatorStage::Draining {
+                Slot::MAX
+            } else {
+                self.bank.slot()
+            };
+        let scheduled_commits = self
+            .transaction_scheduler
+            .take_due_scheduled_commits(due_slot);
         if scheduled_commits.is_empty() {
             return Ok(());
         }
 
-        let mut sendable_payloads_queue = vec![];
         for commit in scheduled_commits {
-            info!("Processing commit: {:?}", commit);
+            info!(commit_id = commit.id; "Processing commit: {:?}", commit);
+            // Continues the trace of the transaction that scheduled the commit
+            let trace_context = child_span(
+                "commit",
+                &take_commit_trace_context(commit.id),
+                vec![
+                    KeyValue::new("commit_id", commit.id as i64),
+                    KeyValue::new("accounts", commit.accounts.len() as i64),
+                ],
+            );
 
             // Determine which accounts are available and can be committed
             let mut committees = vec![];
             let all_pubkeys: HashSet<Pubkey> =
                 HashSet::from_iter(commit.accounts.iter().cloned());
 
+            // For conditional commits we skip accounts whose data did not change
+            // since we last committed them
+            let data_hashes: HashMap<Pubkey, Hash> = commit
+                .data_hashes
+                .map(|hashes| {
+                    commit.accounts.iter().cloned().zip(hashes).collect()
+                })
+                .unwrap_or_default();
+            let mut skipped_pubkeys = vec![];
+            let mut exclusion_reasons = vec![];
+
             for pubkey in commit.accounts {
+                if let Some(data_hash) = data_hashes.get(&pubkey) {
+                    if self.last_committed_data_hash(&pubkey).as_ref()
+                        == Some(data_hash)
+                    {
+                        debug!(
+                            pubkey:% = pubkey;
+                            "Skipping commit of unchanged account '{}'",
+                            pubkey
+                        );
+                        skipped_pubkeys.push(pubkey);
+                        continue;
+                    }
+                }
                 match account_provider.get_account(&pubkey) {
                     Some(account_data) => {
+                        if let ScheduledCommitPolicyOutcome::Exclude(reason) =
+                            self.policy.check(&ScheduledCommitAccount {
+                                pubkey: &pubkey,
+                                account: &account_data,
+                                commit_owner: &commit.owner,
+                                is_delegated: is_delegated(&pubkey),
+                                request_undelegation: commit
+                                    .request_undelegation,
+                            })
+                        {
+                            warn!(
+                                pubkey:% = pubkey;
+                                "Excluding account '{}' from commit: {}",
+                                pubkey, reason
+                            );
+                            exclusion_reasons.push((pubkey, reason));
+                            continue;
+                        }
                         let undelegation_request =
                             if commit.request_undelegation {
                                 Some(UndelegationRequest {
                                     owner: commit.owner,
+                                    refund: commit
+                                        .closed_accounts
+                                        .iter()
+                                        .find(|x| x.account == pubkey)
+                                        .map(|x| x.refund),
                                 })
                             } else {
                                 None
@@ -75,18 +182,25 @@ impl ScheduledCommitsProcessor for RemoteScheduledCommitsProcessor {
                     }
                     None => {
                         error!(
+                            pubkey:% = pubkey;
                             "Scheduled commmit account '{}' not found. It must have gotten undelegated and removed since it was scheduled.",
                             pubkey
                         );
+                        exclusion_reasons
+                            .push((pubkey, "account not found".to_string()));
                     }
                 }
             }
 
-            let payloads = vec![
-                committer
-                    .create_commit_accounts_transaction(committees)
-                    .await?,
-            ];
+            let payloads = if committees.is_empty() {
+                vec![]
+            } else {
+                vec![
+                    committer
+                        .create_commit_accounts_transaction(committees)
+                        .await?,
+                ]
+            };
 
             // Determine which payloads are a noop since all accounts are up to date
             // and which require a commit to chain
@@ -101,6 +215,10 @@ impl ScheduledCommitsProcessor for RemoteScheduledCommitsProcessor {
                                 .iter()
                                 .map(|(pubkey, _)| *pubkey),
                         );
+                        self.record_committed_data_hashes(
+                            &payload.committees,
+                            &transaction.undelegated_accounts,
+                        );
                         Some(SendableCommitAccountsPayload {
                             transaction,
                             committees: payload.committees,
@@ -115,7 +233,10 @@ impl ScheduledCommitsProcessor for RemoteScheduledCommitsProcessor {
             // was not available as determined when creating sendable payloads
             let excluded_pubkeys = all_pubkeys
                 .into_iter()
-                .filter(|pubkey| !included_pubkeys.contains(pubkey))
+                .filter(|pubkey| {
+                    !included_pubkeys.contains(pubkey)
+                        && !skipped_pubkeys.contains(pubkey)
+                })
                 .collect::<Vec<Pubkey>>();
 
             // Extract signatures of all transactions that we we will execute on
@@ -131,16 +252,21 @@ impl ScheduledCommitsProcessor for RemoteScheduledCommitsProcessor {
             let sent_commit = SentCommit {
                 commit_id: commit.id,
                 slot: commit.slot,
+                chain_slot: self.chain_slot_mapping.chain_slot_at(commit.slot),
                 blockhash: commit.blockhash,
                 payer: commit.payer,
                 chain_signatures: signatures,
                 included_pubkeys: included_pubkeys.into_iter().collect(),
                 excluded_pubkeys,
+                exclusion_reasons,
+                skipped_pubkeys,
                 requested_undelegation_to_owner: commit
                     .request_undelegation
                     .then_some(commit.owner),
             };
-            register_scheduled_commit_sent(sent_commit);
+            self.bank
+                .validator_context()
+                .register_scheduled_commit_sent(sent_commit);
             let signature = execute_legacy_transaction(
                 commit.commit_sent_transaction,
                 &self.bank,
@@ -163,15 +289,31 @@ impl ScheduledCommitsProcessor for RemoteScheduledCommitsProcessor {
                 );
             }
 
-            // Queue up the actual commit
-            sendable_payloads_queue.extend(sendable_payloads);
+            // Queue up the actual commit behind the ones of the same accounts
+            // scheduled before, the span ends once its outcome on the base
+            // layer is known
+            let pubkeys = sendable_payloads
+                .iter()
+                .flat_map(|payload| payload.committees.iter())
+                .map(|(pubkey, _)| *pubkey)
+                .collect::<Vec<_>>();
+            let turn = self.commit_ordering.register(&pubkeys);
+            let sent_commit_signatures = (
+                commit.id,
+                sendable_payloads
+                    .iter()
+                    .map(|payload| payload.get_signature())
+                    .collect::<Vec<_>>(),
+                child_span("commit.confirm", &trace_context, vec![]),
+            );
+            self.process_accounts_commit_in_background(
+                committer,
+                turn,
+                sendable_payloads,
+                sent_commit_signatures,
+            );
         }
 
-        self.process_accounts_commits_in_background(
-            committer,
-            sendable_payloads_queue,
-        );
-
         Ok(())
     }
 
@@ -189,32 +331,138 @@ impl RemoteScheduledCommitsProcessor {
         cluster: Cluster,
         bank: Arc<Bank>,
         transaction_status_sender: Option<TransactionStatusSender>,
+        circuit_breaker: CircuitBreaker,
+        max_concurrent_commits: usize,
     ) -> Self {
+        let transaction_scheduler =
+            bank.validator_context().transaction_scheduler().clone();
+        let validator_stage = bank.validator_context().subscribe_to_stage();
         Self {
             cluster,
             bank,
             transaction_status_sender,
-            transaction_scheduler: TransactionScheduler::default(),
+            transaction_scheduler,
+            committed_data_hashes: Default::default(),
+            circuit_breaker,
+            policy: Arc::new(DefaultScheduledCommitPolicy),
+            chain_slot_mapping: ChainSlotMapping::default(),
+            commit_ordering: CommitOrdering::new(max_concurrent_commits),
+            validator_stage,
         }
     }
 
-    fn process_accounts_commits_in_background<AC: AccountCommitter>(
+    pub(crate) fn set_policy(
+        &mut self,
+        policy: Arc<dyn ScheduledCommitPolicy>,
+    ) {
+        self.policy = policy;
+    }
+
+    pub(crate) fn set_chain_slot_mapping(
+        &mut self,
+        chain_slot_mapping: ChainSlotMapping,
+    ) {
+        self.chain_slot_mapping = chain_slot_mapping;
+    }
+
+    fn last_committed_data_hash(&self, pubkey: &Pubkey) -> Option<Hash> {
+        self.committed_data_hashes
+            .read_robust()
+            .get(pubkey)
+            .cloned()
+    }
+
+    /// Records the hashes of the committed account data, the ones of
+    /// [undelegated_accounts] are evicted instead since they are no longer
+    /// committed by this validator until they are delegated again.
+    fn record_committed_data_hashes(
+        &self,
+        committees: &[(Pubkey, AccountSharedData)],
+        undelegated_accounts: &HashSet<Pubkey>,
+    ) {
+        let mut committed_data_hashes =
+            self.committed_data_hashes.write_robust();
+        for (pubkey, account_data) in committees {
+            if undelegated_accounts.contains(pubkey) {
+                committed_data_hashes.remove(pubkey);
+            } else {
+                committed_data_hashes
+                    .insert(*pubkey, hash(account_data.data()));
+            }
+        }
+    }
+
+    fn process_accounts_commit_in_background<AC: AccountCommitter>(
         &self,
         committer: &Arc<AC>,
-        sendable_payloads_queue: Vec<SendableCommitAccountsPayload>,
+        turn: CommitTurn,
+        sendable_payloads: Vec<SendableCommitAccountsPayload>,
+        (commit_id, signatures, trace_context): (
+            u64,
+            Vec<Signature>,
+            TraceContext,
+        ),
     ) {
-        // We process the queue on a separate task in order to not block
-        // the validator (slot advance) itself
+        // We process the commit on a separate task in order to not block
+        // the validator (slot advance) itself nor the commits of other accounts
         // NOTE: @@ we have to be careful here and ensure that the validator does not
         // shutdown before this task is done
         // We will need some tracking machinery which is overkill until we get to the
         // point where we do allow validator shutdown
         let committer = committer.clone();
+        let committed_data_hashes = self.committed_data_hashes.clone();
+        let bank = self.bank.clone();
+        let transaction_status_sender = self.transaction_status_sender.clone();
         tokio::task::spawn(async move {
-            let pending_commits = match committer
-                .send_commit_transactions(sendable_payloads_queue)
-                .await
-            {
+            let mut sendable_payloads = sendable_payloads;
+            let mut signatures = signatures;
+            // Held until the outcome of the commit is recorded
+            let turn_guard = turn.wait().await;
+            // The blockhash may have expired while waiting for the commits
+            // of the same accounts, thus they're signed again. The sent
+            // commit recorded the initial signatures while the confirmed
+            // one records those that actually landed
+            let resigned = if turn_guard.waited() {
+                let resigned = committer
+                    .resign_commit_transactions(&mut sendable_payloads)
+                    .await;
+                signatures = sendable_payloads
+                    .iter()
+                    .map(|payload| payload.get_signature())
+                    .collect();
+                resigned
+            } else {
+                Ok(())
+            };
+            let sent = match resigned {
+                Ok(()) => {
+                    committer.send_commit_transactions(sendable_payloads).await
+                }
+                Err(AccountsError::FailedToGetLatestBlockhash(err)) => {
+                    let (undelegated, committed_only) =
+                        sendable_payloads.iter().fold(
+                            (HashSet::new(), HashSet::new()),
+                            |(mut undelegated, mut committed_only), payload| {
+                                undelegated.extend(
+                                    &payload.transaction.undelegated_accounts,
+                                );
+                                committed_only.extend(
+                                    &payload
+                                        .transaction
+                                        .committed_only_accounts,
+                                );
+                                (undelegated, committed_only)
+                            },
+                        );
+                    Err(AccountsError::FailedToSendCommitTransaction(
+                        format!("Failed to get latest blockhash: {}", err),
+                        undelegated,
+                        committed_only,
+                    ))
+                }
+                Err(err) => Err(err),
+            };
+            let pending_commits = match sent {
                 Ok(pending) => pending,
                 Err(AccountsError::FailedToSendCommitTransaction(
                     err,
@@ -227,6 +475,19 @@ impl RemoteScheduledCommitsProcessor {
                         metrics::Outcome::Error,
                         None,
                     );
+                    // The data of these accounts never made it to chain, thus we
+                    // cannot skip their next conditional commit
+                    {
+                        let mut committed_data_hashes =
+                            committed_data_hashes.write_robust();
+                        for pubkey in commit_and_undelegate_accounts
+                            .iter()
+                            .chain(commit_only_accounts.iter())
+                        {
+                            committed_data_hashes.remove(pubkey);
+                        }
+                    }
+                    record_error(&trace_context, &err);
                     debug_panic!(
                         "Failed to send commit transactions: {:?}",
                         err
@@ -242,7 +503,52 @@ impl RemoteScheduledCommitsProcessor {
                 }
             };
 
-            committer.confirm_pending_commits(pending_commits).await;
+            let confirmed_signatures = committer
+                .confirm_pending_commits(pending_commits)
+                .await
+                .into_iter()
+                .collect::<HashMap<Signature, bool>>();
+
+            // Record the outcome of the commit in our ledger
+            let success = signatures.iter().all(|signature| {
+                confirmed_signatures
+                    .get(signature)
+                    .cloned()
+                    .unwrap_or(false)
+            });
+            if !success {
+                record_error(&trace_context, &"Commit failed to confirm");
+            }
+            let context = bank.validator_context();
+            context.register_scheduled_commit_confirmed(ConfirmedCommit {
+                commit_id,
+                chain_signatures: signatures,
+                success,
+            });
+            let transaction = scheduled_commit_confirmed(
+                context,
+                commit_id,
+                bank.last_blockhash(),
+            );
+            match execute_legacy_transaction(
+                transaction,
+                &bank,
+                transaction_status_sender.as_ref(),
+            ) {
+                Ok(signature) => debug!(
+                    commit_id = commit_id, signature:% = signature;
+                    "Signaled commit {} confirmation with internal signature: {:?}",
+                    commit_id, signature
+                ),
+                Err(err) => error!(
+                    commit_id = commit_id;
+                    "Failed to signal commit {} confirmation: {:?}",
+                    commit_id, err
+                ),
+            }
+            // The commit is only removed by a successful confirmation
+            // instruction, otherwise nothing reads it anymore
+            context.unregister_scheduled_commit_confirmed(commit_id);
         });
     }
 }
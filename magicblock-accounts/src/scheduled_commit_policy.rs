@@ -0,0 +1,119 @@
+use solana_sdk::{account::AccountSharedData, pubkey::Pubkey};
+
+/// An account of a scheduled commit which a [ScheduledCommitPolicy] decides
+/// to include or exclude.
+pub struct ScheduledCommitAccount<'a> {
+    pub pubkey: &'a Pubkey,
+    /// The current state of the account in our validator
+    pub account: &'a AccountSharedData,
+    /// The program that scheduled the commit
+    pub commit_owner: &'a Pubkey,
+    /// `true` if the account was cloned as delegated to our validator
+    pub is_delegated: bool,
+    pub request_undelegation: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScheduledCommitPolicyOutcome {
+    Include,
+    /// The account is not committed, the reason is included in the
+    /// `ScheduledCommitSent` logs
+    Exclude(String),
+}
+
+/// Decides which accounts of a scheduled commit are committed to chain.
+/// Forks can provide their own policy in order to add custom rules, i.e.
+/// excluding accounts above a certain size or requiring allow-listed owners.
+pub trait ScheduledCommitPolicy: Send + Sync {
+    fn check(
+        &self,
+        account: &ScheduledCommitAccount,
+    ) -> ScheduledCommitPolicyOutcome;
+}
+
+/// Includes all accounts that are delegated to our validator.
+/// That they are owned by the program scheduling the commit, or that it is
+/// a commit authority of the owner, is verified when the commit is scheduled.
+#[derive(Debug, Default, Clone)]
+pub struct DefaultScheduledCommitPolicy;
+
+impl ScheduledCommitPolicy for DefaultScheduledCommitPolicy {
+    fn check(
+        &self,
+        account: &ScheduledCommitAccount,
+    ) -> ScheduledCommitPolicyOutcome {
+        if account.is_delegated {
+            ScheduledCommitPolicyOutcome::Include
+        } else {
+            ScheduledCommitPolicyOutcome::Exclude(
+                "account is not delegated to this validator".to_string(),
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(
+        policy: &dyn ScheduledCommitPolicy,
+        account: &AccountSharedData,
+        is_delegated: bool,
+    ) -> ScheduledCommitPolicyOutcome {
+        policy.check(&ScheduledCommitAccount {
+            pubkey: &Pubkey::new_unique(),
+            account,
+            commit_owner: &Pubkey::new_unique(),
+            is_delegated,
+            request_undelegation: false,
+        })
+    }
+
+    #[test]
+    fn test_default_policy_includes_only_delegated_accounts() {
+        let account = AccountSharedData::new(1_000, 0, &Pubkey::new_unique());
+
+        assert_eq!(
+            check(&DefaultScheduledCommitPolicy, &account, true),
+            ScheduledCommitPolicyOutcome::Include
+        );
+        assert!(matches!(
+            check(&DefaultScheduledCommitPolicy, &account, false),
+            ScheduledCommitPolicyOutcome::Exclude(_)
+        ));
+    }
+
+    #[test]
+    fn test_custom_policy_can_exclude_delegated_accounts() {
+        struct MaxDataLenPolicy(usize);
+        impl ScheduledCommitPolicy for MaxDataLenPolicy {
+            fn check(
+                &self,
+                account: &ScheduledCommitAccount,
+            ) -> ScheduledCommitPolicyOutcome {
+                use solana_sdk::account::ReadableAccount;
+                if account.account.data().len() > self.0 {
+                    ScheduledCommitPolicyOutcome::Exclude(
+                        "account data too large".to_string(),
+                    )
+                } else {
+                    DefaultScheduledCommitPolicy.check(account)
+                }
+            }
+        }
+        let policy = MaxDataLenPolicy(10);
+        let owner = Pubkey::new_unique();
+
+        assert_eq!(
+            check(&policy, &AccountSharedData::new(1_000, 10, &owner), true),
+            ScheduledCommitPolicyOutcome::Include
+        );
+        assert_eq!(
+            check(&policy, &AccountSharedData::new(1_000, 11, &owner), true),
+            ScheduledCommitPolicyOutcome::Exclude(
+                "account data too large".to_string()
+            )
+        );
+    }
+}
@@ -13,14 +13,18 @@ use crate::errors::AccountsResult;
 
 #[async_trait]
 pub trait ScheduledCommitsProcessor {
-    /// Processes all commits that were scheduled and accepted
+    /// Processes all commits that were scheduled and accepted and are due
+    /// at the current slot.
+    /// [is_delegated] tells if an account is delegated to our validator.
     async fn process<AC: AccountCommitter, IAP: InternalAccountProvider>(
         &self,
         committer: &Arc<AC>,
         account_provider: &IAP,
+        is_delegated: &(dyn Fn(&Pubkey) -> bool + Sync),
     ) -> AccountsResult<()>;
 
-    /// Returns the number of commits that were scheduled and accepted
+    /// Returns the number of commits that were scheduled and accepted,
+    /// including the ones that are delayed to a later slot
     fn scheduled_commits_len(&self) -> usize;
     /// Clears all scheduled commits
     fn clear_scheduled_commits(&self);
@@ -30,6 +34,10 @@ pub trait ScheduledCommitsProcessor {
 pub struct UndelegationRequest {
     /// The original owner of the account before it was delegated.
     pub owner: Pubkey,
+    /// Only present if the account is ephemeral-only in which case none of
+    /// its data is committed and it is handed back to its owner program
+    /// empty, which can then close it and send its rent to this address.
+    pub refund: Option<Pubkey>,
 }
 
 pub struct AccountCommittee {
@@ -54,6 +62,8 @@ pub struct CommitAccountsTransaction {
     pub undelegated_accounts: HashSet<Pubkey>,
     /// Accounts that are only committed and not undelegated as part of the transaction.
     pub committed_only_accounts: HashSet<Pubkey>,
+    /// The fee in lamports the validator authority pays for the transaction.
+    pub fee_lamports: u64,
 }
 
 impl CommitAccountsTransaction {
@@ -112,10 +122,20 @@ pub trait AccountCommitter: Send + Sync + 'static {
         committees: Vec<AccountCommittee>,
     ) -> AccountsResult<CommitAccountsPayload>;
 
+    /// Signs the transactions of the [payloads] again with a recent blockhash.
+    /// Used for commits that waited for others to complete first, since the
+    /// blockhash they were signed with may have expired in the meantime.
+    async fn resign_commit_transactions(
+        &self,
+        payloads: &mut [SendableCommitAccountsPayload],
+    ) -> AccountsResult<()>;
+
     /// Returns the main-chain signatures of the commit transactions
     /// This will only fail due to network issues, not if the transaction failed.
     /// Therefore we want to either fail all transactions or none which is why
     /// we return a `Result<Vec>` instead of a `Vec<Result>`.
+    /// Transactions that are known to fail on chain may be left out of the
+    /// result without being sent.
     async fn send_commit_transactions(
         &self,
         payloads: Vec<SendableCommitAccountsPayload>,
@@ -125,8 +145,10 @@ pub trait AccountCommitter: Send + Sync + 'static {
     /// commitment level.
     /// Updates the metrics for each transaction in order to record the time it took
     /// to fully confirm it on chain.
+    /// Returns the signature of each transaction together with a flag indicating
+    /// if it was confirmed successfully.
     async fn confirm_pending_commits(
         &self,
         pending_commits: Vec<PendingCommitTransaction>,
-    );
+    ) -> Vec<(Signature, bool)>;
 }
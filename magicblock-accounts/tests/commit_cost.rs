@@ -0,0 +1,37 @@
+use magicblock_accounts::CommitCostTracker;
+use solana_sdk::{account::AccountSharedData, pubkey::Pubkey};
+
+fn committee(owner: &Pubkey) -> (Pubkey, AccountSharedData) {
+    (
+        Pubkey::new_unique(),
+        AccountSharedData::new(1_000, 0, owner),
+    )
+}
+
+#[test]
+fn test_top_account_costs_share_fees_between_committees() {
+    let tracker = CommitCostTracker::default();
+    let owner = Pubkey::new_unique();
+    let frequent = committee(&owner);
+    let rare = committee(&owner);
+
+    tracker.record(10_000, &[frequent.clone(), rare.clone()]);
+    tracker.record(5_000, &[frequent.clone()]);
+
+    assert_eq!(
+        tracker.top_account_costs(10),
+        vec![(frequent.0, 10_000), (rare.0, 5_000)]
+    );
+    assert_eq!(tracker.top_account_costs(1), vec![(frequent.0, 10_000)]);
+}
+
+#[test]
+fn test_top_account_costs_are_shared_between_clones() {
+    let tracker = CommitCostTracker::default();
+    let clone = tracker.clone();
+    let account = committee(&Pubkey::new_unique());
+
+    clone.record(5_000, &[account.clone()]);
+
+    assert_eq!(tracker.top_account_costs(10), vec![(account.0, 5_000)]);
+}
@@ -8,10 +8,16 @@ use conjunto_transwise::{
     CommitFrequency, DelegationRecord,
 };
 use magicblock_account_cloner::{AccountClonerOutput, AccountClonerStub};
-use magicblock_accounts::{ExternalAccountsManager, LifecycleMode};
+use magicblock_accounts::{
+    CommitBudget, CommitCostTracker, CommitPolicy, ExternalAccountsManager,
+    LifecycleMode,
+};
 use magicblock_accounts_api::InternalAccountProviderStub;
+use magicblock_program::{
+    EphemeralOnlyAccount, MagicContext, MAGIC_CONTEXT_PUBKEY,
+};
 use solana_sdk::{
-    account::{Account, AccountSharedData},
+    account::{Account, AccountSharedData, WritableAccount},
     native_token::LAMPORTS_PER_SOL,
     pubkey::Pubkey,
     signature::Signature,
@@ -46,7 +52,11 @@ fn setup(
         transaction_accounts_validator: TransactionAccountsValidatorImpl,
         scheduled_commits_processor: ScheduledCommitsProcessorStub::default(),
         lifecycle: LifecycleMode::Ephemeral,
+        commit_policy: CommitPolicy::all(),
         external_commitable_accounts: Default::default(),
+        dirty_commitable_accounts: Default::default(),
+        ephemeral_only_accounts: Default::default(),
+        commit_cost_tracker: Default::default(),
     }
 }
 
@@ -179,3 +189,315 @@ async fn test_commit_two_delegated_accounts_one_needs_commit() {
         last_commit_of_commit_not_needed
     );
 }
+
+#[tokio::test]
+async fn test_commit_delegated_account_skipped_while_over_budget() {
+    init_logger!();
+
+    let pubkey = Pubkey::new_unique();
+    let account = generate_account(&pubkey);
+    let account_shared = AccountSharedData::from(account.clone());
+
+    let internal_account_provider = InternalAccountProviderStub::default();
+    let account_cloner = AccountClonerStub::default();
+    let account_committer = AccountCommitterStub::default();
+
+    let mut manager = setup(
+        internal_account_provider.clone(),
+        account_cloner.clone(),
+        account_committer.clone(),
+    );
+    manager.commit_cost_tracker = CommitCostTracker::new(Some(CommitBudget {
+        max_lamports: 10_000,
+        window: std::time::Duration::from_secs(60),
+    }));
+
+    account_cloner.set(
+        &pubkey,
+        AccountClonerOutput::Cloned {
+            account_chain_snapshot: generate_delegated_account_chain_snapshot(
+                &pubkey,
+                &account,
+                CommitFrequency::Millis(1),
+            ),
+            signature: Signature::new_unique(),
+        },
+    );
+    let result = manager
+        .ensure_accounts_from_holder(
+            TransactionAccountsHolder {
+                readonly: vec![pubkey],
+                writable: vec![],
+                payer: Pubkey::new_unique(),
+            },
+            "tx-sig".to_string(),
+        )
+        .await;
+    assert!(result.is_ok());
+    internal_account_provider.set(pubkey, account_shared.clone());
+
+    // Spend more than the budget allows
+    manager
+        .commit_cost_tracker
+        .record(15_000, &[(pubkey, account_shared)]);
+
+    tokio::time::sleep(tokio::time::Duration::from_millis(2)).await;
+
+    // The account is due, but we don't commit it while over budget
+    let result = manager.commit_delegated().await;
+    assert!(result.unwrap().is_empty());
+    assert_eq!(account_committer.len(), 0);
+}
+
+#[tokio::test]
+async fn test_commit_delegated_account_skipped_if_policy_disallows_it() {
+    init_logger!();
+
+    let pubkey = Pubkey::new_unique();
+    let account = generate_account(&pubkey);
+    let account_shared = AccountSharedData::from(account.clone());
+
+    let internal_account_provider = InternalAccountProviderStub::default();
+    let account_cloner = AccountClonerStub::default();
+    let account_committer = AccountCommitterStub::default();
+
+    let mut manager = setup(
+        internal_account_provider.clone(),
+        account_cloner.clone(),
+        account_committer.clone(),
+    );
+    manager.commit_policy = CommitPolicy::none();
+
+    account_cloner.set(
+        &pubkey,
+        AccountClonerOutput::Cloned {
+            account_chain_snapshot: generate_delegated_account_chain_snapshot(
+                &pubkey,
+                &account,
+                CommitFrequency::Millis(1),
+            ),
+            signature: Signature::new_unique(),
+        },
+    );
+    let result = manager
+        .ensure_accounts_from_holder(
+            TransactionAccountsHolder {
+                readonly: vec![pubkey],
+                writable: vec![],
+                payer: Pubkey::new_unique(),
+            },
+            "tx-sig".to_string(),
+        )
+        .await;
+    assert!(result.is_ok());
+    internal_account_provider.set(pubkey, account_shared);
+
+    tokio::time::sleep(tokio::time::Duration::from_millis(2)).await;
+
+    // The account is due, but neither periodic nor explicit commits are sent
+    let result = manager.commit_delegated().await;
+    assert!(result.unwrap().is_empty());
+    assert!(manager.commit_all_delegated().await.is_err());
+    assert_eq!(account_committer.len(), 0);
+}
+
+#[tokio::test]
+async fn test_commit_delegated_account_only_when_written_since_last_commit() {
+    init_logger!();
+
+    let pubkey = Pubkey::new_unique();
+    let account = generate_account(&pubkey);
+    let account_shared = AccountSharedData::from(account.clone());
+
+    let internal_account_provider = InternalAccountProviderStub::default();
+    let account_cloner = AccountClonerStub::default();
+    let account_committer = AccountCommitterStub::default();
+
+    let manager = setup(
+        internal_account_provider.clone(),
+        account_cloner.clone(),
+        account_committer.clone(),
+    );
+
+    account_cloner.set(
+        &pubkey,
+        AccountClonerOutput::Cloned {
+            account_chain_snapshot: generate_delegated_account_chain_snapshot(
+                &pubkey,
+                &account,
+                CommitFrequency::Millis(1),
+            ),
+            signature: Signature::new_unique(),
+        },
+    );
+    let result = manager
+        .ensure_accounts_from_holder(
+            TransactionAccountsHolder {
+                readonly: vec![pubkey],
+                writable: vec![],
+                payer: Pubkey::new_unique(),
+            },
+            "tx-sig".to_string(),
+        )
+        .await;
+    assert!(result.is_ok());
+    internal_account_provider.set(pubkey, account_shared.clone());
+
+    // The account was written and is due, thus we commit it
+    tokio::time::sleep(tokio::time::Duration::from_millis(2)).await;
+    let result = manager.commit_delegated().await;
+    assert_eq!(result.unwrap().len(), 1);
+    let last_commit = manager.last_commit(&pubkey).unwrap();
+
+    // The account is due again, but it was not written since
+    tokio::time::sleep(tokio::time::Duration::from_millis(2)).await;
+    let result = manager.commit_delegated().await;
+    assert!(result.unwrap().is_empty());
+    assert_eq!(manager.last_commit(&pubkey).unwrap(), last_commit);
+
+    // Once the account is written again we commit it again
+    let mut updated_account_shared = account_shared;
+    updated_account_shared.set_lamports(account.lamports + 1);
+    internal_account_provider.set(pubkey, updated_account_shared.clone());
+    let result = manager.commit_delegated().await;
+    assert_eq!(result.unwrap().len(), 1);
+    assert_eq!(
+        account_committer.committed(&pubkey),
+        Some(updated_account_shared)
+    );
+    assert!(manager.last_commit(&pubkey).unwrap() > last_commit);
+}
+
+#[tokio::test]
+async fn test_commit_all_delegated_commits_accounts_not_due_yet() {
+    init_logger!();
+
+    let pubkey = Pubkey::new_unique();
+    let account = generate_account(&pubkey);
+    let account_shared = AccountSharedData::from(account.clone());
+
+    let internal_account_provider = InternalAccountProviderStub::default();
+    let account_cloner = AccountClonerStub::default();
+    let account_committer = AccountCommitterStub::default();
+
+    let manager = setup(
+        internal_account_provider.clone(),
+        account_cloner.clone(),
+        account_committer.clone(),
+    );
+
+    account_cloner.set(
+        &pubkey,
+        AccountClonerOutput::Cloned {
+            account_chain_snapshot: generate_delegated_account_chain_snapshot(
+                &pubkey,
+                &account,
+                CommitFrequency::Millis(60_000),
+            ),
+            signature: Signature::new_unique(),
+        },
+    );
+    let result = manager
+        .ensure_accounts_from_holder(
+            TransactionAccountsHolder {
+                readonly: vec![pubkey],
+                writable: vec![],
+                payer: Pubkey::new_unique(),
+            },
+            "tx-sig".to_string(),
+        )
+        .await;
+    assert!(result.is_ok());
+    internal_account_provider.set(pubkey, account_shared.clone());
+
+    // The account was written but is not due for another minute
+    let result = manager.commit_delegated().await;
+    assert!(result.unwrap().is_empty());
+    assert_eq!(account_committer.len(), 0);
+
+    // Committing all accounts ignores the commit frequency
+    let result = manager.commit_all_delegated().await;
+    assert_eq!(result.unwrap().len(), 1);
+    assert_eq!(account_committer.committed(&pubkey), Some(account_shared));
+
+    // Unless written again there is nothing left to commit
+    let result = manager.commit_all_delegated().await;
+    assert!(result.unwrap().is_empty());
+}
+
+fn magic_context_account(
+    ephemeral_only_accounts: Vec<EphemeralOnlyAccount>,
+) -> AccountSharedData {
+    let context = MagicContext {
+        ephemeral_only_accounts,
+        ..Default::default()
+    };
+    AccountSharedData::new_data(u64::MAX, &context, &Pubkey::new_unique())
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_commit_delegated_skips_ephemeral_only_accounts() {
+    init_logger!();
+
+    let pubkey = Pubkey::new_unique();
+    let account = generate_account(&pubkey);
+    let account_shared = AccountSharedData::from(account.clone());
+
+    let internal_account_provider = InternalAccountProviderStub::default();
+    let account_cloner = AccountClonerStub::default();
+    let account_committer = AccountCommitterStub::default();
+
+    let manager = setup(
+        internal_account_provider.clone(),
+        account_cloner.clone(),
+        account_committer.clone(),
+    );
+
+    account_cloner.set(
+        &pubkey,
+        AccountClonerOutput::Cloned {
+            account_chain_snapshot: generate_delegated_account_chain_snapshot(
+                &pubkey,
+                &account,
+                CommitFrequency::Millis(1),
+            ),
+            signature: Signature::new_unique(),
+        },
+    );
+    let result = manager
+        .ensure_accounts_from_holder(
+            TransactionAccountsHolder {
+                readonly: vec![pubkey],
+                writable: vec![],
+                payer: Pubkey::new_unique(),
+            },
+            "tx-sig".to_string(),
+        )
+        .await;
+    assert!(result.is_ok());
+
+    // The account is marked ephemeral-only, thus it is not committed
+    internal_account_provider.set(
+        MAGIC_CONTEXT_PUBKEY,
+        magic_context_account(vec![EphemeralOnlyAccount {
+            account: pubkey,
+            refund: Pubkey::new_unique(),
+        }]),
+    );
+    internal_account_provider.set(pubkey, account_shared.clone());
+    tokio::time::sleep(tokio::time::Duration::from_millis(2)).await;
+    let result = manager.commit_delegated().await;
+    assert!(result.unwrap().is_empty());
+    assert!(account_committer.committed(&pubkey).is_none());
+
+    // Once the MagicContext no longer marks it, the account is committed
+    // when it is written
+    internal_account_provider
+        .set(MAGIC_CONTEXT_PUBKEY, magic_context_account(vec![]));
+    internal_account_provider.set(pubkey, account_shared.clone());
+    tokio::time::sleep(tokio::time::Duration::from_millis(2)).await;
+    let result = manager.commit_delegated().await;
+    assert_eq!(result.unwrap().len(), 1);
+    assert_eq!(account_committer.committed(&pubkey), Some(account_shared));
+}
@@ -0,0 +1,160 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+    time::SystemTime,
+};
+
+use async_trait::async_trait;
+use magicblock_accounts::{
+    AccountCommitter, CommitAccountsTransaction, CommitCostTracker,
+    CommitErrorQueue, CommitTransactionSender, FailedCommit,
+    RemoteAccountCommitter, SendableCommitAccountsPayload,
+};
+use magicblock_core::circuit_breaker::CircuitBreaker;
+use magicblock_program::ValidatorContext;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_rpc_client_api::request::RpcRequest;
+use solana_sdk::{
+    hash::Hash,
+    instruction::InstructionError,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature},
+    signer::Signer,
+    transaction::{Transaction, TransactionError},
+};
+
+fn failed_commit(error: &str) -> FailedCommit {
+    FailedCommit {
+        signature: Signature::new_unique(),
+        undelegated_accounts: HashSet::new(),
+        committed_only_accounts: HashSet::from([Pubkey::new_unique()]),
+        error: error.to_string(),
+        logs: vec![],
+        failed_at: SystemTime::now(),
+    }
+}
+
+#[test]
+fn test_commit_error_queue_is_shared_between_clones() {
+    let queue = CommitErrorQueue::default();
+    let clone = queue.clone();
+    let first = failed_commit("InstructionError(2, InvalidAccountData)");
+    let second = failed_commit("InstructionError(3, AccountDataTooSmall)");
+    clone.push(first.clone());
+    clone.push(second.clone());
+
+    assert_eq!(queue.failed_commits(), vec![first.clone(), second.clone()]);
+    assert_eq!(queue.drain(), vec![first, second]);
+    assert!(clone.is_empty());
+}
+
+// -----------------
+// Simulating commits
+// -----------------
+#[derive(Default)]
+struct RecordingSender {
+    sent: Arc<Mutex<Vec<Signature>>>,
+}
+
+#[async_trait]
+impl CommitTransactionSender for RecordingSender {
+    async fn send_commit_transaction(
+        &self,
+        transaction: &Transaction,
+    ) -> Result<Signature, String> {
+        self.sent.lock().unwrap().push(transaction.signatures[0]);
+        Ok(transaction.signatures[0])
+    }
+}
+
+fn simulating_committer(
+    rpc_client: RpcClient,
+) -> (RemoteAccountCommitter, Arc<Mutex<Vec<Signature>>>) {
+    let sender = RecordingSender::default();
+    let sent = sender.sent.clone();
+    let committer = RemoteAccountCommitter::new(
+        rpc_client,
+        Arc::new(ValidatorContext::new(Keypair::new())),
+        0,
+        CircuitBreaker::disabled(),
+        CommitCostTracker::default(),
+    )
+    .with_sender(Box::new(sender))
+    .with_preflight_simulation(true);
+    (committer, sent)
+}
+
+fn commit_payload(committed_account: Pubkey) -> SendableCommitAccountsPayload {
+    let payer = Keypair::new();
+    SendableCommitAccountsPayload {
+        transaction: CommitAccountsTransaction {
+            transaction: Transaction::new_signed_with_payer(
+                &[],
+                Some(&payer.pubkey()),
+                &[&payer],
+                Hash::new_unique(),
+            ),
+            undelegated_accounts: HashSet::new(),
+            committed_only_accounts: HashSet::from([committed_account]),
+            fee_lamports: 5_000,
+        },
+        committees: vec![],
+    }
+}
+
+#[tokio::test]
+async fn test_commit_failing_simulation_is_queued_instead_of_sent() {
+    let error = TransactionError::InstructionError(
+        2,
+        InstructionError::InvalidAccountData,
+    );
+    let logs = vec!["Program log: delegation record changed".to_string()];
+    let mocks = HashMap::from([(
+        RpcRequest::SimulateTransaction,
+        serde_json::json!({
+            "context": { "slot": 1 },
+            "value": { "err": error, "logs": logs },
+        }),
+    )]);
+    let (committer, sent) = simulating_committer(
+        RpcClient::new_mock_with_mocks("succeeds".to_string(), mocks),
+    );
+    let committed_account = Pubkey::new_unique();
+    let payload = commit_payload(committed_account);
+    let signature = payload.get_signature();
+
+    let pending_commits = committer
+        .send_commit_transactions(vec![payload])
+        .await
+        .unwrap();
+
+    assert!(pending_commits.is_empty());
+    assert!(sent.lock().unwrap().is_empty());
+    let failed_commits = committer.commit_error_queue().failed_commits();
+    assert_eq!(failed_commits.len(), 1);
+    assert_eq!(failed_commits[0].signature, signature);
+    assert_eq!(
+        failed_commits[0].committed_only_accounts,
+        HashSet::from([committed_account])
+    );
+    assert_eq!(failed_commits[0].error, error.to_string());
+    assert_eq!(failed_commits[0].logs, logs);
+}
+
+#[tokio::test]
+async fn test_commit_passing_simulation_is_sent() {
+    let (committer, sent) =
+        simulating_committer(RpcClient::new_mock("succeeds".to_string()));
+    let payload = commit_payload(Pubkey::new_unique());
+    let signature = payload.get_signature();
+
+    let pending_commits = committer
+        .send_commit_transactions(vec![payload])
+        .await
+        .unwrap();
+
+    assert_eq!(pending_commits.len(), 1);
+    assert_eq!(pending_commits[0].signature, signature);
+    assert_eq!(*sent.lock().unwrap(), vec![signature]);
+    assert!(committer.commit_error_queue().is_empty());
+}
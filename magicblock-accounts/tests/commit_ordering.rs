@@ -0,0 +1,70 @@
+use std::time::Duration;
+
+use magicblock_accounts::CommitOrdering;
+use solana_sdk::pubkey::Pubkey;
+use tokio::time::timeout;
+
+const WAIT: Duration = Duration::from_millis(50);
+
+#[tokio::test]
+async fn test_commits_of_same_account_run_in_order() {
+    let ordering = CommitOrdering::new(16);
+    let (lobby, player) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+    let first = ordering.register(&[lobby]);
+    let second = ordering.register(&[player, lobby]);
+    assert_eq!(ordering.pending_accounts_len(), 2);
+
+    // The second commit waits for the first one touching the same account
+    let second = tokio::spawn(second.wait());
+    let first_guard = first.wait().await;
+    assert!(!first_guard.waited());
+    tokio::time::sleep(WAIT).await;
+    assert!(!second.is_finished());
+
+    drop(first_guard);
+    let second_guard = timeout(WAIT, second).await.unwrap().unwrap();
+    assert!(second_guard.waited());
+    drop(second_guard);
+    assert_eq!(ordering.pending_accounts_len(), 0);
+}
+
+#[tokio::test]
+async fn test_commits_of_unrelated_accounts_run_concurrently() {
+    let ordering = CommitOrdering::new(16);
+
+    let first = ordering.register(&[Pubkey::new_unique()]);
+    let second = ordering.register(&[Pubkey::new_unique()]);
+
+    let _first_guard = first.wait().await;
+    let second_guard = timeout(WAIT, second.wait()).await.unwrap();
+    assert!(!second_guard.waited());
+}
+
+#[tokio::test]
+async fn test_commit_with_duplicate_accounts_does_not_wait_for_itself() {
+    let ordering = CommitOrdering::new(16);
+    let lobby = Pubkey::new_unique();
+
+    let turn = ordering.register(&[lobby, lobby]);
+    let guard = timeout(WAIT, turn.wait()).await.unwrap();
+    assert!(!guard.waited());
+    assert_eq!(ordering.pending_accounts_len(), 1);
+}
+
+#[tokio::test]
+async fn test_commits_respect_concurrency_limit() {
+    let ordering = CommitOrdering::new(1);
+
+    let first = ordering.register(&[Pubkey::new_unique()]);
+    let second = ordering.register(&[Pubkey::new_unique()]);
+
+    let first_guard = first.wait().await;
+    let second = tokio::spawn(second.wait());
+    tokio::time::sleep(WAIT).await;
+    assert!(!second.is_finished());
+
+    drop(first_guard);
+    let second_guard = timeout(WAIT, second).await.unwrap().unwrap();
+    assert!(second_guard.waited());
+}
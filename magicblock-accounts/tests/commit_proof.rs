@@ -0,0 +1,64 @@
+use magicblock_accounts::{commit_state_hash, CommitProof, MEMO_PROGRAM_ID};
+use solana_sdk::{hash::Hash, pubkey::Pubkey};
+
+#[test]
+fn test_commit_proof_memo_roundtrip() {
+    let proof = CommitProof::new(Pubkey::new_unique(), 42, &[1, 2, 3]);
+
+    let memo = proof.to_memo();
+    assert!(memo.starts_with("mb-commit:v1:"));
+    assert_eq!(CommitProof::from_memo(&memo), Some(proof));
+
+    let ix = proof.to_instruction();
+    assert_eq!(ix.program_id, MEMO_PROGRAM_ID);
+    assert!(ix.accounts.is_empty());
+    assert_eq!(CommitProof::from_instruction(&ix), Some(proof));
+}
+
+#[test]
+fn test_commit_proof_rejects_invalid_memos() {
+    let proof = CommitProof::new(Pubkey::new_unique(), 42, &[1, 2, 3]);
+    let memo = proof.to_memo();
+
+    assert_eq!(CommitProof::from_memo("hello"), None);
+    assert_eq!(CommitProof::from_memo(&format!("{memo}:extra")), None);
+    assert_eq!(
+        CommitProof::from_memo(&memo.replace("mb-commit:v1", "mb-commit:v2")),
+        None
+    );
+
+    let mut ix = proof.to_instruction();
+    ix.program_id = Pubkey::new_unique();
+    assert_eq!(CommitProof::from_instruction(&ix), None);
+}
+
+#[test]
+fn test_commit_proof_verify() {
+    let pubkey = Pubkey::new_unique();
+    let proof = CommitProof::new(pubkey, 42, &[1, 2, 3]);
+
+    assert!(proof.verify(42, &[1, 2, 3]));
+    assert!(!proof.verify(43, &[1, 2, 3]));
+    assert!(!proof.verify(42, &[1, 2, 4]));
+    assert!(!proof.verify(42, &[1, 2, 3, 0]));
+
+    let other_account = CommitProof {
+        pubkey: Pubkey::new_unique(),
+        ..proof
+    };
+    assert!(!other_account.verify(42, &[1, 2, 3]));
+}
+
+#[test]
+fn test_commit_state_hash_binds_slot_and_account() {
+    let pubkey = Pubkey::new_unique();
+    let hash = commit_state_hash(&pubkey, 1, &[1, 2, 3]);
+
+    assert_ne!(hash, Hash::default());
+    assert_eq!(hash, commit_state_hash(&pubkey, 1, &[1, 2, 3]));
+    assert_ne!(hash, commit_state_hash(&pubkey, 2, &[1, 2, 3]));
+    assert_ne!(
+        hash,
+        commit_state_hash(&Pubkey::new_unique(), 1, &[1, 2, 3])
+    );
+}
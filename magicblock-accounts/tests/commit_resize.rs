@@ -0,0 +1,229 @@
+use std::sync::Arc;
+
+use dlp::instruction::{commit_state, finalize, CommitAccountArgs};
+use magicblock_accounts::{
+    errors::AccountsError, AccountCommittee, AccountCommitter,
+    CommitCostTracker, CommitProof, RemoteAccountCommitter,
+    UndelegationRequest, MEMO_PROGRAM_ID,
+};
+use magicblock_core::circuit_breaker::CircuitBreaker;
+use magicblock_program::ValidatorContext;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    account::AccountSharedData, instruction::Instruction,
+    packet::PACKET_DATA_SIZE, pubkey::Pubkey, signature::Keypair,
+    signer::Signer,
+};
+
+fn setup() -> (RemoteAccountCommitter, Pubkey) {
+    setup_with_commit_proofs(true)
+}
+
+fn setup_with_commit_proofs(
+    commit_proofs: bool,
+) -> (RemoteAccountCommitter, Pubkey) {
+    let authority = Keypair::new();
+    let committer = authority.pubkey();
+    let account_committer = RemoteAccountCommitter::new(
+        RpcClient::new_mock("succeeds".to_string()),
+        Arc::new(ValidatorContext::new(authority)),
+        0,
+        CircuitBreaker::disabled(),
+        CommitCostTracker::default(),
+    )
+    .with_commit_proofs(commit_proofs);
+    (account_committer, committer)
+}
+
+fn committee(
+    pubkey: Pubkey,
+    data: Vec<u8>,
+    undelegation_request: Option<UndelegationRequest>,
+) -> AccountCommittee {
+    let mut account_data =
+        AccountSharedData::new(1_000_000, data.len(), &Pubkey::new_unique());
+    account_data.set_data_from_slice(&data);
+    AccountCommittee {
+        pubkey,
+        account_data,
+        slot: 42,
+        undelegation_request,
+    }
+}
+
+/// The instructions of the commit transaction following the compute budget
+/// instructions.
+async fn commit_instructions(
+    account_committer: &RemoteAccountCommitter,
+    committees: Vec<AccountCommittee>,
+) -> Vec<Instruction> {
+    let payload = account_committer
+        .create_commit_accounts_transaction(committees)
+        .await
+        .unwrap();
+    let message = payload.transaction.unwrap().transaction.message;
+    message
+        .instructions
+        .iter()
+        .skip(2)
+        .map(|ix| Instruction {
+            program_id: message.account_keys[ix.program_id_index as usize],
+            accounts: vec![],
+            data: ix.data.clone(),
+        })
+        .collect()
+}
+
+fn expected_instructions(
+    committer: Pubkey,
+    pubkey: Pubkey,
+    data: &[u8],
+) -> Vec<(Pubkey, Vec<u8>)> {
+    let commit_args = CommitAccountArgs {
+        slot: 42,
+        allow_undelegation: false,
+        data: data.to_vec(),
+    };
+    [
+        commit_state(committer, pubkey, commit_args),
+        CommitProof::new(pubkey, 42, data).to_instruction(),
+        finalize(committer, pubkey, committer),
+    ]
+    .into_iter()
+    .map(|ix| (ix.program_id, ix.data))
+    .collect()
+}
+
+fn program_ids_and_data(ixs: Vec<Instruction>) -> Vec<(Pubkey, Vec<u8>)> {
+    ixs.into_iter().map(|ix| (ix.program_id, ix.data)).collect()
+}
+
+#[tokio::test]
+async fn test_commit_grown_account_in_single_step() {
+    let (account_committer, committer) = setup();
+    let pubkey = Pubkey::new_unique();
+    // Larger than the account was when it was delegated, finalizing the
+    // commit resizes it on the base layer
+    let data = vec![1; 256];
+
+    let ixs = commit_instructions(
+        &account_committer,
+        vec![committee(pubkey, data.clone(), None)],
+    )
+    .await;
+    assert_eq!(
+        program_ids_and_data(ixs),
+        expected_instructions(committer, pubkey, &data)
+    );
+}
+
+#[tokio::test]
+async fn test_commit_shrunk_account_in_single_step() {
+    let (account_committer, committer) = setup();
+    let pubkey = Pubkey::new_unique();
+
+    let ixs = commit_instructions(
+        &account_committer,
+        vec![committee(pubkey, vec![], None)],
+    )
+    .await;
+    assert_eq!(
+        program_ids_and_data(ixs),
+        expected_instructions(committer, pubkey, &[])
+    );
+}
+
+#[tokio::test]
+async fn test_commit_ephemeral_only_account_discards_its_data() {
+    let (account_committer, _) = setup();
+    let pubkey = Pubkey::new_unique();
+    let owner = Pubkey::new_unique();
+
+    let ixs = commit_instructions(
+        &account_committer,
+        vec![committee(
+            pubkey,
+            vec![1; 256],
+            Some(UndelegationRequest {
+                owner,
+                refund: Some(Pubkey::new_unique()),
+            }),
+        )],
+    )
+    .await;
+    // Commit, proof, finalize and undelegate
+    assert_eq!(ixs.len(), 4);
+    assert_eq!(
+        CommitProof::from_instruction(&ixs[1]),
+        Some(CommitProof::new(pubkey, 42, &[]))
+    );
+}
+
+#[tokio::test]
+async fn test_commit_too_large_to_land_is_rejected() {
+    let (account_committer, _) = setup();
+    let pubkey = Pubkey::new_unique();
+
+    let result = account_committer
+        .create_commit_accounts_transaction(vec![committee(
+            pubkey,
+            vec![1; 2_000],
+            None,
+        )])
+        .await;
+    assert!(matches!(
+        result,
+        Err(AccountsError::CommitTransactionTooLarge(_, pubkeys))
+            if pubkeys == vec![pubkey]
+    ));
+}
+
+#[tokio::test]
+async fn test_commit_without_proofs_unless_enabled() {
+    let (account_committer, committer) = setup_with_commit_proofs(false);
+    let pubkey = Pubkey::new_unique();
+    let data = vec![1; 32];
+
+    let ixs = commit_instructions(
+        &account_committer,
+        vec![committee(pubkey, data.clone(), None)],
+    )
+    .await;
+    let expected = expected_instructions(committer, pubkey, &data)
+        .into_iter()
+        .filter(|(program_id, _)| *program_id != MEMO_PROGRAM_ID)
+        .collect::<Vec<_>>();
+    assert_eq!(program_ids_and_data(ixs), expected);
+}
+
+#[tokio::test]
+async fn test_commit_proofs_are_omitted_if_they_do_not_fit() {
+    let (without_proofs, _) = setup_with_commit_proofs(false);
+    let (with_proofs, _) = setup_with_commit_proofs(true);
+    let pubkey = Pubkey::new_unique();
+
+    // Find the largest account data that can be committed without proofs
+    let (mut fits, mut too_large) = (0, PACKET_DATA_SIZE);
+    while too_large - fits > 1 {
+        let data_len = (fits + too_large) / 2;
+        let result = without_proofs
+            .create_commit_accounts_transaction(vec![committee(
+                pubkey,
+                vec![1; data_len],
+                None,
+            )])
+            .await;
+        if result.is_ok() {
+            fits = data_len;
+        } else {
+            too_large = data_len;
+        }
+    }
+
+    let ixs = commit_instructions(
+        &with_proofs,
+        vec![committee(pubkey, vec![1; fits], None)],
+    )
+    .await;
+    assert!(ixs.iter().all(|ix| ix.program_id != MEMO_PROGRAM_ID));
+}
@@ -0,0 +1,57 @@
+use magicblock_accounts::{
+    commit_transaction_sender_from_strategy, default_jito_tip_accounts,
+    Cluster, CommitSendStrategy, JITO_MAINNET_TIP_ACCOUNTS,
+};
+use solana_sdk::genesis_config::ClusterType;
+use url::Url;
+
+const RPC_URL: &str = "http://localhost:8899";
+
+#[test]
+fn test_only_jito_bundles_pay_a_tip() {
+    let rpc = commit_transaction_sender_from_strategy(
+        &CommitSendStrategy::Rpc,
+        RPC_URL,
+        Default::default(),
+    );
+    assert_eq!(rpc.tip_lamports(), 0);
+
+    let fanout = commit_transaction_sender_from_strategy(
+        &CommitSendStrategy::Fanout {
+            urls: vec![Url::parse("http://localhost:9899").unwrap()],
+        },
+        RPC_URL,
+        Default::default(),
+    );
+    assert_eq!(fanout.tip_lamports(), 0);
+
+    let jito = commit_transaction_sender_from_strategy(
+        &CommitSendStrategy::JitoBundle {
+            block_engine_url: Url::parse(
+                "https://mainnet.block-engine.jito.wtf/api/v1/bundles",
+            )
+            .unwrap(),
+            tip_lamports: 10_000,
+            tip_accounts: JITO_MAINNET_TIP_ACCOUNTS.to_vec(),
+        },
+        RPC_URL,
+        Default::default(),
+    );
+    assert_eq!(jito.tip_lamports(), 10_000);
+}
+
+#[test]
+fn test_jito_tip_accounts_are_only_known_for_mainnet() {
+    assert_eq!(
+        default_jito_tip_accounts(&Cluster::Known(ClusterType::MainnetBeta)),
+        Some(JITO_MAINNET_TIP_ACCOUNTS.to_vec())
+    );
+    for cluster in [
+        Cluster::Known(ClusterType::Devnet),
+        Cluster::Known(ClusterType::Testnet),
+        Cluster::Known(ClusterType::Development),
+        Cluster::Custom(Url::parse(RPC_URL).unwrap()),
+    ] {
+        assert_eq!(default_jito_tip_accounts(&cluster), None);
+    }
+}
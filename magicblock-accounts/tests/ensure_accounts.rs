@@ -1,12 +1,18 @@
-use std::{collections::HashSet, sync::Arc};
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
 use conjunto_transwise::{
     transaction_accounts_extractor::TransactionAccountsExtractorImpl,
     transaction_accounts_holder::TransactionAccountsHolder,
     transaction_accounts_validator::TransactionAccountsValidatorImpl,
 };
+use futures_util::future::{pending, ready, BoxFuture};
 use magicblock_account_cloner::{
-    AccountCloner, RemoteAccountClonerClient, RemoteAccountClonerWorker,
+    AccountCloneHook, AccountCloneHooks, AccountCloner,
+    RemoteAccountClonerClient, RemoteAccountClonerWorker,
 };
 use magicblock_account_dumper::AccountDumperStub;
 use magicblock_account_fetcher::AccountFetcherStub;
@@ -15,7 +21,15 @@ use magicblock_accounts::{
     errors::AccountsError, ExternalAccountsManager, LifecycleMode,
 };
 use magicblock_accounts_api::InternalAccountProviderStub;
-use solana_sdk::pubkey::Pubkey;
+use solana_sdk::{
+    account::Account,
+    hash::Hash,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::{Keypair, Signature},
+    signer::Signer,
+    transaction::{SanitizedTransaction, Transaction},
+};
 use stubs::{
     account_committer_stub::AccountCommitterStub,
     scheduled_commits_processor_stub::ScheduledCommitsProcessorStub,
@@ -41,6 +55,24 @@ fn setup_with_lifecycle(
     account_updates: AccountUpdatesStub,
     account_dumper: AccountDumperStub,
     lifecycle: LifecycleMode,
+) -> (StubbedAccountsManager, CancellationToken, JoinHandle<()>) {
+    setup_with_clone_hooks(
+        internal_account_provider,
+        account_fetcher,
+        account_updates,
+        account_dumper,
+        lifecycle,
+        AccountCloneHooks::default(),
+    )
+}
+
+fn setup_with_clone_hooks(
+    internal_account_provider: InternalAccountProviderStub,
+    account_fetcher: AccountFetcherStub,
+    account_updates: AccountUpdatesStub,
+    account_dumper: AccountDumperStub,
+    lifecycle: LifecycleMode,
+    clone_hooks: AccountCloneHooks,
 ) -> (StubbedAccountsManager, CancellationToken, JoinHandle<()>) {
     let cancellation_token = CancellationToken::new();
 
@@ -54,7 +86,8 @@ fn setup_with_lifecycle(
         Some(1_000_000_000),
         lifecycle.to_account_cloner_permissions(),
         Pubkey::new_unique(),
-    );
+    )
+    .with_clone_hooks(clone_hooks);
     let remote_account_cloner_client =
         RemoteAccountClonerClient::new(&remote_account_cloner_worker);
     let remote_account_cloner_worker_handle = {
@@ -73,8 +106,12 @@ fn setup_with_lifecycle(
         transaction_accounts_extractor: TransactionAccountsExtractorImpl,
         transaction_accounts_validator: TransactionAccountsValidatorImpl,
         scheduled_commits_processor: ScheduledCommitsProcessorStub::default(),
+        commit_policy: lifecycle.to_commit_policy(),
         lifecycle,
         external_commitable_accounts: Default::default(),
+        dirty_commitable_accounts: Default::default(),
+        ephemeral_only_accounts: Default::default(),
+        commit_cost_tracker: Default::default(),
     };
     (
         external_account_manager,
@@ -926,3 +963,225 @@ async fn test_ensure_accounts_already_cloned_can_be_reused_without_updates() {
     cancel.cancel();
     assert!(handle.await.is_ok());
 }
+
+#[tokio::test]
+async fn test_ensure_accounts_offline_never_clones_nor_commits() {
+    init_logger!();
+
+    let internal_account_provider = InternalAccountProviderStub::default();
+    let account_fetcher = AccountFetcherStub::default();
+    let account_updates = AccountUpdatesStub::default();
+    let account_dumper = AccountDumperStub::default();
+
+    let (manager, cancel, handle) = setup_with_lifecycle(
+        internal_account_provider.clone(),
+        account_fetcher.clone(),
+        account_updates.clone(),
+        account_dumper.clone(),
+        LifecycleMode::Offline,
+    );
+
+    // Accounts exist on chain, but are never fetched while offline
+    let undelegated_account = Pubkey::new_unique();
+    let delegated_account = Pubkey::new_unique();
+    account_fetcher.set_undelegated_account(undelegated_account, 42);
+    account_fetcher.set_delegated_account(delegated_account, 42, 11);
+
+    // Ensure accounts
+    let result = manager
+        .ensure_accounts_from_holder(
+            TransactionAccountsHolder {
+                readonly: vec![undelegated_account],
+                writable: vec![delegated_account],
+                payer: Pubkey::new_unique(),
+            },
+            "tx-sig".to_string(),
+        )
+        .await;
+    assert!(result.is_ok());
+
+    // Check proper behaviour
+    assert_eq!(account_fetcher.get_fetch_count(&undelegated_account), 0);
+    assert_eq!(account_fetcher.get_fetch_count(&delegated_account), 0);
+    assert!(account_dumper.was_untouched(&undelegated_account));
+    assert!(account_dumper.was_untouched(&delegated_account));
+    assert!(manager.last_commit(&delegated_account).is_none());
+    assert!(matches!(
+        manager.commit_all_delegated().await,
+        Err(AccountsError::RemoteDisabled(_))
+    ));
+
+    // Cleanup
+    cancel.cancel();
+    assert!(handle.await.is_ok());
+}
+
+#[tokio::test]
+async fn test_ensure_accounts_offline_rejects_programs_missing_locally() {
+    init_logger!();
+
+    let internal_account_provider = InternalAccountProviderStub::default();
+    let account_fetcher = AccountFetcherStub::default();
+    let account_updates = AccountUpdatesStub::default();
+    let account_dumper = AccountDumperStub::default();
+
+    let (manager, cancel, handle) = setup_with_lifecycle(
+        internal_account_provider.clone(),
+        account_fetcher.clone(),
+        account_updates.clone(),
+        account_dumper.clone(),
+        LifecycleMode::Offline,
+    );
+
+    // The program exists on chain, but can't be cloned while offline
+    let program_id = Pubkey::new_unique();
+    account_fetcher.set_executable_account(program_id, 42);
+    let payer = Keypair::new();
+    let tx = SanitizedTransaction::from_transaction_for_tests(
+        Transaction::new_signed_with_payer(
+            &[Instruction::new_with_bytes(
+                program_id,
+                &[],
+                vec![AccountMeta::new(Pubkey::new_unique(), false)],
+            )],
+            Some(&payer.pubkey()),
+            &[&payer],
+            Hash::default(),
+        ),
+    );
+
+    // Ensure accounts
+    let result = manager.ensure_accounts(&tx).await;
+    assert!(matches!(result, Err(AccountsError::RemoteDisabled(_))));
+    assert_eq!(account_fetcher.get_fetch_count(&program_id), 0);
+
+    // Once the program exists locally the transaction may run
+    internal_account_provider.set(program_id, Default::default());
+    let result = manager.ensure_accounts(&tx).await;
+    assert!(result.is_ok());
+    assert!(account_dumper.was_untouched(&program_id));
+
+    // Cleanup
+    cancel.cancel();
+    assert!(handle.await.is_ok());
+}
+
+#[derive(Default)]
+struct RecordingCloneHook {
+    events: Mutex<Vec<(&'static str, Pubkey)>>,
+}
+
+impl AccountCloneHook for RecordingCloneHook {
+    fn before_dump<'a>(
+        &'a self,
+        pubkey: &'a Pubkey,
+        _account: &'a mut Account,
+    ) -> BoxFuture<'a, ()> {
+        self.events.lock().unwrap().push(("before_dump", *pubkey));
+        Box::pin(ready(()))
+    }
+
+    fn after_dump<'a>(
+        &'a self,
+        pubkey: &'a Pubkey,
+        _account: &'a Account,
+        _signature: &'a Signature,
+    ) -> BoxFuture<'a, ()> {
+        self.events.lock().unwrap().push(("after_dump", *pubkey));
+        Box::pin(ready(()))
+    }
+}
+
+/// Replaces the data of the dumped accounts, i.e. like re-encrypting it
+struct ReplacingCloneHook;
+
+impl AccountCloneHook for ReplacingCloneHook {
+    fn before_dump<'a>(
+        &'a self,
+        _pubkey: &'a Pubkey,
+        account: &'a mut Account,
+    ) -> BoxFuture<'a, ()> {
+        account.data = vec![42; 3];
+        Box::pin(ready(()))
+    }
+}
+
+/// Modifies the account but never finishes, thus it needs to time out for
+/// cloning to continue and its modification is discarded
+struct StalledCloneHook;
+
+impl AccountCloneHook for StalledCloneHook {
+    fn before_dump<'a>(
+        &'a self,
+        _pubkey: &'a Pubkey,
+        account: &'a mut Account,
+    ) -> BoxFuture<'a, ()> {
+        account.data = vec![0; 3];
+        Box::pin(pending())
+    }
+}
+
+#[tokio::test]
+async fn test_ensure_accounts_invokes_clone_hooks_only_for_dumps() {
+    init_logger!();
+
+    let internal_account_provider = InternalAccountProviderStub::default();
+    let account_fetcher = AccountFetcherStub::default();
+    let account_updates = AccountUpdatesStub::default();
+    let account_dumper = AccountDumperStub::default();
+
+    let clone_hooks = AccountCloneHooks::new(Duration::from_millis(50));
+    let recording_hook = Arc::new(RecordingCloneHook::default());
+    clone_hooks.register("replacing", Arc::new(ReplacingCloneHook));
+    clone_hooks.register("stalled", Arc::new(StalledCloneHook));
+    clone_hooks.register("recording", recording_hook.clone());
+    assert_eq!(
+        clone_hooks.hook_names(),
+        vec!["replacing", "stalled", "recording"]
+    );
+
+    let (manager, cancel, handle) = setup_with_clone_hooks(
+        internal_account_provider.clone(),
+        account_fetcher.clone(),
+        account_updates.clone(),
+        account_dumper.clone(),
+        LifecycleMode::Ephemeral,
+        clone_hooks,
+    );
+
+    let undelegated_account = Pubkey::new_unique();
+    account_updates.set_first_subscribed_slot(undelegated_account, 41);
+    account_fetcher.set_undelegated_account(undelegated_account, 42);
+
+    // The account is dumped by the first transaction and reused by the second
+    for _ in 0..2 {
+        let result = manager
+            .ensure_accounts_from_holder(
+                TransactionAccountsHolder {
+                    readonly: vec![undelegated_account],
+                    writable: vec![],
+                    payer: Pubkey::new_unique(),
+                },
+                "tx-sig".to_string(),
+            )
+            .await;
+        assert!(result.is_ok());
+    }
+
+    // Check proper behaviour
+    assert_eq!(
+        account_dumper.dumped_data(&undelegated_account),
+        Some(vec![42; 3])
+    );
+    assert_eq!(
+        *recording_hook.events.lock().unwrap(),
+        vec![
+            ("before_dump", undelegated_account),
+            ("after_dump", undelegated_account),
+        ]
+    );
+
+    // Cleanup
+    cancel.cancel();
+    assert!(handle.await.is_ok());
+}
@@ -49,6 +49,7 @@ impl AccountCommitter for AccountCommitterStub {
                 transaction,
                 undelegated_accounts: HashSet::new(),
                 committed_only_accounts: HashSet::new(),
+                fee_lamports: 0,
             }),
             committees: committees
                 .iter()
@@ -58,6 +59,13 @@ impl AccountCommitter for AccountCommitterStub {
         Ok(payload)
     }
 
+    async fn resign_commit_transactions(
+        &self,
+        _payloads: &mut [SendableCommitAccountsPayload],
+    ) -> AccountsResult<()> {
+        Ok(())
+    }
+
     async fn send_commit_transactions(
         &self,
         payloads: Vec<SendableCommitAccountsPayload>,
@@ -85,12 +93,15 @@ impl AccountCommitter for AccountCommitterStub {
     async fn confirm_pending_commits(
         &self,
         pending_commits: Vec<PendingCommitTransaction>,
-    ) {
+    ) -> Vec<(Signature, bool)> {
+        let mut confirmed = Vec::new();
         for commit in pending_commits {
             self.confirmed_transactions
                 .write()
                 .unwrap()
                 .insert(commit.signature);
+            confirmed.push((commit.signature, true));
         }
+        confirmed
     }
 }
@@ -5,6 +5,7 @@ use magicblock_accounts::{
     errors::AccountsResult, AccountCommitter, ScheduledCommitsProcessor,
 };
 use magicblock_accounts_api::InternalAccountProvider;
+use solana_sdk::pubkey::Pubkey;
 
 #[derive(Default)]
 pub struct ScheduledCommitsProcessorStub {}
@@ -15,6 +16,7 @@ impl ScheduledCommitsProcessor for ScheduledCommitsProcessorStub {
         &self,
         _committer: &Arc<AC>,
         _account_provider: &IAP,
+        _is_delegated: &(dyn Fn(&Pubkey) -> bool + Sync),
     ) -> AccountsResult<()> {
         Ok(())
     }
@@ -37,6 +37,21 @@ pub enum ApiError {
     #[error("Failed to start metrics service: {0}")]
     FailedToStartMetricsService(std::io::Error),
 
+    #[error("Failed to initialize telemetry: {0}")]
+    FailedToInitTelemetry(#[from] magicblock_telemetry::errors::TelemetryError),
+
+    #[error("Remote cluster at '{0}' is unreachable: {1}")]
+    RemoteClusterUnreachable(String, String),
+
+    #[error("Unable to get the clock of the remote cluster at '{0}': {1}")]
+    RemoteClusterClockUnavailable(String, String),
+
+    #[error("Clock skew of {1}s to the remote cluster at '{0}' exceeds the max of {2}s")]
+    RemoteClusterClockSkewTooLarge(String, i64, u64),
+
+    #[error("Chaos injection is only supported by builds with the 'dev-context-only-utils' feature")]
+    ChaosInjectionNotSupported,
+
     #[error("Ledger Path is missing a parent directory: {0}")]
     LedgerPathIsMissingParent(String),
 
@@ -49,6 +64,9 @@ pub enum ApiError {
     #[error("Ledger could not write faucet keypair file: {0} ({1})")]
     LedgerCouldNotWriteFaucetKeypair(String, String),
 
+    #[error("Configured faucet keypair file is invalid: {0} ({1})")]
+    InvalidFaucetKeypair(String, String),
+
     #[error("Ledger Path has an invalid validator keypair file: {0} ({1})")]
     LedgerInvalidValidatorKeypair(String, String),
 
@@ -60,4 +78,40 @@ pub enum ApiError {
 
     #[error("Ledger validator keypair '{0}' needs to match the provided one '{1}'")]
     LedgerValidatorKeypairNotMatchingProvidedKeypair(String, String),
+
+    #[error("Ledger Path has an invalid previous validator authorities file: {0} ({1})")]
+    LedgerInvalidPreviousValidatorAuthorities(String, String),
+
+    #[error("Ledger could not write previous validator authorities file: {0} ({1})")]
+    LedgerCouldNotWritePreviousValidatorAuthorities(String, String),
+
+    #[error("Ledger Path has an invalid allowed programs file: {0} ({1})")]
+    LedgerInvalidAllowedPrograms(String, String),
+
+    #[error("Ledger Path does not exist, thus it has no inputs to replay: {0}")]
+    LedgerIsMissingReplayInputs(String),
+
+    #[error("Replaying ledger inputs requires the ledger path to be configured")]
+    LedgerInputsReplayRequiresPath,
+
+    #[error("Ledger input was recorded with schema version {0}, but only versions up to {1} are supported")]
+    UnsupportedReplayInputSchemaVersion(u16, u16),
+
+    #[error("Unknown feature '{0}' configured to be active at genesis")]
+    UnknownGenesisFeature(String),
+
+    #[error("Failed to read data of genesis account from '{0}': {1}")]
+    FailedToReadGenesisAccountData(String, String),
+
+    #[error("Failed to open account dump audit log at '{0}': {1}")]
+    FailedToOpenAccountDumpAuditLog(String, std::io::Error),
+
+    #[error("Failed to hydrate {0} accounts that may be delegated to us, see the hydrate report")]
+    FailedToHydrateDelegatedAccounts(usize),
+
+    #[error("Validator identity cannot be rotated before it was set")]
+    ValidatorIdentityNotSet,
+
+    #[error("Failed to load IDL override: {0}")]
+    FailedToLoadIdlOverride(#[from] magicblock_accounts_api::errors::AccountsApiError),
 }
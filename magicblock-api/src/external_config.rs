@@ -1,7 +1,12 @@
-use std::collections::HashSet;
+use std::{collections::HashSet, path::PathBuf, time::Duration};
 
-use magicblock_accounts::{AccountsConfig, Cluster, LifecycleMode};
-use magicblock_config::errors::ConfigResult;
+use magicblock_account_cloner::{ClonePolicy, RefreshPolicy};
+use magicblock_accounts::{
+    default_jito_tip_accounts, AccountsConfig, Cluster, CommitBudget,
+    CommitPolicy, CommitSendStrategy, LifecycleMode, PolicyOverrides,
+};
+use magicblock_bank::blockhash_expiry::BlockhashExpiry;
+use magicblock_config::errors::{ConfigError, ConfigResult};
 use solana_sdk::{genesis_config::ClusterType, pubkey::Pubkey};
 
 pub(crate) fn try_convert_accounts_config(
@@ -13,15 +18,158 @@ pub(crate) fn try_convert_accounts_config(
     let payer_init_lamports = conf.payer.try_init_lamports()?;
     let allowed_program_ids =
         allowed_program_ids_from_allowed_programs(&conf.allowed_programs);
+    let commit_budget =
+        conf.commit_budget
+            .max_lamports
+            .map(|max_lamports| CommitBudget {
+                max_lamports,
+                window: Duration::from_secs(conf.commit_budget.window_secs),
+            });
+    let mint_authority_overrides = mint_authority_overrides_from_config(
+        &conf.remote,
+        &conf.mint_authority_overrides,
+    )?;
+    let refresh_poll_interval = conf
+        .subscriptions
+        .poll_interval_millis
+        .map(Duration::from_millis);
+    let policy_overrides = policy_overrides_from_config(
+        &lifecycle,
+        &conf.policies,
+        refresh_poll_interval,
+    );
+    let commit_send_strategy =
+        commit_send_strategy_from_config(&remote_cluster, &conf.commit_send)?;
     Ok(AccountsConfig {
         remote_cluster,
         lifecycle,
         commit_compute_unit_price,
         payer_init_lamports,
         allowed_program_ids,
+        commit_budget,
+        commit_send_strategy,
+        simulate_commits: conf.simulate_commits,
+        commit_proofs: conf.commit_proofs,
+        max_concurrent_commits: conf.commit.max_concurrent_commits,
+        refresh_poll_interval,
+        policy_overrides,
+        mint_authority_overrides,
+        idl_overrides: conf
+            .idl_overrides
+            .iter()
+            .map(|x| (x.program_id, PathBuf::from(&x.path)))
+            .collect(),
+        mainnet_rent_semantics: conf.mainnet_rent_semantics,
+        dump_audit_log: conf.dump.audit_log.as_ref().map(PathBuf::from),
+        dump_metrics: conf.dump.metrics,
     })
 }
 
+pub(crate) fn blockhash_expiry_from_config(
+    expiry: &magicblock_config::BlockhashExpiryConfig,
+) -> BlockhashExpiry {
+    use magicblock_config::BlockhashExpiryConfig::*;
+    match expiry {
+        Slots => BlockhashExpiry::Slots,
+        ExtendedSlots { max_age_slots } => {
+            BlockhashExpiry::MaxAgeSlots(*max_age_slots)
+        }
+        WallClock => BlockhashExpiry::WallClock,
+    }
+}
+
+fn commit_send_strategy_from_config(
+    remote_cluster: &Cluster,
+    strategy: &magicblock_config::CommitSendStrategy,
+) -> ConfigResult<CommitSendStrategy> {
+    use magicblock_config::CommitSendStrategy::*;
+    Ok(match strategy {
+        Rpc => CommitSendStrategy::Rpc,
+        Fanout { urls } => CommitSendStrategy::Fanout { urls: urls.clone() },
+        JitoBundle {
+            block_engine_url,
+            tip_lamports,
+            tip_accounts,
+        } => CommitSendStrategy::JitoBundle {
+            block_engine_url: block_engine_url.clone(),
+            tip_lamports: *tip_lamports,
+            tip_accounts: if tip_accounts.is_empty() {
+                default_jito_tip_accounts(remote_cluster)
+                    .ok_or(ConfigError::JitoTipAccountsRequired)?
+            } else {
+                tip_accounts.clone()
+            },
+        },
+    })
+}
+
+/// Resolves the policies that are partially overridden against the ones
+/// implied by the [lifecycle].
+fn policy_overrides_from_config(
+    lifecycle: &LifecycleMode,
+    policies: &magicblock_config::AccountPoliciesConfig,
+    refresh_poll_interval: Option<Duration>,
+) -> PolicyOverrides {
+    let clone = (policies.clone_feepayer_accounts.is_some()
+        || policies.clone_undelegated_accounts.is_some()
+        || policies.clone_delegated_accounts.is_some()
+        || policies.clone_program_accounts.is_some())
+    .then(|| {
+        let default = lifecycle.to_clone_policy();
+        ClonePolicy {
+            feepayer_accounts: policies
+                .clone_feepayer_accounts
+                .unwrap_or(default.feepayer_accounts),
+            undelegated_accounts: policies
+                .clone_undelegated_accounts
+                .unwrap_or(default.undelegated_accounts),
+            delegated_accounts: policies
+                .clone_delegated_accounts
+                .unwrap_or(default.delegated_accounts),
+            program_accounts: policies
+                .clone_program_accounts
+                .unwrap_or(default.program_accounts),
+        }
+    });
+    let refresh = policies.refresh_accounts.map(|refresh| {
+        match (refresh, refresh_poll_interval) {
+            (false, _) => RefreshPolicy::Off,
+            (true, Some(interval)) => RefreshPolicy::Poll { interval },
+            (true, None) => RefreshPolicy::Websocket,
+        }
+    });
+    let commit = (policies.commit_delegated_accounts.is_some()
+        || policies.process_scheduled_commits.is_some())
+    .then(|| {
+        let default = lifecycle.to_commit_policy();
+        CommitPolicy {
+            commit_delegated_accounts: policies
+                .commit_delegated_accounts
+                .unwrap_or(default.commit_delegated_accounts),
+            process_scheduled_commits: policies
+                .process_scheduled_commits
+                .unwrap_or(default.process_scheduled_commits),
+        }
+    });
+    PolicyOverrides {
+        clone,
+        refresh,
+        commit,
+    }
+}
+
+fn mint_authority_overrides_from_config(
+    remote: &magicblock_config::RemoteConfig,
+    overrides: &[magicblock_config::MintAuthorityOverride],
+) -> ConfigResult<HashSet<Pubkey>> {
+    if !overrides.is_empty()
+        && matches!(remote, magicblock_config::RemoteConfig::Mainnet)
+    {
+        return Err(ConfigError::MintAuthorityOverrideOnMainnet);
+    }
+    Ok(overrides.iter().map(|x| x.mint).collect())
+}
+
 fn cluster_from_remote(remote: &magicblock_config::RemoteConfig) -> Cluster {
     use magicblock_config::RemoteConfig::*;
     match remote {
@@ -1,14 +1,19 @@
 use std::path::Path;
 
 use magicblock_bank::bank::Bank;
+use magicblock_config::FaucetConfig;
 use magicblock_core::magic_program;
 use solana_sdk::{
-    account::Account, clock::Epoch, pubkey::Pubkey, signature::Keypair,
-    signer::Signer, system_program,
+    account::Account,
+    clock::Epoch,
+    pubkey::Pubkey,
+    signature::Keypair,
+    signer::{EncodableKey, Signer},
+    system_program,
 };
 
 use crate::{
-    errors::ApiResult,
+    errors::{ApiError, ApiResult},
     ledger::{read_faucet_keypair_from_ledger, write_faucet_keypair_to_ledger},
 };
 
@@ -47,16 +52,26 @@ pub(crate) fn fund_validator_identity(bank: &Bank, validator_id: &Pubkey) {
     fund_account(bank, validator_id, u64::MAX / 2);
 }
 
-/// Funds the faucet account.
-/// If the [create_new] is `false` then the faucet keypair will be read from the
-/// existing ledger and an error is raised if it is not found.
-/// Otherwise, a new faucet keypair will be created and saved to the ledger.
+/// Funds the faucet account with the lamports configured in [FaucetConfig].
+/// If a keypair file is configured the faucet keypair is read from it.
+/// Otherwise, if the [create_new] is `false` then the faucet keypair will be
+/// read from the existing ledger and an error is raised if it is not found.
+/// If [create_new] is `true`, a new faucet keypair will be created and saved
+/// to the ledger.
 pub(crate) fn funded_faucet(
     bank: &Bank,
     ledger_path: &Path,
+    faucet_config: &FaucetConfig,
     create_new: bool,
 ) -> ApiResult<Keypair> {
-    let faucet_keypair = if create_new {
+    let faucet_keypair = if let Some(keypair_path) = &faucet_config.keypair {
+        Keypair::read_from_file(keypair_path).map_err(|err| {
+            ApiError::InvalidFaucetKeypair(
+                keypair_path.to_string(),
+                err.to_string(),
+            )
+        })?
+    } else if create_new {
         let faucet_keypair = Keypair::new();
         write_faucet_keypair_to_ledger(ledger_path, &faucet_keypair)?;
         faucet_keypair
@@ -64,7 +79,7 @@ pub(crate) fn funded_faucet(
         read_faucet_keypair_from_ledger(ledger_path)?
     };
 
-    fund_account(bank, &faucet_keypair.pubkey(), u64::MAX / 2);
+    fund_account(bank, &faucet_keypair.pubkey(), faucet_config.lamports);
     Ok(faucet_keypair)
 }
 
@@ -0,0 +1,85 @@
+use std::fs;
+
+use magicblock_bank::{
+    builtins::{BuiltinPrototype, OPTIONAL_BUILTINS},
+    genesis_utils::{activate_all_features, activate_feature},
+};
+use magicblock_config::{GenesisAccountConfig, GenesisBuiltin};
+use solana_sdk::{
+    account::{Account, AccountSharedData},
+    bpf_loader, bpf_loader_deprecated,
+    clock::Epoch,
+    feature_set::FeatureSet,
+    genesis_config::GenesisConfig,
+    loader_v4,
+    pubkey::Pubkey,
+};
+
+use crate::errors::{ApiError, ApiResult};
+
+/// Applies the genesis section of the validator config to the
+/// [genesis_config] and returns the builtins that need to be registered in
+/// addition to the ones the bank always registers.
+pub(crate) fn customize_genesis(
+    genesis_config: &mut GenesisConfig,
+    config: &magicblock_config::GenesisConfig,
+) -> ApiResult<Vec<BuiltinPrototype>> {
+    if config.activate_all_features {
+        activate_all_features(genesis_config);
+    } else {
+        let known_features = FeatureSet::default().inactive;
+        for feature_id in &config.features {
+            if !known_features.contains(feature_id) {
+                return Err(ApiError::UnknownGenesisFeature(
+                    feature_id.to_string(),
+                ));
+            }
+            activate_feature(genesis_config, *feature_id);
+        }
+    }
+
+    for account in &config.accounts {
+        genesis_config.add_account(account.pubkey, genesis_account(account)?);
+    }
+
+    Ok(config
+        .builtins
+        .iter()
+        .filter_map(|builtin| {
+            let program_id = builtin_program_id(builtin);
+            OPTIONAL_BUILTINS
+                .iter()
+                .find(|prototype| prototype.program_id == program_id)
+                .cloned()
+        })
+        .collect())
+}
+
+fn genesis_account(
+    config: &GenesisAccountConfig,
+) -> ApiResult<AccountSharedData> {
+    let data = match &config.data_path {
+        Some(data_path) => fs::read(data_path).map_err(|err| {
+            ApiError::FailedToReadGenesisAccountData(
+                data_path.to_string(),
+                err.to_string(),
+            )
+        })?,
+        None => vec![],
+    };
+    Ok(AccountSharedData::from(Account {
+        lamports: config.lamports,
+        data,
+        owner: config.owner,
+        executable: config.executable,
+        rent_epoch: Epoch::MAX,
+    }))
+}
+
+fn builtin_program_id(builtin: &GenesisBuiltin) -> Pubkey {
+    match builtin {
+        GenesisBuiltin::BpfLoader => bpf_loader::id(),
+        GenesisBuiltin::BpfLoaderDeprecated => bpf_loader_deprecated::id(),
+        GenesisBuiltin::LoaderV4 => loader_v4::id(),
+    }
+}
@@ -0,0 +1,216 @@
+use std::{path::Path, sync::Arc};
+
+use log::*;
+use magicblock_accounts::AccountsManager;
+use magicblock_bank::bank::Bank;
+use magicblock_ledger::Ledger;
+use magicblock_rpc::identity_rotation::IdentityRotationRequest;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    errors::{ApiError, ApiResult},
+    fund_account::fund_validator_identity,
+    ledger::{
+        write_previous_validator_authorities_to_ledger,
+        write_validator_keypair_to_ledger,
+    },
+};
+
+/// Rotates the validator identity to the provided keypair without
+/// requiring a ledger reset.
+/// Commits that are pending are completed with the current identity first,
+/// afterwards all commits are signed with the new one.
+/// The new keypair is written to the ledger directory and thus needs to
+/// be provided when the validator is restarted.
+/// Returns the pubkey of the replaced identity.
+pub(crate) async fn rotate_validator_identity(
+    bank: &Bank,
+    ledger_path: &Path,
+    accounts_manager: &AccountsManager,
+    new_keypair: Keypair,
+) -> ApiResult<Pubkey> {
+    // 1. Finish commits with the identity that accounts were delegated to
+    //    and that accepted the scheduled commits
+    if !accounts_manager.lifecycle.is_offline() {
+        accounts_manager.process_scheduled_commits().await?;
+        accounts_manager.commit_delegated().await?;
+    }
+    switch_validator_identity(bank, ledger_path, new_keypair)
+}
+
+/// Persists the rotation and switches to the [new_keypair].
+fn switch_validator_identity(
+    bank: &Bank,
+    ledger_path: &Path,
+    new_keypair: Keypair,
+) -> ApiResult<Pubkey> {
+    let validator_context = bank.validator_context();
+    let previous_pubkey = validator_context
+        .try_validator_authority_id()
+        .ok_or(ApiError::ValidatorIdentityNotSet)?;
+    let new_pubkey = new_keypair.pubkey();
+    if new_pubkey == previous_pubkey {
+        return Ok(previous_pubkey);
+    }
+
+    // 1. Persist the rotation so that we can replay the ledger, including
+    //    the transactions signed by the previous identity, after a restart
+    let mut previous_authorities =
+        validator_context.previous_validator_authorities();
+    if !previous_authorities.contains(&previous_pubkey) {
+        previous_authorities.push(previous_pubkey);
+    }
+    write_previous_validator_authorities_to_ledger(
+        ledger_path,
+        &previous_authorities,
+    )?;
+    write_validator_keypair_to_ledger(ledger_path, &new_keypair)?;
+
+    // 2. Switch to the new identity
+    fund_validator_identity(bank, &new_pubkey);
+    validator_context
+        .rotate_validator_authority(new_keypair)
+        .ok_or(ApiError::ValidatorIdentityNotSet)?;
+
+    info!(
+        "Rotated validator identity from {} to {}",
+        previous_pubkey, new_pubkey
+    );
+    Ok(previous_pubkey)
+}
+
+/// Handles the identity rotations requested via the admin RPC one at a time
+/// until the validator is stopped.
+pub(crate) fn init_identity_rotation_handler(
+    bank: Arc<Bank>,
+    ledger: Arc<Ledger>,
+    accounts_manager: Arc<AccountsManager>,
+    mut requests: mpsc::Receiver<IdentityRotationRequest>,
+    token: CancellationToken,
+) -> tokio::task::JoinHandle<()> {
+    tokio::task::spawn(async move {
+        loop {
+            tokio::select! {
+                request = requests.recv() => {
+                    let Some(IdentityRotationRequest { keypair, respond_to }) =
+                        request
+                    else {
+                        break;
+                    };
+                    let result = rotate_validator_identity(
+                        &bank,
+                        ledger.ledger_path(),
+                        &accounts_manager,
+                        keypair,
+                    )
+                    .await
+                    .map_err(|err| {
+                        error!("Failed to rotate validator identity: {:?}", err);
+                        err.to_string()
+                    });
+                    // The requester may have given up waiting already
+                    let _ = respond_to.send(result);
+                }
+                _ = token.cancelled() => {
+                    break;
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use test_tools::{
+        bank::bank_with_fees_for_tests, validator::bank_with_started_validator,
+    };
+
+    use super::*;
+    use crate::ledger::{
+        read_previous_validator_authorities_from_ledger,
+        read_validator_keypair_from_ledger,
+    };
+
+    #[test]
+    fn test_switch_validator_identity_persists_the_rotation() {
+        let bank = bank_with_started_validator();
+        let ledger_dir = tempfile::tempdir().unwrap();
+        let ledger_path = ledger_dir.path().join("ledger");
+        let validator_context = bank.validator_context();
+        let first_pubkey = validator_context.validator_authority_id();
+
+        let second = Keypair::new();
+        assert_eq!(
+            switch_validator_identity(
+                &bank,
+                &ledger_path,
+                second.insecure_clone()
+            )
+            .unwrap(),
+            first_pubkey
+        );
+        assert_eq!(validator_context.validator_authority_id(), second.pubkey());
+        assert!(bank.get_balance(&second.pubkey()) > 0);
+        assert_eq!(
+            read_validator_keypair_from_ledger(&ledger_path)
+                .unwrap()
+                .pubkey(),
+            second.pubkey()
+        );
+
+        let third = Keypair::new();
+        assert_eq!(
+            switch_validator_identity(
+                &bank,
+                &ledger_path,
+                third.insecure_clone()
+            )
+            .unwrap(),
+            second.pubkey()
+        );
+        assert_eq!(
+            read_previous_validator_authorities_from_ledger(&ledger_path)
+                .unwrap(),
+            vec![first_pubkey, second.pubkey()]
+        );
+        assert_eq!(
+            validator_context.previous_validator_authorities(),
+            vec![first_pubkey, second.pubkey()]
+        );
+    }
+
+    #[test]
+    fn test_switch_to_the_current_validator_identity_is_a_noop() {
+        let bank = bank_with_started_validator();
+        let ledger_dir = tempfile::tempdir().unwrap();
+        let ledger_path = ledger_dir.path().join("ledger");
+        let current = bank.validator_context().validator_authority();
+
+        assert_eq!(
+            switch_validator_identity(&bank, &ledger_path, current).unwrap(),
+            bank.validator_context().validator_authority_id()
+        );
+        assert!(read_validator_keypair_from_ledger(&ledger_path).is_err());
+        assert!(bank
+            .validator_context()
+            .previous_validator_authorities()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_switch_validator_identity_before_it_was_set() {
+        let bank = bank_with_fees_for_tests();
+        let ledger_dir = tempfile::tempdir().unwrap();
+
+        assert!(matches!(
+            switch_validator_identity(
+                &bank,
+                &ledger_dir.path().join("ledger"),
+                Keypair::new()
+            ),
+            Err(ApiError::ValidatorIdentityNotSet)
+        ));
+    }
+}
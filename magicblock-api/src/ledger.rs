@@ -2,15 +2,17 @@ use std::{
     fs::{self, File, OpenOptions},
     path::{Path, PathBuf},
     process::exit,
+    str::FromStr,
 };
 
 use fd_lock::{RwLock, RwLockWriteGuard};
 use log::*;
 use magicblock_ledger::Ledger;
-use solana_sdk::{signature::Keypair, signer::EncodableKey};
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::EncodableKey};
 
 use crate::{
     errors::{ApiError, ApiResult},
+    replay_inputs::RawReplayInput,
     utils::fs::remove_directory_contents_if_exists,
 };
 
@@ -19,18 +21,7 @@ use crate::{
 // -----------------
 pub(crate) fn init(ledger_path: PathBuf, reset: bool) -> ApiResult<Ledger> {
     if reset {
-        remove_directory_contents_if_exists(ledger_path.as_path()).map_err(
-            |err| {
-                error!(
-                    "Error: Unable to remove {}: {}",
-                    ledger_path.display(),
-                    err
-                );
-                ApiError::UnableToCleanLedgerDirectory(
-                    ledger_path.display().to_string(),
-                )
-            },
-        )?;
+        purge(ledger_path.as_path())?;
     }
 
     fs::create_dir_all(&ledger_path)?;
@@ -38,6 +29,17 @@ pub(crate) fn init(ledger_path: PathBuf, reset: bool) -> ApiResult<Ledger> {
     Ok(Ledger::open(ledger_path.as_path())?)
 }
 
+/// Removes all data from the ledger directory. The keypairs, previous
+/// validator authorities and allowed programs stored next to it are kept.
+pub fn purge(ledger_path: &Path) -> ApiResult<()> {
+    remove_directory_contents_if_exists(ledger_path).map_err(|err| {
+        error!("Error: Unable to remove {}: {}", ledger_path.display(), err);
+        ApiError::UnableToCleanLedgerDirectory(
+            ledger_path.display().to_string(),
+        )
+    })
+}
+
 // -----------------
 // Lockfile
 // -----------------
@@ -154,6 +156,98 @@ pub(crate) fn write_validator_keypair_to_ledger(
     Ok(())
 }
 
+// -----------------
+// Previous Validator Authorities
+// -----------------
+fn previous_validator_authorities_path(
+    ledger_path: &Path,
+) -> ApiResult<PathBuf> {
+    let parent = ledger_parent_dir(ledger_path)?;
+    Ok(parent.join("previous-validator-authorities.txt"))
+}
+
+/// Reads the authorities the validator used before its identity was rotated,
+/// one pubkey per line. Returns an empty list if the identity was never rotated.
+pub(crate) fn read_previous_validator_authorities_from_ledger(
+    ledger_path: &Path,
+) -> ApiResult<Vec<Pubkey>> {
+    let authorities_path = previous_validator_authorities_path(ledger_path)?;
+    if !fs::exists(authorities_path.as_path()).unwrap_or(false) {
+        return Ok(vec![]);
+    }
+    let invalid = |err: String| {
+        ApiError::LedgerInvalidPreviousValidatorAuthorities(
+            authorities_path.display().to_string(),
+            err,
+        )
+    };
+    fs::read_to_string(authorities_path.as_path())
+        .map_err(|err| invalid(err.to_string()))?
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            Pubkey::from_str(line).map_err(|err| invalid(err.to_string()))
+        })
+        .collect()
+}
+
+pub(crate) fn write_previous_validator_authorities_to_ledger(
+    ledger_path: &Path,
+    authorities: &[Pubkey],
+) -> ApiResult<()> {
+    let authorities_path = previous_validator_authorities_path(ledger_path)?;
+    let content = authorities
+        .iter()
+        .map(|authority| format!("{}\n", authority))
+        .collect::<String>();
+    fs::write(authorities_path.as_path(), content).map_err(|err| {
+        ApiError::LedgerCouldNotWritePreviousValidatorAuthorities(
+            authorities_path.display().to_string(),
+            err.to_string(),
+        )
+    })
+}
+
+// -----------------
+// Allowed Programs
+// -----------------
+/// The programs added to or removed from the allow-list at runtime are
+/// persisted next to the ledger and kept when it is reset.
+pub(crate) fn allowed_programs_path(ledger_path: &Path) -> ApiResult<PathBuf> {
+    let parent = ledger_parent_dir(ledger_path)?;
+    Ok(parent.join("allowed-programs.txt"))
+}
+
+// -----------------
+// Replay Inputs
+// -----------------
+/// Reads the inputs recorded to the ledger at [ledger_path] before it is
+/// opened by the validator which may reset it.
+pub(crate) fn read_replay_inputs(
+    ledger_path: &Path,
+) -> ApiResult<Vec<RawReplayInput>> {
+    if !ledger_path.is_dir() {
+        return Err(ApiError::LedgerIsMissingReplayInputs(
+            ledger_path.display().to_string(),
+        ));
+    }
+    let ledger = Ledger::open(ledger_path)?;
+    Ok(ledger.get_replay_inputs()?)
+}
+
+/// Writes the [inputs] read via [read_replay_inputs] back to the ledger
+/// after it was reset so that they can be replayed again.
+pub(crate) fn write_replay_inputs(
+    ledger: &Ledger,
+    inputs: &[RawReplayInput],
+) -> ApiResult<()> {
+    for ((slot, seq), input) in inputs {
+        ledger.write_replay_input(*slot, *seq, input)?;
+    }
+    Ok(())
+}
+
 // -----------------
 // Ledger Directories
 // -----------------
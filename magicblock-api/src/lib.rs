@@ -2,12 +2,21 @@ pub mod accounts;
 pub mod errors;
 pub mod external_config;
 mod fund_account;
+mod genesis;
 mod geyser_transaction_notify_listener;
+mod identity_rotation;
 mod init_geyser_service;
 pub mod ledger;
 pub mod magic_validator;
+mod replay_inputs;
+mod replica;
+mod slot_status;
+mod startup;
+pub mod supervisor;
 mod tickers;
 mod utils;
+mod validator_builder;
 
 pub use init_geyser_service::InitGeyserServiceConfig;
 pub use magicblock_config::EphemeralConfig;
+pub use validator_builder::ValidatorBuilder;
@@ -1,4 +1,5 @@
 use std::{
+    collections::{HashMap, HashSet},
     net::SocketAddr,
     path::{Path, PathBuf},
     process,
@@ -13,65 +14,110 @@ use std::{
 use conjunto_transwise::RpcProviderConfig;
 use log::*;
 use magicblock_account_cloner::{
-    standard_blacklisted_accounts, RemoteAccountClonerClient,
-    RemoteAccountClonerWorker,
+    fail_pending_clones, standard_blacklisted_accounts, AccountClonerListeners,
+    RemoteAccountClonerClient, RemoteAccountClonerWorker,
+};
+use magicblock_account_dumper::{
+    AccountDumperAuditLog, AccountDumperBank, AccountDumperMetrics,
+    AccountDumperStack,
 };
-use magicblock_account_dumper::AccountDumperBank;
 use magicblock_account_fetcher::{
-    RemoteAccountFetcherClient, RemoteAccountFetcherWorker,
+    AccountFetcherStack, RemoteAccountFetcherClient, RemoteAccountFetcherWorker,
 };
 use magicblock_account_updates::{
-    RemoteAccountUpdatesClient, RemoteAccountUpdatesWorker,
+    AccountUpdatesStack, RemoteAccountUpdatesClient, RemoteAccountUpdatesWorker,
 };
 use magicblock_accounts::{
     utils::try_rpc_cluster_from_cluster, AccountsManager,
 };
-use magicblock_accounts_api::BankAccountProvider;
+use magicblock_accounts_api::{read_idl_account, BankAccountProvider};
 use magicblock_bank::{
-    bank::Bank, genesis_utils::create_genesis_config_with_leader,
+    bank::Bank, blockhash_expiry::BlockhashExpiry, builtins::BuiltinPrototype,
+    fee_payer_spend::FeePayerSpendLimit, gasless::GaslessConfig,
+    genesis_utils::create_genesis_config_with_leader,
     program_loader::load_programs_into_bank,
+    slot_status_notifier_interface::SlotStatusNotifiers,
     transaction_logs::TransactionLogCollectorFilter,
     transaction_notifier_interface::TransactionNotifierArc,
 };
-use magicblock_config::{EphemeralConfig, ProgramConfig};
+use magicblock_config::{
+    ChaosConfig, EphemeralConfig, LedgerConfig, LedgerInputsMode, ProgramConfig,
+};
+use magicblock_core::{
+    allowed_programs::AllowedPrograms,
+    chain_slot_mapping::ChainSlotMapping,
+    chaos::ChaosInjector,
+    circuit_breaker::CircuitBreaker,
+    hydrate_report::{HydrateReport, SharedHydrateReport},
+    robust_lock::RobustRwLock,
+};
 use magicblock_geyser_plugin::rpc::GeyserRpcService;
-use magicblock_ledger::{blockstore_processor::process_ledger, Ledger};
+use magicblock_ledger::{
+    blockstore_processor::process_ledger, Ledger, TransactionWriter,
+    TransactionWriterConfig,
+};
 use magicblock_metrics::MetricsService;
 use magicblock_perf_service::SamplePerformanceService;
-use magicblock_program::{init_persister, validator};
+use magicblock_processor::execute_transaction::lock_transactions;
+use magicblock_program::{validator::ValidatorStage, ValidatorContext};
 use magicblock_pubsub::pubsub_service::{
     PubsubConfig, PubsubService, PubsubServiceCloseHandle,
 };
 use magicblock_rpc::{
-    json_rpc_request_processor::JsonRpcConfig, json_rpc_service::JsonRpcService,
+    economics::RpcEconomics, faucet::FaucetLimits,
+    firewall::TransactionFirewallRules,
+    json_rpc_request_processor::JsonRpcConfig,
+    json_rpc_service::JsonRpcService, shutdown::RpcShutdown,
+    sigverify::SigverifyPoolConfig,
 };
 use magicblock_transaction_status::{
     TransactionStatusMessage, TransactionStatusSender,
 };
 use solana_geyser_plugin_manager::geyser_plugin_service::GeyserPluginService;
 use solana_sdk::{
-    commitment_config::CommitmentLevel, genesis_config::GenesisConfig,
+    account::Account, commitment_config::CommitmentLevel,
+    fee_calculator::FeeRateGovernor, genesis_config::GenesisConfig,
     pubkey::Pubkey, signature::Keypair, signer::Signer,
 };
 use tempfile::TempDir;
 use tokio_util::sync::CancellationToken;
 
 use crate::{
-    accounts::create_accounts_run_and_snapshot_dirs,
+    accounts::{create_accounts_run_and_snapshot_dirs, flush_accounts},
     errors::{ApiError, ApiResult},
-    external_config::try_convert_accounts_config,
+    external_config::{
+        blockhash_expiry_from_config, try_convert_accounts_config,
+    },
     fund_account::{
         fund_magic_context, fund_validator_identity, funded_faucet,
     },
+    genesis::customize_genesis,
     geyser_transaction_notify_listener::GeyserTransactionNotifyListener,
+    identity_rotation::{
+        init_identity_rotation_handler, rotate_validator_identity,
+    },
     init_geyser_service::{init_geyser_service, InitGeyserServiceConfig},
     ledger::{
-        self, ledger_parent_dir, read_validator_keypair_from_ledger,
+        self, allowed_programs_path, ledger_parent_dir,
+        read_previous_validator_authorities_from_ledger, read_replay_inputs,
+        read_validator_keypair_from_ledger,
+        write_previous_validator_authorities_to_ledger, write_replay_inputs,
         write_validator_keypair_to_ledger,
     },
+    replay_inputs::{
+        LedgerInputs, RawReplayInput, RecordedInputs, RecordingAccountFetcher,
+        RecordingAccountUpdates, ReplayAccountFetcher, ReplayAccountUpdates,
+        ReplayInputRecorder,
+    },
+    replica::init_replica_follower,
+    slot_status::SlotMetricsNotifier,
+    startup::verify_remote_cluster,
+    supervisor::{Supervisor, SupervisorConfig},
     tickers::{
+        accept_and_process_scheduled_commits, init_clock_sync_ticker,
         init_commit_accounts_ticker, init_slot_ticker,
-        init_system_metrics_ticker,
+        init_system_metrics_ticker, persist_account_journal,
+        persist_escrow_settlements,
     },
 };
 
@@ -110,20 +156,30 @@ pub struct MagicValidator {
     pubsub_close_handle: PubsubServiceCloseHandle,
     sample_performance_service: Option<SamplePerformanceService>,
     commit_accounts_ticker: Option<tokio::task::JoinHandle<()>>,
+    clock_sync_ticker: Option<tokio::task::JoinHandle<()>>,
+    identity_rotation_handler: Option<tokio::task::JoinHandle<()>>,
+    replica_follower: Option<tokio::task::JoinHandle<()>>,
     remote_account_fetcher_worker: Option<RemoteAccountFetcherWorker>,
     remote_account_fetcher_handle: Option<thread::JoinHandle<()>>,
     remote_account_updates_worker: Option<RemoteAccountUpdatesWorker>,
     remote_account_updates_handle: Option<thread::JoinHandle<()>>,
+    chain_slot_mapping: ChainSlotMapping,
+    ledger_inputs: Option<LedgerInputs>,
     remote_account_cloner_worker: Option<
         RemoteAccountClonerWorker<
             BankAccountProvider,
-            RemoteAccountFetcherClient,
-            RemoteAccountUpdatesClient,
-            AccountDumperBank,
+            AccountFetcherStack,
+            AccountUpdatesStack,
+            AccountDumperStack,
         >,
     >,
     remote_account_cloner_handle: Option<thread::JoinHandle<()>>,
+    hydrate_report: SharedHydrateReport,
+    remote_account_cloner_listeners:
+        Arc<RwLock<HashMap<Pubkey, AccountClonerListeners>>>,
+    remote_account_cloner_invalidated_clones: Arc<RwLock<HashSet<Pubkey>>>,
     accounts_manager: Arc<AccountsManager>,
+    supervisor: Supervisor,
     transaction_listener: GeyserTransactionNotifyListener,
     rpc_service: JsonRpcService,
     _metrics: Option<(MetricsService, tokio::task::JoinHandle<()>)>,
@@ -147,37 +203,96 @@ impl MagicValidator {
             init_geyser_service(config.init_geyser_service_config)?;
 
         let validator_pubkey = identity_keypair.pubkey();
+        let validator_context =
+            Arc::new(ValidatorContext::new(identity_keypair.insecure_clone()));
+        validator_context.init_max_commit_delay_slots(
+            config.validator_config.accounts.commit.max_delay_slots,
+        );
         let magicblock_bank::genesis_utils::GenesisConfigInfo {
-            genesis_config,
+            mut genesis_config,
             validator_pubkey,
             ..
         } = create_genesis_config_with_leader(u64::MAX, &validator_pubkey);
+        let additional_builtins = customize_genesis(
+            &mut genesis_config,
+            &config.validator_config.genesis,
+        )?;
+        let fees = &config.validator_config.validator.fees;
+        genesis_config.fee_rate_governor =
+            FeeRateGovernor::new(fees.lamports_per_signature(), 0);
 
+        let replay_inputs =
+            Self::read_inputs_to_replay(&config.validator_config.ledger)?;
         let ledger = Self::init_ledger(
+            &validator_context,
             config.validator_config.ledger.path.as_ref(),
             config.validator_config.ledger.reset,
         )?;
+        Self::init_data_mods_memory_budget(
+            &validator_context,
+            ledger.ledger_path(),
+            config.validator_config.validator.data_mods_memory_budget,
+        )?;
         Self::sync_validator_keypair_with_ledger(
             ledger.ledger_path(),
             &identity_keypair,
             config.validator_config.ledger.reset,
         )?;
+        Self::sync_previous_validator_authorities_with_ledger(
+            &validator_context,
+            ledger.ledger_path(),
+            config.validator_config.ledger.reset,
+        )?;
         let accounts_paths = Self::init_accounts_paths(ledger.ledger_path())?;
 
         let exit = Arc::<AtomicBool>::default();
         let bank = Self::init_bank(
             &geyser_service,
             &genesis_config,
+            &additional_builtins,
             config.validator_config.validator.millis_per_slot,
+            config
+                .validator_config
+                .validator
+                .transaction_expiration_millis,
+            blockhash_expiry_from_config(
+                &config.validator_config.validator.blockhash_expiry,
+            ),
             validator_pubkey,
+            validator_context,
             accounts_paths,
         );
+        let spend_limit =
+            &config.validator_config.validator.fee_payer_spend_limit;
+        bank.set_fee_payer_spend_limit(spend_limit.max_lamports.map(
+            |max_lamports| FeePayerSpendLimit {
+                max_lamports,
+                window: Duration::from_secs(spend_limit.window_secs),
+            },
+        ));
+        let gasless = &config.validator_config.gasless;
+        bank.set_gasless_config(gasless.enabled.then(|| GaslessConfig {
+            sponsored_programs:
+                gasless.sponsored_programs.iter().cloned().collect(),
+            quota: (gasless.quota_lamports > 0).then(|| FeePayerSpendLimit {
+                max_lamports: gasless.quota_lamports,
+                window: Duration::from_secs(gasless.quota_window_secs),
+            }),
+        }));
+
+        let ledger_inputs = Self::init_ledger_inputs(
+            &ledger,
+            &bank,
+            &config.validator_config.ledger,
+            replay_inputs,
+        )?;
 
         fund_validator_identity(&bank, &validator_pubkey);
         fund_magic_context(&bank);
         let faucet_keypair = funded_faucet(
             &bank,
             ledger.ledger_path().as_path(),
+            &config.validator_config.faucet,
             config.validator_config.ledger.reset,
         )?;
 
@@ -218,6 +333,14 @@ impl MagicValidator {
             None
         };
 
+        let telemetry_config = &config.validator_config.telemetry;
+        if telemetry_config.enabled {
+            magicblock_telemetry::init_telemetry(
+                &telemetry_config.otlp_endpoint,
+                &telemetry_config.service_name,
+            )?;
+        }
+
         let accounts_config =
             try_convert_accounts_config(&config.validator_config.accounts)
                 .map_err(ApiError::ConfigError)?;
@@ -227,19 +350,36 @@ impl MagicValidator {
             Some(CommitmentLevel::Confirmed),
         );
 
+        // Shared by all components talking to the remote cluster so that
+        // they back off together when it is failing
+        let circuit_breaker_config =
+            &config.validator_config.accounts.circuit_breaker;
+        let circuit_breaker = CircuitBreaker::new(
+            circuit_breaker_config.failure_threshold,
+            Duration::from_secs(circuit_breaker_config.open_secs),
+        );
+
+        let chaos = Self::init_chaos(&config.validator_config.chaos)?;
+
+        let missing_accounts_ttl = Duration::from_millis(
+            config.validator_config.accounts.missing_accounts_ttl_millis,
+        );
         let remote_account_fetcher_worker =
-            RemoteAccountFetcherWorker::new(remote_rpc_config.clone());
+            RemoteAccountFetcherWorker::new(remote_rpc_config.clone())
+                .with_circuit_breaker(circuit_breaker.clone())
+                .with_chaos(chaos.clone())
+                .with_missing_accounts_ttl(missing_accounts_ttl);
 
+        let subscriptions_config =
+            &config.validator_config.accounts.subscriptions;
         let remote_account_updates_worker = RemoteAccountUpdatesWorker::new(
-            // We'll maintain 3 connections constantly (those could be on different nodes if we wanted to)
-            vec![
-                remote_rpc_config.clone(),
-                remote_rpc_config.clone(),
-                remote_rpc_config.clone(),
-            ],
+            // We'll maintain redundant connection pools constantly, 3 by default (those could be on different nodes if we wanted to)
+            vec![remote_rpc_config.clone(); subscriptions_config.pools.max(1)],
             // We'll kill/refresh one connection every 5 minutes
             Duration::from_secs(60 * 5),
-        );
+        )
+        .with_shard_count(subscriptions_config.shards)
+        .with_chaos(chaos.clone());
 
         let transaction_status_sender = TransactionStatusSender {
             sender: transaction_sndr,
@@ -250,10 +390,77 @@ impl MagicValidator {
             RemoteAccountFetcherClient::new(&remote_account_fetcher_worker);
         let remote_account_updates_client =
             RemoteAccountUpdatesClient::new(&remote_account_updates_worker);
-        let account_dumper_bank = AccountDumperBank::new(
-            bank.clone(),
-            Some(transaction_status_sender.clone()),
+        let chain_slot_mapping =
+            remote_account_updates_worker.get_chain_slot_mapping();
+        let (account_fetcher, account_updates): (
+            AccountFetcherStack,
+            AccountUpdatesStack,
+        ) = match &ledger_inputs {
+            Some(LedgerInputs::Record(recorder)) => (
+                Box::new(RecordingAccountFetcher::new(
+                    remote_account_fetcher_client,
+                    recorder.clone(),
+                )),
+                Box::new(RecordingAccountUpdates::new(
+                    remote_account_updates_client,
+                    recorder.clone(),
+                )),
+            ),
+            Some(LedgerInputs::Replay(inputs)) => (
+                Box::new(ReplayAccountFetcher::new(inputs.clone())),
+                Box::new(ReplayAccountUpdates::new(inputs.clone())),
+            ),
+            None => (
+                Box::new(remote_account_fetcher_client),
+                Box::new(remote_account_updates_client),
+            ),
+        };
+        // When replaying we run hermetically and never talk to the remote
+        let is_replaying =
+            ledger_inputs.as_ref().is_some_and(LedgerInputs::is_replay);
+        // Neither do we when running offline
+        let uses_remote =
+            !is_replaying && !accounts_config.lifecycle.is_offline();
+        // Commits scheduled offline would never be sent, thus programs learn
+        // right away instead of them being dropped once accepted
+        if !is_replaying && accounts_config.lifecycle.is_offline() {
+            bank.validator_context().disable_scheduled_commits();
+        }
+        // The accounts written by transactions are only taken by the commit
+        // ticker, which doesn't run unless we commit to the remote
+        if uses_remote && !config.validator_config.replica.enabled {
+            bank.enable_dirty_accounts_tracking();
+        }
+        let mut account_dumper: AccountDumperStack = Box::new(
+            AccountDumperBank::new(
+                bank.clone(),
+                Some(transaction_status_sender.clone()),
+            )
+            .with_mainnet_rent_semantics(
+                accounts_config.mainnet_rent_semantics,
+            ),
         );
+        if accounts_config.dump_metrics {
+            account_dumper =
+                Box::new(AccountDumperMetrics::new(account_dumper));
+        }
+        if let Some(path) = &accounts_config.dump_audit_log {
+            account_dumper = Box::new(
+                AccountDumperAuditLog::new(account_dumper, bank.clone(), path)
+                    .map_err(|err| {
+                        ApiError::FailedToOpenAccountDumpAuditLog(
+                            path.display().to_string(),
+                            err,
+                        )
+                    })?,
+            );
+        }
+        let allowed_programs = Self::init_allowed_programs(
+            ledger.ledger_path(),
+            accounts_config.allowed_program_ids,
+        )?;
+        let idl_overrides =
+            Self::init_idl_overrides(&bank, &accounts_config.idl_overrides)?;
         let blacklisted_accounts = standard_blacklisted_accounts(
             &identity_keypair.pubkey(),
             &faucet_keypair.pubkey(),
@@ -261,30 +468,35 @@ impl MagicValidator {
 
         let remote_account_cloner_worker = RemoteAccountClonerWorker::new(
             bank_account_provider,
-            remote_account_fetcher_client,
-            remote_account_updates_client,
-            account_dumper_bank,
-            accounts_config.allowed_program_ids,
+            account_fetcher,
+            account_updates,
+            account_dumper,
+            None,
             blacklisted_accounts,
             accounts_config.payer_init_lamports,
-            accounts_config.lifecycle.to_account_cloner_permissions(),
+            accounts_config.to_account_cloner_permissions(),
             identity_keypair.pubkey(),
-        );
+        )
+        .with_allowed_programs(allowed_programs.clone())
+        .with_circuit_breaker(circuit_breaker.clone())
+        .with_mint_authority_overrides(accounts_config.mint_authority_overrides)
+        .with_idl_overrides(idl_overrides)
+        .with_validator_stage(bank.validator_context().subscribe_to_stage());
 
         let accounts_manager = Self::init_accounts_manager(
             &bank,
             RemoteAccountClonerClient::new(&remote_account_cloner_worker),
             transaction_status_sender.clone(),
-            &identity_keypair,
             &config.validator_config,
+            circuit_breaker,
+            chaos,
+            chain_slot_mapping.clone(),
         );
 
-        let pubsub_config = PubsubConfig::from_rpc(
-            config.validator_config.rpc.addr,
-            config.validator_config.rpc.port,
-        );
-        validator::init_validator_authority(identity_keypair);
+        let pubsub_config =
+            PubsubConfig::new(config.validator_config.rpc.pubsub_socket_addr());
 
+        let hydrate_report = SharedHydrateReport::default();
         // Make sure we process the ledger before we're open to handle
         // transactions via RPC
         let rpc_service = Self::init_json_rpc_service(
@@ -296,7 +508,15 @@ impl MagicValidator {
             transaction_status_sender.clone(),
             &pubsub_config,
             &config.validator_config,
+            chain_slot_mapping.clone(),
+            allowed_programs,
+            hydrate_report.clone(),
         )?;
+        let supervisor = Supervisor::new(
+            SupervisorConfig::default(),
+            token.clone(),
+            rpc_service.shutdown(),
+        );
 
         Ok(Self {
             config: config.validator_config,
@@ -306,12 +526,24 @@ impl MagicValidator {
             geyser_rpc_service,
             slot_ticker: None,
             commit_accounts_ticker: None,
-            remote_account_fetcher_worker: Some(remote_account_fetcher_worker),
+            clock_sync_ticker: None,
+            identity_rotation_handler: None,
+            replica_follower: None,
+            remote_account_fetcher_worker: uses_remote
+                .then_some(remote_account_fetcher_worker),
             remote_account_fetcher_handle: None,
-            remote_account_updates_worker: Some(remote_account_updates_worker),
+            remote_account_updates_worker: uses_remote
+                .then_some(remote_account_updates_worker),
             remote_account_updates_handle: None,
+            chain_slot_mapping,
+            ledger_inputs,
+            remote_account_cloner_listeners: remote_account_cloner_worker
+                .get_clone_listeners(),
+            remote_account_cloner_invalidated_clones:
+                remote_account_cloner_worker.get_invalidated_clones(),
             remote_account_cloner_worker: Some(remote_account_cloner_worker),
             remote_account_cloner_handle: None,
+            hydrate_report,
             pubsub_handle: Default::default(),
             pubsub_close_handle: Default::default(),
             sample_performance_service: None,
@@ -320,35 +552,49 @@ impl MagicValidator {
             bank,
             ledger,
             accounts_manager,
+            supervisor,
             transaction_listener,
             transaction_status_sender,
         })
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn init_bank(
         geyser_service: &GeyserPluginService,
         genesis_config: &GenesisConfig,
+        additional_builtins: &[BuiltinPrototype],
         millis_per_slot: u64,
+        transaction_expiration_millis: u64,
+        blockhash_expiry: BlockhashExpiry,
         validator_pubkey: Pubkey,
+        validator_context: Arc<ValidatorContext>,
         accounts_paths: Vec<PathBuf>,
     ) -> Arc<Bank> {
         let runtime_config = Default::default();
+        // Geyser plugins see a slot before it is counted in the metrics
+        let slot_status_notifiers = SlotStatusNotifiers::default();
+        if let Some(notifier) = geyser_service.get_slot_status_notifier() {
+            slot_status_notifiers.register("geyser", notifier);
+        }
+        slot_status_notifiers
+            .register("metrics", Arc::new(SlotMetricsNotifier));
         let bank = Bank::new(
             genesis_config,
             runtime_config,
             None,
-            None,
+            Some(additional_builtins),
             false,
             accounts_paths,
             geyser_service.get_accounts_update_notifier(),
-            geyser_service.get_slot_status_notifier(),
+            slot_status_notifiers,
             millis_per_slot,
+            transaction_expiration_millis,
+            blockhash_expiry,
             validator_pubkey,
+            validator_context,
         );
-        bank.transaction_log_collector_config
-            .write()
-            .unwrap()
-            .filter = TransactionLogCollectorFilter::All;
+        bank.transaction_log_collector_config.write_robust().filter =
+            TransactionLogCollectorFilter::All;
         Arc::new(bank)
     }
 
@@ -356,8 +602,10 @@ impl MagicValidator {
         bank: &Arc<Bank>,
         remote_account_cloner_client: RemoteAccountClonerClient,
         transaction_status_sender: TransactionStatusSender,
-        validator_keypair: &Keypair,
         config: &EphemeralConfig,
+        circuit_breaker: CircuitBreaker,
+        chaos: ChaosInjector,
+        chain_slot_mapping: ChainSlotMapping,
     ) -> Arc<AccountsManager> {
         let accounts_config = try_convert_accounts_config(&config.accounts)
             .expect(
@@ -367,15 +615,12 @@ impl MagicValidator {
             bank,
             remote_account_cloner_client,
             Some(transaction_status_sender),
-            // NOTE: we could avoid passing a copy of the keypair here if we instead pass
-            // something akin to a ValidatorTransactionSigner that gets it via the [validator_authority]
-            // method from the [magicblock_program] module, forgetting it immediately after.
-            // That way we would at least hold it in memory for a long time only in one place and in all other
-            // places only temporarily
-            validator_keypair.insecure_clone(),
             accounts_config,
+            circuit_breaker,
+            chaos,
         )
-        .expect("Failed to create accounts manager");
+        .expect("Failed to create accounts manager")
+        .with_chain_slot_mapping(chain_slot_mapping);
 
         Arc::new(accounts_manager)
     }
@@ -390,6 +635,9 @@ impl MagicValidator {
         transaction_status_sender: TransactionStatusSender,
         pubsub_config: &PubsubConfig,
         config: &EphemeralConfig,
+        chain_slot_mapping: ChainSlotMapping,
+        allowed_programs: AllowedPrograms,
+        hydrate_report: SharedHydrateReport,
     ) -> ApiResult<JsonRpcService> {
         let rpc_socket_addr = SocketAddr::new(config.rpc.addr, config.rpc.port);
         let rpc_json_config = JsonRpcConfig {
@@ -400,8 +648,54 @@ impl MagicValidator {
             transaction_status_sender: Some(transaction_status_sender.clone()),
             rpc_socket_addr: Some(rpc_socket_addr),
             pubsub_socket_addr: Some(*pubsub_config.socket()),
+            tpu_socket_addr: config.rpc.tpu_socket_addr(),
             enable_rpc_transaction_history: true,
             disable_sigverify: !config.validator.sigverify,
+            skip_internal_sigverify: config.validator.skip_internal_sigverify,
+            sigverify_pool: SigverifyPoolConfig {
+                threads: config.validator.sigverify_threads,
+                max_batch_size: config.validator.sigverify_max_batch_size,
+            },
+            enable_admin_rpc: config.rpc.admin,
+            read_only: config.replica.enabled,
+            firewall_rules: TransactionFirewallRules {
+                denied_programs: config
+                    .firewall
+                    .denied_programs
+                    .iter()
+                    .copied()
+                    .collect(),
+                allowed_fee_payers: config
+                    .firewall
+                    .allowed_fee_payers
+                    .iter()
+                    .copied()
+                    .collect(),
+                min_fee_payer_lamports: config.firewall.min_fee_payer_lamports,
+                max_transaction_size: config.firewall.max_transaction_size,
+            },
+            faucet_limits: FaucetLimits {
+                max_lamports_per_request: config
+                    .faucet
+                    .max_lamports_per_request,
+                per_key_lamports_per_window: config
+                    .faucet
+                    .per_key_lamports_per_window,
+                global_lamports_per_window: config
+                    .faucet
+                    .global_lamports_per_window,
+                window: Duration::from_secs(config.faucet.window_secs),
+            },
+            economics: RpcEconomics {
+                supply_lamports: config.rpc.economics.supply_lamports,
+                vote_account_stake_lamports: config
+                    .rpc
+                    .economics
+                    .vote_account_stake_lamports,
+            },
+            chain_slot_mapping,
+            allowed_programs,
+            hydrate_report,
 
             ..Default::default()
         };
@@ -420,6 +714,7 @@ impl MagicValidator {
     }
 
     fn init_ledger(
+        validator_context: &ValidatorContext,
         ledger_path: Option<&String>,
         reset: bool,
     ) -> ApiResult<Arc<Ledger>> {
@@ -432,10 +727,75 @@ impl MagicValidator {
         };
         let ledger = ledger::init(ledger_path, reset)?;
         let ledger_shared = Arc::new(ledger);
-        init_persister(ledger_shared.clone());
+        validator_context
+            .data_mods()
+            .init_persister(ledger_shared.clone());
         Ok(ledger_shared)
     }
 
+    /// Reads the inputs to replay before the ledger is initialized since
+    /// that may reset it.
+    fn read_inputs_to_replay(
+        ledger_config: &LedgerConfig,
+    ) -> ApiResult<Option<Vec<RawReplayInput>>> {
+        if ledger_config.inputs != LedgerInputsMode::Replay {
+            return Ok(None);
+        }
+        let Some(ledger_path) = ledger_config.path.as_ref() else {
+            return Err(ApiError::LedgerInputsReplayRequiresPath);
+        };
+        let replay_inputs = read_replay_inputs(Path::new(ledger_path))?;
+        info!(
+            "Replaying {} inputs recorded to the ledger at {}",
+            replay_inputs.len(),
+            ledger_path
+        );
+        Ok(Some(replay_inputs))
+    }
+
+    fn init_ledger_inputs(
+        ledger: &Arc<Ledger>,
+        bank: &Arc<Bank>,
+        ledger_config: &LedgerConfig,
+        replay_inputs: Option<Vec<RawReplayInput>>,
+    ) -> ApiResult<Option<LedgerInputs>> {
+        let ledger_inputs = match (&ledger_config.inputs, replay_inputs) {
+            (LedgerInputsMode::Record, _) => LedgerInputs::Record(Arc::new(
+                ReplayInputRecorder::try_new(ledger.clone(), bank.clone())?,
+            )),
+            (LedgerInputsMode::Replay, Some(replay_inputs)) => {
+                // Keep the inputs in the reset ledger so that the same run
+                // can be replayed again
+                if ledger_config.reset {
+                    write_replay_inputs(ledger, &replay_inputs)?;
+                }
+                LedgerInputs::Replay(Arc::new(RecordedInputs::try_new(
+                    &replay_inputs,
+                )?))
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(ledger_inputs))
+    }
+
+    fn is_replaying_inputs(&self) -> bool {
+        self.ledger_inputs
+            .as_ref()
+            .is_some_and(LedgerInputs::is_replay)
+    }
+
+    fn init_data_mods_memory_budget(
+        validator_context: &ValidatorContext,
+        ledger_path: &Path,
+        max_size: usize,
+    ) -> ApiResult<()> {
+        let parent = ledger_parent_dir(ledger_path)?;
+        validator_context
+            .data_mods()
+            .init_memory_budget(max_size, parent.join("data-mods"))?;
+        Ok(())
+    }
+
     fn init_accounts_paths(ledger_path: &Path) -> ApiResult<Vec<PathBuf>> {
         let parent = ledger_parent_dir(ledger_path)?;
         let accounts_dir = parent.join("accounts");
@@ -466,6 +826,102 @@ impl MagicValidator {
         Ok(())
     }
 
+    /// Loads the authorities the validator used before its identity was
+    /// rotated so that the transactions they signed can be replayed.
+    fn sync_previous_validator_authorities_with_ledger(
+        validator_context: &ValidatorContext,
+        ledger_path: &Path,
+        reset_ledger: bool,
+    ) -> ApiResult<()> {
+        if reset_ledger {
+            write_previous_validator_authorities_to_ledger(ledger_path, &[])?;
+        } else {
+            let previous_authorities =
+                read_previous_validator_authorities_from_ledger(ledger_path)?;
+            if !previous_authorities.is_empty() {
+                info!(
+                    "Validator identity was rotated before, previous authorities: {:?}",
+                    previous_authorities
+                );
+            }
+            validator_context
+                .init_previous_validator_authorities(previous_authorities);
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "dev-context-only-utils")]
+    fn init_chaos(chaos_config: &ChaosConfig) -> ApiResult<ChaosInjector> {
+        let chaos = ChaosInjector::new(
+            Duration::from_millis(chaos_config.rpc_delay_ms),
+            chaos_config.fail_commit_send_every,
+            chaos_config.drop_websocket_every,
+        );
+        if chaos.is_enabled() {
+            warn!(
+                "Injecting failures into remote requests: {:?}",
+                chaos_config
+            );
+        }
+        Ok(chaos)
+    }
+
+    /// Failures can only be injected into builds meant for testing, thus we
+    /// refuse to start instead of silently ignoring the config.
+    #[cfg(not(feature = "dev-context-only-utils"))]
+    fn init_chaos(chaos_config: &ChaosConfig) -> ApiResult<ChaosInjector> {
+        if chaos_config != &ChaosConfig::default() {
+            return Err(ApiError::ChaosInjectionNotSupported);
+        }
+        Ok(ChaosInjector::disabled())
+    }
+
+    /// Merges the programs added to or removed from the allow-list at
+    /// runtime with the configured ones.
+    fn init_allowed_programs(
+        ledger_path: &Path,
+        allowed_program_ids: Option<HashSet<Pubkey>>,
+    ) -> ApiResult<AllowedPrograms> {
+        let allowed_programs_path = allowed_programs_path(ledger_path)?;
+        let allowed_programs = AllowedPrograms::new(allowed_program_ids)
+            .with_sidecar_file(allowed_programs_path.clone())
+            .map_err(|err| {
+                ApiError::LedgerInvalidAllowedPrograms(
+                    allowed_programs_path.display().to_string(),
+                    err.to_string(),
+                )
+            })?;
+        let (added, removed) = allowed_programs.changes();
+        if !added.is_empty() || !removed.is_empty() {
+            info!(
+                "Allowed programs changed at runtime, added: {:?}, removed: {:?}",
+                added, removed
+            );
+        }
+        Ok(allowed_programs)
+    }
+
+    /// Stores the local IDLs where the IDLs of their programs are found,
+    /// which also covers programs that are never cloned.
+    /// Returns them to be cloned instead of the IDLs on chain.
+    fn init_idl_overrides(
+        bank: &Bank,
+        idl_overrides: &[(Pubkey, PathBuf)],
+    ) -> ApiResult<HashMap<Pubkey, (Pubkey, Account)>> {
+        let mut idl_accounts = HashMap::new();
+        for (program_id, path) in idl_overrides {
+            let (idl_pubkey, idl_account) = read_idl_account(program_id, path)?;
+            info!(
+                "Using local IDL '{}' for program {}",
+                path.display(),
+                program_id
+            );
+            bank.store_account(&idl_pubkey, &idl_account);
+            idl_accounts.insert(*program_id, (idl_pubkey, idl_account));
+        }
+        Ok(idl_accounts)
+    }
+
     fn init_transaction_listener(
         ledger: &Arc<Ledger>,
         transaction_notifier: Option<TransactionNotifierArc>,
@@ -475,12 +931,16 @@ impl MagicValidator {
     ) {
         let (transaction_sndr, transaction_recvr) =
             crossbeam_channel::unbounded();
+        let transaction_writer = TransactionWriter::spawn(
+            ledger.clone(),
+            TransactionWriterConfig::default(),
+        );
         (
             transaction_sndr,
             GeyserTransactionNotifyListener::new(
                 transaction_notifier,
                 transaction_recvr,
-                ledger.clone(),
+                transaction_writer,
             ),
         )
     }
@@ -492,8 +952,17 @@ impl MagicValidator {
         if self.config.ledger.reset {
             return Ok(());
         }
+        self.bank
+            .validator_context()
+            .enter_stage(ValidatorStage::Replaying);
         process_ledger(&self.ledger, &self.bank)?;
 
+        let indexed_accounts = self.bank.rebuild_owner_index();
+        debug!(
+            "Rebuilt owner index of {} accounts after processing ledger",
+            indexed_accounts
+        );
+
         // The transactions to schedule and accept account commits re-run when we
         // process the ledger, however we do not want to re-commit them.
         // Thus while the ledger is processed we don't yet run the machinery to handle
@@ -505,33 +974,62 @@ impl MagicValidator {
             scheduled_commits
         );
         self.accounts_manager.clear_scheduled_commits();
+
+        // The same goes for escrow settlements, they were persisted when
+        // they were accepted the first time
+        let escrow_settlements = self
+            .bank
+            .validator_context()
+            .transaction_scheduler()
+            .take_escrow_settlements();
+        debug!(
+            "Found {} escrow settlements while processing ledger, clearing them",
+            escrow_settlements.len()
+        );
         Ok(())
     }
 
+    /// Starts the validator, bringing up its subsystems in dependency order:
+    ///
+    /// 1. verifies that the remote cluster is reachable and that our clocks agree
+    /// 2. processes the ledger to restore the bank
+    /// 3. starts the transaction listener which feeds geyser and the ledger
+    /// 4. starts the remote account fetcher and updates workers
+    /// 5. hydrates and starts the remote account cloner which relies on them
+    /// 6. starts the slot and commit tickers which need hydrated accounts
+    /// 7. opens the JSON RPC and pubsub services to clients
+    ///
+    /// When running as a read replica steps 4 to 6 are replaced by following
+    /// the primary validator.
+    /// When replaying recorded inputs the remote cluster is not verified in
+    /// step 1 and the remote account workers are not started in step 4.
+    /// The same applies when running offline, in which case no accounts are
+    /// cloned or committed at all and they need to be created locally.
+    ///
+    /// The validator enters the [ValidatorStage::Replaying] stage in step 2,
+    /// [ValidatorStage::Hydrating] in step 5 and [ValidatorStage::Running]
+    /// once all steps completed.
+    ///
+    /// It fails fast with the error of the first step that fails.
     pub async fn start(&mut self) -> ApiResult<()> {
-        self.maybe_process_ledger()?;
-
-        self.transaction_listener.run(true);
+        info!("Startup: verifying preconditions");
+        self.verify_startup_preconditions().await?;
 
-        self.slot_ticker = Some(init_slot_ticker(
-            &self.bank,
-            &self.accounts_manager,
-            Some(self.transaction_status_sender.clone()),
-            self.ledger.clone(),
-            Duration::from_millis(self.config.validator.millis_per_slot),
-            self.exit.clone(),
-        ));
+        info!("Startup: processing ledger");
+        self.maybe_process_ledger()?;
 
-        self.commit_accounts_ticker = Some(init_commit_accounts_ticker(
-            &self.accounts_manager,
-            Duration::from_millis(self.config.accounts.commit.frequency_millis),
-            self.token.clone(),
-        ));
+        info!("Startup: starting transaction listener");
+        self.transaction_listener.run(true, self.supervisor.clone());
 
-        self.start_remote_account_fetcher_worker();
-        self.start_remote_account_updates_worker();
-        self.start_remote_account_cloner_worker().await;
+        if self.config.replica.enabled {
+            info!("Startup: following primary");
+            self.start_replica_follower();
+        } else {
+            self.start_execution_services().await?;
+        }
+        self.start_identity_rotation_handler();
 
+        info!("Startup: starting RPC services");
         self.rpc_service.start().map_err(|err| {
             ApiError::FailedToStartJsonRpcService(format!("{:?}", err))
         })?;
@@ -544,15 +1042,20 @@ impl MagicValidator {
 
         // NOTE: we need to create the pubsub service on each start since spawning
         // it takes ownership
+        let supervisor = self.supervisor.clone();
         let pubsub_service = PubsubService::new(
             self.pubsub_config.clone(),
             self.geyser_rpc_service.clone(),
             self.bank.clone(),
+            Some(self.ledger.clone()),
+            Some(Arc::new(move |result| {
+                supervisor.should_restart("pubsub_actor", result)
+            })),
         );
 
         let (pubsub_handle, pubsub_close_handle) =
             pubsub_service.spawn(self.pubsub_config.socket())?;
-        self.pubsub_handle.write().unwrap().replace(pubsub_handle);
+        self.pubsub_handle.write_robust().replace(pubsub_handle);
         self.pubsub_close_handle = pubsub_close_handle;
 
         self.sample_performance_service
@@ -562,7 +1065,136 @@ impl MagicValidator {
                 self.exit.clone(),
             ));
 
-        validator::finished_starting_up();
+        self.bank.validator_context().finished_starting_up();
+        info!("Startup: completed");
+        Ok(())
+    }
+
+    /// Starts the services needed to execute transactions and commit their
+    /// results, none of which run on a read replica.
+    async fn start_execution_services(&mut self) -> ApiResult<()> {
+        info!("Startup: starting remote account workers");
+        self.start_remote_account_fetcher_worker();
+        self.start_remote_account_updates_worker();
+        self.start_remote_account_cloner_worker().await?;
+
+        info!("Startup: starting tickers");
+        self.start_clock_sync_ticker()?;
+        self.slot_ticker = Some(init_slot_ticker(
+            &self.bank,
+            &self.accounts_manager,
+            self.chain_slot_mapping.clone(),
+            Some(self.transaction_status_sender.clone()),
+            self.ledger.clone(),
+            self.ledger_inputs.clone(),
+            self.remote_account_cloner_invalidated_clones.clone(),
+            Duration::from_millis(self.config.validator.millis_per_slot),
+            self.exit.clone(),
+        ));
+
+        if self.is_replaying_inputs() {
+            warn!("Replaying recorded inputs, commits are not sent to the remote cluster");
+            return Ok(());
+        }
+        if self.accounts_manager.lifecycle.is_offline() {
+            warn!("Running offline, accounts are not committed to the remote cluster");
+            return Ok(());
+        }
+        self.commit_accounts_ticker = Some(init_commit_accounts_ticker(
+            &self.accounts_manager,
+            Duration::from_millis(self.config.accounts.commit.frequency_millis),
+            self.bank.validator_context().subscribe_to_stage(),
+            self.token.clone(),
+        ));
+        Ok(())
+    }
+
+    /// Handles the identity rotations requested via the admin RPC, which
+    /// are rejected on a read replica and while replaying recorded inputs.
+    fn start_identity_rotation_handler(&mut self) {
+        let Some(requests) =
+            self.rpc_service.identity_rotation().take_requests()
+        else {
+            return;
+        };
+        if self.config.replica.enabled || self.is_replaying_inputs() {
+            return;
+        }
+        self.identity_rotation_handler = Some(init_identity_rotation_handler(
+            self.bank.clone(),
+            self.ledger.clone(),
+            self.accounts_manager.clone(),
+            requests,
+            self.token.clone(),
+        ));
+    }
+
+    fn start_replica_follower(&mut self) {
+        let replica = &self.config.replica;
+        info!(
+            "Running as read replica of primary at {}",
+            replica.primary_geyser_grpc_url
+        );
+        self.replica_follower = Some(init_replica_follower(
+            &self.bank,
+            &self.ledger,
+            replica.primary_geyser_grpc_url.clone(),
+            Duration::from_millis(replica.reconnect_delay_millis),
+            self.token.clone(),
+        ));
+    }
+
+    async fn verify_startup_preconditions(&self) -> ApiResult<()> {
+        let accounts_config =
+            try_convert_accounts_config(&self.config.accounts)
+                .map_err(ApiError::ConfigError)?;
+        if accounts_config.lifecycle.is_offline() {
+            info!("Running offline, skipping remote cluster checks");
+            return Ok(());
+        }
+        if self.config.replica.enabled {
+            info!("Running as read replica, skipping remote cluster checks");
+            return Ok(());
+        }
+        if self.is_replaying_inputs() {
+            info!("Replaying recorded inputs, skipping remote cluster checks");
+            return Ok(());
+        }
+        let rpc_cluster =
+            try_rpc_cluster_from_cluster(&accounts_config.remote_cluster)?;
+        verify_remote_cluster(
+            rpc_cluster.url(),
+            self.config.validator.max_clock_skew_secs,
+            self.config.validator.fail_on_clock_skew,
+        )
+        .await
+    }
+
+    fn start_clock_sync_ticker(&mut self) -> ApiResult<()> {
+        let clock_sync = &self.config.validator.clock_sync;
+        if !clock_sync.enabled {
+            return Ok(());
+        }
+        if self.is_replaying_inputs() {
+            info!("Replaying recorded inputs, the clock is restored from them");
+            return Ok(());
+        }
+        let accounts_config =
+            try_convert_accounts_config(&self.config.accounts)
+                .map_err(ApiError::ConfigError)?;
+        if accounts_config.lifecycle.is_offline() {
+            warn!("Running offline, the clock is not synced with the remote cluster");
+            return Ok(());
+        }
+        let rpc_cluster =
+            try_rpc_cluster_from_cluster(&accounts_config.remote_cluster)?;
+        info!("Syncing clock with remote cluster at {}", rpc_cluster.url());
+        self.clock_sync_ticker = Some(init_clock_sync_ticker(
+            &self.bank,
+            rpc_cluster.url().to_string(),
+            Duration::from_millis(clock_sync.poll_interval_millis),
+            self.token.clone(),
+        ));
         Ok(())
     }
 
@@ -571,16 +1203,22 @@ impl MagicValidator {
             self.remote_account_fetcher_worker.take()
         {
             let cancellation_token = self.token.clone();
+            let supervisor = self.supervisor.clone();
             self.remote_account_fetcher_handle =
                 Some(thread::spawn(move || {
-                    create_worker_runtime("remote_account_fetcher_worker")
-                        .block_on(async move {
-                            remote_account_fetcher_worker
-                                .start_fetch_request_processing(
-                                    cancellation_token,
-                                )
-                                .await;
-                        });
+                    let runtime =
+                        create_worker_runtime("remote_account_fetcher_worker");
+                    supervisor.supervise_blocking(
+                        "remote_account_fetcher_worker",
+                        || {
+                            runtime.block_on(
+                                remote_account_fetcher_worker
+                                    .start_fetch_request_processing(
+                                        cancellation_token.clone(),
+                                    ),
+                            )
+                        },
+                    );
                 }));
         }
     }
@@ -590,53 +1228,240 @@ impl MagicValidator {
             self.remote_account_updates_worker.take()
         {
             let cancellation_token = self.token.clone();
+            let supervisor = self.supervisor.clone();
             self.remote_account_updates_handle =
                 Some(thread::spawn(move || {
-                    create_worker_runtime("remote_account_updates_worker")
-                        .block_on(async move {
-                            remote_account_updates_worker
-                                .start_monitoring_request_processing(
-                                    cancellation_token,
-                                )
-                                .await
-                        });
+                    let runtime =
+                        create_worker_runtime("remote_account_updates_worker");
+                    supervisor.supervise_blocking(
+                        "remote_account_updates_worker",
+                        || {
+                            runtime.block_on(
+                                remote_account_updates_worker
+                                    .start_monitoring_request_processing(
+                                        cancellation_token.clone(),
+                                    ),
+                            )
+                        },
+                    );
                 }));
         }
     }
 
-    async fn start_remote_account_cloner_worker(&mut self) {
+    async fn start_remote_account_cloner_worker(&mut self) -> ApiResult<()> {
         if let Some(mut remote_account_cloner_worker) =
             self.remote_account_cloner_worker.take()
         {
             if !self.config.ledger.reset {
-                remote_account_cloner_worker.hydrate().await;
+                self.bank
+                    .validator_context()
+                    .enter_stage(ValidatorStage::Hydrating);
+                let report = remote_account_cloner_worker.hydrate().await;
+                self.handle_hydrate_report(report)?;
             }
 
             let cancellation_token = self.token.clone();
+            let supervisor = self.supervisor.clone();
+            let clone_listeners =
+                remote_account_cloner_worker.get_clone_listeners();
             self.remote_account_cloner_handle =
                 Some(thread::spawn(move || {
-                    create_worker_runtime("remote_account_cloner_worker")
-                        .block_on(async move {
-                            remote_account_cloner_worker
-                                .start_clone_request_processing(
-                                    cancellation_token,
-                                )
-                                .await
-                        });
+                    let runtime =
+                        create_worker_runtime("remote_account_cloner_worker");
+                    supervisor.supervise_blocking_with_recovery(
+                        "remote_account_cloner_worker",
+                        || {
+                            runtime.block_on(
+                                remote_account_cloner_worker
+                                    .start_clone_request_processing(
+                                        cancellation_token.clone(),
+                                    ),
+                            )
+                        },
+                        || fail_pending_clones(&clone_listeners),
+                    );
                 }));
         }
+        Ok(())
+    }
+
+    /// Logs the outcome of hydrating the accounts of the ledger and exposes
+    /// it via the admin RPC.
+    /// Fails if hydrating accounts that may be delegated to us failed and
+    /// the config requires them to be hydrated.
+    fn handle_hydrate_report(&self, report: HydrateReport) -> ApiResult<()> {
+        info!(
+            "Hydrated accounts: {} cloned, {} skipped, {} failed",
+            report.cloned,
+            report.skipped,
+            report.failed()
+        );
+        for failure in &report.failures {
+            warn!(
+                "Failed to hydrate {} owned by {}{}: {}",
+                failure.pubkey,
+                failure.owner,
+                if failure.may_be_delegated {
+                    " (may be delegated)"
+                } else {
+                    ""
+                },
+                failure.reason
+            );
+        }
+        let delegated_failures = report.delegated_failures().count();
+        self.hydrate_report.set(report);
+
+        if delegated_failures > 0
+            && self.config.accounts.require_delegated_hydration
+        {
+            return Err(ApiError::FailedToHydrateDelegatedAccounts(
+                delegated_failures,
+            ));
+        }
+        Ok(())
+    }
+
+    /// Rotates the validator identity to the provided keypair without
+    /// requiring a ledger reset, see [rotate_validator_identity].
+    /// Returns the pubkey of the replaced identity.
+    pub async fn rotate_validator_identity(
+        &self,
+        new_keypair: Keypair,
+    ) -> ApiResult<Pubkey> {
+        rotate_validator_identity(
+            &self.bank,
+            self.ledger.ledger_path(),
+            &self.accounts_manager,
+            new_keypair,
+        )
+        .await
+    }
+
+    /// Shuts the validator down gracefully, i.e. it
+    ///
+    /// 1. stops accepting transactions via RPC
+    /// 2. waits for pending account clones to complete, clones requested
+    ///    from now on fail
+    /// 3. stops advancing slots and sends the scheduled commits, including
+    ///    delayed ones, and due commits to chain
+    /// 4. flushes accounts and the ledger, including account mod data, to disk
+    /// 5. stops all services
+    ///
+    /// Steps 2 and 3 take at most the configured max drain time.
+    pub async fn shutdown(&mut self) {
+        let max_drain = Duration::from_millis(
+            self.config.validator.shutdown_max_drain_millis,
+        );
+        let deadline = tokio::time::Instant::now() + max_drain;
+        info!(
+            "Shutting down validator, draining for at most {:?}",
+            max_drain
+        );
+
+        // 1. Stop accepting transactions
+        self.bank
+            .validator_context()
+            .enter_stage(ValidatorStage::Draining);
+        self.rpc_service.shutdown().stop_accepting_transactions();
+
+        // 2. Drain clones that transactions are waiting for
+        if tokio::time::timeout_at(deadline, self.drain_pending_clones())
+            .await
+            .is_err()
+        {
+            warn!(
+                "Timed out waiting for {} pending clones",
+                self.pending_clones_len()
+            );
+        }
+
+        // 3. Stop advancing slots and flush commits to chain
+        self.exit.store(true, Ordering::Relaxed);
+        if let Some(slot_ticker) = self.slot_ticker.take() {
+            if tokio::time::timeout_at(deadline, slot_ticker)
+                .await
+                .is_err()
+            {
+                warn!("Timed out waiting for slot ticker to stop");
+            }
+        }
+        if tokio::time::timeout_at(deadline, self.flush_commits())
+            .await
+            .is_err()
+        {
+            warn!("Timed out flushing commits to chain");
+        }
+        let remaining_commits = self.accounts_manager.scheduled_commits_len();
+        if remaining_commits > 0 {
+            warn!(
+                "Shutting down with {} scheduled commits that were not sent",
+                remaining_commits
+            );
+        }
+
+        // 4. Persist accounts and ledger
+        {
+            let _lock = lock_transactions();
+            flush_accounts(&self.bank);
+        }
+        persist_account_journal(&self.bank, &self.ledger);
+        persist_escrow_settlements(&self.bank, &self.ledger);
+        self.transaction_listener.flush_ledger_writes();
+        if let Err(err) = self.ledger.flush() {
+            error!("Failed to flush ledger: {:?}", err);
+        }
+
+        // 5. Stop all services
+        self.stop();
+        info!("Validator shut down");
+    }
+
+    fn pending_clones_len(&self) -> usize {
+        self.remote_account_cloner_listeners.read_robust().len()
+    }
+
+    async fn drain_pending_clones(&self) {
+        while self.pending_clones_len() > 0 {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    }
+
+    async fn flush_commits(&self) {
+        if self.is_replaying_inputs() {
+            return;
+        }
+        accept_and_process_scheduled_commits(
+            &self.bank,
+            &self.accounts_manager,
+            Some(&self.transaction_status_sender),
+            true,
+        )
+        .await;
+        if let Err(err) = self.accounts_manager.commit_delegated().await {
+            error!("Failed to commit delegated accounts: {:?}", err);
+        }
+    }
+
+    /// Used to wait for shutdown requests made via the admin RPC.
+    pub fn rpc_shutdown(&self) -> RpcShutdown {
+        self.rpc_service.shutdown()
     }
 
     pub fn stop(&self) {
+        self.bank
+            .validator_context()
+            .enter_stage(ValidatorStage::Stopped);
         self.exit.store(true, Ordering::Relaxed);
         self.rpc_service.close();
         PubsubService::close(&self.pubsub_close_handle);
         self.token.cancel();
+        magicblock_telemetry::shutdown_telemetry();
     }
 
     pub fn join(&self) {
         self.rpc_service.join().unwrap();
-        if let Some(x) = self.pubsub_handle.write().unwrap().take() {
+        if let Some(x) = self.pubsub_handle.write_robust().take() {
             x.join().unwrap()
         }
     }
@@ -652,6 +1477,23 @@ impl MagicValidator {
     pub fn ledger(&self) -> &Ledger {
         &self.ledger
     }
+
+    /// Ensures accounts are cloned and commits them, see [AccountsManager].
+    pub fn accounts_manager(&self) -> Arc<AccountsManager> {
+        self.accounts_manager.clone()
+    }
+
+    pub fn geyser_rpc_service(&self) -> Arc<GeyserRpcService> {
+        self.geyser_rpc_service.clone()
+    }
+
+    pub fn rpc_addr(&self) -> &SocketAddr {
+        self.rpc_service.rpc_addr()
+    }
+
+    pub fn pubsub_addr(&self) -> &SocketAddr {
+        self.pubsub_config.socket()
+    }
 }
 
 fn programs_to_load(programs: &[ProgramConfig]) -> Vec<(Pubkey, String)> {
@@ -0,0 +1,733 @@
+use std::{
+    collections::{BTreeSet, HashMap, VecDeque},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use conjunto_transwise::AccountChainSnapshotShared;
+use futures_util::future::{ready, BoxFuture};
+use log::*;
+use magicblock_account_fetcher::{
+    AccountFetcher, AccountFetcherError, AccountFetcherResult,
+};
+use magicblock_account_updates::{AccountUpdates, AccountUpdatesResult};
+use magicblock_bank::bank::Bank;
+use magicblock_core::robust_lock::RobustMutex;
+use magicblock_ledger::{
+    errors::{LedgerError, LedgerResult},
+    Ledger,
+};
+use serde::{Deserialize, Serialize};
+use solana_sdk::{
+    clock::{Clock, Slot, UnixTimestamp},
+    pubkey::Pubkey,
+};
+
+use crate::errors::{ApiError, ApiResult};
+
+/// An input received from outside of the validator which influences how
+/// accounts are cloned and committed.
+/// Inputs are ordered by a sequence across all slots, see
+/// [serialize_replay_input] for how they are persisted.
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) enum ReplayInput {
+    /// The result of fetching the chain snapshot of an account
+    ChainSnapshot {
+        pubkey: Pubkey,
+        snapshot: Result<AccountChainSnapshotShared, String>,
+    },
+    /// The first slot at which the account was monitored, recorded when it
+    /// changes
+    FirstSubscribedSlot { pubkey: Pubkey, slot: Option<Slot> },
+    /// The remote slot of the last update of the account, recorded when it
+    /// changes
+    LastKnownUpdateSlot { pubkey: Pubkey, slot: Option<Slot> },
+    /// The unix timestamp of the clock sysvar at the start of the slot
+    ClockTimestamp { timestamp: UnixTimestamp },
+    /// The clock sysvar at the start of the slot while it tracks the remote
+    /// clock, recorded instead of the [Self::ClockTimestamp]
+    RemoteClock { clock: Clock },
+}
+
+/// The clock a slot started with as recorded by
+/// [ReplayInput::ClockTimestamp] or [ReplayInput::RemoteClock].
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum RecordedClock {
+    Timestamp(UnixTimestamp),
+    Remote(Clock),
+}
+
+/// Determines how the validator treats the inputs received from outside.
+#[derive(Clone)]
+pub(crate) enum LedgerInputs {
+    Record(Arc<ReplayInputRecorder>),
+    Replay(Arc<RecordedInputs>),
+}
+
+/// A persisted input with the (slot, seq) index it was recorded at.
+pub(crate) type RawReplayInput = ((Slot, u64), Box<[u8]>);
+
+impl LedgerInputs {
+    pub(crate) fn is_replay(&self) -> bool {
+        matches!(self, Self::Replay(_))
+    }
+}
+
+// -----------------
+// Recording
+// -----------------
+pub(crate) struct ReplayInputRecorder {
+    ledger: Arc<Ledger>,
+    bank: Arc<Bank>,
+    next_seq: AtomicU64,
+    first_subscribed_slots: Mutex<HashMap<Pubkey, Option<Slot>>>,
+    last_known_update_slots: Mutex<HashMap<Pubkey, Option<Slot>>>,
+}
+
+impl ReplayInputRecorder {
+    /// Continues after the inputs that were recorded to the [ledger] before.
+    pub(crate) fn try_new(
+        ledger: Arc<Ledger>,
+        bank: Arc<Bank>,
+    ) -> LedgerResult<Self> {
+        let next_seq = ledger
+            .get_last_replay_input_index()?
+            .map_or(0, |(_, seq)| seq + 1);
+        Ok(Self {
+            ledger,
+            bank,
+            next_seq: AtomicU64::new(next_seq),
+            first_subscribed_slots: Default::default(),
+            last_known_update_slots: Default::default(),
+        })
+    }
+
+    /// The slot is only stored alongside the input so that the ledger can be
+    /// truncated, the sequence alone orders the inputs.
+    /// Failing to record an input must not affect the validator, thus
+    /// errors are only logged.
+    pub(crate) fn record(&self, input: &ReplayInput) {
+        let seq = self.next_seq.fetch_add(1, Ordering::Relaxed);
+        let result = serialize_replay_input(input)
+            .map_err(Into::into)
+            .and_then(|bytes| {
+                self.ledger
+                    .write_replay_input(self.bank.slot(), seq, &bytes)
+            });
+        if let Err(err) = result {
+            error!("Failed to record input {:?}: {:?}", input, err);
+        }
+    }
+
+    fn record_first_subscribed_slot(&self, pubkey: Pubkey, slot: Option<Slot>) {
+        if changed(&self.first_subscribed_slots, pubkey, slot) {
+            self.record(&ReplayInput::FirstSubscribedSlot { pubkey, slot });
+        }
+    }
+
+    fn record_last_known_update_slot(
+        &self,
+        pubkey: Pubkey,
+        slot: Option<Slot>,
+    ) {
+        if changed(&self.last_known_update_slots, pubkey, slot) {
+            self.record(&ReplayInput::LastKnownUpdateSlot { pubkey, slot });
+        }
+    }
+}
+
+fn changed(
+    slots: &Mutex<HashMap<Pubkey, Option<Slot>>>,
+    pubkey: Pubkey,
+    slot: Option<Slot>,
+) -> bool {
+    slots.lock_robust().insert(pubkey, slot) != Some(slot)
+}
+
+/// Records the chain snapshots fetched by the [inner] fetcher.
+pub(crate) struct RecordingAccountFetcher<T> {
+    inner: T,
+    recorder: Arc<ReplayInputRecorder>,
+}
+
+impl<T: AccountFetcher> RecordingAccountFetcher<T> {
+    pub(crate) fn new(inner: T, recorder: Arc<ReplayInputRecorder>) -> Self {
+        Self { inner, recorder }
+    }
+}
+
+impl<T: AccountFetcher> AccountFetcher for RecordingAccountFetcher<T> {
+    fn fetch_account_chain_snapshot(
+        &self,
+        pubkey: &Pubkey,
+        min_context_slot: Option<Slot>,
+    ) -> BoxFuture<AccountFetcherResult<AccountChainSnapshotShared>> {
+        let pubkey = *pubkey;
+        let fetch = self
+            .inner
+            .fetch_account_chain_snapshot(&pubkey, min_context_slot);
+        let recorder = self.recorder.clone();
+        Box::pin(async move {
+            let result = fetch.await;
+            recorder.record(&ReplayInput::ChainSnapshot {
+                pubkey,
+                snapshot: result.clone().map_err(|err| err.to_string()),
+            });
+            result
+        })
+    }
+}
+
+/// Records the slots of the account updates observed by the [inner] one.
+pub(crate) struct RecordingAccountUpdates<T> {
+    inner: T,
+    recorder: Arc<ReplayInputRecorder>,
+}
+
+impl<T: AccountUpdates> RecordingAccountUpdates<T> {
+    pub(crate) fn new(inner: T, recorder: Arc<ReplayInputRecorder>) -> Self {
+        Self { inner, recorder }
+    }
+}
+
+impl<T: AccountUpdates> AccountUpdates for RecordingAccountUpdates<T> {
+    fn ensure_account_monitoring(
+        &self,
+        pubkey: &Pubkey,
+    ) -> AccountUpdatesResult<()> {
+        self.inner.ensure_account_monitoring(pubkey)
+    }
+
+    fn get_first_subscribed_slot(&self, pubkey: &Pubkey) -> Option<Slot> {
+        let slot = self.inner.get_first_subscribed_slot(pubkey);
+        self.recorder.record_first_subscribed_slot(*pubkey, slot);
+        slot
+    }
+
+    fn get_last_known_update_slot(&self, pubkey: &Pubkey) -> Option<Slot> {
+        let slot = self.inner.get_last_known_update_slot(pubkey);
+        self.recorder.record_last_known_update_slot(*pubkey, slot);
+        slot
+    }
+}
+
+// -----------------
+// Persistence
+// -----------------
+/// Prefixes the persisted inputs, followed by the schema version they were
+/// written with.
+/// Inputs recorded before they were versioned start with the bincode variant
+/// index of the [ReplayInput] instead, which never matches it.
+const REPLAY_INPUT_MAGIC: [u8; 4] = *b"MBRI";
+
+/// The schema version of inputs recorded before they were versioned.
+const UNVERSIONED_SCHEMA_VERSION: u16 = 0;
+
+/// The schema version inputs are recorded with.
+///
+/// It needs to be bumped whenever the serialization of [ReplayInput]
+/// changes, which includes upgrades of conjunto-transwise that change the
+/// layout of the [AccountChainSnapshotShared] it embeds. A frozen copy of
+/// the previous layout then needs to be kept in order to migrate the inputs
+/// recorded with it in [migrate_replay_input].
+const REPLAY_INPUT_SCHEMA_VERSION: u16 = 2;
+
+const REPLAY_INPUT_HEADER_LEN: usize =
+    REPLAY_INPUT_MAGIC.len() + std::mem::size_of::<u16>();
+
+/// Serializes the [input] prefixed with the schema version it is written
+/// with.
+fn serialize_replay_input(input: &ReplayInput) -> bincode::Result<Vec<u8>> {
+    let payload = bincode::serialize(input)?;
+    let mut bytes = Vec::with_capacity(REPLAY_INPUT_HEADER_LEN + payload.len());
+    bytes.extend_from_slice(&REPLAY_INPUT_MAGIC);
+    bytes.extend_from_slice(&REPLAY_INPUT_SCHEMA_VERSION.to_le_bytes());
+    bytes.extend_from_slice(&payload);
+    Ok(bytes)
+}
+
+/// Deserializes an input persisted via [serialize_replay_input] or before
+/// inputs were versioned, migrating it to the current schema if needed.
+/// Inputs recorded with a newer schema, i.e. by a later version of the
+/// validator, are rejected instead of being misinterpreted.
+fn deserialize_replay_input(bytes: &[u8]) -> ApiResult<ReplayInput> {
+    let (version, payload) = split_schema_version(bytes);
+    migrate_replay_input(version, payload)
+}
+
+fn split_schema_version(bytes: &[u8]) -> (u16, &[u8]) {
+    match bytes.strip_prefix(&REPLAY_INPUT_MAGIC) {
+        Some(rest) if rest.len() >= std::mem::size_of::<u16>() => {
+            let (version, payload) = rest.split_at(std::mem::size_of::<u16>());
+            (u16::from_le_bytes([version[0], version[1]]), payload)
+        }
+        _ => (UNVERSIONED_SCHEMA_VERSION, bytes),
+    }
+}
+
+fn migrate_replay_input(
+    version: u16,
+    payload: &[u8],
+) -> ApiResult<ReplayInput> {
+    match version {
+        // Version 2 only appended the [ReplayInput::RemoteClock] variant,
+        // thus inputs recorded before decode the same way
+        UNVERSIONED_SCHEMA_VERSION | 1 | REPLAY_INPUT_SCHEMA_VERSION => {
+            Ok(bincode::deserialize(payload).map_err(LedgerError::from)?)
+        }
+        _ => Err(ApiError::UnsupportedReplayInputSchemaVersion(
+            version,
+            REPLAY_INPUT_SCHEMA_VERSION,
+        )),
+    }
+}
+
+// -----------------
+// Replaying
+// -----------------
+/// Advances the slot of the [bank] with the [clock] recorded for it, or the
+/// current time if none is left.
+pub(crate) fn replay_next_slot(
+    bank: &Bank,
+    clock: Option<RecordedClock>,
+) -> Slot {
+    match clock {
+        Some(RecordedClock::Timestamp(timestamp)) => {
+            bank.advance_slot_at(Some(timestamp))
+        }
+        // The epoch of the clock is derived from the remote clock while the
+        // timestamp extrapolated from it is replaced by the recorded one
+        Some(RecordedClock::Remote(clock)) => {
+            let timestamp = clock.unix_timestamp;
+            bank.set_remote_clock(clock);
+            bank.advance_slot_at(Some(timestamp))
+        }
+        None => bank.advance_slot(),
+    }
+}
+
+type RecordedChainSnapshots = HashMap<
+    Pubkey,
+    VecDeque<(u64, Result<AccountChainSnapshotShared, String>)>,
+>;
+/// The value of each change together with the sequence it was recorded at.
+type RecordedSlots = HashMap<Pubkey, Vec<(u64, Option<Slot>)>>;
+
+/// The chain snapshots and clocks which were not replayed yet together with
+/// the sequence they were recorded at.
+#[derive(Default)]
+struct ReplayProgress {
+    chain_snapshots: RecordedChainSnapshots,
+    clocks: VecDeque<(u64, RecordedClock)>,
+    /// The sequences of the inputs above
+    pending_seqs: BTreeSet<u64>,
+    /// The highest sequence replayed so far
+    last_replayed_seq: Option<u64>,
+}
+
+impl ReplayProgress {
+    fn replayed(&mut self, seq: u64) {
+        if self.pending_seqs.first() != Some(&seq) {
+            debug!("Replaying input {} out of the recorded order", seq);
+        }
+        self.pending_seqs.remove(&seq);
+        self.last_replayed_seq = self.last_replayed_seq.max(Some(seq));
+    }
+
+    /// Returns the sequence up to which the recorded slot changes were
+    /// observed when recording, which is right before the next input that
+    /// is still to be replayed.
+    fn observed_until(&self) -> u64 {
+        let from = self.last_replayed_seq.map_or(0, |seq| seq + 1);
+        self.pending_seqs
+            .range(from..)
+            .next()
+            .copied()
+            .unwrap_or(u64::MAX)
+    }
+}
+
+/// The inputs recorded to a ledger, replayed in the order of the sequence
+/// they were recorded at.
+/// Chain snapshots and clocks are consumed when replayed which
+/// advances the replay through that sequence. The account update slots are
+/// only recorded when they change and thus replayed as the value they had
+/// at that point of the sequence.
+pub(crate) struct RecordedInputs {
+    progress: Mutex<ReplayProgress>,
+    first_subscribed_slots: RecordedSlots,
+    last_known_update_slots: RecordedSlots,
+}
+
+impl RecordedInputs {
+    pub(crate) fn try_new(raw_inputs: &[RawReplayInput]) -> ApiResult<Self> {
+        let mut raw_inputs = raw_inputs.iter().collect::<Vec<_>>();
+        raw_inputs.sort_by_key(|((_, seq), _)| *seq);
+
+        let mut progress = ReplayProgress::default();
+        let mut first_subscribed_slots = RecordedSlots::new();
+        let mut last_known_update_slots = RecordedSlots::new();
+        for ((_, seq), raw_input) in raw_inputs {
+            let seq = *seq;
+            match deserialize_replay_input(raw_input)? {
+                ReplayInput::ChainSnapshot { pubkey, snapshot } => {
+                    progress
+                        .chain_snapshots
+                        .entry(pubkey)
+                        .or_default()
+                        .push_back((seq, snapshot));
+                    progress.pending_seqs.insert(seq);
+                }
+                ReplayInput::FirstSubscribedSlot { pubkey, slot } => {
+                    first_subscribed_slots
+                        .entry(pubkey)
+                        .or_default()
+                        .push((seq, slot))
+                }
+                ReplayInput::LastKnownUpdateSlot { pubkey, slot } => {
+                    last_known_update_slots
+                        .entry(pubkey)
+                        .or_default()
+                        .push((seq, slot))
+                }
+                ReplayInput::ClockTimestamp { timestamp } => {
+                    progress
+                        .clocks
+                        .push_back((seq, RecordedClock::Timestamp(timestamp)));
+                    progress.pending_seqs.insert(seq);
+                }
+                ReplayInput::RemoteClock { clock } => {
+                    progress
+                        .clocks
+                        .push_back((seq, RecordedClock::Remote(clock)));
+                    progress.pending_seqs.insert(seq);
+                }
+            }
+        }
+        Ok(Self {
+            progress: Mutex::new(progress),
+            first_subscribed_slots,
+            last_known_update_slots,
+        })
+    }
+
+    /// Returns the clock recorded at the start of the next slot.
+    pub(crate) fn next_clock(&self) -> Option<RecordedClock> {
+        let mut progress = self.progress.lock_robust();
+        let (seq, clock) = progress.clocks.pop_front()?;
+        progress.replayed(seq);
+        Some(clock)
+    }
+
+    fn next_chain_snapshot(
+        &self,
+        pubkey: &Pubkey,
+    ) -> AccountFetcherResult<AccountChainSnapshotShared> {
+        let mut progress = self.progress.lock_robust();
+        let Some((seq, snapshot)) = progress
+            .chain_snapshots
+            .get_mut(pubkey)
+            .and_then(VecDeque::pop_front)
+        else {
+            return Err(AccountFetcherError::FailedToFetch(format!(
+                "no recorded chain snapshot left for {}",
+                pubkey
+            )));
+        };
+        progress.replayed(seq);
+        snapshot.map_err(AccountFetcherError::FailedToFetch)
+    }
+
+    /// Returns the value that was observed at the current point of the
+    /// replay.
+    fn observed_slot(
+        &self,
+        slots: &RecordedSlots,
+        pubkey: &Pubkey,
+    ) -> Option<Slot> {
+        let observed_until = self.progress.lock_robust().observed_until();
+        slots.get(pubkey).and_then(|slots| {
+            slots
+                .iter()
+                .rev()
+                .find(|(seq, _)| *seq < observed_until)
+                .and_then(|(_, slot)| *slot)
+        })
+    }
+}
+
+/// Serves the recorded chain snapshots of each account in order instead of
+/// fetching them from the remote cluster.
+pub(crate) struct ReplayAccountFetcher {
+    inputs: Arc<RecordedInputs>,
+}
+
+impl ReplayAccountFetcher {
+    pub(crate) fn new(inputs: Arc<RecordedInputs>) -> Self {
+        Self { inputs }
+    }
+}
+
+impl AccountFetcher for ReplayAccountFetcher {
+    fn fetch_account_chain_snapshot(
+        &self,
+        pubkey: &Pubkey,
+        _min_context_slot: Option<Slot>,
+    ) -> BoxFuture<AccountFetcherResult<AccountChainSnapshotShared>> {
+        Box::pin(ready(self.inputs.next_chain_snapshot(pubkey)))
+    }
+}
+
+/// Serves the recorded account update slots instead of monitoring the
+/// accounts on the remote cluster.
+pub(crate) struct ReplayAccountUpdates {
+    inputs: Arc<RecordedInputs>,
+}
+
+impl ReplayAccountUpdates {
+    pub(crate) fn new(inputs: Arc<RecordedInputs>) -> Self {
+        Self { inputs }
+    }
+}
+
+impl AccountUpdates for ReplayAccountUpdates {
+    fn ensure_account_monitoring(
+        &self,
+        _pubkey: &Pubkey,
+    ) -> AccountUpdatesResult<()> {
+        Ok(())
+    }
+
+    fn get_first_subscribed_slot(&self, pubkey: &Pubkey) -> Option<Slot> {
+        self.inputs
+            .observed_slot(&self.inputs.first_subscribed_slots, pubkey)
+    }
+
+    fn get_last_known_update_slot(&self, pubkey: &Pubkey) -> Option<Slot> {
+        self.inputs
+            .observed_slot(&self.inputs.last_known_update_slots, pubkey)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use magicblock_account_fetcher::AccountFetcherStub;
+    use magicblock_account_updates::AccountUpdatesStub;
+    use magicblock_bank::genesis_utils::create_genesis_config_with_leader_and_fees;
+    use test_tools::bank::{bank_for_tests, bank_with_fees_for_tests};
+
+    use super::*;
+
+    fn raw_input(slot: Slot, seq: u64, input: &ReplayInput) -> RawReplayInput {
+        (
+            (slot, seq),
+            serialize_replay_input(input).unwrap().into_boxed_slice(),
+        )
+    }
+
+    #[test]
+    fn test_replay_input_round_trip() {
+        let pubkey = Pubkey::new_unique();
+        let bytes = serialize_replay_input(&ReplayInput::LastKnownUpdateSlot {
+            pubkey,
+            slot: Some(42),
+        })
+        .unwrap();
+        assert_eq!(&bytes[..REPLAY_INPUT_MAGIC.len()], &REPLAY_INPUT_MAGIC);
+        assert_eq!(split_schema_version(&bytes).0, REPLAY_INPUT_SCHEMA_VERSION);
+
+        match deserialize_replay_input(&bytes).unwrap() {
+            ReplayInput::LastKnownUpdateSlot {
+                pubkey: deserialized_pubkey,
+                slot,
+            } => {
+                assert_eq!(deserialized_pubkey, pubkey);
+                assert_eq!(slot, Some(42));
+            }
+            input => panic!("unexpected input {:?}", input),
+        }
+    }
+
+    #[test]
+    fn test_replay_input_without_schema_version_is_migrated() {
+        // Inputs recorded before they were versioned are plain bincode
+        let bytes =
+            bincode::serialize(&ReplayInput::ClockTimestamp { timestamp: 7 })
+                .unwrap();
+        assert_eq!(split_schema_version(&bytes).0, UNVERSIONED_SCHEMA_VERSION);
+
+        assert!(matches!(
+            deserialize_replay_input(&bytes).unwrap(),
+            ReplayInput::ClockTimestamp { timestamp: 7 }
+        ));
+    }
+
+    #[test]
+    fn test_replay_input_with_newer_schema_version_is_rejected() {
+        let newer_version = REPLAY_INPUT_SCHEMA_VERSION + 1;
+        let mut bytes = serialize_replay_input(&ReplayInput::ClockTimestamp {
+            timestamp: 7,
+        })
+        .unwrap();
+        bytes[REPLAY_INPUT_MAGIC.len()..REPLAY_INPUT_HEADER_LEN]
+            .copy_from_slice(&newer_version.to_le_bytes());
+
+        assert!(matches!(
+            deserialize_replay_input(&bytes),
+            Err(ApiError::UnsupportedReplayInputSchemaVersion(
+                version,
+                REPLAY_INPUT_SCHEMA_VERSION
+            )) if version == newer_version
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_recorded_inputs_replay_in_recorded_order() {
+        let ledger_dir = tempfile::tempdir().unwrap();
+        let ledger = Arc::new(Ledger::open(ledger_dir.path()).unwrap());
+        let bank = Arc::new(bank_with_fees_for_tests());
+        let recorder = Arc::new(
+            ReplayInputRecorder::try_new(ledger.clone(), bank).unwrap(),
+        );
+
+        let undelegated = Pubkey::new_unique();
+        let delegated = Pubkey::new_unique();
+        let unknown = Pubkey::new_unique();
+        let fetcher_stub = AccountFetcherStub::default();
+        fetcher_stub.set_undelegated_account(undelegated, 10);
+        fetcher_stub.set_delegated_account(delegated, 11, 5);
+        let updates_stub = AccountUpdatesStub::default();
+        updates_stub.set_last_known_update_slot(undelegated, 3);
+        let fetcher =
+            RecordingAccountFetcher::new(fetcher_stub, recorder.clone());
+        let updates = RecordingAccountUpdates::new(
+            updates_stub.clone(),
+            recorder.clone(),
+        );
+
+        // Record
+        let snapshot = fetcher
+            .fetch_account_chain_snapshot(&undelegated, None)
+            .await
+            .unwrap();
+        assert_eq!(snapshot.at_slot, 10);
+        assert_eq!(updates.get_last_known_update_slot(&undelegated), Some(3));
+        // Unchanged values are not recorded again
+        assert_eq!(updates.get_last_known_update_slot(&undelegated), Some(3));
+        fetcher
+            .fetch_account_chain_snapshot(&delegated, None)
+            .await
+            .unwrap();
+        updates_stub.set_last_known_update_slot(undelegated, 4);
+        assert_eq!(updates.get_last_known_update_slot(&undelegated), Some(4));
+        assert!(fetcher
+            .fetch_account_chain_snapshot(&unknown, None)
+            .await
+            .is_err());
+        recorder.record(&ReplayInput::ClockTimestamp { timestamp: 42 });
+
+        // Replay
+        let raw_inputs = ledger.get_replay_inputs().unwrap();
+        assert_eq!(raw_inputs.len(), 6);
+        let inputs = Arc::new(RecordedInputs::try_new(&raw_inputs).unwrap());
+        let fetcher = ReplayAccountFetcher::new(inputs.clone());
+        let updates = ReplayAccountUpdates::new(inputs.clone());
+
+        let snapshot = fetcher
+            .fetch_account_chain_snapshot(&undelegated, None)
+            .await
+            .unwrap();
+        assert_eq!(snapshot.pubkey, undelegated);
+        assert_eq!(snapshot.at_slot, 10);
+        assert_eq!(updates.get_last_known_update_slot(&undelegated), Some(3));
+        assert_eq!(updates.get_first_subscribed_slot(&undelegated), None);
+        let snapshot = fetcher
+            .fetch_account_chain_snapshot(&delegated, None)
+            .await
+            .unwrap();
+        assert_eq!(snapshot.at_slot, 11);
+        assert_eq!(updates.get_last_known_update_slot(&undelegated), Some(4));
+        assert!(matches!(
+            fetcher.fetch_account_chain_snapshot(&unknown, None).await,
+            Err(AccountFetcherError::FailedToFetch(_))
+        ));
+        assert_eq!(inputs.next_clock(), Some(RecordedClock::Timestamp(42)));
+
+        // Every recorded input was replayed
+        assert_eq!(inputs.next_clock(), None);
+        assert!(fetcher
+            .fetch_account_chain_snapshot(&undelegated, None)
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn test_recorded_inputs_are_ordered_by_sequence_not_slot() {
+        let pubkey = Pubkey::new_unique();
+        let raw_inputs = vec![
+            raw_input(1, 3, &ReplayInput::ClockTimestamp { timestamp: 20 }),
+            raw_input(
+                1,
+                2,
+                &ReplayInput::FirstSubscribedSlot {
+                    pubkey,
+                    slot: Some(7),
+                },
+            ),
+            raw_input(
+                2,
+                0,
+                &ReplayInput::FirstSubscribedSlot {
+                    pubkey,
+                    slot: Some(5),
+                },
+            ),
+            raw_input(2, 1, &ReplayInput::ClockTimestamp { timestamp: 10 }),
+        ];
+        let inputs = RecordedInputs::try_new(&raw_inputs).unwrap();
+        let updates = ReplayAccountUpdates::new(Arc::new(inputs));
+        let inputs = &updates.inputs;
+
+        // Observed before the first clock timestamp
+        assert_eq!(updates.get_first_subscribed_slot(&pubkey), Some(5));
+        assert_eq!(inputs.next_clock(), Some(RecordedClock::Timestamp(10)));
+        assert_eq!(updates.get_first_subscribed_slot(&pubkey), Some(7));
+        assert_eq!(inputs.next_clock(), Some(RecordedClock::Timestamp(20)));
+        assert_eq!(inputs.next_clock(), None);
+    }
+
+    #[test]
+    fn test_remote_clock_replays_the_recorded_clock() {
+        let genesis_config = create_genesis_config_with_leader_and_fees(
+            u64::MAX,
+            &Pubkey::new_unique(),
+        )
+        .genesis_config;
+        let recording_bank = bank_for_tests(&genesis_config, None, None);
+        recording_bank.set_remote_clock(Clock {
+            slot: 300_000_000,
+            epoch_start_timestamp: 4_000_000_000,
+            epoch: 700,
+            leader_schedule_epoch: 701,
+            unix_timestamp: 4_000_100_000,
+        });
+        recording_bank.advance_slot();
+        let recorded_clock = recording_bank.clock();
+
+        let raw_inputs = vec![raw_input(
+            1,
+            0,
+            &ReplayInput::RemoteClock {
+                clock: recorded_clock.clone(),
+            },
+        )];
+        let inputs = RecordedInputs::try_new(&raw_inputs).unwrap();
+        let replaying_bank = bank_for_tests(&genesis_config, None, None);
+        replay_next_slot(&replaying_bank, inputs.next_clock());
+
+        assert_eq!(replaying_bank.clock(), recorded_clock);
+        assert!(replaying_bank.is_clock_synced());
+    }
+}
@@ -0,0 +1,312 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use futures_util::{stream, StreamExt};
+use geyser_grpc_proto::{
+    convert_from,
+    prelude::{
+        geyser_client::GeyserClient, subscribe_update::UpdateOneof,
+        CommitmentLevel, SubscribeRequest, SubscribeRequestFilterAccounts,
+        SubscribeRequestFilterSlots, SubscribeRequestFilterTransactions,
+        SubscribeUpdateAccount, SubscribeUpdateTransaction,
+    },
+};
+use log::*;
+use magicblock_accounts_db::FLUSH_ACCOUNTS_SLOT_FREQ;
+use magicblock_bank::bank::Bank;
+use magicblock_ledger::Ledger;
+use solana_sdk::{
+    account::AccountSharedData,
+    clock::Slot,
+    transaction::{MessageHash, SanitizedTransaction},
+};
+use tokio_util::sync::CancellationToken;
+
+use crate::accounts::flush_accounts;
+
+/// Keeps the bank and ledger of a read replica in sync with the primary
+/// validator by consuming the primary's geyser gRPC stream.
+///
+/// - account updates are stored in the bank as is
+/// - transactions are written to the ledger to serve history requests
+/// - slots advance the bank until it reached the slot of the primary
+///
+/// The replica produces its own blockhashes when advancing slots, thus they
+/// don't match the ones of the primary.
+/// The stream only contains updates made after we subscribed, so the replica
+/// should be started from a copy of the primary's ledger.
+pub fn init_replica_follower(
+    bank: &Arc<Bank>,
+    ledger: &Arc<Ledger>,
+    primary_geyser_grpc_url: String,
+    reconnect_delay: Duration,
+    token: CancellationToken,
+) -> tokio::task::JoinHandle<()> {
+    let bank = bank.clone();
+    let ledger = ledger.clone();
+    tokio::task::spawn(async move {
+        loop {
+            let follow =
+                follow_primary(&bank, &ledger, &primary_geyser_grpc_url);
+            tokio::select! {
+                result = follow => {
+                    if let Err(err) = result {
+                        warn!(
+                            "Lost stream of primary at {}: {}",
+                            primary_geyser_grpc_url, err
+                        );
+                    }
+                },
+                _ = token.cancelled() => {
+                    break;
+                }
+            }
+            tokio::select! {
+                _ = tokio::time::sleep(reconnect_delay) => {},
+                _ = token.cancelled() => {
+                    break;
+                }
+            }
+        }
+    })
+}
+
+async fn follow_primary(
+    bank: &Bank,
+    ledger: &Ledger,
+    primary_geyser_grpc_url: &str,
+) -> Result<(), String> {
+    let mut client = GeyserClient::connect(primary_geyser_grpc_url.to_string())
+        .await
+        .map_err(|err| format!("failed to connect: {err}"))?;
+    let mut updates = client
+        .subscribe(stream::iter(vec![subscribe_request()]))
+        .await
+        .map_err(|err| format!("failed to subscribe: {err}"))?
+        .into_inner();
+    info!("Following primary at {}", primary_geyser_grpc_url);
+
+    while let Some(update) = updates.next().await {
+        let update = update.map_err(|err| format!("stream failed: {err}"))?;
+        match update.update_oneof {
+            Some(UpdateOneof::Account(update)) => {
+                apply_account_update(bank, update)
+            }
+            Some(UpdateOneof::Transaction(update)) => {
+                apply_transaction_update(bank, ledger, update)
+            }
+            Some(UpdateOneof::Slot(update)) => {
+                advance_to_slot(bank, ledger, update.slot)
+            }
+            _ => {}
+        }
+    }
+    Err("stream ended".to_string())
+}
+
+fn subscribe_request() -> SubscribeRequest {
+    // Empty filters match all accounts and transactions
+    SubscribeRequest {
+        accounts: HashMap::from([(
+            "replica".to_string(),
+            SubscribeRequestFilterAccounts::default(),
+        )]),
+        slots: HashMap::from([(
+            "replica".to_string(),
+            SubscribeRequestFilterSlots::default(),
+        )]),
+        transactions: HashMap::from([(
+            "replica".to_string(),
+            SubscribeRequestFilterTransactions::default(),
+        )]),
+        commitment: Some(CommitmentLevel::Processed as i32),
+        ..Default::default()
+    }
+}
+
+fn apply_account_update(bank: &Bank, update: SubscribeUpdateAccount) {
+    let Some(account) = update.account else {
+        return;
+    };
+    match convert_from::create_account(account) {
+        Ok((pubkey, account)) => {
+            bank.store_account(&pubkey, &AccountSharedData::from(account))
+        }
+        Err(err) => warn!("Invalid account update from primary: {}", err),
+    }
+}
+
+fn apply_transaction_update(
+    bank: &Bank,
+    ledger: &Ledger,
+    update: SubscribeUpdateTransaction,
+) {
+    let Some(info) = update.transaction else {
+        return;
+    };
+    let index = info.index as usize;
+    let is_vote = info.is_vote;
+    let result = info
+        .transaction
+        .ok_or_else(|| "missing transaction".to_string())
+        .and_then(convert_from::create_tx_versioned)
+        .and_then(|tx| {
+            SanitizedTransaction::try_create(
+                tx,
+                MessageHash::Compute,
+                Some(is_vote),
+                bank,
+            )
+            .map_err(|err| err.to_string())
+        })
+        .and_then(|tx| {
+            let meta = info
+                .meta
+                .ok_or_else(|| "missing transaction meta".to_string())
+                .and_then(convert_from::create_tx_meta)?;
+            Ok((tx, meta))
+        });
+    match result {
+        Ok((tx, meta)) => {
+            if let Err(err) = ledger.write_transaction(
+                *tx.signature(),
+                update.slot,
+                tx,
+                meta,
+                index,
+            ) {
+                error!("Failed to write replicated transaction: {:?}", err);
+            }
+        }
+        Err(err) => warn!("Invalid transaction update from primary: {}", err),
+    }
+}
+
+fn advance_to_slot(bank: &Bank, ledger: &Ledger, slot: Slot) {
+    while bank.slot() < slot {
+        let prev_slot = bank.slot();
+        if prev_slot % FLUSH_ACCOUNTS_SLOT_FREQ == 0 {
+            // Accounts are only stored by this task, so they cannot change
+            // while we flush them
+            flush_accounts(bank);
+        }
+        let next_slot = bank.advance_slot();
+        magicblock_logger::set_log_slot(next_slot);
+        if let Err(err) = ledger.write_block(
+            prev_slot,
+            bank.clock().unix_timestamp,
+            bank.last_blockhash(),
+        ) {
+            error!("Failed to write block: {:?}", err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use geyser_grpc_proto::prelude::SubscribeUpdateAccountInfo;
+    use solana_sdk::{account::ReadableAccount, pubkey::Pubkey};
+    use test_tools::bank::bank_with_fees_for_tests;
+
+    use super::*;
+
+    fn account_update(
+        pubkey: Vec<u8>,
+        owner: &Pubkey,
+    ) -> SubscribeUpdateAccount {
+        SubscribeUpdateAccount {
+            account: Some(SubscribeUpdateAccountInfo {
+                pubkey,
+                lamports: 1_000,
+                owner: owner.to_bytes().to_vec(),
+                data: vec![1, 2, 3],
+                ..Default::default()
+            }),
+            slot: 1,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_subscribe_request_matches_everything() {
+        let request = subscribe_request();
+
+        assert!(request.accounts["replica"].account.is_empty());
+        assert!(request.accounts["replica"].owner.is_empty());
+        assert!(request.slots.contains_key("replica"));
+        assert!(request.transactions["replica"].account_include.is_empty());
+        assert_eq!(request.commitment, Some(CommitmentLevel::Processed as i32));
+    }
+
+    #[test]
+    fn test_account_update_is_stored_in_bank() {
+        let bank = bank_with_fees_for_tests();
+        let pubkey = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+
+        apply_account_update(
+            &bank,
+            account_update(pubkey.to_bytes().to_vec(), &owner),
+        );
+
+        let stored = bank.get_account(&pubkey).unwrap();
+        assert_eq!(stored.lamports(), 1_000);
+        assert_eq!(stored.data(), &[1, 2, 3]);
+        assert_eq!(stored.owner(), &owner);
+    }
+
+    #[test]
+    fn test_invalid_account_update_is_ignored() {
+        let bank = bank_with_fees_for_tests();
+        let pubkey = Pubkey::new_unique();
+
+        apply_account_update(
+            &bank,
+            account_update(
+                pubkey.to_bytes()[..16].to_vec(),
+                &Pubkey::new_unique(),
+            ),
+        );
+        apply_account_update(
+            &bank,
+            SubscribeUpdateAccount {
+                account: None,
+                ..Default::default()
+            },
+        );
+
+        assert!(bank.get_account(&pubkey).is_none());
+    }
+
+    #[test]
+    fn test_advance_to_slot_writes_a_block_per_slot() {
+        let ledger_dir = tempfile::tempdir().unwrap();
+        let ledger = Ledger::open(ledger_dir.path()).unwrap();
+        let bank = bank_with_fees_for_tests();
+        let start_slot = bank.slot();
+
+        advance_to_slot(&bank, &ledger, start_slot + 3);
+
+        assert_eq!(bank.slot(), start_slot + 3);
+        assert_eq!(
+            ledger.get_slot_range().unwrap(),
+            Some((start_slot, start_slot + 2))
+        );
+    }
+
+    #[test]
+    fn test_advance_to_older_slot_does_nothing() {
+        let ledger_dir = tempfile::tempdir().unwrap();
+        let ledger = Ledger::open(ledger_dir.path()).unwrap();
+        let bank = bank_with_fees_for_tests();
+        advance_to_slot(&bank, &ledger, bank.slot() + 2);
+        let slot = bank.slot();
+
+        advance_to_slot(&bank, &ledger, slot - 1);
+
+        assert_eq!(bank.slot(), slot);
+        assert_eq!(
+            ledger.get_slot_range().unwrap(),
+            Some((slot - 2, slot - 1))
+        );
+    }
+}
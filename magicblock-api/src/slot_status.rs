@@ -0,0 +1,19 @@
+use magicblock_bank::slot_status_notifier_interface::SlotStatusNotifier;
+use magicblock_metrics::metrics;
+use solana_sdk::{clock::Slot, hash::Hash};
+
+/// Counts the slots the bank advanced to
+#[derive(Debug)]
+pub(crate) struct SlotMetricsNotifier;
+
+impl SlotStatusNotifier for SlotMetricsNotifier {
+    fn notify_slot_status(
+        &self,
+        _slot: Slot,
+        _parent_slot: Option<Slot>,
+        _blockhash: &Hash,
+        _parent_blockhash: &Hash,
+    ) {
+        metrics::inc_slot();
+    }
+}
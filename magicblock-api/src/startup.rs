@@ -0,0 +1,86 @@
+//! Preconditions verified while the validator starts up.
+//! Each unmet precondition fails the startup with a specific [ApiError]
+//! instead of surfacing later as failing clones or commits, except for clock
+//! skew which is only reported unless configured otherwise.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use log::*;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    account::from_account,
+    clock::{Clock, UnixTimestamp},
+    commitment_config::CommitmentConfig,
+    sysvar,
+};
+
+use crate::errors::{ApiError, ApiResult};
+
+/// Verifies that the remote cluster we clone accounts from and commit them to
+/// is reachable and warns if our clock deviates from its clock by more than
+/// [max_clock_skew_secs]. The deviation only fails the startup if
+/// [fail_on_clock_skew] is set.
+pub(crate) async fn verify_remote_cluster(
+    rpc_url: &str,
+    max_clock_skew_secs: u64,
+    fail_on_clock_skew: bool,
+) -> ApiResult<()> {
+    let rpc_client = RpcClient::new_with_commitment(
+        rpc_url.to_string(),
+        CommitmentConfig::confirmed(),
+    );
+
+    let version = rpc_client.get_version().await.map_err(|err| {
+        ApiError::RemoteClusterUnreachable(rpc_url.to_string(), err.to_string())
+    })?;
+    info!(
+        "Remote cluster at {} is reachable, running version {}",
+        rpc_url, version.solana_core
+    );
+
+    let clock_account = rpc_client
+        .get_account(&sysvar::clock::id())
+        .await
+        .map_err(|err| {
+            ApiError::RemoteClusterClockUnavailable(
+                rpc_url.to_string(),
+                err.to_string(),
+            )
+        })?;
+    let clock = from_account::<Clock, _>(&clock_account).ok_or_else(|| {
+        ApiError::RemoteClusterClockUnavailable(
+            rpc_url.to_string(),
+            "invalid clock sysvar account".to_string(),
+        )
+    })?;
+
+    let clock_skew_secs = clock_skew_secs(clock.unix_timestamp);
+    if clock_skew_secs.unsigned_abs() > max_clock_skew_secs {
+        let err = ApiError::RemoteClusterClockSkewTooLarge(
+            rpc_url.to_string(),
+            clock_skew_secs,
+            max_clock_skew_secs,
+        );
+        if fail_on_clock_skew {
+            return Err(err);
+        }
+        warn!("{}", err);
+    } else {
+        debug!(
+            "Clock skew to remote cluster at {} is {}s",
+            rpc_url, clock_skew_secs
+        );
+    }
+
+    Ok(())
+}
+
+/// Returns how many seconds our clock is ahead of the provided remote
+/// timestamp, negative if it is behind.
+fn clock_skew_secs(remote_unix_timestamp: UnixTimestamp) -> i64 {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Time went backwards")
+        .as_secs() as i64;
+    now - remote_unix_timestamp
+}
@@ -0,0 +1,156 @@
+use std::{
+    any::Any,
+    collections::{HashMap, VecDeque},
+    panic::{self, AssertUnwindSafe},
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use log::*;
+use magicblock_core::robust_lock::RobustMutex;
+use magicblock_metrics::metrics;
+use magicblock_rpc::shutdown::RpcShutdown;
+use tokio_util::sync::CancellationToken;
+
+// -----------------
+// SupervisorConfig
+// -----------------
+#[derive(Debug, Clone)]
+pub struct SupervisorConfig {
+    /// Max number of restarts of a subsystem within the [Self::restart_window]
+    /// before the validator is shut down
+    pub max_restarts: usize,
+    pub restart_window: Duration,
+    /// Time to wait before restarting a subsystem
+    pub restart_delay: Duration,
+}
+
+impl Default for SupervisorConfig {
+    fn default() -> Self {
+        Self {
+            max_restarts: 5,
+            restart_window: Duration::from_secs(60),
+            restart_delay: Duration::from_secs(1),
+        }
+    }
+}
+
+// -----------------
+// Supervisor
+// -----------------
+/// Restarts long running subsystems, i.e. the account cloner or the pubsub
+/// actor, when they panic or stop unexpectedly instead of leaving the
+/// validator running without them.
+/// When a subsystem keeps failing it requests a graceful shutdown of the
+/// validator.
+#[derive(Clone)]
+pub struct Supervisor {
+    config: SupervisorConfig,
+    token: CancellationToken,
+    shutdown: RpcShutdown,
+    restarts: Arc<Mutex<HashMap<&'static str, VecDeque<Instant>>>>,
+}
+
+impl Supervisor {
+    pub fn new(
+        config: SupervisorConfig,
+        token: CancellationToken,
+        shutdown: RpcShutdown,
+    ) -> Self {
+        Self {
+            config,
+            token,
+            shutdown,
+            restarts: Default::default(),
+        }
+    }
+
+    /// Runs [run] on the current thread until the validator is stopped,
+    /// restarting it whenever it panics or returns before that.
+    pub fn supervise_blocking(
+        &self,
+        subsystem: &'static str,
+        run: impl FnMut(),
+    ) {
+        self.supervise_blocking_with_recovery(subsystem, run, || {})
+    }
+
+    /// Like [Self::supervise_blocking] but runs [recover] before each restart,
+    /// i.e. to release what the subsystem left behind when it panicked.
+    pub fn supervise_blocking_with_recovery(
+        &self,
+        subsystem: &'static str,
+        mut run: impl FnMut(),
+        mut recover: impl FnMut(),
+    ) {
+        loop {
+            let result = panic::catch_unwind(AssertUnwindSafe(&mut run));
+            if !self.should_restart(subsystem, result) {
+                break;
+            }
+            recover();
+        }
+    }
+
+    /// Decides if the [subsystem] should be restarted after it terminated
+    /// with the given [result] and blocks for the restart delay if so.
+    /// Terminating is expected once the validator is stopped, otherwise the
+    /// subsystem is restarted unless it failed too often already in which
+    /// case the validator is shut down.
+    pub fn should_restart(
+        &self,
+        subsystem: &'static str,
+        result: thread::Result<()>,
+    ) -> bool {
+        if self.token.is_cancelled() {
+            return false;
+        }
+        match result {
+            Ok(()) => error!("{} terminated unexpectedly", subsystem),
+            Err(panic) => {
+                error!("{} panicked: {}", subsystem, panic_message(&*panic))
+            }
+        }
+
+        let restarts = {
+            let mut restarts = self.restarts.lock_robust();
+            let restarts = restarts.entry(subsystem).or_default();
+            let now = Instant::now();
+            while restarts.front().is_some_and(|restart| {
+                now.duration_since(*restart) > self.config.restart_window
+            }) {
+                restarts.pop_front();
+            }
+            restarts.push_back(now);
+            restarts.len()
+        };
+        if restarts > self.config.max_restarts {
+            error!(
+                "{} failed {} times within {:?}, shutting down the validator",
+                subsystem, restarts, self.config.restart_window
+            );
+            self.shutdown.request();
+            return false;
+        }
+
+        metrics::inc_subsystem_restart(subsystem);
+        warn!(
+            "Restarting {} in {:?} ({}/{})",
+            subsystem,
+            self.config.restart_delay,
+            restarts,
+            self.config.max_restarts
+        );
+        thread::sleep(self.config.restart_delay);
+        !self.token.is_cancelled()
+    }
+}
+
+fn panic_message(panic: &(dyn Any + Send)) -> &str {
+    panic
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("<unknown>")
+}
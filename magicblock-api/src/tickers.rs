@@ -1,7 +1,8 @@
 use std::{
+    collections::HashSet,
     sync::{
         atomic::{AtomicBool, Ordering},
-        Arc,
+        Arc, RwLock,
     },
     time::{Duration, SystemTime, UNIX_EPOCH},
 };
@@ -9,27 +10,46 @@ use std::{
 use log::*;
 use magicblock_accounts::AccountsManager;
 use magicblock_accounts_db::FLUSH_ACCOUNTS_SLOT_FREQ;
-use magicblock_bank::bank::Bank;
-use magicblock_core::magic_program;
+use magicblock_bank::{bank::Bank, program_versions::ProgramVersion};
+use magicblock_core::{
+    chain_slot_mapping::ChainSlotMapping, magic_program,
+    robust_lock::RobustRwLock,
+};
 use magicblock_ledger::Ledger;
 use magicblock_metrics::metrics;
 use magicblock_processor::execute_transaction::{
-    execute_legacy_transaction, lock_transactions,
+    execute_legacy_transaction, execute_legacy_transaction_locked,
+    lock_transactions,
 };
 use magicblock_program::{
-    magicblock_instruction::accept_scheduled_commits, MagicContext,
+    magicblock_instruction::accept_scheduled_commits,
+    validator::ValidatorStage, MagicContext,
 };
 use magicblock_transaction_status::TransactionStatusSender;
-use solana_sdk::account::ReadableAccount;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    account::{from_account, ReadableAccount},
+    clock::Clock,
+    commitment_config::CommitmentConfig,
+    pubkey::Pubkey,
+    sysvar,
+};
+use tokio::sync::watch;
 use tokio_util::sync::CancellationToken;
 
-use crate::accounts::flush_accounts;
+use crate::{
+    accounts::flush_accounts,
+    replay_inputs::{replay_next_slot, LedgerInputs, ReplayInput},
+};
 
 pub fn init_slot_ticker(
     bank: &Arc<Bank>,
     accounts_manager: &Arc<AccountsManager>,
+    chain_slot_mapping: ChainSlotMapping,
     transaction_status_sender: Option<TransactionStatusSender>,
     ledger: Arc<Ledger>,
+    ledger_inputs: Option<LedgerInputs>,
+    invalidated_clones: Arc<RwLock<HashSet<Pubkey>>>,
     tick_duration: Duration,
     exit: Arc<AtomicBool>,
 ) -> tokio::task::JoinHandle<()> {
@@ -42,23 +62,59 @@ pub fn init_slot_ticker(
 
             let prev_slot = bank.slot();
 
-            let next_slot = if prev_slot % FLUSH_ACCOUNTS_SLOT_FREQ == 0 {
-                // NOTE: at this point we flush the accounts blocking the slot from advancing as
-                // well as holding the transaction lock.
-                // This is done on purpose in order to avoid transactions writing to the accounts
-                // while we are persisting them.
-                // This is a very slow operation, i.e. in the 30ms+ range and we should consider
-                // making a copy of all accounts, including data and then performing the IO flush
-                // in a separate task.
-                // Also in this case we prevent the transactions from advancing before the bank
-                // slot advanced since only then can we be sure that the accounts did not change
-                // during the same slot after we flushed them.
-                let _lock = lock_transactions();
-                flush_accounts(&bank);
-                bank.advance_slot()
+            let advance_slot = || match &ledger_inputs {
+                Some(LedgerInputs::Replay(inputs)) => {
+                    replay_next_slot(&bank, inputs.next_clock())
+                }
+                _ => bank.advance_slot(),
+            };
+            let flush = prev_slot % FLUSH_ACCOUNTS_SLOT_FREQ == 0;
+            // Upgrades queued after this check are applied at the next tick
+            let next_slot = if flush || bank.has_pending_program_upgrades() {
+                // Holding the transaction lock waits for transactions of this slot to finish
+                // and keeps new ones from running before the slot advanced.
+                let mut transaction_index_locked = lock_transactions();
+                let failed_upgrades = apply_pending_program_upgrades(
+                    &bank,
+                    transaction_status_sender.as_ref(),
+                    &mut transaction_index_locked,
+                );
+                // The cloner considers these programs upgraded, thus we
+                // make it clone them again the next time they are used
+                if !failed_upgrades.is_empty() {
+                    invalidated_clones.write_robust().extend(failed_upgrades);
+                }
+                if flush {
+                    // NOTE: at this point we flush the accounts blocking the slot from advancing as
+                    // well as holding the transaction lock.
+                    // This is done on purpose in order to avoid transactions writing to the accounts
+                    // while we are persisting them.
+                    // This is a very slow operation, i.e. in the 30ms+ range and we should consider
+                    // making a copy of all accounts, including data and then performing the IO flush
+                    // in a separate task.
+                    // Also in this case we prevent the transactions from advancing before the bank
+                    // slot advanced since only then can we be sure that the accounts did not change
+                    // during the same slot after we flushed them.
+                    flush_accounts(&bank);
+                }
+                advance_slot()
             } else {
-                bank.advance_slot()
+                advance_slot()
             };
+            if let Some(LedgerInputs::Record(recorder)) = &ledger_inputs {
+                // A synced clock is recorded as a whole since its epoch is
+                // derived from the remote clock instead of the slot
+                let clock = bank.clock();
+                recorder.record(&if bank.is_clock_synced() {
+                    ReplayInput::RemoteClock { clock }
+                } else {
+                    ReplayInput::ClockTimestamp {
+                        timestamp: clock.unix_timestamp,
+                    }
+                });
+            }
+            magicblock_logger::set_log_slot(next_slot);
+            chain_slot_mapping.record_ephemeral_slot(next_slot);
 
             // Update ledger with previous block's metas
             if let Err(err) = ledger.write_block(
@@ -68,47 +124,148 @@ pub fn init_slot_ticker(
             ) {
                 error!("Failed to write block: {:?}", err);
             }
+            persist_account_journal(&bank, &ledger);
 
             // If accounts were scheduled to be committed, we accept them here
             // and processs the commits
-            let magic_context_acc = bank.get_account(&magic_program::MAGIC_CONTEXT_PUBKEY)
-                .expect("Validator found to be running without MagicContext account!");
-
-            if MagicContext::has_scheduled_commits(magic_context_acc.data()) {
-                // 1. Send the transaction to move the scheduled commits from the MagicContext
-                //    to the global ScheduledCommit store
-                let tx = accept_scheduled_commits(bank.last_blockhash());
-                if let Err(err) = execute_legacy_transaction(
-                    tx,
-                    &bank,
-                    transaction_status_sender.as_ref(),
-                ) {
-                    error!("Failed to accept scheduled commits: {:?}", err);
-                } else {
-                    // 2. Process those scheduled commits
-                    // TODO: fix the possible delay here
-                    // https://github.com/magicblock-labs/magicblock-validator/issues/104
-                    if let Err(err) =
-                        accounts_manager.process_scheduled_commits().await
-                    {
-                        error!(
-                            "Failed to process scheduled commits: {:?}",
-                            err
-                        );
-                    }
-                }
-            }
+            // When replaying inputs the commits are accepted, but not sent
+            accept_and_process_scheduled_commits(
+                &bank,
+                &accounts_manager,
+                transaction_status_sender.as_ref(),
+                !ledger_inputs.as_ref().is_some_and(LedgerInputs::is_replay),
+            )
+            .await;
+            persist_escrow_settlements(&bank, &ledger);
             if log {
                 info!("Advanced to slot {}", next_slot);
             }
-            metrics::inc_slot();
         }
     })
 }
 
+/// Applies the program upgrades that were deferred to the end of the slot.
+/// Needs to run while holding the transaction lock right before advancing the
+/// slot, since an upgraded program only becomes usable in the next slot.
+/// Returns the ids of the programs that failed to upgrade.
+pub(crate) fn apply_pending_program_upgrades(
+    bank: &Arc<Bank>,
+    transaction_status_sender: Option<&TransactionStatusSender>,
+    transaction_index_locked: &mut usize,
+) -> Vec<Pubkey> {
+    let mut failed_upgrades = vec![];
+    for upgrade in bank.take_pending_program_upgrades() {
+        let program_id = upgrade.program_id;
+        match execute_legacy_transaction_locked(
+            upgrade.transaction,
+            bank,
+            transaction_status_sender,
+            transaction_index_locked,
+        ) {
+            Ok(_) => bank.record_program_version(
+                program_id,
+                ProgramVersion {
+                    effective_slot: bank.slot() + 1,
+                    bytecode_hash: upgrade.bytecode_hash,
+                },
+            ),
+            Err(err) => {
+                error!("Failed to upgrade program {}: {:?}", program_id, err);
+                failed_upgrades.push(program_id);
+            }
+        }
+    }
+    failed_upgrades
+}
+
+/// Writes the journal entries of writes to delegated accounts which the bank
+/// recorded since they were last persisted to the ledger.
+pub(crate) fn persist_account_journal(bank: &Bank, ledger: &Ledger) {
+    for entry in bank.take_account_journal_entries() {
+        if let Err(err) = ledger.write_account_journal_entry(&entry) {
+            error!(
+                "Failed to write journal entry of account {}: {:?}",
+                entry.pubkey, err
+            );
+        }
+    }
+}
+
+/// Writes the escrow settlements accepted from the MagicContext to the ledger
+/// from where they are reconciled on the base layer.
+pub(crate) fn persist_escrow_settlements(bank: &Bank, ledger: &Ledger) {
+    let settlements = bank
+        .validator_context()
+        .transaction_scheduler()
+        .take_escrow_settlements();
+    if settlements.is_empty() {
+        return;
+    }
+    if let Err(err) = ledger.write_escrow_settlements(&settlements) {
+        error!(
+            "Failed to write {} escrow settlements: {:?}",
+            settlements.len(),
+            err
+        );
+    }
+}
+
+/// Accepts the commits scheduled in the MagicContext and processes them
+/// together with commits that were accepted before but are only due now.
+/// If [process_commits] is `false` the accepted commits are dropped instead.
+pub(crate) async fn accept_and_process_scheduled_commits(
+    bank: &Arc<Bank>,
+    accounts_manager: &Arc<AccountsManager>,
+    transaction_status_sender: Option<&TransactionStatusSender>,
+    process_commits: bool,
+) {
+    let magic_context_acc = bank
+        .get_account(&magic_program::MAGIC_CONTEXT_PUBKEY)
+        .expect("Validator found to be running without MagicContext account!");
+
+    if MagicContext::has_pending_items(magic_context_acc.data()) {
+        // 1. Send the transaction to move the scheduled commits and escrow
+        //    settlements from the MagicContext to the global stores
+        //    If more commits were scheduled than are accepted in one pass the
+        //    remaining ones are accepted in the following slots
+        let tx = accept_scheduled_commits(
+            bank.validator_context(),
+            bank.last_blockhash(),
+        );
+        if let Err(err) =
+            execute_legacy_transaction(tx, bank, transaction_status_sender)
+        {
+            error!("Failed to accept scheduled commits: {:?}", err);
+        } else if !process_commits {
+            debug!(
+                "Dropping {} accepted commits",
+                accounts_manager.scheduled_commits_len()
+            );
+            accounts_manager.clear_scheduled_commits();
+        } else {
+            // 2. Process those scheduled commits
+            // TODO: fix the possible delay here
+            // https://github.com/magicblock-labs/magicblock-validator/issues/104
+            if let Err(err) = accounts_manager.process_scheduled_commits().await
+            {
+                error!("Failed to process scheduled commits: {:?}", err);
+            }
+        }
+    } else if process_commits && accounts_manager.scheduled_commits_len() > 0 {
+        // Commits that were accepted previously but delayed to a later slot
+        // need to be processed once they become due
+        if let Err(err) = accounts_manager.process_scheduled_commits().await {
+            error!("Failed to process delayed commits: {:?}", err);
+        }
+    }
+}
+
+/// Commits the delegated accounts that are due until the validator starts
+/// draining, the shutdown then commits the remaining ones itself.
 pub fn init_commit_accounts_ticker(
     manager: &Arc<AccountsManager>,
     tick_duration: Duration,
+    validator_stage: watch::Receiver<ValidatorStage>,
     token: CancellationToken,
 ) -> tokio::task::JoinHandle<()> {
     let manager = manager.clone();
@@ -116,6 +273,10 @@ pub fn init_commit_accounts_ticker(
         loop {
             tokio::select! {
                 _ = tokio::time::sleep(tick_duration) => {
+                    if *validator_stage.borrow() >= ValidatorStage::Draining {
+                        debug!("Validator is draining, stopping to commit accounts");
+                        break;
+                    }
                     let sigs = manager.commit_delegated().await;
                     match sigs {
                         Ok(sigs) if sigs.is_empty() => {
@@ -154,6 +315,13 @@ pub fn init_system_metrics_ticker(
             Ok(byte_size) => metrics::set_accounts_size(byte_size),
             Err(err) => warn!("Failed to get accounts storage size: {:?}", err),
         }
+        match bank.accounts_db_storage_file_count() {
+            Ok(count) => metrics::set_accounts_storage_files(count),
+            Err(err) => {
+                warn!("Failed to get accounts storage file count: {:?}", err)
+            }
+        }
+        metrics::set_accounts_count(bank.accounts_db_accounts_count());
     }
     let ledger = ledger.clone();
     let bank = bank.clone();
@@ -174,9 +342,130 @@ pub fn init_system_metrics_ticker(
     })
 }
 
+/// Polls the clock of the remote cluster and feeds it to the bank which
+/// derives its `Clock` sysvar from it.
+pub fn init_clock_sync_ticker(
+    bank: &Arc<Bank>,
+    rpc_url: String,
+    tick_duration: Duration,
+    token: CancellationToken,
+) -> tokio::task::JoinHandle<()> {
+    async fn try_sync_clock(rpc_client: &RpcClient, bank: &Bank) {
+        let clock = match rpc_client.get_account(&sysvar::clock::id()).await {
+            Ok(account) => from_account::<Clock, _>(&account),
+            Err(err) => {
+                warn!("Failed to fetch remote clock: {:?}", err);
+                return;
+            }
+        };
+        match clock {
+            Some(clock) => bank.set_remote_clock(clock),
+            None => warn!("Remote clock sysvar account is invalid"),
+        }
+    }
+    let bank = bank.clone();
+    let rpc_client =
+        RpcClient::new_with_commitment(rpc_url, CommitmentConfig::confirmed());
+    tokio::task::spawn(async move {
+        loop {
+            try_sync_clock(&rpc_client, &bank).await;
+            tokio::select! {
+                _ = tokio::time::sleep(tick_duration) => {},
+                _ = token.cancelled() => {
+                    break;
+                }
+            }
+        }
+    })
+}
+
 fn timestamp_in_secs() -> u64 {
     let now = SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .expect("create timestamp in timing");
     now.as_secs()
 }
+
+#[cfg(test)]
+mod tests {
+    use magicblock_account_dumper::{AccountDumper, AccountDumperBank};
+    use magicblock_bank::program_versions::PendingProgramUpgrade;
+    use solana_sdk::{
+        account::Account, bpf_loader_upgradeable, hash::hash,
+        native_token::LAMPORTS_PER_SOL, transaction::Transaction,
+    };
+    use test_tools::validator::bank_with_started_validator;
+
+    use super::*;
+
+    fn program_account(bytecode: &[u8]) -> Account {
+        Account {
+            lamports: LAMPORTS_PER_SOL,
+            owner: bpf_loader_upgradeable::id(),
+            data: bytecode.to_vec(),
+            executable: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_applied_program_upgrade_is_effective_at_next_slot() {
+        let bank = bank_with_started_validator();
+        let dumper = AccountDumperBank::new(bank.clone(), None);
+        let program_id = Pubkey::new_unique();
+        dumper
+            .dump_program_account_with_old_bpf(
+                &program_id,
+                &program_account(b"v1"),
+            )
+            .unwrap();
+        dumper
+            .dump_program_account_with_old_bpf(
+                &program_id,
+                &program_account(b"v2"),
+            )
+            .unwrap();
+
+        let slot = bank.slot();
+        let failed_upgrades = apply_pending_program_upgrades(
+            &bank,
+            None,
+            &mut lock_transactions(),
+        );
+        assert!(failed_upgrades.is_empty());
+        assert_eq!(
+            bank.get_program_version(&program_id, slot)
+                .map(|version| version.bytecode_hash),
+            Some(hash(b"v1"))
+        );
+        assert_eq!(
+            bank.get_program_version(&program_id, slot + 1),
+            Some(ProgramVersion {
+                effective_slot: slot + 1,
+                bytecode_hash: hash(b"v2"),
+            })
+        );
+        assert!(bank.take_pending_program_upgrades().is_empty());
+    }
+
+    #[test]
+    fn test_failed_program_upgrade_is_reported() {
+        let bank = bank_with_started_validator();
+        let program_id = Pubkey::new_unique();
+        bank.queue_program_upgrade(PendingProgramUpgrade {
+            program_id,
+            bytecode_hash: hash(b"v2"),
+            // Fails since it is not signed
+            transaction: Transaction::default(),
+        });
+
+        let slot = bank.slot();
+        let failed_upgrades = apply_pending_program_upgrades(
+            &bank,
+            None,
+            &mut lock_transactions(),
+        );
+        assert_eq!(failed_upgrades, vec![program_id]);
+        assert_eq!(bank.get_program_version(&program_id, slot + 1), None);
+    }
+}
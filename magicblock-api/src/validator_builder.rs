@@ -0,0 +1,252 @@
+use std::net::IpAddr;
+
+use log::*;
+use magicblock_bank::bank::Bank;
+use magicblock_config::{
+    AllowedProgram, EphemeralConfig, LifecycleMode, ProgramConfig, RemoteConfig,
+};
+use magicblock_ledger::Ledger;
+use solana_geyser_plugin_interface::geyser_plugin_interface::GeyserPlugin;
+use solana_sdk::{account::Account, pubkey::Pubkey, signature::Keypair};
+
+use crate::{
+    errors::ApiResult,
+    magic_validator::{MagicValidator, MagicValidatorConfig},
+    InitGeyserServiceConfig,
+};
+
+// -----------------
+// ValidatorBuilder
+// -----------------
+/// Configures and creates a [MagicValidator] programmatically in order to
+/// embed it into other services or tests without going through a TOML config.
+/// NOTE: the validator keeps part of its state in process wide statics, thus
+/// only one validator can be created per process.
+///
+/// ```ignore
+/// let validator = ValidatorBuilder::new()
+///     .lifecycle(LifecycleMode::Ephemeral)
+///     .remote(RemoteConfig::Development)
+///     .rpc_port(8899)
+///     .start()
+///     .await?;
+/// let bank = validator.bank_rc();
+/// ```
+#[derive(Default)]
+pub struct ValidatorBuilder {
+    config: EphemeralConfig,
+    geyser_config: InitGeyserServiceConfig,
+    keypair: Option<Keypair>,
+    genesis_accounts: Vec<(Pubkey, Account)>,
+}
+
+impl ValidatorBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts out from the provided config which the other methods override.
+    pub fn with_config(config: EphemeralConfig) -> Self {
+        Self {
+            config,
+            ..Default::default()
+        }
+    }
+
+    /// The validator identity, a new keypair is generated if none is provided.
+    pub fn keypair(mut self, keypair: Keypair) -> Self {
+        self.keypair = Some(keypair);
+        self
+    }
+
+    pub fn remote(mut self, remote: RemoteConfig) -> Self {
+        self.config.accounts.remote = remote;
+        self
+    }
+
+    /// Determines which accounts the validator clones from the remote cluster.
+    pub fn lifecycle(mut self, lifecycle: LifecycleMode) -> Self {
+        self.config.accounts.lifecycle = lifecycle;
+        self
+    }
+
+    /// Limits cloning to accounts owned by the provided programs.
+    pub fn allowed_programs(
+        mut self,
+        program_ids: impl IntoIterator<Item = Pubkey>,
+    ) -> Self {
+        self.config.accounts.allowed_programs = program_ids
+            .into_iter()
+            .map(|id| AllowedProgram { id })
+            .collect();
+        self
+    }
+
+    /// Loads the program found at the provided path into the bank.
+    pub fn program(mut self, id: Pubkey, path: impl Into<String>) -> Self {
+        self.config.programs.push(ProgramConfig {
+            id,
+            path: path.into(),
+        });
+        self
+    }
+
+    /// Stores the provided account in the bank before the validator starts.
+    /// NOTE: it is only stored on a fresh ledger, see [store_genesis_accounts].
+    pub fn genesis_account(mut self, pubkey: Pubkey, account: Account) -> Self {
+        self.genesis_accounts.push((pubkey, account));
+        self
+    }
+
+    pub fn rpc_addr(mut self, addr: IpAddr) -> Self {
+        self.config.rpc.addr = addr;
+        self
+    }
+
+    /// The port of the JSON RPC service, the pubsub service listens on the
+    /// next port.
+    pub fn rpc_port(mut self, port: u16) -> Self {
+        self.config.rpc.port = port;
+        self
+    }
+
+    pub fn geyser_grpc_port(mut self, port: u16) -> Self {
+        self.config.geyser_grpc.port = port;
+        self
+    }
+
+    pub fn millis_per_slot(mut self, millis_per_slot: u64) -> Self {
+        self.config.validator.millis_per_slot = millis_per_slot;
+        self
+    }
+
+    /// Uses the ledger at the provided path, a temporary one is used if
+    /// none is provided.
+    pub fn ledger_path(mut self, path: impl Into<String>) -> Self {
+        self.config.ledger.path = Some(path.into());
+        self
+    }
+
+    pub fn reset_ledger(mut self, reset: bool) -> Self {
+        self.config.ledger.reset = reset;
+        self
+    }
+
+    /// Registers a geyser plugin that is notified in process about account
+    /// updates and transactions.
+    pub fn geyser_plugin(
+        mut self,
+        name: impl Into<String>,
+        plugin: Box<dyn GeyserPlugin>,
+    ) -> Self {
+        self.geyser_config.add_plugin(name.into(), plugin);
+        self
+    }
+
+    /// Creates the validator without starting it.
+    pub fn build(self) -> ApiResult<MagicValidator> {
+        let Self {
+            config,
+            mut geyser_config,
+            keypair,
+            genesis_accounts,
+        } = self;
+
+        geyser_config.geyser_grpc = config.geyser_grpc.clone();
+        let validator = MagicValidator::try_from_config(
+            MagicValidatorConfig {
+                validator_config: config,
+                init_geyser_service_config: geyser_config,
+            },
+            keypair.unwrap_or_else(Keypair::new),
+        )?;
+
+        store_genesis_accounts(
+            validator.ledger(),
+            validator.bank(),
+            genesis_accounts,
+        )?;
+
+        Ok(validator)
+    }
+
+    /// Creates and starts the validator.
+    pub async fn start(self) -> ApiResult<MagicValidator> {
+        let mut validator = self.build()?;
+        validator.start().await?;
+        Ok(validator)
+    }
+}
+
+/// Stores the [genesis_accounts] in the [bank] only if the [ledger] is fresh.
+/// An existing ledger was created with them and replaying it restores their
+/// state including the changes of later transactions, which storing them
+/// again would overwrite.
+fn store_genesis_accounts(
+    ledger: &Ledger,
+    bank: &Bank,
+    genesis_accounts: Vec<(Pubkey, Account)>,
+) -> ApiResult<()> {
+    if genesis_accounts.is_empty() {
+        return Ok(());
+    }
+    if ledger.get_slot_range()?.is_some() {
+        warn!(
+            "Not storing {} genesis accounts since the ledger isn't fresh",
+            genesis_accounts.len()
+        );
+        return Ok(());
+    }
+    for (pubkey, account) in genesis_accounts {
+        bank.store_account(&pubkey, &account);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::{account::ReadableAccount, hash::Hash};
+    use test_tools::bank::bank_with_fees_for_tests;
+
+    use super::*;
+
+    fn genesis_account() -> (Pubkey, Account) {
+        let account = Account {
+            lamports: 1_000,
+            data: vec![1, 2, 3],
+            owner: Pubkey::new_unique(),
+            ..Default::default()
+        };
+        (Pubkey::new_unique(), account)
+    }
+
+    #[test]
+    fn test_genesis_accounts_are_stored_on_a_fresh_ledger() {
+        let ledger_dir = tempfile::tempdir().unwrap();
+        let ledger = Ledger::open(ledger_dir.path()).unwrap();
+        let bank = bank_with_fees_for_tests();
+        let (pubkey, account) = genesis_account();
+
+        store_genesis_accounts(&ledger, &bank, vec![(pubkey, account.clone())])
+            .unwrap();
+
+        let stored = bank.get_account(&pubkey).unwrap();
+        assert_eq!(stored.lamports(), account.lamports);
+        assert_eq!(stored.data(), account.data);
+        assert_eq!(stored.owner(), &account.owner);
+    }
+
+    #[test]
+    fn test_genesis_accounts_are_not_stored_on_an_existing_ledger() {
+        let ledger_dir = tempfile::tempdir().unwrap();
+        let ledger = Ledger::open(ledger_dir.path()).unwrap();
+        ledger.write_block(0, 0, Hash::new_unique()).unwrap();
+        let bank = bank_with_fees_for_tests();
+        let (pubkey, account) = genesis_account();
+
+        store_genesis_accounts(&ledger, &bank, vec![(pubkey, account)])
+            .unwrap();
+
+        assert!(bank.get_account(&pubkey).is_none());
+    }
+}
@@ -0,0 +1,108 @@
+use serde::{Deserialize, Serialize};
+use solana_sdk::{
+    account::{AccountSharedData, ReadableAccount},
+    clock::Slot,
+    hash::{hashv, Hash},
+    pubkey::Pubkey,
+    signature::Signature,
+};
+
+/// A compact record of a transaction writing to an account whose
+/// modifications are journaled, see [crate::bank::Bank::set_account_journaled].
+/// It allows auditing how the account changed without storing its data.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountJournalEntry {
+    pub pubkey: Pubkey,
+    pub slot: Slot,
+    pub signature: Signature,
+    /// The owner of the account after the write, which is the only program
+    /// that can modify its data
+    pub writer_program: Pubkey,
+    /// The lamports of the account after the write
+    pub lamports: u64,
+    /// Start of the byte range of the account data that changed
+    pub data_start: u32,
+    /// End (exclusive) of the byte range of the account data that changed
+    pub data_end: u32,
+    /// Hash of the data length followed by the changed byte range of the
+    /// data after the write
+    pub data_digest: Hash,
+}
+
+impl AccountJournalEntry {
+    /// Creates the entry for a write of [post] over [pre] or `None` if the
+    /// account did not change.
+    pub(crate) fn from_write(
+        pubkey: Pubkey,
+        slot: Slot,
+        signature: Signature,
+        pre: Option<&AccountSharedData>,
+        post: &AccountSharedData,
+    ) -> Option<Self> {
+        let pre_data = pre.map(|pre| pre.data()).unwrap_or_default();
+        let (data_start, data_end) = changed_range(pre_data, post.data());
+        let unchanged = pre.map_or(false, |pre| {
+            data_start == data_end
+                && pre.data().len() == post.data().len()
+                && pre.lamports() == post.lamports()
+                && pre.owner() == post.owner()
+        });
+        if unchanged {
+            return None;
+        }
+        let data_len = (post.data().len() as u64).to_le_bytes();
+        let data_digest =
+            hashv(&[&data_len, &post.data()[data_start..data_end]]);
+        Some(Self {
+            pubkey,
+            slot,
+            signature,
+            writer_program: *post.owner(),
+            lamports: post.lamports(),
+            data_start: data_start as u32,
+            data_end: data_end as u32,
+            data_digest,
+        })
+    }
+}
+
+/// Finds the smallest byte range outside of which [pre] and [post] are
+/// equal, bytes removed by shrinking the data are not part of the range.
+fn changed_range(pre: &[u8], post: &[u8]) -> (usize, usize) {
+    let start = pre
+        .iter()
+        .zip(post)
+        .position(|(a, b)| a != b)
+        .unwrap_or_else(|| pre.len().min(post.len()));
+    if start == post.len() {
+        return (start, start);
+    }
+    let end = if pre.len() == post.len() {
+        post.len()
+            - pre
+                .iter()
+                .rev()
+                .zip(post.iter().rev())
+                .position(|(a, b)| a != b)
+                .unwrap_or(0)
+    } else {
+        post.len()
+    };
+    (start, end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_changed_range() {
+        assert_eq!(changed_range(&[1, 2, 3], &[1, 2, 3]), (3, 3));
+        assert_eq!(changed_range(&[1, 2, 3], &[1, 5, 3]), (1, 2));
+        assert_eq!(changed_range(&[1, 2, 3, 4], &[9, 2, 3, 9]), (0, 4));
+        assert_eq!(changed_range(&[1, 2], &[1, 2, 3]), (2, 3));
+        assert_eq!(changed_range(&[1, 2, 3], &[1, 2]), (2, 2));
+        assert_eq!(changed_range(&[1, 2, 3], &[4]), (0, 1));
+        assert_eq!(changed_range(&[], &[1, 2]), (0, 2));
+    }
+}
@@ -1,4 +1,5 @@
 // NOTE: copied from  runtime/src/bank/address_lookup_table.rs
+use magicblock_core::robust_lock::RobustRwLock;
 use solana_sdk::{
     address_lookup_table::error::AddressLookupError,
     message::{
@@ -17,11 +18,9 @@ impl AddressLoader for &Bank {
     ) -> Result<LoadedAddresses, AddressLoaderError> {
         let slot_hashes = self
             .transaction_processor
-            .read()
-            .unwrap()
+            .read_robust()
             .sysvar_cache
-            .read()
-            .unwrap()
+            .read_robust()
             .get_slot_hashes()
             .map_err(|_| AddressLoaderError::SlotHashesSysvarNotFound)?;
 
@@ -1,16 +1,16 @@
 use std::{
     borrow::Cow,
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     path::PathBuf,
     slice,
     sync::{
         atomic::{AtomicBool, AtomicI64, AtomicU64, Ordering},
         Arc, LockResult, RwLock, RwLockReadGuard, RwLockWriteGuard,
     },
-    time::Duration,
+    time::{Duration, Instant},
 };
 
-use log::{debug, info, trace};
+use log::{debug, info, trace, warn};
 use magicblock_accounts_db::{
     accounts::{Accounts, TransactionLoadResult},
     accounts_db::AccountsDb,
@@ -24,6 +24,11 @@ use magicblock_accounts_db::{
         TransactionResults,
     },
 };
+use magicblock_core::{
+    magic_program::MAGIC_CONTEXT_PUBKEY, robust_lock::RobustRwLock,
+};
+use magicblock_metrics::metrics;
+use magicblock_program::{MagicContext, SessionKey, ValidatorContext};
 use solana_bpf_loader_program::syscalls::create_program_runtime_environment_v1;
 use solana_cost_model::cost_tracker::CostTracker;
 use solana_loader_v4_program::create_program_runtime_environment_v2;
@@ -42,10 +47,7 @@ use solana_sdk::{
         create_executable_meta, from_account, Account, AccountSharedData,
         ReadableAccount, WritableAccount,
     },
-    clock::{
-        Epoch, Slot, SlotIndex, UnixTimestamp, DEFAULT_MS_PER_SLOT,
-        MAX_RECENT_BLOCKHASHES,
-    },
+    clock::{Epoch, Slot, SlotIndex, UnixTimestamp},
     epoch_info::EpochInfo,
     epoch_schedule::EpochSchedule,
     feature,
@@ -72,6 +74,7 @@ use solana_sdk::{
     slot_hashes::SlotHashes,
     slot_history::{Check, SlotHistory},
     sysvar::{self, last_restart_slot::LastRestartSlot},
+    timing,
     transaction::{
         Result, SanitizedTransaction, TransactionError,
         TransactionVerificationMode, VersionedTransaction,
@@ -91,13 +94,22 @@ use solana_svm::{
 use solana_system_program::{get_system_account_kind, SystemAccountKind};
 
 use crate::{
+    account_journal::AccountJournalEntry,
     bank_helpers::{
         calculate_data_size_delta, get_epoch_secs,
         inherit_specially_retained_account_fields,
     },
     bank_rc::BankRc,
+    blockhash_expiry::BlockhashExpiry,
     builtins::{BuiltinPrototype, BUILTINS},
-    slot_status_notifier_interface::SlotStatusNotifierArc,
+    fee_payer_spend::{FeePayerSpendLimit, FeePayerSpendTracker},
+    gasless::{GaslessConfig, GaslessSponsor},
+    program_versions::{
+        PendingProgramUpgrade, ProgramVersion, ProgramVersionsTracker,
+    },
+    remote_clock::RemoteClock,
+    session_keys::SessionKeyIndex,
+    slot_status_notifier_interface::SlotStatusNotifiers,
     status_cache::StatusCache,
     transaction_batch::TransactionBatch,
     transaction_logs::{
@@ -155,6 +167,10 @@ pub struct Bank {
     /// Validator Identity
     identity_id: Pubkey,
 
+    /// The state of the validator that the magic program operates on, it is
+    /// entered while transactions are executed
+    validator_context: Arc<ValidatorContext>,
+
     /// initialized from genesis
     pub(crate) epoch_schedule: EpochSchedule,
 
@@ -256,6 +272,23 @@ pub struct Bank {
     // The number of block/slot for which generated transactions can stay valid
     pub max_age: u64,
 
+    /// Determines when blockhashes expire
+    blockhash_expiry: BlockhashExpiry,
+
+    /// Milliseconds for which blockhashes stay valid with
+    /// [BlockhashExpiry::WallClock]
+    transaction_expiration_millis: u64,
+
+    /// The wall-clock time in milliseconds at which each blockhash in the
+    /// [Self::blockhash_queue] was registered, only tracked with
+    /// [BlockhashExpiry::WallClock]
+    blockhash_timestamps: RwLock<HashMap<Hash, u64>>,
+
+    /// The block time in milliseconds of the slot that is being replayed
+    /// from the ledger which replaces the wall-clock when checking if
+    /// blockhashes expired, only tracked with [BlockhashExpiry::WallClock]
+    replayed_block_time: RwLock<Option<u64>>,
+
     // -----------------
     // For TransactionProcessingCallback
     // -----------------
@@ -280,9 +313,62 @@ pub struct Bank {
     cost_tracker: RwLock<CostTracker>,
 
     // -----------------
-    // Geyser
+    // Clock
+    // -----------------
+    /// Latest clock of the remote chain, only set when the clock is
+    /// synchronized with it via [Self::set_remote_clock]
+    remote_clock: RwLock<Option<RemoteClock>>,
+
+    // -----------------
+    // Dirty Accounts
+    // -----------------
+    /// Accounts written by transactions since they were last taken via
+    /// [Self::take_dirty_accounts]
+    dirty_accounts: RwLock<HashSet<Pubkey>>,
+    /// Only tracked once enabled via [Self::enable_dirty_accounts_tracking]
+    /// since otherwise nothing takes them and the set grows unbounded
+    tracks_dirty_accounts: AtomicBool,
+
+    // -----------------
+    // Account Journal
     // -----------------
-    slot_status_notifier: Option<SlotStatusNotifierArc>,
+    /// Accounts for which each write by a transaction is journaled
+    journaled_accounts: RwLock<HashSet<Pubkey>>,
+    /// Journal entries recorded since they were last taken via
+    /// [Self::take_account_journal_entries]
+    account_journal_entries: RwLock<Vec<AccountJournalEntry>>,
+
+    // -----------------
+    // Fee Payer Spend
+    // -----------------
+    /// Lamports spent by each fee payer, limited via
+    /// [Self::set_fee_payer_spend_limit]
+    fee_payer_spend: RwLock<FeePayerSpendTracker>,
+
+    // -----------------
+    // Gasless
+    // -----------------
+    /// Sponsors fees on behalf of fee payers if enabled via
+    /// [Self::set_gasless_config]
+    gasless: RwLock<Option<GaslessSponsor>>,
+
+    // -----------------
+    // Program Versions
+    // -----------------
+    /// Versions of the deployed programs and upgrades queued via
+    /// [Self::queue_program_upgrade]
+    program_versions: RwLock<ProgramVersionsTracker>,
+
+    // -----------------
+    // Session Keys
+    // -----------------
+    /// Session keys registered in the MagicContext, see [Self::session_key]
+    session_keys: RwLock<SessionKeyIndex>,
+
+    // -----------------
+    // Slot Status
+    // -----------------
+    slot_status_notifiers: SlotStatusNotifiers,
 }
 
 // -----------------
@@ -378,9 +464,12 @@ impl Bank {
         debug_do_not_add_builtins: bool,
         accounts_paths: Vec<PathBuf>,
         accounts_update_notifier: Option<AccountsUpdateNotifier>,
-        slot_status_notifier: Option<SlotStatusNotifierArc>,
+        slot_status_notifiers: SlotStatusNotifiers,
         millis_per_slot: u64,
+        transaction_expiration_millis: u64,
+        blockhash_expiry: BlockhashExpiry,
         identity_id: Pubkey,
+        validator_context: Arc<ValidatorContext>,
     ) -> Self {
         let accounts_db = AccountsDb::new_with_config(
             &genesis_config.cluster_type,
@@ -389,10 +478,16 @@ impl Bank {
         );
 
         let accounts = Accounts::new(Arc::new(accounts_db));
-        let mut bank = Self::default_with_accounts(accounts, millis_per_slot);
+        let mut bank = Self::default_with_accounts(
+            accounts,
+            millis_per_slot,
+            transaction_expiration_millis,
+            blockhash_expiry,
+        );
         bank.transaction_debug_keys = debug_keys;
         bank.runtime_config = runtime_config;
-        bank.slot_status_notifier = slot_status_notifier;
+        bank.slot_status_notifiers = slot_status_notifiers;
+        bank.validator_context = validator_context;
 
         bank.process_genesis_config(genesis_config, identity_id);
 
@@ -429,6 +524,8 @@ impl Bank {
     pub(super) fn default_with_accounts(
         accounts: Accounts,
         millis_per_slot: u64,
+        transaction_expiration_millis: u64,
+        blockhash_expiry: BlockhashExpiry,
     ) -> Self {
         // NOTE: this was not part of the original implementation
         let loaded_programs_cache = {
@@ -446,8 +543,9 @@ impl Bank {
         // Transaction expiration needs to be a fixed amount of time
         // So we compute how many slot it takes for a transaction to expire
         // Depending on how fast each slot is compute
-        let max_age = DEFAULT_MS_PER_SLOT * MAX_RECENT_BLOCKHASHES as u64
-            / millis_per_slot;
+        // This is also the window in which duplicate transactions are detected
+        let max_age = blockhash_expiry
+            .max_age(millis_per_slot, transaction_expiration_millis);
 
         let mut bank = Self {
             rc: BankRc::new(accounts),
@@ -469,7 +567,12 @@ impl Bank {
             status_cache: Arc::new(RwLock::new(BankStatusCache::new(max_age))),
             millis_per_slot,
             max_age,
+            blockhash_expiry,
+            transaction_expiration_millis,
+            blockhash_timestamps: RwLock::<HashMap<Hash, u64>>::default(),
+            replayed_block_time: RwLock::<Option<u64>>::default(),
             identity_id: Pubkey::default(),
+            validator_context: Arc::<ValidatorContext>::default(),
 
             // Counters
             transaction_count: AtomicU64::default(),
@@ -503,8 +606,32 @@ impl Bank {
             // Synchronization
             hash: RwLock::<Hash>::default(),
 
-            // Geyser
-            slot_status_notifier: Option::<SlotStatusNotifierArc>::default(),
+            // Clock
+            remote_clock: RwLock::<Option<RemoteClock>>::default(),
+
+            // Dirty Accounts
+            dirty_accounts: RwLock::<HashSet<Pubkey>>::default(),
+            tracks_dirty_accounts: AtomicBool::default(),
+
+            // Account Journal
+            journaled_accounts: RwLock::<HashSet<Pubkey>>::default(),
+            account_journal_entries:
+                RwLock::<Vec<AccountJournalEntry>>::default(),
+
+            // Fee Payer Spend
+            fee_payer_spend: RwLock::<FeePayerSpendTracker>::default(),
+
+            // Gasless
+            gasless: RwLock::<Option<GaslessSponsor>>::default(),
+
+            // Program Versions
+            program_versions: RwLock::<ProgramVersionsTracker>::default(),
+
+            // Session Keys
+            session_keys: RwLock::<SessionKeyIndex>::default(),
+
+            // Slot Status
+            slot_status_notifiers: SlotStatusNotifiers::default(),
         };
 
         bank.transaction_processor =
@@ -558,7 +685,7 @@ impl Bank {
 
         {
             let mut loaded_programs_cache =
-                self.loaded_programs_cache.write().unwrap();
+                self.loaded_programs_cache.write_robust();
             loaded_programs_cache.environments.program_runtime_v1 = Arc::new(
                 create_program_runtime_environment_v1(
                     &self.feature_set,
@@ -580,7 +707,7 @@ impl Bank {
 
     fn sync_loaded_programs_cache_to_slot(&self) {
         let mut loaded_programs_cache =
-            self.loaded_programs_cache.write().unwrap();
+            self.loaded_programs_cache.write_robust();
         loaded_programs_cache.latest_root_slot = self.slot();
         loaded_programs_cache.latest_root_epoch = self.epoch();
     }
@@ -595,6 +722,13 @@ impl Bank {
     ) {
         // Bootstrap validator collects fees until `new_from_parent` is called.
         self.fee_rate_governor = genesis_config.fee_rate_governor.clone();
+        // The fee structure determines the fee per signature while a zero
+        // fee rate makes transactions free altogether
+        if self.fee_rate_governor.lamports_per_signature > 0 {
+            self.fee_structure.lamports_per_signature =
+                self.fee_rate_governor.lamports_per_signature;
+            self.reset_transaction_processor(self.slot());
+        }
 
         // NOTE: these accounts can include feature activation accounts which need to be
         // present in order to properly activate a feature
@@ -611,10 +745,15 @@ impl Bank {
         }
 
         debug!("set blockhash {:?}", genesis_config.hash());
-        self.blockhash_queue.write().unwrap().genesis_hash(
+        self.blockhash_queue.write_robust().genesis_hash(
             &genesis_config.hash(),
             self.fee_rate_governor.lamports_per_signature,
         );
+        if self.blockhash_expiry == BlockhashExpiry::WallClock {
+            self.blockhash_timestamps
+                .write_robust()
+                .insert(genesis_config.hash(), timing::timestamp());
+        }
 
         self.hashes_per_tick = genesis_config.hashes_per_tick();
         self.ticks_per_slot = genesis_config.ticks_per_slot();
@@ -637,6 +776,10 @@ impl Bank {
         self.identity_id
     }
 
+    pub fn validator_context(&self) -> &Arc<ValidatorContext> {
+        &self.validator_context
+    }
+
     // -----------------
     // Slot, Epoch
     // -----------------
@@ -644,23 +787,34 @@ impl Bank {
         self.slot.load(Ordering::Relaxed)
     }
 
+    /// The consumers notified whenever the bank advances a slot, more
+    /// consumers can be registered with them after the bank was created.
+    pub fn slot_status_notifiers(&self) -> &SlotStatusNotifiers {
+        &self.slot_status_notifiers
+    }
+
     fn set_slot(&self, slot: Slot) {
         self.slot.store(slot, Ordering::Relaxed);
     }
 
     pub fn advance_slot(&self) -> Slot {
+        self.advance_slot_at(None)
+    }
+
+    /// Advances the slot like [Self::advance_slot], using the provided
+    /// [timestamp] for the clock sysvar instead of the current time if set,
+    /// i.e. when replaying recorded inputs.
+    pub fn advance_slot_at(&self, timestamp: Option<UnixTimestamp>) -> Slot {
+        let advanced_at = Instant::now();
         // Determine next slot and set it
         let prev_slot = self.slot();
         let next_slot = prev_slot + 1;
         self.set_next_slot(next_slot);
 
         // Add a "root" to the status cache to trigger removing old items
-        self.status_cache
-            .write()
-            .expect("RwLock of status cache poisoned")
-            .add_root(prev_slot);
+        self.status_cache.write_robust().add_root(prev_slot);
 
-        self.update_sysvars(self.genesis_creation_time, None);
+        self.update_sysvars(self.genesis_creation_time, timestamp);
 
         // Determine next blockhash
         let current_hash = self.last_blockhash();
@@ -674,20 +828,18 @@ impl Bank {
             hasher.result()
         };
 
-        // Register the new blockhash with the blockhash queue
-        {
-            let mut blockhash_queue = self.blockhash_queue.write().unwrap();
-            blockhash_queue.register_hash(
-                &blockhash,
-                self.fee_rate_governor.lamports_per_signature,
-            );
-        }
-
-        // Notify Geyser Service
-        if let Some(slot_status_notifier) = &self.slot_status_notifier {
-            slot_status_notifier
-                .notify_slot_status(next_slot, Some(next_slot - 1));
-        }
+        // Register the new blockhash with the blockhash queue, from now on
+        // blockhashes expire according to the wall-clock again
+        self.replayed_block_time.write_robust().take();
+        self.register_hash_with_timestamp(&blockhash, timing::timestamp());
+
+        self.slot_status_notifiers.notify_slot_status(
+            next_slot,
+            Some(next_slot - 1),
+            &blockhash,
+            &current_hash,
+            advanced_at,
+        );
 
         // Update loaded programs cache as otherwise we cannot deploy new programs
         self.sync_loaded_programs_cache_to_slot();
@@ -747,7 +899,7 @@ impl Bank {
     // Blockhash and Lamports
     // -----------------
     pub fn last_blockhash_and_lamports_per_signature(&self) -> (Hash, u64) {
-        let blockhash_queue = self.blockhash_queue.read().unwrap();
+        let blockhash_queue = self.blockhash_queue.read_robust();
         let last_hash = blockhash_queue.last_hash();
         let last_lamports_per_signature = blockhash_queue
             .get_lamports_per_signature(&last_hash)
@@ -757,14 +909,21 @@ impl Bank {
 
     /// Return the last block hash registered.
     pub fn last_blockhash(&self) -> Hash {
-        self.blockhash_queue.read().unwrap().last_hash()
+        self.blockhash_queue.read_robust().last_hash()
     }
 
     pub fn get_blockhash_last_valid_block_height(
         &self,
         blockhash: &Hash,
     ) -> Option<Slot> {
-        let blockhash_queue = self.blockhash_queue.read().unwrap();
+        if self.blockhash_expiry == BlockhashExpiry::WallClock {
+            // The slots remaining until the blockhash expires, assuming that
+            // slots advance as configured
+            return self.blockhash_remaining_millis(blockhash).map(|millis| {
+                self.block_height() + millis / self.millis_per_slot
+            });
+        }
+        let blockhash_queue = self.blockhash_queue.read_robust();
         // This calculation will need to be updated to consider epoch boundaries if BlockhashQueue
         // length is made variable by epoch
         blockhash_queue.get_hash_age(blockhash).map(|age| {
@@ -774,6 +933,41 @@ impl Bank {
         })
     }
 
+    /// Returns `true` if the [hash] is known and did not expire according to
+    /// the [Self::blockhash_expiry] yet.
+    fn is_hash_unexpired(
+        &self,
+        hash: &Hash,
+        blockhash_queue: &BlockhashQueue,
+    ) -> bool {
+        match self.blockhash_expiry {
+            BlockhashExpiry::WallClock => {
+                self.blockhash_remaining_millis(hash).is_some()
+            }
+            BlockhashExpiry::Slots | BlockhashExpiry::MaxAgeSlots(_) => {
+                blockhash_queue.is_hash_valid_for_age(hash, self.max_age)
+            }
+        }
+    }
+
+    /// Returns the milliseconds until the [hash] expires on the wall-clock or
+    /// `None` if it expired already.
+    /// While replaying the ledger the block time of the replayed slot is used
+    /// instead of the wall-clock, otherwise transactions would expire once
+    /// the ledger is older than the [Self::transaction_expiration_millis].
+    fn blockhash_remaining_millis(&self, hash: &Hash) -> Option<u64> {
+        let registered_at =
+            *self.blockhash_timestamps.read_robust().get(hash)?;
+        let (now, tolerance) = match *self.replayed_block_time.read_robust() {
+            // Block times are recorded in seconds, thus a transaction may
+            // have executed up to a second later than the block time implies
+            Some(block_time) => (block_time, 1_000),
+            None => (timing::timestamp(), 0),
+        };
+        (registered_at + self.transaction_expiration_millis + tolerance)
+            .checked_sub(now)
+    }
+
     // -----------------
     // Accounts
     // -----------------
@@ -833,6 +1027,339 @@ impl Bank {
         self.store_accounts((self.slot(), &[(pubkey, account)][..]))
     }
 
+    /// Starts tracking the accounts written by transactions, needs to be
+    /// enabled by the component that regularly takes them via
+    /// [Self::take_dirty_accounts].
+    pub fn enable_dirty_accounts_tracking(&self) {
+        self.tracks_dirty_accounts.store(true, Ordering::Relaxed);
+    }
+
+    /// Takes the accounts that were written by transactions since this was
+    /// called last, which allows finding changed accounts without loading all.
+    pub fn take_dirty_accounts(&self) -> HashSet<Pubkey> {
+        std::mem::take(&mut *self.dirty_accounts.write_robust())
+    }
+
+    fn mark_dirty_accounts(
+        &self,
+        sanitized_txs: &[SanitizedTransaction],
+        execution_results: &[TransactionExecutionResult],
+    ) {
+        if !self.tracks_dirty_accounts.load(Ordering::Relaxed) {
+            return;
+        }
+        let mut dirty_accounts = self.dirty_accounts.write_robust();
+        for (tx, result) in sanitized_txs.iter().zip(execution_results) {
+            if !result.was_executed() {
+                continue;
+            }
+            let message = tx.message();
+            dirty_accounts.extend(
+                message
+                    .account_keys()
+                    .iter()
+                    .enumerate()
+                    .filter(|(idx, _)| message.is_writable(*idx))
+                    .map(|(_, pubkey)| *pubkey),
+            );
+        }
+    }
+
+    /// Enables or disables journaling each write to the account, i.e. for
+    /// accounts delegated to this validator.
+    pub fn set_account_journaled(&self, pubkey: &Pubkey, journaled: bool) {
+        let mut journaled_accounts = self.journaled_accounts.write_robust();
+        if journaled {
+            journaled_accounts.insert(*pubkey);
+        } else {
+            journaled_accounts.remove(pubkey);
+        }
+    }
+
+    /// Takes the journal entries recorded since this was called last in
+    /// order to persist them.
+    pub fn take_account_journal_entries(&self) -> Vec<AccountJournalEntry> {
+        std::mem::take(&mut *self.account_journal_entries.write_robust())
+    }
+
+    /// Creates the journal entries for the writes to journaled accounts of
+    /// the successful transactions.
+    /// Needs to run before the accounts are stored since it compares them
+    /// with the accounts in the bank.
+    fn journal_account_writes(
+        &self,
+        sanitized_txs: &[SanitizedTransaction],
+        execution_results: &[TransactionExecutionResult],
+        loaded_txs: &[TransactionLoadResult],
+    ) -> Vec<AccountJournalEntry> {
+        let journaled_accounts = self.journaled_accounts.read_robust();
+        if journaled_accounts.is_empty() {
+            return vec![];
+        }
+        let mut entries = vec![];
+        for ((tx, result), (loaded_tx, _)) in
+            sanitized_txs.iter().zip(execution_results).zip(loaded_txs)
+        {
+            let (
+                Ok(loaded_tx),
+                TransactionExecutionResult::Executed { details, .. },
+            ) = (loaded_tx, result)
+            else {
+                continue;
+            };
+            if details.status.is_err() {
+                continue;
+            }
+            let message = tx.message();
+            for (idx, (pubkey, post)) in loaded_tx.accounts.iter().enumerate() {
+                if !message.is_writable(idx)
+                    || !journaled_accounts.contains(pubkey)
+                {
+                    continue;
+                }
+                let pre = self.get_account(pubkey);
+                entries.extend(AccountJournalEntry::from_write(
+                    *pubkey,
+                    self.slot(),
+                    *tx.signature(),
+                    pre.as_ref(),
+                    post,
+                ));
+            }
+        }
+        entries
+    }
+
+    /// Limits the lamports each fee payer can spend per window, `None`
+    /// removes the limit.
+    pub fn set_fee_payer_spend_limit(&self, limit: Option<FeePayerSpendLimit>) {
+        self.fee_payer_spend.write_robust().set_limit(limit);
+    }
+
+    /// Returns `true` if transactions paid by the fee payer are currently
+    /// rejected since it spent the max lamports within its window.
+    pub fn is_fee_payer_spend_limit_exceeded(
+        &self,
+        fee_payer: &Pubkey,
+    ) -> bool {
+        self.fee_payer_spend
+            .read_robust()
+            .is_exceeded(fee_payer, Instant::now())
+    }
+
+    /// Enables the validator identity to sponsor the fees of transactions
+    /// invoking the configured programs, `None` disables it.
+    pub fn set_gasless_config(&self, config: Option<GaslessConfig>) {
+        *self.gasless.write_robust() = config.map(GaslessSponsor::new);
+    }
+
+    /// Returns the fee lamports sponsored for the fee payer within its
+    /// current quota window.
+    pub fn sponsored_lamports(&self, fee_payer: &Pubkey) -> u64 {
+        self.gasless
+            .read_robust()
+            .as_ref()
+            .map_or(0, |sponsor| sponsor.used_quota(fee_payer, Instant::now()))
+    }
+
+    /// Returns the session key registered in the MagicContext by the
+    /// [authority] which produced the [signature] of the [message] in place
+    /// of the authority.
+    /// The index of the session keys is only rebuilt if the MagicContext
+    /// changed since it was last built.
+    pub fn session_key_signing_for(
+        &self,
+        authority: &Pubkey,
+        signature: &Signature,
+        message: &[u8],
+    ) -> Option<SessionKey> {
+        if let Some(found) = self
+            .session_keys
+            .read_robust()
+            .signing_for(authority, signature, message)
+        {
+            return found.cloned();
+        }
+        // Holding the write lock while loading the context ensures that an
+        // update of the context is not missed, since it is only invalidated
+        // after the update was stored
+        let mut session_keys = self.session_keys.write_robust();
+        if session_keys.is_stale() {
+            session_keys.rebuild(
+                self.get_account(&MAGIC_CONTEXT_PUBKEY)
+                    .map(|acc| MagicContext::find_session_keys(&acc))
+                    .unwrap_or_default(),
+            );
+        }
+        session_keys
+            .signing_for(authority, signature, message)
+            .flatten()
+            .cloned()
+    }
+
+    fn invalidate_session_keys_if_context_written(
+        &self,
+        sanitized_txs: &[SanitizedTransaction],
+        execution_results: &[TransactionExecutionResult],
+    ) {
+        let context_written = sanitized_txs
+            .iter()
+            .zip(execution_results)
+            .filter(|(_, result)| result.was_executed())
+            .any(|(tx, _)| {
+                let message = tx.message();
+                message
+                    .account_keys()
+                    .iter()
+                    .position(|pubkey| pubkey == &MAGIC_CONTEXT_PUBKEY)
+                    .map_or(false, |idx| message.is_writable(idx))
+            });
+        if context_written {
+            self.session_keys.write_robust().invalidate();
+        }
+    }
+
+    /// Determines which of the transactions are sponsored once per batch
+    /// before any of their fees are recorded against the quotas, such that
+    /// waiving and charging their fees agree on the same set.
+    fn sponsored_transactions(
+        &self,
+        sanitized_txs: &[SanitizedTransaction],
+    ) -> Vec<bool> {
+        let gasless = self.gasless.read_robust();
+        let Some(sponsor) = gasless.as_ref() else {
+            return vec![false; sanitized_txs.len()];
+        };
+        let now = Instant::now();
+        sanitized_txs
+            .iter()
+            .map(|tx| sponsor.is_sponsored(tx, now))
+            .collect()
+    }
+
+    /// Withdraws the fees of the executed sponsored transactions from the
+    /// validator identity and records them against the quotas of their fee
+    /// payers.
+    /// NOTE: a fee payer may exceed its quota by the transactions of one batch
+    fn charge_sponsor(
+        &self,
+        sanitized_txs: &[SanitizedTransaction],
+        execution_results: &[TransactionExecutionResult],
+        sponsored: &[bool],
+        lamports_per_signature: u64,
+    ) {
+        let mut gasless = self.gasless.write_robust();
+        let Some(sponsor) = gasless.as_mut() else {
+            return;
+        };
+        let now = Instant::now();
+        let mut sponsored_fees = 0;
+        for ((tx, result), sponsored) in
+            sanitized_txs.iter().zip(execution_results).zip(sponsored)
+        {
+            if !sponsored || !result.was_executed() {
+                continue;
+            }
+            let fee = self.get_fee_for_message_with_lamports_per_signature(
+                tx.message(),
+                lamports_per_signature,
+            );
+            sponsor.record(tx.message().fee_payer(), fee, now);
+            sponsored_fees += fee;
+            metrics::inc_sponsored_transaction(fee);
+        }
+        if sponsored_fees > 0 {
+            if let Err(err) = self.withdraw(&self.identity_id, sponsored_fees) {
+                warn!(
+                    "Validator identity failed to pay {} lamports of sponsored fees: {:?}",
+                    sponsored_fees, err
+                );
+            }
+        }
+    }
+
+    /// Collects the lamports of the fee payers of executed transactions
+    /// before their results are stored, only needed when a spend limit is set.
+    fn fee_payer_pre_lamports(
+        &self,
+        sanitized_txs: &[SanitizedTransaction],
+        execution_results: &[TransactionExecutionResult],
+    ) -> HashMap<Pubkey, u64> {
+        let has_limit = self.fee_payer_spend.read_robust().limit().is_some();
+        let mut pre_lamports = HashMap::new();
+        if !has_limit {
+            return pre_lamports;
+        }
+        for (tx, result) in sanitized_txs.iter().zip(execution_results) {
+            if result.was_executed() {
+                let fee_payer = *tx.message().fee_payer();
+                pre_lamports
+                    .entry(fee_payer)
+                    .or_insert_with(|| self.get_balance(&fee_payer));
+            }
+        }
+        pre_lamports
+    }
+
+    /// Records the lamports the fee payers spent, i.e. fees and transfers,
+    /// by comparing their stored lamports with [fee_payer_pre_lamports].
+    fn record_fee_payer_spend(
+        &self,
+        fee_payer_pre_lamports: HashMap<Pubkey, u64>,
+    ) {
+        if fee_payer_pre_lamports.is_empty() {
+            return;
+        }
+        let now = Instant::now();
+        let mut fee_payer_spend = self.fee_payer_spend.write_robust();
+        for (fee_payer, pre_lamports) in fee_payer_pre_lamports {
+            let spent =
+                pre_lamports.saturating_sub(self.get_balance(&fee_payer));
+            if spent > 0 {
+                fee_payer_spend.record(&fee_payer, spent, now);
+            }
+        }
+    }
+
+    /// Queues the upgrade of a deployed program to be applied at the end of
+    /// the slot. Until then transactions keep executing the version they
+    /// already loaded instead of failing since the program is not deployed.
+    pub fn queue_program_upgrade(&self, upgrade: PendingProgramUpgrade) {
+        self.program_versions.write_robust().queue_upgrade(upgrade);
+    }
+
+    pub fn has_pending_program_upgrades(&self) -> bool {
+        self.program_versions.read_robust().has_pending_upgrades()
+    }
+
+    /// Takes the upgrades queued since this was called last in order to
+    /// apply them before advancing the slot.
+    pub fn take_pending_program_upgrades(&self) -> Vec<PendingProgramUpgrade> {
+        self.program_versions.write_robust().take_pending_upgrades()
+    }
+
+    pub fn record_program_version(
+        &self,
+        program_id: Pubkey,
+        version: ProgramVersion,
+    ) {
+        self.program_versions
+            .write_robust()
+            .record(program_id, version);
+    }
+
+    /// Returns the version of the program which transactions executed at
+    /// the [slot], `None` if it was not deployed by this validator.
+    pub fn get_program_version(
+        &self,
+        program_id: &Pubkey,
+        slot: Slot,
+    ) -> Option<ProgramVersion> {
+        self.program_versions
+            .read_robust()
+            .version_at(program_id, slot)
+    }
+
     /// Returns all the accounts this bank can load
     pub fn get_all_accounts(
         &self,
@@ -860,7 +1387,12 @@ impl Bank {
             )
         });
         */
+        let context_written = (0..accounts.len())
+            .any(|idx| accounts.pubkey(idx) == &MAGIC_CONTEXT_PUBKEY);
         self.rc.accounts.store_accounts_cached(accounts);
+        if context_written {
+            self.session_keys.write_robust().invalidate();
+        }
         m.stop();
         self.rc
             .accounts
@@ -1004,6 +1536,23 @@ impl Bank {
             .load_by_program_with_filter(program_id, filter, config)
     }
 
+    /// Returns the accounts owned by the [program_id] via the owner index,
+    /// without loading them.
+    pub fn get_program_account_pubkeys(
+        &self,
+        program_id: &Pubkey,
+    ) -> Vec<Pubkey> {
+        self.accounts_db().owner_index.pubkeys(program_id)
+    }
+
+    /// Rebuilds the owner index from all accounts in the bank and returns the
+    /// number of indexed accounts.
+    /// The index is updated on every write, but this ensures it is consistent
+    /// with the accounts after they were restored, i.e. on startup.
+    pub fn rebuild_owner_index(&self) -> usize {
+        self.accounts_db().rebuild_owner_index()
+    }
+
     pub fn byte_limit_for_scans(&self) -> Option<usize> {
         // NOTE I cannot see where the retrieved value [AccountsIndexConfig::scan_results_limit_bytes]
         // solana/accounts-db/src/accounts_index.rs :217
@@ -1021,6 +1570,28 @@ impl Bank {
         .unwrap_or_default()
     }
 
+    /// Synchronizes the clock with the provided [clock] of the remote chain.
+    /// From the next slot on the epoch and unix timestamp of the clock sysvar
+    /// are derived from it while its slot remains the slot of the bank.
+    pub fn set_remote_clock(&self, clock: sysvar::clock::Clock) {
+        *self.remote_clock.write_robust() = Some(RemoteClock::new(clock));
+    }
+
+    /// Returns `true` if the clock is synchronized with the remote chain via
+    /// [Self::set_remote_clock].
+    pub fn is_clock_synced(&self) -> bool {
+        self.remote_clock.read_robust().is_some()
+    }
+
+    /// Returns the estimated current slot of the remote chain if the clock
+    /// is synchronized with it.
+    pub fn remote_slot(&self) -> Option<Slot> {
+        self.remote_clock
+            .read_robust()
+            .as_ref()
+            .map(RemoteClock::slot)
+    }
+
     fn update_clock(
         &self,
         epoch_start_timestamp: UnixTimestamp,
@@ -1044,7 +1615,7 @@ impl Bank {
         // and confirmed that the timestamps match
 
         let slot = self.slot();
-        let clock = sysvar::clock::Clock {
+        let mut clock = sysvar::clock::Clock {
             slot,
             epoch_start_timestamp,
             epoch: self.epoch_schedule().get_epoch(slot),
@@ -1053,6 +1624,14 @@ impl Bank {
                 .get_leader_schedule_epoch(slot),
             unix_timestamp,
         };
+
+        if let Some(remote_clock) = self.remote_clock.read_robust().as_ref() {
+            remote_clock.apply_to(&mut clock, &self.clock());
+        }
+        // When replaying the ledger we restore the recorded timestamp instead
+        if let Some(timestamp) = timestamp {
+            clock.unix_timestamp = timestamp;
+        }
         self.update_sysvar_account(&sysvar::clock::id(), |account| {
             create_account(
                 &clock,
@@ -1159,7 +1738,7 @@ impl Bank {
     }
 
     pub fn update_recent_blockhashes(&self) {
-        let blockhash_queue = self.blockhash_queue.read().unwrap();
+        let blockhash_queue = self.blockhash_queue.read_robust();
         self.update_recent_blockhashes_locked(&blockhash_queue);
     }
 
@@ -1212,8 +1791,8 @@ impl Bank {
     }
 
     pub fn is_blockhash_valid(&self, hash: &Hash) -> bool {
-        let blockhash_queue = self.blockhash_queue.read().unwrap();
-        blockhash_queue.is_hash_valid(hash)
+        let blockhash_queue = self.blockhash_queue.read_robust();
+        self.is_hash_unexpired(hash, &blockhash_queue)
     }
 
     pub fn is_blockhash_valid_for_age(
@@ -1221,7 +1800,7 @@ impl Bank {
         hash: &Hash,
         max_age: u64,
     ) -> bool {
-        let blockhash_queue = self.blockhash_queue.read().unwrap();
+        let blockhash_queue = self.blockhash_queue.read_robust();
         blockhash_queue.is_hash_valid_for_age(hash, max_age)
     }
 
@@ -1285,8 +1864,7 @@ impl Bank {
         self.add_builtin_account(name.as_str(), &program_id, false);
         self.builtin_programs.insert(program_id);
         self.loaded_programs_cache
-            .write()
-            .unwrap()
+            .write_robust()
             .assign_program(program_id, Arc::new(builtin));
     }
 
@@ -1472,11 +2050,19 @@ impl Bank {
         &self,
         sanitized_txs: &[impl core::borrow::Borrow<SanitizedTransaction>],
         lock_results: &[Result<()>],
+        sponsored: &[bool],
         error_counters: &mut TransactionErrorMetrics,
     ) -> Vec<TransactionCheckResult> {
         let age_results =
             self.check_age(sanitized_txs, lock_results, error_counters);
-        self.check_status_cache(sanitized_txs, age_results, error_counters)
+        let cache_results =
+            self.check_status_cache(sanitized_txs, age_results, error_counters);
+        let spend_results = self.check_fee_payer_spend(
+            sanitized_txs,
+            cache_results,
+            error_counters,
+        );
+        Self::waive_sponsored_fees(sponsored, spend_results)
     }
 
     fn check_transaction_for_nonce(
@@ -1499,7 +2085,7 @@ impl Bank {
         lock_results: &[Result<()>],
         error_counters: &mut TransactionErrorMetrics,
     ) -> Vec<TransactionCheckResult> {
-        let hash_queue = self.blockhash_queue.read().unwrap();
+        let hash_queue = self.blockhash_queue.read_robust();
         let last_blockhash = hash_queue.last_hash();
         let next_durable_nonce = DurableNonce::from_blockhash(&last_blockhash);
         sanitized_txs
@@ -1525,7 +2111,7 @@ impl Bank {
         error_counters: &mut TransactionErrorMetrics,
     ) -> TransactionCheckResult {
         let recent_blockhash = tx.message().recent_blockhash();
-        if hash_queue.is_hash_valid_for_age(recent_blockhash, self.max_age) {
+        if self.is_hash_unexpired(recent_blockhash, hash_queue) {
             (
                 Ok(()),
                 None,
@@ -1560,7 +2146,7 @@ impl Bank {
         age_results: Vec<TransactionCheckResult>,
         error_counters: &mut TransactionErrorMetrics,
     ) -> Vec<TransactionCheckResult> {
-        let rcache = self.status_cache.read().unwrap();
+        let rcache = self.status_cache.read_robust();
         sanitized_txs
             .iter()
             .zip(age_results)
@@ -1583,6 +2169,58 @@ impl Bank {
             .collect()
     }
 
+    fn check_fee_payer_spend(
+        &self,
+        sanitized_txs: &[impl core::borrow::Borrow<SanitizedTransaction>],
+        cache_results: Vec<TransactionCheckResult>,
+        error_counters: &mut TransactionErrorMetrics,
+    ) -> Vec<TransactionCheckResult> {
+        let fee_payer_spend = self.fee_payer_spend.read_robust();
+        if fee_payer_spend.limit().is_none() {
+            return cache_results;
+        }
+        let now = Instant::now();
+        sanitized_txs
+            .iter()
+            .zip(cache_results)
+            .map(|(sanitized_tx, (cache_result, nonce, lamports))| {
+                let fee_payer = sanitized_tx.borrow().message().fee_payer();
+                if cache_result.is_ok()
+                    && fee_payer_spend.is_exceeded(fee_payer, now)
+                {
+                    debug!("Fee payer {} exceeded its spend limit", fee_payer);
+                    error_counters.would_exceed_max_account_cost_limit += 1;
+                    return (
+                        Err(TransactionError::WouldExceedMaxAccountCostLimit),
+                        None,
+                        None,
+                    );
+                }
+                (cache_result, nonce, lamports)
+            })
+            .collect()
+    }
+
+    /// Zeroes the fees of sponsored transactions so the fee payer isn't
+    /// charged when they are loaded, the sponsor is charged instead once they
+    /// are committed, see [Self::charge_sponsor].
+    fn waive_sponsored_fees(
+        sponsored: &[bool],
+        spend_results: Vec<TransactionCheckResult>,
+    ) -> Vec<TransactionCheckResult> {
+        sponsored
+            .iter()
+            .zip(spend_results)
+            .map(|(sponsored, (spend_result, nonce, lamports))| {
+                if spend_result.is_ok() && *sponsored {
+                    (spend_result, nonce, lamports.map(|_| 0))
+                } else {
+                    (spend_result, nonce, lamports)
+                }
+            })
+            .collect()
+    }
+
     // -----------------
     // Transaction Execution
     // -----------------
@@ -1636,10 +2274,13 @@ impl Bank {
             })
             .collect();
 
+        let sponsored_transactions = self.sponsored_transactions(sanitized_txs);
+
         let mut check_time = Measure::start("check_transactions");
         let mut check_results = self.check_transactions(
             sanitized_txs,
             batch.lock_results(),
+            &sponsored_transactions,
             &mut error_counters,
         );
         check_time.stop();
@@ -1650,10 +2291,12 @@ impl Bank {
         );
 
         // 2. Load and execute sanitized transactions
+        // The magic program resolves the validator context from the thread
+        // executing its instructions
+        let entered_context = self.validator_context.enter();
         let sanitized_output = self
             .transaction_processor
-            .read()
-            .unwrap()
+            .read_robust()
             .load_and_execute_sanitized_transactions(
                 self,
                 sanitized_txs,
@@ -1667,6 +2310,7 @@ impl Bank {
                 self.builtin_programs.iter(),
                 log_messages_bytes_limit,
             );
+        drop(entered_context);
 
         // 3. Record transaction execution stats
         let mut signature_count = 0;
@@ -1676,7 +2320,7 @@ impl Bank {
         let mut executed_with_successful_result_count: usize = 0;
         let err_count = &mut error_counters.total;
         let transaction_log_collector_config =
-            self.transaction_log_collector_config.read().unwrap();
+            self.transaction_log_collector_config.read_robust();
 
         let mut collect_logs_time = Measure::start("collect_logs_time");
         for (execution_result, tx) in
@@ -1736,7 +2380,7 @@ impl Bank {
                     }) = execution_result.details()
                     {
                         let mut transaction_log_collector =
-                            self.transaction_log_collector.write().unwrap();
+                            self.transaction_log_collector.write_robust();
                         let transaction_log_index =
                             transaction_log_collector.logs.len();
 
@@ -1805,6 +2449,7 @@ impl Bank {
             executed_non_vote_transactions_count,
             executed_with_successful_result_count,
             signature_count,
+            sponsored_transactions,
             error_counters,
         }
     }
@@ -1832,6 +2477,7 @@ impl Bank {
             executed_non_vote_transactions_count,
             executed_with_successful_result_count,
             signature_count,
+            sponsored_transactions,
             ..
         } = self.load_and_execute_transactions(
             batch,
@@ -1847,6 +2493,7 @@ impl Bank {
             batch.sanitized_transactions(),
             &mut loaded_transactions,
             execution_results,
+            &sponsored_transactions,
             last_blockhash,
             lamports_per_signature,
             CommitTransactionCounts {
@@ -1882,6 +2529,7 @@ impl Bank {
         sanitized_txs: &[SanitizedTransaction],
         loaded_txs: &mut [TransactionLoadResult],
         execution_results: Vec<TransactionExecutionResult>,
+        sponsored: &[bool],
         last_blockhash: Hash,
         lamports_per_signature: u64,
         counts: CommitTransactionCounts,
@@ -1920,6 +2568,15 @@ impl Bank {
                 .fetch_max(committed_transactions_count, Ordering::Relaxed);
         }
 
+        let journal_entries = self.journal_account_writes(
+            sanitized_txs,
+            &execution_results,
+            loaded_txs,
+        );
+
+        let fee_payer_pre_lamports =
+            self.fee_payer_pre_lamports(sanitized_txs, &execution_results);
+
         let mut write_time = Measure::start("write_time");
         let durable_nonce = DurableNonce::from_blockhash(&last_blockhash);
         self.rc.accounts.store_cached(
@@ -1930,6 +2587,23 @@ impl Bank {
             &durable_nonce,
             lamports_per_signature,
         );
+        self.mark_dirty_accounts(sanitized_txs, &execution_results);
+        self.invalidate_session_keys_if_context_written(
+            sanitized_txs,
+            &execution_results,
+        );
+        self.record_fee_payer_spend(fee_payer_pre_lamports);
+        self.charge_sponsor(
+            sanitized_txs,
+            &execution_results,
+            sponsored,
+            lamports_per_signature,
+        );
+        if !journal_entries.is_empty() {
+            self.account_journal_entries
+                .write_robust()
+                .extend(journal_entries);
+        }
         let rent_debits = self.collect_rent(&execution_results, loaded_txs);
 
         let mut update_stakes_cache_time =
@@ -1954,7 +2628,7 @@ impl Bank {
             } = execution_result
             {
                 if details.status.is_ok() {
-                    let mut cache = self.loaded_programs_cache.write().unwrap();
+                    let mut cache = self.loaded_programs_cache.write_robust();
                     cache.merge(programs_modified_by_tx);
                 }
             }
@@ -1989,6 +2663,7 @@ impl Bank {
             .filter_program_errors_and_collect_fee(
                 sanitized_txs,
                 &execution_results,
+                sponsored,
             );
         update_transaction_statuses_time.stop();
         timings.saturating_add_in_place(
@@ -2008,7 +2683,7 @@ impl Bank {
         sanitized_txs: &[SanitizedTransaction],
         execution_results: &[TransactionExecutionResult],
     ) {
-        let mut status_cache = self.status_cache.write().unwrap();
+        let mut status_cache = self.status_cache.write_robust();
         assert_eq!(sanitized_txs.len(), execution_results.len());
         for (tx, execution_result) in
             sanitized_txs.iter().zip(execution_results)
@@ -2047,14 +2722,16 @@ impl Bank {
         &self,
         txs: &[SanitizedTransaction],
         execution_results: &[TransactionExecutionResult],
+        sponsored: &[bool],
     ) -> Vec<Result<()>> {
-        let hash_queue = self.blockhash_queue.read().unwrap();
+        let hash_queue = self.blockhash_queue.read_robust();
         let mut fees = 0;
 
         let results = txs
             .iter()
             .zip(execution_results)
-            .map(|(tx, execution_result)| {
+            .zip(sponsored)
+            .map(|((tx, execution_result), sponsored)| {
                 let (execution_status, durable_nonce_fee) =
                     match &execution_result {
                         TransactionExecutionResult::Executed {
@@ -2100,7 +2777,9 @@ impl Bank {
                 //...except nonce accounts, which already have their
                 // post-load, fee deducted, pre-execute account state
                 // stored
-                if execution_status.is_err() && !is_nonce {
+                //...and sponsored transactions whose fee is paid by the
+                // validator identity
+                if execution_status.is_err() && !is_nonce && !sponsored {
                     self.withdraw(tx.message().fee_payer(), fee)?;
                 }
 
@@ -2199,7 +2878,7 @@ impl Bank {
         message: &SanitizedMessage,
     ) -> Option<u64> {
         let lamports_per_signature = {
-            let blockhash_queue = self.blockhash_queue.read().unwrap();
+            let blockhash_queue = self.blockhash_queue.read_robust();
             blockhash_queue
                 .get_lamports_per_signature(message.recent_blockhash())
         }
@@ -2268,11 +2947,30 @@ impl Bank {
         &self,
         transaction: &SanitizedTransaction,
         enable_cpi_recording: bool,
+    ) -> TransactionSimulationResult {
+        self.simulate_transaction_with_state_overrides_unchecked(
+            transaction,
+            enable_cpi_recording,
+            &HashMap::new(),
+        )
+    }
+
+    /// Like [Self::simulate_transaction_unchecked], but the transaction sees
+    /// the accounts in [state_overrides] instead of their state in the bank,
+    /// which allows previewing its outcome for a hypothetical state.
+    pub fn simulate_transaction_with_state_overrides_unchecked(
+        &self,
+        transaction: &SanitizedTransaction,
+        enable_cpi_recording: bool,
+        state_overrides: &HashMap<Pubkey, AccountSharedData>,
     ) -> TransactionSimulationResult {
         let account_keys = transaction.message().account_keys();
         let number_of_accounts = account_keys.len();
-        let account_overrides =
+        let mut account_overrides =
             self.get_account_overrides_for_simulation(&account_keys);
+        for (pubkey, account) in state_overrides {
+            account_overrides.set_account(pubkey, Some(account.clone()));
+        }
         let batch = self.prepare_unlocked_batch_from_single_tx(transaction);
         let mut timings = ExecuteTimings::default();
 
@@ -2339,6 +3037,51 @@ impl Bank {
         }
     }
 
+    /// Executes the [transactions] in order, each on top of the accounts
+    /// modified by the previous ones, without committing any of them.
+    /// Returns the index and error of the first transaction that fails, which
+    /// allows executing a bundle of transactions all or nothing.
+    pub fn simulate_bundle_unchecked(
+        &self,
+        transactions: &[SanitizedTransaction],
+    ) -> std::result::Result<(), (usize, TransactionError)> {
+        let mut account_overrides = AccountOverrides::default();
+        for (index, transaction) in transactions.iter().enumerate() {
+            let batch = self.prepare_unlocked_batch_from_single_tx(transaction);
+            let mut timings = ExecuteTimings::default();
+            let LoadAndExecuteTransactionsOutput {
+                loaded_transactions,
+                execution_results,
+                ..
+            } = self.load_and_execute_transactions(
+                &batch,
+                TransactionExecutionRecordingOpts::default(),
+                &mut timings,
+                Some(&account_overrides),
+                None,
+            );
+            execution_results[0]
+                .flattened_result()
+                .map_err(|err| (index, err))?;
+
+            let message = transaction.message();
+            if let (Ok(loaded_transaction), _) = &loaded_transactions[0] {
+                for (account_index, (pubkey, account)) in loaded_transaction
+                    .accounts
+                    .iter()
+                    .take(message.account_keys().len())
+                    .enumerate()
+                {
+                    if message.is_writable(account_index) {
+                        account_overrides
+                            .set_account(pubkey, Some(account.clone()));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
     fn get_account_overrides_for_simulation(
         &self,
         account_keys: &AccountKeys,
@@ -2419,7 +3162,7 @@ impl Bank {
         &self,
         signature: &Signature,
     ) -> Option<Result<()>> {
-        let rcache = self.status_cache.read().unwrap();
+        let rcache = self.status_cache.read_robust();
         rcache
             .get_recent_transaction_status(signature, None)
             .map(|v| v.1)
@@ -2431,8 +3174,7 @@ impl Bank {
         lookback_slots: Option<Slot>,
     ) -> Option<(Slot, Result<()>)> {
         self.status_cache
-            .read()
-            .expect("RwLock status_cache poisoned")
+            .read_robust()
             .get_recent_transaction_status(signature, lookback_slots)
     }
 
@@ -2584,7 +3326,7 @@ impl Bank {
     // NOTE: seems to be a synchronization point, i.e. only one thread can hold this
     // at a time
     pub fn freeze_lock(&self) -> RwLockReadGuard<Hash> {
-        self.hash.read().unwrap()
+        self.hash.read_robust()
     }
 
     /// Return the total capitalization of the Bank
@@ -2596,6 +3338,14 @@ impl Bank {
         self.accounts_db().storage_size()
     }
 
+    pub fn accounts_db_storage_file_count(&self) -> AccountsDbResult<usize> {
+        self.accounts_db().storage_file_count()
+    }
+
+    pub fn accounts_db_accounts_count(&self) -> usize {
+        self.accounts_db().accounts_count()
+    }
+
     fn accounts_db(&self) -> &AccountsDb {
         self.rc.accounts.accounts_db.as_ref()
     }
@@ -2620,10 +3370,7 @@ impl Bank {
         self.set_next_slot(next_slot);
 
         if next_slot > 0 {
-            self.status_cache
-                .write()
-                .expect("RwLock of status cache poisoned")
-                .add_root(next_slot - 1);
+            self.status_cache.write_robust().add_root(next_slot - 1);
         }
 
         self.update_sysvars(
@@ -2631,8 +3378,13 @@ impl Bank {
             Some(timestamp as UnixTimestamp),
         );
 
-        // Register the new blockhash with the blockhash queue
-        self.register_hash_with_timestamp(blockhash, timestamp);
+        // Register the new blockhash with the blockhash queue, the block time
+        // is stored in seconds
+        let block_time = timestamp.saturating_mul(1_000);
+        if self.blockhash_expiry == BlockhashExpiry::WallClock {
+            self.replayed_block_time.write_robust().replace(block_time);
+        }
+        self.register_hash_with_timestamp(blockhash, block_time);
 
         // NOTE: Not notifying Geyser Service doing replay
 
@@ -2647,13 +3399,21 @@ impl Bank {
         }
     }
 
+    /// Registers the [hash] with the [timestamp] in milliseconds.
     fn register_hash_with_timestamp(&self, hash: &Hash, timestamp: u64) {
-        let mut blockhash_queue = self.blockhash_queue.write().unwrap();
+        let mut blockhash_queue = self.blockhash_queue.write_robust();
         blockhash_queue.register_hash_with_timestamp(
             hash,
             self.fee_rate_governor.lamports_per_signature,
             timestamp,
         );
+        if self.blockhash_expiry == BlockhashExpiry::WallClock {
+            let mut blockhash_timestamps =
+                self.blockhash_timestamps.write_robust();
+            blockhash_timestamps.insert(*hash, timestamp);
+            blockhash_timestamps
+                .retain(|hash, _| blockhash_queue.is_hash_valid(hash));
+        }
     }
 
     // -----------------
@@ -2666,9 +3426,13 @@ impl Bank {
         // Update transaction processor with new slot
         // We used to just set the slot here, but wanted to avoid having a local
         // slightly modified copy of the solana-svm.
-        *self.transaction_processor.write().unwrap() =
+        self.reset_transaction_processor(next_slot);
+    }
+
+    fn reset_transaction_processor(&self, slot: Slot) {
+        *self.transaction_processor.write_robust() =
             TransactionBatchProcessor::new(
-                next_slot,
+                slot,
                 self.epoch,
                 // Potentially expensive clone
                 self.epoch_schedule.clone(),
@@ -17,17 +17,26 @@ use solana_sdk::{
 use solana_svm::runtime_config::RuntimeConfig;
 
 use crate::{
-    bank::Bank, slot_status_notifier_interface::SlotStatusNotifierArc,
+    bank::Bank,
+    blockhash_expiry::BlockhashExpiry,
+    slot_status_notifier_interface::{
+        SlotStatusNotifierArc, SlotStatusNotifiers,
+    },
     transaction_batch::TransactionBatch,
     transaction_logs::TransactionLogCollectorFilter,
-    EPHEM_DEFAULT_MILLIS_PER_SLOT,
+    DEFAULT_TRANSACTION_EXPIRATION_MILLIS, EPHEM_DEFAULT_MILLIS_PER_SLOT,
 };
 
 impl Bank {
     pub fn default_for_tests() -> Self {
         let accounts_db = AccountsDb::default_for_tests();
         let accounts = Accounts::new(Arc::new(accounts_db));
-        Self::default_with_accounts(accounts, EPHEM_DEFAULT_MILLIS_PER_SLOT)
+        Self::default_with_accounts(
+            accounts,
+            EPHEM_DEFAULT_MILLIS_PER_SLOT,
+            DEFAULT_TRANSACTION_EXPIRATION_MILLIS,
+            BlockhashExpiry::default(),
+        )
     }
 
     pub fn new_for_tests(
@@ -41,6 +50,7 @@ impl Bank {
             accounts_update_notifier,
             slot_status_notifier,
             EPHEM_DEFAULT_MILLIS_PER_SLOT,
+            BlockhashExpiry::default(),
         )
     }
 
@@ -50,8 +60,13 @@ impl Bank {
         accounts_update_notifier: Option<AccountsUpdateNotifier>,
         slot_status_notifier: Option<SlotStatusNotifierArc>,
         millis_per_slot: u64,
+        blockhash_expiry: BlockhashExpiry,
     ) -> Self {
         let account_paths = vec![];
+        let slot_status_notifiers = SlotStatusNotifiers::default();
+        if let Some(slot_status_notifier) = slot_status_notifier {
+            slot_status_notifiers.register("test", slot_status_notifier);
+        }
         let bank = Self::new(
             genesis_config,
             runtime_config,
@@ -60,9 +75,12 @@ impl Bank {
             false,
             account_paths,
             accounts_update_notifier,
-            slot_status_notifier,
+            slot_status_notifiers,
             millis_per_slot,
+            DEFAULT_TRANSACTION_EXPIRATION_MILLIS,
+            blockhash_expiry,
             Pubkey::new_unique(),
+            Default::default(),
         );
         bank.transaction_log_collector_config
             .write()
@@ -0,0 +1,37 @@
+/// Determines when the blockhashes handed out to clients expire.
+/// On low-traffic ephemerals slots keep advancing without any activity, so
+/// clients that wait between fetching a blockhash and sending a transaction
+/// may prefer a longer or wall-clock based validity.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BlockhashExpiry {
+    /// Blockhashes expire once `transaction_expiration_millis` worth of
+    /// slots were advanced since they were registered.
+    #[default]
+    Slots,
+    /// Blockhashes expire once the given number of slots were advanced since
+    /// they were registered.
+    MaxAgeSlots(u64),
+    /// Blockhashes expire once `transaction_expiration_millis` passed on the
+    /// wall-clock since they were registered, regardless of how many slots
+    /// were advanced meanwhile.
+    WallClock,
+}
+
+impl BlockhashExpiry {
+    /// The number of blockhashes kept in the queue, which is also the number
+    /// of slots processed transactions are remembered to reject duplicates.
+    pub fn max_age(
+        &self,
+        millis_per_slot: u64,
+        transaction_expiration_millis: u64,
+    ) -> u64 {
+        let expiration_slots =
+            (transaction_expiration_millis / millis_per_slot).max(1);
+        match self {
+            Self::Slots => expiration_slots,
+            Self::MaxAgeSlots(max_age) => (*max_age).max(1),
+            // Leaves room for slots advancing faster than configured
+            Self::WallClock => expiration_slots * 2,
+        }
+    }
+}
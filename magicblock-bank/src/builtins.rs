@@ -1,10 +1,11 @@
 // NOTE: copied from runtime/src/builtins.rs
 use solana_program_runtime::invoke_context::BuiltinFunctionWithContext;
 use solana_sdk::{
-    address_lookup_table, bpf_loader_upgradeable, compute_budget,
-    pubkey::Pubkey,
+    address_lookup_table, bpf_loader, bpf_loader_deprecated,
+    bpf_loader_upgradeable, compute_budget, loader_v4, pubkey::Pubkey,
 };
 
+#[derive(Clone)]
 pub struct BuiltinPrototype {
     pub feature_id: Option<Pubkey>,
     pub program_id: Pubkey,
@@ -98,3 +99,27 @@ pub static BUILTINS: &[BuiltinPrototype] = &[
             solana_address_lookup_table_program::processor::Entrypoint::vm,
     },
 ];
+
+/// Builtin programs we don't load by default, but which can be registered via
+/// the `additional_builtins` of the bank in order to match a specific
+/// environment, i.e. to run programs deployed with the original BPF loader.
+pub static OPTIONAL_BUILTINS: &[BuiltinPrototype] = &[
+    BuiltinPrototype {
+        feature_id: None,
+        program_id: bpf_loader::id(),
+        name: "solana_bpf_loader_program",
+        entrypoint: solana_bpf_loader_program::Entrypoint::vm,
+    },
+    BuiltinPrototype {
+        feature_id: None,
+        program_id: bpf_loader_deprecated::id(),
+        name: "solana_bpf_loader_deprecated_program",
+        entrypoint: solana_bpf_loader_program::Entrypoint::vm,
+    },
+    BuiltinPrototype {
+        feature_id: None,
+        program_id: loader_v4::id(),
+        name: "loader_v4",
+        entrypoint: solana_loader_v4_program::Entrypoint::vm,
+    },
+];
@@ -1,2 +1,4 @@
+pub use magicblock_core::consts::DEFAULT_TRANSACTION_EXPIRATION_MILLIS;
+
 pub const LAMPORTS_PER_SIGNATURE: u64 = 5000;
 pub const EPHEM_DEFAULT_MILLIS_PER_SLOT: u64 = 50;
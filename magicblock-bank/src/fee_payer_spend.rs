@@ -0,0 +1,123 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use solana_sdk::pubkey::Pubkey;
+
+/// Max lamports a single fee payer may spend within one [Self::window].
+/// This prevents a compromised session key from draining its escrow by
+/// spamming transactions inside the ephemeral.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeePayerSpendLimit {
+    pub max_lamports: u64,
+    pub window: Duration,
+}
+
+#[derive(Debug)]
+struct FeePayerSpend {
+    window_started_at: Instant,
+    lamports: u64,
+}
+
+/// Tracks the lamports each fee payer spent within its current window.
+#[derive(Debug, Default)]
+pub(crate) struct FeePayerSpendTracker {
+    limit: Option<FeePayerSpendLimit>,
+    spends: HashMap<Pubkey, FeePayerSpend>,
+}
+
+impl FeePayerSpendTracker {
+    pub fn limit(&self) -> Option<FeePayerSpendLimit> {
+        self.limit
+    }
+
+    pub fn set_limit(&mut self, limit: Option<FeePayerSpendLimit>) {
+        self.limit = limit;
+        self.spends.clear();
+    }
+
+    /// Returns `true` if the fee payer already spent the max lamports within
+    /// its current window.
+    pub fn is_exceeded(&self, fee_payer: &Pubkey, now: Instant) -> bool {
+        let Some(limit) = self.limit else {
+            return false;
+        };
+        self.spends.get(fee_payer).map_or(false, |spend| {
+            now.duration_since(spend.window_started_at) < limit.window
+                && spend.lamports >= limit.max_lamports
+        })
+    }
+
+    /// Returns the lamports the fee payer spent within its current window.
+    pub fn spent(&self, fee_payer: &Pubkey, now: Instant) -> u64 {
+        let Some(limit) = self.limit else {
+            return 0;
+        };
+        self.spends
+            .get(fee_payer)
+            .filter(|spend| {
+                now.duration_since(spend.window_started_at) < limit.window
+            })
+            .map_or(0, |spend| spend.lamports)
+    }
+
+    pub fn record(&mut self, fee_payer: &Pubkey, lamports: u64, now: Instant) {
+        let Some(limit) = self.limit else {
+            return;
+        };
+        if !self.spends.contains_key(fee_payer) {
+            // Only fee payers that spent within their window need tracking
+            self.spends.retain(|_, spend| {
+                now.duration_since(spend.window_started_at) < limit.window
+            });
+        }
+        let spend =
+            self.spends
+                .entry(*fee_payer)
+                .or_insert_with(|| FeePayerSpend {
+                    window_started_at: now,
+                    lamports: 0,
+                });
+        if now.duration_since(spend.window_started_at) >= limit.window {
+            spend.window_started_at = now;
+            spend.lamports = 0;
+        }
+        spend.lamports = spend.lamports.saturating_add(lamports);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fee_payer_spend_limit() {
+        let mut tracker = FeePayerSpendTracker::default();
+        let fee_payer = Pubkey::new_unique();
+        let other_fee_payer = Pubkey::new_unique();
+        let now = Instant::now();
+
+        // Nothing is tracked without a limit
+        tracker.record(&fee_payer, u64::MAX, now);
+        assert!(!tracker.is_exceeded(&fee_payer, now));
+
+        tracker.set_limit(Some(FeePayerSpendLimit {
+            max_lamports: 10_000,
+            window: Duration::from_secs(60),
+        }));
+        tracker.record(&fee_payer, 5_000, now);
+        assert!(!tracker.is_exceeded(&fee_payer, now));
+        tracker.record(&fee_payer, 5_000, now + Duration::from_secs(1));
+        assert!(tracker.is_exceeded(&fee_payer, now + Duration::from_secs(1)));
+        assert_eq!(tracker.spent(&fee_payer, now), 10_000);
+        assert!(!tracker.is_exceeded(&other_fee_payer, now));
+
+        // The spend resets once the window passed
+        let later = now + Duration::from_secs(60);
+        assert!(!tracker.is_exceeded(&fee_payer, later));
+        assert_eq!(tracker.spent(&fee_payer, later), 0);
+        tracker.record(&fee_payer, 5_000, later);
+        assert!(!tracker.is_exceeded(&fee_payer, later));
+    }
+}
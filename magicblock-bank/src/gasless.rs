@@ -0,0 +1,74 @@
+use std::{collections::HashSet, time::Instant};
+
+use solana_sdk::{
+    compute_budget, pubkey::Pubkey, transaction::SanitizedTransaction,
+};
+
+use crate::fee_payer_spend::{FeePayerSpendLimit, FeePayerSpendTracker};
+
+/// Configures the validator identity to sponsor the fees of transactions
+/// which only invoke the [Self::sponsored_programs].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GaslessConfig {
+    pub sponsored_programs: HashSet<Pubkey>,
+    /// Max fee lamports sponsored for a single fee payer within one window,
+    /// once used up the fee payer pays its fees itself again
+    pub quota: Option<FeePayerSpendLimit>,
+}
+
+/// Decides which transactions are sponsored and tracks the quota each fee
+/// payer used.
+#[derive(Debug)]
+pub(crate) struct GaslessSponsor {
+    sponsored_programs: HashSet<Pubkey>,
+    quotas: FeePayerSpendTracker,
+}
+
+impl GaslessSponsor {
+    pub fn new(config: GaslessConfig) -> Self {
+        let mut quotas = FeePayerSpendTracker::default();
+        quotas.set_limit(config.quota);
+        Self {
+            sponsored_programs: config.sponsored_programs,
+            quotas,
+        }
+    }
+
+    /// Returns `true` if the transaction invokes at least one sponsored
+    /// program and no other programs besides the compute budget program.
+    pub fn invokes_sponsored_programs(
+        &self,
+        tx: &SanitizedTransaction,
+    ) -> bool {
+        let mut invokes_sponsored = false;
+        for (program_id, _) in tx.message().program_instructions_iter() {
+            if self.sponsored_programs.contains(program_id) {
+                invokes_sponsored = true;
+            } else if !compute_budget::check_id(program_id) {
+                return false;
+            }
+        }
+        invokes_sponsored
+    }
+
+    pub fn is_quota_exceeded(&self, fee_payer: &Pubkey, now: Instant) -> bool {
+        self.quotas.is_exceeded(fee_payer, now)
+    }
+
+    pub fn is_sponsored(
+        &self,
+        tx: &SanitizedTransaction,
+        now: Instant,
+    ) -> bool {
+        self.invokes_sponsored_programs(tx)
+            && !self.is_quota_exceeded(tx.message().fee_payer(), now)
+    }
+
+    pub fn record(&mut self, fee_payer: &Pubkey, fee: u64, now: Instant) {
+        self.quotas.record(fee_payer, fee, now);
+    }
+
+    pub fn used_quota(&self, fee_payer: &Pubkey, now: Instant) -> u64 {
+        self.quotas.spent(fee_payer, now)
+    }
+}
@@ -1,13 +1,21 @@
+pub mod account_journal;
 pub mod address_lookup_table;
 pub mod bank;
 mod bank_helpers;
 mod bank_rc;
-mod builtins;
+pub mod blockhash_expiry;
+pub mod builtins;
 mod consts;
+pub mod fee_payer_spend;
+pub mod gasless;
 pub mod genesis_utils;
 pub mod get_compute_budget_details;
 pub mod program_loader;
+pub mod program_versions;
+mod remote_clock;
+mod session_keys;
 pub mod slot_status_notifier_interface;
+pub mod state_archive;
 mod status_cache;
 mod sysvar_cache;
 pub mod transaction_batch;
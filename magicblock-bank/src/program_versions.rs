@@ -0,0 +1,167 @@
+use std::collections::HashMap;
+
+use solana_sdk::{
+    clock::Slot, hash::Hash, pubkey::Pubkey, transaction::Transaction,
+};
+
+/// A version of a program's bytecode and the first slot at which
+/// transactions execute it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProgramVersion {
+    pub effective_slot: Slot,
+    pub bytecode_hash: Hash,
+}
+
+/// The upgrade of a program that is already deployed.
+/// Upgrading a program makes it unusable for the remainder of the slot,
+/// thus the upgrade is only applied at the end of the slot.
+#[derive(Debug, Clone)]
+pub struct PendingProgramUpgrade {
+    pub program_id: Pubkey,
+    pub bytecode_hash: Hash,
+    pub transaction: Transaction,
+}
+
+/// The number of versions kept per program, older ones are dropped to keep
+/// programs that are upgraded frequently from growing the history unbounded.
+pub const MAX_PROGRAM_VERSIONS: usize = 64;
+
+/// Tracks the versions of the programs deployed in the bank as well as the
+/// upgrades that are not applied yet.
+#[derive(Debug, Default)]
+pub(crate) struct ProgramVersionsTracker {
+    pending_upgrades: Vec<PendingProgramUpgrade>,
+    versions: HashMap<Pubkey, Vec<ProgramVersion>>,
+}
+
+impl ProgramVersionsTracker {
+    /// Queues the upgrade, replacing a pending upgrade of the same program
+    /// since only the latest version needs to be deployed.
+    pub fn queue_upgrade(&mut self, upgrade: PendingProgramUpgrade) {
+        self.pending_upgrades
+            .retain(|pending| pending.program_id != upgrade.program_id);
+        self.pending_upgrades.push(upgrade);
+    }
+
+    pub fn has_pending_upgrades(&self) -> bool {
+        !self.pending_upgrades.is_empty()
+    }
+
+    pub fn take_pending_upgrades(&mut self) -> Vec<PendingProgramUpgrade> {
+        std::mem::take(&mut self.pending_upgrades)
+    }
+
+    pub fn record(&mut self, program_id: Pubkey, version: ProgramVersion) {
+        let versions = self.versions.entry(program_id).or_default();
+        // Versions deployed later supersede the ones effective at the same
+        // or a later slot
+        versions.retain(|v| v.effective_slot < version.effective_slot);
+        versions.push(version);
+        if versions.len() > MAX_PROGRAM_VERSIONS {
+            versions.drain(..versions.len() - MAX_PROGRAM_VERSIONS);
+        }
+    }
+
+    /// Returns the version of the program that transactions executed at the
+    /// [slot], `None` if that version was pruned already.
+    pub fn version_at(
+        &self,
+        program_id: &Pubkey,
+        slot: Slot,
+    ) -> Option<ProgramVersion> {
+        self.versions
+            .get(program_id)?
+            .iter()
+            .rev()
+            .find(|version| version.effective_slot <= slot)
+            .copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::hash::hash;
+
+    use super::*;
+
+    fn version(effective_slot: Slot, bytecode: &[u8]) -> ProgramVersion {
+        ProgramVersion {
+            effective_slot,
+            bytecode_hash: hash(bytecode),
+        }
+    }
+
+    #[test]
+    fn test_version_at_slot() {
+        let program_id = Pubkey::new_unique();
+        let mut tracker = ProgramVersionsTracker::default();
+        tracker.record(program_id, version(3, b"v1"));
+        tracker.record(program_id, version(7, b"v2"));
+
+        assert_eq!(tracker.version_at(&program_id, 2), None);
+        assert_eq!(tracker.version_at(&program_id, 3), Some(version(3, b"v1")));
+        assert_eq!(tracker.version_at(&program_id, 6), Some(version(3, b"v1")));
+        assert_eq!(tracker.version_at(&program_id, 7), Some(version(7, b"v2")));
+        assert_eq!(
+            tracker.version_at(&program_id, Slot::MAX),
+            Some(version(7, b"v2"))
+        );
+        assert_eq!(tracker.version_at(&Pubkey::new_unique(), 7), None);
+    }
+
+    #[test]
+    fn test_record_supersedes_version_of_same_slot() {
+        let program_id = Pubkey::new_unique();
+        let mut tracker = ProgramVersionsTracker::default();
+        tracker.record(program_id, version(3, b"v1"));
+        tracker.record(program_id, version(5, b"v2"));
+        tracker.record(program_id, version(5, b"v3"));
+
+        assert_eq!(tracker.version_at(&program_id, 4), Some(version(3, b"v1")));
+        assert_eq!(tracker.version_at(&program_id, 5), Some(version(5, b"v3")));
+    }
+
+    #[test]
+    fn test_record_prunes_oldest_versions() {
+        let program_id = Pubkey::new_unique();
+        let mut tracker = ProgramVersionsTracker::default();
+        for slot in 1..=(MAX_PROGRAM_VERSIONS as Slot + 2) {
+            tracker.record(program_id, version(slot, &slot.to_le_bytes()));
+        }
+
+        assert_eq!(tracker.versions[&program_id].len(), MAX_PROGRAM_VERSIONS);
+        assert_eq!(tracker.version_at(&program_id, 2), None);
+        assert_eq!(
+            tracker.version_at(&program_id, 3),
+            Some(version(3, &3u64.to_le_bytes()))
+        );
+    }
+
+    #[test]
+    fn test_queue_upgrade_replaces_pending_upgrade_of_program() {
+        let program_id = Pubkey::new_unique();
+        let other_program_id = Pubkey::new_unique();
+        let upgrade = |program_id, bytecode: &[u8]| PendingProgramUpgrade {
+            program_id,
+            bytecode_hash: hash(bytecode),
+            transaction: Transaction::default(),
+        };
+        let mut tracker = ProgramVersionsTracker::default();
+        assert!(!tracker.has_pending_upgrades());
+        tracker.queue_upgrade(upgrade(program_id, b"v1"));
+        tracker.queue_upgrade(upgrade(other_program_id, b"v1"));
+        tracker.queue_upgrade(upgrade(program_id, b"v2"));
+
+        let pending = tracker
+            .take_pending_upgrades()
+            .into_iter()
+            .map(|upgrade| (upgrade.program_id, upgrade.bytecode_hash))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            pending,
+            vec![(other_program_id, hash(b"v1")), (program_id, hash(b"v2"))]
+        );
+        assert!(!tracker.has_pending_upgrades());
+        assert!(tracker.take_pending_upgrades().is_empty());
+    }
+}
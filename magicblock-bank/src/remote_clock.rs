@@ -0,0 +1,45 @@
+use std::time::Instant;
+
+use solana_sdk::clock::{Clock, Slot, DEFAULT_MS_PER_SLOT};
+
+/// The latest [Clock] received from the remote chain which the clock of the
+/// bank tracks when it is synchronized with the remote chain.
+#[derive(Debug, Clone)]
+pub(crate) struct RemoteClock {
+    clock: Clock,
+    received_at: Instant,
+}
+
+impl RemoteClock {
+    pub(crate) fn new(clock: Clock) -> Self {
+        Self {
+            clock,
+            received_at: Instant::now(),
+        }
+    }
+
+    /// Derives the epoch and unix timestamp of the [clock] from the remote
+    /// clock, extrapolating the time that passed since it was received.
+    /// The [Clock::slot] stays the slot of the bank, see [Self::slot] for
+    /// the slot of the remote chain.
+    /// The timestamp never goes back behind the [prev_clock] in order to
+    /// keep it monotonic when a remote clock arrives late.
+    pub(crate) fn apply_to(&self, clock: &mut Clock, prev_clock: &Clock) {
+        clock.epoch = self.clock.epoch;
+        clock.epoch_start_timestamp = self.clock.epoch_start_timestamp;
+        clock.leader_schedule_epoch = self.clock.leader_schedule_epoch;
+        clock.unix_timestamp = self
+            .clock
+            .unix_timestamp
+            .saturating_add(self.received_at.elapsed().as_secs() as i64)
+            .max(prev_clock.unix_timestamp);
+    }
+
+    /// Estimates the current slot of the remote chain from the time that
+    /// passed since the remote clock was received.
+    pub(crate) fn slot(&self) -> Slot {
+        let elapsed_slots =
+            self.received_at.elapsed().as_millis() as u64 / DEFAULT_MS_PER_SLOT;
+        self.clock.slot.saturating_add(elapsed_slots)
+    }
+}
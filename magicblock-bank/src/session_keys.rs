@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+
+use magicblock_program::SessionKey;
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+
+/// Index of the session keys registered in the MagicContext by authority.
+/// Session keys are looked up for each transaction that is admitted, thus
+/// the index is only rebuilt from the MagicContext once it changed instead of
+/// deserializing the entire context for every lookup.
+#[derive(Debug, Default)]
+pub(crate) struct SessionKeyIndex {
+    /// `None` if the MagicContext changed since the index was built
+    session_keys: Option<HashMap<Pubkey, Vec<SessionKey>>>,
+}
+
+impl SessionKeyIndex {
+    pub fn is_stale(&self) -> bool {
+        self.session_keys.is_none()
+    }
+
+    pub fn invalidate(&mut self) {
+        self.session_keys = None;
+    }
+
+    pub fn rebuild(&mut self, session_keys: Vec<SessionKey>) {
+        let mut by_authority = HashMap::<_, Vec<_>>::new();
+        for session_key in session_keys {
+            by_authority
+                .entry(session_key.authority)
+                .or_default()
+                .push(session_key);
+        }
+        self.session_keys = Some(by_authority);
+    }
+
+    /// Returns the session key of the [authority] that produced the
+    /// [signature] of the [message] in its place unless the index is stale.
+    pub fn signing_for(
+        &self,
+        authority: &Pubkey,
+        signature: &Signature,
+        message: &[u8],
+    ) -> Option<Option<&SessionKey>> {
+        self.session_keys.as_ref().map(|session_keys| {
+            session_keys.get(authority).and_then(|session_keys| {
+                session_keys.iter().find(|session_key| {
+                    signature.verify(session_key.session_key.as_ref(), message)
+                })
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::signature::{Keypair, Signer};
+
+    use super::*;
+
+    fn session_key(session_key: &Keypair, authority: Pubkey) -> SessionKey {
+        SessionKey {
+            session_key: session_key.pubkey(),
+            authority,
+            valid_until: 1_000,
+            allowed_programs: vec![],
+        }
+    }
+
+    #[test]
+    fn test_session_key_index_rebuild_and_invalidate() {
+        let mut index = SessionKeyIndex::default();
+        assert!(index.is_stale());
+
+        let authority = Pubkey::new_unique();
+        let session_keypair = Keypair::new();
+        let registered = session_key(&session_keypair, authority);
+        index.rebuild(vec![
+            session_key(&Keypair::new(), authority),
+            registered.clone(),
+        ]);
+        assert!(!index.is_stale());
+
+        let message = b"message";
+        let signature = session_keypair.sign_message(message);
+        assert_eq!(
+            index.signing_for(&authority, &signature, message),
+            Some(Some(&registered))
+        );
+        assert_eq!(
+            index.signing_for(&Pubkey::new_unique(), &signature, message),
+            Some(None)
+        );
+        assert_eq!(
+            index.signing_for(
+                &authority,
+                &Keypair::new().sign_message(message),
+                message
+            ),
+            Some(None)
+        );
+
+        index.invalidate();
+        assert!(index.is_stale());
+        assert_eq!(index.signing_for(&authority, &signature, message), None);
+    }
+}
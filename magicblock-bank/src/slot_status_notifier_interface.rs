@@ -1,9 +1,103 @@
-use std::{fmt::Debug, sync::Arc};
+use std::{
+    fmt::Debug,
+    sync::{Arc, Mutex},
+    time::Instant,
+};
 
-use solana_sdk::clock::Slot;
+use magicblock_core::robust_lock::RobustMutex;
+use magicblock_metrics::metrics;
+use solana_sdk::{clock::Slot, hash::Hash};
 
 pub trait SlotStatusNotifier: Debug {
-    fn notify_slot_status(&self, slot: Slot, parent_slot: Option<Slot>);
+    /// Notified when the bank advanced to [slot] whose [blockhash] is known
+    /// from its start since we don't produce actual blocks.
+    fn notify_slot_status(
+        &self,
+        slot: Slot,
+        parent_slot: Option<Slot>,
+        blockhash: &Hash,
+        parent_blockhash: &Hash,
+    );
 }
 
 pub type SlotStatusNotifierArc = Arc<dyn SlotStatusNotifier + Sync + Send>;
+
+#[derive(Debug)]
+struct SlotStatusConsumer {
+    name: String,
+    notifier: SlotStatusNotifierArc,
+}
+
+/// The consumers notified whenever the bank advances a slot, i.e. geyser
+/// plugins or metrics.
+///
+/// Consumers are notified one after the other in the order they were
+/// registered and each of them is notified of all slots in order, the next
+/// slot is only delivered once all consumers received the current one.
+/// Thus a consumer can rely on the ones registered before it having seen a
+/// slot already. How long it took from advancing the slot until a consumer
+/// was notified is tracked per consumer as its lag.
+///
+/// Clones share the same consumers such that they can be registered after
+/// the bank was created.
+#[derive(Debug, Clone, Default)]
+pub struct SlotStatusNotifiers {
+    consumers: Arc<Mutex<Vec<SlotStatusConsumer>>>,
+}
+
+impl SlotStatusNotifiers {
+    /// Registers the [notifier] to be notified after all consumers that
+    /// were registered before it, the [name] identifies it in the metrics.
+    pub fn register(&self, name: &str, notifier: SlotStatusNotifierArc) {
+        self.consumers.lock_robust().push(SlotStatusConsumer {
+            name: name.to_string(),
+            notifier,
+        });
+    }
+
+    /// The names of the registered consumers in the order they are notified
+    pub fn consumer_names(&self) -> Vec<String> {
+        self.consumers
+            .lock_robust()
+            .iter()
+            .map(|consumer| consumer.name.clone())
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.consumers.lock_robust().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Notifies all consumers of the [slot] which the bank advanced to at
+    /// [advanced_at].
+    pub(crate) fn notify_slot_status(
+        &self,
+        slot: Slot,
+        parent_slot: Option<Slot>,
+        blockhash: &Hash,
+        parent_blockhash: &Hash,
+        advanced_at: Instant,
+    ) {
+        // Holding the lock while notifying keeps consumers from being
+        // registered in between and delivers a slot to all consumers before
+        // the next slot is delivered, thus consumers must not register other
+        // consumers while they are notified
+        let consumers = self.consumers.lock_robust();
+        for consumer in consumers.iter() {
+            consumer.notifier.notify_slot_status(
+                slot,
+                parent_slot,
+                blockhash,
+                parent_blockhash,
+            );
+            metrics::observe_slot_status_notify_lag(
+                &consumer.name,
+                advanced_at.elapsed(),
+            );
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use std::{
+    collections::HashSet,
+    fs::File,
+    io::{self, BufReader, BufWriter},
+    path::Path,
+};
+
+use magicblock_core::magic_program::MAGIC_CONTEXT_PUBKEY;
+use serde::{Deserialize, Serialize};
+use solana_sdk::{
+    account::{Account, ReadableAccount},
+    clock::Slot,
+    native_loader,
+    pubkey::Pubkey,
+    sysvar,
+};
+
+use crate::bank::Bank;
+
+/// Bumped whenever the layout of [StateArchive] changes.
+pub const STATE_ARCHIVE_VERSION: u8 = 1;
+
+// -----------------
+// StateArchive
+// -----------------
+/// The accounts of an ephemeral in a portable format which can be loaded into
+/// another one, i.e. to clone a staging environment or to reproduce a bug.
+///
+/// Sysvars, builtins and the magic context are not part of it since each
+/// ephemeral maintains its own.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateArchive {
+    pub version: u8,
+    /// The slot of the bank the accounts were exported at
+    pub slot: Slot,
+    pub accounts: Vec<(Pubkey, Account)>,
+}
+
+impl StateArchive {
+    /// Collects the accounts of the [bank], limited to the ones owned by one
+    /// of the [owners] if provided.
+    pub fn from_bank(bank: &Bank, owners: Option<&HashSet<Pubkey>>) -> Self {
+        let accounts = bank
+            .get_all_accounts(true)
+            .into_iter()
+            .filter(|(pubkey, account)| {
+                is_archivable(pubkey, account.owner(), account.lamports())
+                    && owners
+                        .map_or(true, |owners| owners.contains(account.owner()))
+            })
+            .map(|(pubkey, account)| (pubkey, Account::from(account)))
+            .collect();
+        Self {
+            version: STATE_ARCHIVE_VERSION,
+            slot: bank.slot(),
+            accounts,
+        }
+    }
+
+    pub fn write_to_file(&self, path: &Path) -> io::Result<()> {
+        let writer = BufWriter::new(File::create(path)?);
+        bincode::serialize_into(writer, self).map_err(invalid_data)
+    }
+
+    pub fn read_from_file(path: &Path) -> io::Result<Self> {
+        let reader = BufReader::new(File::open(path)?);
+        let archive: Self =
+            bincode::deserialize_from(reader).map_err(invalid_data)?;
+        if archive.version != STATE_ARCHIVE_VERSION {
+            return Err(invalid_data(format!(
+                "unsupported state archive version {}, expected {}",
+                archive.version, STATE_ARCHIVE_VERSION
+            )));
+        }
+        Ok(archive)
+    }
+
+    /// Returns the accounts to import into the [bank].
+    /// The identity of the validator and the [excluded] accounts, i.e. its
+    /// faucet, are skipped since they belong to the importing validator.
+    pub fn accounts_to_import(
+        &self,
+        bank: &Bank,
+        excluded: &[Pubkey],
+    ) -> Vec<(Pubkey, Account)> {
+        let identity = bank.get_identity();
+        self.accounts
+            .iter()
+            .filter(|(pubkey, account)| {
+                is_archivable(pubkey, &account.owner, account.lamports)
+                    && pubkey != &identity
+                    && !excluded.contains(pubkey)
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+fn is_archivable(pubkey: &Pubkey, owner: &Pubkey, lamports: u64) -> bool {
+    lamports > 0
+        && pubkey != &MAGIC_CONTEXT_PUBKEY
+        && owner != &sysvar::ID
+        && owner != &native_loader::ID
+}
+
+fn invalid_data(
+    err: impl Into<Box<dyn std::error::Error + Send + Sync>>,
+) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
@@ -8,6 +8,7 @@ use std::{
 };
 
 use log::*;
+use magicblock_core::robust_lock::RobustMutex;
 use rand::{thread_rng, Rng};
 use solana_frozen_abi_macro::AbiExample;
 use solana_sdk::{clock::Slot, hash::Hash, signature::Signature};
@@ -166,7 +167,7 @@ impl<T: Clone> StatusCache<T> {
         let forks = hash_map.2.entry(key_slice).or_default();
         forks.push((slot, res.clone()));
         let slot_deltas = self.slot_deltas.entry(slot).or_default();
-        let mut fork_entry = slot_deltas.lock().unwrap();
+        let mut fork_entry = slot_deltas.lock_robust();
         let (_, hash_entry) = fork_entry
             .entry(*transaction_blockhash)
             .or_insert((key_index, vec![]));
@@ -1,4 +1,5 @@
 // NOTE: copied from bank/sysvar_cache.rs and tests removed
+use magicblock_core::robust_lock::RobustRwLock;
 use solana_program_runtime::sysvar_cache::SysvarCache;
 use solana_sdk::{account::ReadableAccount, clock::Clock};
 
@@ -6,8 +7,8 @@ use super::bank::Bank;
 
 impl Bank {
     pub(crate) fn fill_missing_sysvar_cache_entries(&self) {
-        let tx_processor = self.transaction_processor.read().unwrap();
-        let mut sysvar_cache = tx_processor.sysvar_cache.write().unwrap();
+        let tx_processor = self.transaction_processor.read_robust();
+        let mut sysvar_cache = tx_processor.sysvar_cache.write_robust();
         sysvar_cache.fill_missing_entries(|pubkey, callback| {
             if let Some(account) = self.get_account_with_fixed_root(pubkey) {
                 callback(account.data());
@@ -16,24 +17,22 @@ impl Bank {
     }
 
     pub(crate) fn set_clock_in_sysvar_cache(&self, clock: Clock) {
-        let tx_processor = self.transaction_processor.read().unwrap();
-        tx_processor.sysvar_cache.write().unwrap().set_clock(clock);
+        let tx_processor = self.transaction_processor.read_robust();
+        tx_processor.sysvar_cache.write_robust().set_clock(clock);
     }
 
     #[allow(dead_code)]
     pub(crate) fn reset_sysvar_cache(&self) {
-        let tx_processor = self.transaction_processor.read().unwrap();
-        let mut sysvar_cache = tx_processor.sysvar_cache.write().unwrap();
+        let tx_processor = self.transaction_processor.read_robust();
+        let mut sysvar_cache = tx_processor.sysvar_cache.write_robust();
         sysvar_cache.reset();
     }
 
     pub fn get_sysvar_cache_for_tests(&self) -> SysvarCache {
         self.transaction_processor
-            .read()
-            .unwrap()
+            .read_robust()
             .sysvar_cache
-            .read()
-            .unwrap()
+            .read_robust()
             .clone()
     }
 }
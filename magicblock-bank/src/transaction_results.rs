@@ -20,6 +20,8 @@ pub struct LoadAndExecuteTransactionsOutput {
     // an error.
     pub executed_with_successful_result_count: usize,
     pub signature_count: u64,
+    // Whether each transaction is sponsored, determined once for the batch
+    pub sponsored_transactions: Vec<bool>,
     pub error_counters: TransactionErrorMetrics,
 }
 
@@ -0,0 +1,117 @@
+#![cfg(feature = "dev-context-only-utils")]
+
+use std::sync::Arc;
+
+use magicblock_bank::{
+    bank::Bank,
+    bank_dev_utils::transactions::{
+        create_funded_account, execute_transactions,
+    },
+    blockhash_expiry::BlockhashExpiry,
+    DEFAULT_TRANSACTION_EXPIRATION_MILLIS, EPHEM_DEFAULT_MILLIS_PER_SLOT,
+};
+use solana_sdk::{
+    genesis_config::create_genesis_config, hash::Hash,
+    native_token::LAMPORTS_PER_SOL, pubkey::Pubkey, system_transaction, timing,
+    transaction::SanitizedTransaction,
+};
+use solana_svm::runtime_config::RuntimeConfig;
+use test_tools_core::init_logger;
+
+fn bank_with_blockhash_expiry(blockhash_expiry: BlockhashExpiry) -> Bank {
+    let (genesis_config, _) = create_genesis_config(u64::MAX);
+    Bank::new_with_config_for_tests(
+        &genesis_config,
+        Arc::new(RuntimeConfig::default()),
+        None,
+        None,
+        EPHEM_DEFAULT_MILLIS_PER_SLOT,
+        blockhash_expiry,
+    )
+}
+
+#[test]
+fn test_blockhash_expires_after_max_age_slots() {
+    init_logger!();
+
+    let bank = bank_with_blockhash_expiry(BlockhashExpiry::MaxAgeSlots(3));
+    let blockhash = bank.last_blockhash();
+    assert_eq!(
+        bank.get_blockhash_last_valid_block_height(&blockhash),
+        Some(bank.block_height() + 3)
+    );
+
+    for _ in 0..3 {
+        bank.advance_slot();
+    }
+    assert!(bank.is_blockhash_valid(&blockhash));
+
+    bank.advance_slot();
+    assert!(!bank.is_blockhash_valid(&blockhash));
+}
+
+#[test]
+fn test_blockhash_outlives_slots_with_wall_clock_expiry() {
+    init_logger!();
+
+    let bank = bank_with_blockhash_expiry(BlockhashExpiry::WallClock);
+    let expiration_slots =
+        DEFAULT_TRANSACTION_EXPIRATION_MILLIS / EPHEM_DEFAULT_MILLIS_PER_SLOT;
+    assert_eq!(bank.max_age, expiration_slots * 2);
+
+    let blockhash = bank.last_blockhash();
+    let last_valid_block_height = bank
+        .get_blockhash_last_valid_block_height(&blockhash)
+        .unwrap();
+    assert!(last_valid_block_height <= bank.block_height() + expiration_slots);
+
+    // Slots advancing faster than configured do not expire the blockhash
+    for _ in 0..expiration_slots + 1 {
+        bank.advance_slot();
+    }
+    assert!(bank.is_blockhash_valid(&blockhash));
+}
+
+#[test]
+fn test_replayed_blockhash_does_not_expire_after_restart() {
+    init_logger!();
+
+    let bank = bank_with_blockhash_expiry(BlockhashExpiry::WallClock);
+
+    // The ledger was recorded long before the validator restarts
+    let block_time = (timing::timestamp()
+        - 10 * DEFAULT_TRANSACTION_EXPIRATION_MILLIS)
+        / 1_000;
+    let recorded_blockhash = Hash::new_unique();
+    bank.replay_slot(
+        1,
+        &bank.last_blockhash(),
+        &recorded_blockhash,
+        block_time,
+    );
+    bank.replay_slot(
+        2,
+        &recorded_blockhash,
+        &Hash::new_unique(),
+        block_time + 1,
+    );
+    assert!(bank.is_blockhash_valid(&recorded_blockhash));
+
+    // Transactions recorded with that blockhash are replayed
+    let from = create_funded_account(&bank, Some(LAMPORTS_PER_SOL));
+    let tx = system_transaction::transfer(
+        &from,
+        &Pubkey::new_unique(),
+        1,
+        recorded_blockhash,
+    );
+    let (results, _) = execute_transactions(
+        &bank,
+        vec![SanitizedTransaction::from_transaction_for_tests(tx)],
+    );
+    assert!(results.execution_results[0].was_executed_successfully());
+
+    // Once the validator runs again the blockhash expires on the wall-clock
+    bank.advance_slot();
+    assert!(!bank.is_blockhash_valid(&recorded_blockhash));
+}
@@ -0,0 +1,73 @@
+#![cfg(feature = "dev-context-only-utils")]
+
+use assert_matches::assert_matches;
+use magicblock_bank::{
+    bank::Bank, bank_dev_utils::transactions::create_funded_account,
+};
+use solana_sdk::{
+    genesis_config::create_genesis_config,
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    signature::Keypair,
+    signer::Signer,
+    system_transaction,
+    transaction::{SanitizedTransaction, TransactionError},
+};
+use test_tools_core::init_logger;
+
+fn transfer(
+    bank: &Bank,
+    from: &Keypair,
+    to: &Pubkey,
+    lamports: u64,
+) -> SanitizedTransaction {
+    SanitizedTransaction::from_transaction_for_tests(
+        system_transaction::transfer(from, to, lamports, bank.last_blockhash()),
+    )
+}
+
+#[test]
+fn test_bundle_executes_on_top_of_previous_transactions() {
+    init_logger!();
+
+    let (genesis_config, _) = create_genesis_config(u64::MAX);
+    let bank = Bank::new_for_tests(&genesis_config, None, None);
+
+    let alice = create_funded_account(&bank, Some(LAMPORTS_PER_SOL));
+    // Bob is only funded by the first transaction of the bundle
+    let bob = Keypair::new();
+    let carol = Pubkey::new_unique();
+    let bundle = vec![
+        transfer(&bank, &alice, &bob.pubkey(), LAMPORTS_PER_SOL / 2),
+        transfer(&bank, &bob, &carol, LAMPORTS_PER_SOL / 4),
+    ];
+
+    assert_eq!(bank.simulate_bundle_unchecked(&bundle), Ok(()));
+    // Nothing is committed while simulating
+    assert_eq!(bank.get_balance(&alice.pubkey()), LAMPORTS_PER_SOL);
+    assert_eq!(bank.get_balance(&bob.pubkey()), 0);
+    assert_eq!(bank.get_balance(&carol), 0);
+}
+
+#[test]
+fn test_bundle_reports_first_failing_transaction() {
+    init_logger!();
+
+    let (genesis_config, _) = create_genesis_config(u64::MAX);
+    let bank = Bank::new_for_tests(&genesis_config, None, None);
+
+    let alice = create_funded_account(&bank, Some(LAMPORTS_PER_SOL));
+    let bundle = vec![
+        transfer(&bank, &alice, &Pubkey::new_unique(), LAMPORTS_PER_SOL / 2),
+        // Succeeds on its own, but not after the first transfer
+        transfer(&bank, &alice, &Pubkey::new_unique(), LAMPORTS_PER_SOL / 2),
+        transfer(&bank, &alice, &Pubkey::new_unique(), 1),
+    ];
+
+    assert_eq!(bank.simulate_bundle_unchecked(&bundle[1..2]), Ok(()));
+    assert_matches!(
+        bank.simulate_bundle_unchecked(&bundle),
+        Err((1, TransactionError::InstructionError(0, _)))
+    );
+    assert_eq!(bank.get_balance(&alice.pubkey()), LAMPORTS_PER_SOL);
+}
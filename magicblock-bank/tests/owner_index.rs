@@ -0,0 +1,202 @@
+#![cfg(feature = "dev-context-only-utils")]
+
+use std::collections::{HashMap, HashSet};
+
+use assert_matches::assert_matches;
+use magicblock_accounts_db::accounts_index::ScanConfig;
+use magicblock_bank::{
+    bank::Bank,
+    bank_dev_utils::transactions::{
+        create_funded_account, execute_transactions,
+    },
+    genesis_utils::create_genesis_config_with_leader_and_fees,
+    LAMPORTS_PER_SIGNATURE,
+};
+use solana_sdk::{
+    account::{Account, ReadableAccount},
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    signer::Signer,
+    system_program, system_transaction,
+    transaction::SanitizedTransaction,
+};
+use test_tools_core::init_logger;
+
+fn bank_for_tests() -> Bank {
+    let genesis_config_info = create_genesis_config_with_leader_and_fees(
+        u64::MAX,
+        &Pubkey::new_unique(),
+    );
+    Bank::new_for_tests(&genesis_config_info.genesis_config, None, None)
+}
+
+fn store_owned_account(bank: &Bank, owner: &Pubkey, lamports: u64) -> Pubkey {
+    let pubkey = Pubkey::new_unique();
+    bank.store_account(
+        &pubkey,
+        &Account {
+            lamports,
+            data: vec![1, 2, 3],
+            owner: *owner,
+            executable: false,
+            rent_epoch: u64::MAX,
+        },
+    );
+    pubkey
+}
+
+fn indexed_pubkeys(bank: &Bank, owner: &Pubkey) -> HashSet<Pubkey> {
+    bank.get_program_account_pubkeys(owner)
+        .into_iter()
+        .collect()
+}
+
+/// Asserts that the owner index contains exactly the accounts found by
+/// scanning all accounts of the bank.
+fn assert_index_consistent(bank: &Bank) {
+    let mut scanned = HashMap::<Pubkey, HashSet<Pubkey>>::new();
+    for (pubkey, account) in bank.get_all_accounts(false) {
+        scanned.entry(*account.owner()).or_default().insert(pubkey);
+    }
+    for (owner, pubkeys) in &scanned {
+        assert_eq!(
+            &indexed_pubkeys(bank, owner),
+            pubkeys,
+            "owner index of {} diverges from accounts",
+            owner
+        );
+    }
+    let num_scanned = scanned.values().map(HashSet::len).sum::<usize>();
+    let num_indexed = bank.rebuild_owner_index();
+    assert_eq!(num_indexed, num_scanned);
+}
+
+#[test]
+fn test_owner_index_store_reassign_and_close() {
+    init_logger!();
+
+    let bank = bank_for_tests();
+    let program_a = Pubkey::new_unique();
+    let program_b = Pubkey::new_unique();
+
+    let first = store_owned_account(&bank, &program_a, LAMPORTS_PER_SOL);
+    let second = store_owned_account(&bank, &program_a, LAMPORTS_PER_SOL);
+    assert_eq!(
+        indexed_pubkeys(&bank, &program_a),
+        HashSet::from([first, second])
+    );
+    assert!(indexed_pubkeys(&bank, &program_b).is_empty());
+    assert_index_consistent(&bank);
+
+    // Reassigning moves the account to the new owner
+    bank.store_account(
+        &first,
+        &Account {
+            lamports: LAMPORTS_PER_SOL,
+            owner: program_b,
+            ..Account::default()
+        },
+    );
+    assert_eq!(indexed_pubkeys(&bank, &program_a), HashSet::from([second]));
+    assert_eq!(indexed_pubkeys(&bank, &program_b), HashSet::from([first]));
+    assert_index_consistent(&bank);
+
+    // Closing removes it from the index
+    bank.store_account(&second, &Account::default());
+    assert!(indexed_pubkeys(&bank, &program_a).is_empty());
+    assert_index_consistent(&bank);
+}
+
+#[test]
+fn test_owner_index_transactions() {
+    init_logger!();
+
+    let bank = bank_for_tests();
+    let program = Pubkey::new_unique();
+
+    // Assigning an account to a program indexes it under the program
+    let assigned = create_funded_account(&bank, Some(LAMPORTS_PER_SOL));
+    let assign_tx =
+        system_transaction::assign(&assigned, bank.last_blockhash(), &program);
+    // Transferring all lamports closes the account
+    let drained = create_funded_account(&bank, Some(LAMPORTS_PER_SOL));
+    let recipient = Pubkey::new_unique();
+    let drain_tx = system_transaction::transfer(
+        &drained,
+        &recipient,
+        LAMPORTS_PER_SOL - LAMPORTS_PER_SIGNATURE,
+        bank.last_blockhash(),
+    );
+    assert!(indexed_pubkeys(&bank, &system_program::id())
+        .contains(&drained.pubkey()));
+
+    let (results, _) = execute_transactions(
+        &bank,
+        [assign_tx, drain_tx]
+            .into_iter()
+            .map(SanitizedTransaction::from_transaction_for_tests)
+            .collect(),
+    );
+    for result in &results.execution_results {
+        assert_matches!(result.details().unwrap().status, Ok(()));
+    }
+
+    assert_eq!(
+        indexed_pubkeys(&bank, &program),
+        HashSet::from([assigned.pubkey()])
+    );
+    let system_accounts = indexed_pubkeys(&bank, &system_program::id());
+    assert!(!system_accounts.contains(&assigned.pubkey()));
+    assert!(!system_accounts.contains(&drained.pubkey()));
+    assert!(system_accounts.contains(&recipient));
+    assert_index_consistent(&bank);
+}
+
+#[test]
+fn test_get_program_accounts_uses_owner_index() {
+    init_logger!();
+
+    let bank = bank_for_tests();
+    let program = Pubkey::new_unique();
+    let mut expected = (0..10)
+        .map(|idx| store_owned_account(&bank, &program, 1_000 + idx))
+        .collect::<Vec<_>>();
+    store_owned_account(&bank, &Pubkey::new_unique(), 1_000);
+    expected.sort();
+
+    let accounts = bank.get_program_accounts(&program, &ScanConfig::new(false));
+    assert_eq!(
+        accounts
+            .iter()
+            .map(|(pubkey, _)| *pubkey)
+            .collect::<Vec<_>>(),
+        expected
+    );
+    assert!(accounts
+        .iter()
+        .all(|(_, account)| account.owner() == &program));
+
+    let filtered = bank.get_filtered_program_accounts(
+        &program,
+        |account| account.lamports() >= 1_005,
+        &ScanConfig::new(false),
+    );
+    assert_eq!(filtered.len(), 5);
+}
+
+#[test]
+fn test_owner_index_rebuild() {
+    init_logger!();
+
+    let bank = bank_for_tests();
+    let program = Pubkey::new_unique();
+    let pubkeys = (0..5)
+        .map(|_| store_owned_account(&bank, &program, LAMPORTS_PER_SOL))
+        .collect::<HashSet<_>>();
+
+    let before = indexed_pubkeys(&bank, &program);
+    bank.rebuild_owner_index();
+    assert_eq!(indexed_pubkeys(&bank, &program), before);
+    assert_eq!(before, pubkeys);
+    assert_index_consistent(&bank);
+}
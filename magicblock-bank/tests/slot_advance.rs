@@ -4,8 +4,8 @@
 use log::*;
 use magicblock_bank::bank::Bank;
 use solana_sdk::{
-    account::Account, genesis_config::create_genesis_config, pubkey::Pubkey,
-    system_program,
+    account::Account, clock::Clock, genesis_config::create_genesis_config,
+    pubkey::Pubkey, system_program,
 };
 use test_tools_core::init_logger;
 
@@ -115,3 +115,80 @@ fn test_bank_advances_slot_in_clock_sysvar() {
     bank.advance_slot();
     assert_eq!(bank.clock().slot, 5);
 }
+
+#[test]
+fn test_bank_clock_tracks_remote_clock() {
+    init_logger!();
+
+    let (genesis_config, _) = create_genesis_config(u64::MAX);
+    let bank = Bank::new_for_tests(&genesis_config, None, None);
+
+    let remote_clock = Clock {
+        slot: 300_000_000,
+        epoch_start_timestamp: 4_000_000_000,
+        epoch: 700,
+        leader_schedule_epoch: 701,
+        // Ahead of the system clock which the bank starts out with
+        unix_timestamp: 4_000_100_000,
+    };
+
+    assert!(!bank.is_clock_synced());
+    assert_eq!(bank.remote_slot(), None);
+
+    bank.set_remote_clock(remote_clock.clone());
+    assert!(bank.is_clock_synced());
+    assert!(bank.remote_slot().unwrap() >= remote_clock.slot);
+
+    // The slot of the clock remains the slot of the bank
+    bank.advance_slot();
+    let clock = bank.clock();
+    assert_eq!(clock.slot, 1);
+    assert_eq!(clock.epoch, remote_clock.epoch);
+    assert_eq!(
+        clock.epoch_start_timestamp,
+        remote_clock.epoch_start_timestamp
+    );
+    assert_eq!(
+        clock.leader_schedule_epoch,
+        remote_clock.leader_schedule_epoch
+    );
+    assert_eq!(clock.unix_timestamp, remote_clock.unix_timestamp);
+
+    // A remote clock that arrives late never moves the clock backwards
+    bank.set_remote_clock(Clock {
+        slot: remote_clock.slot - 10,
+        unix_timestamp: remote_clock.unix_timestamp - 10,
+        ..remote_clock.clone()
+    });
+    bank.advance_slot();
+    let clock = bank.clock();
+    assert_eq!(clock.slot, 2);
+    assert_eq!(clock.unix_timestamp, remote_clock.unix_timestamp);
+}
+
+#[test]
+fn test_bank_clock_replays_recorded_timestamp_with_remote_clock() {
+    init_logger!();
+
+    let (genesis_config, _) = create_genesis_config(u64::MAX);
+    let bank = Bank::new_for_tests(&genesis_config, None, None);
+
+    let remote_clock = Clock {
+        slot: 300_000_000,
+        epoch_start_timestamp: 4_000_000_000,
+        epoch: 700,
+        leader_schedule_epoch: 701,
+        unix_timestamp: 4_000_100_000,
+    };
+    bank.set_remote_clock(remote_clock.clone());
+    bank.advance_slot_at(Some(remote_clock.unix_timestamp - 5));
+
+    assert_eq!(
+        bank.clock(),
+        Clock {
+            slot: 1,
+            unix_timestamp: remote_clock.unix_timestamp - 5,
+            ..remote_clock
+        }
+    );
+}
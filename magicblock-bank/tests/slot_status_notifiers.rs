@@ -0,0 +1,101 @@
+#![cfg(feature = "dev-context-only-utils")]
+
+use std::sync::{Arc, Mutex};
+
+use magicblock_bank::{
+    bank::Bank,
+    slot_status_notifier_interface::{SlotStatusNotifier, SlotStatusNotifiers},
+};
+use solana_sdk::{
+    clock::Slot, genesis_config::create_genesis_config, hash::Hash,
+};
+use test_tools_core::init_logger;
+
+type Notifications = Arc<Mutex<Vec<(&'static str, Slot)>>>;
+
+#[derive(Debug)]
+struct RecordingNotifier {
+    name: &'static str,
+    notifications: Notifications,
+}
+
+impl SlotStatusNotifier for RecordingNotifier {
+    fn notify_slot_status(
+        &self,
+        slot: Slot,
+        _parent_slot: Option<Slot>,
+        _blockhash: &Hash,
+        _parent_blockhash: &Hash,
+    ) {
+        self.notifications.lock().unwrap().push((self.name, slot));
+    }
+}
+
+fn register(
+    notifiers: &SlotStatusNotifiers,
+    name: &'static str,
+    notifications: &Notifications,
+) {
+    notifiers.register(
+        name,
+        Arc::new(RecordingNotifier {
+            name,
+            notifications: notifications.clone(),
+        }),
+    );
+}
+
+#[test]
+fn test_consumers_are_notified_of_each_slot_in_registration_order() {
+    init_logger!();
+
+    let (genesis_config, _) = create_genesis_config(u64::MAX);
+    let bank = Bank::new_for_tests(&genesis_config, None, None);
+    let notifications = Notifications::default();
+    for name in ["geyser", "metrics", "ledger"] {
+        register(bank.slot_status_notifiers(), name, &notifications);
+    }
+    assert_eq!(
+        bank.slot_status_notifiers().consumer_names(),
+        vec!["geyser", "metrics", "ledger"]
+    );
+
+    let first = bank.advance_slot();
+    let second = bank.advance_slot();
+
+    assert_eq!(
+        *notifications.lock().unwrap(),
+        vec![
+            ("geyser", first),
+            ("metrics", first),
+            ("ledger", first),
+            ("geyser", second),
+            ("metrics", second),
+            ("ledger", second),
+        ]
+    );
+}
+
+#[test]
+fn test_consumer_passed_to_bank_is_notified_before_registered_ones() {
+    init_logger!();
+
+    let (genesis_config, _) = create_genesis_config(u64::MAX);
+    let notifications = Notifications::default();
+    let bank = Bank::new_for_tests(
+        &genesis_config,
+        None,
+        Some(Arc::new(RecordingNotifier {
+            name: "geyser",
+            notifications: notifications.clone(),
+        })),
+    );
+    register(bank.slot_status_notifiers(), "metrics", &notifications);
+
+    let slot = bank.advance_slot();
+
+    assert_eq!(
+        *notifications.lock().unwrap(),
+        vec![("geyser", slot), ("metrics", slot)]
+    );
+}
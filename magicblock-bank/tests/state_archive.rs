@@ -0,0 +1,105 @@
+#![cfg(feature = "dev-context-only-utils")]
+
+use std::collections::HashSet;
+
+use magicblock_bank::{
+    bank::Bank, genesis_utils::create_genesis_config_with_leader_and_fees,
+    state_archive::StateArchive,
+};
+use solana_sdk::{
+    account::{Account, ReadableAccount},
+    pubkey::Pubkey,
+    sysvar::clock,
+};
+use tempfile::TempDir;
+
+fn bank_for_tests() -> Bank {
+    let genesis_config_info = create_genesis_config_with_leader_and_fees(
+        u64::MAX,
+        &Pubkey::new_unique(),
+    );
+    Bank::new_for_tests(&genesis_config_info.genesis_config, None, None)
+}
+
+fn store_owned_account(bank: &Bank, owner: &Pubkey, lamports: u64) -> Pubkey {
+    let pubkey = Pubkey::new_unique();
+    bank.store_account(
+        &pubkey,
+        &Account {
+            lamports,
+            data: vec![1, 2, 3],
+            owner: *owner,
+            executable: false,
+            rent_epoch: u64::MAX,
+        },
+    );
+    pubkey
+}
+
+#[test]
+fn test_export_state_and_select_accounts_to_import() {
+    let owner = Pubkey::new_unique();
+    let source = bank_for_tests();
+    let first = store_owned_account(&source, &owner, 1_000);
+    let second = store_owned_account(&source, &owner, 2_000);
+    let faucet = store_owned_account(&source, &owner, 3_000);
+
+    let target = bank_for_tests();
+    let identity = target.get_identity();
+    source.store_account(
+        &identity,
+        &Account {
+            lamports: 4_000,
+            ..Account::default()
+        },
+    );
+
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("state.bin");
+    let archive = StateArchive::from_bank(&source, None);
+    archive.write_to_file(&path).unwrap();
+
+    let archive = StateArchive::read_from_file(&path).unwrap();
+    let accounts = archive.accounts_to_import(&target, &[faucet]);
+    for (pubkey, lamports) in [(first, 1_000), (second, 2_000)] {
+        let (_, account) = accounts
+            .iter()
+            .find(|(imported, _)| imported == &pubkey)
+            .unwrap();
+        assert_eq!(account.lamports(), lamports);
+        assert_eq!(account.owner(), &owner);
+        assert_eq!(account.data(), &[1, 2, 3]);
+    }
+
+    // The identity and faucet of the importing validator are kept
+    assert!(accounts
+        .iter()
+        .all(|(pubkey, _)| pubkey != &identity && pubkey != &faucet));
+}
+
+#[test]
+fn test_export_state_filtered_by_owner() {
+    let owner = Pubkey::new_unique();
+    let bank = bank_for_tests();
+    let owned = store_owned_account(&bank, &owner, 1_000);
+    store_owned_account(&bank, &Pubkey::new_unique(), 1_000);
+
+    let archive = StateArchive::from_bank(&bank, Some(&HashSet::from([owner])));
+    let pubkeys = archive
+        .accounts
+        .iter()
+        .map(|(pubkey, _)| *pubkey)
+        .collect::<Vec<_>>();
+    assert_eq!(pubkeys, vec![owned]);
+}
+
+#[test]
+fn test_export_state_excludes_sysvars() {
+    let bank = bank_for_tests();
+    assert!(bank.get_account(&clock::ID).is_some());
+    let archive = StateArchive::from_bank(&bank, None);
+    assert!(archive
+        .accounts
+        .iter()
+        .all(|(pubkey, _)| pubkey != &clock::ID));
+}
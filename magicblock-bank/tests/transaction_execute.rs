@@ -1,12 +1,15 @@
 #![cfg(feature = "dev-context-only-utils")]
 
+use std::{collections::HashSet, time::Duration};
+
 use assert_matches::assert_matches;
 use magicblock_bank::{
     bank::Bank,
     bank_dev_utils::{
         elfs::{self, add_elf_program},
         transactions::{
-            create_noop_transaction, create_solx_send_post_transaction,
+            create_funded_account, create_noop_transaction,
+            create_solx_send_post_transaction,
             create_system_allocate_transaction,
             create_system_transfer_transaction,
             create_sysvars_from_account_transaction,
@@ -14,14 +17,27 @@ use magicblock_bank::{
             SolanaxPostAccounts,
         },
     },
-    genesis_utils::create_genesis_config_with_leader_and_fees,
+    fee_payer_spend::FeePayerSpendLimit,
+    gasless::GaslessConfig,
+    genesis_utils::{
+        create_genesis_config_with_leader,
+        create_genesis_config_with_leader_and_fees,
+    },
     transaction_results::TransactionBalancesSet,
     LAMPORTS_PER_SIGNATURE,
 };
 use solana_sdk::{
-    account::ReadableAccount, genesis_config::create_genesis_config,
-    hash::Hash, native_token::LAMPORTS_PER_SOL, pubkey::Pubkey, rent::Rent,
-    transaction::SanitizedTransaction,
+    account::{AccountSharedData, ReadableAccount},
+    compute_budget::ComputeBudgetInstruction,
+    fee_calculator::FeeRateGovernor,
+    genesis_config::create_genesis_config,
+    hash::Hash,
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    rent::Rent,
+    signer::Signer,
+    system_instruction, system_program, system_transaction,
+    transaction::{SanitizedTransaction, Transaction},
 };
 use test_tools_core::init_logger;
 
@@ -77,6 +93,173 @@ fn test_bank_system_transfer_instruction() {
     );
 }
 
+#[test]
+fn test_bank_journals_writes_to_journaled_accounts() {
+    init_logger!();
+
+    let genesis_config_info = create_genesis_config_with_leader_and_fees(
+        u64::MAX,
+        &Pubkey::new_unique(),
+    );
+    let bank =
+        Bank::new_for_tests(&genesis_config_info.genesis_config, None, None);
+
+    let (tx, _, to) = create_system_transfer_transaction(
+        &bank,
+        LAMPORTS_PER_SOL,
+        LAMPORTS_PER_SOL / 5,
+    );
+    let signature = *tx.signature();
+    bank.set_account_journaled(&to, true);
+    execute_transactions(&bank, vec![tx]);
+
+    // Only the write to the journaled account is recorded
+    let entries = bank.take_account_journal_entries();
+    assert_eq!(entries.len(), 1);
+    let entry = &entries[0];
+    assert_eq!(entry.pubkey, to);
+    assert_eq!(entry.slot, bank.slot());
+    assert_eq!(entry.signature, signature);
+    assert_eq!(entry.writer_program, system_program::id());
+    assert_eq!(entry.lamports, LAMPORTS_PER_SOL / 5);
+    assert_eq!((entry.data_start, entry.data_end), (0, 0));
+
+    // Entries are taken only once
+    assert!(bank.take_account_journal_entries().is_empty());
+}
+
+#[test]
+fn test_bank_tracks_dirty_accounts_only_once_enabled() {
+    init_logger!();
+
+    let genesis_config_info = create_genesis_config_with_leader_and_fees(
+        u64::MAX,
+        &Pubkey::new_unique(),
+    );
+    let bank =
+        Bank::new_for_tests(&genesis_config_info.genesis_config, None, None);
+
+    // Nothing takes the dirty accounts unless enabled, so they aren't tracked
+    let (tx, _, _) = create_system_transfer_transaction(
+        &bank,
+        LAMPORTS_PER_SOL,
+        LAMPORTS_PER_SOL / 5,
+    );
+    execute_transactions(&bank, vec![tx]);
+    assert!(bank.take_dirty_accounts().is_empty());
+
+    bank.enable_dirty_accounts_tracking();
+    let (tx, from, to) = create_system_transfer_transaction(
+        &bank,
+        LAMPORTS_PER_SOL,
+        LAMPORTS_PER_SOL / 5,
+    );
+    execute_transactions(&bank, vec![tx]);
+    assert_eq!(bank.take_dirty_accounts(), HashSet::from([from, to]));
+    assert!(bank.take_dirty_accounts().is_empty());
+}
+
+#[test]
+fn test_bank_tracks_fee_payer_spend() {
+    init_logger!();
+
+    let genesis_config_info = create_genesis_config_with_leader_and_fees(
+        u64::MAX,
+        &Pubkey::new_unique(),
+    );
+    let bank =
+        Bank::new_for_tests(&genesis_config_info.genesis_config, None, None);
+    bank.set_fee_payer_spend_limit(Some(FeePayerSpendLimit {
+        max_lamports: LAMPORTS_PER_SOL / 10,
+        window: Duration::from_secs(60),
+    }));
+
+    let (tx, from, to) = create_system_transfer_transaction(
+        &bank,
+        LAMPORTS_PER_SOL,
+        LAMPORTS_PER_SOL / 5,
+    );
+    assert!(!bank.is_fee_payer_spend_limit_exceeded(&from));
+    let (results, _) = execute_transactions(&bank, vec![tx]);
+    assert_matches!(
+        results.execution_results[0].details().unwrap().status,
+        Ok(())
+    );
+
+    // The transfer and fee count towards the spend of the fee payer only
+    assert!(bank.is_fee_payer_spend_limit_exceeded(&from));
+    assert!(!bank.is_fee_payer_spend_limit_exceeded(&to));
+
+    bank.set_fee_payer_spend_limit(None);
+    assert!(!bank.is_fee_payer_spend_limit_exceeded(&from));
+}
+
+#[test]
+fn test_bank_sponsors_fees_of_gasless_transactions() {
+    init_logger!();
+
+    let genesis_config_info = create_genesis_config_with_leader_and_fees(
+        u64::MAX,
+        &Pubkey::new_unique(),
+    );
+    let bank =
+        Bank::new_for_tests(&genesis_config_info.genesis_config, None, None);
+    let identity = bank.get_identity();
+    bank.store_account(
+        &identity,
+        &AccountSharedData::new(LAMPORTS_PER_SOL, 0, &system_program::id()),
+    );
+    bank.set_gasless_config(Some(GaslessConfig {
+        sponsored_programs: HashSet::from([system_program::id()]),
+        quota: Some(FeePayerSpendLimit {
+            max_lamports: LAMPORTS_PER_SIGNATURE,
+            window: Duration::from_secs(60),
+        }),
+    }));
+
+    // The first transfer is sponsored by the validator identity
+    let from = create_funded_account(&bank, Some(LAMPORTS_PER_SOL));
+    let transfer = |lamports: u64| {
+        SanitizedTransaction::from_transaction_for_tests(
+            system_transaction::transfer(
+                &from,
+                &Pubkey::new_unique(),
+                lamports,
+                bank.last_blockhash(),
+            ),
+        )
+    };
+    let (results, _) = execute_transactions(&bank, vec![transfer(100)]);
+    assert_matches!(
+        results.execution_results[0].details().unwrap().status,
+        Ok(())
+    );
+    assert_eq!(bank.get_balance(&from.pubkey()), LAMPORTS_PER_SOL - 100);
+    assert_eq!(
+        bank.get_balance(&identity),
+        LAMPORTS_PER_SOL - LAMPORTS_PER_SIGNATURE
+    );
+    assert_eq!(
+        bank.sponsored_lamports(&from.pubkey()),
+        LAMPORTS_PER_SIGNATURE
+    );
+
+    // Once its quota is used up the fee payer pays its fees itself
+    let (results, _) = execute_transactions(&bank, vec![transfer(200)]);
+    assert_matches!(
+        results.execution_results[0].details().unwrap().status,
+        Ok(())
+    );
+    assert_eq!(
+        bank.get_balance(&from.pubkey()),
+        LAMPORTS_PER_SOL - 300 - LAMPORTS_PER_SIGNATURE
+    );
+    assert_eq!(
+        bank.get_balance(&identity),
+        LAMPORTS_PER_SOL - LAMPORTS_PER_SIGNATURE
+    );
+}
+
 #[test]
 fn test_bank_system_allocate_instruction() {
     init_logger!();
@@ -243,3 +426,72 @@ fn test_bank_sysvars_from_account() {
     bank.advance_slot();
     execute_and_check_results(&bank, tx);
 }
+
+/// Executes a transfer paying the [compute_unit_price] for 200k compute units
+/// and returns the fee the payer was charged and the fee quoted for it.
+fn transfer_fee_with_lamports_per_signature(
+    lamports_per_signature: u64,
+    compute_unit_price: u64,
+) -> (u64, Option<u64>) {
+    let mut genesis_config_info =
+        create_genesis_config_with_leader(u64::MAX, &Pubkey::new_unique());
+    genesis_config_info.genesis_config.fee_rate_governor =
+        FeeRateGovernor::new(lamports_per_signature, 0);
+    let bank =
+        Bank::new_for_tests(&genesis_config_info.genesis_config, None, None);
+
+    let from = create_funded_account(&bank, Some(LAMPORTS_PER_SOL));
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            ComputeBudgetInstruction::set_compute_unit_limit(200_000),
+            ComputeBudgetInstruction::set_compute_unit_price(
+                compute_unit_price,
+            ),
+            system_instruction::transfer(
+                &from.pubkey(),
+                &Pubkey::new_unique(),
+                1,
+            ),
+        ],
+        Some(&from.pubkey()),
+        &[&from],
+        bank.last_blockhash(),
+    );
+    let tx = SanitizedTransaction::from_transaction_for_tests(tx);
+    let quoted_fee = bank.get_fee_for_message(tx.message());
+    let (results, _) = execute_transactions(&bank, vec![tx]);
+    assert_matches!(
+        results.execution_results[0].details().unwrap().status,
+        Ok(())
+    );
+
+    let charged_fee = LAMPORTS_PER_SOL - 1 - bank.get_balance(&from.pubkey());
+    (charged_fee, quoted_fee)
+}
+
+#[test]
+fn test_bank_zero_fees() {
+    init_logger!();
+
+    assert_eq!(transfer_fee_with_lamports_per_signature(0, 0), (0, Some(0)));
+    // Prioritization fees are waived as well
+    assert_eq!(
+        transfer_fee_with_lamports_per_signature(0, 1_000),
+        (0, Some(0))
+    );
+}
+
+#[test]
+fn test_bank_fixed_fees() {
+    init_logger!();
+
+    assert_eq!(
+        transfer_fee_with_lamports_per_signature(7_000, 0),
+        (7_000, Some(7_000))
+    );
+    // 200k compute units at 1_000 micro-lamports each add 200 lamports
+    assert_eq!(
+        transfer_fee_with_lamports_per_signature(7_000, 1_000),
+        (7_200, Some(7_200))
+    );
+}
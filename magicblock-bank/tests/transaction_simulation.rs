@@ -0,0 +1,77 @@
+#![cfg(feature = "dev-context-only-utils")]
+
+use std::collections::HashMap;
+
+use magicblock_bank::{
+    bank::Bank, transaction_simulation::TransactionSimulationResult,
+};
+use solana_sdk::{
+    account::{AccountSharedData, ReadableAccount},
+    genesis_config::create_genesis_config,
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    signature::Keypair,
+    signer::Signer,
+    system_program, system_transaction,
+    transaction::SanitizedTransaction,
+};
+use test_tools_core::init_logger;
+
+fn transfer(
+    bank: &Bank,
+    from: &Keypair,
+    to: &Pubkey,
+    lamports: u64,
+) -> SanitizedTransaction {
+    SanitizedTransaction::from_transaction_for_tests(
+        system_transaction::transfer(from, to, lamports, bank.last_blockhash()),
+    )
+}
+
+#[test]
+fn test_simulation_sees_state_overrides_without_committing_them() {
+    init_logger!();
+
+    let (genesis_config, _) = create_genesis_config(u64::MAX);
+    let bank = Bank::new_for_tests(&genesis_config, None, None);
+
+    // Alice only has lamports inside the simulation
+    let alice = Keypair::new();
+    let bob = Pubkey::new_unique();
+    let tx = transfer(&bank, &alice, &bob, LAMPORTS_PER_SOL / 2);
+
+    let TransactionSimulationResult { result, .. } =
+        bank.simulate_transaction_unchecked(&tx, false);
+    assert!(result.is_err());
+
+    let state_overrides = HashMap::from([(
+        alice.pubkey(),
+        AccountSharedData::new(LAMPORTS_PER_SOL, 0, &system_program::id()),
+    )]);
+    let TransactionSimulationResult {
+        result,
+        post_simulation_accounts,
+        ..
+    } = bank.simulate_transaction_with_state_overrides_unchecked(
+        &tx,
+        false,
+        &state_overrides,
+    );
+    assert_eq!(result, Ok(()));
+
+    let post_simulation_accounts = post_simulation_accounts
+        .into_iter()
+        .collect::<HashMap<_, _>>();
+    assert_eq!(
+        post_simulation_accounts[&bob].lamports(),
+        LAMPORTS_PER_SOL / 2
+    );
+    assert!(
+        post_simulation_accounts[&alice.pubkey()].lamports()
+            <= LAMPORTS_PER_SOL / 2
+    );
+
+    // Nothing is committed while simulating
+    assert_eq!(bank.get_balance(&alice.pubkey()), 0);
+    assert_eq!(bank.get_balance(&bob), 0);
+}
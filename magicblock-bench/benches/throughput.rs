@@ -0,0 +1,99 @@
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+use criterion::{
+    criterion_group, criterion_main, BenchmarkId, Criterion, Throughput,
+};
+use magicblock_bench::BenchValidator;
+use solana_sdk::{native_token::LAMPORTS_PER_SOL, pubkey::Pubkey};
+use tokio::runtime::Runtime;
+
+const TRANSFERS_PER_ITER: usize = 64;
+
+fn start_validator(runtime: &Runtime) -> BenchValidator {
+    runtime.block_on(async { BenchValidator::start(Duration::ZERO) })
+}
+
+fn bench_clone(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let validator = start_validator(&runtime);
+
+    let mut group = c.benchmark_group("clone");
+    for delegated in [false, true] {
+        let name = if delegated {
+            "delegated"
+        } else {
+            "undelegated"
+        };
+        group.bench_function(name, |b| {
+            b.to_async(&runtime).iter(|| async {
+                let pubkey = validator.create_chain_account(delegated);
+                validator.clone_account(&pubkey).await.unwrap();
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bench_execute(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let validator = start_validator(&runtime);
+    let payers = validator.fund_keypairs(TRANSFERS_PER_ITER);
+    let shared_account = Pubkey::new_unique();
+    let transfer_idx = AtomicU64::new(0);
+
+    let mut group = c.benchmark_group("execute_transfers");
+    group.throughput(Throughput::Elements(TRANSFERS_PER_ITER as u64));
+    // Percentage of the transfers of an iteration writing to the same account
+    for overlap in [0_usize, 50, 100] {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(format!("{}%_overlap", overlap)),
+            &overlap,
+            |b, overlap| {
+                b.iter(|| {
+                    for (idx, payer) in payers.iter().enumerate() {
+                        let to = if idx * 100 < *overlap * TRANSFERS_PER_ITER {
+                            shared_account
+                        } else {
+                            Pubkey::new_unique()
+                        };
+                        let lamports = LAMPORTS_PER_SOL / 1_000
+                            + transfer_idx.fetch_add(1, Ordering::Relaxed);
+                        validator.transfer(payer, &to, lamports).unwrap();
+                    }
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+fn bench_commit(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let validator = start_validator(&runtime);
+
+    let mut group = c.benchmark_group("commit");
+    for accounts_per_commit in [1, 4, 8] {
+        let accounts =
+            validator.create_commitable_accounts(accounts_per_commit, 256);
+        group.throughput(Throughput::Elements(accounts_per_commit as u64));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(format!(
+                "{}_accounts",
+                accounts_per_commit
+            )),
+            &accounts,
+            |b, accounts| {
+                b.to_async(&runtime).iter(|| async {
+                    validator.commit(accounts).await.unwrap()
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_clone, bench_execute, bench_commit);
+criterion_main!(benches);
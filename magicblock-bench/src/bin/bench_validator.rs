@@ -0,0 +1,75 @@
+use std::{env, process, str::FromStr, sync::Arc, time::Duration};
+
+use magicblock_bench::{run_workloads, BenchValidator, WorkloadConfig};
+
+/// Drives synthetic clone, transaction and commit workloads against an
+/// in-process validator and prints throughput and latency percentiles.
+///
+/// Each setting of [WorkloadConfig] can be overridden via an env var,
+/// i.e. `BENCH_TRANSACTIONS_PER_SEC=5000 BENCH_WRITE_SET_OVERLAP=0.5`.
+/// If `BENCH_MAX_P99_MS` is set, the process fails when the p99 latency of
+/// any workload exceeds it.
+#[tokio::main]
+async fn main() {
+    let config = config_from_envs();
+    let max_p99 = env_var::<u64>("BENCH_MAX_P99_MS").map(Duration::from_millis);
+    println!("Running workloads with {:#?}", config);
+
+    let validator = Arc::new(BenchValidator::start(config.chain_latency));
+    let reports = run_workloads(validator.clone(), &config).await;
+    if let Some(validator) = Arc::into_inner(validator) {
+        validator.shutdown().await;
+    }
+
+    for report in &reports {
+        println!("{}", report);
+    }
+    if let Some(max_p99) = max_p99 {
+        let too_slow = reports
+            .iter()
+            .filter(|report| report.p99 > max_p99)
+            .map(|report| report.name.as_str())
+            .collect::<Vec<_>>();
+        if !too_slow.is_empty() {
+            eprintln!("p99 latency of {:?} exceeds {:?}", too_slow, max_p99);
+            process::exit(1);
+        }
+    }
+}
+
+fn config_from_envs() -> WorkloadConfig {
+    let default = WorkloadConfig::default();
+    WorkloadConfig {
+        duration: env_var("BENCH_DURATION_SECS")
+            .map(Duration::from_secs)
+            .unwrap_or(default.duration),
+        clones_per_sec: env_var("BENCH_CLONES_PER_SEC")
+            .unwrap_or(default.clones_per_sec),
+        transactions_per_sec: env_var("BENCH_TRANSACTIONS_PER_SEC")
+            .unwrap_or(default.transactions_per_sec),
+        write_set_overlap: env_var("BENCH_WRITE_SET_OVERLAP")
+            .unwrap_or(default.write_set_overlap),
+        payers: env_var("BENCH_PAYERS").unwrap_or(default.payers),
+        commits_per_sec: env_var("BENCH_COMMITS_PER_SEC")
+            .unwrap_or(default.commits_per_sec),
+        accounts_per_commit: env_var("BENCH_ACCOUNTS_PER_COMMIT")
+            .unwrap_or(default.accounts_per_commit),
+        commit_account_data_len: env_var("BENCH_COMMIT_ACCOUNT_DATA_LEN")
+            .unwrap_or(default.commit_account_data_len),
+        chain_latency: env_var("BENCH_CHAIN_LATENCY_MS")
+            .map(Duration::from_millis)
+            .unwrap_or(default.chain_latency),
+    }
+}
+
+fn env_var<T>(name: &str) -> Option<T>
+where
+    T: FromStr,
+    T::Err: std::fmt::Debug,
+{
+    env::var(name).ok().map(|value| {
+        value.parse().unwrap_or_else(|err| {
+            panic!("Failed to parse '{}' ({}): {:?}", name, value, err)
+        })
+    })
+}
@@ -0,0 +1,135 @@
+use std::{collections::HashSet, sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use dlp::instruction::{commit_state, finalize, CommitAccountArgs};
+use magicblock_accounts::{
+    errors::AccountsResult, AccountCommittee, AccountCommitter,
+    CommitAccountsPayload, CommitAccountsTransaction, CommitProof,
+    PendingCommitTransaction, SendableCommitAccountsPayload,
+};
+use magicblock_metrics::metrics;
+use magicblock_program::ValidatorContext;
+use solana_sdk::{
+    account::ReadableAccount, hash::Hash, signature::Signature, signer::Signer,
+    transaction::Transaction,
+};
+
+/// Builds the same commit transactions as the committer used by the
+/// validator, but instead of sending them to chain it waits for the
+/// configured [chain_latency] before considering them sent and confirmed.
+pub struct SimulatedCommitter {
+    validator_context: Arc<ValidatorContext>,
+    chain_latency: Duration,
+}
+
+impl SimulatedCommitter {
+    pub fn new(
+        validator_context: Arc<ValidatorContext>,
+        chain_latency: Duration,
+    ) -> Self {
+        Self {
+            validator_context,
+            chain_latency,
+        }
+    }
+}
+
+#[async_trait]
+impl AccountCommitter for SimulatedCommitter {
+    async fn create_commit_accounts_transaction(
+        &self,
+        committees: Vec<AccountCommittee>,
+    ) -> AccountsResult<CommitAccountsPayload> {
+        let committer_authority = self.validator_context.validator_authority();
+        let committer = committer_authority.pubkey();
+
+        let mut ixs = vec![];
+        for AccountCommittee {
+            pubkey,
+            account_data,
+            slot,
+            ..
+        } in committees.iter()
+        {
+            let commit_args = CommitAccountArgs {
+                slot: *slot,
+                allow_undelegation: false,
+                data: account_data.data().to_vec(),
+            };
+            ixs.push(commit_state(committer, *pubkey, commit_args));
+            ixs.push(
+                CommitProof::new(*pubkey, *slot, account_data.data())
+                    .to_instruction(),
+            );
+            ixs.push(finalize(committer, *pubkey, committer));
+        }
+        let transaction = Transaction::new_signed_with_payer(
+            &ixs,
+            Some(&committer),
+            &[&committer_authority],
+            Hash::new_unique(),
+        );
+        let committed_only_accounts =
+            committees.iter().map(|c| c.pubkey).collect();
+        let committees = committees
+            .into_iter()
+            .map(|c| (c.pubkey, c.account_data))
+            .collect();
+
+        Ok(CommitAccountsPayload {
+            transaction: Some(CommitAccountsTransaction {
+                transaction,
+                undelegated_accounts: HashSet::new(),
+                committed_only_accounts,
+                fee_lamports: 0,
+            }),
+            committees,
+        })
+    }
+
+    async fn resign_commit_transactions(
+        &self,
+        payloads: &mut [SendableCommitAccountsPayload],
+    ) -> AccountsResult<()> {
+        let committer_authority = self.validator_context.validator_authority();
+        for payload in payloads.iter_mut() {
+            payload
+                .transaction
+                .transaction
+                .sign(&[&committer_authority], Hash::new_unique());
+        }
+        Ok(())
+    }
+
+    async fn send_commit_transactions(
+        &self,
+        payloads: Vec<SendableCommitAccountsPayload>,
+    ) -> AccountsResult<Vec<PendingCommitTransaction>> {
+        tokio::time::sleep(self.chain_latency / 2).await;
+        Ok(payloads
+            .into_iter()
+            .map(|payload| PendingCommitTransaction {
+                signature: payload.get_signature(),
+                undelegated_accounts: payload.transaction.undelegated_accounts,
+                committed_only_accounts: payload
+                    .transaction
+                    .committed_only_accounts,
+                timer: metrics::account_commit_start(),
+            })
+            .collect())
+    }
+
+    async fn confirm_pending_commits(
+        &self,
+        pending_commits: Vec<PendingCommitTransaction>,
+    ) -> Vec<(Signature, bool)> {
+        tokio::time::sleep(self.chain_latency / 2).await;
+        pending_commits
+            .into_iter()
+            .map(|pending| {
+                metrics::account_commit_end(pending.timer);
+                (pending.signature, true)
+            })
+            .collect()
+    }
+}
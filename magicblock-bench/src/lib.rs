@@ -0,0 +1,8 @@
+pub mod committer;
+pub mod stats;
+pub mod validator;
+pub mod workload;
+
+pub use stats::WorkloadReport;
+pub use validator::BenchValidator;
+pub use workload::{run_workloads, WorkloadConfig};
@@ -0,0 +1,114 @@
+use std::{fmt, time::Duration};
+
+/// Collects the latency of every operation of a workload.
+#[derive(Debug, Default)]
+pub struct LatencyRecorder {
+    latencies: Vec<Duration>,
+    failed: u64,
+}
+
+impl LatencyRecorder {
+    pub fn record(&mut self, latency: Duration) {
+        self.latencies.push(latency);
+    }
+
+    pub fn record_failure(&mut self) {
+        self.failed += 1;
+    }
+
+    pub fn report(mut self, name: &str, elapsed: Duration) -> WorkloadReport {
+        self.latencies.sort_unstable();
+        let latencies = &self.latencies;
+        WorkloadReport {
+            name: name.to_string(),
+            completed: latencies.len() as u64,
+            failed: self.failed,
+            elapsed,
+            p50: percentile(latencies, 50.0),
+            p90: percentile(latencies, 90.0),
+            p99: percentile(latencies, 99.0),
+            max: latencies.last().copied().unwrap_or_default(),
+        }
+    }
+}
+
+/// Nearest-rank percentile of the [sorted] latencies.
+fn percentile(sorted: &[Duration], percentile: f64) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+    let rank = (percentile / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+#[derive(Debug, Clone)]
+pub struct WorkloadReport {
+    pub name: String,
+    /// Operations that completed successfully.
+    pub completed: u64,
+    pub failed: u64,
+    /// Time it took to issue and complete all operations.
+    pub elapsed: Duration,
+    pub p50: Duration,
+    pub p90: Duration,
+    pub p99: Duration,
+    pub max: Duration,
+}
+
+impl WorkloadReport {
+    /// Successfully completed operations per second.
+    pub fn throughput(&self) -> f64 {
+        if self.elapsed.is_zero() {
+            return 0.0;
+        }
+        self.completed as f64 / self.elapsed.as_secs_f64()
+    }
+}
+
+impl fmt::Display for WorkloadReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:<12} {:>8} ok {:>6} failed {:>10.1}/s  p50 {:>9.3?}  p90 {:>9.3?}  p99 {:>9.3?}  max {:>9.3?}",
+            self.name,
+            self.completed,
+            self.failed,
+            self.throughput(),
+            self.p50,
+            self.p90,
+            self.p99,
+            self.max
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_percentiles() {
+        let mut recorder = LatencyRecorder::default();
+        for millis in (1..=100).rev() {
+            recorder.record(Duration::from_millis(millis));
+        }
+        recorder.record_failure();
+
+        let report = recorder.report("test", Duration::from_secs(2));
+        assert_eq!(report.completed, 100);
+        assert_eq!(report.failed, 1);
+        assert_eq!(report.p50, Duration::from_millis(50));
+        assert_eq!(report.p90, Duration::from_millis(90));
+        assert_eq!(report.p99, Duration::from_millis(99));
+        assert_eq!(report.max, Duration::from_millis(100));
+        assert_eq!(report.throughput(), 50.0);
+    }
+
+    #[test]
+    fn test_report_without_operations() {
+        let report = LatencyRecorder::default().report("test", Duration::ZERO);
+        assert_eq!(report.completed, 0);
+        assert_eq!(report.p99, Duration::ZERO);
+        assert_eq!(report.throughput(), 0.0);
+    }
+}
@@ -0,0 +1,228 @@
+use std::{collections::HashSet, sync::Arc, time::Duration};
+
+use magicblock_account_cloner::{
+    AccountCloner, AccountClonerOutput, AccountClonerPermissions,
+    AccountClonerResult, ClonePolicy, RefreshPolicy, RemoteAccountClonerClient,
+    RemoteAccountClonerWorker,
+};
+use magicblock_account_dumper::AccountDumperBank;
+use magicblock_account_fetcher::AccountFetcherStub;
+use magicblock_account_updates::AccountUpdatesStub;
+use magicblock_accounts::{
+    errors::AccountsResult, AccountCommittee, AccountCommitter,
+    SendableCommitAccountsPayload,
+};
+use magicblock_accounts_api::BankAccountProvider;
+use magicblock_bank::bank::Bank;
+use magicblock_processor::execute_transaction::execute_legacy_transaction;
+use solana_sdk::{
+    account::{AccountSharedData, WritableAccount},
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature},
+    signer::Signer,
+    system_program, system_transaction, transaction,
+};
+use test_tools::{
+    account::fund_account, validator::bank_with_started_validator,
+};
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+use crate::committer::SimulatedCommitter;
+
+/// Slot at which all accounts exist on the simulated chain.
+const CHAIN_SLOT: u64 = 1;
+
+/// Bank, account cloner and committer of an ephemeral validator running
+/// in-process.
+/// Cloned accounts are fetched from a simulated chain and dumped into the
+/// bank exactly like the validator does it, commits are built from the
+/// bank's state and sent to the simulated chain.
+pub struct BenchValidator {
+    bank: Arc<Bank>,
+    cloner: RemoteAccountClonerClient,
+    account_fetcher: AccountFetcherStub,
+    account_updates: AccountUpdatesStub,
+    committer: SimulatedCommitter,
+    cancellation_token: CancellationToken,
+    cloner_worker_handle: JoinHandle<()>,
+}
+
+impl BenchValidator {
+    /// Needs to be called from within a tokio runtime since the cloner
+    /// worker is spawned onto it.
+    pub fn start(chain_latency: Duration) -> Self {
+        let bank = bank_with_started_validator();
+
+        let account_fetcher = AccountFetcherStub::default();
+        let account_updates = AccountUpdatesStub::default();
+        let mut cloner_worker = RemoteAccountClonerWorker::new(
+            BankAccountProvider::new(bank.clone()),
+            account_fetcher.clone(),
+            account_updates.clone(),
+            AccountDumperBank::new(bank.clone(), None),
+            None,
+            HashSet::new(),
+            Some(LAMPORTS_PER_SOL),
+            AccountClonerPermissions {
+                clone: ClonePolicy::all(),
+                refresh: RefreshPolicy::Websocket,
+            },
+            Pubkey::new_unique(),
+        );
+        let cloner = RemoteAccountClonerClient::new(&cloner_worker);
+        let cancellation_token = CancellationToken::new();
+        let cloner_worker_handle = {
+            let cancellation_token = cancellation_token.clone();
+            tokio::spawn(async move {
+                cloner_worker
+                    .start_clone_request_processing(cancellation_token)
+                    .await
+            })
+        };
+
+        let committer = SimulatedCommitter::new(
+            bank.validator_context().clone(),
+            chain_latency,
+        );
+        Self {
+            bank,
+            cloner,
+            account_fetcher,
+            account_updates,
+            committer,
+            cancellation_token,
+            cloner_worker_handle,
+        }
+    }
+
+    pub fn bank(&self) -> &Arc<Bank> {
+        &self.bank
+    }
+
+    // -----------------
+    // Clone
+    // -----------------
+    /// Creates a new account on the simulated chain which the validator
+    /// did not clone yet.
+    pub fn create_chain_account(&self, delegated: bool) -> Pubkey {
+        let pubkey = Pubkey::new_unique();
+        self.account_updates
+            .set_first_subscribed_slot(pubkey, CHAIN_SLOT);
+        if delegated {
+            self.account_fetcher
+                .set_delegated_account(pubkey, CHAIN_SLOT, CHAIN_SLOT);
+        } else {
+            self.account_fetcher
+                .set_undelegated_account(pubkey, CHAIN_SLOT);
+        }
+        pubkey
+    }
+
+    pub async fn clone_account(
+        &self,
+        pubkey: &Pubkey,
+    ) -> AccountClonerResult<AccountClonerOutput> {
+        self.cloner.clone_account(pubkey).await
+    }
+
+    // -----------------
+    // Execute
+    // -----------------
+    pub fn fund_keypairs(&self, count: usize) -> Vec<Keypair> {
+        (0..count)
+            .map(|_| {
+                let keypair = Keypair::new();
+                fund_account(
+                    &self.bank,
+                    &keypair.pubkey(),
+                    1_000 * LAMPORTS_PER_SOL,
+                );
+                keypair
+            })
+            .collect()
+    }
+
+    /// Executes a transfer and waits for it to be committed to the bank.
+    /// The [lamports] need to differ between transfers with the same
+    /// accounts, otherwise they are rejected as duplicates.
+    pub fn transfer(
+        &self,
+        from: &Keypair,
+        to: &Pubkey,
+        lamports: u64,
+    ) -> transaction::Result<Signature> {
+        let tx = system_transaction::transfer(
+            from,
+            to,
+            lamports,
+            self.bank.last_blockhash(),
+        );
+        execute_legacy_transaction(tx, &self.bank, None)
+    }
+
+    // -----------------
+    // Commit
+    // -----------------
+    /// Stores accounts with the provided data size which can be committed.
+    pub fn create_commitable_accounts(
+        &self,
+        count: usize,
+        data_len: usize,
+    ) -> Vec<Pubkey> {
+        (0..count)
+            .map(|_| {
+                let pubkey = Pubkey::new_unique();
+                let mut account = AccountSharedData::new(
+                    LAMPORTS_PER_SOL,
+                    data_len,
+                    &system_program::id(),
+                );
+                account.data_as_mut_slice().fill(1);
+                self.bank.store_account(&pubkey, &account);
+                pubkey
+            })
+            .collect()
+    }
+
+    /// Commits the current state of the accounts in a single transaction and
+    /// waits for it to be confirmed.
+    pub async fn commit(&self, pubkeys: &[Pubkey]) -> AccountsResult<()> {
+        let slot = self.bank.slot();
+        let committees = pubkeys
+            .iter()
+            .map(|pubkey| AccountCommittee {
+                pubkey: *pubkey,
+                account_data: self.bank.get_account(pubkey).unwrap_or_else(
+                    || panic!("Account '{}' to commit does not exist", pubkey),
+                ),
+                slot,
+                undelegation_request: None,
+            })
+            .collect::<Vec<_>>();
+        let payload = self
+            .committer
+            .create_commit_accounts_transaction(committees)
+            .await?;
+        let Some(transaction) = payload.transaction else {
+            return Ok(());
+        };
+        let pending_commits = self
+            .committer
+            .send_commit_transactions(vec![SendableCommitAccountsPayload {
+                transaction,
+                committees: payload.committees,
+            }])
+            .await?;
+        self.committer
+            .confirm_pending_commits(pending_commits)
+            .await;
+        Ok(())
+    }
+
+    pub async fn shutdown(self) {
+        self.cancellation_token.cancel();
+        let _ = self.cloner_worker_handle.await;
+    }
+}
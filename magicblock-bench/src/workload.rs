@@ -0,0 +1,190 @@
+use std::{
+    future::Future,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use magicblock_core::robust_lock::RobustMutex;
+use rand::Rng;
+use solana_sdk::{native_token::LAMPORTS_PER_SOL, pubkey::Pubkey};
+use tokio::time::MissedTickBehavior;
+
+use crate::{
+    stats::{LatencyRecorder, WorkloadReport},
+    validator::BenchValidator,
+};
+
+/// Enough for the accounts created by a transfer to be rent exempt.
+const TRANSFER_LAMPORTS: u64 = LAMPORTS_PER_SOL / 1_000;
+
+#[derive(Debug, Clone)]
+pub struct WorkloadConfig {
+    /// How long each workload issues operations.
+    pub duration: Duration,
+    /// Accounts cloned per second, every other one is delegated.
+    pub clones_per_sec: u64,
+    pub transactions_per_sec: u64,
+    /// Share of transactions in `[0.0, 1.0]` that write to one shared
+    /// account, the others write to an account no other transaction uses.
+    pub write_set_overlap: f64,
+    /// Number of funded fee payers the transactions are spread across.
+    pub payers: usize,
+    pub commits_per_sec: u64,
+    pub accounts_per_commit: usize,
+    pub commit_account_data_len: usize,
+    /// Simulated time it takes the chain to land and confirm a commit.
+    pub chain_latency: Duration,
+}
+
+impl Default for WorkloadConfig {
+    fn default() -> Self {
+        Self {
+            duration: Duration::from_secs(10),
+            clones_per_sec: 100,
+            transactions_per_sec: 1_000,
+            write_set_overlap: 0.1,
+            payers: 64,
+            commits_per_sec: 10,
+            accounts_per_commit: 4,
+            commit_account_data_len: 256,
+            chain_latency: Duration::ZERO,
+        }
+    }
+}
+
+/// Runs the clone, transaction and commit workloads concurrently against the
+/// [validator] and reports each of them separately.
+/// A workload with a rate of `0` is skipped.
+pub async fn run_workloads(
+    validator: Arc<BenchValidator>,
+    config: &WorkloadConfig,
+) -> Vec<WorkloadReport> {
+    let (clones, transactions, commits) = tokio::join!(
+        run_clones(validator.clone(), config),
+        run_transactions(validator.clone(), config),
+        run_commits(validator, config),
+    );
+    [clones, transactions, commits]
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+pub async fn run_clones(
+    validator: Arc<BenchValidator>,
+    config: &WorkloadConfig,
+) -> Option<WorkloadReport> {
+    run_at_rate("clone", config.clones_per_sec, config.duration, |idx| {
+        let validator = validator.clone();
+        async move {
+            let pubkey = validator.create_chain_account(idx % 2 == 1);
+            validator.clone_account(&pubkey).await.is_ok()
+        }
+    })
+    .await
+}
+
+pub async fn run_transactions(
+    validator: Arc<BenchValidator>,
+    config: &WorkloadConfig,
+) -> Option<WorkloadReport> {
+    let payers = Arc::new(validator.fund_keypairs(config.payers.max(1)));
+    let shared_account = Pubkey::new_unique();
+    let write_set_overlap = config.write_set_overlap.clamp(0.0, 1.0);
+    run_at_rate(
+        "transaction",
+        config.transactions_per_sec,
+        config.duration,
+        |idx| {
+            let validator = validator.clone();
+            let payers = payers.clone();
+            let to = if rand::thread_rng().gen_bool(write_set_overlap) {
+                shared_account
+            } else {
+                Pubkey::new_unique()
+            };
+            async move {
+                tokio::task::spawn_blocking(move || {
+                    let from = &payers[idx as usize % payers.len()];
+                    validator
+                        .transfer(from, &to, TRANSFER_LAMPORTS + idx)
+                        .is_ok()
+                })
+                .await
+                .unwrap_or(false)
+            }
+        },
+    )
+    .await
+}
+
+pub async fn run_commits(
+    validator: Arc<BenchValidator>,
+    config: &WorkloadConfig,
+) -> Option<WorkloadReport> {
+    let accounts_per_commit = config.accounts_per_commit.max(1);
+    let accounts = Arc::new(validator.create_commitable_accounts(
+        accounts_per_commit,
+        config.commit_account_data_len,
+    ));
+    run_at_rate("commit", config.commits_per_sec, config.duration, |_| {
+        let validator = validator.clone();
+        let accounts = accounts.clone();
+        async move { validator.commit(&accounts).await.is_ok() }
+    })
+    .await
+}
+
+/// Starts an operation at the given rate until the [duration] passed
+/// regardless of how long previous operations take, i.e. a slow validator
+/// results in growing latencies instead of a lower rate of requests.
+async fn run_at_rate<F, Fut>(
+    name: &str,
+    rate_per_sec: u64,
+    duration: Duration,
+    mut operation: F,
+) -> Option<WorkloadReport>
+where
+    F: FnMut(u64) -> Fut,
+    Fut: Future<Output = bool> + Send + 'static,
+{
+    if rate_per_sec == 0 {
+        return None;
+    }
+    let recorder = Arc::new(Mutex::new(LatencyRecorder::default()));
+    let mut interval = tokio::time::interval(Duration::from_secs_f64(
+        1.0 / rate_per_sec as f64,
+    ));
+    interval.set_missed_tick_behavior(MissedTickBehavior::Burst);
+
+    let started_at = Instant::now();
+    let mut handles = vec![];
+    let mut idx = 0;
+    while started_at.elapsed() < duration {
+        interval.tick().await;
+        let operation = operation(idx);
+        let recorder = recorder.clone();
+        handles.push(tokio::spawn(async move {
+            let operation_started_at = Instant::now();
+            let succeeded = operation.await;
+            let latency = operation_started_at.elapsed();
+            let mut recorder = recorder.lock_robust();
+            if succeeded {
+                recorder.record(latency);
+            } else {
+                recorder.record_failure();
+            }
+        }));
+        idx += 1;
+    }
+    for handle in handles {
+        let _ = handle.await;
+    }
+    let elapsed = started_at.elapsed();
+
+    let recorder = Arc::into_inner(recorder)
+        .expect("All operations completed")
+        .into_inner()
+        .expect("Recorder poisoned");
+    Some(recorder.report(name, elapsed))
+}
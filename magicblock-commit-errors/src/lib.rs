@@ -0,0 +1,76 @@
+use std::fmt;
+
+/// Errors of the security checks performed when scheduling commits, shared
+/// by the MagicBlock program and the programs invoking it.
+/// They are returned as `Custom` program errors with the [Self::code], which
+/// clients convert back via [ScheduleCommitError::from_code] in order to
+/// match them by name.
+/// Codes are stable once released, new ones are only ever added.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u32)]
+pub enum ScheduleCommitError {
+    /// The program which invoked the schedule commit via CPI could not be
+    /// found, i.e. since the MagicBlock program was invoked directly.
+    ParentProgramNotFound = 11_000,
+    /// A committed account is neither owned by the invoking program nor was
+    /// the invoking program allowed to commit it by the owning program.
+    AccountNotOwnedByInvoker = 11_001,
+    /// The undelegation of an account was requested by a program which is
+    /// only allowed to commit it.
+    UndelegationNotByOwner = 11_002,
+    /// A provided account does not match the one expected by the program.
+    AccountKeyMismatch = 11_003,
+}
+
+impl ScheduleCommitError {
+    pub const ALL: [Self; 4] = [
+        Self::ParentProgramNotFound,
+        Self::AccountNotOwnedByInvoker,
+        Self::UndelegationNotByOwner,
+        Self::AccountKeyMismatch,
+    ];
+
+    pub fn code(&self) -> u32 {
+        *self as u32
+    }
+
+    pub fn from_code(code: u32) -> Option<Self> {
+        Self::ALL.into_iter().find(|err| err.code() == code)
+    }
+
+    pub fn name(&self) -> &'static str {
+        use ScheduleCommitError::*;
+        match self {
+            ParentProgramNotFound => "ParentProgramNotFound",
+            AccountNotOwnedByInvoker => "AccountNotOwnedByInvoker",
+            UndelegationNotByOwner => "UndelegationNotByOwner",
+            AccountKeyMismatch => "AccountKeyMismatch",
+        }
+    }
+}
+
+impl fmt::Display for ScheduleCommitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl From<ScheduleCommitError> for u32 {
+    fn from(err: ScheduleCommitError) -> Self {
+        err.code()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codes_round_trip_and_are_unique() {
+        for err in ScheduleCommitError::ALL {
+            assert_eq!(ScheduleCommitError::from_code(err.code()), Some(err));
+            assert_eq!(format!("{:?}", err), err.name());
+        }
+        assert_eq!(ScheduleCommitError::from_code(1), None);
+    }
+}
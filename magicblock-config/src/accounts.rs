@@ -1,5 +1,6 @@
 use std::{fmt, str::FromStr};
 
+use magicblock_core::consts::DEFAULT_MAX_COMMIT_DELAY_SLOTS;
 use serde::{
     de::{self, Deserializer, SeqAccess, Visitor},
     Deserialize, Serialize,
@@ -24,8 +25,60 @@ pub struct AccountsConfig {
     pub commit: CommitStrategy,
     #[serde(default)]
     pub payer: Payer,
+    /// The programs that may be cloned, all programs are allowed if empty.
+    /// Programs added or removed via the admin RPC are merged into this
+    /// list at startup.
     #[serde(default)]
     pub allowed_programs: Vec<AllowedProgram>,
+    #[serde(default)]
+    pub circuit_breaker: CircuitBreakerConfig,
+    #[serde(default)]
+    pub subscriptions: SubscriptionsConfig,
+    #[serde(default)]
+    pub policies: AccountPoliciesConfig,
+    /// If non-zero, accounts that don't exist on chain are remembered for
+    /// this long and fetching them again is answered without asking the
+    /// remote, which helps apps that probe many nonexistent PDAs.
+    /// An account created on chain in the meantime is seen once it expired.
+    #[serde(default)]
+    pub missing_accounts_ttl_millis: u64,
+    #[serde(default)]
+    pub commit_budget: CommitBudgetConfig,
+    #[serde(default)]
+    pub commit_send: CommitSendStrategy,
+    /// If set, commits are simulated against the remote cluster before they
+    /// are sent. Commits that would fail, i.e. since the delegation record
+    /// changed, are not sent in order to not pay fees for them.
+    #[serde(default)]
+    pub simulate_commits: bool,
+    /// If set, a memo with a proof of the committed state is attached to
+    /// commit transactions for each account, which allows verifying it
+    /// against the slot it was committed at. Memos are left out of commits
+    /// that would not fit into a transaction with them.
+    #[serde(default)]
+    pub commit_proofs: bool,
+    /// SPL token mints whose mint authority is replaced with the validator
+    /// identity when cloned in order to mint test tokens.
+    /// Not supported when cloning from mainnet.
+    #[serde(default)]
+    pub mint_authority_overrides: Vec<MintAuthorityOverride>,
+    /// Local IDL files of programs whose IDL is missing or outdated on chain.
+    /// They are stored where the Anchor IDL of the program lives and used
+    /// instead of the on chain IDL when the program is cloned.
+    #[serde(default)]
+    pub idl_overrides: Vec<IdlOverride>,
+    /// If set, rent exempt accounts are cloned with the rent epoch mainnet
+    /// assigns them once they are written to, instead of the one they have on
+    /// chain, so that rent checks of programs behave exactly like on mainnet.
+    #[serde(default)]
+    pub mainnet_rent_semantics: bool,
+    /// If set, the validator refuses to start when restoring the ledger and
+    /// hydrating an account that may be delegated to us failed, instead of
+    /// logging the failure and running with a possibly stale clone of it.
+    #[serde(default)]
+    pub require_delegated_hydration: bool,
+    #[serde(default)]
+    pub dump: AccountDumpConfig,
 }
 
 // -----------------
@@ -154,12 +207,30 @@ pub struct CommitStrategy {
     /// This is in micro lamports and defaults to `1_000_000` (1 Lamport)
     #[serde(default = "default_compute_unit_price")]
     pub compute_unit_price: u64,
+    /// How many commits are sent and confirmed at the same time, commits
+    /// of the same account are always processed in the order they were
+    /// scheduled.
+    #[serde(default = "default_max_concurrent_commits")]
+    pub max_concurrent_commits: usize,
+    /// The maximum number of slots a commit may be delayed by when it is
+    /// scheduled for a target slot or after a delay, commits targeting a
+    /// later slot are rejected.
+    #[serde(default = "default_max_delay_slots")]
+    pub max_delay_slots: u64,
 }
 
 fn default_frequency_millis() -> u64 {
     500
 }
 
+fn default_max_concurrent_commits() -> usize {
+    16
+}
+
+fn default_max_delay_slots() -> u64 {
+    DEFAULT_MAX_COMMIT_DELAY_SLOTS
+}
+
 fn default_compute_unit_price() -> u64 {
     // This is the lowest we found to pass the transactions through mainnet fairly
     // consistently
@@ -171,10 +242,190 @@ impl Default for CommitStrategy {
         Self {
             frequency_millis: default_frequency_millis(),
             compute_unit_price: default_compute_unit_price(),
+            max_concurrent_commits: default_max_concurrent_commits(),
+            max_delay_slots: default_max_delay_slots(),
+        }
+    }
+}
+
+// -----------------
+// CommitBudgetConfig
+// -----------------
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct CommitBudgetConfig {
+    /// The lamports the validator authority may spend on commit transactions
+    /// within the rolling window before frequent commits are paused.
+    /// Commits requested by programs are never paused.
+    /// No budget is enforced if this is not set.
+    #[serde(default)]
+    pub max_lamports: Option<u64>,
+    #[serde(default = "default_window_secs")]
+    pub window_secs: u64,
+}
+
+fn default_window_secs() -> u64 {
+    // 1 day
+    24 * 60 * 60
+}
+
+impl Default for CommitBudgetConfig {
+    fn default() -> Self {
+        Self {
+            max_lamports: None,
+            window_secs: default_window_secs(),
+        }
+    }
+}
+
+// -----------------
+// CommitSendStrategy
+// -----------------
+/// How commit transactions are submitted to the remote cluster.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(tag = "strategy", rename_all = "kebab-case")]
+pub enum CommitSendStrategy {
+    /// Sends commits to the RPC of the remote cluster.
+    #[default]
+    Rpc,
+    /// Sends commits to the RPC of the remote cluster and all [urls] at
+    /// once, succeeding if any of them accepts the transaction.
+    Fanout { urls: Vec<Url> },
+    /// Submits each commit as a Jito bundle together with a transaction
+    /// tipping one of the [tip_accounts], the tip is only paid if the commit
+    /// lands. The [tip_accounts] differ per cluster, only those of the Jito
+    /// mainnet block engines are known and used if none are provided.
+    JitoBundle {
+        block_engine_url: Url,
+        tip_lamports: u64,
+        #[serde(
+            default,
+            deserialize_with = "pubkeys_deserialize",
+            serialize_with = "pubkeys_serialize"
+        )]
+        tip_accounts: Vec<Pubkey>,
+    },
+}
+
+// -----------------
+// AccountDumpConfig
+// -----------------
+/// Decorators of the dumper which writes cloned accounts into the bank.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct AccountDumpConfig {
+    /// If set, every dump is appended as a JSON line to the file at this path
+    /// including the hashes of the dumped accounts before and after.
+    #[serde(default)]
+    pub audit_log: Option<String>,
+    /// If set, the count, outcome and duration of dumps are tracked as
+    /// metrics.
+    #[serde(default)]
+    pub metrics: bool,
+}
+
+// -----------------
+// CircuitBreakerConfig
+// -----------------
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct CircuitBreakerConfig {
+    /// The number of consecutive failed requests to the remote cluster after
+    /// which we stop fetching accounts and sending commits for a while.
+    /// Setting it to `0` disables the circuit breaker.
+    #[serde(default = "default_failure_threshold")]
+    pub failure_threshold: u32,
+    /// How long we wait before trying the remote cluster again.
+    #[serde(default = "default_open_secs")]
+    pub open_secs: u64,
+}
+
+fn default_failure_threshold() -> u32 {
+    5
+}
+
+fn default_open_secs() -> u64 {
+    10
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: default_failure_threshold(),
+            open_secs: default_open_secs(),
+        }
+    }
+}
+
+// -----------------
+// SubscriptionsConfig
+// -----------------
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct SubscriptionsConfig {
+    /// The number of redundant connection pools to the remote, each of them
+    /// monitors all accounts so updates arrive even while one is refreshed.
+    #[serde(default = "default_subscription_pools")]
+    pub pools: usize,
+    /// The number of websocket connections per pool across which the
+    /// monitored accounts are sharded, raise it when the remote limits the
+    /// subscriptions per connection.
+    #[serde(default = "default_subscription_shards")]
+    pub shards: usize,
+    /// If set, cloned accounts are not monitored via websocket subscriptions
+    /// which some providers don't offer. Instead non-delegated accounts are
+    /// fetched again once they were cloned longer than this ago.
+    #[serde(default)]
+    pub poll_interval_millis: Option<u64>,
+}
+
+fn default_subscription_pools() -> usize {
+    3
+}
+
+fn default_subscription_shards() -> usize {
+    1
+}
+
+impl Default for SubscriptionsConfig {
+    fn default() -> Self {
+        Self {
+            pools: default_subscription_pools(),
+            shards: default_subscription_shards(),
+            poll_interval_millis: None,
         }
     }
 }
 
+// -----------------
+// AccountPoliciesConfig
+// -----------------
+/// Overrides individual policies which are otherwise implied by the
+/// [LifecycleMode], i.e. to clone everything but never refresh it for
+/// deterministic replays.
+/// Each unset option keeps what the lifecycle mode implies.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct AccountPoliciesConfig {
+    #[serde(default)]
+    pub clone_feepayer_accounts: Option<bool>,
+    #[serde(default)]
+    pub clone_undelegated_accounts: Option<bool>,
+    #[serde(default)]
+    pub clone_delegated_accounts: Option<bool>,
+    #[serde(default)]
+    pub clone_program_accounts: Option<bool>,
+    /// If set, cloned accounts are kept up to date with the remote, either
+    /// via subscriptions or via polling if configured in [SubscriptionsConfig]
+    #[serde(default)]
+    pub refresh_accounts: Option<bool>,
+    #[serde(default)]
+    pub commit_delegated_accounts: Option<bool>,
+    /// If disabled, commits scheduled by programs are dropped
+    #[serde(default)]
+    pub process_scheduled_commits: Option<bool>,
+}
+
 // -----------------
 // Payer
 // -----------------
@@ -217,6 +468,28 @@ pub struct AllowedProgram {
     pub id: Pubkey,
 }
 
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct MintAuthorityOverride {
+    #[serde(
+        deserialize_with = "pubkey_deserialize",
+        serialize_with = "pubkey_serialize"
+    )]
+    pub mint: Pubkey,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct IdlOverride {
+    #[serde(
+        deserialize_with = "pubkey_deserialize",
+        serialize_with = "pubkey_serialize"
+    )]
+    pub program_id: Pubkey,
+    /// Path to the JSON IDL file
+    pub path: String,
+}
+
 fn pubkey_deserialize<'de, D>(deserializer: D) -> Result<Pubkey, D::Error>
 where
     D: serde::Deserializer<'de>,
@@ -231,3 +504,26 @@ where
 {
     key.to_string().serialize(serializer)
 }
+
+fn pubkeys_deserialize<'de, D>(deserializer: D) -> Result<Vec<Pubkey>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|s| Pubkey::from_str(s).map_err(serde::de::Error::custom))
+        .collect()
+}
+
+fn pubkeys_serialize<S>(
+    keys: &[Pubkey],
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    keys.iter()
+        .map(|key| key.to_string())
+        .collect::<Vec<_>>()
+        .serialize(serializer)
+}
@@ -0,0 +1,22 @@
+use serde::{Deserialize, Serialize};
+
+/// Failures injected into the fetcher, committer and account updates to test
+/// how the validator recovers, all of them are disabled by default.
+/// This is meant for integration tests only, validators built without the
+/// `dev-context-only-utils` feature refuse to start if any is enabled.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ChaosConfig {
+    /// Delay added before each account fetch and commit send
+    #[serde(default)]
+    pub rpc_delay_ms: u64,
+
+    /// Fails every n-th commit send, `0` disables it
+    #[serde(default)]
+    pub fail_commit_send_every: u32,
+
+    /// Drops the websocket connection on every n-th account update, `0`
+    /// disables it
+    #[serde(default)]
+    pub drop_websocket_every: u32,
+}
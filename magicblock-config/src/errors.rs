@@ -8,6 +8,8 @@ pub enum ConfigError {
     Io(#[from] std::io::Error),
     #[error("TOML error: {0}")]
     Toml(#[from] toml::de::Error),
+    #[error("TOML serialization error: {0}")]
+    TomlSerialize(#[from] toml::ser::Error),
 
     #[error("Config path error: {0}")]
     ConfigPathInvalid(String),
@@ -15,6 +17,21 @@ pub enum ConfigError {
     #[error("Program with id '{0}' has invalid path '{1}'")]
     ProgramPathInvalidUnicode(String, String),
 
+    #[error("Genesis account '{0}' has invalid data path '{1}'")]
+    GenesisAccountDataPathInvalidUnicode(String, String),
+
     #[error("Cannot specify both init_lamports and init_sol")]
     CannotSpecifyBothInitLamportAndInitSol,
+
+    #[error("Cannot override mint authorities when cloning from mainnet")]
+    MintAuthorityOverrideOnMainnet,
+
+    #[error("Jito tip accounts need to be configured when not sending commits to mainnet")]
+    JitoTipAccountsRequired,
+
+    #[error("Unknown command line argument '{0}'")]
+    CliArgumentUnknown(String),
+
+    #[error("Invalid value for command line argument '{0}': {1}")]
+    CliArgumentInvalid(String, String),
 }
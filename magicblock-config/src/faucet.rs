@@ -0,0 +1,52 @@
+use serde::{Deserialize, Serialize};
+
+/// Configures the faucet backing `requestAirdrop`.
+/// Each limit is disabled when set to `0`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct FaucetConfig {
+    /// Path to the keypair file of the faucet.
+    /// If not provided a faucet keypair is created next to the ledger.
+    #[serde(default)]
+    pub keypair: Option<String>,
+
+    /// The lamports the faucet account is funded with in the bank on startup.
+    #[serde(default = "default_lamports")]
+    pub lamports: u64,
+
+    /// The maximum lamports a single airdrop can request.
+    #[serde(default)]
+    pub max_lamports_per_request: u64,
+
+    /// The maximum lamports a single pubkey can receive per [Self::window_secs].
+    #[serde(default)]
+    pub per_key_lamports_per_window: u64,
+
+    /// The maximum lamports the faucet hands out per [Self::window_secs].
+    #[serde(default)]
+    pub global_lamports_per_window: u64,
+
+    #[serde(default = "default_window_secs")]
+    pub window_secs: u64,
+}
+
+fn default_lamports() -> u64 {
+    u64::MAX / 2
+}
+
+fn default_window_secs() -> u64 {
+    3600
+}
+
+impl Default for FaucetConfig {
+    fn default() -> Self {
+        Self {
+            keypair: None,
+            lamports: default_lamports(),
+            max_lamports_per_request: 0,
+            per_key_lamports_per_window: 0,
+            global_lamports_per_window: 0,
+            window_secs: default_window_secs(),
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+
+/// Rules applied to incoming transactions before any of the accounts they use
+/// are cloned, so hostile traffic cannot force expensive clones.
+/// Each rule is disabled when empty or set to `0`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct FirewallConfig {
+    /// Transactions invoking any of these programs in a top level instruction
+    /// are rejected.
+    #[serde(
+        default,
+        deserialize_with = "pubkeys_deserialize",
+        serialize_with = "pubkeys_serialize"
+    )]
+    pub denied_programs: Vec<Pubkey>,
+
+    /// Fee payers which are admitted without holding [Self::min_fee_payer_lamports].
+    /// If set without a min escrow only these fee payers are admitted.
+    #[serde(
+        default,
+        deserialize_with = "pubkeys_deserialize",
+        serialize_with = "pubkeys_serialize"
+    )]
+    pub allowed_fee_payers: Vec<Pubkey>,
+
+    /// The lamports the escrow of a fee payer that isn't allowed explicitly
+    /// needs to hold, that is its escrow PDA at index 0 managed by the
+    /// delegation program.
+    #[serde(default)]
+    pub min_fee_payer_lamports: u64,
+
+    /// The max size of a serialized transaction in bytes.
+    #[serde(default)]
+    pub max_transaction_size: usize,
+}
+
+fn pubkeys_deserialize<'de, D>(deserializer: D) -> Result<Vec<Pubkey>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|s| Pubkey::from_str(s).map_err(serde::de::Error::custom))
+        .collect()
+}
+
+fn pubkeys_serialize<S>(
+    keys: &[Pubkey],
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    keys.iter()
+        .map(|key| key.to_string())
+        .collect::<Vec<_>>()
+        .serialize(serializer)
+}
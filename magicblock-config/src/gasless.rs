@@ -0,0 +1,68 @@
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+
+/// Lets the validator identity sponsor the fees of transactions that only
+/// invoke the sponsored programs, so games can offer a gasless UX.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct GaslessConfig {
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Transactions invoking only these programs (and the compute budget
+    /// program) are sponsored.
+    #[serde(
+        default,
+        deserialize_with = "pubkeys_deserialize",
+        serialize_with = "pubkeys_serialize"
+    )]
+    pub sponsored_programs: Vec<Pubkey>,
+
+    /// The fee lamports sponsored for each fee payer within one quota window,
+    /// `0` sponsors an unlimited amount.
+    #[serde(default)]
+    pub quota_lamports: u64,
+
+    #[serde(default = "default_quota_window_secs")]
+    pub quota_window_secs: u64,
+}
+
+fn default_quota_window_secs() -> u64 {
+    60 * 60
+}
+
+impl Default for GaslessConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            sponsored_programs: vec![],
+            quota_lamports: 0,
+            quota_window_secs: default_quota_window_secs(),
+        }
+    }
+}
+
+fn pubkeys_deserialize<'de, D>(deserializer: D) -> Result<Vec<Pubkey>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|s| Pubkey::from_str(s).map_err(serde::de::Error::custom))
+        .collect()
+}
+
+fn pubkeys_serialize<S>(
+    keys: &[Pubkey],
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    keys.iter()
+        .map(|key| key.to_string())
+        .collect::<Vec<_>>()
+        .serialize(serializer)
+}
@@ -0,0 +1,116 @@
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use solana_sdk::{pubkey::Pubkey, system_program};
+
+/// Customizes the genesis of the bank, i.e. to match the feature set of a
+/// specific mainnet epoch or to set up a custom test environment.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct GenesisConfig {
+    /// Activates all feature gates known to the validator, regardless of
+    /// the ones listed in [Self::features].
+    #[serde(default)]
+    pub activate_all_features: bool,
+
+    /// Feature gates that are active from genesis, all others are inactive.
+    #[serde(
+        default,
+        deserialize_with = "pubkeys_deserialize",
+        serialize_with = "pubkeys_serialize"
+    )]
+    pub features: Vec<Pubkey>,
+
+    /// Builtin programs registered in addition to the ones the validator
+    /// always registers.
+    #[serde(default)]
+    pub builtins: Vec<GenesisBuiltin>,
+
+    /// Accounts created at genesis.
+    #[serde(default)]
+    #[serde(rename = "account")]
+    pub accounts: Vec<GenesisAccountConfig>,
+}
+
+// -----------------
+// GenesisBuiltin
+// -----------------
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum GenesisBuiltin {
+    BpfLoader,
+    BpfLoaderDeprecated,
+    LoaderV4,
+}
+
+// -----------------
+// GenesisAccountConfig
+// -----------------
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct GenesisAccountConfig {
+    #[serde(
+        deserialize_with = "pubkey_deserialize",
+        serialize_with = "pubkey_serialize"
+    )]
+    pub pubkey: Pubkey,
+
+    pub lamports: u64,
+
+    #[serde(
+        default = "default_owner",
+        deserialize_with = "pubkey_deserialize",
+        serialize_with = "pubkey_serialize"
+    )]
+    pub owner: Pubkey,
+
+    #[serde(default)]
+    pub executable: bool,
+
+    /// File containing the raw account data, relative paths are resolved
+    /// against the directory of the config file.
+    #[serde(default)]
+    pub data_path: Option<String>,
+}
+
+fn default_owner() -> Pubkey {
+    system_program::id()
+}
+
+fn pubkey_deserialize<'de, D>(deserializer: D) -> Result<Pubkey, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    Pubkey::from_str(&s).map_err(serde::de::Error::custom)
+}
+
+fn pubkey_serialize<S>(key: &Pubkey, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    key.to_string().serialize(serializer)
+}
+
+fn pubkeys_deserialize<'de, D>(deserializer: D) -> Result<Vec<Pubkey>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|s| Pubkey::from_str(s).map_err(serde::de::Error::custom))
+        .collect()
+}
+
+fn pubkeys_serialize<S>(
+    keys: &[Pubkey],
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    keys.iter()
+        .map(|key| key.to_string())
+        .collect::<Vec<_>>()
+        .serialize(serializer)
+}
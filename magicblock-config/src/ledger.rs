@@ -13,6 +13,10 @@ pub struct LedgerConfig {
     // If left empty it will be auto-generated to a temporary folder
     #[serde(default)]
     pub path: Option<String>,
+    /// Whether the inputs received from the remote cluster are recorded to
+    /// the ledger or replayed from it, see [LedgerInputsMode].
+    #[serde(default)]
+    pub inputs: LedgerInputsMode,
 }
 
 impl Default for LedgerConfig {
@@ -20,6 +24,28 @@ impl Default for LedgerConfig {
         Self {
             reset: bool_true(),
             path: Default::default(),
+            inputs: Default::default(),
         }
     }
 }
+
+// -----------------
+// LedgerInputsMode
+// -----------------
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LedgerInputsMode {
+    /// Inputs are neither recorded nor replayed.
+    #[default]
+    Off,
+    /// The chain snapshots fetched while cloning accounts, the slots of
+    /// account updates and the clock timestamps are recorded to the ledger.
+    Record,
+    /// The validator runs hermetically, without connecting to the remote
+    /// cluster, and serves the inputs recorded to the ledger at
+    /// [LedgerConfig::path] in the order they were received in order to
+    /// reproduce a previous run. Accepted commits are not sent.
+    /// The inputs are kept when the ledger is reset, which allows to replay
+    /// the transactions of the recorded run from a clean state.
+    Replay,
+}
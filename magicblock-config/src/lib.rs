@@ -10,20 +10,36 @@ use serde::{Deserialize, Serialize};
 use url::Url;
 
 mod accounts;
+mod chaos;
 pub mod errors;
+mod faucet;
+mod firewall;
+mod gasless;
+mod genesis;
 mod geyser_grpc;
 mod helpers;
 mod ledger;
 mod metrics;
+mod profile;
 mod program;
+mod replica;
 mod rpc;
+mod telemetry;
 mod validator;
 pub use accounts::*;
+pub use chaos::*;
+pub use faucet::*;
+pub use firewall::*;
+pub use gasless::*;
+pub use genesis::*;
 pub use geyser_grpc::*;
 pub use ledger::*;
 pub use metrics::*;
+pub use profile::*;
 pub use program::*;
+pub use replica::*;
 pub use rpc::*;
+pub use telemetry::*;
 pub use validator::*;
 
 #[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
@@ -44,40 +60,88 @@ pub struct EphemeralConfig {
     pub programs: Vec<ProgramConfig>,
     #[serde(default)]
     pub metrics: MetricsConfig,
+    #[serde(default)]
+    pub faucet: FaucetConfig,
+    #[serde(default)]
+    pub firewall: FirewallConfig,
+    #[serde(default)]
+    pub gasless: GaslessConfig,
+    #[serde(default)]
+    pub telemetry: TelemetryConfig,
+    #[serde(default)]
+    pub replica: ReplicaConfig,
+    #[serde(default)]
+    pub chaos: ChaosConfig,
+    #[serde(default)]
+    pub genesis: GenesisConfig,
 }
 
 impl EphemeralConfig {
     pub fn try_load_from_file(path: &str) -> ConfigResult<Self> {
+        Self::try_load_from_file_with_profile(path, None)
+    }
+
+    pub fn try_load_from_file_with_profile(
+        path: &str,
+        profile: Option<ConfigProfile>,
+    ) -> ConfigResult<Self> {
         let p = Path::new(path);
         let toml = fs::read_to_string(p)?;
-        Self::try_load_from_toml(&toml, Some(p))
+        Self::try_load_from_toml_with_profile(&toml, Some(p), profile)
     }
 
     pub fn try_load_from_toml(
         toml: &str,
         config_path: Option<&Path>,
     ) -> ConfigResult<Self> {
-        let mut config: Self = toml::from_str(toml)?;
+        Self::try_load_from_toml_with_profile(toml, config_path, None)
+    }
+
+    /// Loads the [toml] on top of the [profile] if provided, otherwise on
+    /// top of the defaults.
+    pub fn try_load_from_toml_with_profile(
+        toml: &str,
+        config_path: Option<&Path>,
+        profile: Option<ConfigProfile>,
+    ) -> ConfigResult<Self> {
+        let mut config: Self = match profile {
+            Some(profile) => profile.load_toml(toml)?,
+            None => toml::from_str(toml)?,
+        };
+        // If we know the config path we can resolve relative paths
+        // Otherwise they have to be absolute. However if no config path was
+        // provided this usually means that we are provided some default toml
+        // config file which doesn't include any paths.
+        let Some(config_path) = config_path else {
+            return Ok(config);
+        };
+        let config_dir = config_path.parent().ok_or_else(|| {
+            ConfigError::ConfigPathInvalid(format!(
+                "Config path: '{}' is missing parent dir",
+                config_path.display()
+            ))
+        })?;
         for program in &mut config.programs {
-            // If we know the config path we can resolve relative program paths
-            // Otherwise they have to be absolute. However if no config path was
-            // provided this usually means that we are provided some default toml
-            // config file which doesn't include any program paths.
-            if let Some(config_path) = config_path {
-                program.path = config_path
-                    .parent()
-                    .ok_or_else(|| {
-                        ConfigError::ConfigPathInvalid(format!(
-                            "Config path: '{}' is missing parent dir",
-                            config_path.display()
-                        ))
-                    })?
-                    .join(&program.path)
+            program.path = config_dir
+                .join(&program.path)
+                .to_str()
+                .ok_or_else(|| {
+                    ConfigError::ProgramPathInvalidUnicode(
+                        program.id.to_string(),
+                        program.path.to_string(),
+                    )
+                })?
+                .to_string()
+        }
+        for account in &mut config.genesis.accounts {
+            if let Some(data_path) = &mut account.data_path {
+                *data_path = config_dir
+                    .join(data_path.as_str())
                     .to_str()
                     .ok_or_else(|| {
-                        ConfigError::ProgramPathInvalidUnicode(
-                            program.id.to_string(),
-                            program.path.to_string(),
+                        ConfigError::GenesisAccountDataPathInvalidUnicode(
+                            account.pubkey.to_string(),
+                            data_path.to_string(),
                         )
                     })?
                     .to_string()
@@ -162,6 +226,35 @@ impl EphemeralConfig {
                 panic!("Failed to parse 'RPC_PORT' as u16: {:?}", err)
             });
         }
+        if let Ok(admin) = env::var("RPC_ADMIN") {
+            config.rpc.admin = bool::from_str(&admin).unwrap_or_else(|err| {
+                panic!("Failed to parse 'RPC_ADMIN' as bool: {:?}", err)
+            });
+        }
+        if let Ok(addr) = env::var("RPC_PUBSUB_ADDR") {
+            config.rpc.pubsub_addr =
+                Some(IpAddr::from_str(&addr).unwrap_or_else(|err| {
+                    panic!(
+                        "Failed to parse 'RPC_PUBSUB_ADDR' as IpAddr: {:?}",
+                        err
+                    )
+                }));
+        }
+        if let Ok(port) = env::var("RPC_PUBSUB_PORT") {
+            config.rpc.pubsub_port =
+                Some(u16::from_str(&port).unwrap_or_else(|err| {
+                    panic!(
+                        "Failed to parse 'RPC_PUBSUB_PORT' as u16: {:?}",
+                        err
+                    )
+                }));
+        }
+        if let Ok(port) = env::var("RPC_TPU_PORT") {
+            config.rpc.tpu_port =
+                Some(u16::from_str(&port).unwrap_or_else(|err| {
+                    panic!("Failed to parse 'RPC_TPU_PORT' as u16: {:?}", err)
+                }));
+        }
 
         // -----------------
         // Geyser GRPC
@@ -193,6 +286,57 @@ impl EphemeralConfig {
             config.validator.millis_per_slot = u64::from_str(&millis_per_slot)
                 .unwrap_or_else(|err| panic!("Failed to parse 'VALIDATOR_MILLIS_PER_SLOT' as u64: {:?}", err));
         }
+        if let Ok(millis) = env::var("VALIDATOR_TRANSACTION_EXPIRATION_MILLIS")
+        {
+            config.validator.transaction_expiration_millis = u64::from_str(&millis)
+                .unwrap_or_else(|err| panic!("Failed to parse 'VALIDATOR_TRANSACTION_EXPIRATION_MILLIS' as u64: {:?}", err));
+        }
+        if let Ok(threads) = env::var("VALIDATOR_SIGVERIFY_THREADS") {
+            config.validator.sigverify_threads = usize::from_str(&threads)
+                .unwrap_or_else(|err| panic!("Failed to parse 'VALIDATOR_SIGVERIFY_THREADS' as usize: {:?}", err));
+        }
+        if let Ok(budget) = env::var("VALIDATOR_DATA_MODS_MEMORY_BUDGET") {
+            config.validator.data_mods_memory_budget = usize::from_str(&budget)
+                .unwrap_or_else(|err| panic!("Failed to parse 'VALIDATOR_DATA_MODS_MEMORY_BUDGET' as usize: {:?}", err));
+        }
+        if let Ok(millis) = env::var("VALIDATOR_SHUTDOWN_MAX_DRAIN_MILLIS") {
+            config.validator.shutdown_max_drain_millis = u64::from_str(&millis)
+                .unwrap_or_else(|err| panic!("Failed to parse 'VALIDATOR_SHUTDOWN_MAX_DRAIN_MILLIS' as u64: {:?}", err));
+        }
+        if let Ok(secs) = env::var("VALIDATOR_MAX_CLOCK_SKEW_SECS") {
+            config.validator.max_clock_skew_secs = u64::from_str(&secs)
+                .unwrap_or_else(|err| panic!("Failed to parse 'VALIDATOR_MAX_CLOCK_SKEW_SECS' as u64: {:?}", err));
+        }
+        if let Ok(fail) = env::var("VALIDATOR_FAIL_ON_CLOCK_SKEW") {
+            config.validator.fail_on_clock_skew = bool::from_str(&fail)
+                .unwrap_or_else(|err| panic!("Failed to parse 'VALIDATOR_FAIL_ON_CLOCK_SKEW' as bool: {:?}", err));
+        }
+        if let Ok(lamports) =
+            env::var("VALIDATOR_FEE_PAYER_SPEND_LIMIT_MAX_LAMPORTS")
+        {
+            config.validator.fee_payer_spend_limit.max_lamports = Some(u64::from_str(&lamports)
+                .unwrap_or_else(|err| panic!("Failed to parse 'VALIDATOR_FEE_PAYER_SPEND_LIMIT_MAX_LAMPORTS' as u64: {:?}", err)));
+        }
+        if let Ok(enabled) = env::var("VALIDATOR_CLOCK_SYNC_ENABLED") {
+            config.validator.clock_sync.enabled = bool::from_str(&enabled)
+                .unwrap_or_else(|err| panic!("Failed to parse 'VALIDATOR_CLOCK_SYNC_ENABLED' as bool: {:?}", err));
+        }
+
+        // -----------------
+        // Replica
+        // -----------------
+        if let Ok(enabled) = env::var("REPLICA_ENABLED") {
+            config.replica.enabled =
+                bool::from_str(&enabled).unwrap_or_else(|err| {
+                    panic!(
+                        "Failed to parse 'REPLICA_ENABLED' as bool: {:?}",
+                        err
+                    )
+                });
+        }
+        if let Ok(url) = env::var("REPLICA_PRIMARY_GEYSER_GRPC_URL") {
+            config.replica.primary_geyser_grpc_url = url;
+        }
 
         // -----------------
         // Ledger
@@ -245,8 +389,187 @@ impl EphemeralConfig {
                     )
                 });
         }
+
+        // -----------------
+        // Faucet
+        // -----------------
+        if let Ok(keypair) = env::var("FAUCET_KEYPAIR") {
+            config.faucet.keypair = Some(keypair);
+        }
+        if let Ok(lamports) = env::var("FAUCET_MAX_LAMPORTS_PER_REQUEST") {
+            config.faucet.max_lamports_per_request = u64::from_str(&lamports)
+                .unwrap_or_else(|err| panic!("Failed to parse 'FAUCET_MAX_LAMPORTS_PER_REQUEST' as u64: {:?}", err));
+        }
+        if let Ok(lamports) = env::var("FAUCET_PER_KEY_LAMPORTS_PER_WINDOW") {
+            config.faucet.per_key_lamports_per_window = u64::from_str(&lamports)
+                .unwrap_or_else(|err| panic!("Failed to parse 'FAUCET_PER_KEY_LAMPORTS_PER_WINDOW' as u64: {:?}", err));
+        }
+        if let Ok(lamports) = env::var("FAUCET_GLOBAL_LAMPORTS_PER_WINDOW") {
+            config.faucet.global_lamports_per_window = u64::from_str(&lamports)
+                .unwrap_or_else(|err| panic!("Failed to parse 'FAUCET_GLOBAL_LAMPORTS_PER_WINDOW' as u64: {:?}", err));
+        }
+        if let Ok(secs) = env::var("FAUCET_WINDOW_SECS") {
+            config.faucet.window_secs =
+                u64::from_str(&secs).unwrap_or_else(|err| {
+                    panic!(
+                        "Failed to parse 'FAUCET_WINDOW_SECS' as u64: {:?}",
+                        err
+                    )
+                });
+        }
+
+        // -----------------
+        // Firewall
+        // -----------------
+        if let Ok(lamports) = env::var("FIREWALL_MIN_FEE_PAYER_LAMPORTS") {
+            config.firewall.min_fee_payer_lamports = u64::from_str(&lamports)
+                .unwrap_or_else(|err| panic!("Failed to parse 'FIREWALL_MIN_FEE_PAYER_LAMPORTS' as u64: {:?}", err));
+        }
+        if let Ok(size) = env::var("FIREWALL_MAX_TRANSACTION_SIZE") {
+            config.firewall.max_transaction_size = usize::from_str(&size)
+                .unwrap_or_else(|err| panic!("Failed to parse 'FIREWALL_MAX_TRANSACTION_SIZE' as usize: {:?}", err));
+        }
+
+        // -----------------
+        // Gasless
+        // -----------------
+        if let Ok(enabled) = env::var("GASLESS_ENABLED") {
+            config.gasless.enabled =
+                bool::from_str(&enabled).unwrap_or_else(|err| {
+                    panic!(
+                        "Failed to parse 'GASLESS_ENABLED' as bool: {:?}",
+                        err
+                    )
+                });
+        }
+        if let Ok(lamports) = env::var("GASLESS_QUOTA_LAMPORTS") {
+            config.gasless.quota_lamports = u64::from_str(&lamports)
+                .unwrap_or_else(|err| {
+                    panic!(
+                        "Failed to parse 'GASLESS_QUOTA_LAMPORTS' as u64: {:?}",
+                        err
+                    )
+                });
+        }
+
+        // -----------------
+        // Telemetry
+        // -----------------
+        if let Ok(enabled) = env::var("TELEMETRY_ENABLED") {
+            config.telemetry.enabled =
+                bool::from_str(&enabled).unwrap_or_else(|err| {
+                    panic!(
+                        "Failed to parse 'TELEMETRY_ENABLED' as bool: {:?}",
+                        err
+                    )
+                });
+        }
+        if let Ok(endpoint) = env::var("TELEMETRY_OTLP_ENDPOINT") {
+            config.telemetry.otlp_endpoint = endpoint;
+        }
+
+        // -----------------
+        // Chaos
+        // -----------------
+        if let Ok(delay) = env::var("CHAOS_RPC_DELAY_MS") {
+            config.chaos.rpc_delay_ms =
+                u64::from_str(&delay).unwrap_or_else(|err| {
+                    panic!(
+                        "Failed to parse 'CHAOS_RPC_DELAY_MS' as u64: {:?}",
+                        err
+                    )
+                });
+        }
+        if let Ok(every) = env::var("CHAOS_FAIL_COMMIT_SEND_EVERY") {
+            config.chaos.fail_commit_send_every = u32::from_str(&every)
+                .unwrap_or_else(|err| {
+                    panic!(
+                        "Failed to parse 'CHAOS_FAIL_COMMIT_SEND_EVERY' as u32: {:?}",
+                        err
+                    )
+                });
+        }
+        if let Ok(every) = env::var("CHAOS_DROP_WEBSOCKET_EVERY") {
+            config.chaos.drop_websocket_every = u32::from_str(&every)
+                .unwrap_or_else(|err| {
+                    panic!(
+                        "Failed to parse 'CHAOS_DROP_WEBSOCKET_EVERY' as u32: {:?}",
+                        err
+                    )
+                });
+        }
         config
     }
+
+    /// Overrides the bind addresses and ports of the services with command
+    /// line flags like `--rpc-port 8899` or `--rpc-port=8899`.
+    /// These take precedence over the env vars and allow containerized
+    /// deployments to adapt to their port mappings without changing the
+    /// config file baked into the image.
+    pub fn override_from_args(
+        &self,
+        args: &[String],
+    ) -> ConfigResult<EphemeralConfig> {
+        fn parse<T: FromStr>(flag: &str, value: &str) -> ConfigResult<T>
+        where
+            T::Err: fmt::Display,
+        {
+            value.parse().map_err(|err: T::Err| {
+                ConfigError::CliArgumentInvalid(
+                    flag.to_string(),
+                    err.to_string(),
+                )
+            })
+        }
+
+        let mut config = self.clone();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let (flag, value) = match arg.split_once('=') {
+                Some((flag, value)) => (flag, value.to_string()),
+                None => (
+                    arg.as_str(),
+                    args.next().cloned().ok_or_else(|| {
+                        ConfigError::CliArgumentInvalid(
+                            arg.to_string(),
+                            "missing value".to_string(),
+                        )
+                    })?,
+                ),
+            };
+            match flag {
+                "--rpc-addr" => config.rpc.addr = parse(flag, &value)?,
+                "--rpc-port" => config.rpc.port = parse(flag, &value)?,
+                "--rpc-pubsub-addr" => {
+                    config.rpc.pubsub_addr = Some(parse(flag, &value)?)
+                }
+                "--rpc-pubsub-port" => {
+                    config.rpc.pubsub_port = Some(parse(flag, &value)?)
+                }
+                "--rpc-tpu-port" => {
+                    config.rpc.tpu_port = Some(parse(flag, &value)?)
+                }
+                "--geyser-grpc-addr" => {
+                    config.geyser_grpc.addr = parse(flag, &value)?
+                }
+                "--geyser-grpc-port" => {
+                    config.geyser_grpc.port = parse(flag, &value)?
+                }
+                "--metrics-addr" => {
+                    config.metrics.service.addr = parse(flag, &value)?
+                }
+                "--metrics-port" => {
+                    config.metrics.service.port = parse(flag, &value)?
+                }
+                _ => {
+                    return Err(ConfigError::CliArgumentUnknown(
+                        flag.to_string(),
+                    ))
+                }
+            }
+        }
+        Ok(config)
+    }
 }
 
 impl fmt::Display for EphemeralConfig {
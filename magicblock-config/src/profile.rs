@@ -0,0 +1,136 @@
+use serde::{Deserialize, Serialize};
+use strum_macros::EnumString;
+use toml::Value;
+
+use crate::{
+    errors::{ConfigError, ConfigResult},
+    AccountsConfig, EphemeralConfig, LedgerConfig, LifecycleMode, RemoteConfig,
+    ReplicaConfig,
+};
+
+/// Named presets which set coherent defaults for how the validator clones
+/// and commits accounts and whether it uses the remote cluster at all.
+/// The settings of the config file are applied on top of the profile.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, EnumString,
+)]
+#[serde(rename_all = "kebab-case")]
+#[strum(serialize_all = "kebab-case")]
+pub enum ConfigProfile {
+    /// Clones accounts from the remote cluster on demand and commits the
+    /// delegated ones back, commits are simulated first to not pay fees for
+    /// ones that would fail.
+    /// The ledger is kept across restarts to not lose delegated state.
+    Ephemeral,
+    /// Runs against a local development cluster without cloning or
+    /// committing anything.
+    DevOffline,
+    /// Mirrors a primary validator as read replica without cloning or
+    /// committing anything itself.
+    Replica,
+}
+
+impl ConfigProfile {
+    /// The config the profile starts from before the config file is applied.
+    pub fn config(&self) -> EphemeralConfig {
+        match self {
+            Self::Ephemeral => EphemeralConfig {
+                accounts: AccountsConfig {
+                    lifecycle: LifecycleMode::Ephemeral,
+                    simulate_commits: true,
+                    ..Default::default()
+                },
+                ledger: LedgerConfig {
+                    reset: false,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            Self::DevOffline => EphemeralConfig {
+                accounts: AccountsConfig {
+                    remote: RemoteConfig::Development,
+                    lifecycle: LifecycleMode::Offline,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            Self::Replica => EphemeralConfig {
+                accounts: AccountsConfig {
+                    lifecycle: LifecycleMode::Offline,
+                    ..Default::default()
+                },
+                replica: ReplicaConfig {
+                    enabled: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Parses the [toml] on top of the profile such that only the settings
+    /// included in it override the ones of the profile.
+    pub(crate) fn load_toml(
+        &self,
+        toml: &str,
+    ) -> ConfigResult<EphemeralConfig> {
+        let mut config = Value::try_from(self.config())?;
+        merge_toml(&mut config, toml::from_str(toml)?);
+        Ok(config.try_into()?)
+    }
+
+    /// Removes the `--profile <name>` or `--profile=<name>` flag from the
+    /// [args] and returns the profile it selects with the remaining args.
+    pub fn extract_from_args(
+        args: &[String],
+    ) -> ConfigResult<(Option<Self>, Vec<String>)> {
+        const FLAG: &str = "--profile";
+        let mut profile = None;
+        let mut remaining = Vec::with_capacity(args.len());
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let value = match arg.split_once('=') {
+                Some((flag, value)) if flag == FLAG => value.to_string(),
+                _ if arg == FLAG => args.next().cloned().ok_or_else(|| {
+                    ConfigError::CliArgumentInvalid(
+                        FLAG.to_string(),
+                        "missing value".to_string(),
+                    )
+                })?,
+                _ => {
+                    remaining.push(arg.clone());
+                    continue;
+                }
+            };
+            profile = Some(value.parse().map_err(|_| {
+                ConfigError::CliArgumentInvalid(
+                    FLAG.to_string(),
+                    format!(
+                        "unknown profile '{}', expected one of ephemeral, \
+                         dev-offline, replica",
+                        value
+                    ),
+                )
+            })?);
+        }
+        Ok((profile, remaining))
+    }
+}
+
+/// Merges tables recursively while any other value of the [overrides]
+/// replaces the one of the [base].
+fn merge_toml(base: &mut Value, overrides: Value) {
+    match (base, overrides) {
+        (Value::Table(base), Value::Table(overrides)) => {
+            for (key, value) in overrides {
+                match base.get_mut(&key) {
+                    Some(base_value) => merge_toml(base_value, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overrides) => *base = overrides,
+    }
+}
@@ -0,0 +1,39 @@
+use serde::{Deserialize, Serialize};
+
+/// Runs the validator as a read replica of a primary validator.
+/// The replica executes no transactions itself, instead it mirrors the
+/// accounts, slots and transactions streamed by the geyser gRPC service of
+/// the primary in order to serve heavy read RPC requests in its place.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ReplicaConfig {
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// The URL of the geyser gRPC service of the primary validator.
+    #[serde(default = "default_primary_geyser_grpc_url")]
+    pub primary_geyser_grpc_url: String,
+
+    /// How long to wait before reconnecting to the primary after the
+    /// stream failed.
+    #[serde(default = "default_reconnect_delay_millis")]
+    pub reconnect_delay_millis: u64,
+}
+
+fn default_primary_geyser_grpc_url() -> String {
+    "http://127.0.0.1:10000".to_string()
+}
+
+fn default_reconnect_delay_millis() -> u64 {
+    1000
+}
+
+impl Default for ReplicaConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            primary_geyser_grpc_url: default_primary_geyser_grpc_url(),
+            reconnect_delay_millis: default_reconnect_delay_millis(),
+        }
+    }
+}
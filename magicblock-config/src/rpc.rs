@@ -13,6 +13,23 @@ pub struct RpcConfig {
     pub addr: IpAddr,
     #[serde(default = "default_port")]
     pub port: u16,
+    /// Enables admin methods like `requestShutdown`, these should only be
+    /// enabled if the RPC port is not publicly reachable.
+    #[serde(default)]
+    pub admin: bool,
+    #[serde(default)]
+    pub economics: RpcEconomicsConfig,
+    /// The address the pubsub service binds to, defaults to [Self::addr].
+    #[serde(default)]
+    pub pubsub_addr: Option<IpAddr>,
+    /// The port of the pubsub service, defaults to the one following
+    /// [Self::port].
+    #[serde(default)]
+    pub pubsub_port: Option<u16>,
+    /// If set, transactions are also accepted via QUIC on this port of
+    /// [Self::addr], speaking the TPU protocol of Solana validators.
+    #[serde(default)]
+    pub tpu_port: Option<u16>,
 }
 
 impl Default for RpcConfig {
@@ -20,6 +37,11 @@ impl Default for RpcConfig {
         Self {
             addr: default_addr(),
             port: default_port(),
+            admin: false,
+            economics: RpcEconomicsConfig::default(),
+            pubsub_addr: None,
+            pubsub_port: None,
+            tpu_port: None,
         }
     }
 }
@@ -28,6 +50,34 @@ impl RpcConfig {
     pub fn socket_addr(&self) -> SocketAddr {
         SocketAddr::new(self.addr, self.port)
     }
+
+    pub fn pubsub_socket_addr(&self) -> SocketAddr {
+        SocketAddr::new(
+            self.pubsub_addr.unwrap_or(self.addr),
+            self.pubsub_port.unwrap_or(self.port + 1),
+        )
+    }
+
+    pub fn tpu_socket_addr(&self) -> Option<SocketAddr> {
+        self.tpu_port.map(|port| SocketAddr::new(self.addr, port))
+    }
+}
+
+/// Values reported by `getSupply`, `getInflationGovernor`,
+/// `getInflationRate` and `getVoteAccounts`, so that tools built for regular
+/// clusters work against the validator. Inflation is always reported as zero.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct RpcEconomicsConfig {
+    /// The total and circulating supply, defaults to the capitalization of
+    /// the bank.
+    #[serde(default)]
+    pub supply_lamports: Option<u64>,
+
+    /// The activated stake of the single vote account reported for the
+    /// validator identity.
+    #[serde(default)]
+    pub vote_account_stake_lamports: u64,
 }
 
 fn deserialize_addr<'de, D>(deserializer: D) -> Result<IpAddr, D::Error>
@@ -1,3 +1,4 @@
+use magicblock_core::consts::DEFAULT_TRANSACTION_EXPIRATION_MILLIS;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
@@ -6,25 +7,231 @@ pub struct ValidatorConfig {
     #[serde(default = "default_millis_per_slot")]
     pub millis_per_slot: u64,
 
+    /// How long blockhashes stay valid and processed transactions are
+    /// remembered in order to reject duplicates.
+    /// Long lived sessions that reuse blockhashes may widen this window.
+    #[serde(default = "default_transaction_expiration_millis")]
+    pub transaction_expiration_millis: u64,
+
+    /// When the blockhashes handed out to clients expire, by default after
+    /// [Self::transaction_expiration_millis] worth of slots.
+    #[serde(default)]
+    pub blockhash_expiry: BlockhashExpiryConfig,
+
     /// By default the validator will verify transaction signature.
     /// This can be disabled by setting [Self::sigverify] to `false`.
     #[serde(default = "default_sigverify")]
     pub sigverify: bool,
+
+    /// The number of threads verifying signatures of sent transactions,
+    /// `0` uses one thread per CPU.
+    #[serde(default = "default_sigverify_threads")]
+    pub sigverify_threads: usize,
+
+    /// The max number of transactions whose signatures are verified together
+    /// in one batch.
+    #[serde(default = "default_sigverify_max_batch_size")]
+    pub sigverify_max_batch_size: usize,
+
+    /// Skips verifying signatures of transactions signed by the validator
+    /// itself, i.e. faucet airdrops.
+    #[serde(default)]
+    pub skip_internal_sigverify: bool,
+
+    /// The maximum size in bytes of account data modifications held in memory
+    /// while cloning accounts. Data exceeding it is spilled to disk.
+    #[serde(default = "default_data_mods_memory_budget")]
+    pub data_mods_memory_budget: usize,
+
+    /// The maximum time in milliseconds the validator spends draining pending
+    /// clones and commits when shutting down gracefully before it exits anyways.
+    #[serde(default = "default_shutdown_max_drain_millis")]
+    pub shutdown_max_drain_millis: u64,
+
+    /// The maximum number of seconds our clock may deviate from the clock of
+    /// the remote cluster, a warning is logged at startup if it is exceeded.
+    #[serde(default = "default_max_clock_skew_secs")]
+    pub max_clock_skew_secs: u64,
+
+    /// If set, the validator refuses to start when the clock skew exceeds
+    /// [Self::max_clock_skew_secs] instead of only warning about it.
+    #[serde(default)]
+    pub fail_on_clock_skew: bool,
+
+    /// Optionally keeps the `Clock` sysvar in sync with the remote cluster.
+    #[serde(default)]
+    pub clock_sync: ClockSyncConfig,
+
+    /// Optionally limits the lamports each fee payer can spend.
+    #[serde(default)]
+    pub fee_payer_spend_limit: FeePayerSpendLimitConfig,
+
+    /// How transactions are charged, transactions are free by default.
+    #[serde(default)]
+    pub fees: FeesConfig,
 }
 
 fn default_millis_per_slot() -> u64 {
     50
 }
 
+fn default_transaction_expiration_millis() -> u64 {
+    DEFAULT_TRANSACTION_EXPIRATION_MILLIS
+}
+
 fn default_sigverify() -> bool {
     true
 }
 
+fn default_sigverify_threads() -> usize {
+    4
+}
+
+fn default_sigverify_max_batch_size() -> usize {
+    64
+}
+
+fn default_data_mods_memory_budget() -> usize {
+    // 256MB
+    256 * 1024 * 1024
+}
+
+fn default_shutdown_max_drain_millis() -> u64 {
+    30_000
+}
+
+fn default_max_clock_skew_secs() -> u64 {
+    30
+}
+
 impl Default for ValidatorConfig {
     fn default() -> Self {
         Self {
             millis_per_slot: default_millis_per_slot(),
+            transaction_expiration_millis:
+                default_transaction_expiration_millis(),
+            blockhash_expiry: BlockhashExpiryConfig::default(),
             sigverify: default_sigverify(),
+            sigverify_threads: default_sigverify_threads(),
+            sigverify_max_batch_size: default_sigverify_max_batch_size(),
+            skip_internal_sigverify: false,
+            data_mods_memory_budget: default_data_mods_memory_budget(),
+            shutdown_max_drain_millis: default_shutdown_max_drain_millis(),
+            max_clock_skew_secs: default_max_clock_skew_secs(),
+            fail_on_clock_skew: false,
+            clock_sync: ClockSyncConfig::default(),
+            fee_payer_spend_limit: FeePayerSpendLimitConfig::default(),
+            fees: FeesConfig::default(),
+        }
+    }
+}
+
+// -----------------
+// ClockSyncConfig
+// -----------------
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ClockSyncConfig {
+    /// If enabled the epoch and unix timestamp of the `Clock` sysvar track
+    /// the clock of the remote cluster instead of our system clock.
+    /// Its slot remains the slot of the ephemeral bank.
+    #[serde(default)]
+    pub enabled: bool,
+    /// How often the clock of the remote cluster is polled.
+    #[serde(default = "default_poll_interval_millis")]
+    pub poll_interval_millis: u64,
+}
+
+fn default_poll_interval_millis() -> u64 {
+    1_000
+}
+
+impl Default for ClockSyncConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            poll_interval_millis: default_poll_interval_millis(),
+        }
+    }
+}
+
+// -----------------
+// BlockhashExpiryConfig
+// -----------------
+/// On low-traffic ephemerals slots keep advancing without any activity, thus
+/// clients holding on to a blockhash for a while may find it expired.
+/// The effective validity is reflected in the `lastValidBlockHeight`
+/// returned by `getLatestBlockhash`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(tag = "mode", rename_all = "kebab-case")]
+pub enum BlockhashExpiryConfig {
+    /// Blockhashes expire once `transaction_expiration_millis` worth of
+    /// slots were advanced.
+    #[default]
+    Slots,
+    /// Blockhashes expire once [max_age_slots] were advanced which extends
+    /// their validity if it exceeds `transaction_expiration_millis` worth of
+    /// slots. This also widens the window in which duplicate transactions
+    /// are rejected.
+    ExtendedSlots { max_age_slots: u64 },
+    /// Blockhashes expire once `transaction_expiration_millis` passed on the
+    /// wall-clock, regardless of how many slots were advanced meanwhile.
+    WallClock,
+}
+
+// -----------------
+// FeePayerSpendLimitConfig
+// -----------------
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct FeePayerSpendLimitConfig {
+    /// The lamports a single fee payer may spend on fees and transfers
+    /// within one window before its transactions are rejected until the
+    /// window passed.
+    /// No limit is enforced if this is not set.
+    #[serde(default)]
+    pub max_lamports: Option<u64>,
+    #[serde(default = "default_spend_window_secs")]
+    pub window_secs: u64,
+}
+
+fn default_spend_window_secs() -> u64 {
+    60
+}
+
+impl Default for FeePayerSpendLimitConfig {
+    fn default() -> Self {
+        Self {
+            max_lamports: None,
+            window_secs: default_spend_window_secs(),
+        }
+    }
+}
+
+// -----------------
+// FeesConfig
+// -----------------
+/// The fee structure of the bank which is also reflected in the fees
+/// returned by `getFeeForMessage`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(tag = "mode", rename_all = "kebab-case")]
+pub enum FeesConfig {
+    /// Transactions are free, including their prioritization fees, i.e. to
+    /// onboard users without requiring them to hold lamports.
+    #[default]
+    Zero,
+    /// Each signature costs [lamports_per_signature] and prioritization fees
+    /// are charged like on mainnet which is more realistic.
+    Fixed { lamports_per_signature: u64 },
+}
+
+impl FeesConfig {
+    pub fn lamports_per_signature(&self) -> u64 {
+        match self {
+            Self::Zero => 0,
+            Self::Fixed {
+                lamports_per_signature,
+            } => *lamports_per_signature,
         }
     }
 }
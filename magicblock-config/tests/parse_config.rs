@@ -1,12 +1,18 @@
-use std::net::{IpAddr, Ipv4Addr};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 
 use magicblock_config::{
-    AccountsConfig, AllowedProgram, CommitStrategy, EphemeralConfig,
-    GeyserGrpcConfig, LedgerConfig, LifecycleMode, MetricsConfig,
-    MetricsServiceConfig, Payer, ProgramConfig, RemoteConfig, RpcConfig,
+    AccountDumpConfig, AccountPoliciesConfig, AccountsConfig, AllowedProgram,
+    BlockhashExpiryConfig, ChaosConfig, CircuitBreakerConfig, ClockSyncConfig,
+    CommitBudgetConfig, CommitSendStrategy, CommitStrategy, ConfigProfile,
+    EphemeralConfig, FaucetConfig, FeePayerSpendLimitConfig, FeesConfig,
+    FirewallConfig, GaslessConfig, GenesisAccountConfig, GenesisBuiltin,
+    GenesisConfig, GeyserGrpcConfig, IdlOverride, LedgerConfig,
+    LedgerInputsMode, LifecycleMode, MetricsConfig, MetricsServiceConfig,
+    MintAuthorityOverride, Payer, ProgramConfig, RemoteConfig, ReplicaConfig,
+    RpcConfig, RpcEconomicsConfig, SubscriptionsConfig, TelemetryConfig,
     ValidatorConfig,
 };
-use solana_sdk::{native_token::LAMPORTS_PER_SOL, pubkey};
+use solana_sdk::{native_token::LAMPORTS_PER_SOL, pubkey, system_program};
 use url::Url;
 
 #[test]
@@ -86,6 +92,7 @@ fn test_local_dev_with_programs_toml() {
                 commit: CommitStrategy {
                     frequency_millis: 600_000,
                     compute_unit_price: 0,
+                    ..Default::default()
                 },
                 ..Default::default()
             },
@@ -96,7 +103,8 @@ fn test_local_dev_with_programs_toml() {
             }],
             rpc: RpcConfig {
                 addr: IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)),
-                port: 7799
+                port: 7799,
+                ..Default::default()
             },
             validator: ValidatorConfig {
                 millis_per_slot: 14,
@@ -117,6 +125,7 @@ fn test_local_dev_with_programs_toml() {
                 },
                 ..Default::default()
             },
+            ..Default::default()
         }
     )
 }
@@ -180,6 +189,183 @@ fn test_accounts_payer() {
     );
 }
 
+#[test]
+fn test_faucet_toml() {
+    let toml = include_str!("fixtures/10_faucet.toml");
+    let config = toml::from_str::<EphemeralConfig>(toml).unwrap();
+    assert_eq!(
+        config,
+        EphemeralConfig {
+            accounts: AccountsConfig {
+                lifecycle: LifecycleMode::Ephemeral,
+                ..Default::default()
+            },
+            faucet: FaucetConfig {
+                keypair: Some("faucet-keypair.json".to_string()),
+                lamports: 1_000_000 * LAMPORTS_PER_SOL,
+                max_lamports_per_request: 5 * LAMPORTS_PER_SOL,
+                per_key_lamports_per_window: 10 * LAMPORTS_PER_SOL,
+                global_lamports_per_window: 1_000 * LAMPORTS_PER_SOL,
+                window_secs: 86_400,
+            },
+            ..Default::default()
+        }
+    );
+}
+
+#[test]
+fn test_telemetry_toml() {
+    let toml = include_str!("fixtures/11_telemetry.toml");
+    let config = toml::from_str::<EphemeralConfig>(toml).unwrap();
+    assert_eq!(
+        config,
+        EphemeralConfig {
+            telemetry: TelemetryConfig {
+                enabled: true,
+                otlp_endpoint: "http://otel-collector:4317".to_string(),
+                service_name: "ephem-devnet".to_string(),
+            },
+            ..Default::default()
+        }
+    );
+}
+
+#[test]
+fn test_circuit_breaker_toml() {
+    let toml = include_str!("fixtures/12_circuit-breaker.toml");
+    let config = toml::from_str::<EphemeralConfig>(toml).unwrap();
+    assert_eq!(
+        config,
+        EphemeralConfig {
+            accounts: AccountsConfig {
+                circuit_breaker: CircuitBreakerConfig {
+                    failure_threshold: 3,
+                    open_secs: 30,
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    );
+}
+
+#[test]
+fn test_commit_budget_toml() {
+    let toml = include_str!("fixtures/13_commit-budget.toml");
+    let config = toml::from_str::<EphemeralConfig>(toml).unwrap();
+    assert_eq!(
+        config,
+        EphemeralConfig {
+            accounts: AccountsConfig {
+                commit_budget: CommitBudgetConfig {
+                    max_lamports: Some(LAMPORTS_PER_SOL),
+                    window_secs: 3_600,
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    );
+}
+
+#[test]
+fn test_mint_authority_overrides_toml() {
+    let toml = include_str!("fixtures/14_mint-authority-overrides.toml");
+    let config = toml::from_str::<EphemeralConfig>(toml).unwrap();
+    assert_eq!(
+        config,
+        EphemeralConfig {
+            accounts: AccountsConfig {
+                lifecycle: LifecycleMode::Ephemeral,
+                mint_authority_overrides: vec![MintAuthorityOverride {
+                    mint: pubkey!(
+                        "4zMMC9srt5Ri5X14GAgXhaHii3GnPAEERYPJgZJDncDU"
+                    ),
+                }],
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    );
+}
+
+#[test]
+fn test_clock_sync_toml() {
+    let toml = include_str!("fixtures/15_clock-sync.toml");
+    let config = toml::from_str::<EphemeralConfig>(toml).unwrap();
+    assert_eq!(
+        config,
+        EphemeralConfig {
+            validator: ValidatorConfig {
+                clock_sync: ClockSyncConfig {
+                    enabled: true,
+                    poll_interval_millis: 500,
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    );
+}
+
+#[test]
+fn test_replica_toml() {
+    let toml = include_str!("fixtures/16_replica.toml");
+    let config = toml::from_str::<EphemeralConfig>(toml).unwrap();
+    assert_eq!(
+        config,
+        EphemeralConfig {
+            accounts: AccountsConfig {
+                lifecycle: LifecycleMode::Offline,
+                ..Default::default()
+            },
+            replica: ReplicaConfig {
+                enabled: true,
+                primary_geyser_grpc_url: "http://10.0.0.1:10000".to_string(),
+                reconnect_delay_millis: 250,
+            },
+            ..Default::default()
+        }
+    );
+}
+
+#[test]
+fn test_firewall_toml() {
+    let toml = include_str!("fixtures/17_firewall.toml");
+    let config = toml::from_str::<EphemeralConfig>(toml).unwrap();
+    assert_eq!(
+        config,
+        EphemeralConfig {
+            accounts: AccountsConfig {
+                lifecycle: LifecycleMode::Ephemeral,
+                ..Default::default()
+            },
+            firewall: FirewallConfig {
+                denied_programs: vec![pubkey!(
+                    "BPFLoaderUpgradeab1e11111111111111111111111"
+                )],
+                allowed_fee_payers: vec![pubkey!(
+                    "mAGicPQYBMvcYveUZA5F5UNNwyHvfYh5xkLS2Fr1mev"
+                )],
+                min_fee_payer_lamports: 100_000_000,
+                max_transaction_size: 1024,
+            },
+            ..Default::default()
+        }
+    );
+}
+
+#[test]
+fn test_firewall_toml_invalid_pubkey() {
+    let toml = r#"
+[firewall]
+denied_programs = ["not-a-pubkey"]
+"#;
+
+    let res = toml::from_str::<EphemeralConfig>(toml);
+    assert!(res.is_err());
+}
+
 #[test]
 fn test_custom_invalid_remote() {
     let toml = r#"
@@ -214,3 +400,597 @@ payer = { init_sol = 2000, init_lamports = 300_000 }
     let config = toml::from_str::<EphemeralConfig>(toml).unwrap();
     assert!(config.accounts.payer.try_init_lamports().is_err());
 }
+
+#[test]
+fn test_fee_payer_spend_limit_toml() {
+    let toml = include_str!("fixtures/18_fee-payer-spend-limit.toml");
+    let config = toml::from_str::<EphemeralConfig>(toml).unwrap();
+    assert_eq!(
+        config,
+        EphemeralConfig {
+            validator: ValidatorConfig {
+                fee_payer_spend_limit: FeePayerSpendLimitConfig {
+                    max_lamports: Some(LAMPORTS_PER_SOL),
+                    window_secs: 30,
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    );
+}
+
+#[test]
+fn test_gasless_toml() {
+    let toml = include_str!("fixtures/19_gasless.toml");
+    let config = toml::from_str::<EphemeralConfig>(toml).unwrap();
+    assert_eq!(
+        config,
+        EphemeralConfig {
+            gasless: GaslessConfig {
+                enabled: true,
+                sponsored_programs: vec![pubkey!(
+                    "wormH7q6y9EBUUL6EyptYhryxs6HoJg8sPK3LMfoNf4"
+                )],
+                quota_lamports: 250_000,
+                quota_window_secs: 600,
+            },
+            ..Default::default()
+        }
+    );
+}
+
+#[test]
+fn test_chaos_toml() {
+    let toml = include_str!("fixtures/20_chaos.toml");
+    let config = toml::from_str::<EphemeralConfig>(toml).unwrap();
+    assert_eq!(
+        config,
+        EphemeralConfig {
+            chaos: ChaosConfig {
+                rpc_delay_ms: 250,
+                fail_commit_send_every: 3,
+                drop_websocket_every: 10,
+            },
+            ..Default::default()
+        }
+    );
+}
+
+#[test]
+fn test_mainnet_rent_semantics_toml() {
+    let toml = include_str!("fixtures/21_mainnet-rent-semantics.toml");
+    let config = toml::from_str::<EphemeralConfig>(toml).unwrap();
+    assert_eq!(
+        config,
+        EphemeralConfig {
+            accounts: AccountsConfig {
+                remote: RemoteConfig::Mainnet,
+                lifecycle: LifecycleMode::Ephemeral,
+                mainnet_rent_semantics: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    );
+}
+
+#[test]
+fn test_account_dump_toml() {
+    let toml = include_str!("fixtures/22_account-dump.toml");
+    let config = toml::from_str::<EphemeralConfig>(toml).unwrap();
+    assert_eq!(
+        config,
+        EphemeralConfig {
+            accounts: AccountsConfig {
+                lifecycle: LifecycleMode::Ephemeral,
+                dump: AccountDumpConfig {
+                    audit_log: Some(
+                        "/var/log/magicblock/account-dumps.jsonl".to_string()
+                    ),
+                    metrics: true,
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    );
+}
+
+#[test]
+fn test_genesis_toml() {
+    let toml = include_str!("fixtures/23_genesis.toml");
+    let config = toml::from_str::<EphemeralConfig>(toml).unwrap();
+    assert_eq!(
+        config,
+        EphemeralConfig {
+            accounts: AccountsConfig {
+                lifecycle: LifecycleMode::Offline,
+                ..Default::default()
+            },
+            genesis: GenesisConfig {
+                activate_all_features: false,
+                features: vec![
+                    pubkey!("5eYk1TwtEwsUTqF9FHhm6tdmvu45csFkKbC4W217TAts"),
+                    pubkey!("EgrsyMAsGYMKjcnTvnzmpJtq3hpmXznKQXk21154TsaS"),
+                ],
+                builtins: vec![GenesisBuiltin::BpfLoader],
+                accounts: vec![
+                    GenesisAccountConfig {
+                        pubkey: pubkey!(
+                            "wormH7q6y9EBUUL6EyptYhryxs6HoJg8sPK3LMfoNf4"
+                        ),
+                        lamports: LAMPORTS_PER_SOL,
+                        owner: system_program::id(),
+                        executable: false,
+                        data_path: None,
+                    },
+                    GenesisAccountConfig {
+                        pubkey: pubkey!(
+                            "4zMMC9srt5Ri5X14GAgXhaHii3GnPAEERYPJgZJDncDU"
+                        ),
+                        lamports: 1_461_600,
+                        owner: pubkey!(
+                            "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"
+                        ),
+                        executable: false,
+                        data_path: Some("mint.bin".to_string()),
+                    },
+                ],
+            },
+            ..Default::default()
+        }
+    );
+}
+
+#[test]
+fn test_rpc_economics_toml() {
+    let toml = include_str!("fixtures/24_rpc-economics.toml");
+    let config = toml::from_str::<EphemeralConfig>(toml).unwrap();
+    assert_eq!(
+        config,
+        EphemeralConfig {
+            accounts: AccountsConfig {
+                lifecycle: LifecycleMode::Ephemeral,
+                ..Default::default()
+            },
+            rpc: RpcConfig {
+                economics: RpcEconomicsConfig {
+                    supply_lamports: Some(500_000_000 * LAMPORTS_PER_SOL),
+                    vote_account_stake_lamports: 1_000 * LAMPORTS_PER_SOL,
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    );
+}
+
+#[test]
+fn test_commit_send_toml() {
+    let toml = include_str!("fixtures/25_commit-send.toml");
+    let config = toml::from_str::<EphemeralConfig>(toml).unwrap();
+    assert_eq!(
+        config,
+        EphemeralConfig {
+            accounts: AccountsConfig {
+                remote: RemoteConfig::Mainnet,
+                lifecycle: LifecycleMode::Ephemeral,
+                commit_send: CommitSendStrategy::JitoBundle {
+                    block_engine_url: Url::parse(
+                        "https://mainnet.block-engine.jito.wtf/api/v1/bundles"
+                    )
+                    .unwrap(),
+                    tip_lamports: 10_000,
+                    tip_accounts: vec![pubkey!(
+                        "96gYZGLnJYVFmbjzopPSU6QiEV5fGqZNyN9nmNhvrZU5"
+                    )],
+                },
+                simulate_commits: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    );
+}
+
+#[test]
+fn test_bind_addresses_toml() {
+    let toml = include_str!("fixtures/26_bind-addresses.toml");
+    let config = toml::from_str::<EphemeralConfig>(toml).unwrap();
+    assert_eq!(
+        config,
+        EphemeralConfig {
+            rpc: RpcConfig {
+                addr: IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+                port: 8899,
+                pubsub_addr: Some(IpAddr::V6(Ipv6Addr::UNSPECIFIED)),
+                pubsub_port: Some(8900),
+                ..Default::default()
+            },
+            geyser_grpc: GeyserGrpcConfig {
+                addr: IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+                port: 10_000,
+            },
+            metrics: MetricsConfig {
+                service: MetricsServiceConfig {
+                    addr: IpAddr::V4(Ipv4Addr::LOCALHOST),
+                    port: 9_000,
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    );
+    assert_eq!(
+        config.rpc.pubsub_socket_addr(),
+        "[::]:8900".parse().unwrap()
+    );
+}
+
+#[test]
+fn test_pubsub_socket_addr_defaults_to_rpc() {
+    let config = RpcConfig {
+        addr: IpAddr::V4(Ipv4Addr::LOCALHOST),
+        port: 7799,
+        ..Default::default()
+    };
+    assert_eq!(
+        config.pubsub_socket_addr(),
+        "127.0.0.1:7800".parse().unwrap()
+    );
+}
+
+#[test]
+fn test_override_from_args() {
+    let args = [
+        "--rpc-addr",
+        "127.0.0.1",
+        "--rpc-port=7799",
+        "--rpc-pubsub-port",
+        "7800",
+        "--geyser-grpc-port",
+        "11000",
+        "--metrics-addr",
+        "::1",
+        "--metrics-port=9100",
+    ]
+    .map(String::from);
+    let config = EphemeralConfig::default()
+        .override_from_args(&args)
+        .unwrap();
+    assert_eq!(
+        config,
+        EphemeralConfig {
+            rpc: RpcConfig {
+                addr: IpAddr::V4(Ipv4Addr::LOCALHOST),
+                port: 7799,
+                pubsub_port: Some(7800),
+                ..Default::default()
+            },
+            geyser_grpc: GeyserGrpcConfig {
+                port: 11_000,
+                ..Default::default()
+            },
+            metrics: MetricsConfig {
+                service: MetricsServiceConfig {
+                    addr: IpAddr::V6(Ipv6Addr::LOCALHOST),
+                    port: 9_100,
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    );
+}
+
+#[test]
+fn test_override_from_args_invalid() {
+    let config = EphemeralConfig::default();
+    assert!(config
+        .override_from_args(&["--rpc-port".to_string()])
+        .is_err());
+    assert!(config
+        .override_from_args(&["--rpc-port=http".to_string()])
+        .is_err());
+    assert!(config
+        .override_from_args(&["--unknown=1".to_string()])
+        .is_err());
+}
+
+#[test]
+fn test_subscriptions_toml() {
+    let toml = include_str!("fixtures/27_subscriptions.toml");
+    let config = toml::from_str::<EphemeralConfig>(toml).unwrap();
+    assert_eq!(
+        config,
+        EphemeralConfig {
+            accounts: AccountsConfig {
+                subscriptions: SubscriptionsConfig {
+                    pools: 2,
+                    shards: 4,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    );
+}
+
+#[test]
+fn test_ledger_inputs_toml() {
+    let toml = include_str!("fixtures/28_ledger-inputs.toml");
+    let config = toml::from_str::<EphemeralConfig>(toml).unwrap();
+    assert_eq!(
+        config,
+        EphemeralConfig {
+            ledger: LedgerConfig {
+                reset: false,
+                path: Some("/tmp/recorded-ledger".to_string()),
+                inputs: LedgerInputsMode::Replay,
+            },
+            ..Default::default()
+        }
+    );
+}
+
+#[test]
+fn test_fees_toml() {
+    let toml = include_str!("fixtures/29_fees.toml");
+    let config = toml::from_str::<EphemeralConfig>(toml).unwrap();
+    assert_eq!(
+        config,
+        EphemeralConfig {
+            validator: ValidatorConfig {
+                fees: FeesConfig::Fixed {
+                    lamports_per_signature: 5000,
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    );
+    assert_eq!(config.validator.fees.lamports_per_signature(), 5000);
+    assert_eq!(FeesConfig::default().lamports_per_signature(), 0);
+}
+
+#[test]
+fn test_blockhash_expiry_toml() {
+    let toml = include_str!("fixtures/30_blockhash-expiry.toml");
+    let config = toml::from_str::<EphemeralConfig>(toml).unwrap();
+    assert_eq!(
+        config,
+        EphemeralConfig {
+            validator: ValidatorConfig {
+                blockhash_expiry: BlockhashExpiryConfig::ExtendedSlots {
+                    max_age_slots: 12_000,
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    );
+
+    let toml = r#"
+[validator.blockhash_expiry]
+mode = "wall-clock"
+"#;
+    let config = toml::from_str::<EphemeralConfig>(toml).unwrap();
+    assert_eq!(
+        config.validator.blockhash_expiry,
+        BlockhashExpiryConfig::WallClock
+    );
+}
+
+#[test]
+fn test_profile_overrides_toml() {
+    let toml = include_str!("fixtures/31_profile-overrides.toml");
+    let config = EphemeralConfig::try_load_from_toml_with_profile(
+        toml,
+        None,
+        Some(ConfigProfile::Ephemeral),
+    )
+    .unwrap();
+    assert_eq!(
+        config,
+        EphemeralConfig {
+            accounts: AccountsConfig {
+                remote: RemoteConfig::Mainnet,
+                lifecycle: LifecycleMode::Ephemeral,
+                simulate_commits: true,
+                ..Default::default()
+            },
+            ledger: LedgerConfig {
+                reset: false,
+                path: Some("/var/lib/ephemeral/ledger".to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    );
+
+    // Without a profile the file is applied to the defaults
+    let config = EphemeralConfig::try_load_from_toml(toml, None).unwrap();
+    assert_eq!(config.accounts.lifecycle, LifecycleMode::default());
+    assert!(config.ledger.reset);
+}
+
+#[test]
+fn test_profiles_from_args() {
+    let args = ["config.toml", "--profile", "dev-offline", "--rpc-port=7799"]
+        .map(String::from);
+    let (profile, remaining) = ConfigProfile::extract_from_args(&args).unwrap();
+    assert_eq!(profile, Some(ConfigProfile::DevOffline));
+    assert_eq!(remaining, ["config.toml", "--rpc-port=7799"]);
+
+    let config = profile.unwrap().config();
+    assert_eq!(config.accounts.remote, RemoteConfig::Development);
+    assert_eq!(config.accounts.lifecycle, LifecycleMode::Offline);
+
+    let args = ["--profile=replica".to_string()];
+    let (profile, remaining) = ConfigProfile::extract_from_args(&args).unwrap();
+    assert_eq!(profile, Some(ConfigProfile::Replica));
+    assert!(remaining.is_empty());
+    assert!(profile.unwrap().config().replica.enabled);
+
+    let args = ["--profile=mainnet".to_string()];
+    assert!(ConfigProfile::extract_from_args(&args).is_err());
+}
+
+#[test]
+fn test_commit_concurrency_toml() {
+    let toml = include_str!("fixtures/32_commit-concurrency.toml");
+    let config = toml::from_str::<EphemeralConfig>(toml).unwrap();
+    assert_eq!(
+        config,
+        EphemeralConfig {
+            accounts: AccountsConfig {
+                lifecycle: LifecycleMode::Ephemeral,
+                commit: CommitStrategy {
+                    frequency_millis: 50,
+                    max_concurrent_commits: 4,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    );
+}
+
+#[test]
+fn test_subscriptions_poll_toml() {
+    let toml = include_str!("fixtures/33_subscriptions-poll.toml");
+    let config = toml::from_str::<EphemeralConfig>(toml).unwrap();
+    assert_eq!(
+        config,
+        EphemeralConfig {
+            accounts: AccountsConfig {
+                subscriptions: SubscriptionsConfig {
+                    poll_interval_millis: Some(2_000),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    );
+}
+
+#[test]
+fn test_idl_overrides_toml() {
+    let toml = include_str!("fixtures/34_idl-overrides.toml");
+    let config = toml::from_str::<EphemeralConfig>(toml).unwrap();
+    assert_eq!(
+        config,
+        EphemeralConfig {
+            accounts: AccountsConfig {
+                lifecycle: LifecycleMode::Ephemeral,
+                idl_overrides: vec![IdlOverride {
+                    program_id: pubkey!(
+                        "wormH7q6y9EBUUL6EyptYhryxs6HoJg8sPK3LMfoNf4"
+                    ),
+                    path: "idls/wormhole.json".to_string(),
+                }],
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    );
+}
+
+#[test]
+fn test_tpu_toml() {
+    let toml = include_str!("fixtures/35_tpu.toml");
+    let config = toml::from_str::<EphemeralConfig>(toml).unwrap();
+    assert_eq!(
+        config,
+        EphemeralConfig {
+            rpc: RpcConfig {
+                addr: IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+                port: 8899,
+                tpu_port: Some(8906),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    );
+    assert_eq!(
+        config.rpc.tpu_socket_addr(),
+        Some("0.0.0.0:8906".parse().unwrap())
+    );
+    assert_eq!(EphemeralConfig::default().rpc.tpu_socket_addr(), None);
+
+    let config = config
+        .override_from_args(&["--rpc-tpu-port=9006".to_string()])
+        .unwrap();
+    assert_eq!(config.rpc.tpu_port, Some(9006));
+}
+
+#[test]
+fn test_account_policies_toml() {
+    let toml = include_str!("fixtures/36_account-policies.toml");
+    let config = toml::from_str::<EphemeralConfig>(toml).unwrap();
+    assert_eq!(
+        config,
+        EphemeralConfig {
+            accounts: AccountsConfig {
+                lifecycle: LifecycleMode::Ephemeral,
+                policies: AccountPoliciesConfig {
+                    refresh_accounts: Some(false),
+                    commit_delegated_accounts: Some(false),
+                    process_scheduled_commits: Some(false),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    );
+}
+
+#[test]
+fn test_missing_accounts_ttl_toml() {
+    let toml = include_str!("fixtures/37_missing-accounts-ttl.toml");
+    let config = toml::from_str::<EphemeralConfig>(toml).unwrap();
+    assert_eq!(
+        config,
+        EphemeralConfig {
+            accounts: AccountsConfig {
+                lifecycle: LifecycleMode::Ephemeral,
+                missing_accounts_ttl_millis: 800,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    );
+    assert_eq!(
+        EphemeralConfig::default()
+            .accounts
+            .missing_accounts_ttl_millis,
+        0
+    );
+}
+
+#[test]
+fn test_require_delegated_hydration_toml() {
+    let toml = include_str!("fixtures/38_require-delegated-hydration.toml");
+    let config = toml::from_str::<EphemeralConfig>(toml).unwrap();
+    assert_eq!(
+        config,
+        EphemeralConfig {
+            accounts: AccountsConfig {
+                lifecycle: LifecycleMode::Ephemeral,
+                require_delegated_hydration: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    );
+    assert!(
+        !EphemeralConfig::default()
+            .accounts
+            .require_delegated_hydration
+    );
+}
@@ -4,9 +4,10 @@ use std::{
 };
 
 use magicblock_config::{
-    AccountsConfig, CommitStrategy, EphemeralConfig, GeyserGrpcConfig,
-    LedgerConfig, LifecycleMode, MetricsConfig, MetricsServiceConfig,
-    ProgramConfig, RemoteConfig, RpcConfig, ValidatorConfig,
+    AccountsConfig, CommitStrategy, EphemeralConfig, FaucetConfig,
+    GeyserGrpcConfig, LedgerConfig, LifecycleMode, MetricsConfig,
+    MetricsServiceConfig, ProgramConfig, RemoteConfig, RpcConfig,
+    TelemetryConfig, ValidatorConfig,
 };
 use solana_sdk::pubkey;
 use test_tools_core::paths::cargo_workspace_dir;
@@ -31,6 +32,7 @@ fn test_load_local_dev_with_programs_toml() {
                 commit: CommitStrategy {
                     frequency_millis: 600_000,
                     compute_unit_price: 0,
+                    ..Default::default()
                 },
                 ..Default::default()
             },
@@ -44,6 +46,7 @@ fn test_load_local_dev_with_programs_toml() {
             rpc: RpcConfig {
                 addr: IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)),
                 port: 7799,
+                ..Default::default()
             },
             geyser_grpc: GeyserGrpcConfig {
                 addr: IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)),
@@ -64,6 +67,7 @@ fn test_load_local_dev_with_programs_toml() {
                 },
                 ..Default::default()
             },
+            ..Default::default()
         }
     )
 }
@@ -96,6 +100,9 @@ fn test_load_local_dev_with_programs_toml_envs_override() {
     env::set_var("METRICS_ENABLED", "false");
     env::set_var("METRICS_PORT", "1234");
     env::set_var("METRICS_SYSTEM_METRICS_TICK_INTERVAL_SECS", "10");
+    env::set_var("FAUCET_MAX_LAMPORTS_PER_REQUEST", "5000");
+    env::set_var("TELEMETRY_ENABLED", "true");
+    env::set_var("TELEMETRY_OTLP_ENDPOINT", "http://collector:4317");
 
     let config =
         EphemeralConfig::try_load_from_file(config_file_dir.to_str().unwrap())
@@ -110,6 +117,7 @@ fn test_load_local_dev_with_programs_toml_envs_override() {
                 commit: CommitStrategy {
                     frequency_millis: 123,
                     compute_unit_price: 1,
+                    ..Default::default()
                 },
                 remote: RemoteConfig::Custom(Url::parse(base_cluster).unwrap()),
                 ..Default::default()
@@ -124,6 +132,7 @@ fn test_load_local_dev_with_programs_toml_envs_override() {
             rpc: RpcConfig {
                 addr: IpAddr::V4(Ipv4Addr::new(0, 1, 0, 1)),
                 port: 123,
+                ..Default::default()
             },
             geyser_grpc: GeyserGrpcConfig {
                 addr: IpAddr::V4(Ipv4Addr::new(0, 1, 0, 1)),
@@ -136,6 +145,7 @@ fn test_load_local_dev_with_programs_toml_envs_override() {
             ledger: LedgerConfig {
                 reset: false,
                 path: Some("/hello/world".to_string()),
+                ..Default::default()
             },
             metrics: MetricsConfig {
                 enabled: false,
@@ -145,6 +155,15 @@ fn test_load_local_dev_with_programs_toml_envs_override() {
                 },
                 system_metrics_tick_interval_secs: 10,
             },
+            faucet: FaucetConfig {
+                max_lamports_per_request: 5_000,
+                ..Default::default()
+            },
+            telemetry: TelemetryConfig {
+                enabled: true,
+                otlp_endpoint: "http://collector:4317".to_string(),
+                ..Default::default()
+            },
         }
     );
     env::set_var("ACCOUNTS_REMOTE_WS", base_cluster_ws);
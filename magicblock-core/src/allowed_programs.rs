@@ -0,0 +1,276 @@
+use std::{
+    collections::{BTreeSet, HashSet},
+    fs, io,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::{Arc, RwLock},
+};
+
+use solana_sdk::pubkey::Pubkey;
+
+use crate::robust_lock::RobustRwLock;
+
+#[derive(Debug, Default)]
+struct AllowedProgramsState {
+    /// `None` if no allow-list is configured which allows all programs
+    program_ids: Option<HashSet<Pubkey>>,
+    /// Programs added at runtime, persisted to the sidecar file
+    added: BTreeSet<Pubkey>,
+    /// Programs removed at runtime, persisted to the sidecar file
+    removed: BTreeSet<Pubkey>,
+    sidecar_path: Option<PathBuf>,
+}
+
+impl AllowedProgramsState {
+    fn apply_changes(&mut self) {
+        if self.added.is_empty() && self.removed.is_empty() {
+            return;
+        }
+        let program_ids = self.program_ids.get_or_insert_with(HashSet::new);
+        program_ids.extend(self.added.iter().copied());
+        program_ids.retain(|program_id| !self.removed.contains(program_id));
+    }
+
+    fn persist(&self) -> io::Result<()> {
+        let Some(sidecar_path) = &self.sidecar_path else {
+            return Ok(());
+        };
+        let content = self
+            .added
+            .iter()
+            .map(|program_id| format!("+{}\n", program_id))
+            .chain(
+                self.removed
+                    .iter()
+                    .map(|program_id| format!("-{}\n", program_id)),
+            )
+            .collect::<String>();
+        fs::write(sidecar_path, content)
+    }
+}
+
+/// The programs that may be cloned from the remote cluster.
+/// Entries can be added and removed at runtime, i.e. via the admin RPC, and
+/// those changes are persisted to a sidecar file which is merged with the
+/// configured programs at startup.
+///
+/// Clones share the same state.
+#[derive(Debug, Clone, Default)]
+pub struct AllowedPrograms {
+    state: Arc<RwLock<AllowedProgramsState>>,
+}
+
+impl AllowedPrograms {
+    pub fn new(program_ids: Option<HashSet<Pubkey>>) -> Self {
+        Self {
+            state: Arc::new(RwLock::new(AllowedProgramsState {
+                program_ids,
+                ..Default::default()
+            })),
+        }
+    }
+
+    /// Loads the changes made at runtime from the [sidecar_path], one
+    /// `+<pubkey>` or `-<pubkey>` per line, and persists further changes to
+    /// it. A missing file is created once the first change is made.
+    pub fn with_sidecar_file(self, sidecar_path: PathBuf) -> io::Result<Self> {
+        let (added, removed) = read_sidecar_file(&sidecar_path)?;
+        {
+            let mut state = self.state.write_robust();
+            state.added = added;
+            state.removed = removed;
+            state.sidecar_path = Some(sidecar_path);
+            state.apply_changes();
+        }
+        Ok(self)
+    }
+
+    /// Returns `true` if the program may be cloned, all programs are allowed
+    /// when no allow-list is configured.
+    pub fn is_allowed(&self, program_id: &Pubkey) -> bool {
+        self.state
+            .read_robust()
+            .program_ids
+            .as_ref()
+            .map_or(true, |program_ids| program_ids.contains(program_id))
+    }
+
+    /// Returns the allowed programs sorted by pubkey or `None` if all programs
+    /// are allowed.
+    pub fn program_ids(&self) -> Option<Vec<Pubkey>> {
+        self.state
+            .read_robust()
+            .program_ids
+            .as_ref()
+            .map(|program_ids| {
+                let mut program_ids =
+                    program_ids.iter().copied().collect::<Vec<_>>();
+                program_ids.sort();
+                program_ids
+            })
+    }
+
+    /// Returns the programs added and removed at runtime.
+    pub fn changes(&self) -> (Vec<Pubkey>, Vec<Pubkey>) {
+        let state = self.state.read_robust();
+        (
+            state.added.iter().copied().collect(),
+            state.removed.iter().copied().collect(),
+        )
+    }
+
+    /// Adds the program to the allow-list and returns `false` if it was
+    /// already allowed.
+    /// NOTE: adding a program while no allow-list is configured restricts
+    /// cloning to that program.
+    pub fn add(&self, program_id: Pubkey) -> io::Result<bool> {
+        let mut state = self.state.write_robust();
+        let added = state
+            .program_ids
+            .get_or_insert_with(HashSet::new)
+            .insert(program_id);
+        if added {
+            state.removed.remove(&program_id);
+            state.added.insert(program_id);
+            state.persist()?;
+        }
+        Ok(added)
+    }
+
+    /// Removes the program from the allow-list and returns `false` if it
+    /// was not allowed.
+    /// The last program is never removed since an empty allow-list would
+    /// allow all programs after a restart, use [Self::is_last] to check.
+    pub fn remove(&self, program_id: &Pubkey) -> io::Result<bool> {
+        let mut state = self.state.write_robust();
+        let removed = match state.program_ids.as_mut() {
+            Some(program_ids) if program_ids.len() > 1 => {
+                program_ids.remove(program_id)
+            }
+            _ => false,
+        };
+        if removed {
+            state.added.remove(program_id);
+            state.removed.insert(*program_id);
+            state.persist()?;
+        }
+        Ok(removed)
+    }
+
+    /// Returns `true` if the program is the only one in the allow-list.
+    pub fn is_last(&self, program_id: &Pubkey) -> bool {
+        self.state.read_robust().program_ids.as_ref().is_some_and(
+            |program_ids| {
+                program_ids.len() == 1 && program_ids.contains(program_id)
+            },
+        )
+    }
+}
+
+fn read_sidecar_file(
+    sidecar_path: &Path,
+) -> io::Result<(BTreeSet<Pubkey>, BTreeSet<Pubkey>)> {
+    let mut added = BTreeSet::new();
+    let mut removed = BTreeSet::new();
+    if !fs::exists(sidecar_path)? {
+        return Ok((added, removed));
+    }
+    let invalid_line = |line: &str| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("invalid allowed program entry '{}'", line),
+        )
+    };
+    for line in fs::read_to_string(sidecar_path)?
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+    {
+        let (changes, program_id) = if let Some(id) = line.strip_prefix('+') {
+            (&mut added, id)
+        } else if let Some(id) = line.strip_prefix('-') {
+            (&mut removed, id)
+        } else {
+            return Err(invalid_line(line));
+        };
+        let program_id =
+            Pubkey::from_str(program_id).map_err(|_| invalid_line(line))?;
+        changes.insert(program_id);
+    }
+    Ok((added, removed))
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn test_all_programs_allowed_without_allow_list() {
+        let allowed_programs = AllowedPrograms::new(None);
+        assert!(allowed_programs.is_allowed(&Pubkey::new_unique()));
+        assert_eq!(allowed_programs.program_ids(), None);
+    }
+
+    #[test]
+    fn test_add_and_remove_programs() {
+        let configured = Pubkey::new_unique();
+        let added = Pubkey::new_unique();
+        let allowed_programs =
+            AllowedPrograms::new(Some(HashSet::from([configured])));
+        assert!(!allowed_programs.is_allowed(&added));
+
+        assert!(allowed_programs.add(added).unwrap());
+        assert!(!allowed_programs.add(added).unwrap());
+        assert!(allowed_programs.is_allowed(&added));
+
+        assert!(allowed_programs.remove(&configured).unwrap());
+        assert!(!allowed_programs.is_allowed(&configured));
+        assert_eq!(allowed_programs.program_ids(), Some(vec![added]));
+    }
+
+    #[test]
+    fn test_last_program_is_not_removed() {
+        let program_id = Pubkey::new_unique();
+        let allowed_programs =
+            AllowedPrograms::new(Some(HashSet::from([program_id])));
+        assert!(allowed_programs.is_last(&program_id));
+        assert!(!allowed_programs.remove(&program_id).unwrap());
+        assert!(allowed_programs.is_allowed(&program_id));
+    }
+
+    #[test]
+    fn test_changes_are_merged_with_configured_programs_after_restart() {
+        let dir = TempDir::new().unwrap();
+        let sidecar_path = dir.path().join("allowed-programs.txt");
+        let kept = Pubkey::new_unique();
+        let removed = Pubkey::new_unique();
+        let added = Pubkey::new_unique();
+        let configured = Some(HashSet::from([kept, removed]));
+
+        let allowed_programs = AllowedPrograms::new(configured.clone())
+            .with_sidecar_file(sidecar_path.clone())
+            .unwrap();
+        allowed_programs.add(added).unwrap();
+        allowed_programs.remove(&removed).unwrap();
+
+        let restarted = AllowedPrograms::new(configured)
+            .with_sidecar_file(sidecar_path)
+            .unwrap();
+        let mut expected = vec![kept, added];
+        expected.sort();
+        assert_eq!(restarted.program_ids(), Some(expected));
+        assert_eq!(restarted.changes(), (vec![added], vec![removed]));
+    }
+
+    #[test]
+    fn test_invalid_sidecar_file() {
+        let dir = TempDir::new().unwrap();
+        let sidecar_path = dir.path().join("allowed-programs.txt");
+        fs::write(&sidecar_path, "not-a-change\n").unwrap();
+        assert!(AllowedPrograms::new(None)
+            .with_sidecar_file(sidecar_path)
+            .is_err());
+    }
+}
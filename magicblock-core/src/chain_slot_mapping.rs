@@ -0,0 +1,124 @@
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, RwLock},
+};
+
+use solana_sdk::clock::Slot;
+
+use crate::robust_lock::RobustRwLock;
+
+/// The number of ephemeral slots for which the mapping is kept, at 50ms
+/// slots this covers a bit more than an hour.
+const MAX_MAPPED_SLOTS: usize = 100_000;
+
+#[derive(Debug, Default)]
+struct ChainSlotState {
+    latest_chain_slot: Option<Slot>,
+    /// Maps each ephemeral slot to the latest slot of the base chain
+    /// observed when the ephemeral slot started.
+    chain_slots: BTreeMap<Slot, Slot>,
+}
+
+/// Maps the slots of the ephemeral bank to the slots of the base chain.
+/// The base chain slots are fed from the slot subscriptions to the remote
+/// cluster while the ephemeral slots are recorded as the bank advances.
+///
+/// Clones share the same state.
+#[derive(Debug, Clone, Default)]
+pub struct ChainSlotMapping {
+    state: Arc<RwLock<ChainSlotState>>,
+}
+
+impl ChainSlotMapping {
+    /// Records a slot reported by the base chain, slots older than the
+    /// latest one are ignored since subscriptions to multiple nodes report
+    /// the same slots at slightly different times.
+    pub fn update_chain_slot(&self, chain_slot: Slot) {
+        let mut state = self.state.write_robust();
+        if state
+            .latest_chain_slot
+            .map_or(true, |slot| slot < chain_slot)
+        {
+            state.latest_chain_slot = Some(chain_slot);
+        }
+    }
+
+    pub fn latest_chain_slot(&self) -> Option<Slot> {
+        self.state.read_robust().latest_chain_slot
+    }
+
+    /// Maps the [ephemeral_slot] that just started to the latest slot of the
+    /// base chain and returns it, unless no base chain slot is known yet.
+    pub fn record_ephemeral_slot(&self, ephemeral_slot: Slot) -> Option<Slot> {
+        let mut state = self.state.write_robust();
+        let chain_slot = state.latest_chain_slot?;
+        state.chain_slots.insert(ephemeral_slot, chain_slot);
+        while state.chain_slots.len() > MAX_MAPPED_SLOTS {
+            state.chain_slots.pop_first();
+        }
+        Some(chain_slot)
+    }
+
+    /// Returns the slot of the base chain that was the latest known one
+    /// during the [ephemeral_slot].
+    pub fn chain_slot_at(&self, ephemeral_slot: Slot) -> Option<Slot> {
+        self.state
+            .read_robust()
+            .chain_slots
+            .range(..=ephemeral_slot)
+            .next_back()
+            .map(|(_, chain_slot)| *chain_slot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ephemeral_slots_before_first_chain_slot_are_not_mapped() {
+        let mapping = ChainSlotMapping::default();
+        assert_eq!(mapping.record_ephemeral_slot(1), None);
+        assert_eq!(mapping.chain_slot_at(1), None);
+
+        mapping.update_chain_slot(1_000);
+        assert_eq!(mapping.record_ephemeral_slot(2), Some(1_000));
+        assert_eq!(mapping.chain_slot_at(1), None);
+        assert_eq!(mapping.chain_slot_at(2), Some(1_000));
+    }
+
+    #[test]
+    fn test_chain_slot_at_ephemeral_slot() {
+        let mapping = ChainSlotMapping::default();
+        mapping.update_chain_slot(1_000);
+        mapping.record_ephemeral_slot(1);
+        mapping.record_ephemeral_slot(2);
+        mapping.update_chain_slot(1_001);
+        mapping.record_ephemeral_slot(3);
+
+        assert_eq!(mapping.chain_slot_at(1), Some(1_000));
+        assert_eq!(mapping.chain_slot_at(2), Some(1_000));
+        assert_eq!(mapping.chain_slot_at(3), Some(1_001));
+        // Slots that were not recorded yet map to the latest recorded one
+        assert_eq!(mapping.chain_slot_at(10), Some(1_001));
+    }
+
+    #[test]
+    fn test_older_chain_slots_are_ignored() {
+        let mapping = ChainSlotMapping::default();
+        mapping.update_chain_slot(1_001);
+        mapping.update_chain_slot(1_000);
+        assert_eq!(mapping.latest_chain_slot(), Some(1_001));
+    }
+
+    #[test]
+    fn test_mapping_is_bounded() {
+        let mapping = ChainSlotMapping::default();
+        mapping.update_chain_slot(1_000);
+        for slot in 0..=MAX_MAPPED_SLOTS as Slot {
+            mapping.record_ephemeral_slot(slot);
+        }
+        assert_eq!(mapping.chain_slot_at(0), None);
+        assert_eq!(mapping.chain_slot_at(1), Some(1_000));
+    }
+}
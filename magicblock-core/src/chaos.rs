@@ -0,0 +1,117 @@
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+#[derive(Debug, Default)]
+struct ChaosCounters {
+    commit_sends: AtomicU64,
+    websocket_updates: AtomicU64,
+}
+
+/// Injects failures into the components talking to the remote cluster so
+/// that integration tests can assert how they recover without relying on
+/// real network flakiness.
+/// Failures are injected on every n-th request instead of randomly, which
+/// makes the tests deterministic.
+///
+/// Clones share the same counters, all failures are disabled by default.
+/// Failures can only be configured with the `dev-context-only-utils`
+/// feature enabled, which production builds must not enable.
+#[derive(Debug, Clone, Default)]
+pub struct ChaosInjector {
+    rpc_delay: Duration,
+    fail_commit_send_every: u32,
+    drop_websocket_every: u32,
+    counters: Arc<ChaosCounters>,
+}
+
+impl ChaosInjector {
+    /// - `rpc_delay`: delay added before each account fetch and commit send
+    /// - `fail_commit_send_every`: fail every n-th commit send, `0` disables
+    /// - `drop_websocket_every`: drop the websocket connection on every n-th
+    ///   account update, `0` disables
+    #[cfg(any(test, feature = "dev-context-only-utils"))]
+    pub fn new(
+        rpc_delay: Duration,
+        fail_commit_send_every: u32,
+        drop_websocket_every: u32,
+    ) -> Self {
+        Self {
+            rpc_delay,
+            fail_commit_send_every,
+            drop_websocket_every,
+            counters: Default::default(),
+        }
+    }
+
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.rpc_delay.is_zero()
+            || self.fail_commit_send_every > 0
+            || self.drop_websocket_every > 0
+    }
+
+    /// Returns the delay the response of an RPC request should be held back.
+    pub fn rpc_delay(&self) -> Option<Duration> {
+        (!self.rpc_delay.is_zero()).then_some(self.rpc_delay)
+    }
+
+    /// Returns `true` if the commit that is about to be sent should fail.
+    pub fn inject_commit_send_failure(&self) -> bool {
+        is_nth(&self.counters.commit_sends, self.fail_commit_send_every)
+    }
+
+    /// Returns `true` if the websocket connection which just received an
+    /// account update should be dropped.
+    pub fn inject_websocket_drop(&self) -> bool {
+        is_nth(&self.counters.websocket_updates, self.drop_websocket_every)
+    }
+}
+
+fn is_nth(counter: &AtomicU64, every: u32) -> bool {
+    if every == 0 {
+        return false;
+    }
+    let count = counter.fetch_add(1, Ordering::Relaxed) + 1;
+    count % every as u64 == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disabled_never_injects() {
+        let chaos = ChaosInjector::disabled();
+        assert!(!chaos.is_enabled());
+        assert!(chaos.rpc_delay().is_none());
+        for _ in 0..10 {
+            assert!(!chaos.inject_commit_send_failure());
+            assert!(!chaos.inject_websocket_drop());
+        }
+    }
+
+    #[test]
+    fn test_injects_every_nth_failure() {
+        let chaos = ChaosInjector::new(Duration::from_millis(100), 3, 2);
+        assert!(chaos.is_enabled());
+        assert_eq!(chaos.rpc_delay(), Some(Duration::from_millis(100)));
+
+        let commit_failures = (0..6)
+            .map(|_| chaos.clone().inject_commit_send_failure())
+            .collect::<Vec<_>>();
+        assert_eq!(commit_failures, [false, false, true, false, false, true]);
+
+        let websocket_drops = (0..4)
+            .map(|_| chaos.inject_websocket_drop())
+            .collect::<Vec<_>>();
+        assert_eq!(websocket_drops, [false, true, false, true]);
+    }
+}
@@ -0,0 +1,119 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::robust_lock::RobustMutex;
+
+#[derive(Debug, Default)]
+struct CircuitState {
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+}
+
+/// Tracks failures of requests to the remote cluster.
+/// After [Self::failure_threshold] consecutive failures the circuit opens
+/// and requests should not be attempted for [Self::open_duration].
+/// Once that passed the circuit is half open, i.e. requests are allowed again,
+/// but a single failure opens it again while a success closes it.
+///
+/// Clones share the same state, a threshold of `0` disables the breaker.
+#[derive(Debug, Clone, Default)]
+pub struct CircuitBreaker {
+    failure_threshold: u32,
+    open_duration: Duration,
+    state: Arc<Mutex<CircuitState>>,
+}
+
+impl CircuitBreaker {
+    pub fn new(failure_threshold: u32, open_duration: Duration) -> Self {
+        Self {
+            failure_threshold,
+            open_duration,
+            state: Default::default(),
+        }
+    }
+
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.failure_threshold > 0
+    }
+
+    /// Returns `true` if requests should not be attempted at this point.
+    pub fn is_open(&self) -> bool {
+        if !self.is_enabled() {
+            return false;
+        }
+        let state = self.state.lock_robust();
+        state
+            .opened_at
+            .map_or(false, |opened_at| opened_at.elapsed() < self.open_duration)
+    }
+
+    pub fn record_success(&self) {
+        if !self.is_enabled() {
+            return;
+        }
+        let mut state = self.state.lock_robust();
+        state.consecutive_failures = 0;
+        state.opened_at = None;
+    }
+
+    /// Records a failed request and returns `true` if that opened the circuit.
+    pub fn record_failure(&self) -> bool {
+        if !self.is_enabled() {
+            return false;
+        }
+        let mut state = self.state.lock_robust();
+        state.consecutive_failures =
+            state.consecutive_failures.saturating_add(1);
+        let half_open = state.opened_at.is_some();
+        if half_open || state.consecutive_failures >= self.failure_threshold {
+            state.opened_at = Some(Instant::now());
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disabled_never_opens() {
+        let breaker = CircuitBreaker::disabled();
+        for _ in 0..10 {
+            assert!(!breaker.record_failure());
+        }
+        assert!(!breaker.is_open());
+    }
+
+    #[test]
+    fn test_opens_after_threshold_and_closes_on_success() {
+        let breaker = CircuitBreaker::new(3, Duration::from_secs(60));
+        assert!(!breaker.record_failure());
+        assert!(!breaker.record_failure());
+        assert!(!breaker.is_open());
+
+        assert!(breaker.record_failure());
+        assert!(breaker.clone().is_open());
+
+        breaker.record_success();
+        assert!(!breaker.is_open());
+    }
+
+    #[test]
+    fn test_half_open_reopens_on_failure() {
+        let breaker = CircuitBreaker::new(2, Duration::ZERO);
+        breaker.record_failure();
+        assert!(breaker.record_failure());
+        // The open duration passed, thus the circuit is half open
+        assert!(!breaker.is_open());
+        assert!(breaker.record_failure());
+    }
+}
@@ -0,0 +1,10 @@
+use solana_sdk::clock::{DEFAULT_MS_PER_SLOT, MAX_RECENT_BLOCKHASHES};
+
+/// How long blockhashes stay valid and processed transactions are remembered
+/// to detect duplicates, matches the window of solana validators.
+pub const DEFAULT_TRANSACTION_EXPIRATION_MILLIS: u64 =
+    DEFAULT_MS_PER_SLOT * MAX_RECENT_BLOCKHASHES as u64;
+
+/// The maximum number of slots a commit may be delayed by, one hour at the
+/// default of 50ms per slot.
+pub const DEFAULT_MAX_COMMIT_DELAY_SLOTS: u64 = 72_000;
@@ -0,0 +1,138 @@
+use std::fmt;
+
+/// Machine-readable codes of the errors surfaced to clients.
+/// They are included in the `data` of JSON-RPC errors so that client SDKs
+/// can branch on failures instead of parsing the error messages.
+/// Codes are stable once released, new ones are only ever added.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MagicErrorCode {
+    // Cloning accounts from the remote cluster
+    CloneBlocklisted,
+    CloneNotAllowed,
+    CloneProgramNotAllowed,
+    CloneFeePayerNotAllowed,
+    CloneUndelegatedNotAllowed,
+    CloneDelegatedNotAllowed,
+    CloneProgramAccountNotAllowed,
+    CloneLocallyOverridden,
+    CloneWhileHydrating,
+    CloneFetchFailed,
+    CloneRemoteUnavailable,
+    CloneSlotNotReached,
+    CloneProgramDataNotFound,
+    CloneValidationFailed,
+    // Dumping cloned accounts into the bank
+    DumpTransactionFailed,
+    // Modifying accounts
+    MutatorProgramDataNotFound,
+    MutatorInvalidProgramData,
+    MutatorOverridesMismatch,
+    MutatorAccountDataTooLarge,
+    MutatorOwnerNotAProgram,
+    MutatorExecutableNotOwnedByLoader,
+    MutatorInvalidPubkey,
+    // Committing accounts to the remote cluster
+    CommitBlockhashUnavailable,
+    CommitBlockhashExpired,
+    CommitSendFailed,
+    CommitTooManyCommittees,
+    CommitTransactionFailed,
+    CommitTransactionTooLarge,
+    // Talking to the remote cluster
+    RemoteRpcFailed,
+    RemoteInvalidUrl,
+    RemoteDisabled,
+    // Handling RPC requests
+    RpcShuttingDown,
+    RpcTransactionRejected,
+    RpcBundleFailed,
+    // Failures of the validator itself
+    Internal,
+}
+
+impl MagicErrorCode {
+    pub fn as_str(&self) -> &'static str {
+        use MagicErrorCode::*;
+        match self {
+            CloneBlocklisted => "CLONE_BLOCKLISTED",
+            CloneNotAllowed => "CLONE_NOT_ALLOWED",
+            CloneProgramNotAllowed => "CLONE_PROGRAM_NOT_ALLOWED",
+            CloneFeePayerNotAllowed => "CLONE_FEE_PAYER_NOT_ALLOWED",
+            CloneUndelegatedNotAllowed => "CLONE_UNDELEGATED_NOT_ALLOWED",
+            CloneDelegatedNotAllowed => "CLONE_DELEGATED_NOT_ALLOWED",
+            CloneProgramAccountNotAllowed => {
+                "CLONE_PROGRAM_ACCOUNT_NOT_ALLOWED"
+            }
+            CloneLocallyOverridden => "CLONE_LOCALLY_OVERRIDDEN",
+            CloneWhileHydrating => "CLONE_WHILE_HYDRATING",
+            CloneFetchFailed => "CLONE_FETCH_FAILED",
+            CloneRemoteUnavailable => "CLONE_REMOTE_UNAVAILABLE",
+            CloneSlotNotReached => "CLONE_SLOT_NOT_REACHED",
+            CloneProgramDataNotFound => "CLONE_PROGRAM_DATA_NOT_FOUND",
+            CloneValidationFailed => "CLONE_VALIDATION_FAILED",
+            DumpTransactionFailed => "DUMP_TRANSACTION_FAILED",
+            MutatorProgramDataNotFound => "MUTATOR_PROGRAM_DATA_NOT_FOUND",
+            MutatorInvalidProgramData => "MUTATOR_INVALID_PROGRAM_DATA",
+            MutatorOverridesMismatch => "MUTATOR_OVERRIDES_MISMATCH",
+            MutatorAccountDataTooLarge => "MUTATOR_ACCOUNT_DATA_TOO_LARGE",
+            MutatorOwnerNotAProgram => "MUTATOR_OWNER_NOT_A_PROGRAM",
+            MutatorExecutableNotOwnedByLoader => {
+                "MUTATOR_EXECUTABLE_NOT_OWNED_BY_LOADER"
+            }
+            MutatorInvalidPubkey => "MUTATOR_INVALID_PUBKEY",
+            CommitBlockhashUnavailable => "COMMIT_BLOCKHASH_UNAVAILABLE",
+            CommitBlockhashExpired => "COMMIT_BLOCKHASH_EXPIRED",
+            CommitSendFailed => "COMMIT_SEND_FAILED",
+            CommitTooManyCommittees => "COMMIT_TOO_MANY_COMMITTEES",
+            CommitTransactionFailed => "COMMIT_TRANSACTION_FAILED",
+            CommitTransactionTooLarge => "COMMIT_TRANSACTION_TOO_LARGE",
+            RemoteRpcFailed => "REMOTE_RPC_FAILED",
+            RemoteInvalidUrl => "REMOTE_INVALID_URL",
+            RemoteDisabled => "REMOTE_DISABLED",
+            RpcShuttingDown => "RPC_SHUTTING_DOWN",
+            RpcTransactionRejected => "RPC_TRANSACTION_REJECTED",
+            RpcBundleFailed => "RPC_BUNDLE_FAILED",
+            Internal => "INTERNAL",
+        }
+    }
+}
+
+impl fmt::Display for MagicErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Implemented by the errors which can be surfaced to clients.
+pub trait HasErrorCode {
+    fn error_code(&self) -> MagicErrorCode;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codes_are_screaming_snake_case_of_variants() {
+        use MagicErrorCode::*;
+        for code in [
+            CloneBlocklisted,
+            CloneProgramAccountNotAllowed,
+            MutatorExecutableNotOwnedByLoader,
+            CommitBlockhashExpired,
+            RpcShuttingDown,
+        ] {
+            let expected = format!("{:?}", code).chars().enumerate().fold(
+                String::new(),
+                |mut acc, (idx, c)| {
+                    if c.is_uppercase() && idx > 0 {
+                        acc.push('_');
+                    }
+                    acc.push(c.to_ascii_uppercase());
+                    acc
+                },
+            );
+            assert_eq!(code.as_str(), expected);
+        }
+    }
+}
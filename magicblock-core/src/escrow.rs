@@ -0,0 +1,14 @@
+use serde::{Deserialize, Serialize};
+use solana_sdk::{clock::Slot, pubkey::Pubkey};
+
+/// Records a transfer of lamports between two fee payer escrows inside the
+/// ephemeral which needs to be reconciled on the base layer.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EscrowSettlement {
+    pub slot: Slot,
+    pub from: Pubkey,
+    pub to: Pubkey,
+    pub lamports: u64,
+    /// The program that authorized the transfer via one of its delegated accounts
+    pub authority_program: Pubkey,
+}
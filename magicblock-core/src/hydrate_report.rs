@@ -0,0 +1,115 @@
+use std::sync::{Arc, RwLock};
+
+use solana_sdk::pubkey::Pubkey;
+
+use crate::robust_lock::RobustRwLock;
+
+/// An account that could not be hydrated at startup.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HydrateFailure {
+    pub pubkey: Pubkey,
+    /// The owner of the account in our bank
+    pub owner: Pubkey,
+    pub reason: String,
+    /// Since the chain state of the account could not be fetched we cannot
+    /// tell if it is delegated to us, thus this is set for all accounts
+    /// owned by programs, which is how delegated accounts are stored locally.
+    /// Programs and accounts owned by the system program or the delegation
+    /// program are never delegated to us.
+    pub may_be_delegated: bool,
+}
+
+/// The outcome of hydrating the accounts found in the bank at startup.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HydrateReport {
+    pub cloned: usize,
+    /// Accounts that were not cloned on purpose, i.e. since the
+    /// permissions of the cloner don't allow it
+    pub skipped: usize,
+    pub failures: Vec<HydrateFailure>,
+}
+
+impl HydrateReport {
+    pub fn failed(&self) -> usize {
+        self.failures.len()
+    }
+
+    pub fn delegated_failures(&self) -> impl Iterator<Item = &HydrateFailure> {
+        self.failures
+            .iter()
+            .filter(|failure| failure.may_be_delegated)
+    }
+
+    pub fn has_delegated_failures(&self) -> bool {
+        self.delegated_failures().next().is_some()
+    }
+}
+
+/// Holds the report of the last hydration, which is none if the validator
+/// did not hydrate, i.e. since it started with a fresh ledger.
+///
+/// Clones share the same report.
+#[derive(Debug, Clone, Default)]
+pub struct SharedHydrateReport {
+    report: Arc<RwLock<Option<HydrateReport>>>,
+}
+
+impl SharedHydrateReport {
+    pub fn set(&self, report: HydrateReport) {
+        self.report.write_robust().replace(report);
+    }
+
+    pub fn get(&self) -> Option<HydrateReport> {
+        self.report.read_robust().clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn failure(may_be_delegated: bool) -> HydrateFailure {
+        HydrateFailure {
+            pubkey: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            reason: "fetch failed".to_string(),
+            may_be_delegated,
+        }
+    }
+
+    #[test]
+    fn test_report_without_delegated_failures() {
+        let report = HydrateReport {
+            cloned: 2,
+            skipped: 1,
+            failures: vec![failure(false)],
+        };
+        assert_eq!(report.failed(), 1);
+        assert!(!report.has_delegated_failures());
+    }
+
+    #[test]
+    fn test_report_with_delegated_failures() {
+        let report = HydrateReport {
+            cloned: 2,
+            skipped: 1,
+            failures: vec![failure(false), failure(true)],
+        };
+        assert_eq!(report.failed(), 2);
+        assert!(report.has_delegated_failures());
+        assert_eq!(report.delegated_failures().count(), 1);
+    }
+
+    #[test]
+    fn test_shared_report_is_shared_by_clones() {
+        let shared = SharedHydrateReport::default();
+        assert_eq!(shared.get(), None);
+
+        let report = HydrateReport {
+            cloned: 3,
+            ..Default::default()
+        };
+        shared.clone().set(report.clone());
+        assert_eq!(shared.get(), Some(report));
+    }
+}
@@ -1,3 +1,12 @@
+pub mod allowed_programs;
+pub mod chain_slot_mapping;
+pub mod chaos;
+pub mod circuit_breaker;
+pub mod consts;
+pub mod error_code;
+pub mod escrow;
+pub mod hydrate_report;
+pub mod robust_lock;
 pub mod traits;
 
 pub mod magic_program {
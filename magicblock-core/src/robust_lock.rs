@@ -0,0 +1,104 @@
+use std::{
+    any::type_name,
+    sync::{
+        LockResult, Mutex, MutexGuard, RwLock, RwLockReadGuard,
+        RwLockWriteGuard,
+    },
+};
+
+use log::*;
+
+// -----------------
+// Robust Locks
+// -----------------
+// A std lock is poisoned when a thread panics while holding it and every
+// later attempt to acquire it fails. Using `expect` on those attempts turns a
+// single panic into a cascade of panics across all components sharing the
+// lock.
+// The shared state is still usable in almost all cases, so instead we
+// recover the guard, log that this happened and clear the poison so that it
+// is only reported once.
+
+pub trait RobustMutex<T: ?Sized> {
+    /// Like [Mutex::lock], but recovers the guard if the mutex is poisoned.
+    fn lock_robust(&self) -> MutexGuard<'_, T>;
+}
+
+impl<T: ?Sized> RobustMutex<T> for Mutex<T> {
+    fn lock_robust(&self) -> MutexGuard<'_, T> {
+        recover(self.lock(), || self.clear_poison())
+    }
+}
+
+pub trait RobustRwLock<T: ?Sized> {
+    /// Like [RwLock::read], but recovers the guard if the lock is poisoned.
+    fn read_robust(&self) -> RwLockReadGuard<'_, T>;
+    /// Like [RwLock::write], but recovers the guard if the lock is poisoned.
+    fn write_robust(&self) -> RwLockWriteGuard<'_, T>;
+}
+
+impl<T: ?Sized> RobustRwLock<T> for RwLock<T> {
+    fn read_robust(&self) -> RwLockReadGuard<'_, T> {
+        recover(self.read(), || self.clear_poison())
+    }
+
+    fn write_robust(&self) -> RwLockWriteGuard<'_, T> {
+        recover(self.write(), || self.clear_poison())
+    }
+}
+
+fn recover<G>(result: LockResult<G>, clear_poison: impl FnOnce()) -> G {
+    result.unwrap_or_else(|poisoned| {
+        warn!(
+            "Recovering {} poisoned by a thread that panicked while holding it",
+            type_name::<G>()
+        );
+        clear_poison();
+        poisoned.into_inner()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, thread};
+
+    use super::*;
+
+    #[test]
+    fn test_mutex_recovers_from_poison() {
+        let mutex = Arc::new(Mutex::new(1));
+        let lock = mutex.clone();
+        let result = thread::spawn(move || {
+            let mut guard = lock.lock().unwrap();
+            *guard = 2;
+            panic!("panicking while holding the lock");
+        })
+        .join();
+        assert!(result.is_err());
+        assert!(mutex.is_poisoned());
+
+        assert_eq!(*mutex.lock_robust(), 2);
+        assert!(!mutex.is_poisoned());
+        *mutex.lock_robust() = 3;
+        assert_eq!(*mutex.lock().unwrap(), 3);
+    }
+
+    #[test]
+    fn test_rwlock_recovers_from_poison() {
+        let rwlock = Arc::new(RwLock::new(vec![1]));
+        let lock = rwlock.clone();
+        let result = thread::spawn(move || {
+            let mut guard = lock.write().unwrap();
+            guard.push(2);
+            panic!("panicking while holding the lock");
+        })
+        .join();
+        assert!(result.is_err());
+        assert!(rwlock.is_poisoned());
+
+        assert_eq!(*rwlock.read_robust(), vec![1, 2]);
+        assert!(!rwlock.is_poisoned());
+        rwlock.write_robust().push(3);
+        assert_eq!(*rwlock.read().unwrap(), vec![1, 2, 3]);
+    }
+}
@@ -7,14 +7,17 @@ use std::{
     },
 };
 
-use geyser_grpc_proto::prelude::{
-    geyser_server::{Geyser, GeyserServer},
-    subscribe_update::UpdateOneof,
-    CommitmentLevel, GetBlockHeightRequest, GetBlockHeightResponse,
-    GetLatestBlockhashRequest, GetLatestBlockhashResponse, GetSlotRequest,
-    GetSlotResponse, GetVersionRequest, GetVersionResponse,
-    IsBlockhashValidRequest, IsBlockhashValidResponse, PingRequest,
-    PongResponse, SubscribeRequest, SubscribeUpdate, SubscribeUpdatePing,
+use geyser_grpc_proto::{
+    geyser::FILE_DESCRIPTOR_SET,
+    prelude::{
+        geyser_server::{Geyser, GeyserServer},
+        subscribe_update::UpdateOneof,
+        CommitmentLevel, GetBlockHeightRequest, GetBlockHeightResponse,
+        GetLatestBlockhashRequest, GetLatestBlockhashResponse, GetSlotRequest,
+        GetSlotResponse, GetVersionRequest, GetVersionResponse,
+        IsBlockhashValidRequest, IsBlockhashValidResponse, PingRequest,
+        PongResponse, SubscribeRequest, SubscribeUpdate, SubscribeUpdatePing,
+    },
 };
 use log::{error, info};
 use tokio::{
@@ -106,14 +109,26 @@ impl GrpcService {
         .send_compressed(CompressionEncoding::Gzip)
         .max_decoding_message_size(max_decoding_message_size);
 
+        // gRPC reflection service, allows exploring the API with tools
+        // like grpcurl
+        let reflection_service = tonic_reflection::server::Builder::configure()
+            .register_encoded_file_descriptor_set(FILE_DESCRIPTOR_SET)
+            .register_encoded_file_descriptor_set(
+                tonic_health::pb::FILE_DESCRIPTOR_SET,
+            )
+            .build()?;
+
         // Run geyser message loop
-        let (messages_tx, messages_rx) = geyser_message_channel();
-        tokio::spawn(Self::geyser_loop(
-            messages_rx,
-            blocks_meta_tx,
-            broadcast_tx,
-            block_fail_action,
-        ));
+        let (messages_tx, mut messages_rx) = geyser_message_channel();
+        tokio::spawn(async move {
+            Self::geyser_loop(
+                &mut messages_rx,
+                blocks_meta_tx,
+                broadcast_tx,
+                block_fail_action,
+            )
+            .await
+        });
 
         // Run Server
         let shutdown = Arc::new(Notify::new());
@@ -126,11 +141,15 @@ impl GrpcService {
             server_builder
                 .http2_keepalive_interval(Some(Duration::from_secs(5)))
                 .add_service(health_service)
+                .add_service(reflection_service)
                 .add_service(service)
-                .serve_with_incoming_shutdown(
-                    incoming,
-                    shutdown_grpc.notified(),
-                )
+                .serve_with_incoming_shutdown(incoming, async move {
+                    shutdown_grpc.notified().await;
+                    // Let load balancers know before we stop accepting connections
+                    health_reporter
+                        .set_not_serving::<GeyserServer<Self>>()
+                        .await;
+                })
                 .await
         });
 
@@ -138,7 +157,7 @@ impl GrpcService {
     }
 
     pub(crate) async fn geyser_loop(
-        mut messages_rx: GeyserMessageReceiver,
+        messages_rx: &mut GeyserMessageReceiver,
         blocks_meta_tx: Option<GeyserMessageSender>,
         broadcast_tx: broadcast::Sender<(CommitmentLevel, GeyserMessages)>,
         block_fail_action: ConfigBlockFailAction,
@@ -83,12 +83,21 @@ impl MessageAccountInfo {
 pub struct MessageAccount {
     pub account: MessageAccountInfo,
     pub slot: u64,
+    /// The hash of the [Self::slot] the account was updated in
+    pub slot_hash: Option<String>,
     pub is_startup: bool,
 }
 
-impl<'a> From<(&'a ReplicaAccountInfoV3<'a>, u64, bool)> for MessageAccount {
+impl<'a> From<(&'a ReplicaAccountInfoV3<'a>, u64, Option<String>, bool)>
+    for MessageAccount
+{
     fn from(
-        (account, slot, is_startup): (&'a ReplicaAccountInfoV3<'a>, u64, bool),
+        (account, slot, slot_hash, is_startup): (
+            &'a ReplicaAccountInfoV3<'a>,
+            u64,
+            Option<String>,
+            bool,
+        ),
     ) -> Self {
         Self {
             account: MessageAccountInfo {
@@ -102,6 +111,7 @@ impl<'a> From<(&'a ReplicaAccountInfoV3<'a>, u64, bool)> for MessageAccount {
                 txn_signature: account.txn.map(|txn| *txn.signature()),
             },
             slot,
+            slot_hash,
             is_startup,
         }
     }
@@ -408,6 +418,7 @@ impl MessageRef<'_> {
                     ),
                     slot: message.slot,
                     is_startup: message.is_startup,
+                    slot_hash: message.slot_hash.clone(),
                 })
             }
             Self::Transaction(message) => {
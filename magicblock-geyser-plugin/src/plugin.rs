@@ -1,15 +1,17 @@
 #![allow(unused)]
 
 use std::{
+    collections::BTreeMap,
     sync::{
         atomic::{AtomicUsize, Ordering},
-        Arc,
+        Arc, RwLock,
     },
     time::Duration,
 };
 
 use expiring_hashmap::ExpiringHashMap as Cache;
 use log::*;
+use magicblock_core::robust_lock::RobustRwLock;
 use solana_geyser_plugin_interface::geyser_plugin_interface::{
     GeyserPlugin, GeyserPluginError, ReplicaAccountInfoVersions,
     ReplicaBlockInfoVersions, ReplicaEntryInfoVersions,
@@ -57,6 +59,8 @@ pub struct GrpcGeyserPlugin {
     rpc_service: Arc<GeyserRpcService>,
     transactions_cache: Option<Cache<Signature, GeyserMessage>>,
     accounts_cache: Option<Cache<Pubkey, GeyserMessage>>,
+    /// The hashes of the recent slots included with the account updates
+    slot_hashes: RwLock<BTreeMap<Slot, String>>,
 }
 
 impl std::fmt::Debug for GrpcGeyserPlugin {
@@ -69,6 +73,7 @@ impl std::fmt::Debug for GrpcGeyserPlugin {
             .field("rpc_service", &self.rpc_service)
             .field("transactions_cache", &tx_cache)
             .field("accounts_cache", &acc_cache)
+            .field("slot_hashes", &self.slot_hashes)
             .finish()
     }
 }
@@ -113,6 +118,7 @@ impl GrpcGeyserPlugin {
             rpc_service,
             transactions_cache,
             accounts_cache,
+            slot_hashes: RwLock::default(),
         })
     }
 
@@ -128,6 +134,20 @@ impl GrpcGeyserPlugin {
             self.inner.as_ref().expect("PluginInner is not initialized");
         f(inner)
     }
+
+    fn slot_hash(&self, slot: Slot) -> Option<String> {
+        self.slot_hashes.read_robust().get(&slot).cloned()
+    }
+
+    /// Records the hash of the [slot] and forgets the ones of slots older
+    /// than the accounts we cache.
+    fn record_slot_hash(&self, slot: Slot, hash: &str) {
+        let mut slot_hashes = self.slot_hashes.write_robust();
+        slot_hashes.insert(slot, hash.to_string());
+        let oldest_slot =
+            slot.saturating_sub(self.config.accounts_cache_max_age_slots);
+        *slot_hashes = slot_hashes.split_off(&oldest_slot);
+    }
 }
 
 impl GeyserPlugin for GrpcGeyserPlugin {
@@ -181,7 +201,8 @@ impl GeyserPlugin for GrpcGeyserPlugin {
             match Pubkey::try_from(account.pubkey) {
                 Ok(pubkey) => {
                     let message = Arc::new(Message::Account(
-                        (account, slot, is_startup).into(),
+                        (account, slot, self.slot_hash(slot), is_startup)
+                            .into(),
                     ));
                     if let Some(accounts_cache) = self.accounts_cache.as_ref() {
                         accounts_cache.insert(pubkey, message.clone(), slot);
@@ -309,6 +330,13 @@ impl GeyserPlugin for GrpcGeyserPlugin {
         &self,
         blockinfo: ReplicaBlockInfoVersions,
     ) -> PluginResult<()> {
+        // We don't produce blocks, the metadata only provides the slot hash
+        match blockinfo {
+            ReplicaBlockInfoVersions::V0_0_3(info) => {
+                self.record_slot_hash(info.slot, info.blockhash)
+            }
+            _ => warn!("Only ReplicaBlockInfoVersions::V0_0_3 is supported"),
+        }
         Ok(())
     }
 
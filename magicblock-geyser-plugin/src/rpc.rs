@@ -19,9 +19,9 @@ use geyser_grpc_proto::{
 };
 use log::*;
 use solana_sdk::{pubkey::Pubkey, signature::Signature};
-use tokio::sync::{broadcast, mpsc, Notify};
+use tokio::sync::{broadcast, mpsc, watch, Notify};
 use tokio_util::sync::CancellationToken;
-use tonic::{Result as TonicResult, Status};
+use tonic::{Code, Result as TonicResult, Status};
 
 use crate::{
     config::{ConfigBlockFailAction, ConfigGrpc},
@@ -29,8 +29,8 @@ use crate::{
     grpc::GrpcService,
     grpc_messages::{BlockMetaStorage, Message},
     types::{
-        geyser_message_channel, GeyserMessage, GeyserMessageSender,
-        GeyserMessages,
+        geyser_message_channel, GeyserMessage, GeyserMessageReceiver,
+        GeyserMessageSender, GeyserMessages,
     },
     utils::{
         short_signature, short_signature_from_sub_update,
@@ -43,6 +43,8 @@ pub struct GeyserRpcService {
     config: ConfigGrpc,
     broadcast_tx: broadcast::Sender<(CommitmentLevel, GeyserMessages)>,
     subscribe_id: AtomicU64,
+    /// Counts the restarts of the geyser message loop
+    restarts: watch::Receiver<u64>,
 
     transactions_cache: Option<SharedMap<Signature, GeyserMessage>>,
     accounts_cache: Option<SharedMap<Pubkey, GeyserMessage>>,
@@ -57,6 +59,7 @@ impl std::fmt::Debug for GeyserRpcService {
             .field("config", &self.config)
             .field("broadcast_tx", &self.broadcast_tx)
             .field("subscribe_id", &self.subscribe_id)
+            .field("restarts", &*self.restarts.borrow())
             .field("transactions_cache", &tx_cache)
             .field("accounts_cache", &acc_cache)
             .finish()
@@ -85,9 +88,11 @@ impl GeyserRpcService {
 
         // Messages to clients combined by commitment
         let (broadcast_tx, _) = broadcast::channel(config.channel_capacity);
+        let (restarts_tx, restarts) = watch::channel(0);
 
         let rpc_service = Self {
             subscribe_id: AtomicU64::new(0),
+            restarts,
             broadcast_tx: broadcast_tx.clone(),
             config: config.clone(),
             grpc_service: GrpcService::new(
@@ -101,11 +106,12 @@ impl GeyserRpcService {
 
         // Run geyser message loop
         let (messages_tx, messages_rx) = geyser_message_channel();
-        tokio::spawn(GrpcService::geyser_loop(
+        tokio::spawn(Self::supervise_geyser_loop(
             messages_rx,
             blocks_meta_tx,
             broadcast_tx.clone(),
             block_fail_action,
+            restarts_tx,
         ));
 
         // TODO: should Geyser handle shutdown or the piece that instantiates
@@ -114,6 +120,45 @@ impl GeyserRpcService {
         Ok((messages_tx, shutdown, rpc_service))
     }
 
+    /// Runs the geyser message loop and restarts it whenever it panics, i.e.
+    /// since it failed to reconstruct a block with
+    /// [ConfigBlockFailAction::Panic].
+    /// The message that was processed when it panicked is lost, thus each
+    /// restart is counted in [restarts] in order to end the subscriptions
+    /// which may have missed it.
+    async fn supervise_geyser_loop(
+        messages_rx: GeyserMessageReceiver,
+        blocks_meta_tx: Option<GeyserMessageSender>,
+        broadcast_tx: broadcast::Sender<(CommitmentLevel, GeyserMessages)>,
+        block_fail_action: ConfigBlockFailAction,
+        restarts: watch::Sender<u64>,
+    ) {
+        let messages_rx = Arc::new(tokio::sync::Mutex::new(messages_rx));
+        loop {
+            let messages_rx = messages_rx.clone();
+            let blocks_meta_tx = blocks_meta_tx.clone();
+            let broadcast_tx = broadcast_tx.clone();
+            let geyser_loop = tokio::spawn(async move {
+                // The guard is released when the loop panics
+                let mut messages_rx = messages_rx.lock().await;
+                GrpcService::geyser_loop(
+                    &mut messages_rx,
+                    blocks_meta_tx,
+                    broadcast_tx,
+                    block_fail_action,
+                )
+                .await
+            });
+            match geyser_loop.await {
+                Err(err) if err.is_panic() => {
+                    error!("Geyser message loop panicked, restarting it");
+                    restarts.send_modify(|restarts| *restarts += 1);
+                }
+                _ => break,
+            }
+        }
+    }
+
     // -----------------
     // Subscriptions
     // -----------------
@@ -245,6 +290,7 @@ impl GeyserRpcService {
             stream_tx,
             unsubscriber,
             self.broadcast_tx.subscribe(),
+            self.restarts.clone(),
             initial_messages,
         ));
 
@@ -255,14 +301,20 @@ impl GeyserRpcService {
     /// messages.
     /// By using the same transport as future messages we ensure to use the same logic WRT
     /// filters.
+    /// If the subscriber may have missed messages the stream is ended with a status for
+    /// which [is_missed_updates_status] holds.
     async fn client_loop(
         subid: u64,
         mut filter: Filter,
         stream_tx: mpsc::Sender<TonicResult<SubscribeUpdate>>,
         unsubscriber: CancellationToken,
         mut messages_rx: broadcast::Receiver<(CommitmentLevel, GeyserMessages)>,
+        mut restarts: watch::Receiver<u64>,
         mut initial_messages: Option<GeyserMessages>,
     ) {
+        // Only restarts after the subscription started concern it
+        restarts.borrow_and_update();
+
         // 1. Send initial messages that were cached from previous updates
         if let Some(messages) = initial_messages.take() {
             let exit = handle_messages(
@@ -289,9 +341,7 @@ impl GeyserRpcService {
                         },
                         Err(broadcast::error::RecvError::Lagged(_)) => {
                             info!("client #{subid}: lagged to receive geyser messages");
-                            // tokio::spawn(async move {
-                            //     let _ = stream_tx.send(Err(Status::internal("lagged"))).await;
-                            // });
+                            send_missed_updates_status(&stream_tx, Status::data_loss("lagged"));
                             break 'outer;
                         }
                     };
@@ -307,6 +357,14 @@ impl GeyserRpcService {
                         break 'outer;
                     }
                 }
+                Ok(()) = restarts.changed() => {
+                    info!("client #{subid}: geyser message loop restarted");
+                    send_missed_updates_status(
+                        &stream_tx,
+                        Status::aborted("geyser message loop restarted"),
+                    );
+                    break 'outer;
+                }
                 _ = unsubscriber.cancelled() => {
                     break 'outer;
                 }
@@ -315,6 +373,29 @@ impl GeyserRpcService {
     }
 }
 
+/// Returns `true` if the [status] ended a subscription stream since the
+/// subscriber may have missed updates, i.e. since it lagged behind or the
+/// geyser message loop restarted.
+/// The subscription needs to be established again to receive more updates.
+pub fn is_missed_updates_status(status: &Status) -> bool {
+    matches!(status.code(), Code::DataLoss | Code::Aborted)
+}
+
+/// Ends the stream with the [status] for which [is_missed_updates_status]
+/// holds.
+/// The stream is usually full at this point, thus the status is sent once
+/// the subscriber made room for it. Until then the stream stays open such
+/// that the subscriber receives the status before the stream ends.
+fn send_missed_updates_status(
+    stream_tx: &mpsc::Sender<TonicResult<SubscribeUpdate>>,
+    status: Status,
+) {
+    let stream_tx = stream_tx.clone();
+    tokio::spawn(async move {
+        let _ = stream_tx.send(Err(status)).await;
+    });
+}
+
 fn handle_messages(
     subid: u64,
     unsubscriber: CancellationToken,
@@ -343,12 +424,10 @@ fn handle_messages(
                     Ok(()) => {}
                     Err(mpsc::error::TrySendError::Full(_)) => {
                         error!("client #{subid}: lagged to send update");
-                        let stream_tx = stream_tx.clone();
-                        tokio::spawn(async move {
-                            let _ = stream_tx
-                                .send(Err(Status::internal("lagged")))
-                                .await;
-                        });
+                        send_missed_updates_status(
+                            stream_tx,
+                            Status::data_loss("lagged"),
+                        );
                         return true;
                     }
                     Err(mpsc::error::TrySendError::Closed(status)) => {
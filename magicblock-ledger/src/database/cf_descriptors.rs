@@ -36,6 +36,9 @@ pub fn cf_descriptors(
         new_cf_descriptor::<TransactionMemos>(options),
         new_cf_descriptor::<PerfSamples>(options),
         new_cf_descriptor::<AccountModDatas>(options),
+        new_cf_descriptor::<AccountJournal>(options),
+        new_cf_descriptor::<ReplayInputs>(options),
+        new_cf_descriptor::<EscrowSettlements>(options),
     ];
 
     // If the access type is Secondary, we don't need to open all of the
@@ -23,6 +23,12 @@ const TRANSACTION_MEMOS_CF: &str = "transaction_memos";
 const PERF_SAMPLES_CF: &str = "perf_samples";
 /// Column family for AccountModDatas
 const ACCOUNT_MOD_DATAS_CF: &str = "account_mod_datas";
+/// Column family for AccountJournal
+const ACCOUNT_JOURNAL_CF: &str = "account_journal";
+/// Column family for ReplayInputs
+const REPLAY_INPUTS_CF: &str = "replay_inputs";
+/// Column family for EscrowSettlements
+const ESCROW_SETTLEMENTS_CF: &str = "escrow_settlements";
 
 #[derive(Debug)]
 /// The transaction status column
@@ -95,6 +101,29 @@ pub struct PerfSamples;
 /// * value type: [`crate::database::meta::AccountModData`]
 pub struct AccountModDatas;
 
+/// The account journal column
+///
+/// * index type: `(`[`Pubkey`]`, `[`Slot`]`, `[`Signature`]`)`
+/// *                account addr,   slot,   tx signature
+/// * value type: [`magicblock_bank::account_journal::AccountJournalEntry`]
+pub struct AccountJournal;
+
+/// The column of inputs received from the remote cluster which are recorded
+/// in order to replay them
+///
+/// * index type: `(`[`Slot`]`, u64)`
+/// *                slot,   sequence number
+/// * value type: raw bytes, encoded by the recorder
+pub struct ReplayInputs;
+
+/// The column of transfers between fee payer escrows which still need to be
+/// reconciled on the base layer
+///
+/// * index type: `(`[`Slot`]`, u64)`
+/// *                slot,   sequence number
+/// * value type: [`magicblock_core::escrow::EscrowSettlement`]
+pub struct EscrowSettlements;
+
 // When adding a new column ...
 // - Add struct below and implement `Column` and `ColumnName` traits
 // - Add descriptor in Rocks::cf_descriptors() and name in Rocks::columns()
@@ -113,6 +142,9 @@ pub fn columns() -> Vec<&'static str> {
         TransactionMemos::NAME,
         PerfSamples::NAME,
         AccountModDatas::NAME,
+        AccountJournal::NAME,
+        ReplayInputs::NAME,
+        EscrowSettlements::NAME,
     ]
 }
 
@@ -657,11 +689,122 @@ impl TypedColumn for AccountModDatas {
     type Type = meta::AccountModData;
 }
 
+// -----------------
+// AccountJournal
+// -----------------
+const ACCOUNT_JOURNAL_INDEX_LEN: usize = 32 + 8 + 64;
+impl Column for AccountJournal {
+    type Index = (Pubkey, Slot, Signature);
+
+    fn key((pubkey, slot, signature): Self::Index) -> Vec<u8> {
+        let mut key = vec![0; ACCOUNT_JOURNAL_INDEX_LEN];
+        key[0..32].copy_from_slice(&pubkey.as_ref()[0..32]);
+        BigEndian::write_u64(&mut key[32..40], slot);
+        key[40..104].copy_from_slice(&signature.as_ref()[0..64]);
+        key
+    }
+
+    fn index(key: &[u8]) -> Self::Index {
+        let pubkey = Pubkey::try_from(&key[0..32]).unwrap();
+        let slot = BigEndian::read_u64(&key[32..40]);
+        let signature = Signature::try_from(&key[40..104]).unwrap();
+        (pubkey, slot, signature)
+    }
+
+    fn slot(index: Self::Index) -> Slot {
+        index.1
+    }
+
+    // The AccountJournal column is not keyed by slot so this method is meaningless
+    // See Column::as_index() declaration for more details
+    fn as_index(_index: u64) -> Self::Index {
+        (Pubkey::default(), 0, Signature::default())
+    }
+}
+
+impl ColumnName for AccountJournal {
+    const NAME: &'static str = ACCOUNT_JOURNAL_CF;
+}
+
+impl TypedColumn for AccountJournal {
+    type Type = magicblock_bank::account_journal::AccountJournalEntry;
+}
+
+// -----------------
+// ReplayInputs
+// -----------------
+const REPLAY_INPUTS_INDEX_LEN: usize = 8 + 8;
+impl Column for ReplayInputs {
+    type Index = (Slot, u64);
+
+    fn key((slot, seq): Self::Index) -> Vec<u8> {
+        let mut key = vec![0; REPLAY_INPUTS_INDEX_LEN];
+        BigEndian::write_u64(&mut key[0..8], slot);
+        BigEndian::write_u64(&mut key[8..16], seq);
+        key
+    }
+
+    fn index(key: &[u8]) -> Self::Index {
+        let slot = BigEndian::read_u64(&key[0..8]);
+        let seq = BigEndian::read_u64(&key[8..16]);
+        (slot, seq)
+    }
+
+    fn slot(index: Self::Index) -> Slot {
+        index.0
+    }
+
+    fn as_index(slot: Slot) -> Self::Index {
+        (slot, 0)
+    }
+}
+
+impl ColumnName for ReplayInputs {
+    const NAME: &'static str = REPLAY_INPUTS_CF;
+}
+
+// -----------------
+// EscrowSettlements
+// -----------------
+const ESCROW_SETTLEMENTS_INDEX_LEN: usize = 8 + 8;
+impl Column for EscrowSettlements {
+    type Index = (Slot, u64);
+
+    fn key((slot, seq): Self::Index) -> Vec<u8> {
+        let mut key = vec![0; ESCROW_SETTLEMENTS_INDEX_LEN];
+        BigEndian::write_u64(&mut key[0..8], slot);
+        BigEndian::write_u64(&mut key[8..16], seq);
+        key
+    }
+
+    fn index(key: &[u8]) -> Self::Index {
+        let slot = BigEndian::read_u64(&key[0..8]);
+        let seq = BigEndian::read_u64(&key[8..16]);
+        (slot, seq)
+    }
+
+    fn slot(index: Self::Index) -> Slot {
+        index.0
+    }
+
+    fn as_index(slot: Slot) -> Self::Index {
+        (slot, 0)
+    }
+}
+
+impl ColumnName for EscrowSettlements {
+    const NAME: &'static str = ESCROW_SETTLEMENTS_CF;
+}
+
+impl TypedColumn for EscrowSettlements {
+    type Type = magicblock_core::escrow::EscrowSettlement;
+}
+
 // -----------------
 // Column Configuration
 // -----------------
 
 // Returns true if the column family enables compression.
 pub fn should_enable_compression<C: 'static + Column + ColumnName>() -> bool {
-    C::NAME == TransactionStatus::NAME
+    C::NAME == TransactionStatus::NAME || C::NAME == Transaction::NAME
 }
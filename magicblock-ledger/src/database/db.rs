@@ -167,6 +167,10 @@ impl Database {
         )
     }
 
+    pub fn flush_wal(&self) -> std::result::Result<(), LedgerError> {
+        self.backend.flush_wal()
+    }
+
     pub fn is_primary_access(&self) -> bool {
         self.backend.is_primary_access()
     }
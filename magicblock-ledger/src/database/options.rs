@@ -117,9 +117,11 @@ impl ShredStorageType {
 // -----------------
 #[derive(Debug, Default, Clone)]
 pub enum LedgerCompressionType {
-    #[default]
     None,
     Snappy,
+    // Transactions and their statuses make up most of the ledger and compress
+    // well, existing uncompressed files remain readable
+    #[default]
     Lz4,
     Zlib,
 }
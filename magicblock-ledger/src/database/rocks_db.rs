@@ -180,6 +180,12 @@ impl Rocks {
         }
     }
 
+    /// Syncs the write ahead log to disk, thus all writes so far survive
+    /// the process exiting.
+    pub fn flush_wal(&self) -> LedgerResult<()> {
+        self.db.flush_wal(true).map_err(LedgerError::RocksDb)
+    }
+
     pub fn is_primary_access(&self) -> bool {
         self.access_type == AccessType::Primary
             || self.access_type == AccessType::PrimaryForMaintenance
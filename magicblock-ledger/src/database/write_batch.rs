@@ -3,7 +3,9 @@ use std::collections::HashMap;
 use bincode::serialize;
 use rocksdb::{ColumnFamily, WriteBatch as RWriteBatch};
 
-use super::columns::{Column, ColumnName, TypedColumn};
+use prost::Message;
+
+use super::columns::{Column, ColumnName, ProtobufColumn, TypedColumn};
 use crate::errors::LedgerError;
 
 pub struct WriteBatch<'a> {
@@ -51,6 +53,18 @@ impl<'a> WriteBatch<'a> {
         Ok(())
     }
 
+    pub fn put_protobuf<C: ProtobufColumn + ColumnName>(
+        &mut self,
+        key: C::Index,
+        value: &C::Type,
+    ) -> std::result::Result<(), LedgerError> {
+        let mut buf = Vec::with_capacity(value.encoded_len());
+        value.encode(&mut buf)?;
+        self.write_batch
+            .put_cf(self.get_cf::<C>(), C::key(key), buf);
+        Ok(())
+    }
+
     #[inline]
     pub fn get_cf<C: Column + ColumnName>(&self) -> &'a ColumnFamily {
         self.map[C::NAME]
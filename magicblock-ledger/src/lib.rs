@@ -6,4 +6,9 @@ mod metrics;
 mod store;
 
 pub use database::meta::PerfSample;
-pub use store::api::{Ledger, SignatureInfosForAddress};
+pub use store::{
+    api::{Ledger, SignatureInfosForAddress},
+    transaction_writer::{
+        LedgerTransaction, TransactionWriter, TransactionWriterConfig,
+    },
+};
@@ -1,5 +1,5 @@
 use std::{
-    collections::HashMap,
+    collections::{hash_map::Entry, HashMap},
     fmt, fs,
     path::{Path, PathBuf},
     sync::{atomic::Ordering, Arc, RwLock},
@@ -7,6 +7,8 @@ use std::{
 
 use bincode::{deserialize, serialize};
 use log::*;
+use magicblock_bank::account_journal::AccountJournalEntry;
+use magicblock_core::{escrow::EscrowSettlement, robust_lock::RobustRwLock};
 use rocksdb::Direction as IteratorDirection;
 use solana_measure::measure::Measure;
 use solana_sdk::{
@@ -32,10 +34,13 @@ use crate::{
         ledger_column::LedgerColumn,
         meta::{AccountModData, AddressSignatureMeta, PerfSample},
         options::LedgerOptions,
+        write_batch::WriteBatch,
     },
     errors::{LedgerError, LedgerResult},
     metrics::LedgerRpcApiMetrics,
-    store::utils::adjust_ulimit_nofile,
+    store::{
+        transaction_writer::LedgerTransaction, utils::adjust_ulimit_nofile,
+    },
 };
 
 #[derive(Default, Debug)]
@@ -59,6 +64,9 @@ pub struct Ledger {
     perf_samples_cf: LedgerColumn<cf::PerfSamples>,
 
     account_mod_datas_cf: LedgerColumn<cf::AccountModDatas>,
+    account_journal_cf: LedgerColumn<cf::AccountJournal>,
+    replay_inputs_cf: LedgerColumn<cf::ReplayInputs>,
+    escrow_settlements_cf: LedgerColumn<cf::EscrowSettlements>,
 
     pub lowest_cleanup_slot: RwLock<Slot>,
     rpc_api_metrics: LedgerRpcApiMetrics,
@@ -87,6 +95,12 @@ impl Ledger {
         self.db.storage_size()
     }
 
+    /// Ensures that everything written to the ledger so far is persisted,
+    /// should be called before the validator shuts down.
+    pub fn flush(&self) -> std::result::Result<(), LedgerError> {
+        self.db.flush_wal()
+    }
+
     /// Opens a Ledger in directory, provides "infinite" window of shreds
     pub fn open(ledger_path: &Path) -> std::result::Result<Self, LedgerError> {
         Self::do_open(ledger_path, LedgerOptions::default())
@@ -127,6 +141,9 @@ impl Ledger {
         let perf_samples_cf = db.column();
 
         let account_mod_datas_cf = db.column();
+        let account_journal_cf = db.column();
+        let replay_inputs_cf = db.column();
+        let escrow_settlements_cf = db.column();
 
         let db = Arc::new(db);
 
@@ -149,6 +166,9 @@ impl Ledger {
             perf_samples_cf,
 
             account_mod_datas_cf,
+            account_journal_cf,
+            replay_inputs_cf,
+            escrow_settlements_cf,
 
             lowest_cleanup_slot: RwLock::<Slot>::default(),
             rpc_api_metrics: LedgerRpcApiMetrics::default(),
@@ -171,6 +191,9 @@ impl Ledger {
         self.transaction_memos_cf.submit_rocksdb_cf_metrics();
         self.perf_samples_cf.submit_rocksdb_cf_metrics();
         self.account_mod_datas_cf.submit_rocksdb_cf_metrics();
+        self.account_journal_cf.submit_rocksdb_cf_metrics();
+        self.replay_inputs_cf.submit_rocksdb_cf_metrics();
+        self.escrow_settlements_cf.submit_rocksdb_cf_metrics();
     }
 
     // -----------------
@@ -187,7 +210,7 @@ impl Ledger {
         slot: Slot,
     ) -> LedgerResult<std::sync::RwLockReadGuard<Slot>> {
         // lowest_cleanup_slot is the last slot that was not cleaned up by LedgerCleanupService
-        let lowest_cleanup_slot = self.lowest_cleanup_slot.read().unwrap();
+        let lowest_cleanup_slot = self.lowest_cleanup_slot.read_robust();
         if *lowest_cleanup_slot > 0 && *lowest_cleanup_slot >= slot {
             return Err(LedgerError::SlotCleanedUp);
         }
@@ -205,7 +228,7 @@ impl Ledger {
     fn ensure_lowest_cleanup_slot(
         &self,
     ) -> (std::sync::RwLockReadGuard<Slot>, Slot) {
-        let lowest_cleanup_slot = self.lowest_cleanup_slot.read().unwrap();
+        let lowest_cleanup_slot = self.lowest_cleanup_slot.read_robust();
         let lowest_available_slot = (*lowest_cleanup_slot)
             .checked_add(1)
             .expect("overflow from trusted value");
@@ -255,6 +278,22 @@ impl Ledger {
         Ok(())
     }
 
+    /// Returns the lowest and highest slot for which a block was written or
+    /// [None] if the ledger contains no blocks.
+    pub fn get_slot_range(&self) -> LedgerResult<Option<(Slot, Slot)>> {
+        let lowest = self
+            .blocktime_cf
+            .iter(IteratorMode::Start)?
+            .next()
+            .map(|(slot, _)| slot);
+        let highest = self
+            .blocktime_cf
+            .iter(IteratorMode::End)?
+            .next()
+            .map(|(slot, _)| slot);
+        Ok(lowest.zip(highest))
+    }
+
     pub fn get_block(
         &self,
         slot: Slot,
@@ -731,24 +770,59 @@ impl Ledger {
         status: TransactionStatusMeta,
         transaction_slot_index: usize,
     ) -> LedgerResult<()> {
-        let tx_account_locks = transaction.get_account_locks_unchecked();
-
-        // 1. Write Transaction Status
-        self.write_transaction_status(
+        self.write_transactions(vec![LedgerTransaction {
+            signature,
             slot,
+            transaction,
+            status,
+            transaction_slot_index,
+            memos: None,
+        }])
+    }
+
+    /// Writes the statuses, address signatures, memos and the transactions
+    /// themselves of all [transactions] in a single atomic write batch.
+    pub fn write_transactions(
+        &self,
+        transactions: Vec<LedgerTransaction>,
+    ) -> LedgerResult<()> {
+        let mut batch = self.db.batch()?;
+        for LedgerTransaction {
             signature,
-            tx_account_locks.writable,
-            tx_account_locks.readonly,
+            slot,
+            transaction,
             status,
             transaction_slot_index,
-        )?;
+            memos,
+        } in transactions
+        {
+            let tx_account_locks = transaction.get_account_locks_unchecked();
 
-        // 2. Write Transaction
-        let versioned = transaction.to_versioned_transaction();
-        let transaction: generated::Transaction = versioned.into();
-        self.transaction_cf
-            .put_protobuf((signature, slot), &transaction)?;
-        Ok(())
+            // 1. Write Transaction Status
+            Self::put_transaction_status(
+                &mut batch,
+                slot,
+                signature,
+                tx_account_locks.writable,
+                tx_account_locks.readonly,
+                status,
+                transaction_slot_index,
+            )?;
+
+            // 2. Write Transaction
+            let versioned = transaction.to_versioned_transaction();
+            let transaction: generated::Transaction = versioned.into();
+            batch.put_protobuf::<cf::Transaction>(
+                (signature, slot),
+                &transaction,
+            )?;
+
+            // 3. Write Memos
+            if let Some(memos) = memos {
+                batch.put::<cf::TransactionMemos>((signature, slot), &memos)?;
+            }
+        }
+        self.db.write(batch)
     }
 
     fn read_transaction(
@@ -850,27 +924,53 @@ impl Ledger {
         readonly_keys: Vec<&Pubkey>,
         status: TransactionStatusMeta,
         transaction_slot_index: usize,
+    ) -> LedgerResult<()> {
+        let mut batch = self.db.batch()?;
+        Self::put_transaction_status(
+            &mut batch,
+            slot,
+            signature,
+            writable_keys,
+            readonly_keys,
+            status,
+            transaction_slot_index,
+        )?;
+        self.db.write(batch)
+    }
+
+    fn put_transaction_status(
+        batch: &mut WriteBatch,
+        slot: Slot,
+        signature: Signature,
+        writable_keys: Vec<&Pubkey>,
+        readonly_keys: Vec<&Pubkey>,
+        status: TransactionStatusMeta,
+        transaction_slot_index: usize,
     ) -> LedgerResult<()> {
         let transaction_slot_index = u32::try_from(transaction_slot_index)
             .map_err(|_| LedgerError::TransactionIndexOverflow)?;
         for address in writable_keys {
-            self.address_signatures_cf.put(
+            batch.put::<cf::AddressSignatures>(
                 (*address, slot, transaction_slot_index, signature),
                 &AddressSignatureMeta { writeable: true },
             )?;
         }
         for address in readonly_keys {
-            self.address_signatures_cf.put(
+            batch.put::<cf::AddressSignatures>(
                 (*address, slot, transaction_slot_index, signature),
                 &AddressSignatureMeta { writeable: false },
             )?;
         }
-        self.slot_signatures_cf
-            .put((slot, transaction_slot_index), &signature)?;
+        batch.put::<cf::SlotSignatures>(
+            (slot, transaction_slot_index),
+            &signature,
+        )?;
 
-        let status = status.into();
-        self.transaction_status_cf
-            .put_protobuf((signature, slot), &status)?;
+        let status: generated::TransactionStatusMeta = status.into();
+        batch.put_protobuf::<cf::TransactionStatus>(
+            (signature, slot),
+            &status,
+        )?;
         Ok(())
     }
 
@@ -922,6 +1022,127 @@ impl Ledger {
     ) -> LedgerResult<Option<AccountModData>> {
         self.account_mod_datas_cf.get(id)
     }
+
+    // -----------------
+    // AccountJournal
+    // -----------------
+    pub fn write_account_journal_entry(
+        &self,
+        entry: &AccountJournalEntry,
+    ) -> LedgerResult<()> {
+        self.account_journal_cf
+            .put((entry.pubkey, entry.slot, entry.signature), entry)
+    }
+
+    /// Returns the most recent journal entries of the account, newest first
+    /// * `pubkey` - The account to get the journal entries for
+    /// * `limit` - The maximum number of entries to return
+    pub fn get_account_journal_entries(
+        &self,
+        pubkey: Pubkey,
+        limit: usize,
+    ) -> LedgerResult<Vec<AccountJournalEntry>> {
+        let iterator = self.account_journal_cf.iter(IteratorMode::From(
+            (pubkey, Slot::MAX, Signature::from([u8::MAX; 64])),
+            IteratorDirection::Reverse,
+        ))?;
+
+        let mut entries = vec![];
+        for ((entry_pubkey, _, _), value) in iterator {
+            if entry_pubkey != pubkey || entries.len() >= limit {
+                break;
+            }
+            entries.push(deserialize(&value)?);
+        }
+        Ok(entries)
+    }
+
+    // -----------------
+    // ReplayInputs
+    // -----------------
+    /// Stores an input received from the remote cluster at the [slot], the
+    /// [seq] number orders all recorded inputs independent of their slot.
+    pub fn write_replay_input(
+        &self,
+        slot: Slot,
+        seq: u64,
+        input: &[u8],
+    ) -> LedgerResult<()> {
+        self.replay_inputs_cf.put_bytes((slot, seq), input)
+    }
+
+    /// Returns the index of the most recently recorded input if any.
+    pub fn get_last_replay_input_index(
+        &self,
+    ) -> LedgerResult<Option<(Slot, u64)>> {
+        Ok(self
+            .replay_inputs_cf
+            .iter(IteratorMode::End)?
+            .next()
+            .map(|(index, _)| index))
+    }
+
+    /// Returns all recorded inputs together with their (slot, seq) index in
+    /// the order they were recorded in.
+    pub fn get_replay_inputs(
+        &self,
+    ) -> LedgerResult<Vec<((Slot, u64), Box<[u8]>)>> {
+        Ok(self.replay_inputs_cf.iter(IteratorMode::Start)?.collect())
+    }
+
+    // -----------------
+    // EscrowSettlements
+    // -----------------
+    /// Stores the escrow settlements in the order they were accepted in,
+    /// following the ones stored for the same slot before.
+    pub fn write_escrow_settlements(
+        &self,
+        settlements: &[EscrowSettlement],
+    ) -> LedgerResult<()> {
+        let mut next_seqs = HashMap::<Slot, u64>::new();
+        for settlement in settlements {
+            let seq = match next_seqs.entry(settlement.slot) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => entry
+                    .insert(self.next_escrow_settlement_seq(settlement.slot)?),
+            };
+            self.escrow_settlements_cf
+                .put((settlement.slot, *seq), settlement)?;
+            *seq += 1;
+        }
+        Ok(())
+    }
+
+    fn next_escrow_settlement_seq(&self, slot: Slot) -> LedgerResult<u64> {
+        Ok(self
+            .escrow_settlements_cf
+            .iter(IteratorMode::From(
+                (slot, u64::MAX),
+                IteratorDirection::Reverse,
+            ))?
+            .next()
+            .filter(|((entry_slot, _), _)| *entry_slot == slot)
+            .map(|((_, seq), _)| seq + 1)
+            .unwrap_or(0))
+    }
+
+    /// Returns the escrow settlements made at [start_slot] or later in the
+    /// order they were stored in, i.e. to reconcile them on the base layer.
+    pub fn get_escrow_settlements(
+        &self,
+        start_slot: Slot,
+    ) -> LedgerResult<Vec<EscrowSettlement>> {
+        let iterator = self.escrow_settlements_cf.iter(IteratorMode::From(
+            (start_slot, 0),
+            IteratorDirection::Forward,
+        ))?;
+
+        let mut settlements = vec![];
+        for (_, value) in iterator {
+            settlements.push(deserialize(&value)?);
+        }
+        Ok(settlements)
+    }
 }
 
 // -----------------
@@ -2041,4 +2262,130 @@ mod tests {
             assert_eq!(sig_info_dos.memo, Some("Test Dos Memo".to_string()));
         }
     }
+
+    #[test]
+    fn test_get_slot_range() {
+        init_logger!();
+
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let store = Ledger::open(ledger_path.path()).unwrap();
+        assert_eq!(store.get_slot_range().unwrap(), None);
+
+        for slot in 3..=7 {
+            store
+                .write_block(slot, slot as UnixTimestamp, Hash::new_unique())
+                .unwrap();
+        }
+        assert_eq!(store.get_slot_range().unwrap(), Some((3, 7)));
+    }
+
+    #[test]
+    fn test_get_account_journal_entries() {
+        init_logger!();
+
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let store = Ledger::open(ledger_path.path()).unwrap();
+
+        let pubkey = Pubkey::new_unique();
+        let other_pubkey = Pubkey::new_unique();
+        let entry = |pubkey: Pubkey, slot: Slot| AccountJournalEntry {
+            pubkey,
+            slot,
+            signature: Signature::new_unique(),
+            writer_program: Pubkey::new_unique(),
+            lamports: slot * 1_000,
+            data_start: 0,
+            data_end: 8,
+            data_digest: Hash::new_unique(),
+        };
+        let entries = [
+            entry(pubkey, 5),
+            entry(other_pubkey, 6),
+            entry(pubkey, 7),
+            entry(pubkey, 9),
+        ];
+        for entry in &entries {
+            store.write_account_journal_entry(entry).unwrap();
+        }
+
+        // Newest first and only for the requested account
+        assert_eq!(
+            store.get_account_journal_entries(pubkey, 10).unwrap(),
+            vec![entries[3].clone(), entries[2].clone(), entries[0].clone()]
+        );
+        assert_eq!(
+            store.get_account_journal_entries(pubkey, 2).unwrap(),
+            vec![entries[3].clone(), entries[2].clone()]
+        );
+        assert_eq!(
+            store.get_account_journal_entries(other_pubkey, 10).unwrap(),
+            vec![entries[1].clone()]
+        );
+        assert!(store
+            .get_account_journal_entries(Pubkey::new_unique(), 10)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_replay_inputs() {
+        init_logger!();
+
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let store = Ledger::open(ledger_path.path()).unwrap();
+
+        assert!(store.get_replay_inputs().unwrap().is_empty());
+        assert_eq!(store.get_last_replay_input_index().unwrap(), None);
+
+        // Written out of order to ensure they are ordered by slot and seq
+        store.write_replay_input(2, 2, &[3]).unwrap();
+        store.write_replay_input(1, 0, &[1]).unwrap();
+        store.write_replay_input(1, 1, &[2]).unwrap();
+
+        assert_eq!(
+            store.get_replay_inputs().unwrap(),
+            vec![
+                ((1, 0), vec![1].into_boxed_slice()),
+                ((1, 1), vec![2].into_boxed_slice()),
+                ((2, 2), vec![3].into_boxed_slice()),
+            ]
+        );
+        assert_eq!(store.get_last_replay_input_index().unwrap(), Some((2, 2)));
+    }
+
+    #[test]
+    fn test_escrow_settlements() {
+        init_logger!();
+
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let store = Ledger::open(ledger_path.path()).unwrap();
+
+        let settlement = |slot: Slot, lamports: u64| EscrowSettlement {
+            slot,
+            from: Pubkey::new_unique(),
+            to: Pubkey::new_unique(),
+            lamports,
+            authority_program: Pubkey::new_unique(),
+        };
+        let first_batch = [settlement(1, 10), settlement(2, 20)];
+        // Settlements of the same slot may be accepted in separate batches
+        let second_batch = [settlement(2, 21), settlement(3, 30)];
+        store.write_escrow_settlements(&first_batch).unwrap();
+        store.write_escrow_settlements(&second_batch).unwrap();
+
+        assert_eq!(
+            store.get_escrow_settlements(0).unwrap(),
+            vec![
+                first_batch[0].clone(),
+                first_batch[1].clone(),
+                second_batch[0].clone(),
+                second_batch[1].clone(),
+            ]
+        );
+        assert_eq!(
+            store.get_escrow_settlements(3).unwrap(),
+            vec![second_batch[1].clone()]
+        );
+        assert!(store.get_escrow_settlements(4).unwrap().is_empty());
+    }
 }
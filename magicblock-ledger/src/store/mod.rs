@@ -1,3 +1,4 @@
 pub mod api;
 pub mod data_mod_persister;
+pub mod transaction_writer;
 mod utils;
@@ -0,0 +1,146 @@
+use std::{
+    sync::Arc,
+    thread,
+    time::{Duration, Instant},
+};
+
+use crossbeam_channel::{bounded, unbounded, Receiver, Sender};
+use log::*;
+use magicblock_metrics::metrics;
+use solana_sdk::{
+    clock::Slot, signature::Signature, transaction::SanitizedTransaction,
+};
+use solana_transaction_status::TransactionStatusMeta;
+
+use crate::Ledger;
+
+// -----------------
+// LedgerTransaction
+// -----------------
+/// A transaction to be written to the ledger along with its status.
+pub struct LedgerTransaction {
+    pub signature: Signature,
+    pub slot: Slot,
+    pub transaction: SanitizedTransaction,
+    pub status: TransactionStatusMeta,
+    pub transaction_slot_index: usize,
+    pub memos: Option<String>,
+}
+
+// -----------------
+// TransactionWriterConfig
+// -----------------
+#[derive(Debug, Clone)]
+pub struct TransactionWriterConfig {
+    /// Max number of transactions written in one batch
+    pub max_batch_size: usize,
+    /// Max time to wait for more transactions before a batch is written
+    pub max_batch_delay: Duration,
+}
+
+impl Default for TransactionWriterConfig {
+    fn default() -> Self {
+        Self {
+            max_batch_size: 256,
+            max_batch_delay: Duration::from_millis(10),
+        }
+    }
+}
+
+// -----------------
+// TransactionWriter
+// -----------------
+enum WriterMessage {
+    Write(Box<LedgerTransaction>),
+    /// Acknowledged once all transactions sent before were written
+    Flush(Sender<()>),
+}
+
+/// Writes transactions to the ledger on a dedicated thread, batching the
+/// ones that arrive close to each other into a single write.
+/// This way persisting transactions doesn't add latency to processing them.
+#[derive(Clone)]
+pub struct TransactionWriter {
+    sender: Sender<WriterMessage>,
+}
+
+impl TransactionWriter {
+    pub fn spawn(ledger: Arc<Ledger>, config: TransactionWriterConfig) -> Self {
+        let (sender, receiver) = unbounded();
+        thread::Builder::new()
+            .name("ledgerTxWriter".to_string())
+            .spawn(move || run_writer(&ledger, &receiver, &config))
+            .expect("Failed to spawn ledger transaction writer thread");
+        Self { sender }
+    }
+
+    /// Queues the [transaction] to be written with the next batch.
+    pub fn write(&self, transaction: LedgerTransaction) {
+        if self
+            .sender
+            .send(WriterMessage::Write(Box::new(transaction)))
+            .is_err()
+        {
+            error!("Ledger transaction writer stopped, dropping transaction");
+        }
+        metrics::set_ledger_transaction_write_backlog(self.backlog());
+    }
+
+    /// The number of transactions waiting to be written.
+    pub fn backlog(&self) -> usize {
+        self.sender.len()
+    }
+
+    /// Blocks until all transactions queued before were written, should be
+    /// called before the ledger is flushed on shutdown.
+    pub fn flush(&self) {
+        let (ack_sender, ack_receiver) = bounded(1);
+        if self.sender.send(WriterMessage::Flush(ack_sender)).is_ok() {
+            let _ = ack_receiver.recv();
+        }
+    }
+}
+
+fn run_writer(
+    ledger: &Ledger,
+    receiver: &Receiver<WriterMessage>,
+    config: &TransactionWriterConfig,
+) {
+    let mut batch = Vec::with_capacity(config.max_batch_size);
+    while let Ok(message) = receiver.recv() {
+        let deadline = Instant::now() + config.max_batch_delay;
+        let mut next = Some(message);
+        let mut flush_ack = None;
+        while let Some(message) = next.take() {
+            match message {
+                WriterMessage::Write(transaction) => batch.push(*transaction),
+                WriterMessage::Flush(ack) => {
+                    flush_ack = Some(ack);
+                    break;
+                }
+            }
+            if batch.len() >= config.max_batch_size {
+                break;
+            }
+            next = receiver.recv_deadline(deadline).ok();
+        }
+
+        write_batch(ledger, std::mem::take(&mut batch));
+        metrics::set_ledger_transaction_write_backlog(receiver.len());
+        if let Some(ack) = flush_ack {
+            let _ = ack.send(());
+        }
+    }
+}
+
+fn write_batch(ledger: &Ledger, batch: Vec<LedgerTransaction>) {
+    if batch.is_empty() {
+        return;
+    }
+    let len = batch.len();
+    let start = Instant::now();
+    if let Err(err) = ledger.write_transactions(batch) {
+        error!("Failed to write {} transactions to ledger: {:?}", len, err);
+    }
+    metrics::observe_ledger_transaction_write_batch(len, start.elapsed());
+}
@@ -0,0 +1,103 @@
+use std::{fs, sync::Arc, time::Duration};
+
+use magicblock_ledger::{
+    Ledger, LedgerTransaction, TransactionWriter, TransactionWriterConfig,
+};
+use solana_sdk::{
+    clock::Slot,
+    hash::Hash,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature},
+    signer::Signer,
+    system_instruction,
+    transaction::{SanitizedTransaction, Transaction},
+};
+use solana_transaction_status::TransactionStatusMeta;
+use tempfile::NamedTempFile;
+use test_tools_core::init_logger;
+
+fn setup() -> Arc<Ledger> {
+    let file = NamedTempFile::new().unwrap();
+    let path = file.into_temp_path();
+    fs::remove_file(&path).unwrap();
+    Arc::new(Ledger::open(&path).unwrap())
+}
+
+fn create_ledger_transaction(
+    slot: Slot,
+    transaction_slot_index: usize,
+) -> LedgerTransaction {
+    let from = Keypair::new();
+    let to = Pubkey::new_unique();
+    let ix = system_instruction::transfer(&from.pubkey(), &to, 99);
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&from.pubkey()),
+        &[&from],
+        Hash::new_unique(),
+    );
+    LedgerTransaction {
+        signature: Signature::new_unique(),
+        slot,
+        transaction: SanitizedTransaction::from_transaction_for_tests(tx),
+        status: TransactionStatusMeta {
+            fee: transaction_slot_index as u64,
+            ..TransactionStatusMeta::default()
+        },
+        transaction_slot_index,
+        memos: Some(format!("memo {}", transaction_slot_index)),
+    }
+}
+
+#[test]
+fn test_write_transactions_in_batches() {
+    init_logger!();
+
+    let ledger = setup();
+    let writer = TransactionWriter::spawn(
+        ledger.clone(),
+        TransactionWriterConfig {
+            max_batch_size: 4,
+            max_batch_delay: Duration::from_millis(50),
+        },
+    );
+
+    let slot = 5;
+    let signatures = (0..10)
+        .map(|idx| {
+            let transaction = create_ledger_transaction(slot, idx);
+            let signature = transaction.signature;
+            writer.write(transaction);
+            signature
+        })
+        .collect::<Vec<_>>();
+    writer.flush();
+    assert_eq!(writer.backlog(), 0);
+
+    for (idx, signature) in signatures.into_iter().enumerate() {
+        let status = ledger
+            .read_transaction_status((signature, slot))
+            .unwrap()
+            .expect("transaction status was not written");
+        assert_eq!(status.fee, idx as u64);
+        assert!(ledger
+            .get_complete_transaction(signature, slot)
+            .unwrap()
+            .is_some());
+        assert_eq!(
+            ledger.read_transaction_memos(signature, slot).unwrap(),
+            Some(format!("memo {}", idx))
+        );
+    }
+}
+
+#[test]
+fn test_flush_without_pending_transactions() {
+    init_logger!();
+
+    let ledger = setup();
+    let writer =
+        TransactionWriter::spawn(ledger, TransactionWriterConfig::default());
+    writer.flush();
+    assert_eq!(writer.backlog(), 0);
+}
@@ -0,0 +1,15 @@
+use thiserror::Error;
+
+pub type LoggerResult<T> = std::result::Result<T, LoggerError>;
+
+#[derive(Error, Debug)]
+pub enum LoggerError {
+    #[error("Invalid log format '{0}', expected 'text' or 'json'")]
+    InvalidLogFormat(String),
+
+    #[error("Logger was not initialized")]
+    LoggerNotInitialized,
+
+    #[error("SetLoggerError: {0}")]
+    SetLoggerError(#[from] log::SetLoggerError),
+}
@@ -0,0 +1,69 @@
+use std::{
+    io::Write,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use log::{
+    kv::{self, Key, Value, VisitSource},
+    Record,
+};
+use serde_json::{json, Map, Value as JsonValue};
+
+struct JsonFields<'a>(&'a mut Map<String, JsonValue>);
+
+impl<'kvs> VisitSource<'kvs> for JsonFields<'_> {
+    fn visit_pair(
+        &mut self,
+        key: Key<'kvs>,
+        value: Value<'kvs>,
+    ) -> Result<(), kv::Error> {
+        self.0
+            .insert(key.to_string(), JsonValue::String(value.to_string()));
+        Ok(())
+    }
+}
+
+/// Derives the subsystem from the crate the record was logged from,
+/// i.e. `magicblock_accounts::remote_account_committer` yields `accounts`.
+fn subsystem(target: &str) -> &str {
+    let krate = target.split("::").next().unwrap_or(target);
+    krate.strip_prefix("magicblock_").unwrap_or(krate)
+}
+
+pub(crate) fn write_json_record(record: &Record, slot: u64) {
+    let timestamp_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis())
+        .unwrap_or_default();
+
+    let mut fields = Map::new();
+    let _ = record.key_values().visit(&mut JsonFields(&mut fields));
+
+    let line = json!({
+        "timestamp_ms": timestamp_ms,
+        "level": record.level().as_str(),
+        "slot": slot,
+        "subsystem": subsystem(record.target()),
+        "target": record.target(),
+        "message": record.args().to_string(),
+        "fields": fields,
+    });
+
+    let mut stderr = std::io::stderr().lock();
+    let _ = writeln!(stderr, "{}", line);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subsystem() {
+        assert_eq!(
+            subsystem("magicblock_accounts::remote_account_committer"),
+            "accounts"
+        );
+        assert_eq!(subsystem("magicblock_rpc"), "rpc");
+        assert_eq!(subsystem("solana_svm::message_processor"), "solana_svm");
+    }
+}
@@ -0,0 +1,8 @@
+pub mod errors;
+mod json;
+mod logger;
+
+pub use logger::{
+    init_logger, log_filter, set_log_filter, set_log_slot, LogFormat,
+    LOG_FORMAT_ENV, LOG_STYLE_ENV,
+};
@@ -0,0 +1,177 @@
+use std::{
+    env,
+    io::Write,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard,
+    },
+};
+
+use env_filter::Filter;
+use lazy_static::lazy_static;
+use log::{LevelFilter, Log, Metadata, Record};
+
+use crate::{
+    errors::{LoggerError, LoggerResult},
+    json::write_json_record,
+};
+
+/// Env var selecting the [LogFormat], i.e. `text` or `json`.
+pub const LOG_FORMAT_ENV: &str = "RUST_LOG_FORMAT";
+/// Env var which if set prefixes each text log line, i.e. `EPHEM`.
+pub const LOG_STYLE_ENV: &str = "RUST_LOG_STYLE";
+const DEFAULT_FILTER: &str = "info";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    #[default]
+    Text,
+    /// One JSON object per line including the slot, subsystem and any
+    /// key-values, i.e. `pubkey` or `signature`, attached to the record.
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = LoggerError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            _ => Err(LoggerError::InvalidLogFormat(s.to_string())),
+        }
+    }
+}
+
+struct LogFilter {
+    spec: String,
+    filter: Filter,
+}
+
+impl LogFilter {
+    fn parse(spec: &str) -> Self {
+        let filter = env_filter::Builder::new().parse(spec).build();
+        Self {
+            spec: spec.to_string(),
+            filter,
+        }
+    }
+}
+
+lazy_static! {
+    /// The filter is kept outside of the logger so that it can be adjusted at
+    /// runtime, i.e. via the admin RPC.
+    static ref LOG_FILTER: RwLock<Option<LogFilter>> = RwLock::new(None);
+}
+
+static LOG_SLOT: AtomicU64 = AtomicU64::new(0);
+
+/// Updates the slot included with each JSON log record.
+pub fn set_log_slot(slot: u64) {
+    LOG_SLOT.store(slot, Ordering::Relaxed);
+}
+
+struct MagicLogger {
+    format: LogFormat,
+    text: env_logger::Logger,
+}
+
+impl Log for MagicLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        read_log_filter()
+            .as_ref()
+            .map_or(false, |log_filter| log_filter.filter.enabled(metadata))
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        match self.format {
+            LogFormat::Text => self.text.log(record),
+            LogFormat::Json => {
+                write_json_record(record, LOG_SLOT.load(Ordering::Relaxed))
+            }
+        }
+    }
+
+    fn flush(&self) {
+        self.text.flush();
+        let _ = std::io::stderr().flush();
+    }
+}
+
+/// Installs the validator logger.
+/// The format is determined by [LOG_FORMAT_ENV] and the initial filter by
+/// `RUST_LOG` which uses the `env_logger` syntax.
+pub fn init_logger() -> LoggerResult<()> {
+    let format = match env::var(LOG_FORMAT_ENV) {
+        Ok(format) => format.parse()?,
+        Err(_) => LogFormat::default(),
+    };
+    let spec = env::var(env_logger::DEFAULT_FILTER_ENV)
+        .unwrap_or_else(|_| DEFAULT_FILTER.to_string());
+    let log_filter = LogFilter::parse(&spec);
+    let max_level = log_filter.filter.filter();
+
+    // Filtering is done by the [MagicLogger] itself, thus the text logger
+    // logs everything it is handed
+    let mut builder = env_logger::Builder::new();
+    builder
+        .format_timestamp_micros()
+        .filter_level(LevelFilter::Trace);
+    if let Ok(style) = env::var(LOG_STYLE_ENV) {
+        builder.format(move |buf, record| {
+            writeln!(
+                buf,
+                "{} [{}] {}: {} {}",
+                style,
+                record.level(),
+                buf.timestamp_millis(),
+                record.module_path().unwrap_or_default(),
+                record.args()
+            )
+        });
+    }
+
+    log::set_boxed_logger(Box::new(MagicLogger {
+        format,
+        text: builder.build(),
+    }))?;
+    *write_log_filter() = Some(log_filter);
+    log::set_max_level(max_level);
+    Ok(())
+}
+
+/// Replaces the current log filter, the [spec] uses the `RUST_LOG` syntax,
+/// i.e. `info,magicblock_accounts=trace`.
+pub fn set_log_filter(spec: &str) -> LoggerResult<()> {
+    let log_filter = LogFilter::parse(spec);
+    let max_level = log_filter.filter.filter();
+    let mut current = write_log_filter();
+    if current.is_none() {
+        return Err(LoggerError::LoggerNotInitialized);
+    }
+    *current = Some(log_filter);
+    log::set_max_level(max_level);
+    Ok(())
+}
+
+/// The filter spec currently in use.
+pub fn log_filter() -> LoggerResult<String> {
+    read_log_filter()
+        .as_ref()
+        .map(|log_filter| log_filter.spec.clone())
+        .ok_or(LoggerError::LoggerNotInitialized)
+}
+
+// Recovering a poisoned lock is logged by the magicblock_core robust locks,
+// which would reenter this logger, so we recover silently here instead.
+fn read_log_filter() -> RwLockReadGuard<'static, Option<LogFilter>> {
+    LOG_FILTER.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn write_log_filter() -> RwLockWriteGuard<'static, Option<LogFilter>> {
+    LOG_FILTER.write().unwrap_or_else(PoisonError::into_inner)
+}
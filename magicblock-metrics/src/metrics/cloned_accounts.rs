@@ -0,0 +1,103 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Mutex,
+    time::Duration,
+};
+
+use magicblock_core::robust_lock::RobustMutex;
+
+/// Bounds the memory used to track clones per account, once reached the
+/// least recently cloned account is evicted to make room for a new one.
+const MAX_TRACKED_ACCOUNTS: usize = 10_000;
+
+lazy_static::lazy_static! {
+    static ref CLONED_ACCOUNTS: Mutex<ClonedAccounts> =
+        Mutex::new(ClonedAccounts::default());
+}
+
+/// The tracked accounts together with the order in which they were last
+/// cloned, which allows evicting the least recently cloned one without
+/// scanning all of them.
+#[derive(Default)]
+struct ClonedAccounts {
+    stats: HashMap<String, (ClonedAccountStats, u64)>,
+    /// Maps the sequence at which an account was last cloned to its pubkey
+    recency: BTreeMap<u64, String>,
+    next_seq: u64,
+}
+
+impl ClonedAccounts {
+    fn record(
+        &mut self,
+        pubkey: &str,
+        kind: &'static str,
+        data_len: usize,
+        elapsed: Duration,
+    ) {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+
+        if let Some((stats, last_seq)) = self.stats.get_mut(pubkey) {
+            self.recency.remove(&*last_seq);
+            *last_seq = seq;
+            stats.kind = kind;
+            stats.clone_count += 1;
+            stats.data_len = data_len;
+            stats.total_clone_time += elapsed;
+        } else {
+            if self.stats.len() >= MAX_TRACKED_ACCOUNTS {
+                if let Some((_, least_recent)) = self.recency.pop_first() {
+                    self.stats.remove(&least_recent);
+                }
+            }
+            let stats = ClonedAccountStats {
+                pubkey: pubkey.to_string(),
+                kind,
+                clone_count: 1,
+                data_len,
+                total_clone_time: elapsed,
+            };
+            self.stats.insert(pubkey.to_string(), (stats, seq));
+        }
+        self.recency.insert(seq, pubkey.to_string());
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClonedAccountStats {
+    pub pubkey: String,
+    pub kind: &'static str,
+    pub clone_count: u64,
+    /// Data size in bytes of the account when it was last cloned.
+    pub data_len: usize,
+    pub total_clone_time: Duration,
+}
+
+pub(crate) fn record_cloned_account(
+    pubkey: &str,
+    kind: &'static str,
+    data_len: usize,
+    elapsed: Duration,
+) {
+    CLONED_ACCOUNTS
+        .lock_robust()
+        .record(pubkey, kind, data_len, elapsed);
+}
+
+/// Returns the [limit] accounts that were cloned most often, i.e. those that
+/// are re-cloned due to updates on chain, most expensive first.
+pub fn top_cloned_accounts(limit: usize) -> Vec<ClonedAccountStats> {
+    let mut accounts = CLONED_ACCOUNTS
+        .lock_robust()
+        .stats
+        .values()
+        .map(|(stats, _)| stats.clone())
+        .collect::<Vec<_>>();
+    accounts.sort_by(|a, b| {
+        b.clone_count
+            .cmp(&a.clone_count)
+            .then(b.total_clone_time.cmp(&a.total_clone_time))
+    });
+    accounts.truncate(limit);
+    accounts
+}
@@ -1,11 +1,13 @@
-use std::sync::Once;
+use std::{sync::Once, time::Duration};
 
+pub use cloned_accounts::{top_cloned_accounts, ClonedAccountStats};
 pub use prometheus::HistogramTimer;
 use prometheus::{
-    Histogram, HistogramOpts, IntCounter, IntCounterVec, IntGauge, Opts,
-    Registry,
+    Histogram, HistogramOpts, HistogramVec, IntCounter, IntCounterVec,
+    IntGauge, IntGaugeVec, Opts, Registry,
 };
 pub use types::{AccountClone, AccountCommit, Outcome};
+mod cloned_accounts;
 mod types;
 
 // -----------------
@@ -29,6 +31,17 @@ const MILLIS_100_900: [f64; 9] = [0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8, 0.9];
 const SECONDS_1_9: [f64; 9] = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0];
 const SECONDS_10_19: [f64; 10] =
     [10.0, 11.0, 12.0, 13.0, 14.0, 15.0, 16.0, 17.0, 18.0, 19.0];
+// Account data sizes in bytes up to the max of 10MiB
+const BYTES_0_10M: [f64; 8] = [
+    0.0,
+    128.0,
+    1024.0,
+    10.0 * 1024.0,
+    100.0 * 1024.0,
+    1024.0 * 1024.0,
+    5.0 * 1024.0 * 1024.0,
+    10.0 * 1024.0 * 1024.0,
+];
 
 lazy_static::lazy_static! {
     pub (crate) static ref REGISTRY: Registry = Registry::new_custom(Some("mbv".to_string()), None).unwrap();
@@ -55,9 +68,50 @@ lazy_static::lazy_static! {
         "fee_count", "Fee Count",
     ).unwrap();
 
+    static ref SPONSORED_TRANSACTION_COUNT: IntCounter = IntCounter::new(
+        "sponsored_transaction_count", "Count of transactions whose fees were sponsored by the validator",
+    ).unwrap();
+
+    static ref SPONSORED_FEE_LAMPORTS_COUNT: IntCounter = IntCounter::new(
+        "sponsored_fee_lamports", "Lamports of fees sponsored by the validator",
+    ).unwrap();
+
     static ref ACCOUNT_CLONE_VEC_COUNT: IntCounterVec = IntCounterVec::new(
-        Opts::new("account_clone_count", "Count clones performed for specific accounts"),
-        &["kind", "pubkey", "owner"],
+        Opts::new("account_clone_count", "Count clones performed per kind of account and owner program"),
+        &["kind", "owner"],
+    ).unwrap();
+
+    static ref ACCOUNT_CLONE_DATA_SIZE_HISTOGRAM: HistogramVec = HistogramVec::new(
+        HistogramOpts::new("account_clone_data_size", "Size in bytes of the data of cloned accounts")
+            .buckets(BYTES_0_10M.to_vec()),
+        &["kind"],
+    ).unwrap();
+
+    static ref ACCOUNT_CLONE_TIME_HISTOGRAM: HistogramVec = HistogramVec::new(
+        HistogramOpts::new("account_clone_time", "Time from requesting an account clone until it is dumped into the bank")
+            .buckets(
+                MILLIS_1_9.iter().chain(
+                MILLIS_10_90.iter()).chain(
+                MILLIS_100_900.iter()).chain(
+                SECONDS_1_9.iter()).cloned().collect()
+            ),
+        &["kind"],
+    ).unwrap();
+
+    static ref ACCOUNT_DUMP_VEC_COUNT: IntCounterVec = IntCounterVec::new(
+        Opts::new("account_dump_count", "Count of accounts dumped into the bank per kind of dump"),
+        &["kind", "outcome"],
+    ).unwrap();
+
+    static ref ACCOUNT_DUMP_TIME_HISTOGRAM: HistogramVec = HistogramVec::new(
+        HistogramOpts::new("account_dump_time", "Time spent dumping accounts into the bank per kind of dump")
+            .buckets(
+                MICROS_100_900.iter().chain(
+                MILLIS_1_9.iter()).chain(
+                MILLIS_10_90.iter()).chain(
+                MILLIS_100_900.iter()).cloned().collect()
+            ),
+        &["kind"],
     ).unwrap();
 
     static ref ACCOUNT_COMMIT_VEC_COUNT: IntCounterVec = IntCounterVec::new(
@@ -75,6 +129,39 @@ lazy_static::lazy_static! {
             ),
     ).unwrap();
 
+    static ref COMMIT_CONFIRMATION_TIME_HISTOGRAM: HistogramVec = HistogramVec::new(
+        HistogramOpts::new("commit_confirmation_time", "Time spent confirming each commit transaction per confirmation method")
+            .buckets(
+                MILLIS_10_90.iter().chain(
+                MILLIS_100_900.iter()).chain(
+                SECONDS_1_9.iter()).chain(
+                SECONDS_10_19.iter()).cloned().collect()
+            ),
+        &["method", "outcome"],
+    ).unwrap();
+
+    static ref COMMIT_COST_LAMPORTS_COUNT: IntCounter = IntCounter::new(
+        "commit_cost_lamports", "Lamports spent by the validator authority on commit transactions",
+    ).unwrap();
+
+    static ref PROGRAM_COMMIT_COST_VEC_COUNT: IntCounterVec = IntCounterVec::new(
+        Opts::new("program_commit_cost_lamports", "Lamports spent on committing accounts owned by specific programs"),
+        &["program"],
+    ).unwrap();
+
+    static ref COMMIT_COST_WINDOW_GAUGE: IntGaugeVec = IntGaugeVec::new(
+        Opts::new("commit_cost_window_lamports", "Lamports spent on commit transactions within the rolling window"),
+        &["window"],
+    ).unwrap();
+
+    static ref COMMIT_BUDGET_EXCEEDED_GAUGE: IntGauge = IntGauge::new(
+        "commit_budget_exceeded", "1 while the commit budget is exceeded and non-critical commits are paused",
+    ).unwrap();
+
+    static ref COMMIT_SIMULATION_FAILURE_COUNT: IntCounter = IntCounter::new(
+        "commit_simulation_failures", "Commit transactions not sent since their simulation against the remote cluster failed",
+    ).unwrap();
+
     static ref LEDGER_SIZE_GAUGE: IntGauge = IntGauge::new(
         "ledger_size", "Ledger size in Bytes",
     ).unwrap();
@@ -87,6 +174,18 @@ lazy_static::lazy_static! {
         "inmemory_accounts_size", "Size of account states kept in RAM",
     ).unwrap();
 
+    static ref ACCOUNTS_COUNT_GAUGE: IntGauge = IntGauge::new(
+        "accounts_count", "Number of accounts kept in the accounts db",
+    ).unwrap();
+
+    static ref ACCOUNTS_STORAGE_FILES_GAUGE: IntGauge = IntGauge::new(
+        "accounts_storage_files", "Number of files persisted accounts are stored in on disk",
+    ).unwrap();
+
+    static ref SLOW_ACCOUNT_WRITE_COUNT: IntCounter = IntCounter::new(
+        "slow_account_writes", "Account writes into the accounts db which exceeded their latency budget",
+    ).unwrap();
+
     static ref PENDING_ACCOUNT_CLONES_GAUGE: IntGauge = IntGauge::new(
         "pending_account_clones", "Total number of account clone requests still in memory",
     ).unwrap();
@@ -99,6 +198,39 @@ lazy_static::lazy_static! {
         "active_data_mods_size", "Total memory consumption by account data modifications",
     ).unwrap();
 
+    static ref SPILLED_DATA_MODS_GAUGE: IntGauge = IntGauge::new(
+        "spilled_data_mods", "Total number of account data modifications spilled to disk",
+    ).unwrap();
+
+    static ref SPILLED_DATA_MODS_SIZE_GAUGE: IntGauge = IntGauge::new(
+        "spilled_data_mods_size", "Total disk consumption by spilled account data modifications",
+    ).unwrap();
+
+    static ref DATA_MODS_BUDGET_EXCEEDED_COUNT: IntCounter = IntCounter::new(
+        "data_mods_budget_exceeded_count", "Count of account data modifications exceeding the memory budget",
+    ).unwrap();
+
+    static ref MAGIC_CONTEXT_SCHEDULED_COMMITS_GAUGE: IntGauge = IntGauge::new(
+        "magic_context_scheduled_commits", "Commits scheduled in the MagicContext account that were not accepted yet",
+    ).unwrap();
+
+    static ref MAGIC_CONTEXT_SIZE_GAUGE: IntGauge = IntGauge::new(
+        "magic_context_size", "Bytes occupied in the MagicContext account",
+    ).unwrap();
+
+    static ref MAGIC_CONTEXT_CAPACITY_EXCEEDED_COUNT: IntCounter = IntCounter::new(
+        "magic_context_capacity_exceeded_count", "Count of commits rejected since they did not fit into the MagicContext account",
+    ).unwrap();
+
+    static ref MISSING_ACCOUNT_CACHE_HIT_COUNT: IntCounter = IntCounter::new(
+        "missing_account_cache_hit_count", "Count of fetches of accounts missing on chain served without asking the remote",
+    ).unwrap();
+
+    static ref REJECTED_TRANSACTION_VEC_COUNT: IntCounterVec = IntCounterVec::new(
+        Opts::new("rejected_transaction_count", "Count of transactions rejected before execution"),
+        &["reason"],
+    ).unwrap();
+
     static ref SIGVERIFY_TIME_HISTOGRAM: Histogram = Histogram::with_opts(
         HistogramOpts::new("sigverify_time", "Time spent in sigverify")
             .buckets(
@@ -127,6 +259,124 @@ lazy_static::lazy_static! {
             ),
     ).unwrap();
 
+    // -----------------
+    // RPC
+    // -----------------
+    static ref RPC_REQUEST_VEC_COUNT: IntCounterVec = IntCounterVec::new(
+        Opts::new("rpc_request_count", "Count of RPC requests per method"),
+        &["method", "outcome"],
+    ).unwrap();
+
+    static ref RPC_REQUEST_TIME_HISTOGRAM: HistogramVec = HistogramVec::new(
+        HistogramOpts::new("rpc_request_time", "Time spent handling RPC requests per method")
+            .buckets(
+                MICROS_100_900.iter().chain(
+                MILLIS_1_9.iter()).chain(
+                MILLIS_10_90.iter()).chain(
+                MILLIS_100_900.iter()).chain(
+                SECONDS_1_9.iter()).cloned().collect()
+            ),
+        &["method"],
+    ).unwrap();
+
+    // -----------------
+    // Pubsub
+    // -----------------
+    static ref PUBSUB_ACTIVE_CONNECTIONS_GAUGE: IntGauge = IntGauge::new(
+        "pubsub_active_connections", "Number of open websocket connections",
+    ).unwrap();
+
+    static ref PUBSUB_SUBSCRIPTION_VEC_COUNT: IntCounterVec = IntCounterVec::new(
+        Opts::new("pubsub_subscription_count", "Count of subscribe requests per subscription type"),
+        &["subscription", "outcome"],
+    ).unwrap();
+
+    static ref PUBSUB_ACTIVE_SUBSCRIPTIONS_GAUGE: IntGaugeVec = IntGaugeVec::new(
+        Opts::new("pubsub_active_subscriptions", "Number of active subscriptions per subscription type"),
+        &["subscription"],
+    ).unwrap();
+
+    static ref PUBSUB_RESUBSCRIPTION_VEC_COUNT: IntCounterVec = IntCounterVec::new(
+        Opts::new("pubsub_resubscription_count", "Count of subscriptions re-established after their geyser stream broke per subscription type"),
+        &["subscription"],
+    ).unwrap();
+
+    static ref PUBSUB_SUBSCRIPTION_TIME_HISTOGRAM: HistogramVec = HistogramVec::new(
+        HistogramOpts::new("pubsub_subscription_time", "Time subscriptions stayed active per subscription type")
+            .buckets(
+                MILLIS_100_900.iter().chain(
+                SECONDS_1_9.iter()).chain(
+                SECONDS_10_19.iter()).chain(
+                [60.0, 300.0, 900.0, 3600.0].iter()).cloned().collect()
+            ),
+        &["subscription"],
+    ).unwrap();
+
+    // -----------------
+    // Remote RPC
+    // -----------------
+    static ref REMOTE_RPC_REQUEST_VEC_COUNT: IntCounterVec = IntCounterVec::new(
+        Opts::new("remote_rpc_request_count", "Count of requests to the remote cluster per endpoint"),
+        &["endpoint", "request", "outcome"],
+    ).unwrap();
+
+    static ref REMOTE_RPC_REQUEST_TIME_HISTOGRAM: HistogramVec = HistogramVec::new(
+        HistogramOpts::new("remote_rpc_request_time", "Time spent on requests to the remote cluster per endpoint")
+            .buckets(
+                MILLIS_1_9.iter().chain(
+                MILLIS_10_90.iter()).chain(
+                MILLIS_100_900.iter()).chain(
+                SECONDS_1_9.iter()).chain(
+                SECONDS_10_19.iter()).cloned().collect()
+            ),
+        &["endpoint", "request"],
+    ).unwrap();
+
+    static ref REMOTE_CIRCUIT_OPEN_GAUGE: IntGaugeVec = IntGaugeVec::new(
+        Opts::new("remote_circuit_open", "1 while requests to the remote cluster are paused due to failures"),
+        &["endpoint"],
+    ).unwrap();
+
+    static ref LEDGER_TRANSACTION_WRITE_BACKLOG_GAUGE: IntGauge = IntGauge::new(
+        "ledger_transaction_write_backlog", "Number of transactions waiting to be written to the ledger",
+    ).unwrap();
+
+    static ref LEDGER_TRANSACTION_WRITE_BATCH_SIZE_HISTOGRAM: Histogram = Histogram::with_opts(
+        HistogramOpts::new("ledger_transaction_write_batch_size", "Number of transactions written to the ledger per batch")
+            .buckets(vec![1.0, 2.0, 4.0, 8.0, 16.0, 32.0, 64.0, 128.0, 256.0]),
+    ).unwrap();
+
+    static ref LEDGER_TRANSACTION_WRITE_TIME_HISTOGRAM: Histogram = Histogram::with_opts(
+        HistogramOpts::new("ledger_transaction_write_time", "Time spent writing a batch of transactions to the ledger")
+            .buckets(
+                MICROS_100_900.iter().chain(
+                MILLIS_1_9.iter()).chain(
+                MILLIS_10_90.iter()).chain(
+                MILLIS_100_900.iter()).cloned().collect()
+            ),
+    ).unwrap();
+
+    static ref SLOT_STATUS_NOTIFY_LAG_HISTOGRAM: HistogramVec = HistogramVec::new(
+        HistogramOpts::new("slot_status_notify_lag", "Time from advancing a slot until a consumer of slot status updates was notified of it")
+            .buckets(
+                MICROS_10_90.iter().chain(
+                MICROS_100_900.iter()).chain(
+                MILLIS_1_9.iter()).chain(
+                MILLIS_10_90.iter()).cloned().collect()
+            ),
+        &["consumer"],
+    ).unwrap();
+
+    static ref CLONE_HOOK_TIMEOUT_VEC_COUNT: IntCounterVec = IntCounterVec::new(
+        Opts::new("clone_hook_timeout_count", "Count of account clone hooks which did not finish in time per hook and stage"),
+        &["hook", "stage"],
+    ).unwrap();
+
+    static ref SUBSYSTEM_RESTART_VEC_COUNT: IntCounterVec = IntCounterVec::new(
+        Opts::new("subsystem_restart_count", "Count of restarts of subsystems which panicked or stopped unexpectedly"),
+        &["subsystem"],
+    ).unwrap();
+
     static ref FLUSH_ACCOUNTS_TIME_HISTOGRAM: Histogram = Histogram::with_opts(
         HistogramOpts::new("flush_accounts_time", "Time spent flushing accounts to disk")
             .buckets(
@@ -136,6 +386,16 @@ lazy_static::lazy_static! {
                 SECONDS_1_9.iter()).cloned().collect()
             ),
     ).unwrap();
+
+    static ref CLEAN_ACCOUNTS_TIME_HISTOGRAM: Histogram = Histogram::with_opts(
+        HistogramOpts::new("clean_accounts_time", "Time spent removing older account storage files from disk")
+            .buckets(
+                MILLIS_1_9.iter().chain(
+                MILLIS_10_90.iter()).chain(
+                MILLIS_100_900.iter()).chain(
+                SECONDS_1_9.iter()).cloned().collect()
+            ),
+    ).unwrap();
 }
 
 pub(crate) fn register() {
@@ -153,19 +413,59 @@ pub(crate) fn register() {
         register!(FEE_PAYER_VEC_COUNT);
         register!(EXECUTED_UNITS_COUNT);
         register!(FEE_COUNT);
+        register!(SPONSORED_TRANSACTION_COUNT);
+        register!(SPONSORED_FEE_LAMPORTS_COUNT);
         register!(ACCOUNT_CLONE_VEC_COUNT);
+        register!(ACCOUNT_CLONE_DATA_SIZE_HISTOGRAM);
+        register!(ACCOUNT_CLONE_TIME_HISTOGRAM);
+        register!(ACCOUNT_DUMP_VEC_COUNT);
+        register!(ACCOUNT_DUMP_TIME_HISTOGRAM);
         register!(ACCOUNT_COMMIT_VEC_COUNT);
         register!(ACCOUNT_COMMIT_TIME_HISTOGRAM);
+        register!(COMMIT_CONFIRMATION_TIME_HISTOGRAM);
+        register!(COMMIT_COST_LAMPORTS_COUNT);
+        register!(PROGRAM_COMMIT_COST_VEC_COUNT);
+        register!(COMMIT_COST_WINDOW_GAUGE);
+        register!(COMMIT_BUDGET_EXCEEDED_GAUGE);
+        register!(COMMIT_SIMULATION_FAILURE_COUNT);
         register!(LEDGER_SIZE_GAUGE);
         register!(ACCOUNTS_SIZE_GAUGE);
         register!(INMEM_ACCOUNTS_SIZE_GAUGE);
+        register!(ACCOUNTS_COUNT_GAUGE);
+        register!(ACCOUNTS_STORAGE_FILES_GAUGE);
+        register!(SLOW_ACCOUNT_WRITE_COUNT);
         register!(PENDING_ACCOUNT_CLONES_GAUGE);
         register!(ACTIVE_DATA_MODS_GAUGE);
         register!(ACTIVE_DATA_MODS_SIZE_GAUGE);
+        register!(SPILLED_DATA_MODS_GAUGE);
+        register!(SPILLED_DATA_MODS_SIZE_GAUGE);
+        register!(DATA_MODS_BUDGET_EXCEEDED_COUNT);
+        register!(MAGIC_CONTEXT_SCHEDULED_COMMITS_GAUGE);
+        register!(MAGIC_CONTEXT_SIZE_GAUGE);
+        register!(MAGIC_CONTEXT_CAPACITY_EXCEEDED_COUNT);
+        register!(MISSING_ACCOUNT_CACHE_HIT_COUNT);
+        register!(REJECTED_TRANSACTION_VEC_COUNT);
         register!(SIGVERIFY_TIME_HISTOGRAM);
         register!(ENSURE_ACCOUNTS_TIME_HISTOGRAM);
         register!(TRANSACTION_EXECUTION_TIME_HISTORY);
         register!(FLUSH_ACCOUNTS_TIME_HISTOGRAM);
+        register!(CLEAN_ACCOUNTS_TIME_HISTOGRAM);
+        register!(LEDGER_TRANSACTION_WRITE_BACKLOG_GAUGE);
+        register!(LEDGER_TRANSACTION_WRITE_BATCH_SIZE_HISTOGRAM);
+        register!(LEDGER_TRANSACTION_WRITE_TIME_HISTOGRAM);
+        register!(SLOT_STATUS_NOTIFY_LAG_HISTOGRAM);
+        register!(CLONE_HOOK_TIMEOUT_VEC_COUNT);
+        register!(SUBSYSTEM_RESTART_VEC_COUNT);
+        register!(RPC_REQUEST_VEC_COUNT);
+        register!(RPC_REQUEST_TIME_HISTOGRAM);
+        register!(PUBSUB_ACTIVE_CONNECTIONS_GAUGE);
+        register!(PUBSUB_SUBSCRIPTION_VEC_COUNT);
+        register!(PUBSUB_ACTIVE_SUBSCRIPTIONS_GAUGE);
+        register!(PUBSUB_SUBSCRIPTION_TIME_HISTOGRAM);
+        register!(PUBSUB_RESUBSCRIPTION_VEC_COUNT);
+        register!(REMOTE_RPC_REQUEST_VEC_COUNT);
+        register!(REMOTE_RPC_REQUEST_TIME_HISTOGRAM);
+        register!(REMOTE_CIRCUIT_OPEN_GAUGE);
     });
 }
 
@@ -173,6 +473,12 @@ pub fn inc_slot() {
     SLOT_COUNT.inc();
 }
 
+pub fn observe_slot_status_notify_lag(consumer: &str, lag: Duration) {
+    SLOT_STATUS_NOTIFY_LAG_HISTOGRAM
+        .with_label_values(&[consumer])
+        .observe(lag.as_secs_f64());
+}
+
 pub fn inc_transaction(is_ok: bool, fee_payer: &str) {
     let outcome = if is_ok { "success" } else { "error" };
     TRANSACTION_VEC_COUNT.with_label_values(&[outcome]).inc();
@@ -189,30 +495,45 @@ pub fn inc_fee(fee: u64) {
     FEE_COUNT.inc_by(fee);
 }
 
-pub fn inc_account_clone(account_clone: AccountClone) {
+pub fn inc_sponsored_transaction(fee: u64) {
+    SPONSORED_TRANSACTION_COUNT.inc();
+    SPONSORED_FEE_LAMPORTS_COUNT.inc_by(fee);
+}
+
+/// Pubkeys are not used as labels since their number is unbounded, instead
+/// the clones of each account are tracked in memory, see [top_cloned_accounts].
+pub fn observe_account_clone(
+    account_clone: AccountClone,
+    data_len: usize,
+    elapsed: Duration,
+) {
     use AccountClone::*;
-    match account_clone {
-        FeePayer { pubkey } => {
-            ACCOUNT_CLONE_VEC_COUNT
-                .with_label_values(&["feepayer", pubkey, ""])
-                .inc();
-        }
-        Undelegated { pubkey, owner } => {
-            ACCOUNT_CLONE_VEC_COUNT
-                .with_label_values(&["undelegated", pubkey, owner])
-                .inc();
-        }
-        Delegated { pubkey, owner } => {
-            ACCOUNT_CLONE_VEC_COUNT
-                .with_label_values(&["delegated", pubkey, owner])
-                .inc();
-        }
-        Program { pubkey } => {
-            ACCOUNT_CLONE_VEC_COUNT
-                .with_label_values(&["program", pubkey, ""])
-                .inc();
-        }
-    }
+    let (kind, pubkey, owner) = match account_clone {
+        FeePayer { pubkey } => ("feepayer", pubkey, ""),
+        Undelegated { pubkey, owner } => ("undelegated", pubkey, owner),
+        Delegated { pubkey, owner } => ("delegated", pubkey, owner),
+        Program { pubkey } => ("program", pubkey, ""),
+        Closed { pubkey } => ("closed", pubkey, ""),
+    };
+    ACCOUNT_CLONE_VEC_COUNT
+        .with_label_values(&[kind, owner])
+        .inc();
+    ACCOUNT_CLONE_DATA_SIZE_HISTOGRAM
+        .with_label_values(&[kind])
+        .observe(data_len as f64);
+    ACCOUNT_CLONE_TIME_HISTOGRAM
+        .with_label_values(&[kind])
+        .observe(elapsed.as_secs_f64());
+    cloned_accounts::record_cloned_account(pubkey, kind, data_len, elapsed);
+}
+
+pub fn observe_account_dump(kind: &str, outcome: Outcome, elapsed: Duration) {
+    ACCOUNT_DUMP_VEC_COUNT
+        .with_label_values(&[kind, outcome.as_str()])
+        .inc();
+    ACCOUNT_DUMP_TIME_HISTOGRAM
+        .with_label_values(&[kind])
+        .observe(elapsed.as_secs_f64());
 }
 
 pub fn inc_account_commit(account_commit: AccountCommit) {
@@ -243,6 +564,42 @@ pub fn account_commit_end(timer: HistogramTimer) {
     timer.stop_and_record();
 }
 
+/// Records how long confirming a commit took with the [method], i.e.
+/// a signature subscription or polling its status.
+pub fn observe_commit_confirmation(
+    method: &str,
+    outcome: Outcome,
+    elapsed: Duration,
+) {
+    COMMIT_CONFIRMATION_TIME_HISTOGRAM
+        .with_label_values(&[method, outcome.as_str()])
+        .observe(elapsed.as_secs_f64());
+}
+
+pub fn inc_commit_cost(lamports: u64) {
+    COMMIT_COST_LAMPORTS_COUNT.inc_by(lamports);
+}
+
+pub fn inc_program_commit_cost(program: &str, lamports: u64) {
+    PROGRAM_COMMIT_COST_VEC_COUNT
+        .with_label_values(&[program])
+        .inc_by(lamports);
+}
+
+pub fn set_commit_cost_window(window: &str, lamports: u64) {
+    COMMIT_COST_WINDOW_GAUGE
+        .with_label_values(&[window])
+        .set(lamports as i64);
+}
+
+pub fn set_commit_budget_exceeded(exceeded: bool) {
+    COMMIT_BUDGET_EXCEEDED_GAUGE.set(exceeded as i64);
+}
+
+pub fn inc_commit_simulation_failure() {
+    COMMIT_SIMULATION_FAILURE_COUNT.inc();
+}
+
 pub fn set_ledger_size(size: u64) {
     LEDGER_SIZE_GAUGE.set(size as i64);
 }
@@ -255,6 +612,18 @@ pub fn adjust_inmemory_accounts_size(delta: i64) {
     INMEM_ACCOUNTS_SIZE_GAUGE.add(delta);
 }
 
+pub fn set_accounts_count(count: usize) {
+    ACCOUNTS_COUNT_GAUGE.set(count as i64);
+}
+
+pub fn set_accounts_storage_files(count: usize) {
+    ACCOUNTS_STORAGE_FILES_GAUGE.set(count as i64);
+}
+
+pub fn inc_slow_account_write() {
+    SLOW_ACCOUNT_WRITE_COUNT.inc();
+}
+
 pub fn inc_pending_clone_requests() {
     PENDING_ACCOUNT_CLONES_GAUGE.inc()
 }
@@ -271,6 +640,43 @@ pub fn adjust_active_data_mods_size(delta: i64) {
     ACTIVE_DATA_MODS_SIZE_GAUGE.add(delta);
 }
 
+pub fn adjust_spilled_data_mods(delta: i64) {
+    SPILLED_DATA_MODS_GAUGE.add(delta)
+}
+
+pub fn adjust_spilled_data_mods_size(delta: i64) {
+    SPILLED_DATA_MODS_SIZE_GAUGE.add(delta);
+}
+
+pub fn inc_data_mods_budget_exceeded() {
+    DATA_MODS_BUDGET_EXCEEDED_COUNT.inc();
+}
+
+pub fn set_magic_context_occupancy(scheduled_commits: usize, size: u64) {
+    MAGIC_CONTEXT_SCHEDULED_COMMITS_GAUGE.set(scheduled_commits as i64);
+    MAGIC_CONTEXT_SIZE_GAUGE.set(size as i64);
+}
+
+pub fn inc_magic_context_capacity_exceeded() {
+    MAGIC_CONTEXT_CAPACITY_EXCEEDED_COUNT.inc();
+}
+
+pub fn inc_missing_account_cache_hit() {
+    MISSING_ACCOUNT_CACHE_HIT_COUNT.inc();
+}
+
+pub fn inc_duplicate_transaction() {
+    REJECTED_TRANSACTION_VEC_COUNT
+        .with_label_values(&["duplicate"])
+        .inc();
+}
+
+pub fn inc_expired_blockhash_transaction() {
+    REJECTED_TRANSACTION_VEC_COUNT
+        .with_label_values(&["expired_blockhash"])
+        .inc();
+}
+
 pub fn observe_sigverify_time<T, F>(f: F) -> T
 where
     F: FnOnce() -> T,
@@ -299,3 +705,105 @@ where
 {
     FLUSH_ACCOUNTS_TIME_HISTOGRAM.observe_closure_duration(f)
 }
+
+pub fn observe_clean_accounts_time<T, F>(f: F) -> T
+where
+    F: FnOnce() -> T,
+{
+    CLEAN_ACCOUNTS_TIME_HISTOGRAM.observe_closure_duration(f)
+}
+
+pub fn set_ledger_transaction_write_backlog(backlog: usize) {
+    LEDGER_TRANSACTION_WRITE_BACKLOG_GAUGE.set(backlog as i64);
+}
+
+pub fn observe_ledger_transaction_write_batch(len: usize, elapsed: Duration) {
+    LEDGER_TRANSACTION_WRITE_BATCH_SIZE_HISTOGRAM.observe(len as f64);
+    LEDGER_TRANSACTION_WRITE_TIME_HISTOGRAM.observe(elapsed.as_secs_f64());
+}
+
+pub fn inc_clone_hook_timeout(hook: &str, stage: &str) {
+    CLONE_HOOK_TIMEOUT_VEC_COUNT
+        .with_label_values(&[hook, stage])
+        .inc();
+}
+
+pub fn inc_subsystem_restart(subsystem: &str) {
+    SUBSYSTEM_RESTART_VEC_COUNT
+        .with_label_values(&[subsystem])
+        .inc();
+}
+
+pub fn observe_rpc_request(method: &str, outcome: Outcome, elapsed: Duration) {
+    RPC_REQUEST_VEC_COUNT
+        .with_label_values(&[method, outcome.as_str()])
+        .inc();
+    RPC_REQUEST_TIME_HISTOGRAM
+        .with_label_values(&[method])
+        .observe(elapsed.as_secs_f64());
+}
+
+pub fn inc_pubsub_connections() {
+    PUBSUB_ACTIVE_CONNECTIONS_GAUGE.inc();
+}
+
+pub fn dec_pubsub_connections() {
+    PUBSUB_ACTIVE_CONNECTIONS_GAUGE.dec();
+}
+
+pub fn inc_pubsub_subscription(subscription: &str, outcome: Outcome) {
+    PUBSUB_SUBSCRIPTION_VEC_COUNT
+        .with_label_values(&[subscription, outcome.as_str()])
+        .inc();
+}
+
+pub fn inc_active_pubsub_subscriptions(subscription: &str) {
+    PUBSUB_ACTIVE_SUBSCRIPTIONS_GAUGE
+        .with_label_values(&[subscription])
+        .inc();
+}
+
+pub fn dec_active_pubsub_subscriptions(subscription: &str, elapsed: Duration) {
+    PUBSUB_ACTIVE_SUBSCRIPTIONS_GAUGE
+        .with_label_values(&[subscription])
+        .dec();
+    PUBSUB_SUBSCRIPTION_TIME_HISTOGRAM
+        .with_label_values(&[subscription])
+        .observe(elapsed.as_secs_f64());
+}
+
+pub fn inc_pubsub_resubscription(subscription: &str) {
+    PUBSUB_RESUBSCRIPTION_VEC_COUNT
+        .with_label_values(&[subscription])
+        .inc();
+}
+
+/// Strips the scheme, path and query from the [url] of a remote endpoint
+/// since those may include API keys.
+pub fn remote_endpoint(url: &str) -> &str {
+    let without_scheme = url.split_once("://").map_or(url, |(_, rest)| rest);
+    without_scheme
+        .split(['/', '?'])
+        .next()
+        .unwrap_or(without_scheme)
+}
+
+pub fn observe_remote_rpc_request(
+    endpoint: &str,
+    request: &str,
+    outcome: Outcome,
+    elapsed: Duration,
+) {
+    REMOTE_RPC_REQUEST_VEC_COUNT
+        .with_label_values(&[endpoint, request, outcome.as_str()])
+        .inc();
+    REMOTE_RPC_REQUEST_TIME_HISTOGRAM
+        .with_label_values(&[endpoint, request])
+        .observe(elapsed.as_secs_f64());
+}
+
+pub fn set_remote_circuit_open(endpoint: &str, open: bool) {
+    REMOTE_CIRCUIT_OPEN_GAUGE
+        .with_label_values(&[endpoint])
+        .set(open as i64);
+}
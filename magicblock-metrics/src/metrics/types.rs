@@ -48,6 +48,7 @@ pub enum AccountClone<'a> {
     Undelegated { pubkey: &'a str, owner: &'a str },
     Delegated { pubkey: &'a str, owner: &'a str },
     Program { pubkey: &'a str },
+    Closed { pubkey: &'a str },
 }
 
 // -----------------
@@ -32,6 +32,7 @@ async fn main() {
             let slot = tx_processor.bank().slot();
             let recent_blockhash = tx_processor.bank().last_blockhash();
             transaction_to_clone_pubkey_from_cluster(
+                tx_processor.bank().validator_context(),
                 // We could also use Cluster::Development here which has the same URL
                 // but wanted to demonstrate using a custom URL
                 &Cluster::Custom("http://localhost:8899".parse().unwrap()),
@@ -1,3 +1,4 @@
+use magicblock_core::error_code::{HasErrorCode, MagicErrorCode};
 use solana_sdk::pubkey::Pubkey;
 use thiserror::Error;
 
@@ -15,6 +16,17 @@ pub enum MutatorError {
     MutatorModificationError(#[from] MutatorModificationError),
 }
 
+impl HasErrorCode for MutatorError {
+    fn error_code(&self) -> MagicErrorCode {
+        use MutatorError::*;
+        match self {
+            RpcClientError(_) => MagicErrorCode::RemoteRpcFailed,
+            PubkeyError(_) => MagicErrorCode::MutatorInvalidPubkey,
+            MutatorModificationError(err) => err.error_code(),
+        }
+    }
+}
+
 pub type MutatorModificationResult<T> = Result<T, MutatorModificationError>;
 
 #[derive(Debug, Clone, Error)]
@@ -24,4 +36,42 @@ pub enum MutatorModificationError {
 
     #[error("Invalid program data account '{0}' for program account '{1}'")]
     InvalidProgramDataContent(Pubkey, Pubkey),
+
+    #[error("Overrides for account '{1}' cannot be applied to account '{0}'")]
+    OverridesPubkeyMismatch(Pubkey, Pubkey),
+
+    #[error("Data of account '{0}' is {1} bytes, exceeding the maximum of {2} bytes")]
+    AccountDataTooLarge(Pubkey, usize, u64),
+
+    #[error("Owner '{1}' of account '{0}' is not a program")]
+    OwnerIsNotAProgram(Pubkey, Pubkey),
+
+    #[error(
+        "Executable account '{0}' is owned by '{1}' which is not a loader"
+    )]
+    ExecutableNotOwnedByLoader(Pubkey, Pubkey),
+}
+
+impl HasErrorCode for MutatorModificationError {
+    fn error_code(&self) -> MagicErrorCode {
+        use MutatorModificationError::*;
+        match self {
+            CouldNotFindExecutableDataAccount(_, _) => {
+                MagicErrorCode::MutatorProgramDataNotFound
+            }
+            InvalidProgramDataContent(_, _) => {
+                MagicErrorCode::MutatorInvalidProgramData
+            }
+            OverridesPubkeyMismatch(_, _) => {
+                MagicErrorCode::MutatorOverridesMismatch
+            }
+            AccountDataTooLarge(_, _, _) => {
+                MagicErrorCode::MutatorAccountDataTooLarge
+            }
+            OwnerIsNotAProgram(_, _) => MagicErrorCode::MutatorOwnerNotAProgram,
+            ExecutableNotOwnedByLoader(_, _) => {
+                MagicErrorCode::MutatorExecutableNotOwnedByLoader
+            }
+        }
+    }
 }
@@ -1,4 +1,6 @@
-use magicblock_program::magicblock_instruction::AccountModification;
+use magicblock_program::{
+    magicblock_instruction::AccountModification, ValidatorContext,
+};
 use solana_rpc_client::nonblocking::rpc_client::RpcClient;
 use solana_sdk::{
     account::Account, bpf_loader_upgradeable::get_program_data_address,
@@ -35,7 +37,9 @@ pub async fn fetch_account_from_cluster(
 /// If [overrides] are provided the included fields will be changed on the account
 /// that was downloaded from the cluster before the modification transaction is
 /// created.
+/// The transaction is signed by the authority of the validator [context].
 pub async fn transaction_to_clone_pubkey_from_cluster(
+    context: &ValidatorContext,
     cluster: &Cluster,
     needs_upgrade: bool,
     pubkey: &Pubkey,
@@ -47,12 +51,14 @@ pub async fn transaction_to_clone_pubkey_from_cluster(
     let account = &fetch_account_from_cluster(cluster, pubkey).await?;
     // If it's a regular account that's not executable (program), use happy path
     if !account.executable {
-        return Ok(transaction_to_clone_regular_account(
+        return transaction_to_clone_regular_account(
+            context,
             pubkey,
             account,
             overrides,
             recent_blockhash,
-        ));
+        )
+        .map_err(MutatorError::MutatorModificationError);
     }
     // To clone a program we need to update multiple accounts at the same time
     let program_id_pubkey = pubkey;
@@ -67,6 +73,7 @@ pub async fn transaction_to_clone_pubkey_from_cluster(
         program_data_modification,
         program_buffer_modification,
     } = create_program_modifications(
+        &context.validator_authority_id(),
         program_id_pubkey,
         program_id_account,
         &program_data_pubkey,
@@ -79,12 +86,14 @@ pub async fn transaction_to_clone_pubkey_from_cluster(
         fetch_program_idl_modification_from_cluster(cluster, program_id_pubkey)
             .await;
     // Done, generate the transaction as normal
-    Ok(transaction_to_clone_program(
+    transaction_to_clone_program(
+        context,
         needs_upgrade,
         program_id_modification,
         program_data_modification,
         program_buffer_modification,
         program_idl_modification,
         recent_blockhash,
-    ))
+    )
+    .map_err(MutatorError::MutatorModificationError)
 }
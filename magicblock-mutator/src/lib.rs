@@ -4,6 +4,7 @@ pub mod fetch;
 pub mod idl;
 pub mod program;
 pub mod transactions;
+pub mod validation;
 
 pub use cluster::*;
 pub use fetch::transaction_to_clone_pubkey_from_cluster;
@@ -1,6 +1,4 @@
-use magicblock_program::{
-    magicblock_instruction::AccountModification, validator,
-};
+use magicblock_program::magicblock_instruction::AccountModification;
 use solana_sdk::{
     account::Account,
     bpf_loader_upgradeable::{self, UpgradeableLoaderState},
@@ -20,6 +18,7 @@ pub struct ProgramModifications {
 }
 
 pub fn create_program_modifications(
+    validator_authority: &Pubkey,
     program_id_pubkey: &Pubkey,
     program_id_account: &Account,
     program_data_pubkey: &Pubkey,
@@ -52,13 +51,16 @@ pub fn create_program_modifications(
         AccountModification::from((program_id_pubkey, program_id_account));
     // Build the proper program_data that we will want to upgrade later
     let program_data_modification = create_program_data_modification(
+        validator_authority,
         program_data_pubkey,
         program_data_bytecode,
         slot,
     );
     // We need to create the upgrade buffer we will use for the bpf_loader transaction later
-    let program_buffer_modification =
-        create_program_buffer_modification(program_data_bytecode);
+    let program_buffer_modification = create_program_buffer_modification(
+        validator_authority,
+        program_data_bytecode,
+    );
     // Done
     Ok(ProgramModifications {
         program_id_modification,
@@ -68,6 +70,7 @@ pub fn create_program_modifications(
 }
 
 pub fn create_program_data_modification(
+    validator_authority: &Pubkey,
     program_data_pubkey: &Pubkey,
     program_data_bytecode: &[u8],
     slot: Slot,
@@ -75,7 +78,7 @@ pub fn create_program_data_modification(
     let mut program_data_data =
         bincode::serialize(&UpgradeableLoaderState::ProgramData {
             slot: slot.saturating_sub(1),
-            upgrade_authority_address: Some(validator::validator_authority_id()),
+            upgrade_authority_address: Some(*validator_authority),
         })
         .unwrap();
     program_data_data.extend_from_slice(program_data_bytecode);
@@ -94,11 +97,12 @@ pub fn create_program_data_modification(
 }
 
 pub fn create_program_buffer_modification(
+    validator_authority: &Pubkey,
     program_data_bytecode: &[u8],
 ) -> AccountModification {
     let mut program_buffer_data =
         bincode::serialize(&UpgradeableLoaderState::Buffer {
-            authority_address: Some(validator::validator_authority_id()),
+            authority_address: Some(*validator_authority),
         })
         .unwrap();
     program_buffer_data.extend_from_slice(program_data_bytecode);
@@ -1,51 +1,52 @@
 use magicblock_program::{
     magicblock_instruction::{
-        modify_accounts, modify_accounts_instruction, AccountModification,
+        modify_accounts, modify_accounts_atomic,
+        modify_accounts_atomic_instruction, AccountModification,
     },
-    validator,
+    ValidatorContext,
 };
 use solana_sdk::{
     account::Account, bpf_loader_upgradeable, hash::Hash, pubkey::Pubkey,
-    transaction::Transaction,
+    signer::Signer, transaction::Transaction,
+};
+
+use crate::{
+    errors::MutatorModificationResult,
+    validation::{apply_overrides, validate_account_modification},
 };
 
 pub fn transaction_to_clone_regular_account(
+    context: &ValidatorContext,
     pubkey: &Pubkey,
     account: &Account,
     overrides: Option<AccountModification>,
     recent_blockhash: Hash,
-) -> Transaction {
+) -> MutatorModificationResult<Transaction> {
     // Just a single mutation for regular accounts, just dump the data directly, while applying overrides
     let mut account_modification = AccountModification::from((pubkey, account));
-    if let Some(overrides) = overrides {
-        if let Some(lamports) = overrides.lamports {
-            account_modification.lamports = Some(lamports);
-        }
-        if let Some(owner) = &overrides.owner {
-            account_modification.owner = Some(*owner);
-        }
-        if let Some(executable) = overrides.executable {
-            account_modification.executable = Some(executable);
-        }
-        if let Some(data) = &overrides.data {
-            account_modification.data = Some(data.clone());
-        }
-        if let Some(rent_epoch) = overrides.rent_epoch {
-            account_modification.rent_epoch = Some(rent_epoch);
+    match overrides {
+        Some(overrides) => {
+            apply_overrides(&mut account_modification, overrides)?
         }
+        None => validate_account_modification(&account_modification)?,
     }
     // We only need a single transaction with a single mutation in this case
-    modify_accounts(vec![account_modification], recent_blockhash)
+    Ok(modify_accounts(
+        context,
+        vec![account_modification],
+        recent_blockhash,
+    ))
 }
 
 pub fn transaction_to_clone_program(
+    context: &ValidatorContext,
     needs_upgrade: bool,
     program_id_modification: AccountModification,
     program_data_modification: AccountModification,
     program_buffer_modification: AccountModification,
     program_idl_modification: Option<AccountModification>,
     recent_blockhash: Hash,
-) -> Transaction {
+) -> MutatorModificationResult<Transaction> {
     // We'll need to run the upgrade IX based on those
     let program_id_pubkey = program_id_modification.pubkey;
     let program_buffer_pubkey = program_buffer_modification.pubkey;
@@ -58,15 +59,26 @@ pub fn transaction_to_clone_program(
     if let Some(program_idl_modification) = program_idl_modification {
         account_modifications.push(program_idl_modification)
     }
+    for account_modification in &account_modifications {
+        validate_account_modification(account_modification)?;
+    }
+    // All program accounts are modified atomically, such that no transaction
+    // observes a program whose accounts are only partially cloned.
     // If the program does not exist yet, we just need to update it's data and don't
     // need to explicitly update using the BPF loader's Upgrade IX
     if !needs_upgrade {
-        return modify_accounts(account_modifications, recent_blockhash);
+        return Ok(modify_accounts_atomic(
+            context,
+            account_modifications,
+            recent_blockhash,
+        ));
     }
     // First dump the necessary set of account to our bank/ledger
-    let modify_ix = modify_accounts_instruction(account_modifications);
+    let modify_ix =
+        modify_accounts_atomic_instruction(context, account_modifications);
     // The validator is marked as the upgrade authority of all program accounts
-    let validator_pubkey = &validator::validator_authority_id();
+    let validator_authority = context.validator_authority();
+    let validator_pubkey = &validator_authority.pubkey();
     // Then we run the official BPF upgrade IX to notify the system of the new program
     let upgrade_ix = bpf_loader_upgradeable::upgrade(
         &program_id_pubkey,
@@ -75,10 +87,46 @@ pub fn transaction_to_clone_program(
         validator_pubkey,
     );
     // Sign the transaction
-    Transaction::new_signed_with_payer(
+    Ok(Transaction::new_signed_with_payer(
         &[modify_ix, upgrade_ix],
         Some(validator_pubkey),
-        &[&validator::validator_authority()],
+        &[&validator_authority],
         recent_blockhash,
-    )
+    ))
+}
+
+/// Max number of accounts imported per transaction, which keeps it well below
+/// the number of accounts a transaction may lock.
+pub const MAX_ACCOUNTS_PER_IMPORT_TRANSACTION: usize = 32;
+
+/// Creates the transactions storing the [accounts] as they are, i.e. when
+/// importing the state exported by another ephemeral.
+/// Going through transactions instead of storing the accounts in the bank
+/// directly ensures that they are locked and that the import is recorded in
+/// the ledger, such that it survives a replay.
+pub fn transactions_to_import_accounts(
+    context: &ValidatorContext,
+    accounts: &[(Pubkey, Account)],
+    recent_blockhash: Hash,
+) -> MutatorModificationResult<Vec<Transaction>> {
+    // Validate all modifications before any of their data is registered
+    let account_modifications = accounts
+        .iter()
+        .map(|(pubkey, account)| {
+            let account_modification =
+                AccountModification::from((pubkey, account));
+            validate_account_modification(&account_modification)?;
+            Ok(account_modification)
+        })
+        .collect::<MutatorModificationResult<Vec<_>>>()?;
+    Ok(account_modifications
+        .chunks(MAX_ACCOUNTS_PER_IMPORT_TRANSACTION)
+        .map(|account_modifications| {
+            modify_accounts(
+                context,
+                account_modifications.to_vec(),
+                recent_blockhash,
+            )
+        })
+        .collect())
 }
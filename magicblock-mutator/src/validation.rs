@@ -0,0 +1,200 @@
+use magicblock_program::magicblock_instruction::AccountModification;
+use solana_sdk::{
+    bpf_loader, bpf_loader_deprecated, bpf_loader_upgradeable, loader_v4,
+    native_loader, pubkey::Pubkey,
+    system_instruction::MAX_PERMITTED_DATA_LENGTH, sysvar,
+};
+
+use crate::errors::{MutatorModificationError, MutatorModificationResult};
+
+const LOADERS: [Pubkey; 5] = [
+    bpf_loader::ID,
+    bpf_loader_deprecated::ID,
+    bpf_loader_upgradeable::ID,
+    loader_v4::ID,
+    native_loader::ID,
+];
+
+/// Checks that the [modification] can be applied by the bank.
+/// Otherwise the transaction applying it would fail inside the runtime,
+/// which only tells us that _something_ went wrong.
+pub fn validate_account_modification(
+    modification: &AccountModification,
+) -> MutatorModificationResult<()> {
+    let pubkey = modification.pubkey;
+    if let Some(data) = &modification.data {
+        if data.len() as u64 > MAX_PERMITTED_DATA_LENGTH {
+            return Err(MutatorModificationError::AccountDataTooLarge(
+                pubkey,
+                data.len(),
+                MAX_PERMITTED_DATA_LENGTH,
+            ));
+        }
+    }
+    if let Some(owner) = modification.owner {
+        // An account cannot own itself and sysvars are data only
+        if owner == pubkey || sysvar::is_sysvar_id(&owner) {
+            return Err(MutatorModificationError::OwnerIsNotAProgram(
+                pubkey, owner,
+            ));
+        }
+        if modification.executable == Some(true) && !LOADERS.contains(&owner) {
+            return Err(MutatorModificationError::ExecutableNotOwnedByLoader(
+                pubkey, owner,
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Applies the fields that are set in [overrides] on top of the
+/// [modification] and validates the result.
+pub fn apply_overrides(
+    modification: &mut AccountModification,
+    overrides: AccountModification,
+) -> MutatorModificationResult<()> {
+    if overrides.pubkey != modification.pubkey {
+        return Err(MutatorModificationError::OverridesPubkeyMismatch(
+            modification.pubkey,
+            overrides.pubkey,
+        ));
+    }
+    if let Some(lamports) = overrides.lamports {
+        modification.lamports = Some(lamports);
+    }
+    if let Some(owner) = overrides.owner {
+        modification.owner = Some(owner);
+    }
+    if let Some(executable) = overrides.executable {
+        modification.executable = Some(executable);
+    }
+    if let Some(data) = overrides.data {
+        modification.data = Some(data);
+    }
+    if let Some(rent_epoch) = overrides.rent_epoch {
+        modification.rent_epoch = Some(rent_epoch);
+    }
+    validate_account_modification(modification)
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+    use solana_sdk::{account::Account, system_program};
+
+    use super::*;
+
+    fn regular_modification(pubkey: &Pubkey) -> AccountModification {
+        AccountModification::from((
+            pubkey,
+            &Account {
+                lamports: 1_000,
+                owner: system_program::id(),
+                data: vec![1; 8],
+                ..Default::default()
+            },
+        ))
+    }
+
+    #[test]
+    fn test_apply_overrides_on_regular_account() {
+        let pubkey = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let mut modification = regular_modification(&pubkey);
+        apply_overrides(
+            &mut modification,
+            AccountModification {
+                pubkey,
+                owner: Some(owner),
+                rent_epoch: Some(u64::MAX),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            modification,
+            AccountModification {
+                pubkey,
+                lamports: Some(1_000),
+                owner: Some(owner),
+                executable: Some(false),
+                data: Some(vec![1; 8]),
+                rent_epoch: Some(u64::MAX),
+            }
+        );
+    }
+
+    #[test]
+    fn test_apply_overrides_for_other_pubkey() {
+        let pubkey = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+        let mut modification = regular_modification(&pubkey);
+        let res = apply_overrides(
+            &mut modification,
+            AccountModification {
+                pubkey: other,
+                ..Default::default()
+            },
+        );
+        assert_matches!(
+            res,
+            Err(MutatorModificationError::OverridesPubkeyMismatch(p, o))
+                if p == pubkey && o == other
+        );
+    }
+
+    #[test]
+    fn test_apply_overrides_with_too_much_data() {
+        let pubkey = Pubkey::new_unique();
+        let mut modification = regular_modification(&pubkey);
+        let res = apply_overrides(
+            &mut modification,
+            AccountModification {
+                pubkey,
+                data: Some(vec![0; MAX_PERMITTED_DATA_LENGTH as usize + 1]),
+                ..Default::default()
+            },
+        );
+        assert_matches!(
+            res,
+            Err(MutatorModificationError::AccountDataTooLarge(p, len, _))
+                if p == pubkey && len == MAX_PERMITTED_DATA_LENGTH as usize + 1
+        );
+    }
+
+    #[test]
+    fn test_validate_owner_is_not_a_program() {
+        let pubkey = Pubkey::new_unique();
+        for owner in [pubkey, sysvar::clock::ID] {
+            let modification = AccountModification {
+                owner: Some(owner),
+                ..regular_modification(&pubkey)
+            };
+            assert_matches!(
+                validate_account_modification(&modification),
+                Err(MutatorModificationError::OwnerIsNotAProgram(p, o))
+                    if p == pubkey && o == owner
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_executable_owner() {
+        let pubkey = Pubkey::new_unique();
+        let executable = AccountModification {
+            executable: Some(true),
+            ..regular_modification(&pubkey)
+        };
+        assert_matches!(
+            validate_account_modification(&executable),
+            Err(MutatorModificationError::ExecutableNotOwnedByLoader(p, o))
+                if p == pubkey && o == system_program::id()
+        );
+
+        let program = AccountModification {
+            owner: Some(bpf_loader_upgradeable::ID),
+            ..executable
+        };
+        assert!(validate_account_modification(&program).is_ok());
+    }
+}
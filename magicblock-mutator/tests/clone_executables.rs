@@ -10,7 +10,7 @@ use magicblock_bank::{
     LAMPORTS_PER_SIGNATURE,
 };
 use magicblock_mutator::fetch::transaction_to_clone_pubkey_from_cluster;
-use magicblock_program::validator;
+use magicblock_program::ValidatorContext;
 use solana_sdk::{
     account::{Account, ReadableAccount},
     bpf_loader_upgradeable,
@@ -32,11 +32,13 @@ use crate::utils::{fund_luzifer, SOLX_EXEC, SOLX_IDL, SOLX_PROG};
 mod utils;
 
 async fn verified_tx_to_clone_executable_from_devnet_first_deploy(
+    context: &ValidatorContext,
     pubkey: &Pubkey,
     slot: Slot,
     recent_blockhash: Hash,
 ) -> Transaction {
     let tx = transaction_to_clone_pubkey_from_cluster(
+        context,
         &ClusterType::Devnet.into(),
         false, // We are deploying the program for the first time
         pubkey,
@@ -51,7 +53,7 @@ async fn verified_tx_to_clone_executable_from_devnet_first_deploy(
     assert_eq!(tx.signatures.len(), 1);
     assert_eq!(
         tx.signer_key(0, 0).unwrap(),
-        &validator::validator_authority_id()
+        &context.validator_authority_id()
     );
     assert!(tx.message().account_keys.len() >= 5);
     assert!(tx.message().account_keys.len() <= 6);
@@ -60,11 +62,13 @@ async fn verified_tx_to_clone_executable_from_devnet_first_deploy(
 }
 
 async fn verified_tx_to_clone_executable_from_devnet_as_upgrade(
+    context: &ValidatorContext,
     pubkey: &Pubkey,
     slot: Slot,
     recent_blockhash: Hash,
 ) -> Transaction {
     let tx = transaction_to_clone_pubkey_from_cluster(
+        context,
         &ClusterType::Devnet.into(),
         true, // We are upgrading the program
         pubkey,
@@ -79,7 +83,7 @@ async fn verified_tx_to_clone_executable_from_devnet_as_upgrade(
     assert_eq!(tx.signatures.len(), 1);
     assert_eq!(
         tx.signer_key(0, 0).unwrap(),
-        &validator::validator_authority_id()
+        &context.validator_authority_id()
     );
     assert!(tx.message().account_keys.len() >= 8);
     assert!(tx.message().account_keys.len() <= 9);
@@ -102,6 +106,7 @@ async fn clone_executable_with_idl_and_program_data_and_then_upgrade() {
     {
         let slot = tx_processor.bank().slot();
         let tx = verified_tx_to_clone_executable_from_devnet_first_deploy(
+            tx_processor.bank().validator_context(),
             &SOLX_PROG,
             slot,
             tx_processor.bank().last_blockhash(),
@@ -215,6 +220,7 @@ async fn clone_executable_with_idl_and_program_data_and_then_upgrade() {
     {
         let slot = tx_processor.bank().slot();
         let tx = verified_tx_to_clone_executable_from_devnet_as_upgrade(
+            tx_processor.bank().validator_context(),
             &SOLX_PROG,
             slot,
             tx_processor.bank().last_blockhash(),
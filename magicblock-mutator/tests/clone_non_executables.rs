@@ -1,7 +1,7 @@
 use assert_matches::assert_matches;
 use log::*;
 use magicblock_mutator::fetch::transaction_to_clone_pubkey_from_cluster;
-use magicblock_program::validator;
+use magicblock_program::ValidatorContext;
 use solana_sdk::{
     account::Account, clock::Slot, genesis_config::ClusterType, hash::Hash,
     native_token::LAMPORTS_PER_SOL, pubkey::Pubkey, system_program,
@@ -17,11 +17,13 @@ use crate::utils::{fund_luzifer, SOLX_POST, SOLX_PROG, SOLX_TIPS};
 mod utils;
 
 async fn verified_tx_to_clone_non_executable_from_devnet(
+    context: &ValidatorContext,
     pubkey: &Pubkey,
     slot: Slot,
     recent_blockhash: Hash,
 ) -> Transaction {
     let tx = transaction_to_clone_pubkey_from_cluster(
+        context,
         &ClusterType::Devnet.into(),
         false,
         pubkey,
@@ -36,7 +38,7 @@ async fn verified_tx_to_clone_non_executable_from_devnet(
     assert_eq!(tx.signatures.len(), 1);
     assert_eq!(
         tx.signer_key(0, 0).unwrap(),
-        &validator::validator_authority_id()
+        &context.validator_authority_id()
     );
     assert_eq!(tx.message().account_keys.len(), 3);
 
@@ -54,6 +56,7 @@ async fn clone_non_executable_without_data() {
 
     let slot = tx_processor.bank().slot();
     let tx = verified_tx_to_clone_non_executable_from_devnet(
+        tx_processor.bank().validator_context(),
         &SOLX_TIPS,
         slot,
         tx_processor.bank().last_blockhash(),
@@ -95,6 +98,7 @@ async fn clone_non_executable_with_data() {
 
     let slot = tx_processor.bank().slot();
     let tx = verified_tx_to_clone_non_executable_from_devnet(
+        tx_processor.bank().validator_context(),
         &SOLX_POST,
         slot,
         tx_processor.bank().last_blockhash(),
@@ -11,6 +11,7 @@ use magicblock_bank::{
     bank::{Bank, TransactionExecutionRecordingOpts},
     transaction_batch::TransactionBatch,
 };
+use magicblock_core::robust_lock::RobustMutex;
 use magicblock_transaction_status::{
     token_balances::TransactionTokenBalancesSet, TransactionStatusSender,
 };
@@ -121,8 +122,7 @@ fn execute_batches_internal(
 
                 let thread_index = PAR_THREAD_POOL.current_thread_index().unwrap();
                 execution_timings_per_thread
-                    .lock()
-                    .unwrap()
+                    .lock_robust()
                     .entry(thread_index)
                     .and_modify(|thread_execution_time| {
                         let ThreadExecuteTimings {
@@ -1,11 +1,17 @@
-use std::sync::{Arc, Mutex, MutexGuard};
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex, MutexGuard},
+};
 
 use lazy_static::lazy_static;
 use magicblock_bank::bank::Bank;
+use magicblock_core::robust_lock::RobustMutex;
 use magicblock_transaction_status::TransactionStatusSender;
 use solana_sdk::{
     signature::Signature,
-    transaction::{Result, SanitizedTransaction, Transaction},
+    transaction::{
+        Result, SanitizedTransaction, Transaction, TransactionError,
+    },
 };
 
 use crate::batch_processor::{execute_batch, TransactionBatchWithIndexes};
@@ -27,7 +33,25 @@ lazy_static! {
 }
 
 pub fn lock_transactions() -> MutexGuard<'static, usize> {
-    TRANSACTION_INDEX_MUTEX.lock().unwrap()
+    TRANSACTION_INDEX_MUTEX.lock_robust()
+}
+
+/// Like [execute_legacy_transaction], but for callers that already hold
+/// the lock obtained via [lock_transactions], i.e. in order to execute
+/// transactions right before the slot advances.
+pub fn execute_legacy_transaction_locked(
+    tx: Transaction,
+    bank: &Arc<Bank>,
+    transaction_status_sender: Option<&TransactionStatusSender>,
+    transaction_index_locked: &mut usize,
+) -> Result<Signature> {
+    let sanitized_tx = SanitizedTransaction::try_from_legacy_transaction(tx)?;
+    execute_sanitized_transaction_with_index(
+        sanitized_tx,
+        bank,
+        transaction_status_sender,
+        transaction_index_locked,
+    )
 }
 
 pub fn execute_sanitized_transaction(
@@ -35,9 +59,6 @@ pub fn execute_sanitized_transaction(
     bank: &Arc<Bank>,
     transaction_status_sender: Option<&TransactionStatusSender>,
 ) -> Result<Signature> {
-    let signature = *sanitized_tx.signature();
-    let txs = &[sanitized_tx];
-
     // Ensure that only one transaction is processed at a time even if it is initiated from
     // multiple threads.
     // TODO: This is a temporary solution until we have a transaction executor which schedules
@@ -45,7 +66,87 @@ pub fn execute_sanitized_transaction(
     // If we choose this as a long term solution we need to lock simulations/preflight with the
     // same mutex once we enable them again
     // Work tracked here: https://github.com/magicblock-labs/magicblock-validator/issues/181
-    let mut transaction_index_locked = TRANSACTION_INDEX_MUTEX.lock().unwrap();
+    let mut transaction_index_locked = TRANSACTION_INDEX_MUTEX.lock_robust();
+    execute_sanitized_transaction_with_index(
+        sanitized_tx,
+        bank,
+        transaction_status_sender,
+        &mut transaction_index_locked,
+    )
+}
+
+/// Why executing a bundle via [execute_sanitized_bundle] failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BundleError {
+    /// The transaction at the index failed, thus none of the bundle
+    /// transactions were committed.
+    Rejected(usize, TransactionError),
+    /// Committing the transaction at the index failed after the ones before
+    /// it, whose signatures are included, were committed already.
+    PartiallyCommitted(usize, TransactionError, Vec<Signature>),
+}
+
+/// Executes the transactions in order and atomically, either all of them are
+/// committed or none if any of them fails, in which case the index and error
+/// of the failing transaction are returned.
+/// Transactions are only committed once the whole bundle succeeded when
+/// simulated, should committing one of them still fail the ones before it
+/// remain committed, see [BundleError::PartiallyCommitted].
+/// No other transaction is executed in between and the slot does not advance
+/// while the bundle executes.
+pub fn execute_sanitized_bundle(
+    sanitized_txs: Vec<SanitizedTransaction>,
+    bank: &Arc<Bank>,
+    transaction_status_sender: Option<&TransactionStatusSender>,
+) -> std::result::Result<Vec<Signature>, BundleError> {
+    let mut transaction_index_locked = TRANSACTION_INDEX_MUTEX.lock_robust();
+
+    // The status cache only rejects duplicates once they were committed
+    let mut signatures = HashSet::new();
+    if let Some(index) = sanitized_txs
+        .iter()
+        .position(|tx| !signatures.insert(*tx.signature()))
+    {
+        return Err(BundleError::Rejected(
+            index,
+            TransactionError::AlreadyProcessed,
+        ));
+    }
+
+    // Committing the transactions only once all of them succeeded, ensures
+    // that no intermediate state of the bundle is observable
+    bank.simulate_bundle_unchecked(&sanitized_txs)
+        .map_err(|(index, err)| BundleError::Rejected(index, err))?;
+    let mut committed = Vec::with_capacity(sanitized_txs.len());
+    for (index, sanitized_tx) in sanitized_txs.into_iter().enumerate() {
+        match execute_sanitized_transaction_with_index(
+            sanitized_tx,
+            bank,
+            transaction_status_sender,
+            &mut transaction_index_locked,
+        ) {
+            Ok(signature) => committed.push(signature),
+            Err(err) if committed.is_empty() => {
+                return Err(BundleError::Rejected(index, err))
+            }
+            Err(err) => {
+                return Err(BundleError::PartiallyCommitted(
+                    index, err, committed,
+                ))
+            }
+        }
+    }
+    Ok(committed)
+}
+
+fn execute_sanitized_transaction_with_index(
+    sanitized_tx: SanitizedTransaction,
+    bank: &Arc<Bank>,
+    transaction_status_sender: Option<&TransactionStatusSender>,
+    transaction_index_locked: &mut usize,
+) -> Result<Signature> {
+    let signature = *sanitized_tx.signature();
+    let txs = &[sanitized_tx];
 
     let batch = bank.prepare_sanitized_batch(txs);
 
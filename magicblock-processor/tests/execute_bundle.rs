@@ -0,0 +1,91 @@
+use std::sync::Arc;
+
+use magicblock_bank::{
+    bank::Bank, bank_dev_utils::transactions::create_funded_account,
+};
+use magicblock_processor::execute_transaction::{
+    execute_sanitized_bundle, BundleError,
+};
+use solana_sdk::{
+    genesis_config::create_genesis_config,
+    instruction::InstructionError,
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    signature::Keypair,
+    signer::Signer,
+    system_transaction,
+    transaction::{SanitizedTransaction, TransactionError},
+};
+
+fn bank() -> Arc<Bank> {
+    let (genesis_config, _) = create_genesis_config(u64::MAX);
+    Arc::new(Bank::new_for_tests(&genesis_config, None, None))
+}
+
+fn transfer(
+    bank: &Bank,
+    from: &Keypair,
+    to: &Pubkey,
+    lamports: u64,
+) -> SanitizedTransaction {
+    SanitizedTransaction::from_transaction_for_tests(
+        system_transaction::transfer(from, to, lamports, bank.last_blockhash()),
+    )
+}
+
+#[test]
+fn test_bundle_commits_all_transactions() {
+    let bank = bank();
+    let alice = create_funded_account(&bank, Some(LAMPORTS_PER_SOL));
+    // Bob is only funded by the first transaction of the bundle
+    let bob = Keypair::new();
+    let carol = Pubkey::new_unique();
+    let bundle = vec![
+        transfer(&bank, &alice, &bob.pubkey(), LAMPORTS_PER_SOL / 2),
+        transfer(&bank, &bob, &carol, LAMPORTS_PER_SOL / 4),
+    ];
+    let expected_signatures =
+        bundle.iter().map(|tx| *tx.signature()).collect::<Vec<_>>();
+
+    let signatures = execute_sanitized_bundle(bundle, &bank, None).unwrap();
+    assert_eq!(signatures, expected_signatures);
+    assert_eq!(bank.get_balance(&carol), LAMPORTS_PER_SOL / 4);
+    assert!(bank.get_balance(&alice.pubkey()) <= LAMPORTS_PER_SOL / 2);
+}
+
+#[test]
+fn test_bundle_with_failing_transaction_commits_none() {
+    let bank = bank();
+    let alice = create_funded_account(&bank, Some(LAMPORTS_PER_SOL));
+    let (bob, carol) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let bundle = vec![
+        transfer(&bank, &alice, &bob, LAMPORTS_PER_SOL / 2),
+        // Succeeds on its own, but not after the first transfer
+        transfer(&bank, &alice, &carol, LAMPORTS_PER_SOL / 2),
+    ];
+
+    assert_eq!(
+        execute_sanitized_bundle(bundle, &bank, None),
+        Err(BundleError::Rejected(
+            1,
+            TransactionError::InstructionError(0, InstructionError::Custom(1))
+        ))
+    );
+    assert_eq!(bank.get_balance(&alice.pubkey()), LAMPORTS_PER_SOL);
+    assert_eq!(bank.get_balance(&bob), 0);
+    assert_eq!(bank.get_balance(&carol), 0);
+}
+
+#[test]
+fn test_bundle_with_duplicate_transaction_commits_none() {
+    let bank = bank();
+    let alice = create_funded_account(&bank, Some(LAMPORTS_PER_SOL));
+    let bob = Pubkey::new_unique();
+    let tx = transfer(&bank, &alice, &bob, LAMPORTS_PER_SOL / 4);
+
+    assert_eq!(
+        execute_sanitized_bundle(vec![tx.clone(), tx], &bank, None),
+        Err(BundleError::Rejected(1, TransactionError::AlreadyProcessed))
+    );
+    assert_eq!(bank.get_balance(&bob), 0);
+}
@@ -15,11 +15,16 @@ use solana_rpc_client_api::{
     filter::{MemcmpEncodedBytes, RpcFilterType},
     response::RpcLogsResponse,
 };
-use solana_sdk::{account::Account, pubkey::Pubkey, signature::Signature};
+use solana_sdk::{
+    account::Account, pubkey::Pubkey, signature::Signature,
+    transaction_context::TransactionReturnData,
+};
+use solana_transaction_status::UiTransactionReturnData;
 
 use crate::{
     errors::{PubsubError, PubsubResult},
-    types::SlotResponse,
+    program_events::program_events,
+    types::{ReturnDataResponse, SlotResponse},
 };
 
 // -----------------
@@ -74,6 +79,22 @@ pub fn try_geyser_sub_for_transaction_logs(
     Ok(map)
 }
 
+pub fn geyser_sub_for_program_return_data(
+    program_id: &Pubkey,
+) -> HashMap<String, SubscribeRequestFilterTransactions> {
+    let tx_sub = SubscribeRequestFilterTransactions {
+        vote: Some(false),
+        failed: None,
+        signature: None,
+        account_include: vec![],
+        account_exclude: vec![],
+        account_required: vec![program_id.to_string()],
+    };
+    let mut map = HashMap::new();
+    map.insert("program_return_data".to_string(), tx_sub);
+    map
+}
+
 // -----------------
 // geyser_sub_for_account
 // -----------------
@@ -324,3 +345,65 @@ pub fn try_subscribe_update_into_logs(
         logs,
     }))
 }
+
+// -----------------
+// Subscribe Update into Return Data
+// -----------------
+/// Returns `None` if the program neither set the return data of the
+/// transaction nor emitted any events.
+pub fn try_subscribe_update_into_return_data(
+    update: SubscribeUpdate,
+    program_id: &Pubkey,
+) -> PubsubResult<Option<ReturnDataResponse>> {
+    use UpdateOneof::*;
+    let tx = match update.update_oneof {
+        Some(Transaction(tx)) => match tx.transaction {
+            Some(tx) => tx,
+            None => return Ok(None),
+        },
+        _ => return Ok(None),
+    };
+
+    let signature = Signature::try_from(tx.signature)
+        .map_err(|err| {
+            PubsubError::InvalidTransactionSignature(format!("{:?}", err))
+        })?
+        .to_string();
+
+    let meta = match tx.meta {
+        Some(meta) => meta,
+        None => return Ok(None),
+    };
+
+    let return_data = meta
+        .return_data
+        .filter(|return_data| {
+            !meta.return_data_none
+                && return_data.program_id == program_id.as_ref()
+        })
+        .map(|return_data| {
+            UiTransactionReturnData::from(TransactionReturnData {
+                program_id: *program_id,
+                data: return_data.data,
+            })
+        });
+    let events = program_events(&meta.log_messages, &program_id.to_string());
+    if return_data.is_none() && events.is_empty() {
+        return Ok(None);
+    }
+
+    let err = meta
+        .err
+        .map(|err| bincode::deserialize(&err.err))
+        .transpose()
+        .map_err(|err| {
+            PubsubError::CouldNotConvertTransactionError(err.to_string())
+        })?;
+
+    Ok(Some(ReturnDataResponse {
+        signature,
+        err,
+        return_data,
+        events,
+    }))
+}
@@ -5,8 +5,13 @@ use solana_sdk::pubkey::Pubkey;
 use tokio_util::sync::CancellationToken;
 
 use crate::{
-    conversions::geyser_sub_for_account, errors::reject_internal_error,
-    handler::common::handle_account_geyser_update, subscription::assign_sub_id,
+    conversions::geyser_sub_for_account,
+    errors::reject_internal_error,
+    handler::{
+        common::handle_account_geyser_update,
+        geyser_subscription::GeyserSubscription,
+    },
+    subscription::assign_sub_id,
     types::AccountParams,
 };
 
@@ -27,13 +32,21 @@ pub async fn handle_account_subscribe(
         }
     };
 
-    let mut geyser_rx = match geyser_service.accounts_subscribe(
-        sub,
+    let geyser_sub = GeyserSubscription::try_new(
         subid,
+        "account",
         unsubscriber,
-        Some(&pubkey),
-    ) {
-        Ok(res) => res,
+        |unsubscriber, replay_cached| {
+            geyser_service.accounts_subscribe(
+                sub.clone(),
+                subid,
+                unsubscriber,
+                replay_cached.then_some(&pubkey),
+            )
+        },
+    );
+    let mut geyser_sub = match geyser_sub {
+        Ok(geyser_sub) => geyser_sub,
         Err(err) => {
             reject_internal_error(
                 subscriber,
@@ -47,7 +60,7 @@ pub async fn handle_account_subscribe(
     if let Some(sink) = assign_sub_id(subscriber, subid) {
         loop {
             tokio::select! {
-                val = geyser_rx.recv() => {
+                val = geyser_sub.recv() => {
                     match val {
                         Some(update) => {
                             if handle_account_geyser_update(
@@ -0,0 +1,226 @@
+use std::{fmt::Debug, time::Duration};
+
+use geyser_grpc_proto::{geyser::SubscribeUpdate, tonic::Status};
+use log::*;
+use magicblock_geyser_plugin::rpc::is_missed_updates_status;
+use magicblock_metrics::metrics;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+type GeyserReceiver = mpsc::Receiver<Result<SubscribeUpdate, Status>>;
+
+/// Times we try to re-establish a subscription without receiving any update
+/// in between before giving up on it
+const MAX_RESUBSCRIBE_ATTEMPTS: usize = 5;
+const RESUBSCRIBE_DELAY: Duration = Duration::from_millis(100);
+
+/// A subscription to the geyser service which is re-established whenever its
+/// stream breaks while the client is still subscribed, i.e. since the geyser
+/// message loop restarted or the subscription lagged behind.
+///
+/// The [subscribe] function is called with the token that ends the
+/// subscription and whether cached updates should be replayed. Those are
+/// only replayed when the subscription is first established or updates were
+/// missed, in which case the client is notified about that once the
+/// subscription was re-established.
+pub(super) struct GeyserSubscription<F> {
+    subid: u64,
+    kind: &'static str,
+    unsubscriber: CancellationToken,
+    subscribe: F,
+    geyser_rx: GeyserReceiver,
+    notify_missed_updates: bool,
+    resubscribe_attempts: usize,
+}
+
+impl<F, E> GeyserSubscription<F>
+where
+    F: FnMut(CancellationToken, bool) -> Result<GeyserReceiver, E>,
+    E: Debug,
+{
+    pub fn try_new(
+        subid: u64,
+        kind: &'static str,
+        unsubscriber: CancellationToken,
+        mut subscribe: F,
+    ) -> Result<Self, E> {
+        let geyser_rx = subscribe(unsubscriber.clone(), true)?;
+        Ok(Self {
+            subid,
+            kind,
+            unsubscriber,
+            subscribe,
+            geyser_rx,
+            notify_missed_updates: true,
+            resubscribe_attempts: 0,
+        })
+    }
+
+    /// Don't notify the client about missed updates, i.e. since they are
+    /// recovered otherwise.
+    pub fn without_missed_updates_notification(mut self) -> Self {
+        self.notify_missed_updates = false;
+        self
+    }
+
+    /// Receives the next update like [mpsc::Receiver::recv] and
+    /// re-establishes the subscription if its stream broke.
+    /// Returns an error status once after updates were missed.
+    pub async fn recv(&mut self) -> Option<Result<SubscribeUpdate, Status>> {
+        let mut missed_updates = None;
+        loop {
+            match self.geyser_rx.recv().await {
+                Some(Err(status)) if is_missed_updates_status(&status) => {
+                    debug!(
+                        "{} subscription {} missed updates: {:?}",
+                        self.kind, self.subid, status
+                    );
+                    missed_updates = Some(status);
+                }
+                Some(update) => {
+                    self.resubscribe_attempts = 0;
+                    return Some(update);
+                }
+                None => {
+                    if self.unsubscriber.is_cancelled() {
+                        return None;
+                    }
+                    self.resubscribe(missed_updates.is_some()).await?;
+                    if let Some(status) = missed_updates.take() {
+                        if self.notify_missed_updates {
+                            return Some(Err(Status::data_loss(format!(
+                                "Missed updates before the subscription was re-established: {}",
+                                status.message()
+                            ))));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    async fn resubscribe(&mut self, replay_cached: bool) -> Option<()> {
+        if self.resubscribe_attempts >= MAX_RESUBSCRIBE_ATTEMPTS {
+            warn!(
+                "Giving up on {} subscription {} after re-establishing it failed {} times",
+                self.kind, self.subid, self.resubscribe_attempts
+            );
+            return None;
+        }
+        self.resubscribe_attempts += 1;
+        tokio::time::sleep(RESUBSCRIBE_DELAY).await;
+
+        match (self.subscribe)(self.unsubscriber.clone(), replay_cached) {
+            Ok(geyser_rx) => {
+                info!(
+                    "Re-established {} subscription {} after its geyser stream ended",
+                    self.kind, self.subid
+                );
+                metrics::inc_pubsub_resubscription(self.kind);
+                self.geyser_rx = geyser_rx;
+                Some(())
+            }
+            Err(err) => {
+                error!(
+                    "Failed to re-establish {} subscription {}: {:?}",
+                    self.kind, self.subid, err
+                );
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use super::*;
+
+    /// Returns a subscribe function which hands out a stream per subscribe
+    /// call with the provided updates and records whether cached updates
+    /// were requested.
+    fn subscribe_with_streams(
+        streams: Vec<Vec<Result<SubscribeUpdate, Status>>>,
+        replayed: &mut Vec<bool>,
+    ) -> impl FnMut(CancellationToken, bool) -> Result<GeyserReceiver, ()> + '_
+    {
+        let mut streams = VecDeque::from(streams);
+        move |_, replay_cached| {
+            replayed.push(replay_cached);
+            let updates = streams.pop_front().ok_or(())?;
+            let (tx, rx) = mpsc::channel(updates.len().max(1));
+            for update in updates {
+                tx.try_send(update).unwrap();
+            }
+            Ok(rx)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resubscribes_and_notifies_missed_updates() {
+        let mut replayed = vec![];
+        let streams = vec![
+            vec![
+                Ok(SubscribeUpdate::default()),
+                Err(Status::data_loss("lagged")),
+            ],
+            vec![Ok(SubscribeUpdate::default())],
+        ];
+        let mut geyser_sub = GeyserSubscription::try_new(
+            1,
+            "account",
+            CancellationToken::new(),
+            subscribe_with_streams(streams, &mut replayed),
+        )
+        .unwrap();
+
+        assert!(matches!(geyser_sub.recv().await, Some(Ok(_))));
+        let missed = geyser_sub.recv().await.unwrap().unwrap_err();
+        assert!(is_missed_updates_status(&missed));
+        assert!(matches!(geyser_sub.recv().await, Some(Ok(_))));
+        // The third subscribe call fails since there are no more streams
+        assert!(geyser_sub.recv().await.is_none());
+        drop(geyser_sub);
+
+        assert_eq!(replayed, vec![true, true, false]);
+    }
+
+    #[tokio::test]
+    async fn test_resubscribes_silently_if_no_updates_were_missed() {
+        let mut replayed = vec![];
+        let streams =
+            vec![vec![], vec![], vec![Ok(SubscribeUpdate::default())]];
+        let mut geyser_sub = GeyserSubscription::try_new(
+            1,
+            "slot",
+            CancellationToken::new(),
+            subscribe_with_streams(streams, &mut replayed),
+        )
+        .unwrap();
+
+        assert!(matches!(geyser_sub.recv().await, Some(Ok(_))));
+        drop(geyser_sub);
+
+        assert_eq!(replayed, vec![true, false, false]);
+    }
+
+    #[tokio::test]
+    async fn test_does_not_resubscribe_after_unsubscribing() {
+        let mut replayed = vec![];
+        let unsubscriber = CancellationToken::new();
+        let mut geyser_sub = GeyserSubscription::try_new(
+            1,
+            "logs",
+            unsubscriber.clone(),
+            subscribe_with_streams(vec![vec![], vec![]], &mut replayed),
+        )
+        .unwrap();
+
+        unsubscriber.cancel();
+        assert!(geyser_sub.recv().await.is_none());
+        drop(geyser_sub);
+
+        assert_eq!(replayed, vec![true]);
+    }
+}
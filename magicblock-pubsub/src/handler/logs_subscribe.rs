@@ -10,6 +10,7 @@ use crate::{
         try_subscribe_update_into_logs,
     },
     errors::{reject_internal_error, sink_notify_error},
+    handler::geyser_subscription::GeyserSubscription,
     subscription::assign_sub_id,
     types::{LogsParams, ResponseWithSubscriptionId},
 };
@@ -33,13 +34,21 @@ pub async fn handle_logs_subscribe(
         }
     };
 
-    let mut geyser_rx = match geyser_service.transaction_subscribe(
-        sub,
+    let geyser_sub = GeyserSubscription::try_new(
         subid,
+        "logs",
         unsubscriber,
-        None,
-    ) {
-        Ok(res) => res,
+        |unsubscriber, _| {
+            geyser_service.transaction_subscribe(
+                sub.clone(),
+                subid,
+                unsubscriber,
+                None,
+            )
+        },
+    );
+    let mut geyser_sub = match geyser_sub {
+        Ok(geyser_sub) => geyser_sub,
         Err(err) => {
             reject_internal_error(
                 subscriber,
@@ -53,7 +62,7 @@ pub async fn handle_logs_subscribe(
     if let Some(sink) = assign_sub_id(subscriber, subid) {
         loop {
             tokio::select! {
-                val = geyser_rx.recv() => {
+                val = geyser_sub.recv() => {
                     match val {
                         Some(update) => {
                             if handle_transaction_logs_geyser_update(
@@ -1,6 +1,7 @@
 use std::time::Instant;
 
 use log::*;
+use magicblock_metrics::metrics;
 use tokio_util::sync::CancellationToken;
 
 use crate::{
@@ -8,6 +9,7 @@ use crate::{
         account_subscribe::handle_account_subscribe,
         logs_subscribe::handle_logs_subscribe,
         program_subscribe::handle_program_subscribe,
+        return_data_subscribe::handle_return_data_subscribe,
         signature_subscribe::handle_signature_subscribe,
         slot_subscribe::handle_slot_subscribe,
     },
@@ -16,8 +18,10 @@ use crate::{
 
 mod account_subscribe;
 mod common;
+mod geyser_subscription;
 mod logs_subscribe;
 mod program_subscribe;
+mod return_data_subscribe;
 mod signature_subscribe;
 mod slot_subscribe;
 
@@ -34,6 +38,7 @@ pub async fn handle_subscription(
             params,
         } => {
             let start = Instant::now();
+            metrics::inc_active_pubsub_subscriptions("account");
             tokio::select! {
                 _ = unsubscriber.cancelled() => {
                     debug!("AccountUnsubscribe: {}", subid);
@@ -48,6 +53,7 @@ pub async fn handle_subscription(
                 },
             };
             let elapsed = start.elapsed();
+            metrics::dec_active_pubsub_subscriptions("account", elapsed);
             debug!("accountSubscribe {} lasted for {:?}", subid, elapsed);
         }
         Program {
@@ -56,6 +62,7 @@ pub async fn handle_subscription(
             params,
         } => {
             let start = Instant::now();
+            metrics::inc_active_pubsub_subscriptions("program");
             tokio::select! {
                 _ = unsubscriber.cancelled() => {
                     debug!("ProgramUnsubscribe: {}", subid);
@@ -70,6 +77,7 @@ pub async fn handle_subscription(
                 },
             };
             let elapsed = start.elapsed();
+            metrics::dec_active_pubsub_subscriptions("program", elapsed);
             debug!("programSubscribe {} lasted for {:?}", subid, elapsed);
         }
         Slot {
@@ -77,6 +85,7 @@ pub async fn handle_subscription(
             geyser_service,
         } => {
             let start = Instant::now();
+            metrics::inc_active_pubsub_subscriptions("slot");
             tokio::select! {
                 _ = unsubscriber.cancelled() => {
                     debug!("SlotUnsubscribe: {}", subid);
@@ -89,6 +98,7 @@ pub async fn handle_subscription(
                 },
             };
             let elapsed = start.elapsed();
+            metrics::dec_active_pubsub_subscriptions("slot", elapsed);
             debug!("slotSubscribe {} lasted for {:?}", subid, elapsed);
         }
 
@@ -96,9 +106,10 @@ pub async fn handle_subscription(
             subscriber,
             geyser_service,
             params,
-            bank,
+            signature_statuses,
         } => {
             let start = Instant::now();
+            metrics::inc_active_pubsub_subscriptions("signature");
             tokio::select! {
                 _ = unsubscriber.cancelled() => {
                     debug!("SignatureUnsubscribe: {}", subid);
@@ -109,10 +120,11 @@ pub async fn handle_subscription(
                         unsubscriber.clone(),
                         &params,
                         &geyser_service,
-                        &bank) => {
+                        &signature_statuses) => {
                 },
             };
             let elapsed = start.elapsed();
+            metrics::dec_active_pubsub_subscriptions("signature", elapsed);
             debug!("slotSubscribe {} lasted for {:?}", subid, elapsed);
         }
         Logs {
@@ -121,6 +133,7 @@ pub async fn handle_subscription(
             params,
         } => {
             let start = Instant::now();
+            metrics::inc_active_pubsub_subscriptions("logs");
             tokio::select! {
                 _ = unsubscriber.cancelled() => {
                     debug!("LogsUnsubscribe: {}", subid);
@@ -135,7 +148,32 @@ pub async fn handle_subscription(
                 },
             };
             let elapsed = start.elapsed();
+            metrics::dec_active_pubsub_subscriptions("logs", elapsed);
             debug!("logsSubscribe {} lasted for {:?}", subid, elapsed);
         }
+        ReturnData {
+            subscriber,
+            geyser_service,
+            params,
+        } => {
+            let start = Instant::now();
+            metrics::inc_active_pubsub_subscriptions("return_data");
+            tokio::select! {
+                _ = unsubscriber.cancelled() => {
+                    debug!("ReturnDataUnsubscribe: {}", subid);
+                },
+                _ = handle_return_data_subscribe(
+                        subid,
+                        subscriber,
+                        unsubscriber.clone(),
+                        &params,
+                        &geyser_service,
+                    ) => {
+                },
+            };
+            let elapsed = start.elapsed();
+            metrics::dec_active_pubsub_subscriptions("return_data", elapsed);
+            debug!("returnDataSubscribe {} lasted for {:?}", subid, elapsed);
+        }
     }
 }
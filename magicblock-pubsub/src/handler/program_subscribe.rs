@@ -4,8 +4,13 @@ use magicblock_geyser_plugin::rpc::GeyserRpcService;
 use tokio_util::sync::CancellationToken;
 
 use crate::{
-    conversions::try_geyser_sub_for_program, errors::reject_internal_error,
-    handler::common::handle_account_geyser_update, subscription::assign_sub_id,
+    conversions::try_geyser_sub_for_program,
+    errors::reject_internal_error,
+    handler::{
+        common::handle_account_geyser_update,
+        geyser_subscription::GeyserSubscription,
+    },
+    subscription::assign_sub_id,
     types::ProgramParams,
 };
 
@@ -27,24 +32,35 @@ pub async fn handle_program_subscribe(
         }
     };
 
-    let mut geyser_rx =
-        match geyser_service.accounts_subscribe(sub, subid, unsubscriber, None)
-        {
-            Ok(res) => res,
-            Err(err) => {
-                reject_internal_error(
-                    subscriber,
-                    "Failed to subscribe to signature",
-                    Some(err),
-                );
-                return;
-            }
-        };
+    let geyser_sub = GeyserSubscription::try_new(
+        subid,
+        "program",
+        unsubscriber,
+        |unsubscriber, _| {
+            geyser_service.accounts_subscribe(
+                sub.clone(),
+                subid,
+                unsubscriber,
+                None,
+            )
+        },
+    );
+    let mut geyser_sub = match geyser_sub {
+        Ok(geyser_sub) => geyser_sub,
+        Err(err) => {
+            reject_internal_error(
+                subscriber,
+                "Failed to subscribe to signature",
+                Some(err),
+            );
+            return;
+        }
+    };
 
     if let Some(sink) = assign_sub_id(subscriber, subid) {
         loop {
             tokio::select! {
-                val = geyser_rx.recv() => {
+                val = geyser_sub.recv() => {
                     match val {
                         Some(update) => {
                             if handle_account_geyser_update(
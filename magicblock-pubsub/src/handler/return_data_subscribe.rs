@@ -0,0 +1,137 @@
+use std::str::FromStr;
+
+use geyser_grpc_proto::{geyser, tonic::Status};
+use jsonrpc_pubsub::{Sink, Subscriber};
+use log::*;
+use magicblock_geyser_plugin::rpc::GeyserRpcService;
+use solana_sdk::pubkey::Pubkey;
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    conversions::{
+        geyser_sub_for_program_return_data, slot_from_update,
+        try_subscribe_update_into_return_data,
+    },
+    errors::{reject_internal_error, sink_notify_error},
+    handler::geyser_subscription::GeyserSubscription,
+    subscription::assign_sub_id,
+    types::{ResponseWithSubscriptionId, ReturnDataParams},
+};
+
+pub async fn handle_return_data_subscribe(
+    subid: u64,
+    subscriber: Subscriber,
+    unsubscriber: CancellationToken,
+    params: &ReturnDataParams,
+    geyser_service: &GeyserRpcService,
+) {
+    // NOTE: the config only includes the commitment level which we don't use
+    let _config = params.config();
+
+    let program_id = match Pubkey::from_str(params.program_id()) {
+        Ok(program_id) => program_id,
+        Err(err) => {
+            reject_internal_error(subscriber, "Invalid program id", Some(err));
+            return;
+        }
+    };
+
+    let sub = geyser_sub_for_program_return_data(&program_id);
+    let geyser_sub = GeyserSubscription::try_new(
+        subid,
+        "return_data",
+        unsubscriber,
+        |unsubscriber, _| {
+            geyser_service.transaction_subscribe(
+                sub.clone(),
+                subid,
+                unsubscriber,
+                None,
+            )
+        },
+    );
+    let mut geyser_sub = match geyser_sub {
+        Ok(geyser_sub) => geyser_sub,
+        Err(err) => {
+            reject_internal_error(
+                subscriber,
+                "Failed to subscribe to return data",
+                Some(err),
+            );
+            return;
+        }
+    };
+
+    if let Some(sink) = assign_sub_id(subscriber, subid) {
+        loop {
+            tokio::select! {
+                val = geyser_sub.recv() => {
+                    match val {
+                        Some(update) => {
+                            if handle_return_data_geyser_update(
+                                &sink,
+                                subid,
+                                &program_id,
+                                update,
+                            ) {
+                                break;
+                            }
+                        }
+                        None => {
+                            debug!(
+                                "Geyser subscription has ended, finishing."
+                            );
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Handles geyser update for return data subscription.
+/// Returns true if subscription has ended.
+fn handle_return_data_geyser_update(
+    sink: &Sink,
+    subid: u64,
+    program_id: &Pubkey,
+    update: Result<geyser::SubscribeUpdate, Status>,
+) -> bool {
+    match update {
+        Ok(update) => {
+            debug!("Received geyser update: {:?}", update);
+            let slot = slot_from_update(&update).unwrap_or(0);
+            match try_subscribe_update_into_return_data(update, program_id) {
+                Ok(Some(return_data)) => {
+                    let res = ResponseWithSubscriptionId::new(
+                        return_data,
+                        slot,
+                        subid,
+                    );
+                    debug!("Sending response: {:?}", res);
+                    if let Err(err) = sink.notify(res.into_params_map()) {
+                        debug!("Subscription has ended, finishing {:?}.", err);
+                        true
+                    } else {
+                        false
+                    }
+                }
+                // The program was invoked without returning data or
+                // emitting events
+                Ok(None) => false,
+                Err(err) => {
+                    let msg = format!(
+                        "Failed to convert update to return data: {:?}",
+                        err
+                    );
+                    sink_notify_error(sink, msg)
+                }
+            }
+        }
+        Err(status) => sink_notify_error(
+            sink,
+            format!("Failed to receive return data update: {:?}", status),
+        ),
+    }
+}
@@ -1,9 +1,8 @@
-use std::{str::FromStr, time::Duration};
+use std::str::FromStr;
 
 use geyser_grpc_proto::{geyser, tonic::Status};
 use jsonrpc_pubsub::{Sink, Subscriber};
 use log::*;
-use magicblock_bank::bank::Bank;
 use magicblock_geyser_plugin::rpc::GeyserRpcService;
 use solana_rpc_client_api::response::{
     ProcessedSignatureResult, RpcSignatureResult,
@@ -14,6 +13,8 @@ use tokio_util::sync::CancellationToken;
 use crate::{
     conversions::{geyser_sub_for_transaction_signature, slot_from_update},
     errors::{reject_internal_error, sink_notify_error},
+    handler::geyser_subscription::GeyserSubscription,
+    signature_status::SignatureStatusPoller,
     subscription::assign_sub_id,
     types::{ResponseWithSubscriptionId, SignatureParams},
 };
@@ -24,7 +25,7 @@ pub async fn handle_signature_subscribe(
     unsubscriber: CancellationToken,
     params: &SignatureParams,
     geyser_service: &GeyserRpcService,
-    bank: &Bank,
+    signature_statuses: &SignatureStatusPoller,
 ) {
     let sigstr = params.signature();
     let sub = geyser_sub_for_transaction_signature(sigstr.to_string());
@@ -37,13 +38,22 @@ pub async fn handle_signature_subscribe(
         }
     };
 
-    let mut geyser_rx = match geyser_service.transaction_subscribe(
-        sub,
+    let geyser_sub = GeyserSubscription::try_new(
         subid,
+        "signature",
         unsubscriber,
-        Some(&sig),
-    ) {
-        Ok(res) => res,
+        |unsubscriber, replay_cached| {
+            geyser_service.transaction_subscribe(
+                sub.clone(),
+                subid,
+                unsubscriber,
+                replay_cached.then_some(&sig),
+            )
+        },
+    )
+    .map(GeyserSubscription::without_missed_updates_notification);
+    let mut geyser_sub = match geyser_sub {
+        Ok(geyser_sub) => geyser_sub,
         Err(err) => {
             reject_internal_error(
                 subscriber,
@@ -55,18 +65,12 @@ pub async fn handle_signature_subscribe(
     };
 
     if let Some(sink) = assign_sub_id(subscriber, subid) {
-        if let Some((slot, res)) = bank.get_recent_signature_status(
-            &sig,
-            Some(bank.slots_for_duration(Duration::from_secs(10))),
-        ) {
-            debug!(
-                "Sending initial signature status from bank: {} {:?}",
-                slot, res
-            );
+        if let Some((slot, res)) = signature_statuses.status(&sig) {
+            debug!("Sending initial signature status: {} {:?}", slot, res);
             sink_notify_transaction_result(&sink, slot, subid, res.err());
         } else {
             tokio::select! {
-                val = geyser_rx.recv() => {
+                val = geyser_sub.recv() => {
                     match val {
                         Some(update) => {
                             if handle_signature_geyser_update(
@@ -82,6 +86,18 @@ pub async fn handle_signature_subscribe(
                         }
                     }
                 }
+                (slot, res) = signature_statuses.poll(&sig) => {
+                    debug!(
+                        "Sending signature status missed by geyser: {} {:?}",
+                        slot, res
+                    );
+                    sink_notify_transaction_result(
+                        &sink,
+                        slot,
+                        subid,
+                        res.err(),
+                    );
+                }
             }
         }
     }
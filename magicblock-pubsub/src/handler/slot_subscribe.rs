@@ -9,6 +9,7 @@ use crate::{
         geyser_sub_for_slot_update, subscribe_update_into_slot_response,
     },
     errors::{reject_internal_error, sink_notify_error},
+    handler::geyser_subscription::GeyserSubscription,
     subscription::assign_sub_id,
     types::ReponseNoContextWithSubscriptionId,
 };
@@ -21,23 +22,30 @@ pub async fn handle_slot_subscribe(
 ) {
     let sub = geyser_sub_for_slot_update();
 
-    let mut geyser_rx =
-        match geyser_service.slot_subscribe(sub, subid, unsubscriber) {
-            Ok(res) => res,
-            Err(err) => {
-                reject_internal_error(
-                    subscriber,
-                    "Failed to subscribe to slot",
-                    Some(err),
-                );
-                return;
-            }
-        };
+    let geyser_sub = GeyserSubscription::try_new(
+        subid,
+        "slot",
+        unsubscriber,
+        |unsubscriber, _| {
+            geyser_service.slot_subscribe(sub.clone(), subid, unsubscriber)
+        },
+    );
+    let mut geyser_sub = match geyser_sub {
+        Ok(geyser_sub) => geyser_sub,
+        Err(err) => {
+            reject_internal_error(
+                subscriber,
+                "Failed to subscribe to slot",
+                Some(err),
+            );
+            return;
+        }
+    };
 
     if let Some(sink) = assign_sub_id(subscriber, subid) {
         loop {
             tokio::select! {
-                val = geyser_rx.recv() => {
+                val = geyser_sub.recv() => {
                     match val {
                         Some(update) => {
                             if handle_account_geyser_update(
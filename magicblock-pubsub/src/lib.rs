@@ -1,8 +1,10 @@
 mod conversions;
 pub mod errors;
 mod handler;
+mod program_events;
 mod pubsub_api;
 pub mod pubsub_service;
+pub mod signature_status;
 mod subscription;
 pub mod types;
 mod unsubscribe_tokens;
@@ -0,0 +1,76 @@
+const PROGRAM_LOG_PREFIX: &str = "Program ";
+const PROGRAM_DATA_PREFIX: &str = "Program data: ";
+
+/// Extracts the events a program emitted via `sol_log_data`, i.e. Anchor
+/// events, from the [logs] of a transaction.
+/// Events are attributed to the program that was executing when they were
+/// logged, by tracking the invocation stack, thus events emitted by programs
+/// invoked via CPI are not included for the caller.
+/// Each event consists of the space separated base64 encoded fields that
+/// were logged.
+pub fn program_events(logs: &[String], program_id: &str) -> Vec<String> {
+    let mut invocations = Vec::<&str>::new();
+    let mut events = vec![];
+    for log in logs {
+        if let Some(event) = log.strip_prefix(PROGRAM_DATA_PREFIX) {
+            if invocations.last() == Some(&program_id) {
+                events.push(event.to_string());
+            }
+            continue;
+        }
+        let Some((invoked_id, status)) = log
+            .strip_prefix(PROGRAM_LOG_PREFIX)
+            .and_then(|log| log.split_once(' '))
+        else {
+            continue;
+        };
+        if status.starts_with("invoke [") {
+            invocations.push(invoked_id);
+        } else if status == "success" || status.starts_with("failed") {
+            invocations.pop();
+        }
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn logs(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|line| line.to_string()).collect()
+    }
+
+    #[test]
+    fn test_events_of_program() {
+        let logs = logs(&[
+            "Program Game111 invoke [1]",
+            "Program log: Instruction: Attack",
+            "Program data: ZXZlbnQx",
+            "Program Token111 invoke [2]",
+            "Program data: dG9rZW4=",
+            "Program Token111 consumed 100 of 200 compute units",
+            "Program Token111 success",
+            "Program data: ZXZlbnQy dmFsdWU=",
+            "Program Game111 success",
+        ]);
+        assert_eq!(
+            program_events(&logs, "Game111"),
+            vec!["ZXZlbnQx".to_string(), "ZXZlbnQy dmFsdWU=".to_string()]
+        );
+        assert_eq!(program_events(&logs, "Token111"), vec!["dG9rZW4="]);
+        assert!(program_events(&logs, "Other111").is_empty());
+    }
+
+    #[test]
+    fn test_events_after_failed_invocation() {
+        let logs = logs(&[
+            "Program Game111 invoke [1]",
+            "Program Token111 invoke [2]",
+            "Program Token111 failed: custom program error: 0x1",
+            "Program data: ZXZlbnQ=",
+            "Program Game111 success",
+        ]);
+        assert_eq!(program_events(&logs, "Game111"), vec!["ZXZlbnQ="]);
+    }
+}
@@ -1,16 +1,22 @@
-use std::sync::Arc;
+use std::{
+    panic::{self, AssertUnwindSafe},
+    sync::Arc,
+};
 
 use jsonrpc_pubsub::Subscriber;
 use log::*;
-use magicblock_bank::bank::Bank;
 use magicblock_geyser_plugin::rpc::GeyserRpcService;
 use tokio::{sync::mpsc, task::JoinSet};
 
 use crate::{
     errors::{reject_internal_error, PubsubError, PubsubResult},
     handler::handle_subscription,
+    signature_status::SignatureStatusPoller,
     subscription::SubscriptionRequest,
-    types::{AccountParams, LogsParams, ProgramParams, SignatureParams},
+    types::{
+        AccountParams, LogsParams, ProgramParams, ReturnDataParams,
+        SignatureParams,
+    },
     unsubscribe_tokens::UnsubscribeTokens,
 };
 
@@ -27,6 +33,60 @@ impl SubscriptionsReceiver {
     }
 }
 
+async fn run_actor(
+    actor: &mut SubscriptionsReceiver,
+    unsubscribe_tokens: &UnsubscribeTokens,
+    subid: &mut u64,
+) {
+    let mut pending_subs = JoinSet::new();
+
+    macro_rules! handle_subscription {
+        ($subscription:ident) => {
+            match $subscription {
+                Some($subscription) => {
+                    *subid += 1;
+                    let unsubscriber = unsubscribe_tokens.add(*subid);
+                    pending_subs.spawn(handle_subscription(
+                        $subscription,
+                        *subid,
+                        unsubscriber,
+                    ));
+                    debug!(
+                        "Added subscription to a total of {}",
+                        pending_subs.len()
+                    );
+                }
+                None => break,
+            }
+        };
+    }
+
+    // Waiting for either of the two:
+    // a) a new subscriptions comes in and we add it to pending subscriptions
+    // b) polling subs, once done they are auto-removed from pending subscriptions
+    loop {
+        // In the case that there are no active subs we just wait for one to be
+        // registered
+        if pending_subs.is_empty() {
+            let subscription = actor.subscriptions.recv().await;
+            handle_subscription!(subscription);
+        } else {
+            // Otherwise we wait for a sub to complete or a new one to be
+            // registered
+            tokio::select! {
+                subscription = actor.subscriptions.recv() => {
+                    handle_subscription!(subscription)
+                },
+                next = pending_subs.join_next() => {
+                    if let Some(Err(err)) = next {
+                        error!("Failed to join task: {:?}", err)
+                    }
+                }
+            }
+        }
+    }
+}
+
 // -----------------
 // PubsubApi
 // -----------------
@@ -36,68 +96,44 @@ pub struct PubsubApi {
     unsubscribe_tokens: UnsubscribeTokens,
 }
 
+/// Decides if the actor is restarted after it panicked.
+pub type ActorRestartPolicy =
+    Arc<dyn Fn(std::thread::Result<()>) -> bool + Send + Sync>;
+
 impl PubsubApi {
-    pub fn new() -> Self {
+    /// Spawns the actor handling subscriptions.
+    /// If it panics it is restarted if the [actor_restart_policy] allows it,
+    /// subscriptions that were active at that point are dropped.
+    pub fn new(actor_restart_policy: Option<ActorRestartPolicy>) -> Self {
         let (subscribe_tx, subscribe_rx) = mpsc::channel(100);
         let unsubscribe_tokens = UnsubscribeTokens::new();
         {
             let unsubscribe_tokens = unsubscribe_tokens.clone();
             std::thread::spawn(move || {
-                tokio::runtime::Builder::new_multi_thread()
-                .enable_all()
-                .thread_name("PubsubActorRuntime")
-                .build()
-                .unwrap()
-                .block_on(async move {
-                    let mut subid: u64 = 0;
-                    let mut pending_subs = JoinSet::new();
-                    let mut actor = SubscriptionsReceiver::new(subscribe_rx);
-
-                    macro_rules! handle_subscription {
-                        ($subscription:ident) => {
-                            match $subscription {
-                                Some($subscription) => {
-                                    subid += 1;
-                                    let unsubscriber = unsubscribe_tokens.add(subid);
-                                    pending_subs
-                                        .spawn(handle_subscription(
-                                            $subscription,
-                                            subid,
-                                            unsubscriber
-                                        ));
-                                    debug!("Added subscription to a total of {}",
-                                        pending_subs.len());
-                                },
-                                None => break,
-                            }
-                        }
+                let runtime = tokio::runtime::Builder::new_multi_thread()
+                    .enable_all()
+                    .thread_name("PubsubActorRuntime")
+                    .build()
+                    .unwrap();
+                let mut actor = SubscriptionsReceiver::new(subscribe_rx);
+                let mut subid: u64 = 0;
+                loop {
+                    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                        runtime.block_on(run_actor(
+                            &mut actor,
+                            &unsubscribe_tokens,
+                            &mut subid,
+                        ))
+                    }));
+                    // The actor returns once the service is dropped
+                    let restart = result.is_err()
+                        && actor_restart_policy
+                            .as_ref()
+                            .is_some_and(|policy| policy(result));
+                    if !restart {
+                        break;
                     }
-
-                    // Waiting for either of the two:
-                    // a) a new subscriptions comes in and we add it to pending subscriptions
-                    // b) polling subs, once done they are auto-removed from pending subscriptions
-                    loop {
-                        // In the case that there are no active subs we just wait for one to be
-                        // registered
-                        if pending_subs.is_empty() {
-                            let subscription = actor.subscriptions.recv().await;
-                            handle_subscription!(subscription);
-                        } else {
-                            // Otherwise we wait for a sub to complete or a new one to be
-                                // registered
-                            tokio::select! {
-                                subscription = actor.subscriptions.recv() => {
-                                    handle_subscription!(subscription)
-                                },
-                                next = pending_subs.join_next() => {
-                                    if let Some(Err(err)) = next {
-                                        error!("Failed to join task: {:?}", err)
-                                    }
-                                }
-                            }
-                        }
-                    }
-                });
+                }
             });
         }
 
@@ -161,14 +197,14 @@ impl PubsubApi {
         subscriber: Subscriber,
         params: SignatureParams,
         geyser_service: Arc<GeyserRpcService>,
-        bank: Arc<Bank>,
+        signature_statuses: Arc<SignatureStatusPoller>,
     ) -> PubsubResult<()> {
         self.subscribe
             .blocking_send(SubscriptionRequest::Signature {
                 subscriber,
                 params,
                 geyser_service,
-                bank,
+                signature_statuses,
             })
             .map_err(map_send_error)?;
 
@@ -192,6 +228,23 @@ impl PubsubApi {
         Ok(())
     }
 
+    pub fn return_data_subscribe(
+        &self,
+        subscriber: Subscriber,
+        params: ReturnDataParams,
+        geyser_service: Arc<GeyserRpcService>,
+    ) -> PubsubResult<()> {
+        self.subscribe
+            .blocking_send(SubscriptionRequest::ReturnData {
+                subscriber,
+                params,
+                geyser_service,
+            })
+            .map_err(map_send_error)?;
+
+        Ok(())
+    }
+
     pub fn unsubscribe(&self, id: u64) {
         self.unsubscribe_tokens.unsubscribe(id);
     }
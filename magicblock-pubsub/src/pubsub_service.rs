@@ -1,5 +1,5 @@
 use std::{
-    net::{IpAddr, SocketAddr},
+    net::SocketAddr,
     sync::{Arc, RwLock},
     thread,
 };
@@ -11,14 +11,25 @@ use jsonrpc_pubsub::{
 use jsonrpc_ws_server::{CloseHandle, RequestContext, Server, ServerBuilder};
 use log::*;
 use magicblock_bank::bank::Bank;
+use magicblock_core::robust_lock::RobustRwLock;
 use magicblock_geyser_plugin::rpc::GeyserRpcService;
+use magicblock_ledger::Ledger;
+use magicblock_metrics::metrics;
 use serde_json::Value;
 use solana_sdk::rpc_port::DEFAULT_RPC_PUBSUB_PORT;
 
+pub use crate::pubsub_api::ActorRestartPolicy;
 use crate::{
     errors::{ensure_and_try_parse_params, ensure_empty_params, PubsubResult},
     pubsub_api::PubsubApi,
-    types::{AccountParams, LogsParams, ProgramParams, SignatureParams},
+    signature_status::{
+        SignatureStatusPoller, SignatureStatusProvider,
+        DEFAULT_SIGNATURE_POLL_INTERVAL,
+    },
+    types::{
+        AccountParams, LogsParams, ProgramParams, ReturnDataParams,
+        SignatureParams,
+    },
 };
 
 // -----------------
@@ -30,10 +41,8 @@ pub struct PubsubConfig {
 }
 
 impl PubsubConfig {
-    pub fn from_rpc(rpc_addr: IpAddr, rpc_port: u16) -> Self {
-        Self {
-            socket: SocketAddr::new(rpc_addr, rpc_port + 1),
-        }
+    pub fn new(socket: SocketAddr) -> Self {
+        Self { socket }
     }
 }
 
@@ -57,7 +66,7 @@ pub struct PubsubService {
     geyser_service: Arc<GeyserRpcService>,
     config: PubsubConfig,
     io: PubSubHandler<Arc<Session>>,
-    bank: Arc<Bank>,
+    signature_statuses: Arc<SignatureStatusPoller>,
 }
 
 impl PubsubService {
@@ -65,14 +74,27 @@ impl PubsubService {
         config: PubsubConfig,
         geyser_rpc_service: Arc<GeyserRpcService>,
         bank: Arc<Bank>,
+        ledger: Option<Arc<Ledger>>,
+        actor_restart_policy: Option<ActorRestartPolicy>,
     ) -> Self {
         let io = PubSubHandler::new(MetaIoHandler::default());
+        // Statuses of transactions evicted from the bank's status cache are
+        // only found in the ledger
+        let mut signature_status_providers: Vec<
+            Arc<dyn SignatureStatusProvider>,
+        > = vec![bank];
+        if let Some(ledger) = ledger {
+            signature_status_providers.push(ledger);
+        }
         let service = Self {
-            api: PubsubApi::new(),
+            api: PubsubApi::new(actor_restart_policy),
             config,
             io,
             geyser_service: geyser_rpc_service,
-            bank,
+            signature_statuses: Arc::new(SignatureStatusPoller::new(
+                signature_status_providers,
+                DEFAULT_SIGNATURE_POLL_INTERVAL,
+            )),
         };
 
         service
@@ -81,12 +103,18 @@ impl PubsubService {
             .add_slot_subscribe()
             .add_signature_subscribe()
             .add_logs_subscribe()
+            .add_return_data_subscribe()
     }
 
     #[allow(clippy::result_large_err)]
     pub fn start(self) -> jsonrpc_ws_server::Result<Server> {
-        let extractor =
-            |context: &RequestContext| Arc::new(Session::new(context.sender()));
+        // A session is created per websocket connection
+        let extractor = |context: &RequestContext| {
+            let session = Session::new(context.sender());
+            metrics::inc_pubsub_connections();
+            session.on_drop(metrics::dec_pubsub_connections);
+            Arc::new(session)
+        };
 
         ServerBuilder::with_meta_extractor(self.io, extractor)
             .start(&self.config.socket)
@@ -98,7 +126,8 @@ impl PubsubService {
         bank: Arc<Bank>,
     ) -> PubsubResult<(thread::JoinHandle<()>, PubsubServiceCloseHandle)> {
         let socket = *config.socket();
-        let service = PubsubService::new(config, geyser_rpc_service, bank);
+        let service =
+            PubsubService::new(config, geyser_rpc_service, bank, None, None);
         Self::spawn(service, &socket)
     }
 
@@ -124,7 +153,7 @@ impl PubsubService {
 
                 info!("Pubsub server started on {}", socket);
                 let close_handle = server.close_handle().clone();
-                close_handle_rc.write().unwrap().replace(close_handle);
+                close_handle_rc.write_robust().replace(close_handle);
                 let _ = server.wait();
             })
         };
@@ -132,7 +161,7 @@ impl PubsubService {
     }
 
     pub fn close(close_handle: &PubsubServiceCloseHandle) {
-        if let Some(close_handle) = close_handle.write().unwrap().take() {
+        if let Some(close_handle) = close_handle.write_robust().take() {
             close_handle.close();
         }
     }
@@ -146,19 +175,28 @@ impl PubsubService {
                     match ensure_and_try_parse_params(subscriber, params) {
                         Some((subscriber, params)) => (subscriber, params),
                         None => {
+                            metrics::inc_pubsub_subscription(
+                                "account",
+                                metrics::Outcome::Error,
+                            );
                             return;
                         }
                     };
 
                 debug!("{:#?}", account_params);
 
-                if let Err(err) = api.account_subscribe(
+                let res = api.account_subscribe(
                     subscriber,
                     account_params,
                     geyser_service.clone(),
-                ) {
+                );
+                metrics::inc_pubsub_subscription(
+                    "account",
+                    metrics::Outcome::from_success(res.is_ok()),
+                );
+                if let Err(err) = res {
                     error!("Failed to handle account subscribe: {:?}", err);
-                };
+                }
             }
         };
         let unsubscribe = self.create_unsubscribe();
@@ -182,19 +220,28 @@ impl PubsubService {
                     match ensure_and_try_parse_params(subscriber, params) {
                         Some((subscriber, params)) => (subscriber, params),
                         None => {
+                            metrics::inc_pubsub_subscription(
+                                "program",
+                                metrics::Outcome::Error,
+                            );
                             return;
                         }
                     };
 
                 debug!("{:#?}", program_params);
 
-                if let Err(err) = api.program_subscribe(
+                let res = api.program_subscribe(
                     subscriber,
                     program_params,
                     geyser_service.clone(),
-                ) {
+                );
+                metrics::inc_pubsub_subscription(
+                    "program",
+                    metrics::Outcome::from_success(res.is_ok()),
+                );
+                if let Err(err) = res {
                     error!("Failed to handle program subscribe: {:?}", err);
-                };
+                }
             }
         };
         let unsubscribe = self.create_unsubscribe();
@@ -217,14 +264,24 @@ impl PubsubService {
                 let subscriber =
                     match ensure_empty_params(subscriber, &params, true) {
                         Some(subscriber) => subscriber,
-                        None => return,
+                        None => {
+                            metrics::inc_pubsub_subscription(
+                                "slot",
+                                metrics::Outcome::Error,
+                            );
+                            return;
+                        }
                     };
 
-                if let Err(err) =
-                    api.slot_subscribe(subscriber, geyser_service.clone())
-                {
+                let res =
+                    api.slot_subscribe(subscriber, geyser_service.clone());
+                metrics::inc_pubsub_subscription(
+                    "slot",
+                    metrics::Outcome::from_success(res.is_ok()),
+                );
+                if let Err(err) = res {
                     error!("Failed to handle slot subscribe: {:?}", err);
-                };
+                }
             }
         };
         let unsubscribe = self.create_unsubscribe();
@@ -243,24 +300,33 @@ impl PubsubService {
         let subscribe = {
             let api = self.api.clone();
             let geyser_service = self.geyser_service.clone();
-            let bank = self.bank.clone();
+            let signature_statuses = self.signature_statuses.clone();
             move |params: Params, _, subscriber: Subscriber| {
                 let (subscriber, params): (Subscriber, SignatureParams) =
                     match ensure_and_try_parse_params(subscriber, params) {
                         Some((subscriber, params)) => (subscriber, params),
                         None => {
+                            metrics::inc_pubsub_subscription(
+                                "signature",
+                                metrics::Outcome::Error,
+                            );
                             return;
                         }
                     };
 
-                if let Err(err) = api.signature_subscribe(
+                let res = api.signature_subscribe(
                     subscriber,
                     params,
                     geyser_service.clone(),
-                    bank.clone(),
-                ) {
+                    signature_statuses.clone(),
+                );
+                metrics::inc_pubsub_subscription(
+                    "signature",
+                    metrics::Outcome::from_success(res.is_ok()),
+                );
+                if let Err(err) = res {
                     error!("Failed to handle signature subscribe: {:?}", err);
-                };
+                }
             }
         };
         let unsubscribe = self.create_unsubscribe();
@@ -284,19 +350,28 @@ impl PubsubService {
                     match ensure_and_try_parse_params(subscriber, params) {
                         Some((subscriber, params)) => (subscriber, params),
                         None => {
+                            metrics::inc_pubsub_subscription(
+                                "logs",
+                                metrics::Outcome::Error,
+                            );
                             return;
                         }
                     };
 
                 debug!("{:#?}", logs_params);
 
-                if let Err(err) = api.logs_subscribe(
+                let res = api.logs_subscribe(
                     subscriber,
                     logs_params,
                     geyser_service.clone(),
-                ) {
+                );
+                metrics::inc_pubsub_subscription(
+                    "logs",
+                    metrics::Outcome::from_success(res.is_ok()),
+                );
+                if let Err(err) = res {
                     error!("Failed to handle logs subscribe: {:?}", err);
-                };
+                }
             }
         };
         let unsubscribe = self.create_unsubscribe();
@@ -311,6 +386,53 @@ impl PubsubService {
         self
     }
 
+    fn add_return_data_subscribe(mut self) -> Self {
+        let subscribe = {
+            let api = self.api.clone();
+            let geyser_service = self.geyser_service.clone();
+            move |params: Params, _, subscriber: Subscriber| {
+                let (subscriber, return_data_params): (
+                    Subscriber,
+                    ReturnDataParams,
+                ) = match ensure_and_try_parse_params(subscriber, params) {
+                    Some((subscriber, params)) => (subscriber, params),
+                    None => {
+                        metrics::inc_pubsub_subscription(
+                            "return_data",
+                            metrics::Outcome::Error,
+                        );
+                        return;
+                    }
+                };
+
+                debug!("{:#?}", return_data_params);
+
+                let res = api.return_data_subscribe(
+                    subscriber,
+                    return_data_params,
+                    geyser_service.clone(),
+                );
+                metrics::inc_pubsub_subscription(
+                    "return_data",
+                    metrics::Outcome::from_success(res.is_ok()),
+                );
+                if let Err(err) = res {
+                    error!("Failed to handle return data subscribe: {:?}", err);
+                }
+            }
+        };
+        let unsubscribe = self.create_unsubscribe();
+
+        let io = &mut self.io;
+        io.add_subscription(
+            "returnDataNotification",
+            ("returnDataSubscribe", subscribe),
+            ("returnDataUnsubscribe", unsubscribe),
+        );
+
+        self
+    }
+
     fn create_unsubscribe(&self) -> impl UnsubscribeRpcMethod<Arc<Session>> {
         let actor = self.api.clone();
         move |id: SubscriptionId,
@@ -0,0 +1,321 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use log::*;
+use magicblock_bank::bank::Bank;
+use magicblock_core::robust_lock::RobustMutex;
+use magicblock_ledger::Ledger;
+use solana_sdk::{clock::Slot, signature::Signature, transaction};
+use tokio::sync::oneshot;
+
+/// Interval at which pending signature subscriptions check if the
+/// transaction was processed without them being notified by geyser.
+pub const DEFAULT_SIGNATURE_POLL_INTERVAL: Duration =
+    Duration::from_millis(200);
+
+/// The slot at which a transaction was processed and its result.
+pub type SignatureStatus = (Slot, transaction::Result<()>);
+
+// -----------------
+// SignatureStatusProvider
+// -----------------
+/// Source of statuses of transactions that were already processed.
+pub trait SignatureStatusProvider: Send + Sync {
+    fn signature_status(
+        &self,
+        signature: &Signature,
+    ) -> Option<SignatureStatus>;
+}
+
+impl SignatureStatusProvider for Bank {
+    fn signature_status(
+        &self,
+        signature: &Signature,
+    ) -> Option<SignatureStatus> {
+        self.get_recent_signature_status(
+            signature,
+            Some(self.slots_for_duration(Duration::from_secs(10))),
+        )
+    }
+}
+
+impl SignatureStatusProvider for Ledger {
+    fn signature_status(
+        &self,
+        signature: &Signature,
+    ) -> Option<SignatureStatus> {
+        match self.get_transaction_status(*signature, Slot::MAX) {
+            Ok(status) => status.map(|(slot, meta)| (slot, meta.status)),
+            Err(err) => {
+                warn!(
+                    "Error loading signature {} from ledger: {:?}",
+                    signature, err
+                );
+                None
+            }
+        }
+    }
+}
+
+// -----------------
+// SignatureStatusPoller
+// -----------------
+/// Resolves the status of transactions for signature subscriptions.
+///
+/// Geyser only notifies subscriptions about transactions processed after they
+/// subscribed and its transactions cache only keeps them for a limited time.
+/// Thus a subscription that arrives late or races with the transaction would
+/// wait forever, which is why pending subscriptions also poll the providers,
+/// i.e. the bank's status cache followed by the ledger.
+///
+/// All pending subscriptions share a single polling task which only runs
+/// while at least one of them is waiting.
+pub struct SignatureStatusPoller {
+    inner: Arc<PollerInner>,
+}
+
+struct PollerInner {
+    providers: Vec<Arc<dyn SignatureStatusProvider>>,
+    poll_interval: Duration,
+    pending: Mutex<PendingSignatures>,
+}
+
+#[derive(Default)]
+struct PendingSignatures {
+    waiters: HashMap<Signature, Vec<oneshot::Sender<SignatureStatus>>>,
+    is_polling: bool,
+}
+
+impl SignatureStatusPoller {
+    pub fn new(
+        providers: Vec<Arc<dyn SignatureStatusProvider>>,
+        poll_interval: Duration,
+    ) -> Self {
+        Self {
+            inner: Arc::new(PollerInner {
+                providers,
+                poll_interval,
+                pending: Mutex::default(),
+            }),
+        }
+    }
+
+    /// Returns the status from the first provider that knows the
+    /// [signature].
+    pub fn status(&self, signature: &Signature) -> Option<SignatureStatus> {
+        self.inner.status(signature)
+    }
+
+    /// Resolves once any provider knows the status of the [signature],
+    /// which is checked every poll interval by the shared polling task.
+    pub async fn poll(&self, signature: &Signature) -> SignatureStatus {
+        let (sender, receiver) = oneshot::channel();
+        {
+            let mut pending = self.inner.pending.lock_robust();
+            pending.waiters.entry(*signature).or_default().push(sender);
+            if !pending.is_polling {
+                pending.is_polling = true;
+                tokio::spawn(self.inner.clone().run());
+            }
+        }
+        match receiver.await {
+            Ok(status) => status,
+            // Only happens when the runtime shuts down
+            Err(_) => std::future::pending().await,
+        }
+    }
+}
+
+impl PollerInner {
+    fn status(&self, signature: &Signature) -> Option<SignatureStatus> {
+        self.providers
+            .iter()
+            .find_map(|provider| provider.signature_status(signature))
+    }
+
+    /// Polls the providers for all pending signatures and stops once no
+    /// subscription is waiting anymore.
+    async fn run(self: Arc<Self>) {
+        loop {
+            tokio::time::sleep(self.poll_interval).await;
+
+            let signatures = {
+                let mut pending = self.pending.lock_robust();
+                // Subscriptions that ended drop their receiver
+                pending.waiters.retain(|_, senders| {
+                    senders.retain(|sender| !sender.is_closed());
+                    !senders.is_empty()
+                });
+                if pending.waiters.is_empty() {
+                    pending.is_polling = false;
+                    return;
+                }
+                pending.waiters.keys().copied().collect::<Vec<_>>()
+            };
+
+            let statuses = signatures
+                .into_iter()
+                .filter_map(|signature| {
+                    self.status(&signature).map(|status| (signature, status))
+                })
+                .collect::<Vec<_>>();
+            if statuses.is_empty() {
+                continue;
+            }
+
+            let mut pending = self.pending.lock_robust();
+            for (signature, status) in statuses {
+                let senders =
+                    pending.waiters.remove(&signature).unwrap_or_default();
+                for sender in senders {
+                    let _ = sender.send(status.clone());
+                }
+            }
+            if pending.waiters.is_empty() {
+                pending.is_polling = false;
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::RwLock;
+
+    use solana_sdk::transaction::TransactionError;
+
+    use super::*;
+
+    #[derive(Default)]
+    struct StatusesStub {
+        statuses: RwLock<HashMap<Signature, SignatureStatus>>,
+    }
+
+    impl StatusesStub {
+        fn insert(&self, signature: Signature, status: SignatureStatus) {
+            self.statuses.write().unwrap().insert(signature, status);
+        }
+    }
+
+    impl SignatureStatusProvider for StatusesStub {
+        fn signature_status(
+            &self,
+            signature: &Signature,
+        ) -> Option<SignatureStatus> {
+            self.statuses.read().unwrap().get(signature).cloned()
+        }
+    }
+
+    fn setup() -> (Arc<StatusesStub>, Arc<StatusesStub>, SignatureStatusPoller)
+    {
+        let bank = Arc::<StatusesStub>::default();
+        let ledger = Arc::<StatusesStub>::default();
+        let poller = SignatureStatusPoller::new(
+            vec![bank.clone(), ledger.clone()],
+            Duration::from_millis(10),
+        );
+        (bank, ledger, poller)
+    }
+
+    fn pending_count(poller: &SignatureStatusPoller) -> usize {
+        poller.inner.pending.lock_robust().waiters.len()
+    }
+
+    fn is_polling(poller: &SignatureStatusPoller) -> bool {
+        poller.inner.pending.lock_robust().is_polling
+    }
+
+    #[test]
+    fn test_status_prefers_first_provider() {
+        let (bank, ledger, poller) = setup();
+        let signature = Signature::new_unique();
+        assert_eq!(poller.status(&signature), None);
+
+        ledger.insert(signature, (2, Err(TransactionError::AccountNotFound)));
+        assert_eq!(
+            poller.status(&signature),
+            Some((2, Err(TransactionError::AccountNotFound)))
+        );
+
+        bank.insert(signature, (3, Ok(())));
+        assert_eq!(poller.status(&signature), Some((3, Ok(()))));
+    }
+
+    #[tokio::test]
+    async fn test_poll_resolves_once_evicted_transaction_is_in_ledger() {
+        let (_bank, ledger, poller) = setup();
+        let signature = Signature::new_unique();
+
+        let store = {
+            let ledger = ledger.clone();
+            async move {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                ledger.insert(signature, (5, Ok(())));
+            }
+        };
+        let (status, _) = tokio::join!(poller.poll(&signature), store);
+        assert_eq!(status, (5, Ok(())));
+    }
+
+    #[tokio::test]
+    async fn test_poll_keeps_waiting_for_unknown_signature() {
+        let (bank, _ledger, poller) = setup();
+        bank.insert(Signature::new_unique(), (1, Ok(())));
+
+        let result = tokio::time::timeout(
+            Duration::from_millis(100),
+            poller.poll(&Signature::new_unique()),
+        )
+        .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_subscriptions_share_one_poller() {
+        let (bank, _ledger, poller) = setup();
+        let first = Signature::new_unique();
+        let second = Signature::new_unique();
+
+        let store = {
+            let bank = bank.clone();
+            async move {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                bank.insert(first, (1, Ok(())));
+                bank.insert(second, (2, Ok(())));
+            }
+        };
+        let (first_status, same_status, second_status, _) = tokio::join!(
+            poller.poll(&first),
+            poller.poll(&first),
+            poller.poll(&second),
+            store
+        );
+        assert_eq!(first_status, (1, Ok(())));
+        assert_eq!(same_status, (1, Ok(())));
+        assert_eq!(second_status, (2, Ok(())));
+
+        assert_eq!(pending_count(&poller), 0);
+        assert!(!is_polling(&poller));
+    }
+
+    #[tokio::test]
+    async fn test_poller_stops_once_subscriptions_ended() {
+        let (_bank, _ledger, poller) = setup();
+
+        let result = tokio::time::timeout(
+            Duration::from_millis(30),
+            poller.poll(&Signature::new_unique()),
+        )
+        .await;
+        assert!(result.is_err());
+        assert_eq!(pending_count(&poller), 1);
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(pending_count(&poller), 0);
+        assert!(!is_polling(&poller));
+    }
+}
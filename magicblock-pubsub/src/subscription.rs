@@ -2,10 +2,15 @@ use std::sync::Arc;
 
 use jsonrpc_pubsub::{Sink, Subscriber, SubscriptionId};
 use log::*;
-use magicblock_bank::bank::Bank;
 use magicblock_geyser_plugin::rpc::GeyserRpcService;
 
-use crate::types::{AccountParams, LogsParams, ProgramParams, SignatureParams};
+use crate::{
+    signature_status::SignatureStatusPoller,
+    types::{
+        AccountParams, LogsParams, ProgramParams, ReturnDataParams,
+        SignatureParams,
+    },
+};
 
 pub enum SubscriptionRequest {
     Account {
@@ -26,13 +31,18 @@ pub enum SubscriptionRequest {
         subscriber: Subscriber,
         geyser_service: Arc<GeyserRpcService>,
         params: SignatureParams,
-        bank: Arc<Bank>,
+        signature_statuses: Arc<SignatureStatusPoller>,
     },
     Logs {
         subscriber: Subscriber,
         params: LogsParams,
         geyser_service: Arc<GeyserRpcService>,
     },
+    ReturnData {
+        subscriber: Subscriber,
+        params: ReturnDataParams,
+        geyser_service: Arc<GeyserRpcService>,
+    },
 }
 
 impl SubscriptionRequest {
@@ -44,6 +54,7 @@ impl SubscriptionRequest {
             Slot { subscriber, .. } => subscriber,
             Signature { subscriber, .. } => subscriber,
             Logs { subscriber, .. } => subscriber,
+            ReturnData { subscriber, .. } => subscriber,
         }
     }
 }
@@ -9,7 +9,10 @@ use solana_rpc_client_api::{
     },
     response::{Response, RpcResponseContext},
 };
-use solana_sdk::commitment_config::CommitmentLevel;
+use solana_sdk::{
+    commitment_config::CommitmentLevel, transaction::TransactionError,
+};
+use solana_transaction_status::UiTransactionReturnData;
 
 // -----------------
 // AccountParams
@@ -137,6 +140,40 @@ impl LogsParams {
     }
 }
 
+// -----------------
+// ReturnDataParams
+// -----------------
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ReturnDataParams(
+    String,
+    #[serde(default)] Option<RpcTransactionLogsConfig>,
+);
+impl ReturnDataParams {
+    pub fn program_id(&self) -> &str {
+        &self.0
+    }
+
+    pub fn config(&self) -> &Option<RpcTransactionLogsConfig> {
+        &self.1
+    }
+}
+
+// -----------------
+// ReturnDataResponse
+// -----------------
+/// The data a program returned and the events it emitted while executing a
+/// transaction.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ReturnDataResponse {
+    pub signature: String,
+    pub err: Option<TransactionError>,
+    /// Only set if the program set the return data of the transaction
+    pub return_data: Option<UiTransactionReturnData>,
+    /// The base64 encoded fields the program logged via `sol_log_data`
+    pub events: Vec<String>,
+}
+
 // -----------------
 // SlotResponse
 // -----------------
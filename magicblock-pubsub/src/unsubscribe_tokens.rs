@@ -3,6 +3,7 @@ use std::{
     sync::{Arc, Mutex},
 };
 
+use magicblock_core::robust_lock::RobustMutex;
 use tokio_util::sync::CancellationToken;
 
 #[derive(Clone)]
@@ -19,13 +20,13 @@ impl UnsubscribeTokens {
 
     pub fn add(&self, id: u64) -> CancellationToken {
         let token = CancellationToken::new();
-        let mut tokens = self.tokens.lock().unwrap();
+        let mut tokens = self.tokens.lock_robust();
         tokens.insert(id, token.clone());
         token
     }
 
     pub fn unsubscribe(&self, id: u64) {
-        let mut tokens = self.tokens.lock().unwrap();
+        let mut tokens = self.tokens.lock_robust();
         if let Some(token) = tokens.remove(&id) {
             token.cancel();
         }
@@ -2,7 +2,8 @@
 #![allow(dead_code)]
 use std::collections::HashMap;
 
-use jsonrpc_core::{error, Result};
+use base64::{prelude::BASE64_STANDARD, Engine};
+use jsonrpc_core::{error, Error, Result};
 use magicblock_bank::bank::Bank;
 use magicblock_tokens::token_balances::get_mint_decimals_from_data;
 use solana_account_decoder::{
@@ -11,10 +12,13 @@ use solana_account_decoder::{
     UiAccount, UiAccountEncoding, UiDataSliceConfig, MAX_BASE58_BYTES,
 };
 use solana_sdk::{
-    account::{AccountSharedData, ReadableAccount},
+    account::{AccountSharedData, ReadableAccount, WritableAccount},
     pubkey::Pubkey,
+    transaction::SanitizedTransaction,
 };
 
+use crate::{traits::rpc_full::RpcAccountStateOverride, utils::verify_pubkey};
+
 pub(crate) fn get_account_from_overwrites_or_bank(
     pubkey: &Pubkey,
     bank: &Bank,
@@ -111,3 +115,54 @@ pub(crate) fn get_parsed_token_account(
         None,
     )
 }
+
+// -----------------
+// Simulation State Overrides
+// -----------------
+/// Resolves the [state_overrides] of a simulation into the accounts the
+/// [transaction] sees instead of the ones in the bank, each based on the
+/// state of the account in the bank.
+pub(crate) fn get_accounts_with_state_overrides(
+    bank: &Bank,
+    transaction: &SanitizedTransaction,
+    state_overrides: HashMap<String, RpcAccountStateOverride>,
+) -> Result<HashMap<Pubkey, AccountSharedData>> {
+    let account_keys = transaction.message().account_keys();
+    state_overrides
+        .into_iter()
+        .map(|(address, state_override)| {
+            let pubkey = verify_pubkey(&address)?;
+            // Only the accounts used by the transaction are loaded
+            if !account_keys.iter().any(|key| *key == pubkey) {
+                return Err(Error::invalid_params(format!(
+                    "State override for {pubkey} which is not used by the transaction"
+                )));
+            }
+            let RpcAccountStateOverride {
+                lamports,
+                data,
+                owner,
+                executable,
+            } = state_override;
+            let mut account = bank.get_account(&pubkey).unwrap_or_default();
+            if let Some(lamports) = lamports {
+                account.set_lamports(lamports);
+            }
+            if let Some(data) = data {
+                let data = BASE64_STANDARD.decode(data).map_err(|err| {
+                    Error::invalid_params(format!(
+                        "Invalid base64 data in state override for {pubkey}: {err}"
+                    ))
+                })?;
+                account.set_data(data);
+            }
+            if let Some(owner) = owner {
+                account.set_owner(verify_pubkey(&owner)?);
+            }
+            if let Some(executable) = executable {
+                account.set_executable(executable);
+            }
+            Ok((pubkey, account))
+        })
+        .collect()
+}
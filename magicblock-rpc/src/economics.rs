@@ -0,0 +1,61 @@
+use solana_rpc_client_api::response::{
+    RpcInflationGovernor, RpcInflationRate, RpcVoteAccountInfo,
+};
+use solana_sdk::{
+    clock::{Epoch, Slot},
+    pubkey::Pubkey,
+};
+
+/// Values reported by the cluster economics methods, i.e. `getSupply`,
+/// `getInflationGovernor` and `getVoteAccounts`.
+/// The ephemeral has no inflation nor staking, but tools built for regular
+/// clusters expect sensible values from these methods.
+#[derive(Debug, Clone, Default)]
+pub struct RpcEconomics {
+    /// Reported as total and circulating supply, the capitalization of the
+    /// bank is reported if not set
+    pub supply_lamports: Option<u64>,
+    /// Activated stake of the vote account reported for our identity
+    pub vote_account_stake_lamports: u64,
+}
+
+impl RpcEconomics {
+    pub(crate) fn inflation_governor(&self) -> RpcInflationGovernor {
+        RpcInflationGovernor {
+            initial: 0.0,
+            terminal: 0.0,
+            taper: 0.0,
+            foundation: 0.0,
+            foundation_term: 0.0,
+        }
+    }
+
+    pub(crate) fn inflation_rate(&self, epoch: Epoch) -> RpcInflationRate {
+        RpcInflationRate {
+            total: 0.0,
+            validator: 0.0,
+            foundation: 0.0,
+            epoch,
+        }
+    }
+
+    /// The validator doesn't vote, thus the vote account reported for the
+    /// [identity] is synthetic and always up to date with the [slot].
+    pub(crate) fn vote_account(
+        &self,
+        identity: &Pubkey,
+        epoch: Epoch,
+        slot: Slot,
+    ) -> RpcVoteAccountInfo {
+        RpcVoteAccountInfo {
+            vote_pubkey: identity.to_string(),
+            node_pubkey: identity.to_string(),
+            activated_stake: self.vote_account_stake_lamports,
+            commission: 0,
+            epoch_vote_account: true,
+            epoch_credits: vec![(epoch, 0, 0)],
+            last_vote: slot,
+            root_slot: slot,
+        }
+    }
+}
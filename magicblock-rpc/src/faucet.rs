@@ -0,0 +1,262 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use jsonrpc_core::{Error, ErrorCode, Result};
+use magicblock_core::robust_lock::RobustMutex;
+use solana_sdk::{native_token::lamports_to_sol, pubkey::Pubkey};
+
+/// Limits applied to `requestAirdrop`, a limit set to `0` is disabled.
+#[derive(Debug, Clone)]
+pub struct FaucetLimits {
+    pub max_lamports_per_request: u64,
+    /// Max lamports a single pubkey can receive within one [Self::window]
+    pub per_key_lamports_per_window: u64,
+    /// Max lamports the faucet hands out within one [Self::window]
+    pub global_lamports_per_window: u64,
+    pub window: Duration,
+}
+
+impl Default for FaucetLimits {
+    fn default() -> Self {
+        Self {
+            max_lamports_per_request: 0,
+            per_key_lamports_per_window: 0,
+            global_lamports_per_window: 0,
+            window: Duration::from_secs(3600),
+        }
+    }
+}
+
+/// Lamports reserved via [FaucetLimiter::reserve] within the window that
+/// started at [Self::window_started_at].
+#[derive(Debug)]
+pub(crate) struct FaucetReservation {
+    window_started_at: Instant,
+    pubkey: Pubkey,
+    lamports: u64,
+}
+
+#[derive(Default)]
+struct FaucetWindow {
+    started_at: Option<Instant>,
+    global_lamports: u64,
+    per_key_lamports: HashMap<Pubkey, u64>,
+}
+
+/// Tracks the lamports handed out by the faucet within the current window.
+#[derive(Clone)]
+pub(crate) struct FaucetLimiter {
+    limits: FaucetLimits,
+    window: Arc<Mutex<FaucetWindow>>,
+}
+
+impl FaucetLimiter {
+    pub fn new(limits: FaucetLimits) -> Self {
+        Self {
+            limits,
+            window: Default::default(),
+        }
+    }
+
+    /// Reserves the lamports of an airdrop to the provided pubkey or errors if
+    /// that would exceed any of the limits.
+    /// The reservation needs to be [Self::release]d if the airdrop fails.
+    pub fn reserve(
+        &self,
+        pubkey: &Pubkey,
+        lamports: u64,
+    ) -> Result<FaucetReservation> {
+        let FaucetLimits {
+            max_lamports_per_request,
+            per_key_lamports_per_window,
+            global_lamports_per_window,
+            window,
+        } = &self.limits;
+
+        if *max_lamports_per_request > 0 && lamports > *max_lamports_per_request
+        {
+            return Err(airdrop_error(format!(
+                "airdrop request too large; req: {} SOL cap: {} SOL",
+                lamports_to_sol(lamports),
+                lamports_to_sol(*max_lamports_per_request)
+            )));
+        }
+
+        let mut state = self.window.lock_robust();
+
+        let now = Instant::now();
+        let window_started_at = match state.started_at {
+            Some(started_at) if now.duration_since(started_at) < *window => {
+                started_at
+            }
+            _ => {
+                *state = FaucetWindow {
+                    started_at: Some(now),
+                    ..Default::default()
+                };
+                now
+            }
+        };
+
+        let global_lamports = state.global_lamports.saturating_add(lamports);
+        if *global_lamports_per_window > 0
+            && global_lamports > *global_lamports_per_window
+        {
+            return Err(airdrop_error(format!(
+                "airdrop limit reached; req: {} SOL current: {} SOL cap: {} SOL",
+                lamports_to_sol(lamports),
+                lamports_to_sol(state.global_lamports),
+                lamports_to_sol(*global_lamports_per_window)
+            )));
+        }
+
+        let current_key_lamports = state
+            .per_key_lamports
+            .get(pubkey)
+            .copied()
+            .unwrap_or_default();
+        let key_lamports = current_key_lamports.saturating_add(lamports);
+        if *per_key_lamports_per_window > 0
+            && key_lamports > *per_key_lamports_per_window
+        {
+            return Err(airdrop_error(format!(
+                "airdrop limit reached for {}; req: {} SOL current: {} SOL cap: {} SOL",
+                pubkey,
+                lamports_to_sol(lamports),
+                lamports_to_sol(current_key_lamports),
+                lamports_to_sol(*per_key_lamports_per_window)
+            )));
+        }
+
+        state.global_lamports = global_lamports;
+        state.per_key_lamports.insert(*pubkey, key_lamports);
+        Ok(FaucetReservation {
+            window_started_at,
+            pubkey: *pubkey,
+            lamports,
+        })
+    }
+
+    /// Releases the lamports of a [reservation] made via [Self::reserve].
+    /// Nothing is released if the window it was made in expired meanwhile,
+    /// since the lamports were never counted in the current window.
+    pub fn release(&self, reservation: FaucetReservation) {
+        let FaucetReservation {
+            window_started_at,
+            pubkey,
+            lamports,
+        } = reservation;
+
+        let mut state = self.window.lock_robust();
+        if state.started_at != Some(window_started_at) {
+            return;
+        }
+        state.global_lamports = state.global_lamports.saturating_sub(lamports);
+        if let Some(key_lamports) = state.per_key_lamports.get_mut(&pubkey) {
+            *key_lamports = key_lamports.saturating_sub(lamports);
+            if *key_lamports == 0 {
+                state.per_key_lamports.remove(&pubkey);
+            }
+        }
+    }
+}
+
+fn airdrop_error(message: String) -> Error {
+    Error {
+        code: ErrorCode::InvalidRequest,
+        message,
+        data: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WINDOW: Duration = Duration::from_millis(100);
+
+    fn limiter(
+        per_key_lamports_per_window: u64,
+        global_lamports_per_window: u64,
+    ) -> FaucetLimiter {
+        FaucetLimiter::new(FaucetLimits {
+            max_lamports_per_request: 100,
+            per_key_lamports_per_window,
+            global_lamports_per_window,
+            window: WINDOW,
+        })
+    }
+
+    #[test]
+    fn test_max_lamports_per_request() {
+        let limiter = limiter(0, 0);
+        let pubkey = Pubkey::new_unique();
+
+        assert!(limiter.reserve(&pubkey, 100).is_ok());
+        assert!(limiter.reserve(&pubkey, 101).is_err());
+    }
+
+    #[test]
+    fn test_per_key_limit() {
+        let limiter = limiter(150, 0);
+        let pubkey = Pubkey::new_unique();
+
+        assert!(limiter.reserve(&pubkey, 100).is_ok());
+        assert!(limiter.reserve(&pubkey, 51).is_err());
+        assert!(limiter.reserve(&pubkey, 50).is_ok());
+        // Other keys have their own limit
+        assert!(limiter.reserve(&Pubkey::new_unique(), 100).is_ok());
+    }
+
+    #[test]
+    fn test_global_limit() {
+        let limiter = limiter(0, 150);
+
+        assert!(limiter.reserve(&Pubkey::new_unique(), 100).is_ok());
+        assert!(limiter.reserve(&Pubkey::new_unique(), 51).is_err());
+        assert!(limiter.reserve(&Pubkey::new_unique(), 50).is_ok());
+    }
+
+    #[test]
+    fn test_limits_reset_with_the_window() {
+        let limiter = limiter(100, 100);
+        let pubkey = Pubkey::new_unique();
+
+        assert!(limiter.reserve(&pubkey, 100).is_ok());
+        assert!(limiter.reserve(&pubkey, 1).is_err());
+
+        std::thread::sleep(WINDOW);
+        assert!(limiter.reserve(&pubkey, 100).is_ok());
+    }
+
+    #[test]
+    fn test_release_frees_the_reserved_lamports() {
+        let limiter = limiter(100, 100);
+        let pubkey = Pubkey::new_unique();
+
+        let reservation = limiter.reserve(&pubkey, 100).unwrap();
+        assert!(limiter.reserve(&pubkey, 1).is_err());
+
+        limiter.release(reservation);
+        assert!(limiter.reserve(&pubkey, 100).is_ok());
+    }
+
+    #[test]
+    fn test_release_of_an_expired_window_keeps_the_current_one() {
+        let limiter = limiter(100, 150);
+        let pubkey = Pubkey::new_unique();
+
+        let expired_reservation = limiter.reserve(&pubkey, 100).unwrap();
+        std::thread::sleep(WINDOW);
+        limiter.reserve(&pubkey, 100).unwrap();
+
+        // Releasing the reservation of the previous window must not free
+        // the lamports reserved in the current one
+        limiter.release(expired_reservation);
+        assert!(limiter.reserve(&pubkey, 1).is_err());
+        assert!(limiter.reserve(&Pubkey::new_unique(), 51).is_err());
+    }
+}
@@ -0,0 +1,289 @@
+use std::collections::HashSet;
+
+use jsonrpc_core::{Error, ErrorCode, Result};
+use magicblock_bank::bank::Bank;
+use magicblock_core::error_code::MagicErrorCode;
+use magicblock_program::{
+    fee_payer_escrow_pda, DEFAULT_FEE_PAYER_ESCROW_INDEX,
+};
+use solana_sdk::{
+    account::ReadableAccount, native_token::lamports_to_sol, pubkey::Pubkey,
+    transaction::SanitizedTransaction,
+};
+
+use crate::utils::error_with_magic_code;
+
+/// Rules deciding which transactions are admitted before any of the accounts
+/// they use are cloned, a rule that is empty or set to `0` is disabled.
+#[derive(Debug, Clone, Default)]
+pub struct TransactionFirewallRules {
+    /// Transactions with a top level instruction invoking any of these
+    /// programs are rejected
+    pub denied_programs: HashSet<Pubkey>,
+    /// Fee payers that are admitted regardless of their escrow
+    pub allowed_fee_payers: HashSet<Pubkey>,
+    /// Lamports a fee payer that isn't in [Self::allowed_fee_payers] needs to
+    /// hold in its escrow, see [fee_payer_escrow_lamports]
+    pub min_fee_payer_lamports: u64,
+    /// Max size of the serialized transaction in bytes
+    pub max_transaction_size: usize,
+}
+
+/// Result of the checks that [TransactionFirewall::check_fee_payer] can
+/// perform with what we currently know about the fee payer.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum FeePayerAdmission {
+    Admitted,
+    /// The fee payer isn't known to the bank yet, so its escrow needs to be
+    /// cloned before we can decide
+    EscrowUnknown,
+}
+
+#[derive(Clone)]
+pub(crate) struct TransactionFirewall {
+    rules: TransactionFirewallRules,
+}
+
+impl TransactionFirewall {
+    pub fn new(rules: TransactionFirewallRules) -> Self {
+        Self { rules }
+    }
+
+    /// Checks the rules that only depend on the transaction itself.
+    pub fn check_transaction(
+        &self,
+        wire_size: usize,
+        tx: &SanitizedTransaction,
+    ) -> Result<()> {
+        let TransactionFirewallRules {
+            denied_programs,
+            max_transaction_size,
+            ..
+        } = &self.rules;
+
+        if *max_transaction_size > 0 && wire_size > *max_transaction_size {
+            return Err(firewall_error(format!(
+                "transaction too large: {} bytes (max: {} bytes)",
+                wire_size, max_transaction_size
+            )));
+        }
+        if let Some(program_id) = tx
+            .message()
+            .program_instructions_iter()
+            .map(|(program_id, _)| program_id)
+            .find(|program_id| denied_programs.contains(program_id))
+        {
+            return Err(firewall_error(format!(
+                "transactions invoking program {} are not accepted",
+                program_id
+            )));
+        }
+        Ok(())
+    }
+
+    /// Checks that the fee payer is allowed or holds the min escrow.
+    /// [escrow_lamports] are the lamports of the escrow of the fee payer in
+    /// the bank or `None` if it wasn't cloned yet.
+    pub fn check_fee_payer(
+        &self,
+        fee_payer: &Pubkey,
+        escrow_lamports: Option<u64>,
+    ) -> Result<FeePayerAdmission> {
+        let TransactionFirewallRules {
+            allowed_fee_payers,
+            min_fee_payer_lamports,
+            ..
+        } = &self.rules;
+
+        let restricted =
+            !allowed_fee_payers.is_empty() || *min_fee_payer_lamports > 0;
+        if !restricted || allowed_fee_payers.contains(fee_payer) {
+            return Ok(FeePayerAdmission::Admitted);
+        }
+        if *min_fee_payer_lamports == 0 {
+            return Err(firewall_error(format!(
+                "fee payer {} is not allowed",
+                fee_payer
+            )));
+        }
+        match escrow_lamports {
+            None => Ok(FeePayerAdmission::EscrowUnknown),
+            Some(lamports) if lamports >= *min_fee_payer_lamports => {
+                Ok(FeePayerAdmission::Admitted)
+            }
+            Some(lamports) => Err(firewall_error(format!(
+                "fee payer {} escrow too small: {} SOL (min: {} SOL)",
+                fee_payer,
+                lamports_to_sol(lamports),
+                lamports_to_sol(*min_fee_payer_lamports)
+            ))),
+        }
+    }
+}
+
+/// Returns the lamports of the default escrow of the [fee_payer] in the
+/// [bank], the lamports of the fee payer itself are spent on fees and
+/// thus don't count.
+pub(crate) fn fee_payer_escrow_lamports(
+    bank: &Bank,
+    fee_payer: &Pubkey,
+) -> Option<u64> {
+    let escrow =
+        fee_payer_escrow_pda(fee_payer, DEFAULT_FEE_PAYER_ESCROW_INDEX);
+    bank.get_account(&escrow).map(|account| account.lamports())
+}
+
+fn firewall_error(message: String) -> Error {
+    error_with_magic_code(
+        ErrorCode::InvalidRequest,
+        message,
+        MagicErrorCode::RpcTransactionRejected,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::{
+        account::AccountSharedData, instruction::Instruction,
+        native_token::LAMPORTS_PER_SOL, transaction::Transaction,
+    };
+    use test_tools::bank::bank_with_fees_for_tests;
+
+    use super::*;
+
+    fn transaction_invoking(program_ids: &[Pubkey]) -> SanitizedTransaction {
+        let instructions = program_ids
+            .iter()
+            .map(|program_id| {
+                Instruction::new_with_bytes(*program_id, &[], vec![])
+            })
+            .collect::<Vec<_>>();
+        SanitizedTransaction::from_transaction_for_tests(
+            Transaction::new_with_payer(
+                &instructions,
+                Some(&Pubkey::new_unique()),
+            ),
+        )
+    }
+
+    #[test]
+    fn test_no_rules_admit_everything() {
+        let firewall =
+            TransactionFirewall::new(TransactionFirewallRules::default());
+
+        assert!(firewall
+            .check_transaction(
+                usize::MAX,
+                &transaction_invoking(&[Pubkey::new_unique()])
+            )
+            .is_ok());
+        assert_eq!(
+            firewall
+                .check_fee_payer(&Pubkey::new_unique(), None)
+                .unwrap(),
+            FeePayerAdmission::Admitted
+        );
+    }
+
+    #[test]
+    fn test_max_transaction_size() {
+        let firewall = TransactionFirewall::new(TransactionFirewallRules {
+            max_transaction_size: 100,
+            ..Default::default()
+        });
+        let transaction = transaction_invoking(&[]);
+
+        assert!(firewall.check_transaction(100, &transaction).is_ok());
+        assert!(firewall.check_transaction(101, &transaction).is_err());
+    }
+
+    #[test]
+    fn test_denied_programs() {
+        let denied_program = Pubkey::new_unique();
+        let firewall = TransactionFirewall::new(TransactionFirewallRules {
+            denied_programs: HashSet::from([denied_program]),
+            ..Default::default()
+        });
+
+        assert!(firewall
+            .check_transaction(
+                0,
+                &transaction_invoking(&[Pubkey::new_unique()])
+            )
+            .is_ok());
+        assert!(firewall
+            .check_transaction(
+                0,
+                &transaction_invoking(&[Pubkey::new_unique(), denied_program])
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn test_allowed_fee_payers_without_min_escrow() {
+        let allowed_fee_payer = Pubkey::new_unique();
+        let firewall = TransactionFirewall::new(TransactionFirewallRules {
+            allowed_fee_payers: HashSet::from([allowed_fee_payer]),
+            ..Default::default()
+        });
+
+        assert_eq!(
+            firewall.check_fee_payer(&allowed_fee_payer, None).unwrap(),
+            FeePayerAdmission::Admitted
+        );
+        assert!(firewall
+            .check_fee_payer(&Pubkey::new_unique(), Some(u64::MAX))
+            .is_err());
+    }
+
+    #[test]
+    fn test_min_fee_payer_lamports() {
+        let allowed_fee_payer = Pubkey::new_unique();
+        let fee_payer = Pubkey::new_unique();
+        let firewall = TransactionFirewall::new(TransactionFirewallRules {
+            allowed_fee_payers: HashSet::from([allowed_fee_payer]),
+            min_fee_payer_lamports: LAMPORTS_PER_SOL,
+            ..Default::default()
+        });
+
+        assert_eq!(
+            firewall.check_fee_payer(&fee_payer, None).unwrap(),
+            FeePayerAdmission::EscrowUnknown
+        );
+        assert!(firewall
+            .check_fee_payer(&fee_payer, Some(LAMPORTS_PER_SOL - 1))
+            .is_err());
+        assert_eq!(
+            firewall
+                .check_fee_payer(&fee_payer, Some(LAMPORTS_PER_SOL))
+                .unwrap(),
+            FeePayerAdmission::Admitted
+        );
+        assert_eq!(
+            firewall
+                .check_fee_payer(&allowed_fee_payer, Some(0))
+                .unwrap(),
+            FeePayerAdmission::Admitted
+        );
+    }
+
+    #[test]
+    fn test_fee_payer_escrow_lamports_are_those_of_the_escrow_pda() {
+        let bank = bank_with_fees_for_tests();
+        let fee_payer = Pubkey::new_unique();
+        let account =
+            |lamports| AccountSharedData::new(lamports, 0, &Pubkey::default());
+
+        bank.store_account(&fee_payer, &account(10 * LAMPORTS_PER_SOL));
+        assert_eq!(fee_payer_escrow_lamports(&bank, &fee_payer), None);
+
+        bank.store_account(
+            &fee_payer_escrow_pda(&fee_payer, DEFAULT_FEE_PAYER_ESCROW_INDEX),
+            &account(LAMPORTS_PER_SOL),
+        );
+        assert_eq!(
+            fee_payer_escrow_lamports(&bank, &fee_payer),
+            Some(LAMPORTS_PER_SOL)
+        );
+    }
+}
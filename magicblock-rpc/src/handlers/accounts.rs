@@ -1,6 +1,7 @@
 // NOTE: from rpc/src/rpc.rs :3014
 use jsonrpc_core::{Error, Result};
 use log::*;
+use magicblock_accounts_api::IdlDecodedAccount;
 use solana_account_decoder::UiAccount;
 use solana_rpc_client_api::{
     config::RpcAccountInfoConfig, request::MAX_MULTIPLE_ACCOUNTS,
@@ -53,4 +54,17 @@ impl AccountsData for AccountsDataImpl {
             .collect::<Result<Vec<_>>>()?;
         meta.get_multiple_accounts(pubkeys, config)
     }
+
+    fn get_parsed_account_info(
+        &self,
+        meta: Self::Metadata,
+        pubkey_str: String,
+    ) -> Result<RpcResponse<Option<IdlDecodedAccount>>> {
+        debug!(
+            "get_parsed_account_info rpc request received: {:?}",
+            pubkey_str
+        );
+        let pubkey = verify_pubkey(&pubkey_str)?;
+        meta.get_parsed_account_info(&pubkey)
+    }
 }
@@ -0,0 +1,357 @@
+use std::{collections::HashSet, fmt, path::Path, time::UNIX_EPOCH};
+
+use jsonrpc_core::{BoxFuture, Error, ErrorCode, Result};
+use log::*;
+use magicblock_bank::state_archive::StateArchive;
+use magicblock_core::error_code::HasErrorCode;
+use magicblock_logger::errors::LoggerError;
+use magicblock_metrics::metrics;
+use magicblock_mutator::transactions::transactions_to_import_accounts;
+use magicblock_processor::execute_transaction::execute_sanitized_transaction;
+use solana_sdk::{
+    signature::Keypair,
+    signer::{EncodableKey, Signer},
+    transaction::SanitizedTransaction,
+};
+
+use crate::{
+    json_rpc_request_processor::JsonRpcRequestProcessor,
+    traits::rpc_admin::{
+        Admin, RpcAccountCommitCost, RpcClonedAccount, RpcFailedCommit,
+        RpcHydrateFailure, RpcHydrateReport,
+    },
+    utils::{error_with_magic_code, verify_pubkey},
+};
+
+const DEFAULT_TOP_CLONED_ACCOUNTS_LIMIT: usize = 20;
+const DEFAULT_TOP_COMMIT_COST_ACCOUNTS_LIMIT: usize = 20;
+
+pub struct AdminImpl;
+impl Admin for AdminImpl {
+    type Metadata = JsonRpcRequestProcessor;
+
+    fn request_shutdown(&self, meta: Self::Metadata) -> Result<()> {
+        info!("request_shutdown rpc request received");
+        meta.shutdown.request();
+        Ok(())
+    }
+
+    fn set_log_filter(
+        &self,
+        _meta: Self::Metadata,
+        filter: String,
+    ) -> Result<()> {
+        info!("set_log_filter rpc request received: '{}'", filter);
+        magicblock_logger::set_log_filter(&filter).map_err(logger_error)
+    }
+
+    fn get_log_filter(&self, _meta: Self::Metadata) -> Result<String> {
+        debug!("get_log_filter rpc request received");
+        magicblock_logger::log_filter().map_err(logger_error)
+    }
+
+    fn get_top_cloned_accounts(
+        &self,
+        _meta: Self::Metadata,
+        limit: Option<usize>,
+    ) -> Result<Vec<RpcClonedAccount>> {
+        debug!("get_top_cloned_accounts rpc request received");
+        let limit = limit.unwrap_or(DEFAULT_TOP_CLONED_ACCOUNTS_LIMIT);
+        Ok(metrics::top_cloned_accounts(limit)
+            .into_iter()
+            .map(|stats| RpcClonedAccount {
+                pubkey: stats.pubkey,
+                kind: stats.kind.to_string(),
+                clone_count: stats.clone_count,
+                data_len: stats.data_len,
+                total_clone_time_ms: stats.total_clone_time.as_millis() as u64,
+            })
+            .collect())
+    }
+
+    fn get_top_commit_cost_accounts(
+        &self,
+        meta: Self::Metadata,
+        limit: Option<usize>,
+    ) -> Result<Vec<RpcAccountCommitCost>> {
+        debug!("get_top_commit_cost_accounts rpc request received");
+        let limit = limit.unwrap_or(DEFAULT_TOP_COMMIT_COST_ACCOUNTS_LIMIT);
+        Ok(meta
+            .accounts_manager
+            .commit_cost_tracker
+            .top_account_costs(limit)
+            .into_iter()
+            .map(|(pubkey, lamports)| RpcAccountCommitCost {
+                pubkey: pubkey.to_string(),
+                lamports,
+            })
+            .collect())
+    }
+
+    fn get_hydrate_report(
+        &self,
+        meta: Self::Metadata,
+    ) -> Result<Option<RpcHydrateReport>> {
+        debug!("get_hydrate_report rpc request received");
+        Ok(meta
+            .config
+            .hydrate_report
+            .get()
+            .map(|report| RpcHydrateReport {
+                cloned: report.cloned,
+                skipped: report.skipped,
+                failed: report.failed(),
+                failures: report
+                    .failures
+                    .into_iter()
+                    .map(|failure| RpcHydrateFailure {
+                        pubkey: failure.pubkey.to_string(),
+                        owner: failure.owner.to_string(),
+                        reason: failure.reason,
+                        may_be_delegated: failure.may_be_delegated,
+                    })
+                    .collect(),
+            }))
+    }
+
+    fn commit_all(
+        &self,
+        meta: Self::Metadata,
+    ) -> BoxFuture<Result<Vec<String>>> {
+        info!("commit_all rpc request received");
+        Box::pin(async move {
+            let signatures =
+                meta.accounts_manager.commit_all_delegated().await.map_err(
+                    |err| {
+                        error_with_magic_code(
+                            ErrorCode::InternalError,
+                            format!("Failed to commit accounts: {err}"),
+                            err.error_code(),
+                        )
+                    },
+                )?;
+            Ok(signatures.iter().map(ToString::to_string).collect())
+        })
+    }
+
+    fn get_failed_commits(
+        &self,
+        meta: Self::Metadata,
+        drain: Option<bool>,
+    ) -> Result<Vec<RpcFailedCommit>> {
+        debug!("get_failed_commits rpc request received");
+        let queue = meta.accounts_manager.commit_error_queue();
+        let failed_commits = if drain.unwrap_or_default() {
+            queue.drain()
+        } else {
+            queue.failed_commits()
+        };
+        Ok(failed_commits
+            .into_iter()
+            .map(|failed_commit| RpcFailedCommit {
+                signature: failed_commit.signature.to_string(),
+                undelegated_accounts: failed_commit
+                    .undelegated_accounts
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect(),
+                committed_only_accounts: failed_commit
+                    .committed_only_accounts
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect(),
+                error: failed_commit.error,
+                logs: failed_commit.logs,
+                failed_at: failed_commit
+                    .failed_at
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_millis() as u64,
+            })
+            .collect())
+    }
+
+    fn get_allowed_programs(
+        &self,
+        meta: Self::Metadata,
+    ) -> Result<Option<Vec<String>>> {
+        debug!("get_allowed_programs rpc request received");
+        Ok(meta
+            .config
+            .allowed_programs
+            .program_ids()
+            .map(|program_ids| {
+                program_ids.iter().map(ToString::to_string).collect()
+            }))
+    }
+
+    fn add_allowed_program(
+        &self,
+        meta: Self::Metadata,
+        program_id: String,
+    ) -> Result<bool> {
+        info!("add_allowed_program rpc request received: {}", program_id);
+        let program_id = verify_pubkey(&program_id)?;
+        meta.config
+            .allowed_programs
+            .add(program_id)
+            .map_err(persist_allowed_programs_error)
+    }
+
+    fn remove_allowed_program(
+        &self,
+        meta: Self::Metadata,
+        program_id: String,
+    ) -> Result<()> {
+        info!(
+            "remove_allowed_program rpc request received: {}",
+            program_id
+        );
+        let program_id = verify_pubkey(&program_id)?;
+        let allowed_programs = &meta.config.allowed_programs;
+        if allowed_programs.is_last(&program_id) {
+            return Err(Error::invalid_params(format!(
+                "Cannot remove {} since it is the last allowed program",
+                program_id
+            )));
+        }
+        let removed = allowed_programs
+            .remove(&program_id)
+            .map_err(persist_allowed_programs_error)?;
+        if !removed {
+            return Err(Error::invalid_params(format!(
+                "{} is not an allowed program",
+                program_id
+            )));
+        }
+        Ok(())
+    }
+
+    fn export_state(
+        &self,
+        meta: Self::Metadata,
+        path: String,
+        owners: Option<Vec<String>>,
+    ) -> Result<usize> {
+        info!("export_state rpc request received: '{}'", path);
+        let owners = owners
+            .map(|owners| {
+                owners
+                    .iter()
+                    .map(|owner| verify_pubkey(owner))
+                    .collect::<Result<HashSet<_>>>()
+            })
+            .transpose()?;
+        let archive =
+            StateArchive::from_bank(&meta.get_bank(), owners.as_ref());
+        archive
+            .write_to_file(Path::new(&path))
+            .map_err(|err| state_archive_error(&path, err))?;
+        info!(
+            "Exported {} accounts at slot {} to '{}'",
+            archive.accounts.len(),
+            archive.slot,
+            path
+        );
+        Ok(archive.accounts.len())
+    }
+
+    fn import_state(
+        &self,
+        meta: Self::Metadata,
+        path: String,
+    ) -> Result<usize> {
+        info!("import_state rpc request received: '{}'", path);
+        let archive = StateArchive::read_from_file(Path::new(&path))
+            .map_err(|err| state_archive_error(&path, err))?;
+        let bank = meta.get_bank();
+        let accounts =
+            archive.accounts_to_import(&bank, &[meta.faucet_keypair.pubkey()]);
+        let transactions = transactions_to_import_accounts(
+            bank.validator_context(),
+            &accounts,
+            bank.last_blockhash(),
+        )
+        .map_err(|err| state_archive_error(&path, err))?;
+
+        // Registered first such that a clone running meanwhile cannot
+        // overwrite the imported state
+        let pubkeys = accounts
+            .iter()
+            .map(|(pubkey, _)| *pubkey)
+            .collect::<Vec<_>>();
+        meta.accounts_manager.register_local_overrides(&pubkeys);
+        for transaction in transactions {
+            let transaction =
+                SanitizedTransaction::try_from_legacy_transaction(transaction)
+                    .map_err(|err| state_archive_error(&path, err))?;
+            execute_sanitized_transaction(
+                transaction,
+                &bank,
+                meta.transaction_status_sender(),
+            )
+            .map_err(|err| state_archive_error(&path, err))?;
+        }
+        info!(
+            "Imported {} accounts exported at slot {} from '{}'",
+            accounts.len(),
+            archive.slot,
+            path
+        );
+        Ok(accounts.len())
+    }
+
+    fn rotate_validator_identity(
+        &self,
+        meta: Self::Metadata,
+        keypair_path: String,
+    ) -> BoxFuture<Result<String>> {
+        info!(
+            "rotate_validator_identity rpc request received: '{}'",
+            keypair_path
+        );
+        Box::pin(async move {
+            let keypair =
+                Keypair::read_from_file(&keypair_path).map_err(|err| {
+                    Error::invalid_params(format!(
+                        "Invalid keypair file '{keypair_path}': {err}"
+                    ))
+                })?;
+            let previous_pubkey =
+                meta.identity_rotation.request(keypair).await.map_err(
+                    |err| Error {
+                        code: ErrorCode::InternalError,
+                        message: format!(
+                            "Failed to rotate validator identity: {err}"
+                        ),
+                        data: None,
+                    },
+                )?;
+            Ok(previous_pubkey.to_string())
+        })
+    }
+}
+
+fn state_archive_error(path: &str, err: impl fmt::Display) -> Error {
+    Error {
+        code: ErrorCode::InvalidRequest,
+        message: format!("State archive '{path}' failed: {err}"),
+        data: None,
+    }
+}
+
+fn persist_allowed_programs_error(err: std::io::Error) -> Error {
+    Error {
+        code: ErrorCode::InternalError,
+        message: format!("Failed to persist allowed programs: {err}"),
+        data: None,
+    }
+}
+
+fn logger_error(err: LoggerError) -> Error {
+    Error {
+        code: ErrorCode::InvalidRequest,
+        message: err.to_string(),
+        data: None,
+    }
+}
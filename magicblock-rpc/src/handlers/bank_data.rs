@@ -2,7 +2,9 @@
 use jsonrpc_core::{Error, Result};
 use log::*;
 use solana_rpc_client_api::{
-    config::RpcContextConfig, request::MAX_GET_SLOT_LEADERS,
+    config::RpcContextConfig,
+    request::MAX_GET_SLOT_LEADERS,
+    response::{RpcInflationGovernor, RpcInflationRate},
 };
 use solana_sdk::{
     clock::Slot, commitment_config::CommitmentConfig,
@@ -11,7 +13,7 @@ use solana_sdk::{
 
 use crate::{
     json_rpc_request_processor::JsonRpcRequestProcessor,
-    traits::rpc_bank_data::BankData,
+    traits::rpc_bank_data::BankData, utils::verify_commitment,
 };
 
 pub struct BankDataImpl;
@@ -23,12 +25,31 @@ impl BankData for BankDataImpl {
         &self,
         meta: Self::Metadata,
         data_len: usize,
-        _commitment: Option<CommitmentConfig>,
+        commitment: Option<CommitmentConfig>,
     ) -> Result<u64> {
         debug!("get_minimum_balance_for_rent_exemption rpc request received");
+        verify_commitment(commitment)?;
         meta.get_minimum_balance_for_rent_exemption(data_len)
     }
 
+    fn get_inflation_governor(
+        &self,
+        meta: Self::Metadata,
+        commitment: Option<CommitmentConfig>,
+    ) -> Result<RpcInflationGovernor> {
+        debug!("get_inflation_governor rpc request received");
+        verify_commitment(commitment)?;
+        Ok(meta.get_inflation_governor())
+    }
+
+    fn get_inflation_rate(
+        &self,
+        meta: Self::Metadata,
+    ) -> Result<RpcInflationRate> {
+        debug!("get_inflation_rate rpc request received");
+        Ok(meta.get_inflation_rate())
+    }
+
     fn get_epoch_schedule(
         &self,
         meta: Self::Metadata,
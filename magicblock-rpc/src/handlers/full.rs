@@ -1,4 +1,4 @@
-use std::{cmp::min, str::FromStr};
+use std::{cmp::min, collections::HashMap, str::FromStr};
 
 // NOTE: from rpc/src/rpc.rs :3432
 use jsonrpc_core::{futures::future, BoxFuture, Error, Result};
@@ -38,18 +38,24 @@ use solana_transaction_status::{
 use crate::{
     json_rpc_request_processor::JsonRpcRequestProcessor,
     perf::rpc_perf_sample_from,
-    traits::rpc_full::Full,
+    traits::rpc_full::{
+        Full, RpcAccountJournalEntry, RpcAccountStateOverride,
+        RpcChainSlotMapping, RpcProgramVersion,
+        RpcSimulateTransactionWithOverridesConfig,
+    },
     transaction::{
-        decode_and_deserialize, sanitize_transaction, send_transaction,
-        SendTransactionConfig,
+        admit_transaction, decode_and_deserialize, sanitize_transaction,
+        send_bundle, send_transaction, SendTransactionConfig,
     },
     utils::{
         new_response, verify_and_parse_signatures_for_address_params,
-        verify_signature,
+        verify_commitment, verify_pubkey, verify_signature,
     },
 };
 
 const PERFORMANCE_SAMPLES_LIMIT: usize = 720;
+const MAX_ACCOUNT_HISTORY_LIMIT: usize = 1_000;
+const MAX_BUNDLE_TRANSACTIONS: usize = 16;
 
 pub struct FullImpl;
 
@@ -164,8 +170,12 @@ impl Full for FullImpl {
         &self,
         meta: Self::Metadata,
         data: String,
-        config: Option<RpcSimulateTransactionConfig>,
+        config: Option<RpcSimulateTransactionWithOverridesConfig>,
     ) -> BoxFuture<Result<RpcResponse<RpcSimulateTransactionResult>>> {
+        let RpcSimulateTransactionWithOverridesConfig {
+            config,
+            state_overrides,
+        } = config.unwrap_or_default();
         let RpcSimulateTransactionConfig {
             sig_verify,
             replace_recent_blockhash,
@@ -174,15 +184,25 @@ impl Full for FullImpl {
             accounts: config_accounts,
             min_context_slot,
             inner_instructions: enable_cpi_recording,
-        } = config.unwrap_or_default();
+        } = config;
         let tx_encoding = encoding.unwrap_or(UiTransactionEncoding::Base58);
 
+        // We only have one bank, so all we need to ensure is that it reached
+        // the min context slot
+        if let Err(err) = meta.get_bank_with_config(RpcContextConfig {
+            commitment,
+            min_context_slot,
+        }) {
+            return Box::pin(future::err(err));
+        }
+
         Box::pin(async move {
             simulate_transaction_impl(
                 &meta,
                 data,
                 tx_encoding,
                 config_accounts,
+                state_overrides.unwrap_or_default(),
                 replace_recent_blockhash,
                 sig_verify,
                 enable_cpi_recording,
@@ -225,6 +245,29 @@ impl Full for FullImpl {
         })
     }
 
+    fn send_bundle(
+        &self,
+        meta: Self::Metadata,
+        data: Vec<String>,
+        config: Option<RpcSendTransactionConfig>,
+    ) -> BoxFuture<Result<Vec<String>>> {
+        debug!(
+            "send_bundle rpc request received with {} transactions",
+            data.len()
+        );
+        let RpcSendTransactionConfig {
+            encoding,
+            min_context_slot,
+            ..
+        } = config.unwrap_or_default();
+
+        let tx_encoding = encoding.unwrap_or(UiTransactionEncoding::Base58);
+
+        Box::pin(async move {
+            send_bundle_impl(&meta, data, min_context_slot, tx_encoding).await
+        })
+    }
+
     fn minimum_ledger_slot(&self, meta: Self::Metadata) -> Result<Slot> {
         debug!("minimum_ledger_slot rpc request received");
         // We always start the validator on slot 0 and never clear or snapshot the history
@@ -276,13 +319,14 @@ impl Full for FullImpl {
         config: Option<RpcBlocksConfigWrapper>,
         commitment: Option<CommitmentConfig>,
     ) -> BoxFuture<Result<Vec<Slot>>> {
-        let (end_slot, _) =
+        let (end_slot, config_commitment) =
             config.map(|wrapper| wrapper.unzip()).unwrap_or_default();
         debug!(
             "get_blocks rpc request received: {} -> {:?}",
             start_slot, end_slot
         );
         Box::pin(async move {
+            verify_commitment(config_commitment.or(commitment))?;
             let end_slot = min(
                 meta.get_bank().slot().saturating_sub(1),
                 end_slot.unwrap_or(u64::MAX),
@@ -311,6 +355,7 @@ impl Full for FullImpl {
             start_slot, limit
         );
         Box::pin(async move {
+            verify_commitment(commitment)?;
             let end_slot = min(
                 meta.get_bank().slot().saturating_sub(1),
                 start_slot.saturating_add(limit).saturating_sub(1),
@@ -352,6 +397,7 @@ impl Full for FullImpl {
     {
         let config = config.unwrap_or_default();
         let commitment = config.commitment;
+        let min_context_slot = config.min_context_slot;
 
         let verification = verify_and_parse_signatures_for_address_params(
             address,
@@ -370,7 +416,7 @@ impl Full for FullImpl {
                     limit,
                     RpcContextConfig {
                         commitment,
-                        min_context_slot: None,
+                        min_context_slot,
                     },
                 )
                 .await
@@ -391,10 +437,10 @@ impl Full for FullImpl {
     fn get_latest_blockhash(
         &self,
         meta: Self::Metadata,
-        _config: Option<RpcContextConfig>,
+        config: Option<RpcContextConfig>,
     ) -> Result<RpcResponse<RpcBlockhash>> {
         debug!("get_latest_blockhash rpc request received");
-        meta.get_latest_blockhash()
+        meta.get_latest_blockhash(config.unwrap_or_default())
     }
 
     fn is_blockhash_valid(
@@ -404,12 +450,10 @@ impl Full for FullImpl {
         config: Option<RpcContextConfig>,
     ) -> Result<RpcResponse<bool>> {
         debug!("is_blockhash_valid rpc request received");
-        let min_context_slot =
-            config.and_then(|config| config.min_context_slot);
         let blockhash = Hash::from_str(&blockhash)
             .map_err(|e| Error::invalid_params(format!("{e:?}")))?;
 
-        meta.is_blockhash_valid(&blockhash, min_context_slot)
+        meta.is_blockhash_valid(&blockhash, config.unwrap_or_default())
     }
 
     fn get_fee_for_message(
@@ -462,6 +506,50 @@ impl Full for FullImpl {
             "Ephemeral validator does not support or require priority fees",
         ))
     }
+
+    fn get_account_history(
+        &self,
+        meta: Self::Metadata,
+        pubkey_str: String,
+        limit: Option<usize>,
+    ) -> BoxFuture<Result<Vec<RpcAccountJournalEntry>>> {
+        debug!("get_account_history rpc request received: {:?}", pubkey_str);
+        let limit = limit.unwrap_or(MAX_ACCOUNT_HISTORY_LIMIT);
+        if limit == 0 || limit > MAX_ACCOUNT_HISTORY_LIMIT {
+            return Box::pin(future::err(Error::invalid_params(format!(
+                "Invalid limit; max {MAX_ACCOUNT_HISTORY_LIMIT}"
+            ))));
+        }
+        match verify_pubkey(&pubkey_str) {
+            Err(err) => Box::pin(future::err(err)),
+            Ok(pubkey) => Box::pin(async move {
+                meta.get_account_history(pubkey, limit).await
+            }),
+        }
+    }
+
+    fn get_program_version(
+        &self,
+        meta: Self::Metadata,
+        program_id_str: String,
+        slot: Option<Slot>,
+    ) -> Result<Option<RpcProgramVersion>> {
+        debug!(
+            "get_program_version rpc request received: {:?}",
+            program_id_str
+        );
+        let program_id = verify_pubkey(&program_id_str)?;
+        Ok(meta.get_program_version(&program_id, slot))
+    }
+
+    fn get_chain_slot_mapping(
+        &self,
+        meta: Self::Metadata,
+        slot: Option<Slot>,
+    ) -> Result<Option<RpcChainSlotMapping>> {
+        debug!("get_chain_slot_mapping rpc request received: {:?}", slot);
+        Ok(meta.get_chain_slot_mapping(slot))
+    }
 }
 
 async fn send_transaction_impl(
@@ -473,13 +561,14 @@ async fn send_transaction_impl(
     tx_encoding: UiTransactionEncoding,
     max_retries: Option<usize>,
 ) -> Result<String> {
+    meta.check_accepts_transactions()?;
     let binary_encoding = tx_encoding.into_binary_encoding().ok_or_else(|| {
         Error::invalid_params(format!(
             "unsupported encoding: {tx_encoding}. Supported encodings: base58, base64"
         ))
     })?;
 
-    let (_wire_transaction, unsanitized_tx) =
+    let (wire_transaction, unsanitized_tx) =
         decode_and_deserialize::<VersionedTransaction>(data, binary_encoding)?;
 
     let preflight_bank = &*meta.get_bank_with_config(RpcContextConfig {
@@ -488,6 +577,7 @@ async fn send_transaction_impl(
     })?;
     let transaction = sanitize_transaction(unsanitized_tx, preflight_bank)?;
     let signature = *transaction.signature();
+    admit_transaction(meta, wire_transaction.len(), &transaction).await?;
 
     let mut last_valid_block_height = preflight_bank
         .get_blockhash_last_valid_block_height(
@@ -527,11 +617,50 @@ async fn send_transaction_impl(
     .await
 }
 
+async fn send_bundle_impl(
+    meta: &JsonRpcRequestProcessor,
+    data: Vec<String>,
+    min_context_slot: Option<Slot>,
+    tx_encoding: UiTransactionEncoding,
+) -> Result<Vec<String>> {
+    meta.check_accepts_transactions()?;
+    if data.is_empty() || data.len() > MAX_BUNDLE_TRANSACTIONS {
+        return Err(Error::invalid_params(format!(
+            "bundle must contain between 1 and {} transactions",
+            MAX_BUNDLE_TRANSACTIONS
+        )));
+    }
+    let binary_encoding = tx_encoding.into_binary_encoding().ok_or_else(|| {
+        Error::invalid_params(format!(
+            "unsupported encoding: {tx_encoding}. Supported encodings: base58, base64"
+        ))
+    })?;
+
+    let bank = &*meta.get_bank_with_config(RpcContextConfig {
+        commitment: None,
+        min_context_slot,
+    })?;
+    let mut transactions = Vec::with_capacity(data.len());
+    for data in data {
+        let (wire_transaction, unsanitized_tx) = decode_and_deserialize::<
+            VersionedTransaction,
+        >(
+            data, binary_encoding
+        )?;
+        let transaction = sanitize_transaction(unsanitized_tx, bank)?;
+        admit_transaction(meta, wire_transaction.len(), &transaction).await?;
+        transactions.push(transaction);
+    }
+
+    send_bundle(meta, transactions, !meta.config.disable_sigverify).await
+}
+
 async fn simulate_transaction_impl(
     meta: &JsonRpcRequestProcessor,
     data: String,
     tx_encoding: UiTransactionEncoding,
     config_accounts: Option<RpcSimulateTransactionAccountsConfig>,
+    state_overrides: HashMap<String, RpcAccountStateOverride>,
     replace_recent_blockhash: bool,
     sig_verify: bool,
     enable_cpi_recording: bool,
@@ -542,12 +671,14 @@ async fn simulate_transaction_impl(
         ))
     })?;
 
-    let (_, unsanitized_tx) =
+    let (wire_transaction, unsanitized_tx) =
         decode_and_deserialize::<VersionedTransaction>(data, binary_encoding)?;
 
     meta.simulate_transaction(
         unsanitized_tx,
+        wire_transaction.len(),
         config_accounts,
+        state_overrides,
         replace_recent_blockhash,
         sig_verify,
         enable_cpi_recording,
@@ -29,9 +29,9 @@ impl Minimal for MinimalImpl {
         &self,
         meta: Self::Metadata,
         pubkey_str: String,
-        _config: Option<RpcContextConfig>,
+        config: Option<RpcContextConfig>,
     ) -> Result<RpcResponse<u64>> {
-        meta.get_balance(pubkey_str)
+        meta.get_balance(pubkey_str, config.unwrap_or_default())
     }
 
     fn get_epoch_info(
@@ -112,10 +112,8 @@ impl Minimal for MinimalImpl {
         meta: Self::Metadata,
         config: Option<RpcGetVoteAccountsConfig>,
     ) -> Result<RpcVoteAccountStatus> {
-        Ok(RpcVoteAccountStatus {
-            current: vec![],
-            delinquent: vec![],
-        })
+        debug!("get_vote_accounts rpc request received");
+        meta.get_vote_accounts(config.unwrap_or_default())
     }
 
     fn get_version(&self, _: Self::Metadata) -> Result<RpcVersionInfo> {
@@ -1,5 +1,6 @@
 pub(crate) mod accounts;
 pub(crate) mod accounts_scan;
+pub(crate) mod admin;
 pub(crate) mod bank_data;
 pub(crate) mod deprecated;
 pub(crate) mod full;
@@ -0,0 +1,96 @@
+use std::sync::{Arc, Mutex};
+
+use magicblock_core::robust_lock::RobustMutex;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair};
+use tokio::sync::{mpsc, oneshot};
+
+/// A request to rotate the validator identity made via the admin RPC.
+pub struct IdentityRotationRequest {
+    pub keypair: Keypair,
+    /// Receives the pubkey of the replaced identity or why rotating failed
+    pub respond_to: oneshot::Sender<Result<Pubkey, String>>,
+}
+
+/// Shared between the RPC service and the validator in order to rotate the
+/// validator identity when requested via the admin RPC.
+#[derive(Clone)]
+pub struct RpcIdentityRotation {
+    requests: mpsc::Sender<IdentityRotationRequest>,
+    /// Taken by the validator that handles the requests
+    receiver: Arc<Mutex<Option<mpsc::Receiver<IdentityRotationRequest>>>>,
+}
+
+impl Default for RpcIdentityRotation {
+    fn default() -> Self {
+        // Rotations are handled one at a time
+        let (requests, receiver) = mpsc::channel(1);
+        Self {
+            requests,
+            receiver: Arc::new(Mutex::new(Some(receiver))),
+        }
+    }
+}
+
+impl RpcIdentityRotation {
+    /// Requests the validator to rotate its identity to the [keypair] and
+    /// resolves once it did with the pubkey of the replaced identity.
+    pub async fn request(&self, keypair: Keypair) -> Result<Pubkey, String> {
+        let (respond_to, response) = oneshot::channel();
+        self.requests
+            .send(IdentityRotationRequest {
+                keypair,
+                respond_to,
+            })
+            .await
+            .map_err(|_| {
+                "The validator does not rotate its identity".to_string()
+            })?;
+        response.await.map_err(|_| {
+            "The validator stopped before rotating its identity".to_string()
+        })?
+    }
+
+    /// Takes the requests made via [Self::request], only the first caller
+    /// receives them.
+    /// Requests fail once the receiver is dropped, thus a validator that
+    /// doesn't support rotating its identity drops it right away.
+    pub fn take_requests(
+        &self,
+    ) -> Option<mpsc::Receiver<IdentityRotationRequest>> {
+        self.receiver.lock_robust().take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::signer::Signer;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_request_is_answered_by_the_handler() {
+        let rotation = RpcIdentityRotation::default();
+        let mut requests = rotation.take_requests().unwrap();
+        assert!(rotation.take_requests().is_none());
+
+        let previous = Pubkey::new_unique();
+        let handler = tokio::spawn(async move {
+            let request = requests.recv().await.unwrap();
+            let new_pubkey = request.keypair.pubkey();
+            request.respond_to.send(Ok(previous)).unwrap();
+            new_pubkey
+        });
+
+        let keypair = Keypair::new();
+        let new_pubkey = keypair.pubkey();
+        assert_eq!(rotation.request(keypair).await, Ok(previous));
+        assert_eq!(handler.await.unwrap(), new_pubkey);
+    }
+
+    #[tokio::test]
+    async fn test_request_fails_without_handler() {
+        let rotation = RpcIdentityRotation::default();
+        drop(rotation.take_requests());
+        assert!(rotation.request(Keypair::new()).await.is_err());
+    }
+}
@@ -6,25 +6,37 @@ use std::{
 use jsonrpc_core::{Error, ErrorCode, Metadata, Result, Value};
 use log::*;
 use magicblock_accounts::AccountsManager;
+use magicblock_accounts_api::{
+    BankAccountProvider, IdlAccountDecoder, IdlDecodedAccount,
+};
 use magicblock_accounts_db::accounts_index::AccountSecondaryIndexes;
 use magicblock_bank::{
     bank::Bank, transaction_simulation::TransactionSimulationResult,
 };
+use magicblock_core::{
+    allowed_programs::AllowedPrograms, chain_slot_mapping::ChainSlotMapping,
+    error_code::HasErrorCode, hydrate_report::SharedHydrateReport,
+};
 use magicblock_ledger::{Ledger, SignatureInfosForAddress};
+use magicblock_telemetry::TraceContext;
 use magicblock_transaction_status::TransactionStatusSender;
-use solana_account_decoder::{UiAccount, UiAccountEncoding};
+use solana_account_decoder::{
+    parse_token::is_known_spl_token_id, UiAccount, UiAccountEncoding,
+};
 use solana_rpc_client_api::{
     config::{
         RpcAccountInfoConfig, RpcContextConfig, RpcEncodingConfigWrapper,
-        RpcSignatureStatusConfig, RpcSimulateTransactionAccountsConfig,
-        RpcSupplyConfig, RpcTransactionConfig,
+        RpcGetVoteAccountsConfig, RpcSignatureStatusConfig,
+        RpcSimulateTransactionAccountsConfig, RpcSupplyConfig,
+        RpcTransactionConfig,
     },
     custom_error::RpcCustomError,
     filter::RpcFilterType,
     response::{
         OptionalContext, Response as RpcResponse, RpcBlockhash,
         RpcConfirmedTransactionStatusWithSignature, RpcContactInfo,
-        RpcKeyedAccount, RpcSimulateTransactionResult, RpcSupply,
+        RpcInflationGovernor, RpcInflationRate, RpcKeyedAccount,
+        RpcSimulateTransactionResult, RpcSupply, RpcVoteAccountStatus,
     },
 };
 use solana_sdk::{
@@ -44,14 +56,27 @@ use solana_transaction_status::{
 };
 
 use crate::{
-    account_resolver::{encode_account, get_encoded_account},
+    account_resolver::{
+        encode_account, get_accounts_with_state_overrides, get_encoded_account,
+        get_parsed_token_account,
+    },
+    economics::RpcEconomics,
+    faucet::{FaucetLimiter, FaucetLimits},
     filters::{get_filtered_program_accounts, optimize_filters},
+    firewall::{TransactionFirewall, TransactionFirewallRules},
+    identity_rotation::RpcIdentityRotation,
     rpc_health::{RpcHealth, RpcHealthStatus},
+    shutdown::RpcShutdown,
+    sigverify::{SigverifyPool, SigverifyPoolConfig},
+    traits::rpc_full::{
+        RpcAccountJournalEntry, RpcAccountStateOverride, RpcChainSlotMapping,
+        RpcProgramVersion,
+    },
     transaction::{
-        airdrop_transaction, ensure_accounts, sanitize_transaction,
-        sig_verify_transaction_and_check_precompiles,
+        admit_transaction, airdrop_transaction, ensure_accounts,
+        sanitize_transaction, sig_verify_transaction_and_check_precompiles,
     },
-    utils::{new_response, verify_pubkey},
+    utils::{new_response, verify_context_config, verify_pubkey},
     RpcCustomResult,
 };
 
@@ -83,9 +108,41 @@ pub struct JsonRpcConfig {
     pub transaction_status_sender: Option<TransactionStatusSender>,
     pub rpc_socket_addr: Option<SocketAddr>,
     pub pubsub_socket_addr: Option<SocketAddr>,
+    /// Accepts transactions via QUIC like the TPU of a Solana validator
+    pub tpu_socket_addr: Option<SocketAddr>,
 
     /// Configures if to verify transaction signatures
     pub disable_sigverify: bool,
+    /// Skips verifying signatures of transactions the validator signed
+    /// itself, i.e. faucet airdrops
+    pub skip_internal_sigverify: bool,
+    /// Configures the threads verifying signatures of sent transactions
+    pub sigverify_pool: SigverifyPoolConfig,
+
+    /// Registers the admin methods, i.e. `requestShutdown`
+    pub enable_admin_rpc: bool,
+
+    /// Limits applied to airdrops requested via `requestAirdrop`
+    pub faucet_limits: FaucetLimits,
+
+    /// Rejects requests that execute transactions, i.e. when serving as
+    /// a read replica of a primary validator
+    pub read_only: bool,
+
+    /// Rules applied to transactions before their accounts are cloned
+    pub firewall_rules: TransactionFirewallRules,
+
+    /// Values reported by the cluster economics methods, i.e. `getSupply`
+    pub economics: RpcEconomics,
+
+    /// Maps our slots to the slots of the base chain for `getChainSlotMapping`
+    pub chain_slot_mapping: ChainSlotMapping,
+
+    /// The programs that may be cloned, managed via the admin RPC
+    pub allowed_programs: AllowedPrograms,
+
+    /// The outcome of hydrating accounts at startup for `getHydrateReport`
+    pub hydrate_report: SharedHydrateReport,
 }
 
 // NOTE: from rpc/src/rpc.rs :193
@@ -97,8 +154,15 @@ pub struct JsonRpcRequestProcessor {
     pub(crate) config: JsonRpcConfig,
     pub(crate) genesis_hash: Hash,
     pub faucet_keypair: Arc<Keypair>,
+    pub(crate) faucet_limiter: FaucetLimiter,
+    pub(crate) firewall: TransactionFirewall,
+    pub(crate) sigverify_pool: SigverifyPool,
 
     pub accounts_manager: Arc<AccountsManager>,
+    pub(crate) shutdown: RpcShutdown,
+    pub(crate) identity_rotation: RpcIdentityRotation,
+    /// The trace context propagated with the request via the `traceparent` header
+    pub(crate) trace_context: TraceContext,
 }
 impl Metadata for JsonRpcRequestProcessor {}
 
@@ -111,18 +175,54 @@ impl JsonRpcRequestProcessor {
         genesis_hash: Hash,
         accounts_manager: Arc<AccountsManager>,
         config: JsonRpcConfig,
+        shutdown: RpcShutdown,
+        identity_rotation: RpcIdentityRotation,
     ) -> Self {
+        let faucet_limiter = FaucetLimiter::new(config.faucet_limits.clone());
+        let firewall = TransactionFirewall::new(config.firewall_rules.clone());
+        let sigverify_pool = SigverifyPool::new(&config.sigverify_pool);
         Self {
             bank,
             ledger,
             health,
             config,
             faucet_keypair: Arc::new(faucet_keypair),
+            faucet_limiter,
+            firewall,
+            sigverify_pool,
             genesis_hash,
             accounts_manager,
+            shutdown,
+            identity_rotation,
+            trace_context: TraceContext::new(),
         }
     }
 
+    pub(crate) fn with_trace_context(
+        &self,
+        trace_context: TraceContext,
+    ) -> Self {
+        Self {
+            trace_context,
+            ..self.clone()
+        }
+    }
+
+    /// Fails for requests that would execute a transaction when this node
+    /// only serves reads
+    pub(crate) fn check_accepts_transactions(&self) -> Result<()> {
+        if self.config.read_only {
+            return Err(Error {
+                code: ErrorCode::InvalidRequest,
+                message: "This node is a read replica and does not accept \
+                          transactions, send them to the primary validator"
+                    .to_string(),
+                data: None,
+            });
+        }
+        Ok(())
+    }
+
     // -----------------
     // Transaction Signatures
     // -----------------
@@ -137,17 +237,7 @@ impl JsonRpcRequestProcessor {
         let upper_limit = before;
         let lower_limit = until;
 
-        let highest_slot = {
-            let min_context_slot = config.min_context_slot.unwrap_or_default();
-            let bank_slot = self.bank.slot();
-            if bank_slot < min_context_slot {
-                return Err(RpcCustomError::MinContextSlotNotReached {
-                    context_slot: bank_slot,
-                }
-                .into());
-            }
-            bank_slot
-        };
+        let highest_slot = self.get_bank_with_config(config)?.slot();
 
         let SignatureInfosForAddress { infos, .. } = self
             .ledger
@@ -201,13 +291,17 @@ impl JsonRpcRequestProcessor {
         let RpcAccountInfoConfig {
             encoding,
             data_slice,
-            ..
+            commitment,
+            min_context_slot,
         } = config.unwrap_or_default();
+        let bank = self.get_bank_with_config(RpcContextConfig {
+            commitment,
+            min_context_slot,
+        })?;
         let encoding = encoding.unwrap_or(UiAccountEncoding::Binary);
-        let response = get_encoded_account(
-            &self.bank, pubkey, encoding, data_slice, None,
-        )?;
-        Ok(new_response(&self.bank, response))
+        let response =
+            get_encoded_account(&bank, pubkey, encoding, data_slice, None)?;
+        Ok(new_response(&bank, response))
     }
 
     pub fn get_multiple_accounts(
@@ -218,20 +312,36 @@ impl JsonRpcRequestProcessor {
         let RpcAccountInfoConfig {
             encoding,
             data_slice,
-            ..
+            commitment,
+            min_context_slot,
         } = config.unwrap_or_default();
+        let bank = self.get_bank_with_config(RpcContextConfig {
+            commitment,
+            min_context_slot,
+        })?;
 
         let encoding = encoding.unwrap_or(UiAccountEncoding::Base64);
 
         let accounts = pubkeys
             .into_iter()
             .map(|pubkey| {
-                get_encoded_account(
-                    &self.bank, &pubkey, encoding, data_slice, None,
-                )
+                get_encoded_account(&bank, &pubkey, encoding, data_slice, None)
             })
             .collect::<Result<Vec<_>>>()?;
-        Ok(new_response(&self.bank, accounts))
+        Ok(new_response(&bank, accounts))
+    }
+
+    pub fn get_parsed_account_info(
+        &self,
+        pubkey: &Pubkey,
+    ) -> Result<RpcResponse<Option<IdlDecodedAccount>>> {
+        let bank = self.get_bank();
+        let decoder =
+            IdlAccountDecoder::new(BankAccountProvider::new(bank.clone()));
+        let account = decoder
+            .decode_account(pubkey)
+            .map_err(|err| Error::invalid_params(format!("{err}")))?;
+        Ok(new_response(&bank, account))
     }
 
     pub fn get_program_accounts(
@@ -244,10 +354,14 @@ impl JsonRpcRequestProcessor {
         let RpcAccountInfoConfig {
             encoding,
             data_slice: data_slice_config,
-            ..
+            commitment,
+            min_context_slot,
         } = config.unwrap_or_default();
 
-        let bank = &self.bank;
+        let bank = &*self.get_bank_with_config(RpcContextConfig {
+            commitment,
+            min_context_slot,
+        })?;
 
         let encoding = encoding.unwrap_or(UiAccountEncoding::Binary);
 
@@ -276,19 +390,27 @@ impl JsonRpcRequestProcessor {
                 filters,
             )?
         };
-        // TODO: possibly JSON parse the accounts
 
+        // Token accounts are parsed along with their Token-2022 extensions
+        // which requires the decimals of their mint
+        let parse_token_accounts = is_known_spl_token_id(program_id)
+            && encoding == UiAccountEncoding::JsonParsed;
         let accounts = keyed_accounts
             .into_iter()
             .map(|(pubkey, account)| {
-                Ok(RpcKeyedAccount {
-                    pubkey: pubkey.to_string(),
-                    account: encode_account(
+                let account = if parse_token_accounts {
+                    get_parsed_token_account(bank, &pubkey, account, None)
+                } else {
+                    encode_account(
                         &account,
                         &pubkey,
                         encoding,
                         data_slice_config,
-                    )?,
+                    )?
+                };
+                Ok(RpcKeyedAccount {
+                    pubkey: pubkey.to_string(),
+                    account,
                 })
             })
             .collect::<Result<Vec<_>>>()?;
@@ -299,27 +421,35 @@ impl JsonRpcRequestProcessor {
         })
     }
 
-    pub fn get_balance(&self, pubkey_str: String) -> Result<RpcResponse<u64>> {
+    pub fn get_balance(
+        &self,
+        pubkey_str: String,
+        config: RpcContextConfig,
+    ) -> Result<RpcResponse<u64>> {
         let pubkey = Pubkey::from_str(&pubkey_str).map_err(|e| Error {
             code: ErrorCode::InvalidParams,
             message: format!("Invalid pubkey: {}", e),
             data: Some(Value::String(pubkey_str)),
         })?;
-        let balance = self.bank.get_balance(&pubkey);
-        Ok(new_response(&self.bank, balance))
+        let bank = self.get_bank_with_config(config)?;
+        let balance = bank.get_balance(&pubkey);
+        Ok(new_response(&bank, balance))
     }
 
     // -----------------
     // BlockHash
     // -----------------
-    pub fn get_latest_blockhash(&self) -> Result<RpcResponse<RpcBlockhash>> {
-        let bank = &self.bank;
+    pub fn get_latest_blockhash(
+        &self,
+        config: RpcContextConfig,
+    ) -> Result<RpcResponse<RpcBlockhash>> {
+        let bank = self.get_bank_with_config(config)?;
         let blockhash = bank.last_blockhash();
         let last_valid_block_height = bank
             .get_blockhash_last_valid_block_height(&blockhash)
             .expect("bank blockhash queue should contain blockhash");
         Ok(new_response(
-            bank,
+            &bank,
             RpcBlockhash {
                 blockhash: blockhash.to_string(),
                 last_valid_block_height,
@@ -330,36 +460,10 @@ impl JsonRpcRequestProcessor {
     pub fn is_blockhash_valid(
         &self,
         blockhash: &Hash,
-        min_context_slot: Option<u64>,
+        config: RpcContextConfig,
     ) -> Result<RpcResponse<bool>> {
-        let bank = self.get_bank();
-        let age = match min_context_slot {
-            Some(min_slot) => {
-                // The original implementation can rely on just the slot to determinine
-                // if the min context slot rule applies. It can do that since it can select
-                // the appropriate bank for it.
-                // In our case we have to estimate this by calculating the age the block hash
-                // can have based on the genesis creation time and the slot duration.
-                let current_slot = bank.slot();
-                if min_slot > current_slot {
-                    return Err(Error::invalid_params(format!(
-                        "min_context_slot {min_slot} is in the future"
-                    )));
-                }
-                let slot_diff = current_slot - min_slot;
-                let slot_diff_millis =
-                    (self.config.slot_duration.as_micros() as f64 / 1_000.0
-                        * (slot_diff as f64)) as u64;
-                let age = slot_diff_millis;
-                Some(age)
-            }
-            None => None,
-        };
-        let is_valid = match age {
-            Some(age) => bank.is_blockhash_valid_for_age(blockhash, age),
-            None => bank.is_blockhash_valid(blockhash),
-        };
-
+        let bank = self.get_bank_with_config(config)?;
+        let is_valid = bank.is_blockhash_valid(blockhash);
         Ok(new_response(&bank, is_valid))
     }
 
@@ -441,12 +545,17 @@ impl JsonRpcRequestProcessor {
     // -----------------
     // Bank
     // -----------------
+    /// Returns the bank to serve a request with the provided context.
+    /// We only keep the latest bank, see [verify_context_config] for how it
+    /// serves the commitment levels.
+    /// Fails if the bank didn't reach the `min_context_slot` yet.
     pub fn get_bank_with_config(
         &self,
-        _config: RpcContextConfig,
+        config: RpcContextConfig,
     ) -> Result<Arc<Bank>> {
-        // We only have one bank, so the config isn't important to us
-        Ok(self.get_bank())
+        let bank = self.get_bank();
+        verify_context_config(bank.slot(), &config)?;
+        Ok(bank)
     }
 
     pub fn get_bank(&self) -> Arc<Bank> {
@@ -481,7 +590,11 @@ impl JsonRpcRequestProcessor {
             lamports: 0,
             accounts: vec![],
         };
-        let total_supply = bank.capitalization();
+        let total_supply = self
+            .config
+            .economics
+            .supply_lamports
+            .unwrap_or_else(|| bank.capitalization());
         let non_circulating_accounts =
             if config.exclude_non_circulating_accounts_list {
                 vec![]
@@ -517,6 +630,35 @@ impl JsonRpcRequestProcessor {
         Ok(balance)
     }
 
+    pub fn get_inflation_governor(&self) -> RpcInflationGovernor {
+        self.config.economics.inflation_governor()
+    }
+
+    pub fn get_inflation_rate(&self) -> RpcInflationRate {
+        self.config.economics.inflation_rate(self.bank.epoch())
+    }
+
+    pub fn get_vote_accounts(
+        &self,
+        config: RpcGetVoteAccountsConfig,
+    ) -> Result<RpcVoteAccountStatus> {
+        let identity = self.bank.get_identity();
+        let current = match config.vote_pubkey {
+            Some(vote_pubkey) if verify_pubkey(&vote_pubkey)? != identity => {
+                vec![]
+            }
+            _ => vec![self.config.economics.vote_account(
+                &identity,
+                self.bank.epoch(),
+                self.bank.slot(),
+            )],
+        };
+        Ok(RpcVoteAccountStatus {
+            current,
+            delinquent: vec![],
+        })
+    }
+
     pub fn get_epoch_schedule(&self) -> EpochSchedule {
         // Since epoch schedule data comes from the genesis config, any commitment level should be
         // fine
@@ -531,18 +673,25 @@ impl JsonRpcRequestProcessor {
         pubkey_str: String,
         lamports: u64,
     ) -> Result<String> {
+        self.check_accepts_transactions()?;
         let pubkey = pubkey_str.parse().map_err(|e| Error {
             code: ErrorCode::InvalidParams,
             message: format!("Invalid pubkey: {}", e),
             data: None,
         })?;
-        airdrop_transaction(
+        let reservation = self.faucet_limiter.reserve(&pubkey, lamports)?;
+        let res = airdrop_transaction(
             self,
             pubkey,
             lamports,
-            !self.config.disable_sigverify,
+            !self.config.disable_sigverify
+                && !self.config.skip_internal_sigverify,
         )
-        .await
+        .await;
+        if res.is_err() {
+            self.faucet_limiter.release(reservation);
+        }
+        res
     }
 
     pub async fn get_transaction(
@@ -579,6 +728,56 @@ impl JsonRpcRequestProcessor {
         Ok(None)
     }
 
+    pub async fn get_account_history(
+        &self,
+        pubkey: Pubkey,
+        limit: usize,
+    ) -> Result<Vec<RpcAccountJournalEntry>> {
+        let entries = self
+            .ledger
+            .get_account_journal_entries(pubkey, limit)
+            .map_err(|err| Error::invalid_params(format!("{err}")))?;
+        Ok(entries
+            .into_iter()
+            .map(|entry| RpcAccountJournalEntry {
+                slot: entry.slot,
+                signature: entry.signature.to_string(),
+                writer_program: entry.writer_program.to_string(),
+                lamports: entry.lamports,
+                data_range: (entry.data_start, entry.data_end),
+                data_digest: entry.data_digest.to_string(),
+            })
+            .collect())
+    }
+
+    pub fn get_program_version(
+        &self,
+        program_id: &Pubkey,
+        slot: Option<Slot>,
+    ) -> Option<RpcProgramVersion> {
+        let slot = slot.unwrap_or_else(|| self.bank.slot());
+        self.bank
+            .get_program_version(program_id, slot)
+            .map(|version| RpcProgramVersion {
+                effective_slot: version.effective_slot,
+                bytecode_hash: version.bytecode_hash.to_string(),
+            })
+    }
+
+    pub fn get_chain_slot_mapping(
+        &self,
+        slot: Option<Slot>,
+    ) -> Option<RpcChainSlotMapping> {
+        let ephemeral_slot = slot.unwrap_or_else(|| self.bank.slot());
+        self.config
+            .chain_slot_mapping
+            .chain_slot_at(ephemeral_slot)
+            .map(|chain_slot| RpcChainSlotMapping {
+                ephemeral_slot,
+                chain_slot,
+            })
+    }
+
     pub fn transaction_status_sender(
         &self,
     ) -> Option<&TransactionStatusSender> {
@@ -642,7 +841,9 @@ impl JsonRpcRequestProcessor {
     pub async fn simulate_transaction(
         &self,
         mut unsanitized_tx: VersionedTransaction,
+        wire_size: usize,
         config_accounts: Option<RpcSimulateTransactionAccountsConfig>,
+        state_overrides: HashMap<String, RpcAccountStateOverride>,
         replace_recent_blockhash: bool,
         sig_verify: bool,
         enable_cpi_recording: bool,
@@ -663,10 +864,11 @@ impl JsonRpcRequestProcessor {
             sanitize_transaction(unsanitized_tx, &*bank)?;
         if sig_verify {
             sig_verify_transaction_and_check_precompiles(
+                &bank,
                 &sanitized_transaction,
-                &bank.feature_set,
             )?;
         }
+        admit_transaction(self, wire_size, &sanitized_transaction).await?;
 
         if let Err(err) =
             ensure_accounts(&self.accounts_manager, &sanitized_transaction)
@@ -678,6 +880,7 @@ impl JsonRpcRequestProcessor {
                 format!("{MAGIC_ID}: An error was encountered before simulating the transaction."),
                 format!("{MAGIC_ID}: Something went wrong when trying to clone the needed accounts into the validator."),
                 format!("{MAGIC_ID}: Error: {err:?}"),
+                format!("{MAGIC_ID}: ErrorCode: {}", err.error_code()),
             ];
             return Ok(new_response(
                 &bank,
@@ -692,6 +895,13 @@ impl JsonRpcRequestProcessor {
             ));
         }
 
+        // Overrides are resolved once the accounts were cloned such that
+        // they replace the state of the clones
+        let state_overrides = get_accounts_with_state_overrides(
+            &bank,
+            &sanitized_transaction,
+            state_overrides,
+        )?;
         let TransactionSimulationResult {
             result,
             logs,
@@ -699,9 +909,10 @@ impl JsonRpcRequestProcessor {
             units_consumed,
             return_data,
             inner_instructions,
-        } = bank.simulate_transaction_unchecked(
+        } = bank.simulate_transaction_with_state_overrides_unchecked(
             &sanitized_transaction,
             enable_cpi_recording,
+            &state_overrides,
         );
 
         let account_keys = sanitized_transaction.message().account_keys();
@@ -788,7 +999,7 @@ impl JsonRpcRequestProcessor {
             pubkey: identity_id.to_string(),
             gossip: None,
             tpu: None,
-            tpu_quic: None,
+            tpu_quic: self.config.tpu_socket_addr,
             rpc: self.config.rpc_socket_addr,
             pubsub: self.config.pubsub_socket_addr,
             version: Some(magicblock_version::version!().to_string()),
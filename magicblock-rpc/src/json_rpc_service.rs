@@ -1,6 +1,7 @@
 use std::{
+    collections::HashMap,
     net::SocketAddr,
-    sync::{atomic::AtomicBool, Arc, RwLock},
+    sync::{Arc, RwLock},
     thread::{self, JoinHandle},
 };
 
@@ -9,11 +10,15 @@ use jsonrpc_http_server::{
     hyper, AccessControlAllowOrigin, CloseHandle, DomainsValidation,
     ServerBuilder,
 };
+use magicblock_core::robust_lock::RobustRwLock;
 // NOTE: from rpc/src/rpc_service.rs
 use log::*;
 use magicblock_accounts::AccountsManager;
 use magicblock_bank::bank::Bank;
 use magicblock_ledger::Ledger;
+use magicblock_telemetry::{
+    is_telemetry_enabled, trace_context_from_headers, TraceContext,
+};
 use solana_perf::thread::renice_this_thread;
 use solana_sdk::{hash::Hash, signature::Keypair};
 use tokio::runtime::Runtime;
@@ -21,16 +26,20 @@ use tokio::runtime::Runtime;
 use crate::{
     handlers::{
         accounts::AccountsDataImpl, accounts_scan::AccountsScanImpl,
-        bank_data::BankDataImpl, deprecated::DeprecatedImpl, full::FullImpl,
-        minimal::MinimalImpl,
+        admin::AdminImpl, bank_data::BankDataImpl, deprecated::DeprecatedImpl,
+        full::FullImpl, minimal::MinimalImpl,
     },
+    identity_rotation::RpcIdentityRotation,
     json_rpc_request_processor::{JsonRpcConfig, JsonRpcRequestProcessor},
     rpc_health::RpcHealth,
+    rpc_metrics_middleware::RpcMetricsMiddleware,
     rpc_request_middleware::RpcRequestMiddleware,
+    shutdown::RpcShutdown,
+    tpu::TpuService,
     traits::{
         rpc_accounts::AccountsData, rpc_accounts_scan::AccountsScan,
-        rpc_bank_data::BankData, rpc_deprecated::Deprecated, rpc_full::Full,
-        rpc_minimal::Minimal,
+        rpc_admin::Admin, rpc_bank_data::BankData, rpc_deprecated::Deprecated,
+        rpc_full::Full, rpc_minimal::Minimal,
     },
     utils::MAX_REQUEST_BODY_SIZE,
 };
@@ -40,10 +49,12 @@ pub struct JsonRpcService {
     rpc_niceness_adj: i8,
     runtime: Arc<Runtime>,
     request_processor: JsonRpcRequestProcessor,
-    startup_verification_complete: Arc<AtomicBool>,
+    shutdown: RpcShutdown,
+    identity_rotation: RpcIdentityRotation,
     max_request_body_size: usize,
     rpc_thread_handle: RwLock<Option<JoinHandle<()>>>,
     close_handle: Arc<RwLock<Option<CloseHandle>>>,
+    tpu_service: RwLock<Option<TpuService>>,
 }
 
 impl JsonRpcService {
@@ -66,9 +77,12 @@ impl JsonRpcService {
         let runtime = get_runtime(&config);
         let rpc_niceness_adj = config.rpc_niceness_adj;
 
-        let startup_verification_complete =
-            Arc::clone(bank.get_startup_verification_complete());
-        let health = RpcHealth::new(startup_verification_complete.clone());
+        let health = RpcHealth::new(
+            Arc::clone(bank.get_startup_verification_complete()),
+            bank.validator_context().subscribe_to_stage(),
+        );
+        let shutdown = RpcShutdown::default();
+        let identity_rotation = RpcIdentityRotation::default();
 
         let request_processor = JsonRpcRequestProcessor::new(
             bank,
@@ -78,6 +92,8 @@ impl JsonRpcService {
             genesis_hash,
             accounts_manager,
             config,
+            shutdown.clone(),
+            identity_rotation.clone(),
         );
 
         Ok(Self {
@@ -86,24 +102,26 @@ impl JsonRpcService {
             max_request_body_size,
             runtime,
             request_processor,
-            startup_verification_complete,
+            shutdown,
+            identity_rotation,
             rpc_thread_handle: Default::default(),
             close_handle: Default::default(),
+            tpu_service: Default::default(),
         })
     }
 
     pub fn start(&self) -> Result<(), String> {
-        if self.close_handle.read().unwrap().is_some() {
+        if self.close_handle.read_robust().is_some() {
             return Err("JSON RPC service already running".to_string());
         }
 
         let rpc_niceness_adj = self.rpc_niceness_adj;
-        let startup_verification_complete =
-            self.startup_verification_complete.clone();
+        let health = self.request_processor.health.clone();
         let request_processor = self.request_processor.clone();
         let rpc_addr = self.rpc_addr;
         let runtime = self.runtime.handle().clone();
         let max_request_body_size = self.max_request_body_size;
+        let enable_admin_rpc = self.request_processor.config.enable_admin_rpc;
 
         let close_handle_rc = self.close_handle.clone();
         let thread_handle = thread::Builder::new()
@@ -111,7 +129,9 @@ impl JsonRpcService {
             .spawn(move || {
                 renice_this_thread(rpc_niceness_adj).unwrap();
 
-                let mut io = MetaIoHandler::default();
+                let mut io = MetaIoHandler::with_middleware(
+                    RpcMetricsMiddleware,
+                );
 
                 io.extend_with(AccountsDataImpl.to_delegate());
                 io.extend_with(AccountsScanImpl.to_delegate());
@@ -119,14 +139,21 @@ impl JsonRpcService {
                 io.extend_with(BankDataImpl.to_delegate());
                 io.extend_with(MinimalImpl.to_delegate());
                 io.extend_with(DeprecatedImpl.to_delegate());
+                if enable_admin_rpc {
+                    io.extend_with(AdminImpl.to_delegate());
+                }
 
-                let health = RpcHealth::new(startup_verification_complete);
                 let request_middleware = RpcRequestMiddleware::new(health);
 
                 let server = ServerBuilder::with_meta_extractor(
                     io,
-                    move |_req: &hyper::Request<hyper::Body>| {
-                       request_processor.clone()
+                    move |req: &hyper::Request<hyper::Body>| {
+                        if is_telemetry_enabled() {
+                            request_processor
+                                .with_trace_context(trace_context(req))
+                        } else {
+                            request_processor.clone()
+                        }
                     },
                 )
                 .event_loop_executor(runtime)
@@ -152,8 +179,7 @@ impl JsonRpcService {
                     Ok(server) => {
                         let close_handle = server.close_handle().clone();
                         close_handle_rc
-                            .write()
-                            .unwrap()
+                            .write_robust()
                             .replace(close_handle);
                         server.wait();
                     }
@@ -161,30 +187,52 @@ impl JsonRpcService {
             })
             .unwrap();
 
-        self.rpc_thread_handle
-            .write()
-            .unwrap()
-            .replace(thread_handle);
+        self.rpc_thread_handle.write_robust().replace(thread_handle);
+
+        if let Some(tpu_addr) = self.request_processor.config.tpu_socket_addr {
+            let tpu_service = TpuService::start(
+                tpu_addr,
+                self.request_processor.clone(),
+                self.runtime.handle().clone(),
+            )?;
+            self.tpu_service.write_robust().replace(tpu_service);
+        }
 
         Ok(())
     }
 
     pub fn close(&self) {
-        if let Some(close_handle) = self.close_handle.write().unwrap().take() {
+        if let Some(close_handle) = self.close_handle.write_robust().take() {
             close_handle.close();
         }
+        if let Some(tpu_service) = self.tpu_service.read_robust().as_ref() {
+            tpu_service.close();
+        }
     }
 
     pub fn join(&self) -> Result<(), String> {
+        if let Some(tpu_service) = self.tpu_service.write_robust().take() {
+            tpu_service.join().map_err(|err| format!("{:?}", err))?;
+        }
         self.rpc_thread_handle
-            .write()
-            .unwrap()
+            .write_robust()
             .take()
             .map(|x| x.join())
             .unwrap_or(Ok(()))
             .map_err(|err| format!("{:?}", err))
     }
 
+    /// Used to stop accepting transactions and to wait for shutdown requests
+    /// made via the admin RPC.
+    pub fn shutdown(&self) -> RpcShutdown {
+        self.shutdown.clone()
+    }
+
+    /// Used to handle the identity rotations requested via the admin RPC.
+    pub fn identity_rotation(&self) -> RpcIdentityRotation {
+        self.identity_rotation.clone()
+    }
+
     pub fn rpc_addr(&self) -> &SocketAddr {
         &self.rpc_addr
     }
@@ -213,3 +261,15 @@ fn get_runtime(config: &JsonRpcConfig) -> Arc<tokio::runtime::Runtime> {
             .expect("Runtime"),
     )
 }
+
+/// Extracts the W3C trace context headers of the request.
+fn trace_context(req: &hyper::Request<hyper::Body>) -> TraceContext {
+    let headers = ["traceparent", "tracestate"]
+        .into_iter()
+        .filter_map(|name| {
+            let value = req.headers().get(name)?.to_str().ok()?;
+            Some((name.to_string(), value.to_string()))
+        })
+        .collect::<HashMap<_, _>>();
+    trace_context_from_headers(&headers)
+}
@@ -1,13 +1,21 @@
 use solana_rpc_client_api::custom_error::RpcCustomError;
 
 mod account_resolver;
+pub mod economics;
+pub mod faucet;
 mod filters;
+pub mod firewall;
 mod handlers;
+pub mod identity_rotation;
 pub mod json_rpc_request_processor;
 pub mod json_rpc_service;
 mod perf;
 mod rpc_health;
+mod rpc_metrics_middleware;
 mod rpc_request_middleware;
+pub mod shutdown;
+pub mod sigverify;
+mod tpu;
 mod traits;
 mod transaction;
 mod utils;
@@ -4,6 +4,9 @@ use std::sync::{
     Arc,
 };
 
+use magicblock_program::validator::ValidatorStage;
+use tokio::sync::watch;
+
 #[derive(PartialEq, Eq, Clone, Copy, Debug)]
 pub enum RpcHealthStatus {
     Ok,
@@ -13,12 +16,17 @@ pub enum RpcHealthStatus {
 #[derive(Clone)]
 pub struct RpcHealth {
     startup_verification_complete: Arc<AtomicBool>,
+    stage: watch::Receiver<ValidatorStage>,
 }
 
 impl RpcHealth {
-    pub(crate) fn new(startup_verification_complete: Arc<AtomicBool>) -> Self {
+    pub(crate) fn new(
+        startup_verification_complete: Arc<AtomicBool>,
+        stage: watch::Receiver<ValidatorStage>,
+    ) -> Self {
         Self {
             startup_verification_complete,
+            stage,
         }
     }
 
@@ -29,4 +37,9 @@ impl RpcHealth {
             RpcHealthStatus::Ok
         }
     }
+
+    /// The stage the validator is in
+    pub(crate) fn stage(&self) -> ValidatorStage {
+        *self.stage.borrow()
+    }
 }
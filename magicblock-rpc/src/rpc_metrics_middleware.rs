@@ -0,0 +1,50 @@
+use std::time::Instant;
+
+use jsonrpc_core::{
+    futures::future::Either, middleware, Call, ErrorCode, FutureOutput,
+    Metadata, Middleware, Output,
+};
+use magicblock_metrics::metrics;
+
+/// Records count, outcome and duration of each RPC call per method.
+pub(crate) struct RpcMetricsMiddleware;
+
+impl<M: Metadata> Middleware<M> for RpcMetricsMiddleware {
+    type Future = middleware::NoopFuture;
+    type CallFuture = FutureOutput;
+
+    fn on_call<F, X>(
+        &self,
+        call: Call,
+        meta: M,
+        next: F,
+    ) -> Either<Self::CallFuture, X>
+    where
+        F: Fn(Call, M) -> X + Send + Sync,
+        X: std::future::Future<Output = Option<Output>> + Send + 'static,
+    {
+        let method = match &call {
+            Call::MethodCall(method_call) => method_call.method.clone(),
+            Call::Notification(notification) => notification.method.clone(),
+            Call::Invalid { .. } => return Either::Right(next(call, meta)),
+        };
+        let start = Instant::now();
+        let output = next(call, meta);
+        Either::Left(Box::pin(async move {
+            let output = output.await;
+            let (method, outcome) = match &output {
+                // Methods are provided by the client, we don't want to
+                // create a label for each one that doesn't exist
+                Some(Output::Failure(failure))
+                    if failure.error.code == ErrorCode::MethodNotFound =>
+                {
+                    ("unknown".to_string(), metrics::Outcome::Error)
+                }
+                Some(Output::Failure(_)) => (method, metrics::Outcome::Error),
+                _ => (method, metrics::Outcome::Success),
+            };
+            metrics::observe_rpc_request(&method, outcome, start.elapsed());
+            output
+        }))
+    }
+}
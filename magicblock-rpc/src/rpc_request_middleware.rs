@@ -2,6 +2,7 @@
 
 use jsonrpc_http_server::{hyper, RequestMiddleware, RequestMiddlewareAction};
 use log::*;
+use magicblock_program::validator::ValidatorStage;
 
 use crate::rpc_health::{RpcHealth, RpcHealthStatus};
 pub(crate) struct RpcRequestMiddleware {
@@ -13,9 +14,14 @@ impl RpcRequestMiddleware {
         Self { health }
     }
 
+    /// Responds with `ok` once the validator is running and with the stage
+    /// it is in otherwise, i.e. `hydrating` or `draining`.
     fn health_check(&self) -> &'static str {
         let response = match self.health.check() {
-            RpcHealthStatus::Ok => "ok",
+            RpcHealthStatus::Ok => match self.health.stage() {
+                ValidatorStage::Running => "ok",
+                stage => stage.as_str(),
+            },
             RpcHealthStatus::Unknown => "unknown",
         };
         info!("health check: {}", response);
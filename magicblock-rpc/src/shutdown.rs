@@ -0,0 +1,39 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use tokio::sync::Notify;
+
+/// Shared between the RPC service and the validator in order to coordinate
+/// a graceful shutdown.
+#[derive(Clone, Default)]
+pub struct RpcShutdown {
+    /// Set once the validator started shutting down and thus no longer
+    /// accepts transactions
+    draining: Arc<AtomicBool>,
+    /// Notified when a shutdown was requested via the admin RPC
+    requested: Arc<Notify>,
+}
+
+impl RpcShutdown {
+    pub fn stop_accepting_transactions(&self) {
+        self.draining.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_accepting_transactions(&self) -> bool {
+        !self.draining.load(Ordering::Relaxed)
+    }
+
+    /// Requests the validator to shut down, see [Self::requested].
+    pub fn request(&self) {
+        // notify_one stores a permit, thus the request isn't lost if
+        // nobody is waiting for it yet
+        self.requested.notify_one();
+    }
+
+    /// Resolves once a shutdown was requested via [Self::request].
+    pub async fn requested(&self) {
+        self.requested.notified().await
+    }
+}
@@ -0,0 +1,339 @@
+use curve25519_dalek::edwards::CompressedEdwardsY;
+use jsonrpc_core::{Error, ErrorCode, Result};
+use log::*;
+use magicblock_bank::bank::Bank;
+use magicblock_core::error_code::MagicErrorCode;
+use magicblock_metrics::metrics;
+use rayon::{prelude::*, ThreadPool, ThreadPoolBuilder};
+use solana_sdk::{
+    pubkey::Pubkey, signature::Signature, transaction::SanitizedTransaction,
+};
+use tokio::sync::oneshot;
+
+use crate::{
+    transaction::{verify_session_key_signatures, verify_signatures},
+    utils::error_with_magic_code,
+};
+
+/// Transactions waiting to be verified, once that many are queued new ones
+/// are rejected until the pool catches up
+const MAX_QUEUED_TRANSACTIONS: usize = 4_096;
+
+/// Configures the [SigverifyPool].
+#[derive(Debug, Clone)]
+pub struct SigverifyPoolConfig {
+    /// Number of threads verifying signatures, `0` uses one per CPU
+    pub threads: usize,
+    /// Max number of transactions verified together in one batch
+    pub max_batch_size: usize,
+}
+
+impl Default for SigverifyPoolConfig {
+    fn default() -> Self {
+        Self {
+            threads: 4,
+            max_batch_size: 64,
+        }
+    }
+}
+
+struct SigverifyRequest {
+    transaction: SanitizedTransaction,
+    respond_to: oneshot::Sender<(SanitizedTransaction, Vec<bool>)>,
+}
+
+/// Verifies transaction signatures on a dedicated thread pool instead of the
+/// RPC threads.
+/// Transactions that arrive while a batch is verified are collected into the
+/// next batch. Its signatures are split into one chunk per thread, each of
+/// which is checked via a single batched ed25519 verification.
+#[derive(Clone)]
+pub(crate) struct SigverifyPool {
+    sender: crossbeam_channel::Sender<SigverifyRequest>,
+}
+
+impl SigverifyPool {
+    pub fn new(config: &SigverifyPoolConfig) -> Self {
+        let (sender, receiver) =
+            crossbeam_channel::bounded(MAX_QUEUED_TRANSACTIONS);
+        let pool = ThreadPoolBuilder::new()
+            .num_threads(config.threads)
+            .thread_name(|idx| format!("mbSigverify{idx:02}"))
+            .build()
+            .expect("Failed to build sigverify thread pool");
+        let max_batch_size = config.max_batch_size.max(1);
+        std::thread::Builder::new()
+            .name("mbSigverifyBatcher".to_string())
+            .spawn(move || run_batches(receiver, pool, max_batch_size))
+            .expect("Failed to spawn sigverify batcher thread");
+        Self { sender }
+    }
+
+    /// Verifies the signatures of the transaction and hands it back if they
+    /// are valid.
+    /// Signatures produced by a session key registered in the [bank] in place
+    /// of their signer are valid as well.
+    pub async fn verify(
+        &self,
+        bank: &Bank,
+        transaction: SanitizedTransaction,
+    ) -> Result<SanitizedTransaction> {
+        let (respond_to, response) = oneshot::channel();
+        self.sender
+            .try_send(SigverifyRequest {
+                transaction,
+                respond_to,
+            })
+            .map_err(|err| match err {
+                crossbeam_channel::TrySendError::Full(_) => {
+                    sigverify_pool_full()
+                }
+                crossbeam_channel::TrySendError::Disconnected(_) => {
+                    sigverify_pool_stopped()
+                }
+            })?;
+        let (transaction, verified) =
+            response.await.map_err(|_| sigverify_pool_stopped())?;
+        verify_session_key_signatures(bank, &transaction, &verified)?;
+        Ok(transaction)
+    }
+}
+
+/// Runs until all [SigverifyPool] handles were dropped.
+fn run_batches(
+    receiver: crossbeam_channel::Receiver<SigverifyRequest>,
+    pool: ThreadPool,
+    max_batch_size: usize,
+) {
+    let threads = pool.current_num_threads().max(1);
+    while let Ok(request) = receiver.recv() {
+        let mut batch = vec![request];
+        batch.extend(receiver.try_iter().take(max_batch_size - 1));
+        trace!("Verifying signatures of {} transactions", batch.len());
+        let chunk_size = batch.len().div_ceil(threads);
+        let mut requests = batch.into_iter();
+        let chunks = std::iter::from_fn(|| {
+            let chunk = requests.by_ref().take(chunk_size).collect::<Vec<_>>();
+            (!chunk.is_empty()).then_some(chunk)
+        })
+        .collect::<Vec<_>>();
+        pool.install(|| {
+            chunks.into_par_iter().for_each(|chunk| {
+                let verified = metrics::observe_sigverify_time(|| {
+                    verify_signatures_batched(
+                        &chunk
+                            .iter()
+                            .map(|request| &request.transaction)
+                            .collect::<Vec<_>>(),
+                    )
+                });
+                for (request, verified) in chunk.into_iter().zip(verified) {
+                    // The requester is gone if the RPC request was dropped
+                    let _ = request
+                        .respond_to
+                        .send((request.transaction, verified));
+                }
+            })
+        });
+    }
+}
+
+/// Returns for each signature of the [transactions] if it was produced by
+/// the signer it belongs to, like [verify_signatures] does for each
+/// transaction.
+/// All signatures are verified at once via batched ed25519 verification.
+/// That only tells if all of them are valid, thus the signatures are
+/// verified one by one if any of them is not.
+pub(crate) fn verify_signatures_batched(
+    transactions: &[&SanitizedTransaction],
+) -> Vec<Vec<bool>> {
+    let verify_one_by_one = || {
+        transactions
+            .iter()
+            .map(|transaction| verify_signatures(transaction))
+            .collect()
+    };
+
+    let message_datas = transactions
+        .iter()
+        .map(|transaction| transaction.message_data())
+        .collect::<Vec<_>>();
+    let mut messages = vec![];
+    let mut signatures = vec![];
+    let mut public_keys = vec![];
+    let mut signature_counts = vec![];
+    for (transaction, message_data) in transactions.iter().zip(&message_datas) {
+        let signed_by = transaction
+            .signatures()
+            .iter()
+            .zip(transaction.message().account_keys().iter())
+            .collect::<Vec<_>>();
+        for (signature, signer) in &signed_by {
+            let Some((signature, public_key)) =
+                batchable_signature(signature, signer)
+            else {
+                return verify_one_by_one();
+            };
+            messages.push(message_data.as_slice());
+            signatures.push(signature);
+            public_keys.push(public_key);
+        }
+        signature_counts.push(signed_by.len());
+    }
+    if !signatures.is_empty()
+        && ed25519_dalek::verify_batch(&messages, &signatures, &public_keys)
+            .is_err()
+    {
+        return verify_one_by_one();
+    }
+    signature_counts
+        .into_iter()
+        .map(|count| vec![true; count])
+        .collect()
+}
+
+/// Parses the [signature] and [signer] unless the signature can only be
+/// verified individually.
+/// Batched verification accepts points that are of small order or not
+/// canonically encoded which the strict verification of single signatures
+/// rejects, thus such signatures are never verified in a batch.
+fn batchable_signature(
+    signature: &Signature,
+    signer: &Pubkey,
+) -> Option<(ed25519_dalek::Signature, ed25519_dalek::PublicKey)> {
+    let signature_bytes: &[u8] = signature.as_ref();
+    let (r, _) = signature_bytes.split_at(32);
+    if !is_strict_point(r) || !is_strict_point(signer.as_ref()) {
+        return None;
+    }
+    let public_key =
+        ed25519_dalek::PublicKey::from_bytes(signer.as_ref()).ok()?;
+    let signature = ed25519_dalek::Signature::try_from(signature_bytes).ok()?;
+    Some((signature, public_key))
+}
+
+fn is_strict_point(bytes: &[u8]) -> bool {
+    let compressed = CompressedEdwardsY::from_slice(bytes);
+    compressed.decompress().is_some_and(|point| {
+        !point.is_small_order() && point.compress() == compressed
+    })
+}
+
+fn sigverify_pool_full() -> Error {
+    error_with_magic_code(
+        ErrorCode::InvalidRequest,
+        "Validator is overloaded verifying signatures, retry later".to_string(),
+        MagicErrorCode::RpcTransactionRejected,
+    )
+}
+
+fn sigverify_pool_stopped() -> Error {
+    Error::internal_error()
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::{
+        hash::Hash,
+        signature::{Keypair, Signer},
+        system_transaction,
+        transaction::Transaction,
+    };
+    use test_tools::bank::bank_with_fees_for_tests;
+
+    use super::*;
+
+    fn transfer() -> Transaction {
+        system_transaction::transfer(
+            &Keypair::new(),
+            &Pubkey::new_unique(),
+            1,
+            Hash::new_unique(),
+        )
+    }
+
+    /// Signed by the right signer, but for a different message.
+    fn forged_transfer() -> Transaction {
+        let payer = Keypair::new();
+        let mut transaction = system_transaction::transfer(
+            &payer,
+            &Pubkey::new_unique(),
+            1,
+            Hash::new_unique(),
+        );
+        transaction.signatures[0] = payer.sign_message(b"another message");
+        transaction
+    }
+
+    fn sanitized(transaction: Transaction) -> SanitizedTransaction {
+        SanitizedTransaction::from_transaction_for_tests(transaction)
+    }
+
+    #[test]
+    fn test_verify_signatures_batched_of_valid_transactions() {
+        let transactions =
+            (0..3).map(|_| sanitized(transfer())).collect::<Vec<_>>();
+
+        let verified =
+            verify_signatures_batched(&transactions.iter().collect::<Vec<_>>());
+
+        assert_eq!(verified, vec![vec![true]; 3]);
+    }
+
+    #[test]
+    fn test_verify_signatures_batched_finds_invalid_signature() {
+        let transactions = vec![
+            sanitized(transfer()),
+            sanitized(forged_transfer()),
+            sanitized(transfer()),
+        ];
+
+        let verified =
+            verify_signatures_batched(&transactions.iter().collect::<Vec<_>>());
+
+        assert_eq!(verified, vec![vec![true], vec![false], vec![true]]);
+    }
+
+    #[test]
+    fn test_small_order_points_are_not_batched() {
+        // The identity point is of small order
+        let mut identity = [0u8; 32];
+        identity[0] = 1;
+        assert!(!is_strict_point(&identity));
+        assert!(is_strict_point(Keypair::new().pubkey().as_ref()));
+    }
+
+    #[tokio::test]
+    async fn test_pool_verifies_transactions() {
+        let bank = bank_with_fees_for_tests();
+        let pool = SigverifyPool::new(&SigverifyPoolConfig::default());
+
+        let transaction = sanitized(transfer());
+        let signature = *transaction.signature();
+        let verified = pool.verify(&bank, transaction).await.unwrap();
+        assert_eq!(verified.signature(), &signature);
+
+        assert!(pool
+            .verify(&bank, sanitized(forged_transfer()))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_pool_rejects_transactions_while_queue_is_full() {
+        let bank = bank_with_fees_for_tests();
+        // Nothing takes the queued transactions off the queue
+        let (sender, _receiver) = crossbeam_channel::bounded(1);
+        let (respond_to, _) = oneshot::channel();
+        sender
+            .send(SigverifyRequest {
+                transaction: sanitized(transfer()),
+                respond_to,
+            })
+            .unwrap();
+        let pool = SigverifyPool { sender };
+
+        let err = pool.verify(&bank, sanitized(transfer())).await.unwrap_err();
+        assert_eq!(err.code, ErrorCode::InvalidRequest);
+    }
+}
@@ -0,0 +1,271 @@
+use std::{
+    net::{SocketAddr, UdpSocket},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, RwLock,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use crossbeam_channel::{bounded, Receiver, RecvTimeoutError};
+use jsonrpc_core::Result;
+use log::*;
+use magicblock_streamer::quic::{spawn_server, SpawnServerResult, StakedNodes};
+use solana_perf::packet::PacketBatch;
+use solana_sdk::{signature::Keypair, transaction::VersionedTransaction};
+use tokio::{runtime::Handle, sync::Semaphore};
+
+use crate::{
+    json_rpc_request_processor::JsonRpcRequestProcessor,
+    transaction::{
+        admit_transaction, sanitize_transaction, send_transaction,
+        SendTransactionConfig,
+    },
+};
+
+/// Connections a single client may keep open at once
+const MAX_CONNECTIONS_PER_PEER: usize = 8;
+/// Connections accepted overall, all clients are unstaked since we
+/// don't have a notion of stake
+const MAX_UNSTAKED_CONNECTIONS: usize = 500;
+const WAIT_FOR_CHUNK_TIMEOUT: Duration = Duration::from_secs(2);
+/// How long received packets are coalesced into a single batch
+const COALESCE: Duration = Duration::from_millis(5);
+/// How often the receiver checks if it should exit
+const RECV_TIMEOUT: Duration = Duration::from_millis(500);
+/// Packet batches the QUIC server may queue before it has to wait for the
+/// receiver, which applies backpressure to the clients
+const MAX_QUEUED_PACKET_BATCHES: usize = 1_024;
+/// Transactions processed at once, packets received while that many are
+/// in flight are dropped like a Solana validator drops them when its TPU
+/// is overloaded
+const MAX_IN_FLIGHT_TRANSACTIONS: usize = 1_024;
+
+/// Accepts transactions via QUIC speaking the TPU protocol of Solana
+/// validators, such that clients and infrastructure that push transactions
+/// to the TPU of the leader can target the ephemeral validator directly.
+/// Like transactions sent to the TPU of a Solana validator they are not
+/// simulated before they are executed and their outcome is not reported
+/// back to the client.
+pub(crate) struct TpuService {
+    exit: Arc<AtomicBool>,
+    thread_handles: Vec<JoinHandle<()>>,
+}
+
+impl TpuService {
+    pub(crate) fn start(
+        tpu_addr: SocketAddr,
+        request_processor: JsonRpcRequestProcessor,
+        runtime: Handle,
+    ) -> std::result::Result<Self, String> {
+        let socket = UdpSocket::bind(tpu_addr).map_err(|err| {
+            format!("Failed to bind TPU socket at {}: {:?}", tpu_addr, err)
+        })?;
+        let exit = Arc::new(AtomicBool::new(false));
+        let (packet_sender, packet_receiver) =
+            bounded(MAX_QUEUED_PACKET_BATCHES);
+        // Clients don't verify the certificate of the server, thus it does
+        // not need to be derived from the validator identity
+        let SpawnServerResult {
+            thread: quic_thread,
+            ..
+        } = spawn_server(
+            "mbTpuQuic",
+            "magicblock_tpu_quic",
+            socket,
+            &Keypair::new(),
+            packet_sender,
+            exit.clone(),
+            MAX_CONNECTIONS_PER_PEER,
+            Arc::new(RwLock::new(StakedNodes::default())),
+            0,
+            MAX_UNSTAKED_CONNECTIONS,
+            WAIT_FOR_CHUNK_TIMEOUT,
+            COALESCE,
+        )
+        .map_err(|err| format!("Failed to start TPU: {:?}", err))?;
+
+        let receiver_thread = {
+            let exit = exit.clone();
+            thread::Builder::new()
+                .name("mbTpuRecv".to_string())
+                .spawn(move || {
+                    receive_packets(
+                        packet_receiver,
+                        request_processor,
+                        InFlightLimiter::new(
+                            runtime,
+                            MAX_IN_FLIGHT_TRANSACTIONS,
+                        ),
+                        exit,
+                    )
+                })
+                .map_err(|err| {
+                    format!("Failed to spawn TPU receiver: {:?}", err)
+                })?
+        };
+
+        info!("Accepting transactions via QUIC at {}", tpu_addr);
+        Ok(Self {
+            exit,
+            thread_handles: vec![quic_thread, receiver_thread],
+        })
+    }
+
+    pub(crate) fn close(&self) {
+        self.exit.store(true, Ordering::Relaxed);
+    }
+
+    pub(crate) fn join(self) -> thread::Result<()> {
+        for handle in self.thread_handles {
+            handle.join()?;
+        }
+        Ok(())
+    }
+}
+
+/// Spawns futures on the runtime unless [Self::max_in_flight] of them are
+/// still running, which bounds the memory and remote requests that clients
+/// can cause by flooding the TPU.
+struct InFlightLimiter {
+    runtime: Handle,
+    permits: Arc<Semaphore>,
+}
+
+impl InFlightLimiter {
+    fn new(runtime: Handle, max_in_flight: usize) -> Self {
+        Self {
+            runtime,
+            permits: Arc::new(Semaphore::new(max_in_flight)),
+        }
+    }
+
+    /// Spawns the [future] and returns `true` unless the limit is reached in
+    /// which case it is dropped without being polled.
+    fn try_spawn(
+        &self,
+        future: impl std::future::Future<Output = ()> + Send + 'static,
+    ) -> bool {
+        let Ok(permit) = self.permits.clone().try_acquire_owned() else {
+            return false;
+        };
+        self.runtime.spawn(async move {
+            future.await;
+            drop(permit);
+        });
+        true
+    }
+}
+
+fn receive_packets(
+    packet_receiver: Receiver<PacketBatch>,
+    request_processor: JsonRpcRequestProcessor,
+    in_flight: InFlightLimiter,
+    exit: Arc<AtomicBool>,
+) {
+    while !exit.load(Ordering::Relaxed) {
+        let batch = match packet_receiver.recv_timeout(RECV_TIMEOUT) {
+            Ok(batch) => batch,
+            Err(RecvTimeoutError::Timeout) => continue,
+            Err(RecvTimeoutError::Disconnected) => return,
+        };
+        for packet in batch.iter() {
+            if packet.meta().discard() {
+                continue;
+            }
+            let transaction =
+                match packet.deserialize_slice::<VersionedTransaction, _>(..) {
+                    Ok(transaction) => transaction,
+                    Err(err) => {
+                        debug!("Dropping invalid TPU packet: {:?}", err);
+                        continue;
+                    }
+                };
+            let wire_size = packet.meta().size;
+            let request_processor = request_processor.clone();
+            let spawned = in_flight.try_spawn(async move {
+                if let Err(err) = process_tpu_transaction(
+                    &request_processor,
+                    wire_size,
+                    transaction,
+                )
+                .await
+                {
+                    debug!("Dropping TPU transaction: {}", err.message);
+                }
+            });
+            if !spawned {
+                debug!("Dropping TPU packet since the TPU is overloaded");
+            }
+        }
+    }
+}
+
+async fn process_tpu_transaction(
+    meta: &JsonRpcRequestProcessor,
+    wire_size: usize,
+    transaction: VersionedTransaction,
+) -> Result<String> {
+    meta.check_accepts_transactions()?;
+    let transaction = sanitize_transaction(transaction, &*meta.get_bank())?;
+    let signature = *transaction.signature();
+    admit_transaction(meta, wire_size, &transaction).await?;
+    send_transaction(
+        meta,
+        None,
+        signature,
+        transaction,
+        SendTransactionConfig {
+            sigverify: !meta.config.disable_sigverify,
+            last_valid_block_height: 0,
+            durable_nonce_info: None,
+            max_retries: None,
+        },
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::sync::oneshot;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_in_flight_limiter_drops_futures_over_the_limit() {
+        let in_flight = InFlightLimiter::new(Handle::current(), 2);
+
+        let (first_tx, first_rx) = oneshot::channel::<()>();
+        let (second_tx, second_rx) = oneshot::channel::<()>();
+        assert!(in_flight.try_spawn(async move {
+            let _ = first_rx.await;
+        }));
+        assert!(in_flight.try_spawn(async move {
+            let _ = second_rx.await;
+        }));
+
+        // Both are still running, thus the next one is dropped
+        let (polled_tx, mut polled_rx) = oneshot::channel::<()>();
+        assert!(!in_flight.try_spawn(async move {
+            let _ = polled_tx.send(());
+        }));
+        assert!(polled_rx.try_recv().is_err());
+
+        // Once one of them completes another one is spawned
+        first_tx.send(()).unwrap();
+        tokio::time::timeout(Duration::from_secs(1), async {
+            while in_flight.permits.available_permits() == 0 {
+                tokio::task::yield_now().await;
+            }
+        })
+        .await
+        .unwrap();
+        let (spawned_tx, spawned_rx) = oneshot::channel::<()>();
+        assert!(in_flight.try_spawn(async move {
+            let _ = spawned_tx.send(());
+        }));
+        spawned_rx.await.unwrap();
+        drop(second_tx);
+    }
+}
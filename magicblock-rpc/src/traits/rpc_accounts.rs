@@ -1,5 +1,6 @@
 use jsonrpc_core::Result;
 use jsonrpc_derive::rpc;
+use magicblock_accounts_api::IdlDecodedAccount;
 use solana_account_decoder::UiAccount;
 use solana_rpc_client_api::{
     config::RpcAccountInfoConfig, response::Response as RpcResponse,
@@ -25,6 +26,15 @@ pub trait AccountsData {
         config: Option<RpcAccountInfoConfig>,
     ) -> Result<RpcResponse<Vec<Option<UiAccount>>>>;
 
+    /// Deserializes the account into JSON using the Anchor or Shank IDL of
+    /// the program owning it, which needs to have been cloned with it.
+    #[rpc(meta, name = "getParsedAccountInfo")]
+    fn get_parsed_account_info(
+        &self,
+        meta: Self::Metadata,
+        pubkey_str: String,
+    ) -> Result<RpcResponse<Option<IdlDecodedAccount>>>;
+
     /* TODO: need solana_runtime::BlockCommitmentArray
     #[rpc(meta, name = "getBlockCommitment")]
     fn get_block_commitment(
@@ -0,0 +1,182 @@
+use jsonrpc_core::{BoxFuture, Result};
+use jsonrpc_derive::rpc;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcClonedAccount {
+    pub pubkey: String,
+    pub kind: String,
+    pub clone_count: u64,
+    pub data_len: usize,
+    pub total_clone_time_ms: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcAccountCommitCost {
+    pub pubkey: String,
+    pub lamports: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcHydrateFailure {
+    pub pubkey: String,
+    pub owner: String,
+    pub reason: String,
+    pub may_be_delegated: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcHydrateReport {
+    pub cloned: usize,
+    pub skipped: usize,
+    pub failed: usize,
+    pub failures: Vec<RpcHydrateFailure>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcFailedCommit {
+    pub signature: String,
+    pub undelegated_accounts: Vec<String>,
+    pub committed_only_accounts: Vec<String>,
+    pub error: String,
+    pub logs: Vec<String>,
+    /// Unix timestamp in milliseconds
+    pub failed_at: u64,
+}
+
+/// Methods to administer the validator, only registered if enabled via
+/// the `admin` option of the RPC config.
+#[rpc]
+pub trait Admin {
+    type Metadata;
+
+    /// Requests a graceful shutdown of the validator which stops accepting
+    /// transactions, drains pending clones and commits and then exits.
+    #[rpc(meta, name = "requestShutdown")]
+    fn request_shutdown(&self, meta: Self::Metadata) -> Result<()>;
+
+    /// Replaces the log filter, it uses the `RUST_LOG` syntax,
+    /// i.e. `info,magicblock_accounts=trace`.
+    #[rpc(meta, name = "setLogFilter")]
+    fn set_log_filter(
+        &self,
+        meta: Self::Metadata,
+        filter: String,
+    ) -> Result<()>;
+
+    #[rpc(meta, name = "getLogFilter")]
+    fn get_log_filter(&self, meta: Self::Metadata) -> Result<String>;
+
+    /// Returns the accounts that were cloned most often, defaults to the
+    /// top 20 if no [limit] is provided.
+    #[rpc(meta, name = "getTopClonedAccounts")]
+    fn get_top_cloned_accounts(
+        &self,
+        meta: Self::Metadata,
+        limit: Option<usize>,
+    ) -> Result<Vec<RpcClonedAccount>>;
+
+    /// Returns the accounts the most lamports were spent on committing,
+    /// defaults to the top 20 if no [limit] is provided.
+    #[rpc(meta, name = "getTopCommitCostAccounts")]
+    fn get_top_commit_cost_accounts(
+        &self,
+        meta: Self::Metadata,
+        limit: Option<usize>,
+    ) -> Result<Vec<RpcAccountCommitCost>>;
+
+    /// Returns how many accounts were cloned, skipped or failed to clone
+    /// when the accounts of the ledger were hydrated at startup, including
+    /// the reason each failed for. Returns `None` if the validator did not
+    /// hydrate, i.e. since it started with a fresh ledger.
+    #[rpc(meta, name = "getHydrateReport")]
+    fn get_hydrate_report(
+        &self,
+        meta: Self::Metadata,
+    ) -> Result<Option<RpcHydrateReport>>;
+
+    /// Commits all delegated accounts that changed since their last commit
+    /// regardless of their commit frequency and returns the signatures of
+    /// the commit transactions.
+    #[rpc(meta, name = "commitAll")]
+    fn commit_all(
+        &self,
+        meta: Self::Metadata,
+    ) -> BoxFuture<Result<Vec<String>>>;
+
+    /// Returns the commits that were not sent since their simulation against
+    /// the remote cluster failed, oldest first. They are removed from the
+    /// queue if [drain] is `true` such that only new failures are returned
+    /// by the next request.
+    #[rpc(meta, name = "getFailedCommits")]
+    fn get_failed_commits(
+        &self,
+        meta: Self::Metadata,
+        drain: Option<bool>,
+    ) -> Result<Vec<RpcFailedCommit>>;
+
+    /// Returns the programs that may be cloned or `None` if all programs
+    /// are allowed.
+    #[rpc(meta, name = "getAllowedPrograms")]
+    fn get_allowed_programs(
+        &self,
+        meta: Self::Metadata,
+    ) -> Result<Option<Vec<String>>>;
+
+    /// Allows cloning the program without restarting the validator, the
+    /// change is persisted and applied on top of the configured
+    /// `allowed_programs` after a restart.
+    /// Adding a program while all programs are allowed restricts cloning to
+    /// that program. Returns `false` if the program was already allowed.
+    #[rpc(meta, name = "addAllowedProgram")]
+    fn add_allowed_program(
+        &self,
+        meta: Self::Metadata,
+        program_id: String,
+    ) -> Result<bool>;
+
+    /// Disallows cloning the program, copies that were cloned already stay
+    /// in the validator. The last allowed program cannot be removed since
+    /// that would allow all programs.
+    #[rpc(meta, name = "removeAllowedProgram")]
+    fn remove_allowed_program(
+        &self,
+        meta: Self::Metadata,
+        program_id: String,
+    ) -> Result<()>;
+
+    /// Writes all accounts of the bank, optionally limited to the ones owned
+    /// by the [owners], to an archive at [path] on the validator host and
+    /// returns the number of exported accounts.
+    #[rpc(meta, name = "exportState")]
+    fn export_state(
+        &self,
+        meta: Self::Metadata,
+        path: String,
+        owners: Option<Vec<String>>,
+    ) -> Result<usize>;
+
+    /// Loads the accounts of an archive written by `exportState` into the
+    /// bank, overwriting existing ones, and returns their number.
+    /// Meant to seed a fresh ephemeral, imported accounts are not committed
+    /// to the base chain.
+    #[rpc(meta, name = "importState")]
+    fn import_state(&self, meta: Self::Metadata, path: String)
+        -> Result<usize>;
+
+    /// Rotates the validator identity to the keypair read from the file at
+    /// [keypair_path] on the validator host without resetting the ledger
+    /// and returns the pubkey of the replaced identity.
+    /// Pending commits are completed with the replaced identity first.
+    #[rpc(meta, name = "rotateValidatorIdentity")]
+    fn rotate_validator_identity(
+        &self,
+        meta: Self::Metadata,
+        keypair_path: String,
+    ) -> BoxFuture<Result<String>>;
+}
@@ -1,7 +1,10 @@
 // NOTE: from rpc/src/rpc.rs :2741
 use jsonrpc_core::Result;
 use jsonrpc_derive::rpc;
-use solana_rpc_client_api::config::RpcContextConfig;
+use solana_rpc_client_api::{
+    config::RpcContextConfig,
+    response::{RpcInflationGovernor, RpcInflationRate},
+};
 use solana_sdk::{
     commitment_config::CommitmentConfig, epoch_schedule::EpochSchedule,
 };
@@ -18,7 +21,6 @@ pub trait BankData {
         commitment: Option<CommitmentConfig>,
     ) -> Result<u64>;
 
-    /*
     #[rpc(meta, name = "getInflationGovernor")]
     fn get_inflation_governor(
         &self,
@@ -31,7 +33,6 @@ pub trait BankData {
         &self,
         meta: Self::Metadata,
     ) -> Result<RpcInflationRate>;
-    */
 
     #[rpc(meta, name = "getEpochSchedule")]
     fn get_epoch_schedule(&self, meta: Self::Metadata)
@@ -1,7 +1,10 @@
 // NOTE: from rpc/src/rpc.rs :3278
 //! The `rpc` module implements the Solana RPC interface.
+use std::collections::HashMap;
+
 use jsonrpc_core::{BoxFuture, Result};
 use jsonrpc_derive::rpc;
+use serde::{Deserialize, Serialize};
 use solana_rpc_client_api::{
     config::{
         RpcBlockConfig, RpcBlocksConfigWrapper, RpcContextConfig,
@@ -26,6 +29,61 @@ use solana_transaction_status::{
     UiConfirmedBlock,
 };
 
+/// A write to a delegated account as recorded in its journal
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcAccountJournalEntry {
+    pub slot: Slot,
+    pub signature: String,
+    pub writer_program: String,
+    pub lamports: u64,
+    /// The byte range `[start, end)` of the account data that changed
+    pub data_range: (u32, u32),
+    pub data_digest: String,
+}
+
+/// A version of a program deployed in this validator
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcProgramVersion {
+    /// The first slot at which transactions execute this version
+    pub effective_slot: Slot,
+    pub bytecode_hash: String,
+}
+
+/// The slot of the base chain that was the latest known one during a slot of
+/// this validator
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcChainSlotMapping {
+    pub ephemeral_slot: Slot,
+    pub chain_slot: Slot,
+}
+
+/// The config of `simulateTransaction`, which extends the one of Solana by
+/// account state overrides
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcSimulateTransactionWithOverridesConfig {
+    #[serde(flatten)]
+    pub config: RpcSimulateTransactionConfig,
+    /// States of accounts by address which the simulated transaction sees
+    /// instead of their state in the bank
+    pub state_overrides: Option<HashMap<String, RpcAccountStateOverride>>,
+}
+
+/// Replaces the state of an account inside a simulation, fields that are not
+/// set keep the state the account has in the bank.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcAccountStateOverride {
+    pub lamports: Option<u64>,
+    /// The base64 encoded data of the account
+    pub data: Option<String>,
+    pub owner: Option<String>,
+    pub executable: Option<bool>,
+}
+
 #[rpc]
 pub trait Full {
     type Metadata;
@@ -79,7 +137,7 @@ pub trait Full {
         &self,
         meta: Self::Metadata,
         data: String,
-        config: Option<RpcSimulateTransactionConfig>,
+        config: Option<RpcSimulateTransactionWithOverridesConfig>,
     ) -> BoxFuture<Result<RpcResponse<RpcSimulateTransactionResult>>>;
 
     #[rpc(meta, name = "sendTransaction")]
@@ -90,6 +148,19 @@ pub trait Full {
         config: Option<RpcSendTransactionConfig>,
     ) -> BoxFuture<Result<String>>;
 
+    /// Sends an ordered bundle of transactions which are executed atomically
+    /// within the same slot, either all of them are committed or none.
+    /// Returns the signatures of the transactions in order.
+    /// Since the bundle is always executed before it is committed, the
+    /// preflight and retry options of the config are ignored.
+    #[rpc(meta, name = "sendBundle")]
+    fn send_bundle(
+        &self,
+        meta: Self::Metadata,
+        data: Vec<String>,
+        config: Option<RpcSendTransactionConfig>,
+    ) -> BoxFuture<Result<Vec<String>>>;
+
     #[rpc(meta, name = "minimumLedgerSlot")]
     fn minimum_ledger_slot(&self, meta: Self::Metadata) -> Result<Slot>;
 
@@ -184,4 +255,36 @@ pub trait Full {
         meta: Self::Metadata,
         pubkey_strs: Option<Vec<String>>,
     ) -> Result<Vec<RpcPrioritizationFee>>;
+
+    /// Returns the journal of writes to a delegated account, newest first,
+    /// which allows auditing how its state changed in this validator.
+    #[rpc(meta, name = "getAccountHistory")]
+    fn get_account_history(
+        &self,
+        meta: Self::Metadata,
+        pubkey_str: String,
+        limit: Option<usize>,
+    ) -> BoxFuture<Result<Vec<RpcAccountJournalEntry>>>;
+
+    /// Returns the version of the program that transactions executed at the
+    /// slot, defaulting to the current slot.
+    /// Upgrades of a program only take effect at the next slot, thus all
+    /// transactions of a slot execute the same version.
+    #[rpc(meta, name = "getProgramVersion")]
+    fn get_program_version(
+        &self,
+        meta: Self::Metadata,
+        program_id_str: String,
+        slot: Option<Slot>,
+    ) -> Result<Option<RpcProgramVersion>>;
+
+    /// Returns the slot of the base chain that corresponds to the provided
+    /// slot of this validator, defaulting to the current slot.
+    /// Returns `null` if the base chain slot was not known at that point.
+    #[rpc(meta, name = "getChainSlotMapping")]
+    fn get_chain_slot_mapping(
+        &self,
+        meta: Self::Metadata,
+        slot: Option<Slot>,
+    ) -> Result<Option<RpcChainSlotMapping>>;
 }
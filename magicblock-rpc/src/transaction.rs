@@ -4,12 +4,26 @@ use base64::{prelude::BASE64_STANDARD, Engine};
 use bincode::Options;
 use jsonrpc_core::{Error, ErrorCode, Result};
 use log::*;
-use magicblock_accounts::{errors::AccountsResult, AccountsManager};
+use magicblock_accounts::{
+    errors::{AccountsError, AccountsResult},
+    AccountsManager,
+};
 use magicblock_bank::bank::Bank;
+use magicblock_core::error_code::{HasErrorCode, MagicErrorCode};
 use magicblock_metrics::metrics;
-use magicblock_processor::execute_transaction::execute_sanitized_transaction;
+use magicblock_processor::execute_transaction::{
+    execute_sanitized_bundle, execute_sanitized_transaction, BundleError,
+};
+use magicblock_program::{
+    magicblock_instruction::MagicBlockInstruction, SessionKey,
+};
+use magicblock_telemetry::{
+    child_span, record_error, KeyValue, TraceContext, TraceFutureExt,
+};
 use solana_metrics::inc_new_counter_info;
-use solana_rpc_client_api::custom_error::RpcCustomError;
+use solana_rpc_client_api::custom_error::{
+    RpcCustomError, JSON_RPC_SERVER_ERROR_SEND_TRANSACTION_PREFLIGHT_FAILURE,
+};
 use solana_sdk::{
     feature_set,
     hash::Hash,
@@ -18,11 +32,18 @@ use solana_sdk::{
     pubkey::Pubkey,
     signature::Signature,
     system_transaction,
-    transaction::{MessageHash, SanitizedTransaction, VersionedTransaction},
+    transaction::{
+        MessageHash, SanitizedTransaction, TransactionError,
+        VersionedTransaction,
+    },
 };
 use solana_transaction_status::TransactionBinaryEncoding;
 
-use crate::json_rpc_request_processor::JsonRpcRequestProcessor;
+use crate::{
+    firewall::{fee_payer_escrow_lamports, FeePayerAdmission},
+    json_rpc_request_processor::JsonRpcRequestProcessor,
+    utils::error_with_magic_code,
+};
 
 const MAX_BASE58_SIZE: usize = 1683; // Golden, bump if PACKET_DATA_SIZE changes
 const MAX_BASE64_SIZE: usize = 1644; // Golden, bump if PACKET_DATA_SIZE changes
@@ -157,27 +178,60 @@ pub(crate) async fn send_transaction(
     signature: Signature,
     sanitized_transaction: SanitizedTransaction,
     config: SendTransactionConfig,
+) -> Result<String> {
+    check_not_shutting_down(meta)?;
+
+    // Root of the clone -> execute -> commit trace of this transaction
+    let trace_context = child_span(
+        "send_transaction",
+        &meta.trace_context,
+        vec![KeyValue::new("signature", signature.to_string())],
+    );
+    let result = process_send_transaction(
+        meta,
+        preflight_bank,
+        signature,
+        sanitized_transaction,
+        config,
+        &trace_context,
+    )
+    .await;
+    if let Err(err) = &result {
+        record_error(&trace_context, &err.message);
+    }
+    result
+}
+
+async fn process_send_transaction(
+    meta: &JsonRpcRequestProcessor,
+    preflight_bank: Option<&Bank>,
+    signature: Signature,
+    sanitized_transaction: SanitizedTransaction,
+    config: SendTransactionConfig,
+    trace_context: &TraceContext,
 ) -> Result<String> {
     let SendTransactionConfig { sigverify, .. } = config;
     let bank = &meta.get_bank();
 
-    if sigverify {
-        metrics::observe_sigverify_time(|| {
-            sig_verify_transaction(&sanitized_transaction)
-        })?;
-    }
+    let sanitized_transaction = if sigverify {
+        meta.sigverify_pool
+            .verify(bank, sanitized_transaction)
+            .await?
+    } else {
+        sanitized_transaction
+    };
+    // Unlike signatures, precompiles are always verified since the base chain
+    // rejects transactions with invalid precompile instructions as well
+    verify_precompiles(&sanitized_transaction, &bank.feature_set)?;
 
     // It is very important that we ensure accounts before simulating transactions
     // since they could depend on specific accounts to be in our validator
     {
         let timer = metrics::ensure_accounts_start();
         ensure_accounts(&meta.accounts_manager, &sanitized_transaction)
+            .with_context(child_span("ensure_accounts", trace_context, vec![]))
             .await
-            .map_err(|err| Error {
-                code: ErrorCode::InvalidRequest,
-                message: format!("{:?}", err),
-                data: None,
-            })?;
+            .map_err(accounts_error)?;
         metrics::ensure_accounts_end(timer);
     }
 
@@ -185,16 +239,30 @@ pub(crate) async fn send_transaction(
         meta.transaction_preflight(preflight_bank, &sanitized_transaction)?;
     }
 
+    // Attached while executing, so that the magic program can link commits
+    // scheduled by this transaction to the trace
+    let _guard = child_span("execute", trace_context, vec![]).attach();
     metrics::observe_transaction_execution_time(|| {
         execute_sanitized_transaction(
             sanitized_transaction,
             bank,
             meta.transaction_status_sender(),
         )
-        .map_err(|err| jsonrpc_core::Error {
-            code: jsonrpc_core::ErrorCode::InternalError,
-            message: err.to_string(),
-            data: None,
+        .map_err(|err| {
+            match err {
+                TransactionError::AlreadyProcessed => {
+                    metrics::inc_duplicate_transaction()
+                }
+                TransactionError::BlockhashNotFound => {
+                    metrics::inc_expired_blockhash_transaction()
+                }
+                _ => {}
+            }
+            jsonrpc_core::Error {
+                code: jsonrpc_core::ErrorCode::InternalError,
+                message: err.to_string(),
+                data: None,
+            }
         })
     })?;
 
@@ -204,40 +272,215 @@ pub(crate) async fn send_transaction(
     Ok(signature.to_string())
 }
 
-/// Verifies only the transaction signature and is used when sending a
-/// transaction to avoid the extra overhead of [sig_verify_transaction_and_check_precompiles]
-/// TODO(thlorenz): @@ sigverify takes upwards of 90µs which is 30%+ of
-/// the entire time it takes to execute a transaction.
-/// Therefore this an intermediate solution and we need to investigate verifying the
-/// wire_transaction instead (solana sigverify implementation is packet based)
+/// Executes the transactions atomically as a bundle once all of them were
+/// verified and their accounts ensured, see [execute_sanitized_bundle].
+pub(crate) async fn send_bundle(
+    meta: &JsonRpcRequestProcessor,
+    sanitized_transactions: Vec<SanitizedTransaction>,
+    sigverify: bool,
+) -> Result<Vec<String>> {
+    check_not_shutting_down(meta)?;
+    let bank = &meta.get_bank();
+
+    let mut verified_transactions =
+        Vec::with_capacity(sanitized_transactions.len());
+    for sanitized_transaction in sanitized_transactions {
+        let sanitized_transaction = if sigverify {
+            meta.sigverify_pool
+                .verify(bank, sanitized_transaction)
+                .await?
+        } else {
+            sanitized_transaction
+        };
+        verify_precompiles(&sanitized_transaction, &bank.feature_set)?;
+        ensure_accounts(&meta.accounts_manager, &sanitized_transaction)
+            .await
+            .map_err(accounts_error)?;
+        verified_transactions.push(sanitized_transaction);
+    }
+
+    let signatures = execute_sanitized_bundle(
+        verified_transactions,
+        bank,
+        meta.transaction_status_sender(),
+    )
+    .map_err(|err| match err {
+        // The bundle failed like a transaction failing its preflight
+        BundleError::Rejected(index, err) => error_with_magic_code(
+            ErrorCode::ServerError(
+                JSON_RPC_SERVER_ERROR_SEND_TRANSACTION_PREFLIGHT_FAILURE,
+            ),
+            format!(
+                "transaction {} of the bundle failed, none were committed: {}",
+                index, err
+            ),
+            MagicErrorCode::RpcBundleFailed,
+        ),
+        BundleError::PartiallyCommitted(index, err, committed) => {
+            error_with_magic_code(
+                ErrorCode::InternalError,
+                format!(
+                    "transaction {} of the bundle failed after the ones before it were committed [{}]: {}",
+                    index,
+                    committed
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    err
+                ),
+                MagicErrorCode::RpcBundleFailed,
+            )
+        }
+    })?;
+    Ok(signatures.iter().map(ToString::to_string).collect())
+}
+
+fn check_not_shutting_down(meta: &JsonRpcRequestProcessor) -> Result<()> {
+    if !meta.shutdown.is_accepting_transactions() {
+        return Err(error_with_magic_code(
+            ErrorCode::InvalidRequest,
+            "Validator is shutting down and no longer accepts transactions"
+                .to_string(),
+            MagicErrorCode::RpcShuttingDown,
+        ));
+    }
+    Ok(())
+}
+
+/// Verifies only the transaction signature inline.
+/// Sent transactions are verified via the [crate::sigverify::SigverifyPool]
+/// instead since sigverify takes upwards of 90µs which is 30%+ of the entire
+/// time it takes to execute a transaction.
 pub(crate) fn sig_verify_transaction(
+    bank: &Bank,
     transaction: &SanitizedTransaction,
 ) -> Result<()> {
     let now = match log::log_enabled!(log::Level::Trace) {
         true => Some(std::time::Instant::now()),
         false => None,
     };
-    #[allow(clippy::question_mark)]
-    if transaction.verify().is_err() {
-        return Err(
-            RpcCustomError::TransactionSignatureVerificationFailure.into()
-        );
-    }
+    let verified = verify_signatures(transaction);
     if let Some(now) = now {
         trace!("Sigverify took: {:?}", now.elapsed());
     }
-
-    Ok(())
+    verify_session_key_signatures(bank, transaction, &verified)
 }
 
 /// Verifies both transaction signature and precompiles which results in
 /// max overhead and thus should only be used when simulating transactions
 pub(crate) fn sig_verify_transaction_and_check_precompiles(
+    bank: &Bank,
     transaction: &SanitizedTransaction,
-    feature_set: &feature_set::FeatureSet,
 ) -> Result<()> {
-    sig_verify_transaction(transaction)?;
+    sig_verify_transaction(bank, transaction)?;
+    verify_precompiles(transaction, &bank.feature_set)
+}
+
+/// Returns for each signature of the transaction if it was produced by the
+/// signer it belongs to.
+pub(crate) fn verify_signatures(
+    transaction: &SanitizedTransaction,
+) -> Vec<bool> {
+    let message_data = transaction.message_data();
+    transaction
+        .signatures()
+        .iter()
+        .zip(transaction.message().account_keys().iter())
+        .map(|(signature, signer)| {
+            signature.verify(signer.as_ref(), &message_data)
+        })
+        .collect()
+}
 
+/// Accepts the signatures that were not produced by their signer if a
+/// session key registered for the signer produced them in its place.
+/// Transactions of expired session keys, invoking programs outside of the
+/// scope of the session key or managing session keys are rejected.
+pub(crate) fn verify_session_key_signatures(
+    bank: &Bank,
+    transaction: &SanitizedTransaction,
+    verified: &[bool],
+) -> Result<()> {
+    if verified.iter().all(|verified| *verified) {
+        return Ok(());
+    }
+    let message_data = transaction.message_data();
+    let unverified = transaction
+        .signatures()
+        .iter()
+        .zip(transaction.message().account_keys().iter())
+        .zip(verified)
+        .filter(|(_, verified)| !**verified)
+        .map(|(signed_by, _)| signed_by);
+    for (signature, signer) in unverified {
+        let Some(session_key) =
+            bank.session_key_signing_for(signer, signature, &message_data)
+        else {
+            return Err(
+                RpcCustomError::TransactionSignatureVerificationFailure.into(),
+            );
+        };
+        check_session_key_scope(bank, &session_key, transaction)?;
+    }
+    Ok(())
+}
+
+/// Ensures that the session key did not expire and that the transaction
+/// only invokes programs the session key is allowed to invoke.
+/// Session keys can never register or revoke session keys themselves since
+/// they could otherwise widen their own scope.
+fn check_session_key_scope(
+    bank: &Bank,
+    session_key: &SessionKey,
+    sanitized_transaction: &SanitizedTransaction,
+) -> Result<()> {
+    let session_key_error = |message: String| {
+        error_with_magic_code(
+            ErrorCode::InvalidRequest,
+            message,
+            MagicErrorCode::RpcTransactionRejected,
+        )
+    };
+    if !session_key.is_valid_at(bank.clock().unix_timestamp) {
+        return Err(session_key_error(format!(
+            "session key {} expired at {}",
+            session_key.session_key, session_key.valid_until
+        )));
+    }
+    for (program_id, instruction) in
+        sanitized_transaction.message().program_instructions_iter()
+    {
+        if !session_key.allows_program(program_id) {
+            return Err(session_key_error(format!(
+                "session key {} is not allowed to invoke program {}",
+                session_key.session_key, program_id
+            )));
+        }
+        if program_id == &magicblock_program::id()
+            && matches!(
+                bincode::deserialize::<MagicBlockInstruction>(
+                    &instruction.data
+                ),
+                Ok(MagicBlockInstruction::RegisterSessionKey { .. }
+                    | MagicBlockInstruction::RevokeSessionKey(_))
+            )
+        {
+            return Err(session_key_error(format!(
+                "session key {} is not allowed to manage session keys",
+                session_key.session_key
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Verifies the ed25519 and secp256k1 precompile instructions of the
+/// transaction, these are not verified when the instructions are executed
+pub(crate) fn verify_precompiles(
+    transaction: &SanitizedTransaction,
+    feature_set: &feature_set::FeatureSet,
+) -> Result<()> {
     if let Err(e) = transaction.verify_precompiles(feature_set) {
         return Err(RpcCustomError::TransactionPrecompileVerificationFailure(
             e,
@@ -248,6 +491,60 @@ pub(crate) fn sig_verify_transaction_and_check_precompiles(
     Ok(())
 }
 
+/// Applies the [crate::firewall::TransactionFirewall] rules before any of the
+/// accounts used by the transaction are cloned, so that rejected transactions
+/// cannot trigger expensive clones.
+/// Only the fee payer is cloned if needed to check its escrow.
+/// Fee payers that exhausted their spend limit in the bank are rejected early.
+pub(crate) async fn admit_transaction(
+    meta: &JsonRpcRequestProcessor,
+    wire_size: usize,
+    sanitized_transaction: &SanitizedTransaction,
+) -> Result<()> {
+    let firewall = &meta.firewall;
+    firewall.check_transaction(wire_size, sanitized_transaction)?;
+
+    let fee_payer = sanitized_transaction.message().fee_payer();
+    if meta.get_bank().is_fee_payer_spend_limit_exceeded(fee_payer) {
+        return Err(error_with_magic_code(
+            ErrorCode::InvalidRequest,
+            format!(
+                "fee payer {} exceeded its spend limit, retry later",
+                fee_payer
+            ),
+            MagicErrorCode::RpcTransactionRejected,
+        ));
+    }
+    let escrow_lamports =
+        || fee_payer_escrow_lamports(&meta.get_bank(), fee_payer);
+    if firewall.check_fee_payer(fee_payer, escrow_lamports())?
+        == FeePayerAdmission::EscrowUnknown
+    {
+        meta.accounts_manager
+            .ensure_fee_payer(
+                *fee_payer,
+                sanitized_transaction.signature().to_string(),
+            )
+            .await
+            .map_err(accounts_error)?;
+        firewall.check_fee_payer(
+            fee_payer,
+            Some(escrow_lamports().unwrap_or_default()),
+        )?;
+    }
+    Ok(())
+}
+
+/// Surfaces the failure to clone or validate the accounts of a transaction
+/// together with its [MagicErrorCode].
+fn accounts_error(err: AccountsError) -> Error {
+    error_with_magic_code(
+        ErrorCode::InvalidRequest,
+        format!("{:?}", err),
+        err.error_code(),
+    )
+}
+
 pub(crate) async fn ensure_accounts(
     accounts_manager: &AccountsManager,
     sanitized_transaction: &SanitizedTransaction,
@@ -1,10 +1,16 @@
-use jsonrpc_core::{Error, Result};
+use jsonrpc_core::{serde_json::json, Error, ErrorCode, Result};
 use magicblock_bank::bank::Bank;
+use magicblock_core::error_code::MagicErrorCode;
 use solana_rpc_client_api::{
+    config::RpcContextConfig,
+    custom_error::RpcCustomError,
     request::MAX_GET_CONFIRMED_SIGNATURES_FOR_ADDRESS2_LIMIT,
     response::{Response as RpcResponse, RpcResponseContext},
 };
-use solana_sdk::{pubkey::Pubkey, signature::Signature};
+use solana_sdk::{
+    clock::Slot, commitment_config::CommitmentConfig, pubkey::Pubkey,
+    signature::Signature,
+};
 
 pub const MAX_REQUEST_BODY_SIZE: usize = 50 * (1 << 10); // 50kB
 
@@ -20,6 +26,56 @@ pub(crate) fn verify_signature(input: &str) -> Result<Signature> {
         .map_err(|e| Error::invalid_params(format!("Invalid param: {e:?}")))
 }
 
+/// Includes the machine-readable [magic_code] as `errorCode` in the `data` of
+/// the error.
+pub(crate) fn error_with_magic_code(
+    code: ErrorCode,
+    message: String,
+    magic_code: MagicErrorCode,
+) -> Error {
+    Error {
+        code,
+        message,
+        data: Some(json!({ "errorCode": magic_code.as_str() })),
+    }
+}
+
+/// As a single node every slot is final as soon as it was processed, thus
+/// the latest bank satisfies the processed, confirmed and finalized
+/// commitment. Any other level is rejected instead of being ignored.
+pub(crate) fn verify_commitment(
+    commitment: Option<CommitmentConfig>,
+) -> Result<()> {
+    let commitment = commitment.unwrap_or_default();
+    if commitment.is_processed()
+        || commitment.is_confirmed()
+        || commitment.is_finalized()
+    {
+        Ok(())
+    } else {
+        Err(Error::invalid_params(format!(
+            "Unsupported commitment: {:?}",
+            commitment.commitment
+        )))
+    }
+}
+
+/// Verifies that a bank at the [context_slot] can serve a request with the
+/// provided [config].
+pub(crate) fn verify_context_config(
+    context_slot: Slot,
+    config: &RpcContextConfig,
+) -> Result<()> {
+    verify_commitment(config.commitment)?;
+    match config.min_context_slot {
+        Some(min_context_slot) if context_slot < min_context_slot => {
+            Err(RpcCustomError::MinContextSlotNotReached { context_slot }
+                .into())
+        }
+        _ => Ok(()),
+    }
+}
+
 pub(crate) fn new_response<T>(bank: &Bank, value: T) -> RpcResponse<T> {
     RpcResponse {
         context: RpcResponseContext::new(bank.slot()),
@@ -48,3 +104,67 @@ pub(crate) fn verify_and_parse_signatures_for_address_params(
     }
     Ok((address, before, until, limit))
 }
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::commitment_config::CommitmentLevel;
+
+    use super::*;
+
+    fn context_config(
+        commitment: Option<CommitmentLevel>,
+        min_context_slot: Option<Slot>,
+    ) -> RpcContextConfig {
+        RpcContextConfig {
+            commitment: commitment
+                .map(|commitment| CommitmentConfig { commitment }),
+            min_context_slot,
+        }
+    }
+
+    #[test]
+    fn test_every_commitment_level_is_served() {
+        for commitment in [
+            None,
+            Some(CommitmentLevel::Processed),
+            Some(CommitmentLevel::Confirmed),
+            Some(CommitmentLevel::Finalized),
+        ] {
+            assert!(verify_commitment(
+                commitment.map(|commitment| CommitmentConfig { commitment })
+            )
+            .is_ok());
+            assert!(verify_context_config(
+                10,
+                &context_config(commitment, None)
+            )
+            .is_ok());
+        }
+    }
+
+    #[test]
+    fn test_min_context_slot() {
+        for commitment in [
+            Some(CommitmentLevel::Processed),
+            Some(CommitmentLevel::Finalized),
+        ] {
+            assert!(verify_context_config(
+                10,
+                &context_config(commitment, Some(10))
+            )
+            .is_ok());
+
+            let err = verify_context_config(
+                10,
+                &context_config(commitment, Some(11)),
+            )
+            .unwrap_err();
+            assert_eq!(
+                err,
+                Error::from(RpcCustomError::MinContextSlotNotReached {
+                    context_slot: 10
+                })
+            );
+        }
+    }
+}
@@ -1,3 +1,7 @@
 pub mod streamer {
     pub use solana_streamer::streamer::*;
 }
+
+pub mod quic {
+    pub use solana_streamer::quic::*;
+}
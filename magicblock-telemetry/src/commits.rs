@@ -0,0 +1,50 @@
+use std::{collections::BTreeMap, sync::Mutex};
+
+use lazy_static::lazy_static;
+use magicblock_core::robust_lock::RobustMutex;
+use opentelemetry::{trace::TraceContextExt, Context};
+
+use crate::is_telemetry_enabled;
+
+/// Commits are scheduled while a transaction executes, but processed later
+/// by the slot ticker. Their trace context is kept here in the meantime.
+/// If the transaction scheduling a commit fails, the commit is never
+/// processed, thus we only keep the most recent ones.
+const MAX_PENDING_COMMIT_CONTEXTS: usize = 10_000;
+
+lazy_static! {
+    static ref COMMIT_TRACE_CONTEXTS: Mutex<BTreeMap<u64, Context>> =
+        Mutex::new(BTreeMap::new());
+}
+
+/// Links the commit with [commit_id] to the span of the [cx].
+/// Only the span context is stored so that the span itself ends as usual.
+pub fn register_commit_trace_context(commit_id: u64, cx: &Context) {
+    if !is_telemetry_enabled() {
+        return;
+    }
+    let span_context = cx.span().span_context().clone();
+    if !span_context.is_valid() {
+        return;
+    }
+    let mut contexts = COMMIT_TRACE_CONTEXTS.lock_robust();
+    if contexts.len() >= MAX_PENDING_COMMIT_CONTEXTS {
+        contexts.pop_first();
+    }
+    contexts.insert(
+        commit_id,
+        Context::new().with_remote_span_context(span_context),
+    );
+}
+
+/// Removes and returns the trace context registered for the commit, falling
+/// back to an empty context which starts a new trace.
+pub fn take_commit_trace_context(commit_id: u64) -> Context {
+    if !is_telemetry_enabled() {
+        return Context::new();
+    }
+    COMMIT_TRACE_CONTEXTS
+        .lock_robust()
+        .remove(&commit_id)
+        .unwrap_or_default()
+}
@@ -0,0 +1,9 @@
+use thiserror::Error;
+
+pub type TelemetryResult<T> = std::result::Result<T, TelemetryError>;
+
+#[derive(Error, Debug)]
+pub enum TelemetryError {
+    #[error("TraceError: {0}")]
+    TraceError(#[from] opentelemetry::trace::TraceError),
+}
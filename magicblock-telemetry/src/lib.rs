@@ -0,0 +1,17 @@
+//! Tracing spans exported via OTLP, covering the clone -> execute -> commit
+//! path of a transaction.
+//! The trace context flows with the RPC request into the cloner and via the
+//! commit id from the scheduling transaction to the commit on the base layer.
+mod commits;
+pub mod errors;
+mod spans;
+mod telemetry;
+
+pub use commits::{register_commit_trace_context, take_commit_trace_context};
+pub use opentelemetry::{
+    trace::FutureExt as TraceFutureExt, Context as TraceContext, KeyValue,
+};
+pub use spans::{
+    child_span, in_span, record_error, trace_context_from_headers, trace_id,
+};
+pub use telemetry::{init_telemetry, is_telemetry_enabled, shutdown_telemetry};
@@ -0,0 +1,69 @@
+use std::{collections::HashMap, fmt};
+
+use opentelemetry::{
+    global,
+    propagation::TextMapPropagator,
+    trace::{Status, TraceContextExt, Tracer},
+    Context, KeyValue,
+};
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+
+use crate::is_telemetry_enabled;
+
+const TRACER_NAME: &str = "magicblock";
+
+/// Starts a span as child of the [parent] and returns a context including it.
+/// The span ends once the last clone of that context is dropped.
+pub fn child_span(
+    name: &'static str,
+    parent: &Context,
+    attributes: Vec<KeyValue>,
+) -> Context {
+    if !is_telemetry_enabled() {
+        return parent.clone();
+    }
+    let tracer = global::tracer(TRACER_NAME);
+    let span = tracer
+        .span_builder(name)
+        .with_attributes(attributes)
+        .start_with_context(&tracer, parent);
+    parent.with_span(span)
+}
+
+/// Runs [f] inside a span that is a child of the current context.
+pub fn in_span<T>(
+    name: &'static str,
+    attributes: Vec<KeyValue>,
+    f: impl FnOnce() -> T,
+) -> T {
+    let _guard = child_span(name, &Context::current(), attributes).attach();
+    f()
+}
+
+/// Marks the span of the [cx] as failed.
+pub fn record_error(cx: &Context, err: &impl fmt::Display) {
+    if is_telemetry_enabled() {
+        cx.span().set_status(Status::error(err.to_string()));
+    }
+}
+
+/// Extracts the context of a W3C `traceparent` header, i.e. passed with an
+/// RPC request, so that our spans become part of the caller's trace.
+/// The header names are expected in lowercase.
+pub fn trace_context_from_headers(
+    headers: &HashMap<String, String>,
+) -> Context {
+    if !is_telemetry_enabled() {
+        return Context::new();
+    }
+    TraceContextPropagator::new().extract(headers)
+}
+
+/// The hex encoded trace id of the span in the [cx] if it has one.
+pub fn trace_id(cx: &Context) -> Option<String> {
+    let span = cx.span();
+    let span_context = span.span_context();
+    span_context
+        .is_valid()
+        .then(|| span_context.trace_id().to_string())
+}
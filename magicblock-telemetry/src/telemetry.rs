@@ -0,0 +1,45 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use log::*;
+use opentelemetry::{global, KeyValue};
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::{runtime, trace, Resource};
+
+use crate::errors::TelemetryResult;
+
+static TELEMETRY_ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Whether spans are exported, if not span helpers are noops.
+pub fn is_telemetry_enabled() -> bool {
+    TELEMETRY_ENABLED.load(Ordering::Relaxed)
+}
+
+/// Installs a global tracer provider exporting spans in batches to the OTLP
+/// collector at the [otlp_endpoint] via gRPC.
+/// Needs to be called from within a tokio runtime.
+pub fn init_telemetry(
+    otlp_endpoint: &str,
+    service_name: &str,
+) -> TelemetryResult<()> {
+    opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_exporter(
+            opentelemetry_otlp::new_exporter()
+                .tonic()
+                .with_endpoint(otlp_endpoint),
+        )
+        .with_trace_config(trace::config().with_resource(Resource::new(vec![
+            KeyValue::new("service.name", service_name.to_string()),
+        ])))
+        .install_batch(runtime::Tokio)?;
+    TELEMETRY_ENABLED.store(true, Ordering::Relaxed);
+    info!("Exporting traces to OTLP collector at '{}'", otlp_endpoint);
+    Ok(())
+}
+
+/// Flushes pending spans and uninstalls the tracer provider.
+pub fn shutdown_telemetry() {
+    if TELEMETRY_ENABLED.swap(false, Ordering::Relaxed) {
+        global::shutdown_tracer_provider();
+    }
+}
@@ -0,0 +1,84 @@
+#![no_main]
+
+use std::{collections::BTreeMap, sync::OnceLock};
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use magicblock_program::{
+    fuzzing::{register_data_mod, resolve_data_mod},
+    ValidatorContext,
+};
+
+/// Data mods exceeding this are spilled to disk, thus both ways of resolving
+/// them are exercised.
+const MEMORY_BUDGET: usize = 64 * 1024;
+
+#[derive(Debug, Arbitrary)]
+enum Operation {
+    Register(Vec<u8>),
+    /// Resolves one of the registered data mods which weren't resolved yet.
+    Resolve(usize),
+    /// Resolves a key which may or may not reference a registered data mod.
+    ResolveKey(u64),
+}
+
+fn context() -> &'static ValidatorContext {
+    static CONTEXT: OnceLock<ValidatorContext> = OnceLock::new();
+    CONTEXT.get_or_init(|| {
+        let context = ValidatorContext::default();
+        let spill_dir = std::env::temp_dir()
+            .join(format!("magicblock-fuzz-data-mods-{}", std::process::id()));
+        context
+            .data_mods()
+            .init_memory_budget(MEMORY_BUDGET, spill_dir)
+            .unwrap();
+        // Data which isn't in memory is only loaded from storage while the
+        // validator is replaying the ledger
+        context.ensure_started_up();
+        context
+    })
+}
+
+fn resolve_checked(key: u64, expected: Option<Vec<u8>>) {
+    match (resolve_data_mod(context(), key), expected) {
+        (Ok(Some(data)), Some(expected)) => assert_eq!(data, expected),
+        (Err(_), None) => {}
+        (result, expected) => panic!(
+            "Resolving {} returned {:?}, expected {:?}",
+            key, result, expected
+        ),
+    }
+}
+
+fuzz_target!(|operations: Vec<Operation>| {
+    let mut registered = BTreeMap::<u64, Vec<u8>>::new();
+    for operation in operations {
+        match operation {
+            Operation::Register(data) => {
+                let key = register_data_mod(context(), data.clone());
+                assert!(
+                    registered.insert(key, data).is_none(),
+                    "Key {} was handed out twice",
+                    key
+                );
+            }
+            Operation::Resolve(idx) if !registered.is_empty() => {
+                let key =
+                    *registered.keys().nth(idx % registered.len()).unwrap();
+                let expected = registered.remove(&key);
+                resolve_checked(key, expected);
+            }
+            Operation::Resolve(_) => {}
+            Operation::ResolveKey(key) => {
+                // Resolving a key that wasn't registered or was resolved
+                // already needs to fail
+                let expected = registered.remove(&key);
+                resolve_checked(key, expected);
+            }
+        }
+    }
+    // Don't leak data mods into the following runs
+    for (key, data) in registered {
+        resolve_checked(key, Some(data));
+    }
+});
@@ -0,0 +1,8 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use magicblock_program::fuzzing::deserialize_instruction;
+
+fuzz_target!(|data: &[u8]| {
+    deserialize_instruction(data);
+});
@@ -1,8 +1,17 @@
 // -----------------
 // Program CustomError Codes
 // -----------------
+// NOTE: the errors of the schedule commit security checks are defined in
+// magicblock_commit_errors::ScheduleCommitError since the programs invoking
+// us share them, their codes start at 11_000
 pub mod custom_error_codes {
     pub const FAILED_TO_TRANSFER_SCHEDULE_COMMIT_COST: u32 = 10_000;
     pub const UNABLE_TO_UNLOCK_SENT_COMMITS: u32 = 10_001;
     pub const CANNOT_FIND_SCHEDULED_COMMIT: u32 = 10_002;
+    pub const UNABLE_TO_UNLOCK_CONFIRMED_COMMITS: u32 = 10_003;
+    pub const CANNOT_FIND_CONFIRMED_COMMIT: u32 = 10_004;
+    pub const MAGIC_CONTEXT_CAPACITY_EXCEEDED: u32 = 10_005;
+    pub const MAGIC_CONTEXT_REGISTRY_FULL: u32 = 10_006;
+    pub const COMMIT_DELAY_TOO_LONG: u32 = 10_007;
+    pub const SCHEDULED_COMMITS_DISABLED: u32 = 10_008;
 }
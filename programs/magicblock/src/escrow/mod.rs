@@ -0,0 +1,22 @@
+mod process_transfer_escrowed_lamports;
+
+pub(crate) use process_transfer_escrowed_lamports::*;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::utils::DELEGATION_PROGRAM_ID;
+
+/// Seed the delegation program uses to derive the fee payer escrows of a payer.
+pub const FEE_PAYER_ESCROW_SEED: &[u8] = b"balance";
+
+/// The escrow index fee payers use unless they manage several escrows.
+pub const DEFAULT_FEE_PAYER_ESCROW_INDEX: u8 = 0;
+
+/// Derives the fee payer escrow at [index] that the delegation program
+/// manages for the [payer].
+pub fn fee_payer_escrow_pda(payer: &Pubkey, index: u8) -> Pubkey {
+    Pubkey::find_program_address(
+        &[FEE_PAYER_ESCROW_SEED, payer.as_ref(), &[index]],
+        &DELEGATION_PROGRAM_ID,
+    )
+    .0
+}
@@ -0,0 +1,471 @@
+use std::collections::HashSet;
+
+use solana_program_runtime::{ic_msg, invoke_context::InvokeContext};
+use solana_sdk::{
+    account::ReadableAccount, account_utils::StateMut,
+    instruction::InstructionError, pubkey::Pubkey, system_program,
+};
+
+use crate::{
+    escrow::fee_payer_escrow_pda,
+    magic_context::{EscrowSettlement, MagicContext},
+    schedule_transactions::check_magic_context_id,
+    utils::accounts::{
+        credit_instruction_account_at_index,
+        debit_instruction_account_at_index, get_instruction_account_with_idx,
+        get_instruction_pubkey_with_idx,
+    },
+    validator_context::ValidatorContext,
+};
+
+pub(crate) fn process_transfer_escrowed_lamports(
+    signers: HashSet<Pubkey>,
+    context: &ValidatorContext,
+    invoke_context: &mut InvokeContext,
+    amount: u64,
+    from_index: u8,
+    to_index: u8,
+) -> Result<(), InstructionError> {
+    const FROM_PAYER_IDX: u16 = 0;
+    const FROM_IDX: u16 = FROM_PAYER_IDX + 1;
+    const TO_PAYER_IDX: u16 = FROM_IDX + 1;
+    const TO_IDX: u16 = TO_PAYER_IDX + 1;
+    const MAGIC_CONTEXT_IDX: u16 = TO_IDX + 1;
+    const DELEGATED_ACCOUNT_IDX: u16 = MAGIC_CONTEXT_IDX + 1;
+
+    check_magic_context_id(invoke_context, MAGIC_CONTEXT_IDX)?;
+
+    let transaction_context = &invoke_context.transaction_context.clone();
+    let ix_ctx = transaction_context.get_current_instruction_context()?;
+    let ix_accs_len = ix_ctx.get_number_of_instruction_accounts();
+
+    // Assert enough accounts
+    if ix_accs_len <= DELEGATED_ACCOUNT_IDX {
+        ic_msg!(
+            invoke_context,
+            "TransferEscrowedLamports ERR: not enough accounts ({}), need both payers with their escrows, magic context and delegated account",
+            ix_accs_len
+        );
+        return Err(InstructionError::NotEnoughAccountKeys);
+    }
+
+    // Assert payer owning the source escrow is signer
+    let from_payer =
+        get_instruction_pubkey_with_idx(transaction_context, FROM_PAYER_IDX)?;
+    if !signers.contains(from_payer) {
+        ic_msg!(
+            invoke_context,
+            "TransferEscrowedLamports ERR: from payer {} not in signers",
+            from_payer
+        );
+        return Err(InstructionError::MissingRequiredSignature);
+    }
+
+    // Assert both accounts are the fee payer escrows derived for their payers
+    let from_pubkey =
+        get_instruction_pubkey_with_idx(transaction_context, FROM_IDX)?;
+    let to_payer =
+        get_instruction_pubkey_with_idx(transaction_context, TO_PAYER_IDX)?;
+    let to_pubkey =
+        get_instruction_pubkey_with_idx(transaction_context, TO_IDX)?;
+    for (pubkey, payer, index) in [
+        (from_pubkey, from_payer, from_index),
+        (to_pubkey, to_payer, to_index),
+    ] {
+        let expected = fee_payer_escrow_pda(payer, index);
+        if pubkey != &expected {
+            ic_msg!(
+                invoke_context,
+                "TransferEscrowedLamports ERR: account {} is not the fee payer escrow {} of {} at index {}",
+                pubkey,
+                expected,
+                payer,
+                index
+            );
+            return Err(InstructionError::InvalidSeeds);
+        }
+    }
+    if from_pubkey == to_pubkey {
+        ic_msg!(
+            invoke_context,
+            "TransferEscrowedLamports ERR: cannot transfer to the same escrow {}",
+            from_pubkey
+        );
+        return Err(InstructionError::InvalidArgument);
+    }
+
+    // Assert both escrows hold nothing but lamports
+    for (idx, pubkey) in [(FROM_IDX, from_pubkey), (TO_IDX, to_pubkey)] {
+        let acc = get_instruction_account_with_idx(transaction_context, idx)?;
+        let acc = acc.borrow();
+        if acc.owner() != &system_program::id() || !acc.data().is_empty() {
+            ic_msg!(
+                invoke_context,
+                "TransferEscrowedLamports ERR: account {} is not a fee payer escrow",
+                pubkey
+            );
+            return Err(InstructionError::InvalidAccountOwner);
+        }
+    }
+
+    //
+    // Get the program_id of the parent instruction that invoked this one via CPI
+    //
+
+    // We cannot easily simulate the transaction being invoked via CPI
+    // from the owning program during unit tests
+    // Instead the integration tests ensure that this works as expected
+    #[cfg(not(test))]
+    let frames = crate::utils::instruction_context_frames::InstructionContextFrames::try_from(transaction_context)?;
+    #[cfg(not(test))]
+    let parent_program_id = frames
+        .find_program_id_of_parent_of_current_instruction()
+        .ok_or_else(|| {
+            ic_msg!(
+                invoke_context,
+                "TransferEscrowedLamports ERR: failed to find parent program id"
+            );
+            InstructionError::InvalidInstructionData
+        })?;
+
+    // During unit tests we assume the delegated account has the correct program ID
+    #[cfg(test)]
+    let delegated_account_owner = {
+        *get_instruction_account_with_idx(
+            transaction_context,
+            DELEGATED_ACCOUNT_IDX,
+        )?
+        .borrow()
+        .owner()
+    };
+    #[cfg(test)]
+    let parent_program_id = &delegated_account_owner;
+
+    // Assert the delegated account authorizing the transfer is owned by the
+    // invoking program
+    let delegated_pubkey = get_instruction_pubkey_with_idx(
+        transaction_context,
+        DELEGATED_ACCOUNT_IDX,
+    )?;
+    let delegated_acc = get_instruction_account_with_idx(
+        transaction_context,
+        DELEGATED_ACCOUNT_IDX,
+    )?;
+    if parent_program_id != delegated_acc.borrow().owner() {
+        ic_msg!(
+            invoke_context,
+            "TransferEscrowedLamports ERR: account {} needs to be owned by the invoking program {}, but is owned by {}",
+            delegated_pubkey,
+            parent_program_id,
+            delegated_acc.borrow().owner()
+        );
+        return Err(InstructionError::InvalidAccountOwner);
+    }
+
+    // Assert the account is delegated to us, otherwise the program owning it
+    // has no authority over the escrows in the ephemeral.
+    // Delegations are only known once the accounts were cloned again after
+    // the ledger was processed, however the transfers found in the ledger
+    // were checked when they first ran.
+    if !context.is_starting_up()
+        && !context.is_account_delegated(delegated_pubkey)
+    {
+        ic_msg!(
+            invoke_context,
+            "TransferEscrowedLamports ERR: account {} is not delegated",
+            delegated_pubkey
+        );
+        return Err(InstructionError::InvalidAccountOwner);
+    }
+
+    // Move the lamports
+    debit_instruction_account_at_index(transaction_context, FROM_IDX, amount)
+        .inspect_err(|_| {
+            ic_msg!(
+                invoke_context,
+                "TransferEscrowedLamports ERR: escrow {} has insufficient funds to transfer {} lamports",
+                from_pubkey,
+                amount
+            );
+        })?;
+    credit_instruction_account_at_index(transaction_context, TO_IDX, amount)?;
+
+    // Record the settlement in the MagicContext, it is only accepted if the
+    // transaction including this instruction succeeds
+    let clock =
+        invoke_context
+            .get_sysvar_cache()
+            .get_clock()
+            .map_err(|err| {
+                ic_msg!(invoke_context, "Failed to get clock sysvar: {}", err);
+                InstructionError::UnsupportedSysvar
+            })?;
+    let settlement = EscrowSettlement {
+        slot: clock.slot,
+        from: *from_pubkey,
+        to: *to_pubkey,
+        lamports: amount,
+        authority_program: *parent_program_id,
+    };
+
+    let context_acc = get_instruction_account_with_idx(
+        transaction_context,
+        MAGIC_CONTEXT_IDX,
+    )?;
+    let context_data = &mut context_acc.borrow_mut();
+    let mut context =
+        MagicContext::deserialize(context_data).map_err(|err| {
+            ic_msg!(
+                invoke_context,
+                "Failed to deserialize MagicContext: {}",
+                err
+            );
+            InstructionError::GenericError
+        })?;
+    context.add_escrow_settlement(settlement);
+    context_data.set_state(&context)?;
+
+    ic_msg!(
+        invoke_context,
+        "TransferEscrowedLamports: transferred {} lamports from {} to {}",
+        amount,
+        from_pubkey,
+        to_pubkey
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::{account::AccountSharedData, clock};
+
+    use super::*;
+    use crate::{
+        magicblock_instruction::transfer_escrowed_lamports_instruction,
+        test_utils::{
+            empty_magic_context, prepare_accounts, process_instruction,
+            test_context, MAGIC_CONTEXT_ACC_IDX,
+        },
+    };
+
+    const FROM_ESCROW_ACC_IDX: usize = 4;
+    const TO_ESCROW_ACC_IDX: usize = 6;
+
+    fn prepare_transfer_accounts(
+        from_payer: Pubkey,
+        to_payer: Pubkey,
+        delegated: Pubkey,
+        program: Pubkey,
+    ) -> Vec<(Pubkey, AccountSharedData)> {
+        let clock = clock::Clock {
+            slot: 100,
+            ..Default::default()
+        };
+        let mut transaction_accounts = prepare_accounts(
+            clock,
+            from_payer,
+            empty_magic_context(),
+            &[(delegated, program)],
+        );
+        transaction_accounts.extend([
+            (
+                fee_payer_escrow_pda(&from_payer, 0),
+                AccountSharedData::new(1_000, 0, &system_program::id()),
+            ),
+            (
+                to_payer,
+                AccountSharedData::new(0, 0, &system_program::id()),
+            ),
+            (
+                fee_payer_escrow_pda(&to_payer, 0),
+                AccountSharedData::new(0, 0, &system_program::id()),
+            ),
+        ]);
+        test_context().set_account_delegated(&delegated, true);
+        transaction_accounts
+    }
+
+    fn unique_pubkeys() -> (Pubkey, Pubkey, Pubkey, Pubkey) {
+        (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        )
+    }
+
+    #[test]
+    fn test_transfer_escrowed_lamports_records_settlement() {
+        let (from_payer, to_payer, delegated, program) = unique_pubkeys();
+        let transaction_accounts =
+            prepare_transfer_accounts(from_payer, to_payer, delegated, program);
+        let ix = transfer_escrowed_lamports_instruction(
+            &from_payer,
+            0,
+            &to_payer,
+            0,
+            &delegated,
+            400,
+        );
+
+        let accounts = process_instruction(
+            ix.data.as_slice(),
+            transaction_accounts,
+            ix.accounts,
+            Ok(()),
+        );
+
+        assert_eq!(accounts[FROM_ESCROW_ACC_IDX].lamports(), 600);
+        assert_eq!(accounts[TO_ESCROW_ACC_IDX].lamports(), 400);
+
+        let magic_context = bincode::deserialize::<MagicContext>(
+            accounts[MAGIC_CONTEXT_ACC_IDX].data(),
+        )
+        .unwrap();
+        assert_eq!(
+            magic_context.escrow_settlements,
+            vec![EscrowSettlement {
+                slot: 100,
+                from: fee_payer_escrow_pda(&from_payer, 0),
+                to: fee_payer_escrow_pda(&to_payer, 0),
+                lamports: 400,
+                authority_program: program,
+            }]
+        );
+        assert!(MagicContext::has_pending_items(
+            accounts[MAGIC_CONTEXT_ACC_IDX].data()
+        ));
+        assert!(!MagicContext::has_scheduled_commits(
+            accounts[MAGIC_CONTEXT_ACC_IDX].data()
+        ));
+    }
+
+    #[test]
+    fn test_transfer_escrowed_lamports_insufficient_funds() {
+        let (from_payer, to_payer, delegated, program) = unique_pubkeys();
+        let transaction_accounts =
+            prepare_transfer_accounts(from_payer, to_payer, delegated, program);
+        let ix = transfer_escrowed_lamports_instruction(
+            &from_payer,
+            0,
+            &to_payer,
+            0,
+            &delegated,
+            1_001,
+        );
+
+        process_instruction(
+            ix.data.as_slice(),
+            transaction_accounts,
+            ix.accounts,
+            Err(InstructionError::InsufficientFunds),
+        );
+    }
+
+    #[test]
+    fn test_transfer_escrowed_lamports_to_non_escrow() {
+        let (from_payer, to_payer, delegated, program) = unique_pubkeys();
+        let mut transaction_accounts =
+            prepare_transfer_accounts(from_payer, to_payer, delegated, program);
+        transaction_accounts[TO_ESCROW_ACC_IDX].1 =
+            AccountSharedData::new(0, 8, &program);
+        let ix = transfer_escrowed_lamports_instruction(
+            &from_payer,
+            0,
+            &to_payer,
+            0,
+            &delegated,
+            1,
+        );
+
+        process_instruction(
+            ix.data.as_slice(),
+            transaction_accounts,
+            ix.accounts,
+            Err(InstructionError::InvalidAccountOwner),
+        );
+    }
+
+    #[test]
+    fn test_transfer_escrowed_lamports_to_account_not_derived_for_payer() {
+        let (from_payer, to_payer, delegated, program) = unique_pubkeys();
+        let mut transaction_accounts =
+            prepare_transfer_accounts(from_payer, to_payer, delegated, program);
+        // A system account that looks like an escrow, but isn't the PDA
+        // derived for the payer
+        let impostor = Pubkey::new_unique();
+        transaction_accounts.push((
+            impostor,
+            AccountSharedData::new(0, 0, &system_program::id()),
+        ));
+        let mut ix = transfer_escrowed_lamports_instruction(
+            &from_payer,
+            0,
+            &to_payer,
+            0,
+            &delegated,
+            1,
+        );
+        ix.accounts[3].pubkey = impostor;
+
+        process_instruction(
+            ix.data.as_slice(),
+            transaction_accounts,
+            ix.accounts,
+            Err(InstructionError::InvalidSeeds),
+        );
+    }
+
+    #[test]
+    fn test_transfer_escrowed_lamports_from_escrow_at_other_index() {
+        let (from_payer, to_payer, delegated, program) = unique_pubkeys();
+        let transaction_accounts =
+            prepare_transfer_accounts(from_payer, to_payer, delegated, program);
+        // The provided escrow was derived at index 0
+        let mut ix = transfer_escrowed_lamports_instruction(
+            &from_payer,
+            1,
+            &to_payer,
+            0,
+            &delegated,
+            1,
+        );
+        ix.accounts[1].pubkey = fee_payer_escrow_pda(&from_payer, 0);
+
+        process_instruction(
+            ix.data.as_slice(),
+            transaction_accounts,
+            ix.accounts,
+            Err(InstructionError::InvalidSeeds),
+        );
+    }
+
+    #[test]
+    fn test_transfer_escrowed_lamports_authorized_by_undelegated_account() {
+        let (from_payer, to_payer, undelegated, program) = unique_pubkeys();
+        let transaction_accounts = prepare_transfer_accounts(
+            from_payer,
+            to_payer,
+            undelegated,
+            program,
+        );
+        // Owned by the invoking program, but no longer delegated to us
+        test_context().set_account_delegated(&undelegated, false);
+        let ix = transfer_escrowed_lamports_instruction(
+            &from_payer,
+            0,
+            &to_payer,
+            0,
+            &undelegated,
+            1,
+        );
+
+        process_instruction(
+            ix.data.as_slice(),
+            transaction_accounts,
+            ix.accounts,
+            Err(InstructionError::InvalidAccountOwner),
+        );
+    }
+}
@@ -0,0 +1,58 @@
+//! Entry points for the fuzz targets in `programs/magicblock/fuzz` which need
+//! access to crate internals.
+
+use solana_program_runtime::with_mock_invoke_context;
+use solana_sdk::{
+    account::AccountSharedData, program_utils::limited_deserialize,
+    pubkey::Pubkey,
+};
+
+use crate::{
+    magicblock_instruction::{MagicBlockInstruction, MagicBlockProgramError},
+    mutate_accounts::resolve_account_mod_data,
+    validator_context::ValidatorContext,
+};
+
+/// Deserializes the instruction data exactly like the processor does and
+/// checks that any instruction it accepts survives a serialization roundtrip
+/// and is tagged with its discriminant.
+/// Returns `true` if the data is a valid instruction.
+pub fn deserialize_instruction(data: &[u8]) -> bool {
+    let Ok(instruction) = limited_deserialize::<MagicBlockInstruction>(data)
+    else {
+        return false;
+    };
+    assert_eq!(data[..4], instruction.discriminant());
+
+    let serialized = instruction
+        .try_to_vec()
+        .expect("Deserialized instruction should serialize");
+    let roundtripped =
+        limited_deserialize::<MagicBlockInstruction>(&serialized)
+            .expect("Serialized instruction should deserialize");
+    assert_eq!(instruction, roundtripped);
+    true
+}
+
+/// Registers data to be resolved by a [MagicBlockInstruction::ModifyAccounts]
+/// and returns the key that references it.
+pub fn register_data_mod(context: &ValidatorContext, data: Vec<u8>) -> u64 {
+    context.data_mods().insert(data)
+}
+
+/// Resolves the data of the key like processing a
+/// [MagicBlockInstruction::ModifyAccounts] does.
+/// Returns `None` if the data was not found in memory or storage.
+pub fn resolve_data_mod(
+    context: &ValidatorContext,
+    id: u64,
+) -> Result<Option<Vec<u8>>, MagicBlockProgramError> {
+    let transaction_accounts: Vec<(Pubkey, AccountSharedData)> = vec![];
+    with_mock_invoke_context!(
+        invoke_context,
+        transaction_context,
+        transaction_accounts
+    );
+    resolve_account_mod_data(id, context, &invoke_context)
+        .map(|resolved| resolved.data().map(<[u8]>::to_vec))
+}
@@ -1,18 +1,31 @@
 pub mod errors;
+mod escrow;
+pub use escrow::{
+    fee_payer_escrow_pda, DEFAULT_FEE_PAYER_ESCROW_INDEX, FEE_PAYER_ESCROW_SEED,
+};
+#[cfg(feature = "dev-context-only-utils")]
+pub mod fuzzing;
 mod magic_context;
 mod mutate_accounts;
 mod schedule_transactions;
-pub use magic_context::{MagicContext, ScheduledCommit};
+mod session_keys;
+pub use magic_context::{
+    CommitAuthority, EphemeralOnlyAccount, EscrowSettlement, MagicContext,
+    ScheduledCommit, SessionKey,
+};
 pub mod magicblock_instruction;
 pub mod magicblock_processor;
 #[cfg(test)]
 mod test_utils;
 mod utils;
 pub mod validator;
+mod validator_context;
 
 pub use magicblock_core::magic_program::*;
 pub use mutate_accounts::*;
 pub use schedule_transactions::{
-    process_scheduled_commit_sent, register_scheduled_commit_sent,
-    transaction_scheduler::TransactionScheduler, SentCommit,
+    commit_events, process_scheduled_commit_confirmed,
+    process_scheduled_commit_sent, transaction_scheduler::TransactionScheduler,
+    ConfirmedCommit, SentCommit,
 };
+pub use validator_context::{EnteredValidatorContext, ValidatorContext};
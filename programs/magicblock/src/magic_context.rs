@@ -1,15 +1,20 @@
 use std::mem;
 
+pub use magicblock_core::escrow::EscrowSettlement;
 use magicblock_core::magic_program;
 use serde::{Deserialize, Serialize};
+use solana_program_runtime::{ic_msg, invoke_context::InvokeContext};
 use solana_sdk::{
     account::{AccountSharedData, ReadableAccount},
-    clock::Slot,
+    clock::{Slot, UnixTimestamp},
     hash::Hash,
+    instruction::InstructionError,
     pubkey::Pubkey,
     transaction::Transaction,
 };
 
+use crate::errors::custom_error_codes;
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ScheduledCommit {
     pub id: u64,
@@ -20,16 +25,139 @@ pub struct ScheduledCommit {
     pub owner: Pubkey,
     pub commit_sent_transaction: Transaction,
     pub request_undelegation: bool,
+    /// If set the commit is not realized before this slot is reached
+    pub commit_at_slot: Option<Slot>,
+    /// Hashes of the account data at the time the commit was scheduled in the
+    /// same order as [ScheduledCommit::accounts].
+    /// Only present for conditional commits in which case accounts whose data
+    /// did not change since they were last committed are skipped.
+    pub data_hashes: Option<Vec<Hash>>,
+    /// Ephemeral-only accounts of [ScheduledCommit::accounts] which are
+    /// handed back empty when undelegated instead of being committed.
+    pub closed_accounts: Vec<EphemeralOnlyAccount>,
+}
+
+impl ScheduledCommit {
+    /// Returns `true` if the commit should be realized at the provided slot.
+    pub fn is_due(&self, current_slot: Slot) -> bool {
+        self.commit_at_slot
+            .map_or(true, |commit_at_slot| commit_at_slot <= current_slot)
+    }
+}
+
+/// Allows a program other than the owner of a delegated account to schedule
+/// commits for it, i.e. a coordinator program in a multi-program game.
+/// It is granted by the [Self::program] owning the account as recorded in its
+/// delegation record and only applies while the account is delegated to it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommitAuthority {
+    pub account: Pubkey,
+    pub authority: Pubkey,
+    /// The program that owned the account when granting the authority
+    pub program: Pubkey,
+}
+
+/// An account holding transient state, i.e. of a matchmaking lobby or a game
+/// session, which is never committed.
+/// Undelegating it hands it back to its owner program without any data, which
+/// can then close it on the base layer and send its rent to [Self::refund].
+/// It is declared by the program owning the account.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EphemeralOnlyAccount {
+    pub account: Pubkey,
+    pub refund: Pubkey,
+}
+
+/// A temporary keypair that is allowed to sign transactions on behalf of
+/// [Self::authority] until [Self::valid_until] without the primary key.
+/// Its signature takes the place of the authority's signature, thus the
+/// authority pays the fees and is the signer programs see, as long as the
+/// transaction only invokes allowed programs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionKey {
+    pub session_key: Pubkey,
+    pub authority: Pubkey,
+    /// Unix timestamp after which the session key expires
+    pub valid_until: UnixTimestamp,
+    /// Programs that transactions signed by the session key may invoke,
+    /// if empty any program may be invoked
+    pub allowed_programs: Vec<Pubkey>,
+}
+
+impl SessionKey {
+    pub fn is_valid_at(&self, unix_timestamp: UnixTimestamp) -> bool {
+        unix_timestamp <= self.valid_until
+    }
+
+    pub fn allows_program(&self, program_id: &Pubkey) -> bool {
+        self.allowed_programs.is_empty()
+            || self.allowed_programs.contains(program_id)
+    }
+}
+
+/// Returned when one of the registries kept in the [MagicContext] across
+/// slots reached its cap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct RegistryFull {
+    pub registry: &'static str,
+    pub max: usize,
+}
+
+impl RegistryFull {
+    /// Logs which cap was reached and returns the error the instruction
+    /// trying to exceed it fails with.
+    pub fn into_instruction_error(
+        self,
+        invoke_context: &InvokeContext,
+        ix_name: &str,
+    ) -> InstructionError {
+        ic_msg!(
+            invoke_context,
+            "{} ERR: MagicContext holds the max of {} {} already",
+            ix_name,
+            self.max,
+            self.registry
+        );
+        InstructionError::Custom(
+            custom_error_codes::MAGIC_CONTEXT_REGISTRY_FULL,
+        )
+    }
 }
 
 #[derive(Debug, Default, Serialize, Deserialize)]
 pub struct MagicContext {
     pub scheduled_commits: Vec<ScheduledCommit>,
+    pub escrow_settlements: Vec<EscrowSettlement>,
+    /// NOTE: these are kept in the context when scheduled commits and escrow
+    /// settlements are accepted
+    pub commit_authorities: Vec<CommitAuthority>,
+    /// NOTE: these are kept in the context as well and pruned once expired
+    pub session_keys: Vec<SessionKey>,
+    /// NOTE: these are kept in the context until the account is undelegated
+    pub ephemeral_only_accounts: Vec<EphemeralOnlyAccount>,
 }
 
 impl MagicContext {
     pub const SIZE: usize = magic_program::MAGIC_CONTEXT_SIZE;
     pub const ZERO: [u8; Self::SIZE] = [0; Self::SIZE];
+    /// The maximum number of scheduled commits accepted in one pass.
+    /// Accepting emits an event per commit and thus is chunked to stay within
+    /// the compute and log limits of a transaction when many commits were
+    /// scheduled in the same slot, the remaining commits are accepted in the
+    /// following slots.
+    pub const MAX_COMMITS_ACCEPTED_PER_PASS: usize = 100;
+    /// Unlike scheduled commits the commit authorities, session keys and
+    /// ephemeral-only accounts stay in the context across slots, thus they
+    /// are capped to leave enough room for scheduling commits.
+    /// Together they occupy less than 1MB of the context at their caps.
+    pub const MAX_COMMIT_AUTHORITIES: usize = 4_096;
+    /// Each program may only occupy a share of the commit authorities such
+    /// that a single program cannot exhaust them for all others.
+    pub const MAX_COMMIT_AUTHORITIES_PER_PROGRAM: usize = 256;
+    pub const MAX_SESSION_KEYS: usize = 1_024;
+    /// The max number of programs a single session key may be scoped to.
+    pub const MAX_SESSION_KEY_ALLOWED_PROGRAMS: usize = 8;
+    pub const MAX_EPHEMERAL_ONLY_ACCOUNTS: usize = 4_096;
     pub(crate) fn deserialize(
         data: &AccountSharedData,
     ) -> Result<Self, bincode::Error> {
@@ -44,17 +172,240 @@ impl MagicContext {
         self.scheduled_commits.push(commit);
     }
 
-    pub(crate) fn take_scheduled_commits(&mut self) -> Vec<ScheduledCommit> {
-        mem::take(&mut self.scheduled_commits)
+    /// Takes up to [max] of the oldest scheduled commits, the remaining ones
+    /// stay in the context to be taken in a later pass.
+    pub(crate) fn take_scheduled_commits(
+        &mut self,
+        max: usize,
+    ) -> Vec<ScheduledCommit> {
+        if self.scheduled_commits.len() <= max {
+            mem::take(&mut self.scheduled_commits)
+        } else {
+            self.scheduled_commits.drain(..max).collect()
+        }
+    }
+
+    /// Returns the number of bytes the context occupies in the account.
+    pub(crate) fn serialized_size(&self) -> Result<u64, bincode::Error> {
+        bincode::serialized_size(self)
+    }
+
+    pub(crate) fn add_escrow_settlement(
+        &mut self,
+        settlement: EscrowSettlement,
+    ) {
+        self.escrow_settlements.push(settlement);
+    }
+
+    pub(crate) fn take_escrow_settlements(&mut self) -> Vec<EscrowSettlement> {
+        mem::take(&mut self.escrow_settlements)
+    }
+
+    pub(crate) fn allow_commit_authority(
+        &mut self,
+        account: Pubkey,
+        program: Pubkey,
+        authority: Pubkey,
+    ) -> Result<(), RegistryFull> {
+        if self.is_commit_authority(&account, &program, &authority) {
+            return Ok(());
+        }
+        let granted_by_program = self
+            .commit_authorities
+            .iter()
+            .filter(|x| x.program.eq(&program))
+            .count();
+        if granted_by_program >= Self::MAX_COMMIT_AUTHORITIES_PER_PROGRAM {
+            return Err(RegistryFull {
+                registry: "commit authorities granted by the program",
+                max: Self::MAX_COMMIT_AUTHORITIES_PER_PROGRAM,
+            });
+        }
+        if self.commit_authorities.len() >= Self::MAX_COMMIT_AUTHORITIES {
+            return Err(RegistryFull {
+                registry: "commit authorities",
+                max: Self::MAX_COMMIT_AUTHORITIES,
+            });
+        }
+        self.commit_authorities.push(CommitAuthority {
+            account,
+            authority,
+            program,
+        });
+        Ok(())
+    }
+
+    pub(crate) fn revoke_commit_authority(
+        &mut self,
+        account: &Pubkey,
+        authority: &Pubkey,
+    ) {
+        self.commit_authorities
+            .retain(|x| !(x.account.eq(account) && x.authority.eq(authority)));
+    }
+
+    pub(crate) fn revoke_commit_authorities_of_account(
+        &mut self,
+        account: &Pubkey,
+    ) {
+        self.commit_authorities.retain(|x| !x.account.eq(account));
+    }
+
+    /// Returns `true` if the [authority] was allowed to commit the [account]
+    /// by the [program] currently owning it.
+    pub(crate) fn is_commit_authority(
+        &self,
+        account: &Pubkey,
+        program: &Pubkey,
+        authority: &Pubkey,
+    ) -> bool {
+        self.commit_authorities.iter().any(|x| {
+            x.account.eq(account)
+                && x.program.eq(program)
+                && x.authority.eq(authority)
+        })
+    }
+
+    /// Marks the account as ephemeral-only, if it was marked already the
+    /// refund address is replaced.
+    pub(crate) fn mark_ephemeral_only(
+        &mut self,
+        account: Pubkey,
+        refund: Pubkey,
+    ) -> Result<(), RegistryFull> {
+        match self
+            .ephemeral_only_accounts
+            .iter_mut()
+            .find(|x| x.account.eq(&account))
+        {
+            Some(existing) => existing.refund = refund,
+            None if self.ephemeral_only_accounts.len()
+                >= Self::MAX_EPHEMERAL_ONLY_ACCOUNTS =>
+            {
+                return Err(RegistryFull {
+                    registry: "ephemeral-only accounts",
+                    max: Self::MAX_EPHEMERAL_ONLY_ACCOUNTS,
+                });
+            }
+            None => self
+                .ephemeral_only_accounts
+                .push(EphemeralOnlyAccount { account, refund }),
+        }
+        Ok(())
+    }
+
+    pub(crate) fn remove_ephemeral_only(&mut self, account: &Pubkey) {
+        self.ephemeral_only_accounts
+            .retain(|x| !x.account.eq(account));
+    }
+
+    pub fn ephemeral_only_account(
+        &self,
+        account: &Pubkey,
+    ) -> Option<&EphemeralOnlyAccount> {
+        self.ephemeral_only_accounts
+            .iter()
+            .find(|x| x.account.eq(account))
+    }
+
+    /// Looks up the ephemeral-only accounts in the data of the MagicContext
+    /// account.
+    pub fn find_ephemeral_only_accounts(
+        magic_context_acc: &AccountSharedData,
+    ) -> Vec<EphemeralOnlyAccount> {
+        Self::deserialize(magic_context_acc)
+            .map(|context| context.ephemeral_only_accounts)
+            .unwrap_or_default()
+    }
+
+    /// Registers the session key unless it is registered by another
+    /// authority already, in which case `false` is returned.
+    /// Expired session keys are removed at the same time.
+    pub(crate) fn register_session_key(
+        &mut self,
+        session_key: SessionKey,
+        unix_timestamp: UnixTimestamp,
+    ) -> Result<bool, RegistryFull> {
+        self.session_keys.retain(|x| x.is_valid_at(unix_timestamp));
+        match self
+            .session_keys
+            .iter_mut()
+            .find(|x| x.session_key.eq(&session_key.session_key))
+        {
+            Some(existing) if existing.authority.ne(&session_key.authority) => {
+                Ok(false)
+            }
+            Some(existing) => {
+                *existing = session_key;
+                Ok(true)
+            }
+            None if self.session_keys.len() >= Self::MAX_SESSION_KEYS => {
+                Err(RegistryFull {
+                    registry: "session keys",
+                    max: Self::MAX_SESSION_KEYS,
+                })
+            }
+            None => {
+                self.session_keys.push(session_key);
+                Ok(true)
+            }
+        }
+    }
+
+    /// Removes the session key if it was registered by the authority and
+    /// returns `true` if it was found.
+    pub(crate) fn revoke_session_key(
+        &mut self,
+        session_key: &Pubkey,
+        authority: &Pubkey,
+    ) -> bool {
+        let len = self.session_keys.len();
+        self.session_keys.retain(|x| {
+            !(x.session_key.eq(session_key) && x.authority.eq(authority))
+        });
+        self.session_keys.len() != len
+    }
+
+    pub fn session_key(&self, session_key: &Pubkey) -> Option<&SessionKey> {
+        self.session_keys
+            .iter()
+            .find(|x| x.session_key.eq(session_key))
+    }
+
+    /// Looks up the session key in the data of the MagicContext account.
+    pub fn find_session_key(
+        magic_context_acc: &AccountSharedData,
+        session_key: &Pubkey,
+    ) -> Option<SessionKey> {
+        Self::deserialize(magic_context_acc)
+            .ok()?
+            .session_key(session_key)
+            .cloned()
+    }
+
+    /// Looks up all session keys in the data of the MagicContext account.
+    pub fn find_session_keys(
+        magic_context_acc: &AccountSharedData,
+    ) -> Vec<SessionKey> {
+        Self::deserialize(magic_context_acc)
+            .map(|context| context.session_keys)
+            .unwrap_or_default()
     }
 
     pub fn has_scheduled_commits(data: &[u8]) -> bool {
-        // Currently we only store a vec of scheduled commits in the MagicContext
-        // The first 8 bytes contain the length of the vec
+        // The first 8 bytes contain the length of the scheduled commits vec
         // This works even if the length is actually stored as a u32
         // since we zero out the entire context whenever we update the vec
         !is_zeroed(&data[0..8])
     }
+
+    /// Returns `true` if either scheduled commits or escrow settlements need
+    /// to be accepted.
+    pub fn has_pending_items(data: &[u8]) -> bool {
+        // If no commits are scheduled, the 8 bytes following the length of the
+        // scheduled commits vec contain the length of the escrow settlements vec
+        !is_zeroed(&data[0..16])
+    }
 }
 
 fn is_zeroed(buf: &[u8]) -> bool {
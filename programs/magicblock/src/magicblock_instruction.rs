@@ -5,6 +5,7 @@ use num_derive::{FromPrimitive, ToPrimitive};
 use serde::{Deserialize, Serialize};
 use solana_sdk::{
     account::Account,
+    clock::{Slot, UnixTimestamp},
     decode_error::DecodeError,
     hash::Hash,
     instruction::{AccountMeta, Instruction},
@@ -15,10 +16,7 @@ use solana_sdk::{
 };
 use thiserror::Error;
 
-use crate::{
-    mutate_accounts::set_account_mod_data,
-    validator::{validator_authority, validator_authority_id},
-};
+use crate::{fee_payer_escrow_pda, validator_context::ValidatorContext};
 
 #[derive(
     Error, Debug, Serialize, Clone, PartialEq, Eq, FromPrimitive, ToPrimitive,
@@ -56,6 +54,11 @@ pub enum MagicBlockProgramError {
 
     #[error("Encountered an error when persisting account modification data.")]
     FailedToPersistAccountModData,
+
+    #[error(
+        "The combined account data does not match the account modifications."
+    )]
+    InvalidCombinedAccountModData,
 }
 
 impl<T> DecodeError<T> for MagicBlockProgramError {
@@ -98,10 +101,27 @@ pub(crate) struct AccountModificationForInstruction {
     pub rent_epoch: Option<u64>,
 }
 
+/// Determines when a commit scheduled via
+/// [MagicBlockInstruction::ScheduleDelayedCommit] is realized on chain.
+/// Targets further out than the maximum delay the validator is configured
+/// with are rejected.
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub enum CommitSlotTarget {
+    /// Commit once the ephemeral reached the provided slot.
+    /// If that slot already passed the commit is realized right away.
+    Slot(Slot),
+    /// Commit once the provided number of slots passed since the commit
+    /// was scheduled.
+    Delay(u64),
+}
+
 #[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
 pub(crate) enum MagicBlockInstruction {
     /// Modify one or more accounts
     ///
+    /// The modifications of all accounts, including their data mods, are resolved
+    /// before any of them is applied. Thus either all accounts are modified or none.
+    ///
     /// # Account references
     ///  - **0.**    `[WRITE, SIGNER]` Validator Authority
     ///  - **1..n.** `[WRITE]` Accounts to modify
@@ -160,6 +180,156 @@ pub(crate) enum MagicBlockInstruction {
     /// We implement it this way so we can log the signature of this transaction
     /// as part of the [MagicBlockInstruction::ScheduleCommit] instruction.
     ScheduledCommitSent(u64),
+
+    /// This is the exact same instruction as [MagicBlockInstruction::ScheduleCommit]
+    /// (or [MagicBlockInstruction::ScheduleCommitAndUndelegate] if `request_undelegation`
+    /// is set) except that the commit is not realized before the slot determined by
+    /// the provided [CommitSlotTarget] is reached.
+    ///
+    /// This allows programs to schedule a commit to happen at a later point,
+    /// i.e. at the end of a game round.
+    ///
+    /// # Account references
+    /// - **0.**   `[WRITE, SIGNER]` Payer requesting the commit to be scheduled
+    /// - **1.**   `[WRITE]`         Magic Context Account containing to which we store
+    ///                              the scheduled commits
+    /// - **2..n** `[]`              Accounts to be committed
+    ScheduleDelayedCommit {
+        target: CommitSlotTarget,
+        request_undelegation: bool,
+    },
+
+    /// This is the exact same instruction as [MagicBlockInstruction::ScheduleCommit] except
+    /// that the data of each account is hashed when the commit is scheduled.
+    /// When the commit is realized, accounts whose data did not change since they were
+    /// last committed are skipped and no transaction is sent to chain for them.
+    ///
+    /// This saves fees for accounts that are committed frequently, but rarely change.
+    ///
+    /// # Account references
+    /// - **0.**   `[WRITE, SIGNER]` Payer requesting the commit to be scheduled
+    /// - **1.**   `[WRITE]`         Magic Context Account containing to which we store
+    ///                              the scheduled commits
+    /// - **2..n** `[]`              Accounts to be committed if they changed
+    ScheduleConditionalCommit,
+
+    /// Moves escrowed lamports from one fee payer escrow to another inside the
+    /// ephemeral and records an [crate::EscrowSettlement] in the MagicContext so
+    /// the transfer can be reconciled on the base layer later.
+    /// It should be invoked via CPI from the program owning the provided delegated
+    /// account, i.e. in order to charge fees in an in-game economy.
+    ///
+    /// Both escrows need to be the fee payer escrows the delegation program derives
+    /// for their payers at the provided indexes, see [crate::fee_payer_escrow_pda].
+    ///
+    /// # Account references
+    /// - **0.** `[SIGNER]` Payer owning the escrow to debit the lamports from
+    /// - **1.** `[WRITE]`  Fee payer escrow to debit the lamports from
+    /// - **2.** `[]`       Payer owning the escrow to credit the lamports to
+    /// - **3.** `[WRITE]`  Fee payer escrow to credit the lamports to
+    /// - **4.** `[WRITE]`  Magic Context Account to which we store the settlement
+    /// - **5.** `[]`       Account delegated to the validator and owned by the invoking program
+    TransferEscrowedLamports {
+        amount: u64,
+        /// Index of the escrow to debit among the escrows of its payer
+        from_index: u8,
+        /// Index of the escrow to credit among the escrows of its payer
+        to_index: u8,
+    },
+
+    /// Allows the provided program to schedule commits for the provided delegated
+    /// accounts even though it does not own them, i.e. a coordinator program
+    /// committing accounts of partner programs.
+    /// It has to be invoked via CPI from the program owning the accounts as recorded
+    /// in their delegation record.
+    /// Undelegation can still only be requested by the owning program.
+    /// The authority lapses once the account is owned by another program and
+    /// each program may only grant up to
+    /// [crate::MagicContext::MAX_COMMIT_AUTHORITIES_PER_PROGRAM] of them.
+    ///
+    /// # Account references
+    /// - **0.**   `[WRITE, SIGNER]` Payer requesting the authority to be allowed
+    /// - **1.**   `[WRITE]`         Magic Context Account to which we store the authority
+    /// - **2..n** `[]`              Delegated accounts the authority is allowed to commit
+    AllowCommitAuthority(Pubkey),
+
+    /// Revokes an authority previously allowed via
+    /// [MagicBlockInstruction::AllowCommitAuthority].
+    ///
+    /// # Account references
+    /// - **0.**   `[WRITE, SIGNER]` Payer requesting the authority to be revoked
+    /// - **1.**   `[WRITE]`         Magic Context Account from which we remove the authority
+    /// - **2..n** `[]`              Delegated accounts the authority is no longer allowed to commit
+    RevokeCommitAuthority(Pubkey),
+
+    /// Records the outcome of confirming the transactions that realized a
+    /// scheduled commit on chain.
+    ///
+    /// Same as for [MagicBlockInstruction::ScheduledCommitSent] we only pass
+    /// the ID of the scheduled commit and retrieve the outcome from a globally
+    /// stored hashmap.
+    ///
+    /// # Account references
+    /// - **0.**  `[]`       MagicBlock Program
+    /// - **1.**  `[SIGNER]` Validator Authority
+    ScheduledCommitConfirmed(u64),
+
+    /// Registers a session key which may sign transactions on behalf of the
+    /// authority until `valid_until` (unix timestamp) without the primary
+    /// key, optionally limited to invoking the `allowed_programs`.
+    /// The session key signs in place of the authority, see
+    /// [sign_with_session_key], and its signature is accepted when
+    /// transactions are admitted.
+    /// The session key has to sign as well so that no authority can register
+    /// the key of someone else and thereby restrict its transactions.
+    ///
+    /// # Account references
+    /// - **0.** `[WRITE, SIGNER]` Authority registering the session key
+    /// - **1.** `[WRITE]`         Magic Context Account to which we store the session key
+    /// - **2.** `[SIGNER]`        Session key being registered
+    RegisterSessionKey {
+        session_key: Pubkey,
+        valid_until: UnixTimestamp,
+        allowed_programs: Vec<Pubkey>,
+    },
+
+    /// Revokes a session key previously registered via
+    /// [MagicBlockInstruction::RegisterSessionKey] before it expires.
+    ///
+    /// # Account references
+    /// - **0.** `[WRITE, SIGNER]` Authority that registered the session key
+    /// - **1.** `[WRITE]`         Magic Context Account from which we remove the session key
+    RevokeSessionKey(Pubkey),
+
+    /// Marks the provided delegated accounts as ephemeral-only, i.e. accounts
+    /// holding transient state like matchmaking lobbies or game sessions.
+    /// They are never committed, scheduled commits skip them unless they
+    /// are undelegated.
+    /// Undelegating them hands them back to their owner program without
+    /// committing their data, such that it can close them on the base layer
+    /// and send their rent to the provided refund address.
+    /// It has to be invoked via CPI from the program owning the accounts.
+    ///
+    /// # Account references
+    /// - **0.**   `[WRITE, SIGNER]` Payer requesting the accounts to be marked
+    /// - **1.**   `[WRITE]`         Magic Context Account to which we store the marked accounts
+    /// - **2..n** `[]`              Delegated accounts to mark as ephemeral-only
+    MarkEphemeralOnly(Pubkey),
+
+    /// Modify several accounts atomically, i.e. when cloning a program
+    /// together with its accounts.
+    ///
+    /// Unlike [MagicBlockInstruction::ModifyAccounts] the data of all accounts
+    /// is registered as a single data mod which holds the data of each
+    /// modification, thus it is resolved at once.
+    ///
+    /// # Account references
+    ///  - **0.**    `[WRITE, SIGNER]` Validator Authority
+    ///  - **1..n.** `[WRITE]` Accounts to modify, in the order of `account_mods`
+    ModifyAccountsAtomic {
+        account_mods: Vec<AccountModificationForInstruction>,
+        data_key: Option<u64>,
+    },
 }
 
 #[allow(unused)]
@@ -172,6 +342,16 @@ impl MagicBlockInstruction {
             ScheduleCommitAndUndelegate => 2,
             AcceptScheduleCommits => 3,
             ScheduledCommitSent(_) => 4,
+            ScheduleDelayedCommit { .. } => 5,
+            ScheduleConditionalCommit => 6,
+            TransferEscrowedLamports { .. } => 7,
+            AllowCommitAuthority(_) => 8,
+            RevokeCommitAuthority(_) => 9,
+            ScheduledCommitConfirmed(_) => 10,
+            RegisterSessionKey { .. } => 11,
+            RevokeSessionKey(_) => 12,
+            MarkEphemeralOnly(_) => 13,
+            ModifyAccountsAtomic { .. } => 14,
         }
     }
 
@@ -189,18 +369,22 @@ impl MagicBlockInstruction {
 // ModifyAccounts
 // -----------------
 pub fn modify_accounts(
+    context: &ValidatorContext,
     account_modifications: Vec<AccountModification>,
     recent_blockhash: Hash,
 ) -> Transaction {
-    let ix = modify_accounts_instruction(account_modifications);
-    into_transaction(&validator_authority(), ix, recent_blockhash)
+    let ix = modify_accounts_instruction(context, account_modifications);
+    into_transaction(&context.validator_authority(), ix, recent_blockhash)
 }
 
+/// Registers the data of the modifications with the data mods of the
+/// [context] which resolves them once the instruction is processed.
 pub fn modify_accounts_instruction(
+    context: &ValidatorContext,
     account_modifications: Vec<AccountModification>,
 ) -> Instruction {
     let mut account_metas =
-        vec![AccountMeta::new(validator_authority_id(), true)];
+        vec![AccountMeta::new(context.validator_authority_id(), true)];
     let mut account_mods: HashMap<Pubkey, AccountModificationForInstruction> =
         HashMap::new();
     for account_modification in account_modifications {
@@ -210,7 +394,9 @@ pub fn modify_accounts_instruction(
             lamports: account_modification.lamports,
             owner: account_modification.owner,
             executable: account_modification.executable,
-            data_key: account_modification.data.map(set_account_mod_data),
+            data_key: account_modification
+                .data
+                .map(|data| context.data_mods().insert(data)),
             rent_epoch: account_modification.rent_epoch,
         };
         account_mods
@@ -223,6 +409,52 @@ pub fn modify_accounts_instruction(
     )
 }
 
+pub fn modify_accounts_atomic(
+    context: &ValidatorContext,
+    account_modifications: Vec<AccountModification>,
+    recent_blockhash: Hash,
+) -> Transaction {
+    let ix = modify_accounts_atomic_instruction(context, account_modifications);
+    into_transaction(&context.validator_authority(), ix, recent_blockhash)
+}
+
+/// Registers the data of all modifications as one combined data mod of the
+/// [context] which is resolved at once when the instruction is processed.
+pub fn modify_accounts_atomic_instruction(
+    context: &ValidatorContext,
+    account_modifications: Vec<AccountModification>,
+) -> Instruction {
+    let mut account_metas =
+        vec![AccountMeta::new(context.validator_authority_id(), true)];
+    let mut account_mods = Vec::with_capacity(account_modifications.len());
+    let mut datas = Vec::with_capacity(account_modifications.len());
+    for account_modification in account_modifications {
+        account_metas
+            .push(AccountMeta::new(account_modification.pubkey, false));
+        account_mods.push(AccountModificationForInstruction {
+            lamports: account_modification.lamports,
+            owner: account_modification.owner,
+            executable: account_modification.executable,
+            data_key: None,
+            rent_epoch: account_modification.rent_epoch,
+        });
+        datas.push(account_modification.data);
+    }
+    let data_key = datas.iter().any(Option::is_some).then(|| {
+        let combined_data = bincode::serialize(&datas)
+            .expect("serializing account data is infallible");
+        context.data_mods().insert(combined_data)
+    });
+    Instruction::new_with_bincode(
+        crate::id(),
+        &MagicBlockInstruction::ModifyAccountsAtomic {
+            account_mods,
+            data_key,
+        },
+        account_metas,
+    )
+}
+
 // -----------------
 // Schedule Commit
 // -----------------
@@ -284,17 +516,341 @@ pub(crate) fn schedule_commit_and_undelegate_instruction(
     )
 }
 
+// -----------------
+// Schedule Delayed Commit
+// -----------------
+pub fn schedule_delayed_commit(
+    payer: &Keypair,
+    pubkeys: Vec<Pubkey>,
+    target: CommitSlotTarget,
+    request_undelegation: bool,
+    recent_blockhash: Hash,
+) -> Transaction {
+    let ix = schedule_delayed_commit_instruction(
+        &payer.pubkey(),
+        pubkeys,
+        target,
+        request_undelegation,
+    );
+    into_transaction(payer, ix, recent_blockhash)
+}
+
+pub(crate) fn schedule_delayed_commit_instruction(
+    payer: &Pubkey,
+    pdas: Vec<Pubkey>,
+    target: CommitSlotTarget,
+    request_undelegation: bool,
+) -> Instruction {
+    let mut account_metas = vec![
+        AccountMeta::new(*payer, true),
+        AccountMeta::new(MAGIC_CONTEXT_PUBKEY, false),
+    ];
+    for pubkey in &pdas {
+        account_metas.push(AccountMeta::new_readonly(*pubkey, true));
+    }
+    Instruction::new_with_bincode(
+        crate::id(),
+        &MagicBlockInstruction::ScheduleDelayedCommit {
+            target,
+            request_undelegation,
+        },
+        account_metas,
+    )
+}
+
+// -----------------
+// Schedule Conditional Commit
+// -----------------
+pub fn schedule_conditional_commit(
+    payer: &Keypair,
+    pubkeys: Vec<Pubkey>,
+    recent_blockhash: Hash,
+) -> Transaction {
+    let ix = schedule_conditional_commit_instruction(&payer.pubkey(), pubkeys);
+    into_transaction(payer, ix, recent_blockhash)
+}
+
+pub(crate) fn schedule_conditional_commit_instruction(
+    payer: &Pubkey,
+    pdas: Vec<Pubkey>,
+) -> Instruction {
+    let mut account_metas = vec![
+        AccountMeta::new(*payer, true),
+        AccountMeta::new(MAGIC_CONTEXT_PUBKEY, false),
+    ];
+    for pubkey in &pdas {
+        account_metas.push(AccountMeta::new_readonly(*pubkey, true));
+    }
+    Instruction::new_with_bincode(
+        crate::id(),
+        &MagicBlockInstruction::ScheduleConditionalCommit,
+        account_metas,
+    )
+}
+
+// -----------------
+// Transfer Escrowed Lamports
+// -----------------
+pub fn transfer_escrowed_lamports(
+    from_payer: &Keypair,
+    from_index: u8,
+    to_payer: &Pubkey,
+    to_index: u8,
+    delegated_account: &Pubkey,
+    amount: u64,
+    recent_blockhash: Hash,
+) -> Transaction {
+    let ix = transfer_escrowed_lamports_instruction(
+        &from_payer.pubkey(),
+        from_index,
+        to_payer,
+        to_index,
+        delegated_account,
+        amount,
+    );
+    into_transaction(from_payer, ix, recent_blockhash)
+}
+
+pub(crate) fn transfer_escrowed_lamports_instruction(
+    from_payer: &Pubkey,
+    from_index: u8,
+    to_payer: &Pubkey,
+    to_index: u8,
+    delegated_account: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let account_metas = vec![
+        AccountMeta::new_readonly(*from_payer, true),
+        AccountMeta::new(fee_payer_escrow_pda(from_payer, from_index), false),
+        AccountMeta::new_readonly(*to_payer, false),
+        AccountMeta::new(fee_payer_escrow_pda(to_payer, to_index), false),
+        AccountMeta::new(MAGIC_CONTEXT_PUBKEY, false),
+        AccountMeta::new_readonly(*delegated_account, false),
+    ];
+    Instruction::new_with_bincode(
+        crate::id(),
+        &MagicBlockInstruction::TransferEscrowedLamports {
+            amount,
+            from_index,
+            to_index,
+        },
+        account_metas,
+    )
+}
+
+// -----------------
+// Allow/Revoke Commit Authority
+// -----------------
+pub fn allow_commit_authority(
+    payer: &Keypair,
+    pubkeys: Vec<Pubkey>,
+    authority: Pubkey,
+    recent_blockhash: Hash,
+) -> Transaction {
+    let ix =
+        allow_commit_authority_instruction(&payer.pubkey(), pubkeys, authority);
+    into_transaction(payer, ix, recent_blockhash)
+}
+
+pub(crate) fn allow_commit_authority_instruction(
+    payer: &Pubkey,
+    pdas: Vec<Pubkey>,
+    authority: Pubkey,
+) -> Instruction {
+    commit_authority_instruction(
+        payer,
+        pdas,
+        MagicBlockInstruction::AllowCommitAuthority(authority),
+    )
+}
+
+pub fn revoke_commit_authority(
+    payer: &Keypair,
+    pubkeys: Vec<Pubkey>,
+    authority: Pubkey,
+    recent_blockhash: Hash,
+) -> Transaction {
+    let ix = revoke_commit_authority_instruction(
+        &payer.pubkey(),
+        pubkeys,
+        authority,
+    );
+    into_transaction(payer, ix, recent_blockhash)
+}
+
+pub(crate) fn revoke_commit_authority_instruction(
+    payer: &Pubkey,
+    pdas: Vec<Pubkey>,
+    authority: Pubkey,
+) -> Instruction {
+    commit_authority_instruction(
+        payer,
+        pdas,
+        MagicBlockInstruction::RevokeCommitAuthority(authority),
+    )
+}
+
+fn commit_authority_instruction(
+    payer: &Pubkey,
+    pdas: Vec<Pubkey>,
+    instruction: MagicBlockInstruction,
+) -> Instruction {
+    let mut account_metas = vec![
+        AccountMeta::new(*payer, true),
+        AccountMeta::new(MAGIC_CONTEXT_PUBKEY, false),
+    ];
+    for pubkey in &pdas {
+        account_metas.push(AccountMeta::new_readonly(*pubkey, false));
+    }
+    Instruction::new_with_bincode(crate::id(), &instruction, account_metas)
+}
+
+// -----------------
+// Mark Ephemeral Only
+// -----------------
+pub fn mark_ephemeral_only(
+    payer: &Keypair,
+    pubkeys: Vec<Pubkey>,
+    refund: Pubkey,
+    recent_blockhash: Hash,
+) -> Transaction {
+    let ix = mark_ephemeral_only_instruction(&payer.pubkey(), pubkeys, refund);
+    into_transaction(payer, ix, recent_blockhash)
+}
+
+pub(crate) fn mark_ephemeral_only_instruction(
+    payer: &Pubkey,
+    pdas: Vec<Pubkey>,
+    refund: Pubkey,
+) -> Instruction {
+    let mut account_metas = vec![
+        AccountMeta::new(*payer, true),
+        AccountMeta::new(MAGIC_CONTEXT_PUBKEY, false),
+    ];
+    for pubkey in &pdas {
+        account_metas.push(AccountMeta::new_readonly(*pubkey, false));
+    }
+    Instruction::new_with_bincode(
+        crate::id(),
+        &MagicBlockInstruction::MarkEphemeralOnly(refund),
+        account_metas,
+    )
+}
+
+// -----------------
+// Register/Revoke Session Key
+// -----------------
+pub fn register_session_key(
+    authority: &Keypair,
+    session_key: &Keypair,
+    valid_until: UnixTimestamp,
+    allowed_programs: Vec<Pubkey>,
+    recent_blockhash: Hash,
+) -> Transaction {
+    let ix = register_session_key_instruction(
+        &authority.pubkey(),
+        session_key.pubkey(),
+        valid_until,
+        allowed_programs,
+    );
+    Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&authority.pubkey()),
+        &[authority, session_key],
+        recent_blockhash,
+    )
+}
+
+pub(crate) fn register_session_key_instruction(
+    authority: &Pubkey,
+    session_key: Pubkey,
+    valid_until: UnixTimestamp,
+    allowed_programs: Vec<Pubkey>,
+) -> Instruction {
+    Instruction::new_with_bincode(
+        crate::id(),
+        &MagicBlockInstruction::RegisterSessionKey {
+            session_key,
+            valid_until,
+            allowed_programs,
+        },
+        vec![
+            AccountMeta::new(*authority, true),
+            AccountMeta::new(MAGIC_CONTEXT_PUBKEY, false),
+            AccountMeta::new_readonly(session_key, true),
+        ],
+    )
+}
+
+/// Signs the [transaction] with the [session_key] in place of the
+/// [authority] which has to be one of its signers, i.e. the fee payer.
+/// The validator accepts the signature as long as the session key is
+/// registered for the authority, thus fees are charged to the authority and
+/// programs see it as the signer.
+/// Returns `false` if the authority is not a signer of the transaction.
+pub fn sign_with_session_key(
+    transaction: &mut Transaction,
+    authority: &Pubkey,
+    session_key: &Keypair,
+) -> bool {
+    let Some(position) = transaction
+        .message
+        .account_keys
+        .iter()
+        .take(transaction.message.header.num_required_signatures as usize)
+        .position(|key| key == authority)
+    else {
+        return false;
+    };
+    transaction.signatures.resize(
+        transaction.message.header.num_required_signatures as usize,
+        Default::default(),
+    );
+    transaction.signatures[position] =
+        session_key.sign_message(&transaction.message_data());
+    true
+}
+
+pub fn revoke_session_key(
+    authority: &Keypair,
+    session_key: Pubkey,
+    recent_blockhash: Hash,
+) -> Transaction {
+    let ix = revoke_session_key_instruction(&authority.pubkey(), session_key);
+    into_transaction(authority, ix, recent_blockhash)
+}
+
+pub(crate) fn revoke_session_key_instruction(
+    authority: &Pubkey,
+    session_key: Pubkey,
+) -> Instruction {
+    Instruction::new_with_bincode(
+        crate::id(),
+        &MagicBlockInstruction::RevokeSessionKey(session_key),
+        vec![
+            AccountMeta::new(*authority, true),
+            AccountMeta::new(MAGIC_CONTEXT_PUBKEY, false),
+        ],
+    )
+}
+
 // -----------------
 // Accept Scheduled Commits
 // -----------------
-pub fn accept_scheduled_commits(recent_blockhash: Hash) -> Transaction {
-    let ix = accept_scheduled_commits_instruction();
-    into_transaction(&validator_authority(), ix, recent_blockhash)
+pub fn accept_scheduled_commits(
+    context: &ValidatorContext,
+    recent_blockhash: Hash,
+) -> Transaction {
+    let ix =
+        accept_scheduled_commits_instruction(&context.validator_authority_id());
+    into_transaction(&context.validator_authority(), ix, recent_blockhash)
 }
 
-pub(crate) fn accept_scheduled_commits_instruction() -> Instruction {
+pub(crate) fn accept_scheduled_commits_instruction(
+    validator_authority: &Pubkey,
+) -> Instruction {
     let account_metas = vec![
-        AccountMeta::new_readonly(validator_authority_id(), true),
+        AccountMeta::new_readonly(*validator_authority, true),
         AccountMeta::new(MAGIC_CONTEXT_PUBKEY, false),
     ];
     Instruction::new_with_bincode(
@@ -308,15 +864,16 @@ pub(crate) fn accept_scheduled_commits_instruction() -> Instruction {
 // Scheduled Commit Sent
 // -----------------
 pub fn scheduled_commit_sent(
+    context: &ValidatorContext,
     scheduled_commit_id: u64,
     recent_blockhash: Hash,
 ) -> Transaction {
     let ix = scheduled_commit_sent_instruction(
         &crate::id(),
-        &validator_authority_id(),
+        &context.validator_authority_id(),
         scheduled_commit_id,
     );
-    into_transaction(&validator_authority(), ix, recent_blockhash)
+    into_transaction(&context.validator_authority(), ix, recent_blockhash)
 }
 
 pub(crate) fn scheduled_commit_sent_instruction(
@@ -335,6 +892,38 @@ pub(crate) fn scheduled_commit_sent_instruction(
     )
 }
 
+// -----------------
+// Scheduled Commit Confirmed
+// -----------------
+pub fn scheduled_commit_confirmed(
+    context: &ValidatorContext,
+    scheduled_commit_id: u64,
+    recent_blockhash: Hash,
+) -> Transaction {
+    let ix = scheduled_commit_confirmed_instruction(
+        &crate::id(),
+        &context.validator_authority_id(),
+        scheduled_commit_id,
+    );
+    into_transaction(&context.validator_authority(), ix, recent_blockhash)
+}
+
+pub(crate) fn scheduled_commit_confirmed_instruction(
+    magic_block_program: &Pubkey,
+    validator_authority: &Pubkey,
+    scheduled_commit_id: u64,
+) -> Instruction {
+    let account_metas = vec![
+        AccountMeta::new_readonly(*magic_block_program, false),
+        AccountMeta::new_readonly(*validator_authority, true),
+    ];
+    Instruction::new_with_bincode(
+        *magic_block_program,
+        &MagicBlockInstruction::ScheduledCommitConfirmed(scheduled_commit_id),
+        account_metas,
+    )
+}
+
 // -----------------
 // Utils
 // -----------------
@@ -1,14 +1,22 @@
-use solana_program_runtime::declare_process_instruction;
-use solana_sdk::program_utils::limited_deserialize;
+use solana_program_runtime::{declare_process_instruction, ic_msg};
+use solana_sdk::{
+    instruction::InstructionError, program_utils::limited_deserialize,
+};
 
 use crate::{
+    escrow::process_transfer_escrowed_lamports,
     magicblock_instruction::MagicBlockInstruction,
-    mutate_accounts::process_mutate_accounts,
-    process_scheduled_commit_sent,
+    mutate_accounts::{
+        process_mutate_accounts, process_mutate_accounts_atomic,
+    },
+    process_scheduled_commit_confirmed, process_scheduled_commit_sent,
     schedule_transactions::{
-        process_accept_scheduled_commits, process_schedule_commit,
+        process_accept_scheduled_commits, process_mark_ephemeral_only,
+        process_schedule_commit, process_set_commit_authority,
         ProcessScheduleCommitOptions,
     },
+    session_keys::{process_register_session_key, process_revoke_session_key},
+    validator_context::ValidatorContext,
 };
 
 pub const DEFAULT_COMPUTE_UNITS: u64 = 150;
@@ -23,11 +31,21 @@ declare_process_instruction!(
         let instruction_data = instruction_context.get_instruction_data();
         let instruction = limited_deserialize(instruction_data)?;
         let signers = instruction_context.get_signers(transaction_context)?;
+        // The bank executing this instruction entered the context of its
+        // validator for the invoke context, see [ValidatorContext::enter]
+        let Some(context) = ValidatorContext::entered() else {
+            ic_msg!(
+                invoke_context,
+                "MagicBlockProgram ERR: no validator context was entered"
+            );
+            return Err(InstructionError::GenericError);
+        };
 
         match instruction {
             MagicBlockInstruction::ModifyAccounts(mut account_mods) => {
                 process_mutate_accounts(
                     signers,
+                    &context,
                     invoke_context,
                     transaction_context,
                     &mut account_mods,
@@ -35,31 +53,132 @@ declare_process_instruction!(
             }
             MagicBlockInstruction::ScheduleCommit => process_schedule_commit(
                 signers,
+                &context,
                 invoke_context,
                 ProcessScheduleCommitOptions {
                     request_undelegation: false,
+                    ..Default::default()
                 },
             ),
             MagicBlockInstruction::ScheduleCommitAndUndelegate => {
                 process_schedule_commit(
                     signers,
+                    &context,
                     invoke_context,
                     ProcessScheduleCommitOptions {
                         request_undelegation: true,
+                        ..Default::default()
                     },
                 )
             }
             MagicBlockInstruction::AcceptScheduleCommits => {
-                process_accept_scheduled_commits(signers, invoke_context)
+                process_accept_scheduled_commits(
+                    signers,
+                    &context,
+                    invoke_context,
+                )
             }
             MagicBlockInstruction::ScheduledCommitSent(id) => {
                 process_scheduled_commit_sent(
                     signers,
+                    &context,
                     invoke_context,
                     transaction_context,
                     id,
                 )
             }
+            MagicBlockInstruction::ScheduleDelayedCommit {
+                target,
+                request_undelegation,
+            } => process_schedule_commit(
+                signers,
+                &context,
+                invoke_context,
+                ProcessScheduleCommitOptions {
+                    request_undelegation,
+                    commit_target: Some(target),
+                    max_commit_delay_slots: context.max_commit_delay_slots(),
+                    ..Default::default()
+                },
+            ),
+            MagicBlockInstruction::ScheduleConditionalCommit => {
+                process_schedule_commit(
+                    signers,
+                    &context,
+                    invoke_context,
+                    ProcessScheduleCommitOptions {
+                        commit_only_if_changed: true,
+                        ..Default::default()
+                    },
+                )
+            }
+            MagicBlockInstruction::TransferEscrowedLamports {
+                amount,
+                from_index,
+                to_index,
+            } => process_transfer_escrowed_lamports(
+                signers,
+                &context,
+                invoke_context,
+                amount,
+                from_index,
+                to_index,
+            ),
+            MagicBlockInstruction::ScheduledCommitConfirmed(id) => {
+                process_scheduled_commit_confirmed(
+                    signers,
+                    &context,
+                    invoke_context,
+                    transaction_context,
+                    id,
+                )
+            }
+            MagicBlockInstruction::AllowCommitAuthority(authority) => {
+                process_set_commit_authority(
+                    signers,
+                    &context,
+                    invoke_context,
+                    authority,
+                    true,
+                )
+            }
+            MagicBlockInstruction::RevokeCommitAuthority(authority) => {
+                process_set_commit_authority(
+                    signers,
+                    &context,
+                    invoke_context,
+                    authority,
+                    false,
+                )
+            }
+            MagicBlockInstruction::RegisterSessionKey {
+                session_key,
+                valid_until,
+                allowed_programs,
+            } => process_register_session_key(
+                signers,
+                invoke_context,
+                session_key,
+                valid_until,
+                allowed_programs,
+            ),
+            MagicBlockInstruction::RevokeSessionKey(session_key) => {
+                process_revoke_session_key(signers, invoke_context, session_key)
+            }
+            MagicBlockInstruction::MarkEphemeralOnly(refund) => {
+                process_mark_ephemeral_only(signers, invoke_context, refund)
+            }
+            MagicBlockInstruction::ModifyAccountsAtomic {
+                account_mods,
+                data_key,
+            } => process_mutate_accounts_atomic(
+                signers,
+                &context,
+                invoke_context,
+                transaction_context,
+                account_mods,
+                data_key,
+            ),
         }
     }
 );
@@ -1,122 +1,241 @@
 use std::{
     collections::HashMap,
+    fs,
     ops::Neg,
+    path::PathBuf,
     sync::{
-        atomic::{AtomicU64, Ordering},
+        atomic::{AtomicU64, AtomicUsize, Ordering},
         Arc, Mutex, RwLock,
     },
 };
 
-use lazy_static::lazy_static;
-use magicblock_core::traits::PersistsAccountModData;
+use log::{error, warn};
+use magicblock_core::{
+    robust_lock::{RobustMutex, RobustRwLock},
+    traits::PersistsAccountModData,
+};
 use solana_program_runtime::{ic_msg, invoke_context::InvokeContext};
 
-use crate::{magicblock_instruction::MagicBlockProgramError, validator};
+use crate::{
+    magicblock_instruction::MagicBlockProgramError,
+    validator_context::ValidatorContext,
+};
 
-lazy_static! {
+/// Data mods that are registered for [MagicBlockInstruction::ModifyAccounts]
+/// transactions and the persister that makes them available during ledger
+/// replay.
+///
+/// [MagicBlockInstruction::ModifyAccounts]: crate::magicblock_instruction::MagicBlockInstruction::ModifyAccounts
+#[derive(Default)]
+pub struct DataMods {
     /// In order to modify large data chunks we cannot include all the data as part of the
     /// transaction.
     /// Instead we register data here _before_ invoking the actual instruction and when it is
     /// processed it resolved that data from the key that we provide in its place.
-    static ref DATA_MODS: Mutex<HashMap<u64, Vec<u8>>> = Mutex::new(HashMap::new());
+    data: Mutex<HashMap<u64, Vec<u8>>>,
+
+    /// Total size of the data held in [Self::data].
+    size: AtomicUsize,
+
+    /// Data mods that did not fit into the [DataModsMemoryBudget] are written to disk
+    /// instead of being added to [Self::data].
+    /// This tracks the file and size of each of them until they are resolved.
+    spilled: Mutex<HashMap<u64, (PathBuf, usize)>>,
+
+    /// The memory budget for [Self::data], if not set all data mods are kept
+    /// in memory.
+    memory_budget: RwLock<Option<DataModsMemoryBudget>>,
 
     /// In order to support replaying transactions we need to persist the data that is
-    /// loaded from the [DATA_MODS]
-    /// During replay the [DATA_MODS] won't have the data for the particular id in which
+    /// loaded from [Self::data]
+    /// During replay [Self::data] won't have the data for the particular id in which
     /// case it is loaded via the persister instead.
-    static ref PERSISTER: RwLock<Option<Arc<dyn PersistsAccountModData>>> = RwLock::new(None);
+    persister: RwLock<Option<Arc<dyn PersistsAccountModData>>>,
 
-    static ref DATA_MOD_ID: AtomicU64 = AtomicU64::new(0);
+    next_id: AtomicU64,
 }
 
-pub fn get_account_mod_data_id() -> u64 {
-    DATA_MOD_ID.fetch_add(1, Ordering::Relaxed)
+struct DataModsMemoryBudget {
+    max_size: usize,
+    spill_dir: PathBuf,
 }
 
-/// This increases the data mod id and verifies that the expected
-/// next id is in sequence.
-/// We crash here since if not this is only used during ledger replay and
-/// if the sequence is broken this indidcates an invalid ledger and
-/// we don't want to keep running in this case
-/// As a result once the validator starts running after ledger replay
-/// the [DATA_MOD_ID] has the same value as it did when the initial validator
-/// instance stopped.
-fn set_data_mod_id_checking_sequence(next_id: u64) {
-    if next_id == 0 {
-        assert_eq!(
-            DATA_MOD_ID.load(Ordering::Relaxed),
-            0,
-            "Data mod id sequence is broken"
+impl DataMods {
+    /// Limits the memory consumed by registered data mods to `max_size` bytes.
+    /// Any data mod that would exceed it is written into the `spill_dir` and
+    /// loaded from there once it is resolved.
+    pub fn init_memory_budget(
+        &self,
+        max_size: usize,
+        spill_dir: PathBuf,
+    ) -> std::io::Result<()> {
+        fs::create_dir_all(&spill_dir)?;
+        self.memory_budget
+            .write_robust()
+            .replace(DataModsMemoryBudget {
+                max_size,
+                spill_dir,
+            });
+        Ok(())
+    }
+
+    pub fn next_id(&self) -> u64 {
+        self.next_id.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// This increases the data mod id and verifies that the expected
+    /// next id is in sequence.
+    /// We crash here since if not this is only used during ledger replay and
+    /// if the sequence is broken this indidcates an invalid ledger and
+    /// we don't want to keep running in this case
+    /// As a result once the validator starts running after ledger replay
+    /// the next id has the same value as it did when the initial validator
+    /// instance stopped.
+    fn set_id_checking_sequence(&self, next_id: u64) {
+        if next_id == 0 {
+            assert_eq!(
+                self.next_id.load(Ordering::Relaxed),
+                0,
+                "Data mod id sequence is broken"
+            );
+            return;
+        }
+        let current_id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        assert_eq!(current_id + 1, next_id, "Data mod id sequence is broken");
+    }
+
+    pub(crate) fn insert(&self, data: Vec<u8>) -> u64 {
+        let id = self.next_id();
+        let Some(data) = self.spill_data_exceeding_memory_budget(id, data)
+        else {
+            return id;
+        };
+        let len = data.len();
+        self.data.lock_robust().insert(id, data);
+        self.size.fetch_add(len, Ordering::Relaxed);
+        // update metrics related to total count and size of data mods
+        magicblock_metrics::metrics::adjust_active_data_mods(1);
+        magicblock_metrics::metrics::adjust_active_data_mods_size(len as i64);
+        id
+    }
+
+    /// Writes the data to disk if adding it to [Self::data] would exceed the
+    /// memory budget.
+    /// Returns the data if it should be kept in memory instead.
+    fn spill_data_exceeding_memory_budget(
+        &self,
+        id: u64,
+        data: Vec<u8>,
+    ) -> Option<Vec<u8>> {
+        let memory_budget = self.memory_budget.read_robust();
+        let Some(memory_budget) = memory_budget.as_ref() else {
+            return Some(data);
+        };
+        let size = self.size.load(Ordering::Relaxed);
+        if size.saturating_add(data.len()) <= memory_budget.max_size {
+            return Some(data);
+        }
+
+        magicblock_metrics::metrics::inc_data_mods_budget_exceeded();
+        warn!(
+            "Data mods memory budget of {} bytes exceeded ({} bytes in use), spilling data mod {} ({} bytes) to disk",
+            memory_budget.max_size,
+            size,
+            id,
+            data.len()
         );
-        return;
+        let path = memory_budget.spill_dir.join(id.to_string());
+        match fs::write(&path, &data) {
+            Ok(()) => {
+                let len = data.len();
+                self.spilled.lock_robust().insert(id, (path, len));
+                magicblock_metrics::metrics::adjust_spilled_data_mods(1);
+                magicblock_metrics::metrics::adjust_spilled_data_mods_size(
+                    len as i64,
+                );
+                None
+            }
+            Err(err) => {
+                error!(
+                    "Failed to spill data mod {} to {:?}, keeping it in memory: {}",
+                    id, path, err
+                );
+                Some(data)
+            }
+        }
     }
-    let current_id = DATA_MOD_ID.fetch_add(1, Ordering::Relaxed);
-    assert_eq!(current_id + 1, next_id, "Data mod id sequence is broken");
-}
 
-pub(crate) fn set_account_mod_data(data: Vec<u8>) -> u64 {
-    let id = get_account_mod_data_id();
-    DATA_MODS
-        .lock()
-        .expect("DATA_MODS poisoned")
-        .insert(id, data);
-    // update metrics related to total count of data mods
-    magicblock_metrics::metrics::adjust_active_data_mods(1);
-    id
-}
+    pub(crate) fn get(&self, id: u64) -> Option<Vec<u8>> {
+        self.data
+            .lock_robust()
+            .remove(&id)
+            .inspect(|v| {
+                self.size.fetch_sub(v.len(), Ordering::Relaxed);
+                // decrement metrics
+                let len = (v.len() as i64).neg();
+                magicblock_metrics::metrics::adjust_active_data_mods_size(len);
+                magicblock_metrics::metrics::adjust_active_data_mods(-1);
+            })
+            .or_else(|| self.get_spilled(id))
+    }
 
-pub(super) fn get_data(id: u64) -> Option<Vec<u8>> {
-    DATA_MODS
-        .lock()
-        .expect("DATA_MODS poisoned")
-        .remove(&id)
-        .inspect(|v| {
-            // decrement metrics
-            let len = (v.len() as i64).neg();
-            magicblock_metrics::metrics::adjust_active_data_mods_size(len);
-            magicblock_metrics::metrics::adjust_active_data_mods(-1);
-        })
-}
+    fn get_spilled(&self, id: u64) -> Option<Vec<u8>> {
+        let (path, len) = self.spilled.lock_robust().remove(&id)?;
+        // decrement metrics
+        magicblock_metrics::metrics::adjust_spilled_data_mods_size(
+            (len as i64).neg(),
+        );
+        magicblock_metrics::metrics::adjust_spilled_data_mods(-1);
 
-pub fn init_persister<T: PersistsAccountModData>(persister: Arc<T>) {
-    PERSISTER
-        .write()
-        .expect("PERSISTER poisoned")
-        .replace(persister);
-}
+        let data = fs::read(&path)
+            .inspect_err(|err| {
+                error!("Failed to read spilled data mod {}: {}", id, err)
+            })
+            .ok();
+        if let Err(err) = fs::remove_file(&path) {
+            warn!("Failed to remove spilled data mod {}: {}", id, err);
+        }
+        data
+    }
 
-pub fn persister_info() -> String {
-    PERSISTER
-        .read()
-        .expect("PERSISTER poisoned")
-        .as_ref()
-        .map(|p| p.to_string())
-        .unwrap_or_else(|| "None".to_string())
-}
+    pub fn init_persister<T: PersistsAccountModData>(&self, persister: Arc<T>) {
+        self.persister.write_robust().replace(persister);
+    }
 
-fn load_data(id: u64) -> Result<Option<Vec<u8>>, Box<dyn std::error::Error>> {
-    PERSISTER
-        .read()
-        .expect("PERSISTER poisoned")
-        .as_ref()
-        .ok_or("AccountModPersister needs to be set on startup")?
-        .load(id)
-}
+    pub fn persister_info(&self) -> String {
+        self.persister
+            .read_robust()
+            .as_ref()
+            .map(|p| p.to_string())
+            .unwrap_or_else(|| "None".to_string())
+    }
 
-fn persist_data(
-    id: u64,
-    data: Vec<u8>,
-) -> Result<(), Box<dyn std::error::Error>> {
-    PERSISTER
-        .read()
-        .expect("PERSISTER poisoned")
-        .as_ref()
-        .ok_or("AccounModPersister needs to be set on startup")?
-        .persist(id, data)
+    fn load(
+        &self,
+        id: u64,
+    ) -> Result<Option<Vec<u8>>, Box<dyn std::error::Error>> {
+        self.persister
+            .read_robust()
+            .as_ref()
+            .ok_or("AccountModPersister needs to be set on startup")?
+            .load(id)
+    }
+
+    fn persist(
+        &self,
+        id: u64,
+        data: Vec<u8>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.persister
+            .read_robust()
+            .as_ref()
+            .ok_or("AccounModPersister needs to be set on startup")?
+            .persist(id, data)
+    }
 }
 
 /// The resolved data including an indication about how it was resolved.
-pub(super) enum ResolvedAccountModData {
+pub(crate) enum ResolvedAccountModData {
     /// The data was resolved from memory while the validator was processing
     /// mutation transactions.
     FromMemory { id: u64, data: Vec<u8> },
@@ -148,6 +267,7 @@ impl ResolvedAccountModData {
 
     pub fn persist(
         self,
+        data_mods: &DataMods,
         invoke_context: &InvokeContext,
     ) -> Result<(), MagicBlockProgramError> {
         use ResolvedAccountModData::*;
@@ -178,7 +298,7 @@ impl ResolvedAccountModData {
             }
         };
 
-        persist_data(id, data).map_err(|err| {
+        data_mods.persist(id, data).map_err(|err| {
             ic_msg!(
                 invoke_context,
                 "MutateAccounts: failed to persist account mod data: {}",
@@ -195,14 +315,16 @@ impl ResolvedAccountModData {
     }
 }
 
-pub(super) fn resolve_account_mod_data(
+pub(crate) fn resolve_account_mod_data(
     id: u64,
+    context: &ValidatorContext,
     invoke_context: &InvokeContext,
 ) -> Result<ResolvedAccountModData, MagicBlockProgramError> {
-    if let Some(data) = get_data(id) {
+    let data_mods = context.data_mods();
+    if let Some(data) = data_mods.get(id) {
         Ok(ResolvedAccountModData::FromMemory { id, data })
-    } else if validator::is_starting_up() {
-        match load_data(id).map_err(|err| {
+    } else if context.is_starting_up() {
+        match data_mods.load(id).map_err(|err| {
             ic_msg!(
                 invoke_context,
                 "MutateAccounts: failed to load account mod data: {}",
@@ -211,7 +333,7 @@ pub(super) fn resolve_account_mod_data(
             MagicBlockProgramError::AccountDataResolutionFailed
         })? {
             Some(data) => {
-                set_data_mod_id_checking_sequence(id);
+                data_mods.set_id_checking_sequence(id);
                 Ok(ResolvedAccountModData::FromStorage { id, data })
             }
             None => Ok(ResolvedAccountModData::NotFound { id }),
@@ -229,3 +351,46 @@ pub(super) fn resolve_account_mod_data(
         Err(MagicBlockProgramError::AccountDataMissingFromMemory)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_data_mods_exceeding_memory_budget_are_spilled_to_disk() {
+        let spill_dir = std::env::temp_dir()
+            .join(format!("data-mods-test-{}", std::process::id()));
+        let data_mods = DataMods::default();
+        data_mods.init_memory_budget(0, spill_dir.clone()).unwrap();
+
+        let data = vec![1, 2, 3, 4, 5];
+        let id = data_mods.insert(data.clone());
+        assert_eq!(id, 0);
+
+        let path = spill_dir.join(id.to_string());
+        assert!(data_mods.data.lock().unwrap().is_empty());
+        assert_eq!(
+            data_mods.spilled.lock().unwrap().get(&id),
+            Some(&(path.clone(), data.len()))
+        );
+        assert!(path.exists());
+
+        assert_eq!(data_mods.get(id), Some(data));
+        assert!(data_mods.spilled.lock().unwrap().is_empty());
+        assert!(!path.exists());
+
+        let _ = fs::remove_dir_all(spill_dir);
+    }
+
+    #[test]
+    fn test_data_mods_are_not_shared_between_instances() {
+        let data_mods = DataMods::default();
+        let other_data_mods = DataMods::default();
+
+        let id = data_mods.insert(vec![1, 2, 3]);
+        assert_eq!(other_data_mods.insert(vec![4, 5, 6]), id);
+
+        assert_eq!(other_data_mods.get(id), Some(vec![4, 5, 6]));
+        assert_eq!(data_mods.get(id), Some(vec![1, 2, 3]));
+    }
+}
@@ -1,5 +1,7 @@
 mod account_mod_data;
 mod process_mutate_accounts;
+pub use account_mod_data::DataMods;
 pub(crate) use account_mod_data::*;
-pub use account_mod_data::{init_persister, persister_info};
-pub(crate) use process_mutate_accounts::process_mutate_accounts;
+pub(crate) use process_mutate_accounts::{
+    process_mutate_accounts, process_mutate_accounts_atomic,
+};
@@ -1,8 +1,11 @@
-use std::collections::{HashMap, HashSet};
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+};
 
 use solana_program_runtime::{ic_msg, invoke_context::InvokeContext};
 use solana_sdk::{
-    account::{ReadableAccount, WritableAccount},
+    account::{AccountSharedData, ReadableAccount, WritableAccount},
     instruction::InstructionError,
     pubkey::Pubkey,
     system_program,
@@ -13,94 +16,36 @@ use crate::{
     magicblock_instruction::{
         AccountModificationForInstruction, MagicBlockProgramError,
     },
-    mutate_accounts::account_mod_data::resolve_account_mod_data,
-    validator::validator_authority_id,
+    mutate_accounts::account_mod_data::{
+        resolve_account_mod_data, ResolvedAccountModData,
+    },
+    validator_context::ValidatorContext,
 };
 
 pub(crate) fn process_mutate_accounts(
     signers: HashSet<Pubkey>,
+    context: &ValidatorContext,
     invoke_context: &InvokeContext,
     transaction_context: &TransactionContext,
     account_mods: &mut HashMap<Pubkey, AccountModificationForInstruction>,
 ) -> Result<(), InstructionError> {
     let instruction_context =
         transaction_context.get_current_instruction_context()?;
-
-    // First account is the MagicBlock authority
-    let accounts_len = instruction_context.get_number_of_instruction_accounts();
-    let accounts_to_mod_len = accounts_len - 1;
     let account_mods_len = account_mods.len() as u64;
 
     // 1. Checks
-    let validator_authority_acc = {
-        // 1.1. MagicBlock authority must sign
-        let validator_authority_id = validator_authority_id();
-        if !signers.contains(&validator_authority_id) {
-            ic_msg!(
-                invoke_context,
-                "Validator identity '{}' not in signers",
-                &validator_authority_id.to_string()
-            );
-            return Err(InstructionError::MissingRequiredSignature);
-        }
+    let validator_authority_acc = check_accounts_to_modify(
+        &signers,
+        context,
+        invoke_context,
+        transaction_context,
+        account_mods_len,
+    )?;
 
-        // 1.2. Need to have some accounts to modify
-        if accounts_to_mod_len == 0 {
-            ic_msg!(invoke_context, "MutateAccounts: no accounts to modify");
-            return Err(MagicBlockProgramError::NoAccountsToModify.into());
-        }
-
-        // 1.3. Number of accounts to modify must match number of account modifications
-        if accounts_to_mod_len as u64 != account_mods_len {
-            ic_msg!(
-                    invoke_context,
-                    "MutateAccounts: number of accounts to modify ({}) does not match number of account modifications ({})",
-                    accounts_to_mod_len,
-                    account_mods_len
-                );
-            return Err(
-                MagicBlockProgramError::AccountsToModifyNotMatchingAccountModifications
-                    .into(),
-            );
-        }
-
-        // 1.4. Check that first account is the MagicBlock authority
-        let authority_transaction_index = instruction_context
-            .get_index_of_instruction_account_in_transaction(0)?;
-        let magicblock_authority_key = transaction_context
-            .get_key_of_account_at_index(authority_transaction_index)?;
-        if magicblock_authority_key != &validator_authority_id {
-            ic_msg!(
-                invoke_context,
-                "MutateAccounts: first account must be the MagicBlock authority"
-            );
-            return Err(
-                MagicBlockProgramError::FirstAccountNeedsToBeMagicBlockAuthority.into(),
-            );
-        }
-        let magicblock_authority_acc = transaction_context
-            .get_account_at_index(authority_transaction_index)?;
-        if magicblock_authority_acc
-            .borrow()
-            .owner()
-            .ne(&system_program::id())
-        {
-            ic_msg!(
-                invoke_context,
-                "MutateAccounts: MagicBlock authority needs to be owned by the system program"
-            );
-            return Err(
-                MagicBlockProgramError::MagicBlockAuthorityNeedsToBeOwnedBySystemProgram
-                    .into(),
-            );
-        }
-        magicblock_authority_acc
-    };
-
-    let mut lamports_to_debit: i128 = 0;
-
-    // 2. Apply account modifications
-    let mut memory_data_mods = Vec::new();
+    // 2. Collect the modification for each account
+    //    We do this for all accounts before modifying any of them so that
+    //    a mismatch between accounts and modifications is detected up front.
+    let mut modifications = Vec::with_capacity(account_mods_len as usize);
     for idx in 0..account_mods_len {
         // NOTE: first account is the MagicBlock authority, account mods start at second account
         let account_idx = (idx + 1) as u16;
@@ -111,7 +56,7 @@ pub(crate) fn process_mutate_accounts(
         let account_key = transaction_context
             .get_key_of_account_at_index(account_transaction_index)?;
 
-        let mut modification = account_mods.remove(account_key).ok_or_else(|| {
+        let modification = account_mods.remove(account_key).ok_or_else(|| {
             ic_msg!(
                 invoke_context,
                 "MutateAccounts: account modification for the provided key {} is missing",
@@ -119,91 +64,364 @@ pub(crate) fn process_mutate_accounts(
             );
             MagicBlockProgramError::AccountModificationMissing
         })?;
+        modifications.push((account, account_key, modification));
+    }
 
-        ic_msg!(
-            invoke_context,
-            "MutateAccounts: modifying '{}'.",
-            account_key,
-        );
-
-        if let Some(lamports) = modification.lamports {
+    // 3. Resolve the data mods of all accounts together
+    //    Each resolution consumes the registered data, thus we resolve all of
+    //    them even if one fails in order to not leave any data of this
+    //    instruction behind in memory.
+    let resolved_data_mods = modifications
+        .iter_mut()
+        .map(|(_, _, modification)| {
+            modification.data_key.take().map(|data_key| {
+                (
+                    data_key,
+                    resolve_account_mod_data(data_key, context, invoke_context),
+                )
+            })
+        })
+        .collect::<Vec<_>>();
+    let mut resolved_datas = Vec::with_capacity(resolved_data_mods.len());
+    for resolved_data_mod in resolved_data_mods {
+        let Some((data_key, resolved_data)) = resolved_data_mod else {
+            resolved_datas.push(None);
+            continue;
+        };
+        let resolved_data = resolved_data.inspect_err(|err| {
             ic_msg!(
                 invoke_context,
-                "MutateAccounts: setting lamports to {}",
-                lamports
+                "MutateAccounts: an error occurred when resolving account mod data for the provided key {}. Error: {:?}",
+                data_key,
+                err
             );
-            let current_lamports = account.borrow().lamports();
-            lamports_to_debit += lamports as i128 - current_lamports as i128;
-
-            account.borrow_mut().set_lamports(lamports);
-        }
-        if let Some(owner) = modification.owner {
+        })?;
+        if resolved_data.data().is_none() {
             ic_msg!(
                 invoke_context,
-                "MutateAccounts: setting owner to {}",
-                owner
+                "MutateAccounts: account data for the provided key {} is missing",
+                data_key
             );
-            account.borrow_mut().set_owner(owner);
+            return Err(MagicBlockProgramError::AccountDataMissing.into());
         }
-        if let Some(executable) = modification.executable {
-            ic_msg!(
-                invoke_context,
-                "MutateAccounts: setting executable to {}",
-                executable
-            );
-            account.borrow_mut().set_executable(executable);
+        ic_msg!(
+            invoke_context,
+            "MutateAccounts: resolved data from id {}",
+            resolved_data.id()
+        );
+        resolved_datas.push(Some(resolved_data));
+    }
+
+    // 4. Apply account modifications
+    let mut lamports_to_debit: i128 = 0;
+    let mut memory_data_mods = Vec::new();
+    for ((account, account_key, modification), resolved_data) in
+        modifications.into_iter().zip(resolved_datas)
+    {
+        // NOTE: we ensured that the data was found when resolving it
+        let data = resolved_data.as_ref().and_then(|data| data.data());
+        lamports_to_debit += apply_account_modification(
+            invoke_context,
+            account,
+            account_key,
+            &modification,
+            data,
+        );
+
+        // We track resolved data mods in order to persist them at the end
+        // of the transaction.
+        // NOTE: that during ledger replay all mods came from storage, so we
+        // don't persist them again.
+        if let Some(resolved_data) = resolved_data {
+            if resolved_data.is_from_memory() {
+                memory_data_mods.push(resolved_data);
+            }
         }
-        if let Some(data_key) = modification.data_key.take() {
-            let resolved_data = resolve_account_mod_data(
-                data_key,
-                invoke_context,
-            ).inspect_err(|err| {
-                ic_msg!(
-                    invoke_context,
-                    "MutateAccounts: an error occurred when resolving account mod data for the provided key {}. Error: {:?}",
-                    data_key,
-                    err
-                );
-            })?;
-            if let Some(data) = resolved_data.data() {
+    }
+    settle_authority_lamports(
+        invoke_context,
+        validator_authority_acc,
+        lamports_to_debit,
+    )?;
+
+    // Now it is super unlikely for the transaction to fail since all checks passed.
+    // The only option would be if another instruction runs after it which at this point
+    // is impossible since we create/send them from insider our validator.
+    // Thus we can persist the applied data mods to make them available for ledger replay.
+    persist_data_mods(context, invoke_context, memory_data_mods)
+}
+
+/// Modifies the accounts like [process_mutate_accounts], but resolves the
+/// data of all of them from a single combined data mod, such that no
+/// account is modified unless the data of all of them is found.
+/// The modifications apply to the accounts in the order they're passed.
+pub(crate) fn process_mutate_accounts_atomic(
+    signers: HashSet<Pubkey>,
+    context: &ValidatorContext,
+    invoke_context: &InvokeContext,
+    transaction_context: &TransactionContext,
+    account_mods: Vec<AccountModificationForInstruction>,
+    data_key: Option<u64>,
+) -> Result<(), InstructionError> {
+    let instruction_context =
+        transaction_context.get_current_instruction_context()?;
+
+    // 1. Checks
+    let validator_authority_acc = check_accounts_to_modify(
+        &signers,
+        context,
+        invoke_context,
+        transaction_context,
+        account_mods.len() as u64,
+    )?;
+    // The data of all accounts is provided via the combined data mod
+    if account_mods.iter().any(|m| m.data_key.is_some()) {
+        ic_msg!(
+            invoke_context,
+            "MutateAccountsAtomic: data needs to be provided via the combined data mod"
+        );
+        return Err(
+            MagicBlockProgramError::InvalidCombinedAccountModData.into()
+        );
+    }
+
+    // 2. Resolve the combined data of all accounts at once
+    let resolved_data = match data_key {
+        Some(data_key) => {
+            let resolved_data =
+                resolve_account_mod_data(data_key, context, invoke_context)
+                    .inspect_err(|err| {
+                        ic_msg!(
+                            invoke_context,
+                            "MutateAccountsAtomic: an error occurred when resolving account mod data for the provided key {}. Error: {:?}",
+                            data_key,
+                            err
+                        );
+                    })?;
+            if resolved_data.data().is_none() {
                 ic_msg!(
                     invoke_context,
-                    "MutateAccounts: resolved data from id {}",
-                    resolved_data.id()
+                    "MutateAccountsAtomic: account data for the provided key {} is missing",
+                    data_key
                 );
+                return Err(MagicBlockProgramError::AccountDataMissing.into());
+            }
+            Some(resolved_data)
+        }
+        None => None,
+    };
+    let datas = match resolved_data.as_ref().and_then(|data| data.data()) {
+        Some(data) => bincode::deserialize::<Vec<Option<Vec<u8>>>>(data)
+            .map_err(|err| {
                 ic_msg!(
                     invoke_context,
-                    "MutateAccounts: setting data to len {}",
-                    data.len()
+                    "MutateAccountsAtomic: failed to decode the combined account data: {}",
+                    err
                 );
-                account.borrow_mut().set_data_from_slice(data);
-            } else {
-                ic_msg!(
-                        invoke_context,
-                        "MutateAccounts: account data for the provided key {} is missing",
-                        data_key
-                    );
-                return Err(MagicBlockProgramError::AccountDataMissing.into());
-            }
+                MagicBlockProgramError::InvalidCombinedAccountModData
+            })?,
+        None => vec![None; account_mods.len()],
+    };
+    if datas.len() != account_mods.len() {
+        ic_msg!(
+            invoke_context,
+            "MutateAccountsAtomic: combined account data for {} accounts does not match {} account modifications",
+            datas.len(),
+            account_mods.len()
+        );
+        return Err(
+            MagicBlockProgramError::InvalidCombinedAccountModData.into()
+        );
+    }
 
-            // We track resolved data mods in order to persist them at the end
-            // of the transaction.
-            // NOTE: that during ledger replay all mods came from storage, so we
-            // don't persist them again.
-            if resolved_data.is_from_memory() {
-                memory_data_mods.push(resolved_data);
-            }
-        }
-        if let Some(rent_epoch) = modification.rent_epoch {
-            ic_msg!(
-                invoke_context,
-                "MutateAccounts: setting rent_epoch to {}",
-                rent_epoch
-            );
-            account.borrow_mut().set_rent_epoch(rent_epoch);
-        }
+    // 3. Apply account modifications
+    let mut lamports_to_debit: i128 = 0;
+    for (idx, (modification, data)) in
+        account_mods.iter().zip(datas.iter()).enumerate()
+    {
+        // NOTE: first account is the MagicBlock authority, account mods start at second account
+        let account_transaction_index = instruction_context
+            .get_index_of_instruction_account_in_transaction(
+                (idx + 1) as u16,
+            )?;
+        let account = transaction_context
+            .get_account_at_index(account_transaction_index)?;
+        let account_key = transaction_context
+            .get_key_of_account_at_index(account_transaction_index)?;
+        lamports_to_debit += apply_account_modification(
+            invoke_context,
+            account,
+            account_key,
+            modification,
+            data.as_deref(),
+        );
+    }
+    settle_authority_lamports(
+        invoke_context,
+        validator_authority_acc,
+        lamports_to_debit,
+    )?;
+
+    // NOTE: during ledger replay the combined data came from storage, so we
+    // don't persist it again
+    persist_data_mods(
+        context,
+        invoke_context,
+        resolved_data.filter(ResolvedAccountModData::is_from_memory),
+    )
+}
+
+/// Checks that the validator authority signed and is passed as the first
+/// account, followed by one account for each of the [account_mods_len]
+/// modifications.
+/// Returns the account of the validator authority.
+fn check_accounts_to_modify<'a>(
+    signers: &HashSet<Pubkey>,
+    context: &ValidatorContext,
+    invoke_context: &InvokeContext,
+    transaction_context: &'a TransactionContext,
+    account_mods_len: u64,
+) -> Result<&'a RefCell<AccountSharedData>, InstructionError> {
+    let instruction_context =
+        transaction_context.get_current_instruction_context()?;
+
+    // First account is the MagicBlock authority
+    let accounts_len = instruction_context.get_number_of_instruction_accounts();
+    let accounts_to_mod_len = accounts_len - 1;
+
+    // 1.1. MagicBlock authority must sign
+    let validator_authority_id =
+        context.validator_authority_id_for_signers(signers);
+    if !signers.contains(&validator_authority_id) {
+        ic_msg!(
+            invoke_context,
+            "Validator identity '{}' not in signers",
+            &validator_authority_id.to_string()
+        );
+        return Err(InstructionError::MissingRequiredSignature);
+    }
+
+    // 1.2. Need to have some accounts to modify
+    if accounts_to_mod_len == 0 {
+        ic_msg!(invoke_context, "MutateAccounts: no accounts to modify");
+        return Err(MagicBlockProgramError::NoAccountsToModify.into());
+    }
+
+    // 1.3. Number of accounts to modify must match number of account modifications
+    if accounts_to_mod_len as u64 != account_mods_len {
+        ic_msg!(
+            invoke_context,
+            "MutateAccounts: number of accounts to modify ({}) does not match number of account modifications ({})",
+            accounts_to_mod_len,
+            account_mods_len
+        );
+        return Err(
+            MagicBlockProgramError::AccountsToModifyNotMatchingAccountModifications
+                .into(),
+        );
     }
 
+    // 1.4. Check that first account is the MagicBlock authority
+    let authority_transaction_index = instruction_context
+        .get_index_of_instruction_account_in_transaction(0)?;
+    let magicblock_authority_key = transaction_context
+        .get_key_of_account_at_index(authority_transaction_index)?;
+    if magicblock_authority_key != &validator_authority_id {
+        ic_msg!(
+            invoke_context,
+            "MutateAccounts: first account must be the MagicBlock authority"
+        );
+        return Err(
+            MagicBlockProgramError::FirstAccountNeedsToBeMagicBlockAuthority
+                .into(),
+        );
+    }
+    let magicblock_authority_acc = transaction_context
+        .get_account_at_index(authority_transaction_index)?;
+    if magicblock_authority_acc
+        .borrow()
+        .owner()
+        .ne(&system_program::id())
+    {
+        ic_msg!(
+            invoke_context,
+            "MutateAccounts: MagicBlock authority needs to be owned by the system program"
+        );
+        return Err(
+            MagicBlockProgramError::MagicBlockAuthorityNeedsToBeOwnedBySystemProgram
+                .into(),
+        );
+    }
+    Ok(magicblock_authority_acc)
+}
+
+/// Applies the [modification] to the [account], setting its data to the
+/// resolved [data] if provided.
+/// Returns the lamports that need to be debited from the validator authority
+/// in order to fund the account, negative if they're credited instead.
+fn apply_account_modification(
+    invoke_context: &InvokeContext,
+    account: &RefCell<AccountSharedData>,
+    account_key: &Pubkey,
+    modification: &AccountModificationForInstruction,
+    data: Option<&[u8]>,
+) -> i128 {
+    ic_msg!(
+        invoke_context,
+        "MutateAccounts: modifying '{}'.",
+        account_key,
+    );
+
+    let mut lamports_to_debit: i128 = 0;
+    if let Some(lamports) = modification.lamports {
+        ic_msg!(
+            invoke_context,
+            "MutateAccounts: setting lamports to {}",
+            lamports
+        );
+        let current_lamports = account.borrow().lamports();
+        lamports_to_debit = lamports as i128 - current_lamports as i128;
+
+        account.borrow_mut().set_lamports(lamports);
+    }
+    if let Some(owner) = modification.owner {
+        ic_msg!(invoke_context, "MutateAccounts: setting owner to {}", owner);
+        account.borrow_mut().set_owner(owner);
+    }
+    if let Some(executable) = modification.executable {
+        ic_msg!(
+            invoke_context,
+            "MutateAccounts: setting executable to {}",
+            executable
+        );
+        account.borrow_mut().set_executable(executable);
+    }
+    if let Some(data) = data {
+        ic_msg!(
+            invoke_context,
+            "MutateAccounts: setting data to len {}",
+            data.len()
+        );
+        account.borrow_mut().set_data_from_slice(data);
+    }
+    if let Some(rent_epoch) = modification.rent_epoch {
+        ic_msg!(
+            invoke_context,
+            "MutateAccounts: setting rent_epoch to {}",
+            rent_epoch
+        );
+        account.borrow_mut().set_rent_epoch(rent_epoch);
+    }
+    lamports_to_debit
+}
+
+/// Debits the lamports that funded the modified accounts from the validator
+/// authority, or credits them if [lamports_to_debit] is negative.
+fn settle_authority_lamports(
+    invoke_context: &InvokeContext,
+    validator_authority_acc: &RefCell<AccountSharedData>,
+    lamports_to_debit: i128,
+) -> Result<(), InstructionError> {
     if lamports_to_debit != 0 {
         let authority_lamports = validator_authority_acc.borrow().lamports();
         let adjusted_authority_lamports = if lamports_to_debit > 0 {
@@ -243,14 +461,19 @@ pub(crate) fn process_mutate_accounts(
             })?,
         );
     }
+    Ok(())
+}
 
-    // Now it is super unlikely for the transaction to fail since all checks passed.
-    // The only option would be if another instruction runs after it which at this point
-    // is impossible since we create/send them from insider our validator.
-    // Thus we can persist the applied data mods to make them available for ledger replay.
+/// Persists the data mods resolved from memory to make them available for
+/// ledger replay.
+fn persist_data_mods(
+    context: &ValidatorContext,
+    invoke_context: &InvokeContext,
+    memory_data_mods: impl IntoIterator<Item = ResolvedAccountModData>,
+) -> Result<(), InstructionError> {
     for resolved_data in memory_data_mods {
         resolved_data
-            .persist(invoke_context)
+            .persist(context.data_mods(), invoke_context)
             .inspect_err(|err| {
                 ic_msg!(
                     invoke_context,
@@ -277,10 +500,12 @@ mod tests {
     use super::*;
     use crate::{
         magicblock_instruction::{
-            modify_accounts_instruction, AccountModification,
+            modify_accounts_atomic_instruction, modify_accounts_instruction,
+            AccountModification, MagicBlockInstruction,
         },
         test_utils::{
-            ensure_started_validator, process_instruction, AUTHORITY_BALANCE,
+            ensure_started_validator, process_instruction, test_context,
+            AUTHORITY_BALANCE,
         },
     };
 
@@ -308,7 +533,10 @@ mod tests {
             data: Some(vec![1, 2, 3, 4, 5]),
             rent_epoch: Some(88),
         };
-        let ix = modify_accounts_instruction(vec![modification.clone()]);
+        let ix = modify_accounts_instruction(
+            &test_context(),
+            vec![modification.clone()],
+        );
         let transaction_accounts = ix
             .accounts
             .iter()
@@ -375,18 +603,21 @@ mod tests {
         };
         ensure_started_validator(&mut account_data);
 
-        let ix = modify_accounts_instruction(vec![
-            AccountModification {
-                pubkey: mod_key1,
-                lamports: Some(300),
-                ..AccountModification::default()
-            },
-            AccountModification {
-                pubkey: mod_key2,
-                lamports: Some(400),
-                ..AccountModification::default()
-            },
-        ]);
+        let ix = modify_accounts_instruction(
+            &test_context(),
+            vec![
+                AccountModification {
+                    pubkey: mod_key1,
+                    lamports: Some(300),
+                    ..AccountModification::default()
+                },
+                AccountModification {
+                    pubkey: mod_key2,
+                    lamports: Some(400),
+                    ..AccountModification::default()
+                },
+            ],
+        );
         let transaction_accounts = ix
             .accounts
             .iter()
@@ -472,33 +703,36 @@ mod tests {
         };
         ensure_started_validator(&mut account_data);
 
-        let ix = modify_accounts_instruction(vec![
-            AccountModification {
-                pubkey: mod_key1,
-                lamports: Some(1000),
-                data: Some(vec![1, 2, 3, 4, 5]),
-                ..Default::default()
-            },
-            AccountModification {
-                pubkey: mod_key2,
-                owner: Some(mod_2_owner),
-                ..Default::default()
-            },
-            AccountModification {
-                pubkey: mod_key3,
-                lamports: Some(3000),
-                rent_epoch: Some(90),
-                ..Default::default()
-            },
-            AccountModification {
-                pubkey: mod_key4,
-                lamports: Some(100),
-                executable: Some(true),
-                data: Some(vec![16, 17, 18, 19, 20]),
-                rent_epoch: Some(91),
-                ..Default::default()
-            },
-        ]);
+        let ix = modify_accounts_instruction(
+            &test_context(),
+            vec![
+                AccountModification {
+                    pubkey: mod_key1,
+                    lamports: Some(1000),
+                    data: Some(vec![1, 2, 3, 4, 5]),
+                    ..Default::default()
+                },
+                AccountModification {
+                    pubkey: mod_key2,
+                    owner: Some(mod_2_owner),
+                    ..Default::default()
+                },
+                AccountModification {
+                    pubkey: mod_key3,
+                    lamports: Some(3000),
+                    rent_epoch: Some(90),
+                    ..Default::default()
+                },
+                AccountModification {
+                    pubkey: mod_key4,
+                    lamports: Some(100),
+                    executable: Some(true),
+                    data: Some(vec![16, 17, 18, 19, 20]),
+                    rent_epoch: Some(91),
+                    ..Default::default()
+                },
+            ],
+        );
 
         let transaction_accounts = ix
             .accounts
@@ -595,4 +829,220 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn test_mod_data_of_two_accounts_second_data_missing() {
+        init_logger!();
+
+        let mod_key1 = Pubkey::new_unique();
+        let mod_key2 = Pubkey::new_unique();
+        let mut account_data = {
+            let mut map = HashMap::new();
+            map.insert(mod_key1, AccountSharedData::new(100, 0, &mod_key1));
+            map.insert(mod_key2, AccountSharedData::new(200, 0, &mod_key2));
+            map
+        };
+        ensure_started_validator(&mut account_data);
+
+        let ix = modify_accounts_instruction(
+            &test_context(),
+            vec![
+                AccountModification {
+                    pubkey: mod_key1,
+                    data: Some(vec![1, 2, 3, 4, 5]),
+                    ..AccountModification::default()
+                },
+                AccountModification {
+                    pubkey: mod_key2,
+                    data: Some(vec![6, 7, 8, 9, 10]),
+                    ..AccountModification::default()
+                },
+            ],
+        );
+
+        // Consume the data of the second account before the instruction runs
+        let account_mods = match bincode::deserialize(&ix.data).unwrap() {
+            MagicBlockInstruction::ModifyAccounts(account_mods) => account_mods,
+            _ => panic!("expected ModifyAccounts instruction"),
+        };
+        let data_key1 = account_mods[&mod_key1].data_key.unwrap();
+        let data_key2 = account_mods[&mod_key2].data_key.unwrap();
+        let consumed_data = test_context().data_mods().get(data_key2);
+        assert!(consumed_data.is_some());
+
+        let transaction_accounts = ix
+            .accounts
+            .iter()
+            .flat_map(|acc| {
+                account_data
+                    .remove(&acc.pubkey)
+                    .map(|shared_data| (acc.pubkey, shared_data))
+            })
+            .collect();
+
+        let mut accounts = process_instruction(
+            ix.data.as_slice(),
+            transaction_accounts,
+            ix.accounts,
+            Err(MagicBlockProgramError::AccountDataMissingFromMemory.into()),
+        );
+
+        // The data of the first account was resolved together with the second
+        // one and thus is not left behind in memory
+        assert!(test_context().data_mods().get(data_key1).is_none());
+
+        // None of the accounts were modified
+        let _account_authority = accounts.drain(0..1).next().unwrap();
+        let modified_account1: Account =
+            accounts.drain(0..1).next().unwrap().into();
+        assert_eq!(modified_account1.lamports, 100);
+        assert!(modified_account1.data.is_empty());
+        let modified_account2: Account =
+            accounts.drain(0..1).next().unwrap().into();
+        assert_eq!(modified_account2.lamports, 200);
+        assert!(modified_account2.data.is_empty());
+    }
+
+    // -----------------
+    // ModifyAccountsAtomic
+    // -----------------
+    #[test]
+    fn test_mod_atomic_data_of_two_accounts() {
+        init_logger!();
+
+        let mod_key1 = Pubkey::new_unique();
+        let mod_key2 = Pubkey::new_unique();
+        let mut account_data = {
+            let mut map = HashMap::new();
+            map.insert(mod_key1, AccountSharedData::new(100, 0, &mod_key1));
+            map.insert(mod_key2, AccountSharedData::new(200, 0, &mod_key2));
+            map
+        };
+        ensure_started_validator(&mut account_data);
+
+        let ix = modify_accounts_atomic_instruction(
+            &test_context(),
+            vec![
+                AccountModification {
+                    pubkey: mod_key1,
+                    lamports: Some(300),
+                    data: Some(vec![1, 2, 3]),
+                    ..AccountModification::default()
+                },
+                AccountModification {
+                    pubkey: mod_key2,
+                    executable: Some(true),
+                    ..AccountModification::default()
+                },
+            ],
+        );
+        let data_key = match bincode::deserialize(&ix.data).unwrap() {
+            MagicBlockInstruction::ModifyAccountsAtomic {
+                data_key, ..
+            } => data_key.unwrap(),
+            _ => panic!("expected ModifyAccountsAtomic instruction"),
+        };
+        let transaction_accounts = ix
+            .accounts
+            .iter()
+            .flat_map(|acc| {
+                account_data
+                    .remove(&acc.pubkey)
+                    .map(|shared_data| (acc.pubkey, shared_data))
+            })
+            .collect();
+
+        let mut accounts = process_instruction(
+            ix.data.as_slice(),
+            transaction_accounts,
+            ix.accounts,
+            Ok(()),
+        );
+
+        // The combined data was resolved once
+        assert!(test_context().data_mods().get(data_key).is_none());
+
+        let account_authority: Account =
+            accounts.drain(0..1).next().unwrap().into();
+        assert_eq!(account_authority.lamports, AUTHORITY_BALANCE - 200);
+        let modified_account1: Account =
+            accounts.drain(0..1).next().unwrap().into();
+        assert_eq!(modified_account1.lamports, 300);
+        assert_eq!(modified_account1.data, vec![1, 2, 3]);
+        assert!(!modified_account1.executable);
+        let modified_account2: Account =
+            accounts.drain(0..1).next().unwrap().into();
+        assert_eq!(modified_account2.lamports, 200);
+        assert!(modified_account2.data.is_empty());
+        assert!(modified_account2.executable);
+    }
+
+    #[test]
+    fn test_mod_atomic_combined_data_missing() {
+        init_logger!();
+
+        let mod_key1 = Pubkey::new_unique();
+        let mod_key2 = Pubkey::new_unique();
+        let mut account_data = {
+            let mut map = HashMap::new();
+            map.insert(mod_key1, AccountSharedData::new(100, 0, &mod_key1));
+            map.insert(mod_key2, AccountSharedData::new(200, 0, &mod_key2));
+            map
+        };
+        ensure_started_validator(&mut account_data);
+
+        let ix = modify_accounts_atomic_instruction(
+            &test_context(),
+            vec![
+                AccountModification {
+                    pubkey: mod_key1,
+                    lamports: Some(300),
+                    ..AccountModification::default()
+                },
+                AccountModification {
+                    pubkey: mod_key2,
+                    data: Some(vec![6, 7, 8]),
+                    ..AccountModification::default()
+                },
+            ],
+        );
+
+        // Consume the combined data before the instruction runs
+        let data_key = match bincode::deserialize(&ix.data).unwrap() {
+            MagicBlockInstruction::ModifyAccountsAtomic {
+                data_key, ..
+            } => data_key.unwrap(),
+            _ => panic!("expected ModifyAccountsAtomic instruction"),
+        };
+        let consumed_data = test_context().data_mods().get(data_key);
+        assert!(consumed_data.is_some());
+
+        let transaction_accounts = ix
+            .accounts
+            .iter()
+            .flat_map(|acc| {
+                account_data
+                    .remove(&acc.pubkey)
+                    .map(|shared_data| (acc.pubkey, shared_data))
+            })
+            .collect();
+
+        let mut accounts = process_instruction(
+            ix.data.as_slice(),
+            transaction_accounts,
+            ix.accounts,
+            Err(MagicBlockProgramError::AccountDataMissingFromMemory.into()),
+        );
+
+        // None of the accounts were modified, not even the lamports of the
+        // account without data
+        let _account_authority = accounts.drain(0..1).next().unwrap();
+        let modified_account1: Account =
+            accounts.drain(0..1).next().unwrap().into();
+        assert_eq!(modified_account1.lamports, 100);
+        let modified_account2: Account =
+            accounts.drain(0..1).next().unwrap().into();
+        assert_eq!(modified_account2.lamports, 200);
+        assert!(modified_account2.data.is_empty());
+    }
 }
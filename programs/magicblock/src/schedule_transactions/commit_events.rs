@@ -0,0 +1,209 @@
+//! Structured events emitted for each stage of a scheduled commit's lifecycle.
+//!
+//! They are logged the same way Anchor's `emit!` does it, i.e. as
+//! `Program data: <base64>` log lines whose data consists of an 8 byte
+//! discriminator (`sha256("event:<EventName>")[..8]`) followed by the
+//! borsh encoded event.
+//! Thus programs and indexers can decode them with the same tooling they use
+//! for Anchor events instead of parsing the free-form log lines.
+
+use solana_program_runtime::{invoke_context::InvokeContext, stable_log};
+use solana_sdk::{
+    clock::Slot, hash::hashv, pubkey::Pubkey, signature::Signature,
+};
+
+/// Emitted when a scheduled commit is accepted by the validator, see
+/// [crate::magicblock_instruction::MagicBlockInstruction::AcceptScheduleCommits].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScheduledCommitAccepted {
+    pub commit_id: u64,
+    pub slot: Slot,
+    pub payer: Pubkey,
+    pub owner: Pubkey,
+    pub accounts: Vec<Pubkey>,
+    pub request_undelegation: bool,
+    pub commit_at_slot: Option<Slot>,
+}
+
+/// Emitted when the transactions realizing a scheduled commit are sent to chain, see
+/// [crate::magicblock_instruction::MagicBlockInstruction::ScheduledCommitSent].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScheduledCommitSent {
+    pub commit_id: u64,
+    pub slot: Slot,
+    pub chain_signatures: Vec<Signature>,
+    pub included_pubkeys: Vec<Pubkey>,
+    pub excluded_pubkeys: Vec<Pubkey>,
+    pub skipped_pubkeys: Vec<Pubkey>,
+    pub requested_undelegation_to_owner: Option<Pubkey>,
+}
+
+/// Emitted once the transactions realizing a scheduled commit were confirmed
+/// on chain or failed to confirm, see
+/// [crate::magicblock_instruction::MagicBlockInstruction::ScheduledCommitConfirmed].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScheduledCommitConfirmed {
+    pub commit_id: u64,
+    pub chain_signatures: Vec<Signature>,
+    pub success: bool,
+}
+
+pub trait CommitEvent {
+    const NAME: &'static str;
+
+    fn write_fields(&self, writer: &mut EventWriter);
+
+    fn discriminator() -> [u8; 8] {
+        let hash = hashv(&[format!("event:{}", Self::NAME).as_bytes()]);
+        let mut discriminator = [0u8; 8];
+        discriminator.copy_from_slice(&hash.to_bytes()[..8]);
+        discriminator
+    }
+
+    /// The data that is logged for this event, including the discriminator.
+    fn to_event_data(&self) -> Vec<u8> {
+        let mut writer = EventWriter(Self::discriminator().to_vec());
+        self.write_fields(&mut writer);
+        writer.0
+    }
+}
+
+impl CommitEvent for ScheduledCommitAccepted {
+    const NAME: &'static str = "ScheduledCommitAccepted";
+
+    fn write_fields(&self, writer: &mut EventWriter) {
+        writer.u64(self.commit_id);
+        writer.u64(self.slot);
+        writer.pubkey(&self.payer);
+        writer.pubkey(&self.owner);
+        writer.pubkeys(&self.accounts);
+        writer.bool(self.request_undelegation);
+        writer.option_u64(self.commit_at_slot);
+    }
+}
+
+impl CommitEvent for ScheduledCommitSent {
+    const NAME: &'static str = "ScheduledCommitSent";
+
+    fn write_fields(&self, writer: &mut EventWriter) {
+        writer.u64(self.commit_id);
+        writer.u64(self.slot);
+        writer.signatures(&self.chain_signatures);
+        writer.pubkeys(&self.included_pubkeys);
+        writer.pubkeys(&self.excluded_pubkeys);
+        writer.pubkeys(&self.skipped_pubkeys);
+        writer.option_pubkey(self.requested_undelegation_to_owner.as_ref());
+    }
+}
+
+impl CommitEvent for ScheduledCommitConfirmed {
+    const NAME: &'static str = "ScheduledCommitConfirmed";
+
+    fn write_fields(&self, writer: &mut EventWriter) {
+        writer.u64(self.commit_id);
+        writer.signatures(&self.chain_signatures);
+        writer.bool(self.success);
+    }
+}
+
+/// Writes event fields using the borsh encoding.
+/// We only need to support a handful of types, thus we don't pull in borsh
+/// for this.
+pub struct EventWriter(Vec<u8>);
+
+impl EventWriter {
+    fn u64(&mut self, value: u64) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn bool(&mut self, value: bool) {
+        self.0.push(value as u8);
+    }
+
+    fn len(&mut self, len: usize) {
+        self.0.extend_from_slice(&(len as u32).to_le_bytes());
+    }
+
+    fn pubkey(&mut self, pubkey: &Pubkey) {
+        self.0.extend_from_slice(pubkey.as_ref());
+    }
+
+    fn pubkeys(&mut self, pubkeys: &[Pubkey]) {
+        self.len(pubkeys.len());
+        pubkeys.iter().for_each(|pubkey| self.pubkey(pubkey));
+    }
+
+    fn signatures(&mut self, signatures: &[Signature]) {
+        self.len(signatures.len());
+        for signature in signatures {
+            self.0.extend_from_slice(signature.as_ref());
+        }
+    }
+
+    fn option_u64(&mut self, value: Option<u64>) {
+        match value {
+            Some(value) => {
+                self.bool(true);
+                self.u64(value);
+            }
+            None => self.bool(false),
+        }
+    }
+
+    fn option_pubkey(&mut self, value: Option<&Pubkey>) {
+        match value {
+            Some(value) => {
+                self.bool(true);
+                self.pubkey(value);
+            }
+            None => self.bool(false),
+        }
+    }
+}
+
+/// Logs the already encoded event data as `Program data: <base64>`.
+pub(crate) fn emit_event_data(invoke_context: &InvokeContext, data: &[u8]) {
+    stable_log::program_data(&invoke_context.get_log_collector(), &[data]);
+}
+
+pub(crate) fn emit_event<E: CommitEvent>(
+    invoke_context: &InvokeContext,
+    event: &E,
+) {
+    emit_event_data(invoke_context, &event.to_event_data());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scheduled_commit_confirmed_event_data() {
+        let signature = Signature::from([1; 64]);
+        let event = ScheduledCommitConfirmed {
+            commit_id: 3,
+            chain_signatures: vec![signature],
+            success: true,
+        };
+        let data = event.to_event_data();
+
+        let mut expected = ScheduledCommitConfirmed::discriminator().to_vec();
+        expected.extend_from_slice(&3u64.to_le_bytes());
+        expected.extend_from_slice(&1u32.to_le_bytes());
+        expected.extend_from_slice(signature.as_ref());
+        expected.push(1);
+        assert_eq!(data, expected);
+    }
+
+    #[test]
+    fn test_event_discriminators_differ() {
+        assert_ne!(
+            ScheduledCommitAccepted::discriminator(),
+            ScheduledCommitSent::discriminator()
+        );
+        assert_ne!(
+            ScheduledCommitSent::discriminator(),
+            ScheduledCommitConfirmed::discriminator()
+        );
+    }
+}
@@ -1,10 +1,20 @@
+pub mod commit_events;
+mod process_mark_ephemeral_only;
 mod process_schedule_commit;
+mod process_scheduled_commit_confirmed;
 mod process_scheduled_commit_sent;
+mod process_set_commit_authority;
 pub(crate) mod transaction_scheduler;
+pub(crate) use process_mark_ephemeral_only::*;
 pub(crate) use process_schedule_commit::*;
+pub use process_scheduled_commit_confirmed::{
+    process_scheduled_commit_confirmed, ConfirmedCommit,
+};
+pub(crate) use process_scheduled_commit_sent::SentCommitPrintable;
 pub use process_scheduled_commit_sent::{
-    process_scheduled_commit_sent, register_scheduled_commit_sent, SentCommit,
+    process_scheduled_commit_sent, SentCommit,
 };
+pub(crate) use process_set_commit_authority::*;
 
 #[cfg(test)]
 mod process_schedule_commit_tests;
@@ -0,0 +1,219 @@
+use std::collections::HashSet;
+
+use solana_program_runtime::{ic_msg, invoke_context::InvokeContext};
+use solana_sdk::{
+    account::ReadableAccount, account_utils::StateMut,
+    instruction::InstructionError, pubkey::Pubkey,
+};
+
+use crate::{
+    magic_context::MagicContext,
+    schedule_transactions::check_magic_context_id,
+    utils::accounts::{
+        get_instruction_account_with_idx, get_instruction_pubkey_with_idx,
+    },
+};
+
+/// Marks the provided delegated accounts as ephemeral-only such that they
+/// are never committed and handed back empty once undelegated, such that the
+/// owner program can close them and send their rent to the `refund` address.
+/// Only the program owning the accounts can do this by invoking us via CPI.
+pub(crate) fn process_mark_ephemeral_only(
+    signers: HashSet<Pubkey>,
+    invoke_context: &mut InvokeContext,
+    refund: Pubkey,
+) -> Result<(), InstructionError> {
+    const PAYER_IDX: u16 = 0;
+    const MAGIC_CONTEXT_IDX: u16 = PAYER_IDX + 1;
+    const ACCOUNTS_START: usize = MAGIC_CONTEXT_IDX as usize + 1;
+
+    check_magic_context_id(invoke_context, MAGIC_CONTEXT_IDX)?;
+
+    let transaction_context = &invoke_context.transaction_context.clone();
+    let ix_ctx = transaction_context.get_current_instruction_context()?;
+    let ix_accs_len = ix_ctx.get_number_of_instruction_accounts() as usize;
+
+    // Assert enough accounts
+    if ix_accs_len <= ACCOUNTS_START {
+        ic_msg!(
+            invoke_context,
+            "MarkEphemeralOnly ERR: not enough accounts ({}), need payer, magic context and at least one delegated account",
+            ix_accs_len
+        );
+        return Err(InstructionError::NotEnoughAccountKeys);
+    }
+
+    // Assert Payer is signer
+    let payer_pubkey =
+        get_instruction_pubkey_with_idx(transaction_context, PAYER_IDX)?;
+    if !signers.contains(payer_pubkey) {
+        ic_msg!(
+            invoke_context,
+            "MarkEphemeralOnly ERR: payer pubkey {} not in signers",
+            payer_pubkey
+        );
+        return Err(InstructionError::MissingRequiredSignature);
+    }
+
+    //
+    // Get the program_id of the parent instruction that invoked this one via CPI
+    //
+
+    // We cannot easily simulate the transaction being invoked via CPI
+    // from the owning program during unit tests
+    // Instead the integration tests ensure that this works as expected
+    #[cfg(not(test))]
+    let frames = crate::utils::instruction_context_frames::InstructionContextFrames::try_from(transaction_context)?;
+    #[cfg(not(test))]
+    let parent_program_id = frames
+        .find_program_id_of_parent_of_current_instruction()
+        .ok_or_else(|| {
+            ic_msg!(
+                invoke_context,
+                "MarkEphemeralOnly ERR: failed to find parent program id"
+            );
+            InstructionError::InvalidInstructionData
+        })?;
+
+    // During unit tests we assume the first account has the correct program ID
+    #[cfg(test)]
+    let first_account_owner = {
+        *get_instruction_account_with_idx(
+            transaction_context,
+            ACCOUNTS_START as u16,
+        )?
+        .borrow()
+        .owner()
+    };
+    #[cfg(test)]
+    let parent_program_id = &first_account_owner;
+
+    // Assert all accounts are owned by the invoking program, since skipping
+    // their commits and closing them discards their state
+    let mut pubkeys = Vec::new();
+    for idx in ACCOUNTS_START..ix_accs_len {
+        let acc_pubkey =
+            get_instruction_pubkey_with_idx(transaction_context, idx as u16)?;
+        let acc =
+            get_instruction_account_with_idx(transaction_context, idx as u16)?;
+        if parent_program_id != acc.borrow().owner() {
+            ic_msg!(
+                invoke_context,
+                "MarkEphemeralOnly ERR: account {} needs to be owned by the invoking program {}, but is owned by {}",
+                acc_pubkey, parent_program_id, acc.borrow().owner()
+            );
+            return Err(InstructionError::InvalidAccountOwner);
+        }
+        pubkeys.push(*acc_pubkey);
+    }
+
+    // Store the accounts in the MagicContext, like scheduled commits this
+    // only takes effect if the transaction including this instruction succeeds
+    let context_acc = get_instruction_account_with_idx(
+        transaction_context,
+        MAGIC_CONTEXT_IDX,
+    )?;
+    let context_data = &mut context_acc.borrow_mut();
+    let mut context =
+        MagicContext::deserialize(context_data).map_err(|err| {
+            ic_msg!(
+                invoke_context,
+                "MarkEphemeralOnly ERR: failed to deserialize MagicContext: {}",
+                err
+            );
+            InstructionError::GenericError
+        })?;
+    for pubkey in pubkeys {
+        context.mark_ephemeral_only(pubkey, refund).map_err(|err| {
+            err.into_instruction_error(invoke_context, "MarkEphemeralOnly")
+        })?;
+        ic_msg!(
+            invoke_context,
+            "MarkEphemeralOnly: account {} with refund to {}",
+            pubkey,
+            refund
+        );
+    }
+    context_data.set_state(&context)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::clock::Clock;
+
+    use super::*;
+    use crate::{
+        magic_context::EphemeralOnlyAccount,
+        magicblock_instruction::mark_ephemeral_only_instruction,
+        test_utils::{
+            empty_magic_context, prepare_accounts, process_instruction,
+            MAGIC_CONTEXT_ACC_IDX,
+        },
+    };
+
+    #[test]
+    fn test_mark_ephemeral_only() {
+        let (payer, lobby, program, refund) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let transaction_accounts = prepare_accounts(
+            Clock::default(),
+            payer,
+            empty_magic_context(),
+            &[(lobby, program)],
+        );
+        let ix = mark_ephemeral_only_instruction(&payer, vec![lobby], refund);
+        let accounts = process_instruction(
+            ix.data.as_slice(),
+            transaction_accounts,
+            ix.accounts,
+            Ok(()),
+        );
+        let magic_context = bincode::deserialize::<MagicContext>(
+            accounts[MAGIC_CONTEXT_ACC_IDX].data(),
+        )
+        .unwrap();
+        assert_eq!(
+            magic_context.ephemeral_only_accounts,
+            vec![EphemeralOnlyAccount {
+                account: lobby,
+                refund,
+            }]
+        );
+        assert!(!MagicContext::has_pending_items(
+            accounts[MAGIC_CONTEXT_ACC_IDX].data()
+        ));
+    }
+
+    #[test]
+    fn test_mark_ephemeral_only_for_account_not_owned_by_invoker() {
+        let (payer, lobby, other_lobby, program) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let transaction_accounts = prepare_accounts(
+            Clock::default(),
+            payer,
+            empty_magic_context(),
+            &[(lobby, program), (other_lobby, Pubkey::new_unique())],
+        );
+        let ix = mark_ephemeral_only_instruction(
+            &payer,
+            vec![lobby, other_lobby],
+            Pubkey::new_unique(),
+        );
+        process_instruction(
+            ix.data.as_slice(),
+            transaction_accounts,
+            ix.accounts,
+            Err(InstructionError::InvalidAccountOwner),
+        );
+    }
+}
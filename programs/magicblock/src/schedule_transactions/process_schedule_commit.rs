@@ -3,32 +3,47 @@ use std::{
     sync::atomic::{AtomicU64, Ordering},
 };
 
+use magicblock_commit_errors::ScheduleCommitError;
 use magicblock_core::magic_program::MAGIC_CONTEXT_PUBKEY;
+use magicblock_metrics::metrics;
+use magicblock_telemetry::{
+    child_span, register_commit_trace_context, KeyValue, TraceContext,
+};
 use solana_program_runtime::{ic_msg, invoke_context::InvokeContext};
 use solana_sdk::{
-    account::ReadableAccount, instruction::InstructionError, pubkey::Pubkey,
+    account::ReadableAccount, hash::hash, instruction::InstructionError,
+    pubkey::Pubkey,
 };
 
 use crate::{
+    errors::custom_error_codes,
     magic_context::{MagicContext, ScheduledCommit},
-    magicblock_instruction::scheduled_commit_sent,
-    schedule_transactions::transaction_scheduler::TransactionScheduler,
+    magicblock_instruction::{scheduled_commit_sent, CommitSlotTarget},
+    schedule_transactions::{
+        commit_events::{emit_event, ScheduledCommitAccepted},
+        transaction_scheduler::TransactionScheduler,
+    },
     utils::{
         account_actions::set_account_owner_to_delegation_program,
         accounts::{
             get_instruction_account_with_idx, get_instruction_pubkey_with_idx,
         },
     },
-    validator::validator_authority_id,
+    validator_context::ValidatorContext,
 };
 
 #[derive(Default)]
 pub(crate) struct ProcessScheduleCommitOptions {
     pub request_undelegation: bool,
+    pub commit_target: Option<CommitSlotTarget>,
+    /// Delayed commits targeting a slot further out are rejected
+    pub max_commit_delay_slots: u64,
+    pub commit_only_if_changed: bool,
 }
 
 pub(crate) fn process_schedule_commit(
     signers: HashSet<Pubkey>,
+    context: &ValidatorContext,
     invoke_context: &mut InvokeContext,
     opts: ProcessScheduleCommitOptions,
 ) -> Result<(), InstructionError> {
@@ -77,6 +92,18 @@ pub(crate) fn process_schedule_commit(
         return Err(InstructionError::MissingRequiredSignature);
     }
 
+    // Commits accepted before are replayed from the ledger as is, even if
+    // the validator runs offline now
+    if context.scheduled_commits_disabled() && !context.is_starting_up() {
+        ic_msg!(
+            invoke_context,
+            "ScheduleCommit ERR: the validator does not send commits to chain, i.e. since it runs offline"
+        );
+        return Err(InstructionError::Custom(
+            custom_error_codes::SCHEDULED_COMMITS_DISABLED,
+        ));
+    }
+
     //
     // Get the program_id of the parent instruction that invoked this one via CPI
     //
@@ -93,9 +120,12 @@ pub(crate) fn process_schedule_commit(
             .ok_or_else(|| {
                 ic_msg!(
                     invoke_context,
-                    "ScheduleCommit ERR: failed to find parent program id"
+                    "ScheduleCommit ERR: {}: failed to find parent program id",
+                    ScheduleCommitError::ParentProgramNotFound
                 );
-                InstructionError::InvalidInstructionData
+                InstructionError::Custom(
+                    ScheduleCommitError::ParentProgramNotFound.into(),
+                )
             })?;
 
         ic_msg!(
@@ -120,49 +150,98 @@ pub(crate) fn process_schedule_commit(
     #[cfg(test)]
     let parent_program_id = &first_committee_owner;
 
-    // Assert all PDAs are owned by invoking program
+    // Programs that were allowed to commit accounts they don't own by the
+    // program owning them
+    let magic_context = {
+        let context_acc = get_instruction_account_with_idx(
+            transaction_context,
+            MAGIC_CONTEXT_IDX,
+        )?;
+        let context_data = context_acc.borrow();
+        MagicContext::deserialize(&context_data).map_err(|err| {
+            ic_msg!(
+                invoke_context,
+                "ScheduleCommit ERR: failed to deserialize MagicContext: {}",
+                err
+            );
+            InstructionError::GenericError
+        })?
+    };
+
+    // Assert all PDAs are owned by invoking program or that it was allowed to
+    // commit them by the owning program
     // NOTE: we don't require them to be signers as in our case verifying that the
     // program owning the PDAs invoked us via CPI is sufficient
     // Thus we can be `invoke`d unsigned and no seeds need to be provided
     let mut pubkeys = Vec::new();
+    let mut data_hashes = Vec::new();
+    let mut closed_accounts = Vec::new();
     for idx in COMMITTEES_START..ix_accs_len {
         let acc_pubkey =
             get_instruction_pubkey_with_idx(transaction_context, idx as u16)?;
         let acc =
             get_instruction_account_with_idx(transaction_context, idx as u16)?;
 
+        if parent_program_id != acc.borrow().owner() {
+            let is_commit_authority = magic_context.is_commit_authority(
+                acc_pubkey,
+                acc.borrow().owner(),
+                parent_program_id,
+            );
+            if !is_commit_authority {
+                ic_msg!(
+                    invoke_context,
+                    "ScheduleCommit ERR: {}: account {} needs to be owned by the invoking program {} to be committed, but is owned by {}",
+                    ScheduleCommitError::AccountNotOwnedByInvoker,
+                    acc_pubkey, parent_program_id, acc.borrow().owner()
+                );
+                return Err(InstructionError::Custom(
+                    ScheduleCommitError::AccountNotOwnedByInvoker.into(),
+                ));
+            }
+            // Undelegation returns the account to the program owning the
+            // commit, thus only the actual owner may request it
+            if opts.request_undelegation {
+                ic_msg!(
+                    invoke_context,
+                    "ScheduleCommit ERR: {}: account {} can only be undelegated by its owner {}, not by the commit authority {}",
+                    ScheduleCommitError::UndelegationNotByOwner,
+                    acc_pubkey, acc.borrow().owner(), parent_program_id
+                );
+                return Err(InstructionError::Custom(
+                    ScheduleCommitError::UndelegationNotByOwner.into(),
+                ));
+            }
+        }
+        // Ephemeral-only accounts are never committed, only closed once
+        // they are undelegated
+        if let Some(ephemeral_only) =
+            magic_context.ephemeral_only_account(acc_pubkey)
         {
-            if parent_program_id != acc.borrow().owner() {
+            if !opts.request_undelegation {
                 ic_msg!(
-                invoke_context,
-                "ScheduleCommit ERR: account {} needs to be owned by the invoking program {} to be committed, but is owned by {}",
-                acc_pubkey, parent_program_id, acc.borrow().owner()
-            );
-                return Err(InstructionError::InvalidAccountOwner);
+                    invoke_context,
+                    "ScheduleCommit: skipping ephemeral-only account {}",
+                    acc_pubkey
+                );
+                continue;
             }
-            pubkeys.push(*acc_pubkey);
+            closed_accounts.push(ephemeral_only.clone());
         }
-
-        if opts.request_undelegation {
-            // If the account is scheduled to be undelegated then we need to lock it
-            // immediately in order to prevent the following actions:
-            // - writes to the account
-            // - scheduling further commits for this account
-            //
-            // Setting the owner will prevent both, since in both cases the _actual_
-            // owner program needs to sign for the account which is not possible at
-            // that point
-            // NOTE: this owner change only takes effect if the transaction which
-            // includes this instruction succeeds.
-            set_account_owner_to_delegation_program(acc);
-            ic_msg!(
-                invoke_context,
-                "ScheduleCommit: account {} owner set to delegation program",
-                acc_pubkey
-            );
+        pubkeys.push(*acc_pubkey);
+        if opts.commit_only_if_changed {
+            data_hashes.push(hash(acc.borrow().data()));
         }
     }
 
+    if pubkeys.is_empty() {
+        ic_msg!(
+            invoke_context,
+            "ScheduleCommit: all accounts are ephemeral-only, no commit scheduled"
+        );
+        return Ok(());
+    }
+
     // Determine id and slot
     let commit_id = COMMIT_ID.fetch_add(1, Ordering::Relaxed);
 
@@ -177,8 +256,35 @@ pub(crate) fn process_schedule_commit(
                 InstructionError::UnsupportedSysvar
             })?;
 
+    let commit_at_slot = opts.commit_target.map(|target| match target {
+        CommitSlotTarget::Slot(slot) => slot,
+        CommitSlotTarget::Delay(delay) => clock.slot.saturating_add(delay),
+    });
+    if let Some(commit_at_slot) = commit_at_slot {
+        // Commits accepted before are replayed from the ledger as is, even
+        // if the maximum was lowered since
+        let delay = commit_at_slot.saturating_sub(clock.slot);
+        if delay > opts.max_commit_delay_slots && !context.is_starting_up() {
+            ic_msg!(
+                invoke_context,
+                "ScheduleCommit ERR: commit delayed by {} slots, but at most {} are allowed",
+                delay,
+                opts.max_commit_delay_slots
+            );
+            return Err(InstructionError::Custom(
+                custom_error_codes::COMMIT_DELAY_TOO_LONG,
+            ));
+        }
+        ic_msg!(
+            invoke_context,
+            "ScheduleCommit: commit delayed until slot {}",
+            commit_at_slot
+        );
+    }
+
     let blockhash = invoke_context.blockhash;
-    let commit_sent_transaction = scheduled_commit_sent(commit_id, blockhash);
+    let commit_sent_transaction =
+        scheduled_commit_sent(context, commit_id, blockhash);
 
     let commit_sent_sig = commit_sent_transaction.signatures[0];
     let scheduled_commit = ScheduledCommit {
@@ -190,11 +296,15 @@ pub(crate) fn process_schedule_commit(
         owner: *parent_program_id,
         commit_sent_transaction,
         request_undelegation: opts.request_undelegation,
+        commit_at_slot,
+        data_hashes: opts.commit_only_if_changed.then_some(data_hashes),
+        closed_accounts,
     };
 
-    // NOTE: this is only protected by all the above checks however if the
-    // instruction fails for other reasons detected afterward then the commit
-    // stays scheduled
+    // NOTE: the commit is stored in the MagicContext account which is only
+    // persisted if the transaction including this instruction succeeds.
+    // Thus if the transaction fails for any reason after this instruction ran,
+    // the commit is never accepted and thus never realized.
     let context_acc = get_instruction_account_with_idx(
         transaction_context,
         MAGIC_CONTEXT_IDX,
@@ -210,8 +320,52 @@ pub(crate) fn process_schedule_commit(
             "ScheduleCommit ERR: failed to schedule commit: {}",
             err
         );
-        InstructionError::GenericError
+        match err {
+            // Program errors, i.e. an exceeded MagicContext capacity are
+            // surfaced as is
+            InstructionError::Custom(_) => err,
+            _ => InstructionError::GenericError,
+        }
     })?;
+
+    // The transaction is executed with the trace context of the RPC request
+    // attached, we link the commit to it in order to trace it once processed
+    let trace_context = child_span(
+        "schedule_commit",
+        &TraceContext::current(),
+        vec![KeyValue::new("commit_id", commit_id as i64)],
+    );
+    register_commit_trace_context(commit_id, &trace_context);
+
+    if opts.request_undelegation {
+        // If the accounts are scheduled to be undelegated then we need to lock them
+        // immediately in order to prevent the following actions:
+        // - writes to the account
+        // - scheduling further commits for this account
+        //
+        // Setting the owner will prevent both, since in both cases the _actual_
+        // owner program needs to sign for the account which is not possible at
+        // that point
+        // We only do this once all checks passed and the commit was scheduled.
+        // NOTE: same as the scheduled commit, this owner change only takes effect if
+        // the transaction which includes this instruction succeeds.
+        for idx in COMMITTEES_START..ix_accs_len {
+            let acc_pubkey = get_instruction_pubkey_with_idx(
+                transaction_context,
+                idx as u16,
+            )?;
+            let acc = get_instruction_account_with_idx(
+                transaction_context,
+                idx as u16,
+            )?;
+            set_account_owner_to_delegation_program(acc);
+            ic_msg!(
+                invoke_context,
+                "ScheduleCommit: account {} owner set to delegation program",
+                acc_pubkey
+            );
+        }
+    }
     ic_msg!(invoke_context, "Scheduled commit with ID: {}", commit_id,);
     ic_msg!(
         invoke_context,
@@ -224,6 +378,7 @@ pub(crate) fn process_schedule_commit(
 
 pub fn process_accept_scheduled_commits(
     signers: HashSet<Pubkey>,
+    context: &ValidatorContext,
     invoke_context: &mut InvokeContext,
 ) -> Result<(), InstructionError> {
     const VALIDATOR_AUTHORITY_IDX: u16 = 0;
@@ -249,10 +404,12 @@ pub fn process_accept_scheduled_commits(
                 );
                 InstructionError::InvalidAccountData
             })?;
-    if magic_context.scheduled_commits.is_empty() {
+    if magic_context.scheduled_commits.is_empty()
+        && magic_context.escrow_settlements.is_empty()
+    {
         ic_msg!(
             invoke_context,
-            "AcceptScheduledCommits: no scheduled commits to accept"
+            "AcceptScheduledCommits: no scheduled commits or escrow settlements to accept"
         );
         // NOTE: we should have not been called if no commits are scheduled
         return Ok(());
@@ -263,7 +420,7 @@ pub fn process_accept_scheduled_commits(
         transaction_context,
         VALIDATOR_AUTHORITY_IDX,
     )?;
-    let validator_auth = validator_authority_id();
+    let validator_auth = context.validator_authority_id_for_signers(&signers);
     if !provided_validator_auth.eq(&validator_auth) {
         ic_msg!(
              invoke_context,
@@ -283,13 +440,45 @@ pub fn process_accept_scheduled_commits(
     }
 
     // 3. Move scheduled commits (without copying)
-    let scheduled_commits = magic_context.take_scheduled_commits();
+    //    If too many were scheduled the remaining ones are left in the
+    //    context to be accepted in another pass
+    let scheduled_commits = magic_context
+        .take_scheduled_commits(MagicContext::MAX_COMMITS_ACCEPTED_PER_PASS);
     ic_msg!(
         invoke_context,
-        "AcceptScheduledCommits: accepted {} scheduled commit(s)",
-        scheduled_commits.len()
+        "AcceptScheduledCommits: accepted {} scheduled commit(s), {} remaining",
+        scheduled_commits.len(),
+        magic_context.scheduled_commits.len()
     );
-    TransactionScheduler::default().accept_scheduled_commits(scheduled_commits);
+    for commit in &scheduled_commits {
+        emit_event(
+            invoke_context,
+            &ScheduledCommitAccepted {
+                commit_id: commit.id,
+                slot: commit.slot,
+                payer: commit.payer,
+                owner: commit.owner,
+                accounts: commit.accounts.clone(),
+                request_undelegation: commit.request_undelegation,
+                commit_at_slot: commit.commit_at_slot,
+            },
+        );
+    }
+    context
+        .transaction_scheduler()
+        .accept_scheduled_commits(scheduled_commits);
+
+    let escrow_settlements = magic_context.take_escrow_settlements();
+    if !escrow_settlements.is_empty() {
+        ic_msg!(
+            invoke_context,
+            "AcceptScheduledCommits: accepted {} escrow settlement(s)",
+            escrow_settlements.len()
+        );
+        context
+            .transaction_scheduler()
+            .accept_escrow_settlements(escrow_settlements);
+    }
 
     // 4. Serialize and store the updated `MagicContext` account
     // Zero fill account before updating data
@@ -310,11 +499,17 @@ pub fn process_accept_scheduled_commits(
             );
             InstructionError::GenericError
         })?;
+    if let Ok(size) = magic_context.serialized_size() {
+        metrics::set_magic_context_occupancy(
+            magic_context.scheduled_commits.len(),
+            size,
+        );
+    }
 
     Ok(())
 }
 
-fn check_magic_context_id(
+pub(crate) fn check_magic_context_id(
     invoke_context: &InvokeContext,
     idx: u16,
 ) -> Result<(), InstructionError> {
@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 
 use assert_matches::assert_matches;
+use magicblock_commit_errors::ScheduleCommitError;
 use magicblock_core::magic_program::MAGIC_CONTEXT_PUBKEY;
 use solana_sdk::{
     account::{
@@ -8,25 +9,33 @@ use solana_sdk::{
     },
     clock,
     fee_calculator::DEFAULT_TARGET_LAMPORTS_PER_SIGNATURE,
+    hash::{hash, Hash},
     instruction::{AccountMeta, Instruction, InstructionError},
     pubkey::Pubkey,
     signature::Keypair,
     signer::{SeedDerivable, Signer},
     system_program,
     sysvar::SysvarId,
+    transaction::Transaction,
 };
 use test_tools_core::init_logger;
 
 use crate::{
-    magic_context::MagicContext,
+    errors::custom_error_codes,
+    magic_context::{CommitAuthority, EphemeralOnlyAccount, MagicContext},
     magicblock_instruction::{
         accept_scheduled_commits_instruction,
         schedule_commit_and_undelegate_instruction,
-        schedule_commit_instruction, MagicBlockInstruction,
+        schedule_commit_instruction, schedule_conditional_commit_instruction,
+        schedule_delayed_commit_instruction, CommitSlotTarget,
+        MagicBlockInstruction,
+    },
+    test_utils::{
+        empty_magic_context, ensure_started_validator, process_instruction,
+        test_context,
     },
-    schedule_transactions::transaction_scheduler::TransactionScheduler,
-    test_utils::{ensure_started_validator, process_instruction},
     utils::DELEGATION_PROGRAM_ID,
+    validator_context::DEFAULT_MAX_COMMIT_DELAY_SLOTS,
     ScheduledCommit,
 };
 
@@ -147,8 +156,9 @@ fn assert_non_accepted_commits<'a>(
     let magic_context =
         bincode::deserialize::<MagicContext>(magic_context_acc.data()).unwrap();
 
-    let accepted_scheduled_commits =
-        TransactionScheduler::default().get_scheduled_commits_by_payer(payer);
+    let accepted_scheduled_commits = test_context()
+        .transaction_scheduler()
+        .get_scheduled_commits_by_payer(payer);
     assert_eq!(
         magic_context.scheduled_commits.len(),
         expected_non_accepted_commits
@@ -168,8 +178,9 @@ fn assert_accepted_commits(
     let magic_context =
         bincode::deserialize::<MagicContext>(magic_context_acc.data()).unwrap();
 
-    let scheduled_commits =
-        TransactionScheduler::default().get_scheduled_commits_by_payer(payer);
+    let scheduled_commits = test_context()
+        .transaction_scheduler()
+        .get_scheduled_commits_by_payer(payer);
 
     assert_eq!(magic_context.scheduled_commits.len(), 0);
     assert_eq!(scheduled_commits.len(), expected_scheduled_commits);
@@ -213,6 +224,7 @@ fn assert_first_commit(
     owner: &Pubkey,
     committees: &[Pubkey],
     expected_request_undelegation: bool,
+    expected_commit_at_slot: Option<clock::Slot>,
 ) {
     let commit = &scheduled_commits[0];
     let test_clock = get_clock();
@@ -227,6 +239,9 @@ fn assert_first_commit(
             blockhash: _,
             commit_sent_transaction,
             request_undelegation,
+            commit_at_slot,
+            data_hashes,
+            closed_accounts,
         } => {
             assert!(id >= &0);
             assert_eq!(slot, &test_clock.slot);
@@ -236,6 +251,9 @@ fn assert_first_commit(
             let instruction = MagicBlockInstruction::ScheduledCommitSent(*id);
             assert_eq!(commit_sent_transaction.data(0), instruction.try_to_vec().unwrap());
             assert_eq!(*request_undelegation, expected_request_undelegation);
+            assert_eq!(*commit_at_slot, expected_commit_at_slot);
+            assert!(data_hashes.is_none());
+            assert!(closed_accounts.is_empty());
         }
     );
 }
@@ -288,7 +306,9 @@ fn test_schedule_commit_single_account_success() {
                 &payer, program, committee,
             );
 
-        let ix = accept_scheduled_commits_instruction();
+        let ix = accept_scheduled_commits_instruction(
+            &test_context().validator_authority_id(),
+        );
         extend_transaction_accounts_from_ix_adding_magic_context(
             &ix,
             &magic_context_acc,
@@ -313,6 +333,7 @@ fn test_schedule_commit_single_account_success() {
             &program,
             &[committee],
             false,
+            None,
         );
     }
     let committed_account = processed_scheduled.last().unwrap();
@@ -370,7 +391,9 @@ fn test_schedule_commit_single_account_and_request_undelegate_success() {
                 &payer, program, committee,
             );
 
-        let ix = accept_scheduled_commits_instruction();
+        let ix = accept_scheduled_commits_instruction(
+            &test_context().validator_authority_id(),
+        );
         extend_transaction_accounts_from_ix_adding_magic_context(
             &ix,
             &magic_context_acc,
@@ -395,12 +418,215 @@ fn test_schedule_commit_single_account_and_request_undelegate_success() {
             &program,
             &[committee],
             true,
+            None,
         );
     }
     let committed_account = processed_scheduled.last().unwrap();
     assert_eq!(*committed_account.owner(), DELEGATION_PROGRAM_ID);
 }
 
+#[test]
+fn test_schedule_delayed_commit_single_account_success() {
+    init_logger!();
+    let payer =
+        Keypair::from_seed(b"schedule_delayed_commit_single_account").unwrap();
+    let program = Pubkey::new_unique();
+    let committee = Pubkey::new_unique();
+    let delay = 10;
+
+    // 1. We run the transaction that registers the intent to schedule a commit
+    let magic_context_acc = {
+        let (mut account_data, mut transaction_accounts) =
+            prepare_transaction_with_single_committee(
+                &payer, program, committee,
+            );
+
+        let ix = schedule_delayed_commit_instruction(
+            &payer.pubkey(),
+            vec![committee],
+            CommitSlotTarget::Delay(delay),
+            false,
+        );
+
+        extend_transaction_accounts_from_ix(
+            &ix,
+            &mut account_data,
+            &mut transaction_accounts,
+        );
+
+        let processed_scheduled = process_instruction(
+            ix.data.as_slice(),
+            transaction_accounts.clone(),
+            ix.accounts,
+            Ok(()),
+        );
+
+        assert_non_accepted_commits(&processed_scheduled, &payer.pubkey(), 1)
+            .clone()
+    };
+
+    // 2. We run the transaction that accepts the scheduled commit
+    {
+        let (mut account_data, mut transaction_accounts) =
+            prepare_transaction_with_single_committee(
+                &payer, program, committee,
+            );
+
+        let ix = accept_scheduled_commits_instruction(
+            &test_context().validator_authority_id(),
+        );
+        extend_transaction_accounts_from_ix_adding_magic_context(
+            &ix,
+            &magic_context_acc,
+            &mut account_data,
+            &mut transaction_accounts,
+        );
+
+        let processed_accepted = process_instruction(
+            ix.data.as_slice(),
+            transaction_accounts,
+            ix.accounts,
+            Ok(()),
+        );
+
+        let scheduled_commits =
+            assert_accepted_commits(&processed_accepted, &payer.pubkey(), 1);
+
+        let commit_at_slot = get_clock().slot + delay;
+        assert_first_commit(
+            &scheduled_commits,
+            &payer.pubkey(),
+            &program,
+            &[committee],
+            false,
+            Some(commit_at_slot),
+        );
+
+        let commit = &scheduled_commits[0];
+        assert!(!commit.is_due(commit_at_slot - 1));
+        assert!(commit.is_due(commit_at_slot));
+    }
+}
+
+#[test]
+fn test_schedule_delayed_commit_exceeding_max_delay() {
+    init_logger!();
+    let payer =
+        Keypair::from_seed(b"schedule_delayed_commit_exceeding_max_delay")
+            .unwrap();
+    let program = Pubkey::new_unique();
+    let committee = Pubkey::new_unique();
+
+    for target in [
+        CommitSlotTarget::Delay(DEFAULT_MAX_COMMIT_DELAY_SLOTS + 1),
+        CommitSlotTarget::Slot(
+            get_clock().slot + DEFAULT_MAX_COMMIT_DELAY_SLOTS + 1,
+        ),
+    ] {
+        let (mut account_data, mut transaction_accounts) =
+            prepare_transaction_with_single_committee(
+                &payer, program, committee,
+            );
+
+        let ix = schedule_delayed_commit_instruction(
+            &payer.pubkey(),
+            vec![committee],
+            target,
+            false,
+        );
+        extend_transaction_accounts_from_ix(
+            &ix,
+            &mut account_data,
+            &mut transaction_accounts,
+        );
+
+        process_instruction(
+            ix.data.as_slice(),
+            transaction_accounts,
+            ix.accounts,
+            Err(InstructionError::Custom(
+                custom_error_codes::COMMIT_DELAY_TOO_LONG,
+            )),
+        );
+    }
+}
+
+#[test]
+fn test_schedule_commit_while_scheduled_commits_disabled() {
+    init_logger!();
+    let payer =
+        Keypair::from_seed(b"schedule_commit_while_commits_disabled").unwrap();
+    let program = Pubkey::new_unique();
+    let committee = Pubkey::new_unique();
+    test_context().disable_scheduled_commits();
+
+    let (mut account_data, mut transaction_accounts) =
+        prepare_transaction_with_single_committee(&payer, program, committee);
+
+    let ix = schedule_commit_instruction(&payer.pubkey(), vec![committee]);
+    extend_transaction_accounts_from_ix(
+        &ix,
+        &mut account_data,
+        &mut transaction_accounts,
+    );
+
+    process_instruction(
+        ix.data.as_slice(),
+        transaction_accounts,
+        ix.accounts,
+        Err(InstructionError::Custom(
+            custom_error_codes::SCHEDULED_COMMITS_DISABLED,
+        )),
+    );
+}
+
+#[test]
+fn test_schedule_conditional_commit_single_account_hashes_data() {
+    init_logger!();
+    let payer =
+        Keypair::from_seed(b"schedule_conditional_commit_single_account")
+            .unwrap();
+    let program = Pubkey::new_unique();
+    let committee = Pubkey::new_unique();
+
+    let (mut account_data, mut transaction_accounts) =
+        prepare_transaction_with_single_committee(&payer, program, committee);
+    account_data.insert(
+        committee,
+        AccountSharedData::from(solana_sdk::account::Account {
+            lamports: 0,
+            data: vec![1, 2, 3],
+            owner: program,
+            ..Default::default()
+        }),
+    );
+
+    let ix = schedule_conditional_commit_instruction(
+        &payer.pubkey(),
+        vec![committee],
+    );
+    extend_transaction_accounts_from_ix(
+        &ix,
+        &mut account_data,
+        &mut transaction_accounts,
+    );
+
+    let processed_scheduled = process_instruction(
+        ix.data.as_slice(),
+        transaction_accounts,
+        ix.accounts,
+        Ok(()),
+    );
+
+    let magic_context_acc =
+        assert_non_accepted_commits(&processed_scheduled, &payer.pubkey(), 1);
+    let magic_context =
+        bincode::deserialize::<MagicContext>(magic_context_acc.data()).unwrap();
+    let commit = &magic_context.scheduled_commits[0];
+    assert_eq!(commit.accounts, vec![committee]);
+    assert_eq!(commit.data_hashes, Some(vec![hash(&[1, 2, 3])]));
+}
+
 #[test]
 fn test_schedule_commit_three_accounts_success() {
     init_logger!();
@@ -473,7 +699,9 @@ fn test_schedule_commit_three_accounts_success() {
             Some((committee_uno, committee_dos, committee_tres)),
         );
 
-        let ix = accept_scheduled_commits_instruction();
+        let ix = accept_scheduled_commits_instruction(
+            &test_context().validator_authority_id(),
+        );
         extend_transaction_accounts_from_ix_adding_magic_context(
             &ix,
             &magic_context_acc,
@@ -498,6 +726,7 @@ fn test_schedule_commit_three_accounts_success() {
             &program,
             &[committee_uno, committee_dos, committee_tres],
             false,
+            None,
         );
         for _ in &[committee_uno, committee_dos, committee_tres] {
             let committed_account = processed_scheduled.pop().unwrap();
@@ -578,7 +807,9 @@ fn test_schedule_commit_three_accounts_and_request_undelegate_success() {
             Some((committee_uno, committee_dos, committee_tres)),
         );
 
-        let ix = accept_scheduled_commits_instruction();
+        let ix = accept_scheduled_commits_instruction(
+            &test_context().validator_authority_id(),
+        );
         extend_transaction_accounts_from_ix_adding_magic_context(
             &ix,
             &magic_context_acc,
@@ -603,6 +834,7 @@ fn test_schedule_commit_three_accounts_and_request_undelegate_success() {
             &program,
             &[committee_uno, committee_dos, committee_tres],
             true,
+            None,
         );
         for _ in &[committee_uno, committee_dos, committee_tres] {
             let committed_account = processed_scheduled.pop().unwrap();
@@ -611,6 +843,225 @@ fn test_schedule_commit_three_accounts_and_request_undelegate_success() {
     }
 }
 
+fn magic_context_with_commit_authority(
+    account: Pubkey,
+    program: Pubkey,
+    authority: Pubkey,
+) -> AccountSharedData {
+    let mut magic_context_acc = empty_magic_context();
+    let magic_context = MagicContext {
+        commit_authorities: vec![CommitAuthority {
+            account,
+            authority,
+            program,
+        }],
+        ..Default::default()
+    };
+    magic_context_acc.serialize_data(&magic_context).unwrap();
+    magic_context_acc
+}
+
+#[test]
+fn test_schedule_commit_three_accounts_second_owned_by_partner_program_with_commit_authority(
+) {
+    init_logger!();
+
+    let payer = Keypair::from_seed(b"schedule_commit_partner_commit_authority")
+        .unwrap();
+
+    let PreparedTransactionThreeCommittees {
+        mut accounts_data,
+        committee_uno,
+        committee_dos,
+        committee_tres,
+        mut transaction_accounts,
+        program,
+    } = prepare_transaction_with_three_committees(&payer, None);
+
+    // The partner program allowed the coordinator program to commit its account
+    let partner_program = Pubkey::new_unique();
+    accounts_data.insert(
+        committee_dos,
+        AccountSharedData::new(0, 0, &partner_program),
+    );
+    accounts_data.insert(
+        MAGIC_CONTEXT_PUBKEY,
+        magic_context_with_commit_authority(
+            committee_dos,
+            partner_program,
+            program,
+        ),
+    );
+
+    let ix = schedule_commit_instruction(
+        &payer.pubkey(),
+        vec![committee_uno, committee_dos, committee_tres],
+    );
+    extend_transaction_accounts_from_ix(
+        &ix,
+        &mut accounts_data,
+        &mut transaction_accounts,
+    );
+
+    let processed = process_instruction(
+        ix.data.as_slice(),
+        transaction_accounts,
+        ix.accounts,
+        Ok(()),
+    );
+
+    let magic_context_acc = find_magic_context_account(&processed)
+        .expect("magic context account not found");
+    let magic_context =
+        bincode::deserialize::<MagicContext>(magic_context_acc.data()).unwrap();
+    assert_eq!(magic_context.scheduled_commits.len(), 1);
+    assert_first_commit(
+        &magic_context.scheduled_commits,
+        &payer.pubkey(),
+        &program,
+        &[committee_uno, committee_dos, committee_tres],
+        false,
+        None,
+    );
+    // The authority is kept for further commits
+    assert!(magic_context.is_commit_authority(
+        &committee_dos,
+        &partner_program,
+        &program
+    ));
+}
+
+fn magic_context_with_ephemeral_only_account(
+    account: Pubkey,
+    refund: Pubkey,
+) -> AccountSharedData {
+    let mut magic_context_acc = empty_magic_context();
+    let magic_context = MagicContext {
+        ephemeral_only_accounts: vec![EphemeralOnlyAccount { account, refund }],
+        ..Default::default()
+    };
+    magic_context_acc.serialize_data(&magic_context).unwrap();
+    magic_context_acc
+}
+
+#[test]
+fn test_schedule_commit_three_accounts_second_ephemeral_only_is_skipped() {
+    init_logger!();
+
+    let payer =
+        Keypair::from_seed(b"schedule_commit_skips_ephemeral_only").unwrap();
+
+    let PreparedTransactionThreeCommittees {
+        mut accounts_data,
+        committee_uno,
+        committee_dos,
+        committee_tres,
+        mut transaction_accounts,
+        program,
+    } = prepare_transaction_with_three_committees(&payer, None);
+    accounts_data.insert(
+        MAGIC_CONTEXT_PUBKEY,
+        magic_context_with_ephemeral_only_account(
+            committee_dos,
+            Pubkey::new_unique(),
+        ),
+    );
+
+    let ix = schedule_commit_instruction(
+        &payer.pubkey(),
+        vec![committee_uno, committee_dos, committee_tres],
+    );
+    extend_transaction_accounts_from_ix(
+        &ix,
+        &mut accounts_data,
+        &mut transaction_accounts,
+    );
+
+    let processed = process_instruction(
+        ix.data.as_slice(),
+        transaction_accounts,
+        ix.accounts,
+        Ok(()),
+    );
+
+    let magic_context_acc = find_magic_context_account(&processed)
+        .expect("magic context account not found");
+    let magic_context =
+        bincode::deserialize::<MagicContext>(magic_context_acc.data()).unwrap();
+    assert_eq!(magic_context.scheduled_commits.len(), 1);
+    assert_first_commit(
+        &magic_context.scheduled_commits,
+        &payer.pubkey(),
+        &program,
+        &[committee_uno, committee_tres],
+        false,
+        None,
+    );
+    // The account stays ephemeral-only until it is undelegated
+    assert!(magic_context
+        .ephemeral_only_account(&committee_dos)
+        .is_some());
+}
+
+#[test]
+fn test_schedule_commit_and_undelegate_closes_ephemeral_only_account() {
+    init_logger!();
+
+    let payer =
+        Keypair::from_seed(b"schedule_undelegate_closes_ephemeral_only")
+            .unwrap();
+
+    let PreparedTransactionThreeCommittees {
+        mut accounts_data,
+        committee_uno,
+        committee_dos,
+        committee_tres,
+        mut transaction_accounts,
+        ..
+    } = prepare_transaction_with_three_committees(&payer, None);
+    let refund = Pubkey::new_unique();
+    accounts_data.insert(
+        MAGIC_CONTEXT_PUBKEY,
+        magic_context_with_ephemeral_only_account(committee_dos, refund),
+    );
+
+    let ix = schedule_commit_and_undelegate_instruction(
+        &payer.pubkey(),
+        vec![committee_uno, committee_dos, committee_tres],
+    );
+    extend_transaction_accounts_from_ix(
+        &ix,
+        &mut accounts_data,
+        &mut transaction_accounts,
+    );
+
+    let processed = process_instruction(
+        ix.data.as_slice(),
+        transaction_accounts,
+        ix.accounts,
+        Ok(()),
+    );
+
+    let magic_context_acc = find_magic_context_account(&processed)
+        .expect("magic context account not found");
+    let magic_context =
+        bincode::deserialize::<MagicContext>(magic_context_acc.data()).unwrap();
+    assert_eq!(magic_context.scheduled_commits.len(), 1);
+    let commit = &magic_context.scheduled_commits[0];
+    assert_eq!(
+        commit.accounts,
+        vec![committee_uno, committee_dos, committee_tres]
+    );
+    assert_eq!(
+        commit.closed_accounts,
+        vec![EphemeralOnlyAccount {
+            account: committee_dos,
+            refund,
+        }]
+    );
+    assert!(magic_context.ephemeral_only_accounts.is_empty());
+}
+
 // -----------------
 // Failure Cases
 // ----------------
@@ -715,6 +1166,235 @@ fn test_schedule_commit_three_accounts_second_not_owned_by_program() {
         ix.data.as_slice(),
         transaction_accounts,
         ix.accounts,
-        Err(InstructionError::InvalidAccountOwner),
+        Err(InstructionError::Custom(
+            ScheduleCommitError::AccountNotOwnedByInvoker.into(),
+        )),
+    );
+}
+
+#[test]
+fn test_schedule_commit_and_undelegate_three_accounts_third_not_owned_by_program(
+) {
+    init_logger!();
+
+    let payer =
+        Keypair::from_seed(b"undelegate_three_accounts_third_not_owned")
+            .unwrap();
+
+    let PreparedTransactionThreeCommittees {
+        mut accounts_data,
+        committee_uno,
+        committee_dos,
+        committee_tres,
+        mut transaction_accounts,
+        program,
+    } = prepare_transaction_with_three_committees(&payer, None);
+
+    accounts_data.insert(
+        committee_tres,
+        AccountSharedData::new(0, 0, &Pubkey::new_unique()),
+    );
+
+    let ix = schedule_commit_and_undelegate_instruction(
+        &payer.pubkey(),
+        vec![committee_uno, committee_dos, committee_tres],
+    );
+    extend_transaction_accounts_from_ix(
+        &ix,
+        &mut accounts_data,
+        &mut transaction_accounts,
+    );
+
+    let mut processed = process_instruction(
+        ix.data.as_slice(),
+        transaction_accounts,
+        ix.accounts,
+        Err(InstructionError::Custom(
+            ScheduleCommitError::AccountNotOwnedByInvoker.into(),
+        )),
+    );
+
+    // Neither is the commit scheduled nor are the valid accounts locked
+    // for undelegation
+    let magic_context_acc = find_magic_context_account(&processed)
+        .expect("magic context account not found");
+    assert!(!MagicContext::has_scheduled_commits(
+        magic_context_acc.data()
+    ));
+
+    processed.pop();
+    for _ in &[committee_uno, committee_dos] {
+        let account = processed.pop().unwrap();
+        assert_eq!(*account.owner(), program);
+    }
+}
+
+#[test]
+fn test_schedule_commit_and_undelegate_account_owned_by_partner_program_with_commit_authority(
+) {
+    init_logger!();
+
+    let payer =
+        Keypair::from_seed(b"undelegate_partner_commit_authority").unwrap();
+
+    let PreparedTransactionThreeCommittees {
+        mut accounts_data,
+        committee_uno,
+        committee_dos,
+        committee_tres,
+        mut transaction_accounts,
+        program,
+    } = prepare_transaction_with_three_committees(&payer, None);
+
+    let partner_program = Pubkey::new_unique();
+    accounts_data.insert(
+        committee_dos,
+        AccountSharedData::new(0, 0, &partner_program),
+    );
+    accounts_data.insert(
+        MAGIC_CONTEXT_PUBKEY,
+        magic_context_with_commit_authority(
+            committee_dos,
+            partner_program,
+            program,
+        ),
+    );
+
+    // Only the partner program itself may undelegate its account
+    let ix = schedule_commit_and_undelegate_instruction(
+        &payer.pubkey(),
+        vec![committee_uno, committee_dos, committee_tres],
+    );
+    extend_transaction_accounts_from_ix(
+        &ix,
+        &mut accounts_data,
+        &mut transaction_accounts,
+    );
+
+    process_instruction(
+        ix.data.as_slice(),
+        transaction_accounts,
+        ix.accounts,
+        Err(InstructionError::Custom(
+            ScheduleCommitError::UndelegationNotByOwner.into(),
+        )),
+    );
+}
+
+fn scheduled_commit(payer: &Pubkey, accounts: Vec<Pubkey>) -> ScheduledCommit {
+    ScheduledCommit {
+        id: 0,
+        slot: get_clock().slot,
+        blockhash: Hash::default(),
+        accounts,
+        payer: *payer,
+        owner: Pubkey::new_unique(),
+        commit_sent_transaction: Transaction::default(),
+        request_undelegation: false,
+        commit_at_slot: None,
+        data_hashes: None,
+        closed_accounts: vec![],
+    }
+}
+
+#[test]
+fn test_schedule_commit_exceeding_magic_context_capacity() {
+    init_logger!();
+
+    let payer =
+        Keypair::from_seed(b"schedule_commit_exceeding_capacity").unwrap();
+    let program = Pubkey::new_unique();
+    let committee = Pubkey::new_unique();
+
+    let (mut account_data, mut transaction_accounts) =
+        prepare_transaction_with_single_committee(&payer, program, committee);
+
+    // Fill the magic context up to the last few bytes
+    let mut magic_context = MagicContext {
+        scheduled_commits: vec![scheduled_commit(
+            &Pubkey::new_unique(),
+            vec![Pubkey::default(); MagicContext::SIZE / 32],
+        )],
+        ..Default::default()
+    };
+    while magic_context.serialized_size().unwrap() > MagicContext::SIZE as u64 {
+        magic_context.scheduled_commits[0].accounts.pop();
+    }
+    let mut magic_context_acc = empty_magic_context();
+    magic_context_acc.serialize_data(&magic_context).unwrap();
+    account_data.insert(MAGIC_CONTEXT_PUBKEY, magic_context_acc);
+
+    let ix = schedule_commit_instruction(&payer.pubkey(), vec![committee]);
+    extend_transaction_accounts_from_ix(
+        &ix,
+        &mut account_data,
+        &mut transaction_accounts,
+    );
+
+    process_instruction(
+        ix.data.as_slice(),
+        transaction_accounts,
+        ix.accounts,
+        Err(InstructionError::Custom(
+            custom_error_codes::MAGIC_CONTEXT_CAPACITY_EXCEEDED,
+        )),
+    );
+}
+
+#[test]
+fn test_accept_scheduled_commits_in_chunks() {
+    init_logger!();
+
+    let payer =
+        Keypair::from_seed(b"accept_scheduled_commits_in_chunks").unwrap();
+    let program = Pubkey::new_unique();
+    let committee = Pubkey::new_unique();
+    let scheduled_count = MagicContext::MAX_COMMITS_ACCEPTED_PER_PASS + 50;
+
+    let (mut account_data, mut transaction_accounts) =
+        prepare_transaction_with_single_committee(&payer, program, committee);
+
+    let magic_context = MagicContext {
+        scheduled_commits: (0..scheduled_count)
+            .map(|_| scheduled_commit(&payer.pubkey(), vec![committee]))
+            .collect(),
+        ..Default::default()
+    };
+    let mut magic_context_acc = empty_magic_context();
+    magic_context_acc.serialize_data(&magic_context).unwrap();
+    account_data.insert(MAGIC_CONTEXT_PUBKEY, magic_context_acc);
+
+    let ix = accept_scheduled_commits_instruction(
+        &test_context().validator_authority_id(),
+    );
+    extend_transaction_accounts_from_ix(
+        &ix,
+        &mut account_data,
+        &mut transaction_accounts,
+    );
+
+    let processed_accepted = process_instruction(
+        ix.data.as_slice(),
+        transaction_accounts,
+        ix.accounts,
+        Ok(()),
+    );
+
+    // Only the first chunk was accepted, the rest remains for the next pass
+    let magic_context_acc = find_magic_context_account(&processed_accepted)
+        .expect("magic context account not found");
+    let magic_context =
+        bincode::deserialize::<MagicContext>(magic_context_acc.data()).unwrap();
+    assert_eq!(magic_context.scheduled_commits.len(), 50);
+    assert!(MagicContext::has_scheduled_commits(
+        magic_context_acc.data()
+    ));
+
+    let accepted_commits = test_context()
+        .transaction_scheduler()
+        .get_scheduled_commits_by_payer(&payer.pubkey());
+    assert_eq!(
+        accepted_commits.len(),
+        MagicContext::MAX_COMMITS_ACCEPTED_PER_PASS
     );
 }
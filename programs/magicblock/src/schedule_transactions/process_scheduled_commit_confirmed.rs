@@ -0,0 +1,243 @@
+use std::collections::HashSet;
+
+use solana_program_runtime::{ic_msg, invoke_context::InvokeContext};
+use solana_sdk::{
+    instruction::InstructionError, pubkey::Pubkey, signature::Signature,
+    transaction_context::TransactionContext,
+};
+
+use crate::{
+    errors::custom_error_codes,
+    schedule_transactions::commit_events::{
+        emit_event, ScheduledCommitConfirmed,
+    },
+    utils::accounts::get_instruction_pubkey_with_idx,
+    validator_context::ValidatorContext,
+};
+
+#[derive(Debug, Clone)]
+pub struct ConfirmedCommit {
+    pub commit_id: u64,
+    pub chain_signatures: Vec<Signature>,
+    /// `true` if all transactions realizing the commit were confirmed on chain
+    pub success: bool,
+}
+
+#[cfg(test)]
+fn get_confirmed_commit(
+    context: &ValidatorContext,
+    id: u64,
+) -> Option<ConfirmedCommit> {
+    context
+        .confirmed_commits()
+        .read()
+        .unwrap()
+        .get(&id)
+        .cloned()
+}
+
+pub fn process_scheduled_commit_confirmed(
+    signers: HashSet<Pubkey>,
+    context: &ValidatorContext,
+    invoke_context: &InvokeContext,
+    transaction_context: &TransactionContext,
+    commit_id: u64,
+) -> Result<(), InstructionError> {
+    if context.is_starting_up() {
+        ic_msg!(
+            invoke_context,
+            "ScheduledCommitConfirmed: validator is starting up, this instruction is skipped"
+        );
+        return Ok(());
+    }
+
+    const PROGRAM_IDX: u16 = 0;
+    const VALIDATOR_IDX: u16 = 1;
+
+    // Assert MagicBlock program
+    let program_id =
+        get_instruction_pubkey_with_idx(transaction_context, PROGRAM_IDX)?;
+    if program_id.ne(&crate::id()) {
+        ic_msg!(
+            invoke_context,
+            "ScheduledCommitConfirmed ERR: Invalid program id '{}'",
+            program_id
+        );
+        return Err(InstructionError::IncorrectProgramId);
+    }
+
+    // Assert validator identity matches
+    let validator_pubkey =
+        get_instruction_pubkey_with_idx(transaction_context, VALIDATOR_IDX)?;
+    let validator_authority_id = context.validator_authority_id();
+    if validator_pubkey != &validator_authority_id {
+        ic_msg!(
+            invoke_context,
+            "ScheduledCommitConfirmed ERR: provided validator account {} does not match validator identity {}",
+            validator_pubkey, validator_authority_id
+        );
+        return Err(InstructionError::IncorrectAuthority);
+    }
+
+    // Assert signers
+    if !signers.contains(&validator_authority_id) {
+        ic_msg!(
+            invoke_context,
+            "ScheduledCommitConfirmed ERR: validator authority not found in signers"
+        );
+        return Err(InstructionError::MissingRequiredSignature);
+    }
+
+    // Only after we passed all checks do we remove the commit from the hashmap
+    let commit = match context.confirmed_commits().write() {
+        Ok(mut commits) => match commits.remove(&commit_id) {
+            Some(commit) => commit,
+            None => {
+                ic_msg!(
+                    invoke_context,
+                    "ScheduledCommitConfirmed ERR: commit with id {} not found",
+                    commit_id
+                );
+                return Err(InstructionError::Custom(
+                    custom_error_codes::CANNOT_FIND_CONFIRMED_COMMIT,
+                ));
+            }
+        },
+        Err(err) => {
+            ic_msg!(
+                invoke_context,
+                "ScheduledCommitConfirmed ERR: failed to lock confirmed commits: {}",
+                err
+            );
+            return Err(InstructionError::Custom(
+                custom_error_codes::UNABLE_TO_UNLOCK_CONFIRMED_COMMITS,
+            ));
+        }
+    };
+
+    ic_msg!(
+        invoke_context,
+        "ScheduledCommitConfirmed id: {}, success: {}",
+        commit.commit_id,
+        commit.success,
+    );
+    emit_event(
+        invoke_context,
+        &ScheduledCommitConfirmed {
+            commit_id: commit.commit_id,
+            chain_signatures: commit.chain_signatures,
+            success: commit.success,
+        },
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::{
+        account::AccountSharedData,
+        instruction::{Instruction, InstructionError},
+    };
+
+    use super::*;
+    use crate::{
+        magicblock_instruction::scheduled_commit_confirmed_instruction,
+        test_utils::{
+            ensure_started_validator, process_instruction, test_context,
+        },
+    };
+
+    fn transaction_accounts_from_map(
+        ix: &Instruction,
+        account_data: &mut HashMap<Pubkey, AccountSharedData>,
+    ) -> Vec<(Pubkey, AccountSharedData)> {
+        ix.accounts
+            .iter()
+            .flat_map(|acc| {
+                account_data
+                    .remove(&acc.pubkey)
+                    .map(|shared_data| (acc.pubkey, shared_data))
+            })
+            .collect()
+    }
+
+    fn setup_registered_commit() -> ConfirmedCommit {
+        let commit = ConfirmedCommit {
+            commit_id: rand::random(),
+            chain_signatures: vec![Signature::default()],
+            success: true,
+        };
+        test_context().register_scheduled_commit_confirmed(commit.clone());
+        commit
+    }
+
+    #[test]
+    fn test_registered_but_missing_validator_auth_signer() {
+        let commit = setup_registered_commit();
+
+        let mut account_data = HashMap::new();
+        ensure_started_validator(&mut account_data);
+
+        let mut ix = scheduled_commit_confirmed_instruction(
+            &crate::id(),
+            &test_context().validator_authority_id(),
+            commit.commit_id,
+        );
+        ix.accounts[1].is_signer = false;
+
+        let transaction_accounts =
+            transaction_accounts_from_map(&ix, &mut account_data);
+        process_instruction(
+            ix.data.as_slice(),
+            transaction_accounts,
+            ix.accounts,
+            Err(InstructionError::MissingRequiredSignature),
+        );
+
+        assert!(
+            get_confirmed_commit(&test_context(), commit.commit_id).is_some(),
+            "does not remove confirmed commit data"
+        );
+    }
+
+    #[test]
+    fn test_registered_all_checks_out() {
+        let commit = setup_registered_commit();
+
+        let mut account_data = HashMap::new();
+        ensure_started_validator(&mut account_data);
+
+        let ix = scheduled_commit_confirmed_instruction(
+            &crate::id(),
+            &test_context().validator_authority_id(),
+            commit.commit_id,
+        );
+
+        let transaction_accounts =
+            transaction_accounts_from_map(&ix, &mut account_data);
+        process_instruction(
+            ix.data.as_slice(),
+            transaction_accounts,
+            ix.accounts,
+            Ok(()),
+        );
+
+        assert!(
+            get_confirmed_commit(&test_context(), commit.commit_id).is_none(),
+            "removes confirmed commit data"
+        );
+    }
+
+    #[test]
+    fn test_unregister_removes_commit_that_was_not_signaled() {
+        let commit = setup_registered_commit();
+
+        test_context().unregister_scheduled_commit_confirmed(commit.commit_id);
+
+        assert!(
+            get_confirmed_commit(&test_context(), commit.commit_id).is_none(),
+            "removes confirmed commit data"
+        );
+    }
+}
@@ -1,9 +1,5 @@
-use std::{
-    collections::{HashMap, HashSet},
-    sync::RwLock,
-};
+use std::collections::HashSet;
 
-use lazy_static::lazy_static;
 use solana_program_runtime::{ic_msg, invoke_context::InvokeContext};
 use solana_sdk::{
     clock::Slot, hash::Hash, instruction::InstructionError, pubkey::Pubkey,
@@ -12,40 +8,67 @@ use solana_sdk::{
 
 use crate::{
     errors::custom_error_codes,
-    utils::accounts::get_instruction_pubkey_with_idx, validator,
+    schedule_transactions::commit_events::{
+        emit_event_data, CommitEvent, ScheduledCommitSent,
+    },
+    utils::accounts::get_instruction_pubkey_with_idx,
+    validator_context::ValidatorContext,
 };
 
 #[derive(Debug, Clone)]
 pub struct SentCommit {
     pub commit_id: u64,
     pub slot: Slot,
+    /// The slot of the base chain at the time the commit was scheduled
+    pub chain_slot: Option<Slot>,
     pub blockhash: Hash,
     pub payer: Pubkey,
     pub chain_signatures: Vec<Signature>,
     pub included_pubkeys: Vec<Pubkey>,
     pub excluded_pubkeys: Vec<Pubkey>,
+    /// Why each of the [Self::excluded_pubkeys] was excluded
+    pub exclusion_reasons: Vec<(Pubkey, String)>,
+    /// Accounts of a conditional commit that were not committed since their
+    /// data did not change since they were last committed
+    pub skipped_pubkeys: Vec<Pubkey>,
     pub requested_undelegation_to_owner: Option<Pubkey>,
 }
 
 /// This is a printable version of the SentCommit struct.
 /// We prepare this outside of the VM in order to reduce overhead there.
 #[derive(Debug, Clone)]
-struct SentCommitPrintable {
+pub(crate) struct SentCommitPrintable {
     id: u64,
     slot: Slot,
+    chain_slot: Option<Slot>,
     blockhash: String,
     payer: String,
     chain_signatures: Vec<String>,
     included_pubkeys: String,
     excluded_pubkeys: String,
+    exclusion_reasons: Vec<String>,
+    skipped_pubkeys: String,
     requested_undelegation_to_owner: Option<String>,
+    event_data: Vec<u8>,
 }
 
 impl From<SentCommit> for SentCommitPrintable {
     fn from(commit: SentCommit) -> Self {
+        let event_data = ScheduledCommitSent {
+            commit_id: commit.commit_id,
+            slot: commit.slot,
+            chain_signatures: commit.chain_signatures.clone(),
+            included_pubkeys: commit.included_pubkeys.clone(),
+            excluded_pubkeys: commit.excluded_pubkeys.clone(),
+            skipped_pubkeys: commit.skipped_pubkeys.clone(),
+            requested_undelegation_to_owner: commit
+                .requested_undelegation_to_owner,
+        }
+        .to_event_data();
         Self {
             id: commit.commit_id,
             slot: commit.slot,
+            chain_slot: commit.chain_slot,
             blockhash: commit.blockhash.to_string(),
             payer: commit.payer.to_string(),
             chain_signatures: commit
@@ -65,42 +88,41 @@ impl From<SentCommit> for SentCommitPrintable {
                 .map(|x| x.to_string())
                 .collect::<Vec<_>>()
                 .join(", "),
+            exclusion_reasons: commit
+                .exclusion_reasons
+                .iter()
+                .map(|(pubkey, reason)| format!("{}: {}", pubkey, reason))
+                .collect(),
+            skipped_pubkeys: commit
+                .skipped_pubkeys
+                .iter()
+                .map(|x| x.to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
             requested_undelegation_to_owner: commit
                 .requested_undelegation_to_owner
                 .map(|x| x.to_string()),
+            event_data,
         }
     }
 }
 
-lazy_static! {
-    // We need to determine the transaction signature before we even know the
-    // signature of the transaction we are sending to chain and we don't know
-    // what Pubkeys we will include before hand either.
-    // Therefore the transaction itself only includes the ID of the scheduled
-    // commit and we store the signature in a globally accessible hashmap.
-    static ref SENT_COMMITS: RwLock<HashMap<u64, SentCommitPrintable>> = RwLock::new(HashMap::new());
-}
-
-pub fn register_scheduled_commit_sent(commit: SentCommit) {
-    let id = commit.commit_id;
-    SENT_COMMITS
-        .write()
-        .expect("SENT_COMMITS lock poisoned")
-        .insert(id, commit.into());
-}
-
 #[cfg(test)]
-fn get_scheduled_commit(id: u64) -> Option<SentCommitPrintable> {
-    SENT_COMMITS.read().unwrap().get(&id).cloned()
+fn get_scheduled_commit(
+    context: &ValidatorContext,
+    id: u64,
+) -> Option<SentCommitPrintable> {
+    context.sent_commits().read().unwrap().get(&id).cloned()
 }
 
 pub fn process_scheduled_commit_sent(
     signers: HashSet<Pubkey>,
+    context: &ValidatorContext,
     invoke_context: &InvokeContext,
     transaction_context: &TransactionContext,
     commit_id: u64,
 ) -> Result<(), InstructionError> {
-    if validator::is_starting_up() {
+    if context.is_starting_up() {
         ic_msg!(
             invoke_context,
             "ScheduleCommitSent: validator is starting up, this instruction is skipped"
@@ -126,7 +148,7 @@ pub fn process_scheduled_commit_sent(
     // Assert validator identity matches
     let validator_pubkey =
         get_instruction_pubkey_with_idx(transaction_context, VALIDATOR_IDX)?;
-    let validator_authority_id = validator::validator_authority_id();
+    let validator_authority_id = context.validator_authority_id();
     if validator_pubkey != &validator_authority_id {
         ic_msg!(
             invoke_context,
@@ -145,10 +167,10 @@ pub fn process_scheduled_commit_sent(
         return Err(InstructionError::MissingRequiredSignature);
     }
 
-    // Only after we passed all checks do we remove the commit from the global hashmap
+    // Only after we passed all checks do we remove the commit from the hashmap
     // Otherwise a malicious actor could remove a commit from the hashmap without
     // signing as the validator
-    let commit = match SENT_COMMITS.write() {
+    let commit = match context.sent_commits().write() {
         Ok(mut commits) => match commits.remove(&commit_id) {
             Some(commit) => commit,
             None => {
@@ -165,7 +187,7 @@ pub fn process_scheduled_commit_sent(
         Err(err) => {
             ic_msg!(
                 invoke_context,
-                "ScheduleCommitSent ERR: failed to lock SENT_COMMITS: {}",
+                "ScheduleCommitSent ERR: failed to lock sent commits: {}",
                 err
             );
             return Err(InstructionError::Custom(
@@ -182,6 +204,14 @@ pub fn process_scheduled_commit_sent(
         commit.blockhash,
     );
 
+    if let Some(chain_slot) = commit.chain_slot {
+        ic_msg!(
+            invoke_context,
+            "ScheduledCommitSent chain slot: {}",
+            chain_slot
+        );
+    }
+
     ic_msg!(
         invoke_context,
         "ScheduledCommitSent payer: {}",
@@ -198,6 +228,20 @@ pub fn process_scheduled_commit_sent(
         "ScheduledCommitSent excluded: [{}]",
         commit.excluded_pubkeys
     );
+    for reason in &commit.exclusion_reasons {
+        ic_msg!(
+            invoke_context,
+            "ScheduledCommitSent exclusion reason {}",
+            reason
+        );
+    }
+    if !commit.skipped_pubkeys.is_empty() {
+        ic_msg!(
+            invoke_context,
+            "ScheduledCommitSent skipped (unchanged): [{}]",
+            commit.skipped_pubkeys
+        );
+    }
     for (idx, sig) in commit.chain_signatures.iter().enumerate() {
         ic_msg!(
             invoke_context,
@@ -215,6 +259,8 @@ pub fn process_scheduled_commit_sent(
         );
     }
 
+    emit_event_data(invoke_context, &commit.event_data);
+
     Ok(())
 }
 
@@ -232,8 +278,9 @@ mod tests {
     use super::*;
     use crate::{
         magicblock_instruction::scheduled_commit_sent_instruction,
-        test_utils::{ensure_started_validator, process_instruction},
-        validator,
+        test_utils::{
+            ensure_started_validator, process_instruction, test_context,
+        },
     };
 
     fn single_acc_commit(commit_id: u64) -> SentCommit {
@@ -244,11 +291,14 @@ mod tests {
         SentCommit {
             commit_id,
             slot,
+            chain_slot: None,
             blockhash: Hash::default(),
             payer,
             chain_signatures: vec![sig],
             included_pubkeys: vec![acc],
             excluded_pubkeys: Default::default(),
+            exclusion_reasons: Default::default(),
+            skipped_pubkeys: Default::default(),
             requested_undelegation_to_owner: None,
         }
     }
@@ -270,7 +320,7 @@ mod tests {
     fn setup_registered_commit() -> SentCommit {
         let id: u64 = rand::random();
         let commit = single_acc_commit(id);
-        register_scheduled_commit_sent(commit.clone());
+        test_context().register_scheduled_commit_sent(commit.clone());
         commit
     }
 
@@ -284,7 +334,7 @@ mod tests {
 
         let mut ix = scheduled_commit_sent_instruction(
             &crate::id(),
-            &validator::validator_authority_id(),
+            &test_context().validator_authority_id(),
             commit.commit_id,
         );
         ix.accounts[1].is_signer = false;
@@ -299,7 +349,7 @@ mod tests {
         );
 
         assert!(
-            get_scheduled_commit(commit.commit_id).is_some(),
+            get_scheduled_commit(&test_context(), commit.commit_id).is_some(),
             "does not remove scheduled commit data"
         );
     }
@@ -334,7 +384,7 @@ mod tests {
         );
 
         assert!(
-            get_scheduled_commit(commit.commit_id).is_some(),
+            get_scheduled_commit(&test_context(), commit.commit_id).is_some(),
             "does not remove scheduled commit data"
         );
     }
@@ -356,7 +406,7 @@ mod tests {
 
         let ix = scheduled_commit_sent_instruction(
             &fake_program.pubkey(),
-            &validator::validator_authority_id(),
+            &test_context().validator_authority_id(),
             commit.commit_id,
         );
         let transaction_accounts =
@@ -370,7 +420,7 @@ mod tests {
         );
 
         assert!(
-            get_scheduled_commit(commit.commit_id).is_some(),
+            get_scheduled_commit(&test_context(), commit.commit_id).is_some(),
             "does not remove scheduled commit data"
         );
     }
@@ -385,7 +435,7 @@ mod tests {
 
         let ix = scheduled_commit_sent_instruction(
             &crate::id(),
-            &validator::validator_authority_id(),
+            &test_context().validator_authority_id(),
             commit.commit_id,
         );
 
@@ -399,8 +449,31 @@ mod tests {
         );
 
         assert!(
-            get_scheduled_commit(commit.commit_id).is_none(),
+            get_scheduled_commit(&test_context(), commit.commit_id).is_none(),
             "removes scheduled commit data"
         );
     }
+
+    #[test]
+    fn test_exclusion_reasons_are_printable() {
+        let excluded = Pubkey::new_unique();
+        let commit = SentCommit {
+            excluded_pubkeys: vec![excluded],
+            exclusion_reasons: vec![(
+                excluded,
+                "account is not delegated to this validator".to_string(),
+            )],
+            ..single_acc_commit(rand::random())
+        };
+
+        let printable = SentCommitPrintable::from(commit);
+        assert_eq!(printable.excluded_pubkeys, excluded.to_string());
+        assert_eq!(
+            printable.exclusion_reasons,
+            vec![format!(
+                "{}: account is not delegated to this validator",
+                excluded
+            )]
+        );
+    }
 }
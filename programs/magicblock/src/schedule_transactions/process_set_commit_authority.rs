@@ -0,0 +1,462 @@
+use std::collections::HashSet;
+
+use solana_program_runtime::{ic_msg, invoke_context::InvokeContext};
+use solana_sdk::{
+    account::ReadableAccount, account_utils::StateMut,
+    instruction::InstructionError, pubkey::Pubkey,
+};
+
+use crate::{
+    magic_context::MagicContext,
+    schedule_transactions::check_magic_context_id,
+    utils::accounts::{
+        get_instruction_account_with_idx, get_instruction_pubkey_with_idx,
+    },
+    validator_context::ValidatorContext,
+};
+
+/// Allows or revokes the `authority` program to schedule commits for the
+/// provided delegated accounts.
+/// Only the program owning the accounts, as recorded in their delegation
+/// record, can do this by invoking us via CPI.
+/// Each authority is recorded with the program granting it and counts
+/// towards the share of the registry that program may occupy.
+pub(crate) fn process_set_commit_authority(
+    signers: HashSet<Pubkey>,
+    context: &ValidatorContext,
+    invoke_context: &mut InvokeContext,
+    authority: Pubkey,
+    allow: bool,
+) -> Result<(), InstructionError> {
+    const PAYER_IDX: u16 = 0;
+    const MAGIC_CONTEXT_IDX: u16 = PAYER_IDX + 1;
+    const ACCOUNTS_START: usize = MAGIC_CONTEXT_IDX as usize + 1;
+
+    let ix_name = if allow {
+        "AllowCommitAuthority"
+    } else {
+        "RevokeCommitAuthority"
+    };
+
+    check_magic_context_id(invoke_context, MAGIC_CONTEXT_IDX)?;
+
+    let transaction_context = &invoke_context.transaction_context.clone();
+    let ix_ctx = transaction_context.get_current_instruction_context()?;
+    let ix_accs_len = ix_ctx.get_number_of_instruction_accounts() as usize;
+
+    // Assert enough accounts
+    if ix_accs_len <= ACCOUNTS_START {
+        ic_msg!(
+            invoke_context,
+            "{} ERR: not enough accounts ({}), need payer, magic context and at least one delegated account",
+            ix_name,
+            ix_accs_len
+        );
+        return Err(InstructionError::NotEnoughAccountKeys);
+    }
+
+    // Assert Payer is signer
+    let payer_pubkey =
+        get_instruction_pubkey_with_idx(transaction_context, PAYER_IDX)?;
+    if !signers.contains(payer_pubkey) {
+        ic_msg!(
+            invoke_context,
+            "{} ERR: payer pubkey {} not in signers",
+            ix_name,
+            payer_pubkey
+        );
+        return Err(InstructionError::MissingRequiredSignature);
+    }
+
+    //
+    // Get the program_id of the parent instruction that invoked this one via CPI
+    //
+
+    // We cannot easily simulate the transaction being invoked via CPI
+    // from the owning program during unit tests
+    // Instead the integration tests ensure that this works as expected
+    #[cfg(not(test))]
+    let frames = crate::utils::instruction_context_frames::InstructionContextFrames::try_from(transaction_context)?;
+    #[cfg(not(test))]
+    let parent_program_id = frames
+        .find_program_id_of_parent_of_current_instruction()
+        .ok_or_else(|| {
+            ic_msg!(
+                invoke_context,
+                "{} ERR: failed to find parent program id",
+                ix_name
+            );
+            InstructionError::InvalidInstructionData
+        })?;
+
+    // During unit tests we assume the first account has the correct program ID
+    #[cfg(test)]
+    let first_account_owner = {
+        *get_instruction_account_with_idx(
+            transaction_context,
+            ACCOUNTS_START as u16,
+        )?
+        .borrow()
+        .owner()
+    };
+    #[cfg(test)]
+    let parent_program_id = &first_account_owner;
+
+    // Assert all accounts are delegated and owned by the invoking program.
+    // Delegated accounts are cloned with the owner found in their delegation
+    // record, thus only that program can allow others to commit them.
+    // Accounts that only exist in the ephemeral are not backed by a
+    // delegation record, thus they cannot be used to occupy the registry.
+    let mut pubkeys = Vec::new();
+    for idx in ACCOUNTS_START..ix_accs_len {
+        let acc_pubkey =
+            get_instruction_pubkey_with_idx(transaction_context, idx as u16)?;
+        let acc =
+            get_instruction_account_with_idx(transaction_context, idx as u16)?;
+        if parent_program_id != acc.borrow().owner() {
+            ic_msg!(
+                invoke_context,
+                "{} ERR: account {} needs to be owned by the invoking program {}, but is owned by {}",
+                ix_name, acc_pubkey, parent_program_id, acc.borrow().owner()
+            );
+            return Err(InstructionError::InvalidAccountOwner);
+        }
+        // The delegated accounts aren't known while the ledger is replayed,
+        // however the authorities found in the ledger were checked when they
+        // were first granted
+        if allow
+            && !context.is_starting_up()
+            && !context.is_account_delegated(acc_pubkey)
+        {
+            ic_msg!(
+                invoke_context,
+                "{} ERR: account {} is not delegated",
+                ix_name,
+                acc_pubkey
+            );
+            return Err(InstructionError::InvalidAccountOwner);
+        }
+        pubkeys.push(*acc_pubkey);
+    }
+
+    // Store the authority in the MagicContext, like scheduled commits this
+    // only takes effect if the transaction including this instruction succeeds
+    let context_acc = get_instruction_account_with_idx(
+        transaction_context,
+        MAGIC_CONTEXT_IDX,
+    )?;
+    let context_data = &mut context_acc.borrow_mut();
+    let mut magic_context =
+        MagicContext::deserialize(context_data).map_err(|err| {
+            ic_msg!(
+                invoke_context,
+                "{} ERR: failed to deserialize MagicContext: {}",
+                ix_name,
+                err
+            );
+            InstructionError::GenericError
+        })?;
+    for pubkey in pubkeys {
+        if allow {
+            magic_context
+                .allow_commit_authority(pubkey, *parent_program_id, authority)
+                .map_err(|err| {
+                    err.into_instruction_error(invoke_context, ix_name)
+                })?;
+        } else {
+            magic_context.revoke_commit_authority(&pubkey, &authority);
+        }
+        ic_msg!(
+            invoke_context,
+            "{}: {} for account {}",
+            ix_name,
+            authority,
+            pubkey
+        );
+    }
+    context_data.set_state(&magic_context)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::{account::AccountSharedData, clock::Clock};
+
+    use super::*;
+    use crate::{
+        errors::custom_error_codes,
+        magic_context::CommitAuthority,
+        magicblock_instruction::{
+            allow_commit_authority_instruction,
+            revoke_commit_authority_instruction,
+        },
+        test_utils::{
+            empty_magic_context, prepare_accounts, process_instruction,
+            test_context, MAGIC_CONTEXT_ACC_IDX,
+        },
+    };
+
+    fn magic_context_with_commit_authorities(
+        commit_authorities: Vec<CommitAuthority>,
+    ) -> AccountSharedData {
+        let magic_context = MagicContext {
+            commit_authorities,
+            ..Default::default()
+        };
+        let mut magic_context_acc = empty_magic_context();
+        magic_context_acc.serialize_data(&magic_context).unwrap();
+        magic_context_acc
+    }
+
+    #[test]
+    fn test_allow_and_revoke_commit_authority() {
+        let (payer, delegated, program, coordinator) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        test_context().set_account_delegated(&delegated, true);
+
+        // Allow
+        let transaction_accounts = prepare_accounts(
+            Clock::default(),
+            payer,
+            empty_magic_context(),
+            &[(delegated, program)],
+        );
+        let ix = allow_commit_authority_instruction(
+            &payer,
+            vec![delegated],
+            coordinator,
+        );
+        let accounts = process_instruction(
+            ix.data.as_slice(),
+            transaction_accounts,
+            ix.accounts,
+            Ok(()),
+        );
+        let magic_context = bincode::deserialize::<MagicContext>(
+            accounts[MAGIC_CONTEXT_ACC_IDX].data(),
+        )
+        .unwrap();
+        assert_eq!(
+            magic_context.commit_authorities,
+            vec![CommitAuthority {
+                account: delegated,
+                authority: coordinator,
+                program,
+            }]
+        );
+        assert!(magic_context.is_commit_authority(
+            &delegated,
+            &program,
+            &coordinator
+        ));
+        assert!(!MagicContext::has_pending_items(
+            accounts[MAGIC_CONTEXT_ACC_IDX].data()
+        ));
+
+        // Revoke
+        let transaction_accounts = prepare_accounts(
+            Clock::default(),
+            payer,
+            accounts[MAGIC_CONTEXT_ACC_IDX].clone(),
+            &[(delegated, program)],
+        );
+        let ix = revoke_commit_authority_instruction(
+            &payer,
+            vec![delegated],
+            coordinator,
+        );
+        let accounts = process_instruction(
+            ix.data.as_slice(),
+            transaction_accounts,
+            ix.accounts,
+            Ok(()),
+        );
+        let magic_context = bincode::deserialize::<MagicContext>(
+            accounts[MAGIC_CONTEXT_ACC_IDX].data(),
+        )
+        .unwrap();
+        assert!(magic_context.commit_authorities.is_empty());
+    }
+
+    #[test]
+    fn test_allow_commit_authority_for_account_not_owned_by_invoker() {
+        let (payer, delegated, other_delegated, program, coordinator) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let transaction_accounts = prepare_accounts(
+            Clock::default(),
+            payer,
+            empty_magic_context(),
+            &[
+                (delegated, program),
+                (other_delegated, Pubkey::new_unique()),
+            ],
+        );
+        let ix = allow_commit_authority_instruction(
+            &payer,
+            vec![delegated, other_delegated],
+            coordinator,
+        );
+        process_instruction(
+            ix.data.as_slice(),
+            transaction_accounts,
+            ix.accounts,
+            Err(InstructionError::InvalidAccountOwner),
+        );
+    }
+
+    #[test]
+    fn test_allow_commit_authority_for_account_not_delegated() {
+        let (payer, undelegated, program, coordinator) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let transaction_accounts = prepare_accounts(
+            Clock::default(),
+            payer,
+            empty_magic_context(),
+            &[(undelegated, program)],
+        );
+        let ix = allow_commit_authority_instruction(
+            &payer,
+            vec![undelegated],
+            coordinator,
+        );
+        process_instruction(
+            ix.data.as_slice(),
+            transaction_accounts,
+            ix.accounts,
+            Err(InstructionError::InvalidAccountOwner),
+        );
+    }
+
+    #[test]
+    fn test_allow_commit_authority_when_program_share_full() {
+        let (payer, delegated, program, coordinator) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        test_context().set_account_delegated(&delegated, true);
+        let granted_by_program = (0
+            ..MagicContext::MAX_COMMIT_AUTHORITIES_PER_PROGRAM)
+            .map(|_| CommitAuthority {
+                account: Pubkey::new_unique(),
+                authority: coordinator,
+                program,
+            })
+            .collect::<Vec<_>>();
+
+        // The program exhausted its share
+        let transaction_accounts = prepare_accounts(
+            Clock::default(),
+            payer,
+            magic_context_with_commit_authorities(granted_by_program.clone()),
+            &[(delegated, program)],
+        );
+        let ix = allow_commit_authority_instruction(
+            &payer,
+            vec![delegated],
+            coordinator,
+        );
+        process_instruction(
+            ix.data.as_slice(),
+            transaction_accounts,
+            ix.accounts,
+            Err(InstructionError::Custom(
+                custom_error_codes::MAGIC_CONTEXT_REGISTRY_FULL,
+            )),
+        );
+
+        // Other programs can still grant authorities
+        let other_program = Pubkey::new_unique();
+        let other_delegated = Pubkey::new_unique();
+        test_context().set_account_delegated(&other_delegated, true);
+        let transaction_accounts = prepare_accounts(
+            Clock::default(),
+            payer,
+            magic_context_with_commit_authorities(granted_by_program),
+            &[(other_delegated, other_program)],
+        );
+        let ix = allow_commit_authority_instruction(
+            &payer,
+            vec![other_delegated],
+            coordinator,
+        );
+        let accounts = process_instruction(
+            ix.data.as_slice(),
+            transaction_accounts,
+            ix.accounts,
+            Ok(()),
+        );
+        let magic_context = bincode::deserialize::<MagicContext>(
+            accounts[MAGIC_CONTEXT_ACC_IDX].data(),
+        )
+        .unwrap();
+        assert!(magic_context.is_commit_authority(
+            &other_delegated,
+            &other_program,
+            &coordinator
+        ));
+        // Authorities only apply while the account is owned by the program
+        // that granted them
+        assert!(!magic_context.is_commit_authority(
+            &other_delegated,
+            &program,
+            &coordinator
+        ));
+    }
+
+    #[test]
+    fn test_allow_commit_authority_when_registry_full() {
+        let (payer, delegated, program, coordinator) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        test_context().set_account_delegated(&delegated, true);
+        // Filled by many programs, each within its share
+        let magic_context_acc = magic_context_with_commit_authorities(
+            (0..MagicContext::MAX_COMMIT_AUTHORITIES)
+                .map(|_| CommitAuthority {
+                    account: Pubkey::new_unique(),
+                    authority: coordinator,
+                    program: Pubkey::new_unique(),
+                })
+                .collect(),
+        );
+
+        let transaction_accounts = prepare_accounts(
+            Clock::default(),
+            payer,
+            magic_context_acc,
+            &[(delegated, program)],
+        );
+        let ix = allow_commit_authority_instruction(
+            &payer,
+            vec![delegated],
+            coordinator,
+        );
+        process_instruction(
+            ix.data.as_slice(),
+            transaction_accounts,
+            ix.accounts,
+            Err(InstructionError::Custom(
+                custom_error_codes::MAGIC_CONTEXT_REGISTRY_FULL,
+            )),
+        );
+    }
+}
@@ -4,33 +4,31 @@ use std::{
     sync::{Arc, RwLock},
 };
 
-use lazy_static::lazy_static;
+use magicblock_core::robust_lock::RobustRwLock;
+use magicblock_metrics::metrics;
 use solana_program_runtime::{ic_msg, invoke_context::InvokeContext};
 use solana_sdk::{
-    account::AccountSharedData, account_utils::StateMut,
+    account::AccountSharedData, account_utils::StateMut, clock::Slot,
     instruction::InstructionError, pubkey::Pubkey,
 };
 
-use crate::magic_context::{MagicContext, ScheduledCommit};
+use crate::{
+    errors::custom_error_codes,
+    magic_context::{EscrowSettlement, MagicContext, ScheduledCommit},
+};
 
-#[derive(Clone)]
+/// Clones share the same state, the scheduler of a validator is part of its
+/// [crate::ValidatorContext].
+#[derive(Clone, Default)]
 pub struct TransactionScheduler {
+    /// This vec tracks commits that went through the entire process of first
+    /// being scheduled into the MagicContext, and then being moved over to
+    /// the scheduler.
     scheduled_commits: Arc<RwLock<Vec<ScheduledCommit>>>,
-}
 
-impl Default for TransactionScheduler {
-    fn default() -> Self {
-        lazy_static! {
-            /// This vec tracks commits that went through the entire process of first
-            /// being scheduled into the MagicContext, and then being moved
-            /// over to this global.
-            static ref SCHEDULED_COMMITS: Arc<RwLock<Vec<ScheduledCommit>>> =
-                Default::default();
-        }
-        Self {
-            scheduled_commits: SCHEDULED_COMMITS.clone(),
-        }
-    }
+    /// This vec tracks escrow settlements that were accepted and need to be
+    /// reconciled on the base layer.
+    escrow_settlements: Arc<RwLock<Vec<EscrowSettlement>>>,
 }
 
 impl TransactionScheduler {
@@ -49,26 +47,58 @@ impl TransactionScheduler {
                 );
                 InstructionError::GenericError
             })?;
+        // Once undelegated the accounts are no longer ours to commit, thus
+        // any authority that was allowed to commit them is revoked and they
+        // are no longer ephemeral-only should they be delegated again
+        if commit.request_undelegation {
+            for pubkey in &commit.accounts {
+                context.revoke_commit_authorities_of_account(pubkey);
+                context.remove_ephemeral_only(pubkey);
+            }
+        }
         context.add_scheduled_commit(commit);
+
+        // Reject the commit with a clear error instead of failing to write the
+        // context, the commit can be retried once the scheduled commits were
+        // accepted at the end of the slot
+        let size = context.serialized_size().map_err(|err| {
+            ic_msg!(
+                invoke_context,
+                "Failed to determine size of MagicContext: {}",
+                err
+            );
+            InstructionError::GenericError
+        })?;
+        if size > MagicContext::SIZE as u64 {
+            ic_msg!(
+                invoke_context,
+                "MagicContext capacity exceeded: {} bytes needed, {} available with {} commits scheduled",
+                size,
+                MagicContext::SIZE,
+                context.scheduled_commits.len() - 1
+            );
+            metrics::inc_magic_context_capacity_exceeded();
+            return Err(InstructionError::Custom(
+                custom_error_codes::MAGIC_CONTEXT_CAPACITY_EXCEEDED,
+            ));
+        }
         context_data.set_state(&context)?;
+        metrics::set_magic_context_occupancy(
+            context.scheduled_commits.len(),
+            size,
+        );
         Ok(())
     }
 
     pub fn accept_scheduled_commits(&self, commits: Vec<ScheduledCommit>) {
-        self.scheduled_commits
-            .write()
-            .expect("scheduled_commits lock poisoned")
-            .extend(commits);
+        self.scheduled_commits.write_robust().extend(commits);
     }
 
     pub fn get_scheduled_commits_by_payer(
         &self,
         payer: &Pubkey,
     ) -> Vec<ScheduledCommit> {
-        let commits = self
-            .scheduled_commits
-            .read()
-            .expect("scheduled_commits lock poisoned");
+        let commits = self.scheduled_commits.read_robust();
 
         commits
             .iter()
@@ -78,27 +108,46 @@ impl TransactionScheduler {
     }
 
     pub fn take_scheduled_commits(&self) -> Vec<ScheduledCommit> {
-        let mut lock = self
-            .scheduled_commits
-            .write()
-            .expect("scheduled_commits lock poisoned");
+        let mut lock = self.scheduled_commits.write_robust();
         mem::take(&mut *lock)
     }
 
+    /// Takes all commits that are due at the provided slot, leaving commits
+    /// that were delayed to a later slot in place.
+    pub fn take_due_scheduled_commits(
+        &self,
+        current_slot: Slot,
+    ) -> Vec<ScheduledCommit> {
+        let mut lock = self.scheduled_commits.write_robust();
+        let (due, pending): (Vec<_>, Vec<_>) = mem::take(&mut *lock)
+            .into_iter()
+            .partition(|commit| commit.is_due(current_slot));
+        *lock = pending;
+        due
+    }
+
     pub fn scheduled_commits_len(&self) -> usize {
-        let lock = self
-            .scheduled_commits
-            .read()
-            .expect("scheduled_commits lock poisoned");
+        let lock = self.scheduled_commits.read_robust();
 
         lock.len()
     }
 
     pub fn clear_scheduled_commits(&self) {
-        let mut lock = self
-            .scheduled_commits
-            .write()
-            .expect("scheduled_commits lock poisoned");
+        let mut lock = self.scheduled_commits.write_robust();
         lock.clear();
     }
+
+    pub fn accept_escrow_settlements(
+        &self,
+        settlements: Vec<EscrowSettlement>,
+    ) {
+        self.escrow_settlements.write_robust().extend(settlements);
+    }
+
+    /// Takes all accepted escrow settlements in order to reconcile them on
+    /// the base layer.
+    pub fn take_escrow_settlements(&self) -> Vec<EscrowSettlement> {
+        let mut lock = self.escrow_settlements.write_robust();
+        mem::take(&mut *lock)
+    }
 }
@@ -0,0 +1,3 @@
+mod process_session_key;
+
+pub(crate) use process_session_key::*;
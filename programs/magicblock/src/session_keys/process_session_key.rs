@@ -0,0 +1,440 @@
+use std::collections::HashSet;
+
+use solana_program_runtime::{ic_msg, invoke_context::InvokeContext};
+use solana_sdk::{
+    account_utils::StateMut, clock::UnixTimestamp,
+    instruction::InstructionError, pubkey::Pubkey,
+};
+
+use crate::{
+    magic_context::{MagicContext, RegistryFull, SessionKey},
+    schedule_transactions::check_magic_context_id,
+    utils::accounts::{
+        get_instruction_account_with_idx, get_instruction_pubkey_with_idx,
+    },
+};
+
+const AUTHORITY_IDX: u16 = 0;
+const MAGIC_CONTEXT_IDX: u16 = AUTHORITY_IDX + 1;
+const SESSION_KEY_IDX: u16 = MAGIC_CONTEXT_IDX + 1;
+
+/// Registers a session key that may sign transactions on behalf of the
+/// authority signing this instruction.
+/// The session key has to sign as well, otherwise any authority could
+/// register the key of someone else with a narrow scope and thereby have
+/// its transactions rejected.
+pub(crate) fn process_register_session_key(
+    signers: HashSet<Pubkey>,
+    invoke_context: &mut InvokeContext,
+    session_key: Pubkey,
+    valid_until: UnixTimestamp,
+    allowed_programs: Vec<Pubkey>,
+) -> Result<(), InstructionError> {
+    const IX_NAME: &str = "RegisterSessionKey";
+
+    let authority = check_authority(&signers, invoke_context, IX_NAME)?;
+    if authority == session_key {
+        ic_msg!(
+            invoke_context,
+            "{} ERR: authority {} cannot be its own session key",
+            IX_NAME,
+            authority
+        );
+        return Err(InstructionError::InvalidArgument);
+    }
+
+    let session_key_acc = get_instruction_pubkey_with_idx(
+        invoke_context.transaction_context,
+        SESSION_KEY_IDX,
+    )?;
+    if session_key_acc.ne(&session_key) {
+        ic_msg!(
+            invoke_context,
+            "{} ERR: session key account {} does not match session key {}",
+            IX_NAME,
+            session_key_acc,
+            session_key
+        );
+        return Err(InstructionError::InvalidArgument);
+    }
+    if !signers.contains(&session_key) {
+        ic_msg!(
+            invoke_context,
+            "{} ERR: session key {} not in signers",
+            IX_NAME,
+            session_key
+        );
+        return Err(InstructionError::MissingRequiredSignature);
+    }
+
+    let clock =
+        invoke_context
+            .get_sysvar_cache()
+            .get_clock()
+            .map_err(|err| {
+                ic_msg!(invoke_context, "Failed to get clock sysvar: {}", err);
+                InstructionError::UnsupportedSysvar
+            })?;
+    if allowed_programs.len() > MagicContext::MAX_SESSION_KEY_ALLOWED_PROGRAMS {
+        ic_msg!(
+            invoke_context,
+            "{} ERR: session key {} may be scoped to at most {} programs",
+            IX_NAME,
+            session_key,
+            MagicContext::MAX_SESSION_KEY_ALLOWED_PROGRAMS
+        );
+        return Err(InstructionError::InvalidArgument);
+    }
+    if valid_until <= clock.unix_timestamp {
+        ic_msg!(
+            invoke_context,
+            "{} ERR: session key {} would already be expired at {}",
+            IX_NAME,
+            session_key,
+            valid_until
+        );
+        return Err(InstructionError::InvalidArgument);
+    }
+
+    let unix_timestamp = clock.unix_timestamp;
+    let registered =
+        update_magic_context(invoke_context, IX_NAME, |context| {
+            context.register_session_key(
+                SessionKey {
+                    session_key,
+                    authority,
+                    valid_until,
+                    allowed_programs,
+                },
+                unix_timestamp,
+            )
+        })?;
+    if !registered {
+        ic_msg!(
+            invoke_context,
+            "{} ERR: session key {} is registered by another authority",
+            IX_NAME,
+            session_key
+        );
+        return Err(InstructionError::AccountAlreadyInitialized);
+    }
+
+    ic_msg!(
+        invoke_context,
+        "{}: {} for authority {} until {}",
+        IX_NAME,
+        session_key,
+        authority,
+        valid_until
+    );
+    Ok(())
+}
+
+/// Revokes a session key registered by the authority signing this
+/// instruction.
+pub(crate) fn process_revoke_session_key(
+    signers: HashSet<Pubkey>,
+    invoke_context: &mut InvokeContext,
+    session_key: Pubkey,
+) -> Result<(), InstructionError> {
+    const IX_NAME: &str = "RevokeSessionKey";
+
+    let authority = check_authority(&signers, invoke_context, IX_NAME)?;
+    let revoked = update_magic_context(invoke_context, IX_NAME, |context| {
+        Ok(context.revoke_session_key(&session_key, &authority))
+    })?;
+    if !revoked {
+        ic_msg!(
+            invoke_context,
+            "{} ERR: session key {} is not registered by authority {}",
+            IX_NAME,
+            session_key,
+            authority
+        );
+        return Err(InstructionError::InvalidArgument);
+    }
+
+    ic_msg!(
+        invoke_context,
+        "{}: {} of authority {}",
+        IX_NAME,
+        session_key,
+        authority
+    );
+    Ok(())
+}
+
+fn check_authority(
+    signers: &HashSet<Pubkey>,
+    invoke_context: &InvokeContext,
+    ix_name: &str,
+) -> Result<Pubkey, InstructionError> {
+    check_magic_context_id(invoke_context, MAGIC_CONTEXT_IDX)?;
+
+    let authority = get_instruction_pubkey_with_idx(
+        invoke_context.transaction_context,
+        AUTHORITY_IDX,
+    )?;
+    if !signers.contains(authority) {
+        ic_msg!(
+            invoke_context,
+            "{} ERR: authority pubkey {} not in signers",
+            ix_name,
+            authority
+        );
+        return Err(InstructionError::MissingRequiredSignature);
+    }
+    Ok(*authority)
+}
+
+/// Like commit authorities the session keys are stored in the MagicContext,
+/// thus they only take effect if the transaction including the instruction
+/// succeeds.
+fn update_magic_context<F>(
+    invoke_context: &InvokeContext,
+    ix_name: &str,
+    update: F,
+) -> Result<bool, InstructionError>
+where
+    F: FnOnce(&mut MagicContext) -> Result<bool, RegistryFull>,
+{
+    let context_acc = get_instruction_account_with_idx(
+        invoke_context.transaction_context,
+        MAGIC_CONTEXT_IDX,
+    )?;
+    let context_data = &mut context_acc.borrow_mut();
+    let mut context =
+        MagicContext::deserialize(context_data).map_err(|err| {
+            ic_msg!(
+                invoke_context,
+                "{} ERR: failed to deserialize MagicContext: {}",
+                ix_name,
+                err
+            );
+            InstructionError::GenericError
+        })?;
+    let updated = update(&mut context)
+        .map_err(|err| err.into_instruction_error(invoke_context, ix_name))?;
+    if updated {
+        context_data.set_state(&context)?;
+    }
+    Ok(updated)
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::{
+        account::{AccountSharedData, ReadableAccount},
+        clock,
+        signature::Keypair,
+        signer::Signer,
+        system_program,
+        transaction::Transaction,
+    };
+
+    use super::*;
+    use crate::{
+        magicblock_instruction::{
+            register_session_key_instruction, revoke_session_key_instruction,
+            sign_with_session_key,
+        },
+        test_utils::{
+            empty_magic_context, prepare_accounts, process_instruction,
+            MAGIC_CONTEXT_ACC_IDX,
+        },
+    };
+
+    const NOW: UnixTimestamp = 1_000;
+
+    fn prepare_session_accounts(
+        authority: Pubkey,
+        session_key: Pubkey,
+        magic_context: AccountSharedData,
+    ) -> Vec<(Pubkey, AccountSharedData)> {
+        let clock = clock::Clock {
+            unix_timestamp: NOW,
+            ..Default::default()
+        };
+        let mut transaction_accounts =
+            prepare_accounts(clock, authority, magic_context, &[]);
+        transaction_accounts.push((
+            session_key,
+            AccountSharedData::new(0, 0, &system_program::id()),
+        ));
+        transaction_accounts
+    }
+
+    #[test]
+    fn test_register_and_revoke_session_key() {
+        let (authority, session_key, program) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+
+        // Register
+        let ix = register_session_key_instruction(
+            &authority,
+            session_key,
+            NOW + 60,
+            vec![program],
+        );
+        let accounts = process_instruction(
+            ix.data.as_slice(),
+            prepare_session_accounts(
+                authority,
+                session_key,
+                empty_magic_context(),
+            ),
+            ix.accounts,
+            Ok(()),
+        );
+        let registered = MagicContext::find_session_key(
+            &accounts[MAGIC_CONTEXT_ACC_IDX],
+            &session_key,
+        )
+        .unwrap();
+        assert_eq!(
+            registered,
+            SessionKey {
+                session_key,
+                authority,
+                valid_until: NOW + 60,
+                allowed_programs: vec![program],
+            }
+        );
+        assert!(registered.is_valid_at(NOW + 60));
+        assert!(!registered.is_valid_at(NOW + 61));
+        assert!(registered.allows_program(&program));
+        assert!(!registered.allows_program(&Pubkey::new_unique()));
+        assert!(!MagicContext::has_pending_items(
+            accounts[MAGIC_CONTEXT_ACC_IDX].data()
+        ));
+
+        // Another authority cannot take over the session key
+        let other_authority = Pubkey::new_unique();
+        let ix = register_session_key_instruction(
+            &other_authority,
+            session_key,
+            NOW + 60,
+            vec![],
+        );
+        process_instruction(
+            ix.data.as_slice(),
+            prepare_session_accounts(
+                other_authority,
+                session_key,
+                accounts[MAGIC_CONTEXT_ACC_IDX].clone(),
+            ),
+            ix.accounts,
+            Err(InstructionError::AccountAlreadyInitialized),
+        );
+
+        // Revoke
+        let ix = revoke_session_key_instruction(&authority, session_key);
+        let accounts = process_instruction(
+            ix.data.as_slice(),
+            prepare_session_accounts(
+                authority,
+                session_key,
+                accounts[MAGIC_CONTEXT_ACC_IDX].clone(),
+            ),
+            ix.accounts,
+            Ok(()),
+        );
+        assert!(MagicContext::find_session_key(
+            &accounts[MAGIC_CONTEXT_ACC_IDX],
+            &session_key
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn test_register_expired_session_key() {
+        let (authority, session_key) =
+            (Pubkey::new_unique(), Pubkey::new_unique());
+        let ix = register_session_key_instruction(
+            &authority,
+            session_key,
+            NOW,
+            vec![],
+        );
+        process_instruction(
+            ix.data.as_slice(),
+            prepare_session_accounts(
+                authority,
+                session_key,
+                empty_magic_context(),
+            ),
+            ix.accounts,
+            Err(InstructionError::InvalidArgument),
+        );
+    }
+
+    #[test]
+    fn test_register_session_key_missing_authority_signer() {
+        let (authority, session_key) =
+            (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut ix = register_session_key_instruction(
+            &authority,
+            session_key,
+            NOW + 60,
+            vec![],
+        );
+        ix.accounts[0].is_signer = false;
+        process_instruction(
+            ix.data.as_slice(),
+            prepare_session_accounts(
+                authority,
+                session_key,
+                empty_magic_context(),
+            ),
+            ix.accounts,
+            Err(InstructionError::MissingRequiredSignature),
+        );
+    }
+
+    #[test]
+    fn test_register_session_key_missing_session_key_signer() {
+        let (authority, session_key) =
+            (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut ix = register_session_key_instruction(
+            &authority,
+            session_key,
+            NOW + 60,
+            vec![],
+        );
+        ix.accounts[2].is_signer = false;
+        process_instruction(
+            ix.data.as_slice(),
+            prepare_session_accounts(
+                authority,
+                session_key,
+                empty_magic_context(),
+            ),
+            ix.accounts,
+            Err(InstructionError::MissingRequiredSignature),
+        );
+    }
+
+    #[test]
+    fn test_sign_with_session_key_in_place_of_authority() {
+        let authority = Pubkey::new_unique();
+        let session_key = Keypair::new();
+        let ix =
+            revoke_session_key_instruction(&authority, Pubkey::new_unique());
+        let mut tx = Transaction::new_with_payer(&[ix], Some(&authority));
+
+        assert!(sign_with_session_key(&mut tx, &authority, &session_key));
+        let message_data = tx.message_data();
+        assert!(tx.signatures[0]
+            .verify(session_key.pubkey().as_ref(), &message_data));
+        assert!(!tx.signatures[0].verify(authority.as_ref(), &message_data));
+
+        assert!(!sign_with_session_key(
+            &mut tx,
+            &Pubkey::new_unique(),
+            &session_key
+        ));
+    }
+}
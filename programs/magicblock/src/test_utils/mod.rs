@@ -1,30 +1,80 @@
 use std::{collections::HashMap, sync::Arc};
 
+use magicblock_core::magic_program::MAGIC_CONTEXT_PUBKEY;
 use solana_program_runtime::invoke_context::mock_process_instruction;
 use solana_sdk::{
-    account::AccountSharedData,
+    account::{create_account_shared_data_for_test, AccountSharedData},
+    clock::Clock,
     instruction::{AccountMeta, InstructionError},
     pubkey::Pubkey,
     system_program,
+    sysvar::SysvarId,
 };
 use test_tools::validator::PersisterStub;
 
 use self::magicblock_processor::Entrypoint;
 use super::*;
-use crate::validator;
+
+thread_local! {
+    /// Each test runs on its own thread and thus gets its own context.
+    static TEST_CONTEXT: Arc<ValidatorContext> = Default::default();
+}
+
+/// Returns the context that [process_instruction] enters for the current
+/// test.
+pub fn test_context() -> Arc<ValidatorContext> {
+    TEST_CONTEXT.with(|context| context.clone())
+}
 
 pub const AUTHORITY_BALANCE: u64 = u64::MAX / 2;
 pub fn ensure_started_validator(map: &mut HashMap<Pubkey, AccountSharedData>) {
-    validator::generate_validator_authority_if_needed();
-    let validator_authority_id = validator::validator_authority_id();
+    let context = test_context();
+    context.generate_validator_authority_if_needed();
+    let validator_authority_id = context.validator_authority_id();
     map.entry(validator_authority_id).or_insert_with(|| {
         AccountSharedData::new(AUTHORITY_BALANCE, 0, &system_program::id())
     });
 
     let stub = Arc::new(PersisterStub::default());
-    init_persister(stub);
+    context.data_mods().init_persister(stub);
+
+    context.ensure_started_up();
+}
+
+/// Index of the MagicContext in the accounts returned by [prepare_accounts]
+pub const MAGIC_CONTEXT_ACC_IDX: usize = 2;
+
+/// Prepares the accounts of an instruction which updates the MagicContext,
+/// namely the clock sysvar, the payer, the MagicContext and the delegated
+/// accounts given with their owners in that order.
+/// It also ensures that the validator is started.
+pub fn prepare_accounts(
+    clock: Clock,
+    payer: Pubkey,
+    magic_context: AccountSharedData,
+    delegated: &[(Pubkey, Pubkey)],
+) -> Vec<(Pubkey, AccountSharedData)> {
+    let mut account_data = HashMap::new();
+    ensure_started_validator(&mut account_data);
+
+    let mut transaction_accounts = vec![
+        (Clock::id(), create_account_shared_data_for_test(&clock)),
+        (
+            payer,
+            AccountSharedData::new(1_000, 0, &system_program::id()),
+        ),
+        (MAGIC_CONTEXT_PUBKEY, magic_context),
+    ];
+    for (pubkey, owner) in delegated {
+        transaction_accounts
+            .push((*pubkey, AccountSharedData::new(0, 0, owner)));
+    }
+    transaction_accounts
+}
 
-    validator::ensure_started_up();
+/// Returns the MagicContext account as it is before anything was stored in it.
+pub fn empty_magic_context() -> AccountSharedData {
+    AccountSharedData::new(u64::MAX, MagicContext::SIZE, &crate::id())
 }
 
 pub fn process_instruction(
@@ -33,6 +83,7 @@ pub fn process_instruction(
     instruction_accounts: Vec<AccountMeta>,
     expected_result: Result<(), InstructionError>,
 ) -> Vec<AccountSharedData> {
+    let _entered = test_context().enter();
     mock_process_instruction(
         &crate::id(),
         Vec::new(),
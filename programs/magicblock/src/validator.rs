@@ -1,89 +1,65 @@
-use std::sync::{
-    atomic::{AtomicBool, Ordering},
-    RwLock,
-};
+use std::fmt;
 
-use lazy_static::lazy_static;
-use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
-
-lazy_static! {
-    static ref VALIDATOR_AUTHORITY: RwLock<Option<Keypair>> = RwLock::new(None);
-
-    /// Flag to indicate if the validator is starting up which includes
-    /// processing the ledger.
-    /// Certain transactions behave slightly different during that phase
-    /// especially those that interact with main chain like account mutations
-    /// and scheduled commits.
-    static ref STARTING_UP: AtomicBool = AtomicBool::new(
-        #[cfg(not(test))]
-        true,
-        // our unit tests assume the validator is already running
-        #[cfg(test)]
-        false,
-    );
-}
-
-pub fn validator_authority() -> Keypair {
-    VALIDATOR_AUTHORITY
-        .read()
-        .expect("RwLock VALIDATOR_AUTHORITY poisoned")
-        .as_ref()
-        .expect("Validator authority needs to be set on startup")
-        .insecure_clone()
-}
-
-pub fn validator_authority_id() -> Pubkey {
-    VALIDATOR_AUTHORITY
-        .read()
-        .expect("RwLock VALIDATOR_AUTHORITY poisoned")
-        .as_ref()
-        .map(|x| x.pubkey())
-        .expect("Validator authority needs to be set on startup")
+/// The stages the validator moves through during its lifetime, it only ever
+/// moves on to later stages, though it may skip some of them, i.e. it does
+/// not hydrate accounts when started with a fresh ledger.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ValidatorStage {
+    /// Verifying its preconditions and setting up services
+    Starting,
+    /// Processing the ledger to restore the bank
+    Replaying,
+    /// Cloning the accounts found in the ledger again to sync them with chain
+    Hydrating,
+    /// Executing transactions and committing accounts
+    Running,
+    /// Shutting down gracefully, no new transactions are accepted
+    Draining,
+    Stopped,
 }
 
-pub fn init_validator_authority(keypair: Keypair) {
-    let mut validator_authority_lock = VALIDATOR_AUTHORITY
-        .write()
-        .expect("RwLock VALIDATOR_AUTHORITY poisoned");
-    if let Some(validator_authority) = validator_authority_lock.as_ref() {
-        panic!("Validator authority can only be set once, but was set before to '{}'", validator_authority.pubkey());
+impl ValidatorStage {
+    pub fn as_str(&self) -> &'static str {
+        use ValidatorStage::*;
+        match self {
+            Starting => "starting",
+            Replaying => "replaying",
+            Hydrating => "hydrating",
+            Running => "running",
+            Draining => "draining",
+            Stopped => "stopped",
+        }
     }
-    validator_authority_lock.replace(keypair);
-}
 
-pub fn generate_validator_authority_if_needed() {
-    let mut validator_authority_lock = VALIDATOR_AUTHORITY
-        .write()
-        .expect("RwLock VALIDATOR_AUTHORITY poisoned");
-    if validator_authority_lock.as_ref().is_some() {
-        return;
+    /// Certain transactions behave slightly different before the validator
+    /// is running, especially those that interact with main chain like
+    /// account mutations and scheduled commits.
+    pub fn is_starting_up(&self) -> bool {
+        *self < ValidatorStage::Running
     }
-    validator_authority_lock.replace(Keypair::new());
 }
 
-/// Returns `true` if the validator is starting up which is the initial
-/// state.
-pub fn is_starting_up() -> bool {
-    STARTING_UP.load(Ordering::Relaxed)
+impl fmt::Display for ValidatorStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
 }
 
-/// Ensures that the flag indicating if the validator started up is flipped
-/// to `false`.
-/// This version does not check if the validator was already started up and
-/// thus should only be used in tests.
-pub fn ensure_started_up() {
-    STARTING_UP.store(false, Ordering::Relaxed);
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-/// Needs to be called after the validator is done starting up, i.e.
-/// the ledger has been processed.
-/// This version ensures that the validator hadn't started before and
-/// should be used in prod code to avoid logic errors.
-pub fn finished_starting_up() {
-    let was_starting_up =
-        STARTING_UP.swap(false, std::sync::atomic::Ordering::Relaxed);
-    assert!(
-        was_starting_up,
-        "validator::finished_starting_up should only be called once"
-    );
+    #[test]
+    fn test_stages_before_running_are_starting_up() {
+        use ValidatorStage::*;
+        let stages =
+            [Starting, Replaying, Hydrating, Running, Draining, Stopped];
+        assert!(stages.windows(2).all(|pair| pair[0] < pair[1]));
+        let starting_up = stages
+            .iter()
+            .filter(|stage| stage.is_starting_up())
+            .copied()
+            .collect::<Vec<_>>();
+        assert_eq!(starting_up, vec![Starting, Replaying, Hydrating]);
+    }
 }
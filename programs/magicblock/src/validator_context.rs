@@ -0,0 +1,455 @@
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, RwLock,
+    },
+};
+
+use magicblock_core::robust_lock::RobustRwLock;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+use tokio::sync::watch;
+
+use crate::{
+    mutate_accounts::DataMods,
+    schedule_transactions::{
+        transaction_scheduler::TransactionScheduler, ConfirmedCommit,
+        SentCommit, SentCommitPrintable,
+    },
+    validator::ValidatorStage,
+};
+
+/// The state of the validator hosting the magic program, i.e. its authority,
+/// the stage it is in, the data mods registered for account mutations and
+/// the commits it scheduled.
+///
+/// Each bank owns the context of its validator and enters it while executing
+/// transactions, see [ValidatorContext::enter]. The program resolves the
+/// entered context once per instruction and passes it on to the processors
+/// that need it.
+pub struct ValidatorContext {
+    authority: RwLock<Option<Keypair>>,
+
+    /// Authorities the validator used before its identity was rotated.
+    /// Transactions in the ledger were signed by them, thus we need to accept
+    /// them while replaying it.
+    previous_authorities: RwLock<Vec<Pubkey>>,
+
+    data_mods: DataMods,
+
+    /// Accounts that are currently delegated to the validator, i.e. those
+    /// that were last cloned with their delegation record.
+    delegated_accounts: RwLock<HashSet<Pubkey>>,
+
+    /// The maximum number of slots a commit may be delayed by, if not set
+    /// [DEFAULT_MAX_COMMIT_DELAY_SLOTS] applies.
+    max_commit_delay_slots: RwLock<Option<u64>>,
+
+    /// Set if scheduled commits are never sent to chain, i.e. while running
+    /// offline, in which case programs cannot schedule commits.
+    scheduled_commits_disabled: AtomicBool,
+
+    /// The stage the validator is in, subsystems observe it via
+    /// [ValidatorContext::subscribe_to_stage].
+    stage: watch::Sender<ValidatorStage>,
+
+    /// Tracks the commits that were accepted from the MagicContext as well as
+    /// escrow settlements that need to be reconciled on the base layer.
+    transaction_scheduler: TransactionScheduler,
+
+    /// We need to determine the transaction signature before we even know the
+    /// signature of the transaction we are sending to chain and we don't know
+    /// what Pubkeys we will include before hand either.
+    /// Therefore the transaction itself only includes the ID of the scheduled
+    /// commit and we store the details here.
+    sent_commits: RwLock<HashMap<u64, SentCommitPrintable>>,
+
+    /// Same as for the sent commits we pass only the ID of the commit to the
+    /// transaction and store the outcome here.
+    confirmed_commits: RwLock<HashMap<u64, ConfirmedCommit>>,
+}
+
+pub use magicblock_core::consts::DEFAULT_MAX_COMMIT_DELAY_SLOTS;
+
+thread_local! {
+    /// The context of the validator whose bank executes transactions on this
+    /// thread, see [ValidatorContext::enter].
+    static ENTERED: RefCell<Option<Arc<ValidatorContext>>> =
+        const { RefCell::new(None) };
+}
+
+/// Keeps a [ValidatorContext] entered on the current thread until dropped,
+/// at which point the context that was entered before is restored.
+pub struct EnteredValidatorContext {
+    previous: Option<Arc<ValidatorContext>>,
+}
+
+impl Drop for EnteredValidatorContext {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        ENTERED.with(|entered| *entered.borrow_mut() = previous);
+    }
+}
+
+impl Default for ValidatorContext {
+    fn default() -> Self {
+        Self {
+            authority: Default::default(),
+            previous_authorities: Default::default(),
+            data_mods: Default::default(),
+            delegated_accounts: Default::default(),
+            max_commit_delay_slots: Default::default(),
+            scheduled_commits_disabled: Default::default(),
+            stage: watch::channel(
+                #[cfg(not(test))]
+                ValidatorStage::Starting,
+                // our unit tests assume the validator is already running
+                #[cfg(test)]
+                ValidatorStage::Running,
+            )
+            .0,
+            transaction_scheduler: Default::default(),
+            sent_commits: Default::default(),
+            confirmed_commits: Default::default(),
+        }
+    }
+}
+
+impl ValidatorContext {
+    pub fn new(authority: Keypair) -> Self {
+        Self {
+            authority: RwLock::new(Some(authority)),
+            ..Default::default()
+        }
+    }
+
+    /// Makes this context available to the magic program for instructions
+    /// executed on the current thread until the returned guard is dropped.
+    ///
+    /// The pinned program runtime does not support extensions on the invoke
+    /// context, however it executes the instructions of a transaction batch
+    /// synchronously on the thread of the bank processing it. Thus the bank
+    /// enters its context around the execution which scopes it to exactly
+    /// the invoke contexts that it creates.
+    pub fn enter(self: &Arc<Self>) -> EnteredValidatorContext {
+        let previous =
+            ENTERED.with(|entered| entered.borrow_mut().replace(self.clone()));
+        EnteredValidatorContext { previous }
+    }
+
+    /// Returns the context entered on the current thread if any.
+    pub(crate) fn entered() -> Option<Arc<ValidatorContext>> {
+        ENTERED.with(|entered| entered.borrow().clone())
+    }
+
+    pub fn data_mods(&self) -> &DataMods {
+        &self.data_mods
+    }
+
+    pub fn validator_authority(&self) -> Keypair {
+        self.authority
+            .read_robust()
+            .as_ref()
+            .expect("Validator authority needs to be set on startup")
+            .insecure_clone()
+    }
+
+    pub fn validator_authority_id(&self) -> Pubkey {
+        self.try_validator_authority_id()
+            .expect("Validator authority needs to be set on startup")
+    }
+
+    /// Returns `None` until the authority was set, see
+    /// [Self::init_validator_authority].
+    pub fn try_validator_authority_id(&self) -> Option<Pubkey> {
+        self.authority.read_robust().as_ref().map(|x| x.pubkey())
+    }
+
+    pub fn init_validator_authority(&self, keypair: Keypair) {
+        let mut authority_lock = self.authority.write_robust();
+        if let Some(authority) = authority_lock.as_ref() {
+            panic!("Validator authority can only be set once, but was set before to '{}'", authority.pubkey());
+        }
+        authority_lock.replace(keypair);
+    }
+
+    /// Replaces the validator authority with the provided keypair and
+    /// returns the pubkey of the authority that was replaced.
+    /// Returns `None` without setting the keypair if no authority was set
+    /// yet, see [Self::init_validator_authority].
+    /// The replaced authority is remembered so that transactions it signed
+    /// are still accepted when the ledger is replayed.
+    /// Callers need to make sure that commits pending with the previous
+    /// authority were completed before rotating.
+    pub fn rotate_validator_authority(
+        &self,
+        keypair: Keypair,
+    ) -> Option<Pubkey> {
+        let mut authority_lock = self.authority.write_robust();
+        let previous_authority = authority_lock.as_ref()?.pubkey();
+        let mut previous_authorities_lock =
+            self.previous_authorities.write_robust();
+        if !previous_authorities_lock.contains(&previous_authority) {
+            previous_authorities_lock.push(previous_authority);
+        }
+        authority_lock.replace(keypair);
+        Some(previous_authority)
+    }
+
+    /// Sets the authorities that the validator used before, usually loaded
+    /// from the ledger directory on startup.
+    pub fn init_previous_validator_authorities(
+        &self,
+        authorities: Vec<Pubkey>,
+    ) {
+        *self.previous_authorities.write_robust() = authorities;
+    }
+
+    pub fn previous_validator_authorities(&self) -> Vec<Pubkey> {
+        self.previous_authorities.read_robust().clone()
+    }
+
+    /// Returns the validator authority that is expected to have signed a
+    /// transaction with the provided signers.
+    /// While the ledger is replayed this may be an authority the validator
+    /// used before its identity was rotated, otherwise it is always the
+    /// current one.
+    pub(crate) fn validator_authority_id_for_signers(
+        &self,
+        signers: &HashSet<Pubkey>,
+    ) -> Pubkey {
+        let validator_authority_id = self.validator_authority_id();
+        if !self.is_starting_up() || signers.contains(&validator_authority_id) {
+            return validator_authority_id;
+        }
+        self.previous_authorities
+            .read_robust()
+            .iter()
+            .find(|authority| signers.contains(authority))
+            .copied()
+            .unwrap_or(validator_authority_id)
+    }
+
+    /// Records whether the account is delegated to the validator, which needs
+    /// to be updated whenever the account is cloned.
+    /// Instructions that are authorized by a delegated account check this
+    /// since the owner of an account alone does not tell if it is delegated
+    /// to us.
+    pub fn set_account_delegated(&self, pubkey: &Pubkey, delegated: bool) {
+        let mut delegated_accounts = self.delegated_accounts.write_robust();
+        if delegated {
+            delegated_accounts.insert(*pubkey);
+        } else {
+            delegated_accounts.remove(pubkey);
+        }
+    }
+
+    pub fn is_account_delegated(&self, pubkey: &Pubkey) -> bool {
+        self.delegated_accounts.read_robust().contains(pubkey)
+    }
+
+    /// Sets how many slots a commit may at most be delayed by, delayed
+    /// commits targeting a later slot are rejected.
+    pub fn init_max_commit_delay_slots(&self, slots: u64) {
+        self.max_commit_delay_slots.write_robust().replace(slots);
+    }
+
+    pub fn max_commit_delay_slots(&self) -> u64 {
+        self.max_commit_delay_slots
+            .read_robust()
+            .unwrap_or(DEFAULT_MAX_COMMIT_DELAY_SLOTS)
+    }
+
+    /// Rejects commits scheduled from now on since they would never be sent,
+    /// i.e. since the validator runs offline.
+    pub fn disable_scheduled_commits(&self) {
+        self.scheduled_commits_disabled
+            .store(true, Ordering::Relaxed);
+    }
+
+    pub fn scheduled_commits_disabled(&self) -> bool {
+        self.scheduled_commits_disabled.load(Ordering::Relaxed)
+    }
+
+    pub fn generate_validator_authority_if_needed(&self) {
+        let mut authority_lock = self.authority.write_robust();
+        if authority_lock.as_ref().is_some() {
+            return;
+        }
+        authority_lock.replace(Keypair::new());
+    }
+
+    pub fn transaction_scheduler(&self) -> &TransactionScheduler {
+        &self.transaction_scheduler
+    }
+
+    pub fn register_scheduled_commit_sent(&self, commit: SentCommit) {
+        let id = commit.commit_id;
+        self.sent_commits.write_robust().insert(id, commit.into());
+    }
+
+    pub(crate) fn sent_commits(
+        &self,
+    ) -> &RwLock<HashMap<u64, SentCommitPrintable>> {
+        &self.sent_commits
+    }
+
+    pub fn register_scheduled_commit_confirmed(&self, commit: ConfirmedCommit) {
+        self.confirmed_commits
+            .write_robust()
+            .insert(commit.commit_id, commit);
+    }
+
+    /// Removes the confirmed commit unless the instruction signaling its
+    /// confirmation already did so.
+    pub fn unregister_scheduled_commit_confirmed(&self, commit_id: u64) {
+        self.confirmed_commits.write_robust().remove(&commit_id);
+    }
+
+    pub(crate) fn confirmed_commits(
+        &self,
+    ) -> &RwLock<HashMap<u64, ConfirmedCommit>> {
+        &self.confirmed_commits
+    }
+
+    pub fn stage(&self) -> ValidatorStage {
+        *self.stage.borrow()
+    }
+
+    /// Returns a receiver which observes the stage the validator is in and is
+    /// notified whenever it moves on to the next one.
+    pub fn subscribe_to_stage(&self) -> watch::Receiver<ValidatorStage> {
+        self.stage.subscribe()
+    }
+
+    /// Moves the validator on to [stage] and notifies the subsystems
+    /// observing it. Entering the current stage again does nothing.
+    ///
+    /// Panics if [stage] comes before the current stage in order to avoid
+    /// logic errors.
+    pub fn enter_stage(&self, stage: ValidatorStage) {
+        let mut current_stage = stage;
+        self.stage.send_if_modified(|current| {
+            current_stage = *current;
+            if stage > *current {
+                *current = stage;
+                true
+            } else {
+                false
+            }
+        });
+        assert!(
+            current_stage <= stage,
+            "validator cannot enter stage {} after stage {}",
+            stage,
+            current_stage
+        );
+    }
+
+    /// Returns `true` if the validator is starting up which is the initial
+    /// state, see [ValidatorStage::is_starting_up].
+    pub fn is_starting_up(&self) -> bool {
+        self.stage().is_starting_up()
+    }
+
+    /// Ensures that the validator is running.
+    /// This version does not check which stage the validator was in before
+    /// and thus should only be used in tests.
+    pub fn ensure_started_up(&self) {
+        self.stage.send_replace(ValidatorStage::Running);
+    }
+
+    /// Needs to be called after the validator is done starting up, i.e.
+    /// the ledger has been processed.
+    /// This version ensures that the validator hadn't started before and
+    /// should be used in prod code to avoid logic errors.
+    pub fn finished_starting_up(&self) {
+        assert!(
+            self.is_starting_up(),
+            "finished_starting_up should only be called once"
+        );
+        self.enter_stage(ValidatorStage::Running);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotating_authority_remembers_previous_ones() {
+        let first = Keypair::new();
+        let context = ValidatorContext::new(first.insecure_clone());
+
+        let second = Keypair::new();
+        assert_eq!(
+            context.rotate_validator_authority(second.insecure_clone()),
+            Some(first.pubkey())
+        );
+        assert_eq!(context.validator_authority_id(), second.pubkey());
+
+        context.rotate_validator_authority(Keypair::new());
+        assert_eq!(
+            context.previous_validator_authorities(),
+            vec![first.pubkey(), second.pubkey()]
+        );
+    }
+
+    #[test]
+    fn test_rotating_authority_before_it_was_set() {
+        let context = ValidatorContext::default();
+        assert_eq!(context.rotate_validator_authority(Keypair::new()), None);
+        assert!(context.previous_validator_authorities().is_empty());
+    }
+
+    #[test]
+    fn test_contexts_do_not_share_state() {
+        let context = ValidatorContext::new(Keypair::new());
+        let other_context = ValidatorContext::default();
+
+        other_context.generate_validator_authority_if_needed();
+        assert_ne!(
+            context.validator_authority_id(),
+            other_context.validator_authority_id()
+        );
+
+        let id = context.data_mods().insert(vec![1, 2, 3]);
+        assert_eq!(other_context.data_mods().get(id), None);
+        assert_eq!(context.data_mods().get(id), Some(vec![1, 2, 3]));
+
+        context.enter_stage(ValidatorStage::Draining);
+        assert_eq!(other_context.stage(), ValidatorStage::Running);
+    }
+
+    #[test]
+    fn test_entered_context_is_scoped_to_guard() {
+        let outer = Arc::new(ValidatorContext::new(Keypair::new()));
+        let inner = Arc::new(ValidatorContext::new(Keypair::new()));
+        assert!(ValidatorContext::entered().is_none());
+        {
+            let _outer_guard = outer.enter();
+            {
+                let _inner_guard = inner.enter();
+                assert!(Arc::ptr_eq(
+                    &ValidatorContext::entered().unwrap(),
+                    &inner
+                ));
+            }
+            assert!(Arc::ptr_eq(&ValidatorContext::entered().unwrap(), &outer));
+            let other_thread =
+                std::thread::spawn(|| ValidatorContext::entered().is_none());
+            assert!(other_thread.join().unwrap());
+        }
+        assert!(ValidatorContext::entered().is_none());
+    }
+
+    #[test]
+    fn test_entering_the_current_stage_again_does_nothing() {
+        let context = ValidatorContext::default();
+        let stage_receiver = context.subscribe_to_stage();
+        context.enter_stage(ValidatorStage::Running);
+        assert_eq!(context.stage(), ValidatorStage::Running);
+        assert!(!stage_receiver.has_changed().unwrap());
+    }
+}
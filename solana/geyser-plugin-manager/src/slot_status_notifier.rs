@@ -2,10 +2,12 @@ use std::sync::{Arc, RwLock};
 
 use log::*;
 use magicblock_bank::slot_status_notifier_interface::SlotStatusNotifier;
-use solana_geyser_plugin_interface::geyser_plugin_interface::SlotStatus;
+use solana_geyser_plugin_interface::geyser_plugin_interface::{
+    ReplicaBlockInfoV3, ReplicaBlockInfoVersions, SlotStatus,
+};
 use solana_measure::measure::Measure;
 use solana_metrics::*;
-use solana_sdk::clock::Slot;
+use solana_sdk::{clock::Slot, hash::Hash};
 
 use crate::geyser_plugin_manager::GeyserPluginManager;
 
@@ -21,7 +23,13 @@ impl SlotStatusNotifierImpl {
 }
 
 impl SlotStatusNotifier for SlotStatusNotifierImpl {
-    fn notify_slot_status(&self, slot: Slot, parent: Option<Slot>) {
+    fn notify_slot_status(
+        &self,
+        slot: Slot,
+        parent: Option<Slot>,
+        blockhash: &Hash,
+        parent_blockhash: &Hash,
+    ) {
         // We use a single bank only
         let slot_status: SlotStatus = SlotStatus::Processed;
 
@@ -30,6 +38,22 @@ impl SlotStatusNotifier for SlotStatusNotifierImpl {
             return;
         }
 
+        // We don't produce blocks, the metadata only provides the hash of the
+        // slot to plugins, i.e. to include it with the account updates
+        let blockhash = blockhash.to_string();
+        let parent_blockhash = parent_blockhash.to_string();
+        let block_info = ReplicaBlockInfoV3 {
+            parent_slot: parent.unwrap_or_default(),
+            parent_blockhash: &parent_blockhash,
+            slot,
+            blockhash: &blockhash,
+            rewards: &[],
+            block_time: None,
+            block_height: None,
+            executed_transaction_count: 0,
+            entry_count: 0,
+        };
+
         for plugin in plugin_manager.plugins.iter() {
             let mut measure = Measure::start("geyser-plugin-update-slot");
             match plugin.update_slot_status(slot, parent, slot_status) {
@@ -49,6 +73,16 @@ impl SlotStatusNotifier for SlotStatusNotifierImpl {
                     );
                 }
             }
+            if let Err(err) = plugin.notify_block_metadata(
+                ReplicaBlockInfoVersions::V0_0_3(&block_info),
+            ) {
+                error!(
+                    "Failed to update block metadata at slot {}, error: {} to plugin {}",
+                    slot,
+                    err,
+                    plugin.name()
+                )
+            }
             measure.stop();
             inc_new_counter_debug!(
                 "geyser-plugin-update-slot-us",
@@ -0,0 +1,233 @@
+use std::path::Path;
+
+use magicblock_api::ledger;
+use magicblock_config::{ConfigProfile, EphemeralConfig};
+use magicblock_ledger::Ledger;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_rpc_client_api::request::RpcRequest;
+use solana_sdk::{
+    signature::Keypair,
+    signer::{EncodableKey, Signer},
+};
+
+pub(crate) type CliResult = Result<(), String>;
+
+const DEFAULT_RPC_URL: &str = "http://127.0.0.1:8899";
+
+const USAGE: &str = "\
+Usage:
+  rpc [<config>] [--profile <profile>] [<overrides>]
+  rpc run [<config>] [--profile <profile>] [<overrides>]
+      Runs the validator with the provided config file or the default config.
+      The profile provides the defaults the config file is applied to, one of:
+        ephemeral, dev-offline, replica
+      The bind addresses and ports can be overridden with the following flags
+      which take precedence over the config file and env vars:
+        --rpc-addr, --rpc-port, --rpc-pubsub-addr, --rpc-pubsub-port,
+        --geyser-grpc-addr, --geyser-grpc-port, --metrics-addr, --metrics-port
+  rpc keygen [<keypair>] [--format base58|array] [--outfile <path>]
+      Generates a new keypair or converts the provided one which may be a
+      base58 string, a JSON byte array or the path to a keypair file.
+      Prints the keypair in the requested format, base58 by default, unless
+      it is written to the outfile.
+  rpc ledger info <ledger-path>
+      Prints the size and slot range of the ledger.
+  rpc ledger purge <ledger-path>
+      Removes all data from the ledger, the validator needs to be stopped.
+  rpc commit-all [<rpc-url>]
+      Commits all delegated accounts that changed since their last commit via
+      the admin RPC of a running validator, defaults to http://127.0.0.1:8899.
+  rpc config validate <config> [--profile <profile>]
+      Checks that the config file can be loaded.
+  rpc help
+      Prints this message.";
+
+pub(crate) fn print_usage() {
+    println!("{}", USAGE);
+}
+
+// -----------------
+// Keygen
+// -----------------
+enum KeypairFormat {
+    Base58,
+    Array,
+}
+
+pub(crate) fn keygen(args: &[String]) -> CliResult {
+    let mut input = None;
+    let mut format = KeypairFormat::Base58;
+    let mut outfile = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" => {
+                format = match args.next().map(String::as_str) {
+                    Some("base58") => KeypairFormat::Base58,
+                    Some("array") => KeypairFormat::Array,
+                    other => {
+                        return Err(format!(
+                        "Invalid keypair format {:?}, use 'base58' or 'array'",
+                        other
+                    ))
+                    }
+                }
+            }
+            "--outfile" => {
+                outfile = Some(
+                    args.next()
+                        .ok_or("Missing path after '--outfile'")?
+                        .clone(),
+                )
+            }
+            _ if input.is_none() => input = Some(arg.clone()),
+            _ => return Err(format!("Unexpected argument '{}'", arg)),
+        }
+    }
+
+    let keypair = match input {
+        Some(input) => parse_keypair(&input)?,
+        None => Keypair::new(),
+    };
+    eprintln!("Pubkey: {}", keypair.pubkey());
+    match outfile {
+        Some(outfile) => {
+            keypair.write_to_file(&outfile).map_err(|err| {
+                format!("Failed to write keypair to '{}': {}", outfile, err)
+            })?;
+            eprintln!("Wrote keypair to '{}'", outfile);
+        }
+        None => match format {
+            KeypairFormat::Base58 => println!("{}", keypair.to_base58_string()),
+            KeypairFormat::Array => println!("{:?}", keypair.to_bytes()),
+        },
+    }
+    Ok(())
+}
+
+/// Parses a keypair provided as the path of a keypair file, a JSON byte
+/// array or a base58 string.
+fn parse_keypair(input: &str) -> Result<Keypair, String> {
+    let input = input.trim();
+    if Path::new(input).is_file() {
+        return Keypair::read_from_file(input).map_err(|err| {
+            format!("Failed to read keypair from '{}': {}", input, err)
+        });
+    }
+    let bytes = if input.starts_with('[') {
+        serde_json::from_str::<Vec<u8>>(input)
+            .map_err(|err| format!("Invalid keypair byte array: {}", err))?
+    } else {
+        bs58::decode(input)
+            .into_vec()
+            .map_err(|err| format!("Invalid base58 keypair: {}", err))?
+    };
+    Keypair::from_bytes(&bytes)
+        .map_err(|err| format!("Invalid keypair: {}", err))
+}
+
+// -----------------
+// Ledger
+// -----------------
+pub(crate) fn ledger(args: &[String]) -> CliResult {
+    let (subcommand, ledger_path) = match args {
+        [subcommand, ledger_path] => {
+            (subcommand.as_str(), Path::new(ledger_path))
+        }
+        _ => {
+            return Err("Expected 'ledger info|purge <ledger-path>'".to_string())
+        }
+    };
+    if !ledger_path.is_dir() {
+        return Err(format!(
+            "Ledger directory '{}' does not exist",
+            ledger_path.display()
+        ));
+    }
+
+    // Make sure no validator is using the ledger while we access it
+    let mut ledger_lock = ledger::ledger_lockfile(ledger_path);
+    let _ledger_write_guard =
+        ledger::lock_ledger(ledger_path, &mut ledger_lock);
+
+    match subcommand {
+        "info" => {
+            let ledger = Ledger::open(ledger_path)
+                .map_err(|err| format!("Failed to open ledger: {}", err))?;
+            let storage_size = ledger
+                .storage_size()
+                .map_err(|err| format!("Failed to get ledger size: {}", err))?;
+            let slot_range = ledger
+                .get_slot_range()
+                .map_err(|err| format!("Failed to get slot range: {}", err))?;
+            println!("Path:  {}", ledger_path.display());
+            println!("Size:  {} bytes", storage_size);
+            match slot_range {
+                Some((lowest, highest)) => {
+                    println!("Slots: {}..={}", lowest, highest)
+                }
+                None => println!("Slots: none"),
+            }
+            Ok(())
+        }
+        "purge" => {
+            ledger::purge(ledger_path).map_err(|err| err.to_string())?;
+            println!("Purged ledger at '{}'", ledger_path.display());
+            Ok(())
+        }
+        other => Err(format!("Unknown ledger subcommand '{}'", other)),
+    }
+}
+
+// -----------------
+// Commit All
+// -----------------
+pub(crate) async fn commit_all(args: &[String]) -> CliResult {
+    let rpc_url = match args {
+        [] => DEFAULT_RPC_URL,
+        [rpc_url] => rpc_url.as_str(),
+        _ => return Err("Expected 'commit-all [<rpc-url>]'".to_string()),
+    };
+    let rpc_client = RpcClient::new(rpc_url.to_string());
+    let signatures = rpc_client
+        .send::<Vec<String>>(
+            RpcRequest::Custom {
+                method: "commitAll",
+            },
+            serde_json::json!([]),
+        )
+        .await
+        .map_err(|err| {
+            format!(
+                "Failed to commit accounts via '{}', make sure the admin RPC is enabled: {}",
+                rpc_url, err
+            )
+        })?;
+    println!("Sent {} commit transaction(s)", signatures.len());
+    for signature in signatures {
+        println!("{}", signature);
+    }
+    Ok(())
+}
+
+// -----------------
+// Config
+// -----------------
+pub(crate) fn config(args: &[String]) -> CliResult {
+    let (profile, args) = ConfigProfile::extract_from_args(args)
+        .map_err(|err| err.to_string())?;
+    match args.as_slice() {
+        [subcommand, config_file] if subcommand == "validate" => {
+            EphemeralConfig::try_load_from_file_with_profile(
+                config_file,
+                profile,
+            )
+            .map_err(|err| {
+                format!("Config '{}' is invalid: {}", config_file, err)
+            })?;
+            println!("Config '{}' is valid", config_file);
+            Ok(())
+        }
+        _ => Err("Expected 'config validate <config>'".to_string()),
+    }
+}
@@ -1,12 +1,17 @@
+use std::process::exit;
+
 use log::*;
 use magicblock_api::{
     ledger,
     magic_validator::{MagicValidator, MagicValidatorConfig},
     InitGeyserServiceConfig,
 };
-use magicblock_config::{EphemeralConfig, GeyserGrpcConfig};
+use magicblock_config::{ConfigProfile, EphemeralConfig, GeyserGrpcConfig};
+use magicblock_rpc::shutdown::RpcShutdown;
 use solana_sdk::signature::Keypair;
-use test_tools::init_logger;
+use tokio::signal::unix::{signal, SignalKind};
+
+mod cli;
 
 // mAGicPQYBMvcYveUZA5F5UNNwyHvfYh5xkLS2Fr1mev
 const TEST_KEYPAIR_BYTES: [u8; 64] = [
@@ -16,52 +21,57 @@ const TEST_KEYPAIR_BYTES: [u8; 64] = [
     202, 240, 105, 168, 157, 64, 233, 249, 100, 104, 210, 41, 83, 87,
 ];
 
-fn init_logger() {
-    if let Ok(style) = std::env::var("RUST_LOG_STYLE") {
-        use std::io::Write;
-        let mut builder = env_logger::builder();
-        builder.format_timestamp_micros().is_test(false);
-        match style.as_str() {
-            "EPHEM" => {
-                builder.format(|buf, record| {
-                    writeln!(
-                        buf,
-                        "EPHEM [{}] {}: {} {}",
-                        record.level(),
-                        buf.timestamp_millis(),
-                        record.module_path().unwrap_or_default(),
-                        record.args()
-                    )
-                });
-            }
-            "DEVNET" => {
-                builder.format(|buf, record| {
-                    writeln!(
-                        buf,
-                        "DEVNET [{}] {}: {} {}",
-                        record.level(),
-                        buf.timestamp_millis(),
-                        record.module_path().unwrap_or_default(),
-                        record.args()
-                    )
-                });
-            }
-            _ => {}
+#[tokio::main]
+async fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let result = match args.first().map(String::as_str) {
+        Some("run") => {
+            run(&args[1..]).await;
+            Ok(())
         }
-        let _ = builder.try_init();
-    } else {
-        init_logger!();
+        Some("keygen") => cli::keygen(&args[1..]),
+        Some("ledger") => cli::ledger(&args[1..]),
+        Some("commit-all") => cli::commit_all(&args[1..]).await,
+        Some("config") => cli::config(&args[1..]),
+        Some("help" | "--help" | "-h") => {
+            cli::print_usage();
+            Ok(())
+        }
+        // Without a subcommand the arguments are the same as for `run`
+        _ => {
+            run(&args).await;
+            Ok(())
+        }
+    };
+    if let Err(err) = result {
+        eprintln!("Error: {}", err);
+        exit(1);
     }
 }
 
-#[tokio::main]
-async fn main() {
-    init_logger();
+/// Runs the validator, the [args] are the optional config file followed by
+/// flags overriding the bind addresses and ports, i.e. `--rpc-port 8899`.
+/// The config file is applied on top of the profile selected via
+/// `--profile <name>`, if any.
+async fn run(args: &[String]) {
+    magicblock_logger::init_logger().expect("Failed to initialize logger");
     #[cfg(feature = "tokio-console")]
     console_subscriber::init();
 
-    let (file, config) = load_config_from_arg();
-    let config = config.override_from_envs();
+    let (profile, args) = ConfigProfile::extract_from_args(args)
+        .unwrap_or_else(|err| panic!("Invalid arguments. ({})", err));
+    let (config_file, overrides) = match args.first() {
+        Some(arg) if !arg.starts_with("--") => (Some(arg.clone()), &args[1..]),
+        _ => (None, &args[..]),
+    };
+    let (file, config) = load_config(config_file, profile);
+    let config = config
+        .override_from_envs()
+        .override_from_args(overrides)
+        .unwrap_or_else(|err| panic!("Invalid arguments. ({})", err));
+    if let Some(profile) = profile {
+        info!("Using config profile '{:?}'.", profile);
+    }
     match file {
         Some(file) => info!("Loading config from '{}'.", file),
         None => info!("Using default config. Override it by passing the path to a config file."),
@@ -89,9 +99,24 @@ async fn main() {
         ledger::lock_ledger(api.ledger().ledger_path(), &mut ledger_lock);
 
     api.start().await.expect("Failed to start validator");
+
+    wait_for_shutdown_request(api.rpc_shutdown()).await;
+    api.shutdown().await;
     api.join();
 }
 
+/// Resolves once we receive SIGTERM, SIGINT or a shutdown request via the
+/// admin RPC.
+async fn wait_for_shutdown_request(rpc_shutdown: RpcShutdown) {
+    let mut sigterm = signal(SignalKind::terminate())
+        .expect("Failed to install SIGTERM handler");
+    tokio::select! {
+        _ = sigterm.recv() => info!("Received SIGTERM"),
+        _ = tokio::signal::ctrl_c() => info!("Received SIGINT"),
+        _ = rpc_shutdown.requested() => info!("Received shutdown request"),
+    }
+}
+
 fn validator_keypair() -> Keypair {
     // Try to load it from an env var base58 encoded private key
     if let Ok(keypair) = std::env::var("VALIDATOR_KEYPAIR") {
@@ -104,20 +129,25 @@ fn validator_keypair() -> Keypair {
     }
 }
 
-fn load_config_from_arg() -> (Option<String>, EphemeralConfig) {
-    let config_file = std::env::args().nth(1);
+fn load_config(
+    config_file: Option<String>,
+    profile: Option<ConfigProfile>,
+) -> (Option<String>, EphemeralConfig) {
     match config_file {
         Some(config_file) => {
-            let config = EphemeralConfig::try_load_from_file(&config_file)
-                .unwrap_or_else(|err| {
-                    panic!(
-                        "Failed to load config file from '{}'. ({})",
-                        config_file, err
-                    )
-                });
+            let config = EphemeralConfig::try_load_from_file_with_profile(
+                &config_file,
+                profile,
+            )
+            .unwrap_or_else(|err| {
+                panic!(
+                    "Failed to load config file from '{}'. ({})",
+                    config_file, err
+                )
+            });
             (Some(config_file), config)
         }
-        None => (None, Default::default()),
+        None => (None, profile.map(|p| p.config()).unwrap_or_default()),
     }
 }
 
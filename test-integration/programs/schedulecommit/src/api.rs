@@ -8,7 +8,8 @@ use solana_program::{
 };
 
 use crate::{
-    DelegateCpiArgs, ScheduleCommitCpiArgs, ScheduleCommitInstruction,
+    pattern::DataPattern, DelegateCpiArgs, ScheduleCommitCpiArgs,
+    ScheduleCommitInstruction,
 };
 
 pub fn init_account_instruction(
@@ -170,6 +171,50 @@ pub fn increase_count_instruction(committee: Pubkey) -> Instruction {
     )
 }
 
+pub fn resize_account_instruction(
+    payer: Pubkey,
+    committee: Pubkey,
+    data_len: u32,
+) -> Instruction {
+    let program_id = crate::id();
+    let account_metas = vec![
+        AccountMeta::new(payer, true),
+        AccountMeta::new(committee, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    Instruction::new_with_borsh(
+        program_id,
+        &ScheduleCommitInstruction::ResizeAccount(data_len),
+        account_metas,
+    )
+}
+
+pub fn fill_pattern_instruction(
+    committee: Pubkey,
+    pattern: DataPattern,
+) -> Instruction {
+    let program_id = crate::id();
+    let account_metas = vec![AccountMeta::new(committee, false)];
+    Instruction::new_with_borsh(
+        program_id,
+        &ScheduleCommitInstruction::FillPattern(pattern),
+        account_metas,
+    )
+}
+
+pub fn verify_pattern_instruction(
+    committee: Pubkey,
+    pattern: DataPattern,
+) -> Instruction {
+    let program_id = crate::id();
+    let account_metas = vec![AccountMeta::new_readonly(committee, false)];
+    Instruction::new_with_borsh(
+        program_id,
+        &ScheduleCommitInstruction::VerifyPattern(pattern),
+        account_metas,
+    )
+}
+
 // -----------------
 // PDA
 // -----------------
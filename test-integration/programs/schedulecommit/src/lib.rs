@@ -13,17 +13,21 @@ use solana_program::{
     msg,
     program_error::ProgramError,
     pubkey::Pubkey,
+    rent::Rent,
+    sysvar::Sysvar,
 };
 
 use crate::{
     api::{pda_and_bump, pda_seeds, pda_seeds_with_bump},
+    pattern::DataPattern,
     utils::{
         allocate_account_and_assign_owner, assert_is_signer, assert_keys_equal,
-        AllocateAndAssignAccountArgs,
+        transfer_lamports, AllocateAndAssignAccountArgs,
     },
 };
 pub mod api;
 pub mod magicblock_program;
+pub mod pattern;
 mod utils;
 
 declare_id!("9hgprgZiRWmy8KkfvUuaVkDGrqo9GzeXMohwq6BazgUY");
@@ -88,6 +92,26 @@ pub enum ScheduleCommitInstruction {
     /// # Account references:
     /// - **0.** `[WRITE]` Account to increase count
     IncreaseCount,
+
+    /// Reallocs a PDA of this program so that it holds the given number of
+    /// pattern data bytes after the [MainAccount].
+    /// The runtime limits the growth per instruction to
+    /// [solana_program::entrypoint::MAX_PERMITTED_DATA_INCREASE] bytes.
+    /// # Account references:
+    /// - **0.** `[WRITE, SIGNER]` Payer funding the rent of the larger account
+    /// - **1.** `[WRITE]`         Account to resize
+    /// - **2.** `[]`              System program
+    ResizeAccount(u32),
+
+    /// Fills the data after the [MainAccount] with the pattern.
+    /// # Account references:
+    /// - **0.** `[WRITE]` Account to fill
+    FillPattern(DataPattern),
+
+    /// Fails if the data after the [MainAccount] doesn't follow the pattern.
+    /// # Account references:
+    /// - **0.** `[]` Account to verify
+    VerifyPattern(DataPattern),
     // This is invoked by the delegation program when we request to undelegate
     // accounts.
     // # Account references:
@@ -137,6 +161,9 @@ pub fn process_instruction<'a>(
             )
         }
         IncreaseCount => process_increase_count(accounts),
+        ResizeAccount(data_len) => process_resize_account(accounts, data_len),
+        FillPattern(pattern) => process_fill_pattern(accounts, pattern),
+        VerifyPattern(pattern) => process_verify_pattern(accounts, pattern),
     }
 }
 
@@ -152,8 +179,9 @@ pub struct MainAccount {
 impl MainAccount {
     pub const SIZE: usize = std::mem::size_of::<Self>();
 
+    /// Decodes the account ignoring the pattern data of resized accounts.
     pub fn try_decode(data: &[u8]) -> std::io::Result<Self> {
-        Self::try_from_slice(data)
+        Self::deserialize(&mut &data[..])
     }
 }
 
@@ -271,7 +299,7 @@ pub fn process_schedulecommit_cpi(
             let main_account = {
                 let main_account_data = committee.try_borrow_data()?;
                 let mut main_account =
-                    MainAccount::try_from_slice(&main_account_data)?;
+                    MainAccount::try_decode(&main_account_data)?;
                 main_account.count += 1;
                 main_account
             };
@@ -317,7 +345,7 @@ fn process_increase_count(accounts: &[AccountInfo]) -> ProgramResult {
     let account = next_account_info(accounts_iter)?;
     let mut main_account = {
         let main_account_data = account.try_borrow_data()?;
-        MainAccount::try_from_slice(&main_account_data)?
+        MainAccount::try_decode(&main_account_data)?
     };
     main_account.count += 1;
     main_account
@@ -325,6 +353,68 @@ fn process_increase_count(accounts: &[AccountInfo]) -> ProgramResult {
     Ok(())
 }
 
+// -----------------
+// Account Size and Data Patterns
+// -----------------
+fn process_resize_account(
+    accounts: &[AccountInfo],
+    data_len: u32,
+) -> ProgramResult {
+    msg!("Processing resize_account instruction");
+    let accounts_iter = &mut accounts.iter();
+    let payer = next_account_info(accounts_iter)?;
+    let account = next_account_info(accounts_iter)?;
+    assert_is_signer(payer, "payer")?;
+
+    let size = MainAccount::SIZE + data_len as usize;
+    let required_lamports = Rent::get()?
+        .minimum_balance(size)
+        .saturating_sub(account.lamports());
+    if required_lamports > 0 {
+        transfer_lamports(payer, account, required_lamports)?;
+    }
+    account.realloc(size, false)?;
+    Ok(())
+}
+
+fn process_fill_pattern(
+    accounts: &[AccountInfo],
+    pattern: DataPattern,
+) -> ProgramResult {
+    msg!("Processing fill_pattern instruction");
+    let accounts_iter = &mut accounts.iter();
+    let account = next_account_info(accounts_iter)?;
+    let mut data = account.try_borrow_mut_data()?;
+    let pattern_data = data
+        .get_mut(MainAccount::SIZE..)
+        .ok_or(ProgramError::AccountDataTooSmall)?;
+    pattern.fill(pattern_data);
+    Ok(())
+}
+
+fn process_verify_pattern(
+    accounts: &[AccountInfo],
+    pattern: DataPattern,
+) -> ProgramResult {
+    msg!("Processing verify_pattern instruction");
+    let accounts_iter = &mut accounts.iter();
+    let account = next_account_info(accounts_iter)?;
+    let data = account.try_borrow_data()?;
+    let pattern_data = data
+        .get(MainAccount::SIZE..)
+        .ok_or(ProgramError::AccountDataTooSmall)?;
+    if let Some(offset) = pattern.find_mismatch(pattern_data) {
+        msg!(
+            "ERROR: data of {} doesn't match {:?} at offset {}",
+            account.key,
+            pattern,
+            offset
+        );
+        return Err(ProgramError::InvalidAccountData);
+    }
+    Ok(())
+}
+
 // -----------------
 // process_schedulecommit_and_undelegation_cpi_with_mod_after
 // -----------------
@@ -370,8 +460,7 @@ fn process_schedulecommit_and_undelegation_cpi_with_mod_after(
         // Increase count of the PDA account
         let main_account = {
             let main_account_data = committee.try_borrow_data()?;
-            let mut main_account =
-                MainAccount::try_from_slice(&main_account_data)?;
+            let mut main_account = MainAccount::try_decode(&main_account_data)?;
             main_account.count += 1;
             main_account
         };
@@ -0,0 +1,56 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+
+/// Pattern of the data which is stored after the [crate::MainAccount] in
+/// accounts that were resized, which allows verifying that large accounts
+/// arrive intact after they were committed.
+#[derive(
+    BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq,
+)]
+pub enum DataPattern {
+    /// Every byte has the same value
+    Repeat(u8),
+    /// Each byte is the previous one plus one, wrapping around at 255
+    Increasing(u8),
+    /// Bytes generated via xorshift from the seed, so that shifted or
+    /// truncated data is detected
+    Pseudorandom(u64),
+}
+
+impl DataPattern {
+    /// Returns `len` bytes following this pattern.
+    pub fn bytes(&self, len: usize) -> Vec<u8> {
+        let mut data = vec![0; len];
+        self.fill(&mut data);
+        data
+    }
+
+    pub fn fill(&self, data: &mut [u8]) {
+        match *self {
+            DataPattern::Repeat(byte) => data.fill(byte),
+            DataPattern::Increasing(start) => {
+                for (idx, byte) in data.iter_mut().enumerate() {
+                    *byte = start.wrapping_add(idx as u8);
+                }
+            }
+            DataPattern::Pseudorandom(seed) => {
+                // xorshift is stuck at 0, thus we never start from there
+                let mut state = seed.max(1);
+                for chunk in data.chunks_mut(8) {
+                    state ^= state << 13;
+                    state ^= state >> 7;
+                    state ^= state << 17;
+                    chunk.copy_from_slice(&state.to_le_bytes()[..chunk.len()]);
+                }
+            }
+        }
+    }
+
+    /// Returns the offset of the first byte that doesn't follow this pattern.
+    pub fn find_mismatch(&self, data: &[u8]) -> Option<usize> {
+        let expected = self.bytes(data.len());
+        expected
+            .iter()
+            .zip(data.iter())
+            .position(|(expected, actual)| expected != actual)
+    }
+}
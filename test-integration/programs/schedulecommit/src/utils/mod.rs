@@ -1,3 +1,4 @@
+use magicblock_commit_errors::ScheduleCommitError;
 use solana_program::{
     account_info::AccountInfo,
     entrypoint::ProgramResult,
@@ -21,7 +22,9 @@ pub fn assert_keys_equal<F: FnOnce() -> String>(
     if provided_key.ne(expected_key) {
         msg!("Err: {}", get_msg());
         msg!("Err: provided {} expected {}", provided_key, expected_key);
-        Err(ProgramError::Custom(1))
+        Err(ProgramError::Custom(
+            ScheduleCommitError::AccountKeyMismatch.into(),
+        ))
     } else {
         Ok(())
     }
@@ -4,7 +4,9 @@ use anyhow::{Context, Result};
 use integration_test_tools::IntegrationTestContext;
 use program_schedulecommit::api::{
     delegate_account_cpi_instruction, init_account_instruction, pda_and_bump,
+    resize_account_instruction,
 };
+use solana_program::entrypoint::MAX_PERMITTED_DATA_INCREASE;
 use solana_rpc_client::rpc_client::RpcClient;
 use solana_rpc_client_api::config::RpcSendTransactionConfig;
 #[allow(unused_imports)]
@@ -126,6 +128,48 @@ impl ScheduleCommitTestContext {
             .with_context(|| "Failed to initialize committees")
     }
 
+    /// Resizes the committees on chain so that they hold [data_len] pattern
+    /// bytes after the main account.
+    /// Needs to run before they are delegated and grows them in steps of
+    /// [MAX_PERMITTED_DATA_INCREASE] since that is the max per instruction.
+    pub fn resize_committees(&self, data_len: u32) -> Result<Signature> {
+        let step = MAX_PERMITTED_DATA_INCREASE as u32;
+        let mut ixs = vec![];
+        for (payer, committee) in &self.committees {
+            let mut len = 0;
+            while len < data_len {
+                len = (len + step).min(data_len);
+                ixs.push(resize_account_instruction(
+                    payer.pubkey(),
+                    *committee,
+                    len,
+                ));
+            }
+        }
+        let payers = self
+            .committees
+            .iter()
+            .map(|(payer, _)| payer)
+            .collect::<Vec<_>>();
+
+        let tx = Transaction::new_signed_with_payer(
+            &ixs,
+            Some(&payers[0].pubkey()),
+            &payers,
+            *self.try_chain_blockhash()?,
+        );
+        self.try_chain_client()?
+            .send_and_confirm_transaction_with_spinner_and_config(
+                &tx,
+                self.commitment,
+                RpcSendTransactionConfig {
+                    skip_preflight: true,
+                    ..Default::default()
+                },
+            )
+            .with_context(|| "Failed to resize committees")
+    }
+
     pub fn delegate_committees(
         &self,
         blockhash: Option<Hash>,
@@ -27,6 +27,7 @@ use solana_sdk::{
 };
 use test_tools_core::init_logger;
 use utils::{
+    assert_committee_accounts_were_not_committed_nor_undelegated,
     assert_is_instruction_error,
     assert_one_committee_account_was_undelegated_on_chain,
     assert_one_committee_synchronized_count,
@@ -314,7 +315,7 @@ fn test_committed_and_undelegated_single_account_redelegation() {
 
         // 3. Re-delegate the same account
         {
-            std::thread::sleep(std::time::Duration::from_secs(2));
+            ctx.wait_for_undelegation(committees[0].1).unwrap();
             let blockhash = chain_client.get_latest_blockhash().unwrap();
             ctx.delegate_committees(Some(blockhash)).unwrap();
         }
@@ -401,7 +402,8 @@ fn test_committed_and_undelegated_accounts_redelegation() {
 
         // 3. Re-delegate the same accounts
         {
-            std::thread::sleep(std::time::Duration::from_secs(2));
+            ctx.wait_for_undelegation(committees[0].1).unwrap();
+            ctx.wait_for_undelegation(committees[1].1).unwrap();
             let blockhash = chain_client.get_latest_blockhash().unwrap();
             ctx.delegate_committees(Some(blockhash)).unwrap();
         }
@@ -458,9 +460,8 @@ fn test_committing_and_undelegating_one_account_modifying_it_after() {
             "instruction modified data of an account it does not own",
         );
 
-        // TODO(thlorenz): even though the transaction fails the account is still committed and undelegated
-        // this should be fixed ASAP and this test extended to verify that
-        // Same for test_committing_and_undelegating_two_accounts_modifying_them_after
+        // Since the transaction failed the commit must never be realized
+        assert_committee_accounts_were_not_committed_nor_undelegated(&ctx);
     });
 }
 
@@ -475,5 +476,8 @@ fn test_committing_and_undelegating_two_accounts_modifying_them_after() {
             &res,
             "instruction modified data of an account it does not own",
         );
+
+        // Since the transaction failed the commit must never be realized
+        assert_committee_accounts_were_not_committed_nor_undelegated(&ctx);
     });
 }
@@ -0,0 +1,179 @@
+use integration_test_tools::{
+    conversions::pubkey_from_magic_program, run_test,
+};
+use log::*;
+use magicblock_core::magic_program;
+use program_schedulecommit::{
+    api::{
+        fill_pattern_instruction,
+        schedule_commit_and_undelegate_cpi_instruction,
+        schedule_commit_cpi_instruction, verify_pattern_instruction,
+    },
+    pattern::DataPattern,
+    MainAccount,
+};
+use schedulecommit_client::{
+    ScheduleCommitTestContext, ScheduleCommitTestContextFields,
+};
+use solana_rpc_client::rpc_client::SerializableTransaction;
+use solana_rpc_client_api::config::RpcSendTransactionConfig;
+use solana_sdk::{
+    instruction::Instruction, signature::Signature, signer::Signer,
+    transaction::Transaction,
+};
+use test_tools_core::init_logger;
+
+// The committed data is sent inside the commit transaction, thus it needs to
+// fit into one together with the accounts of the commit and undelegation
+const PATTERN_DATA_LEN: u32 = 256;
+
+fn get_context_with_delegated_resized_committees(
+    ncommittees: usize,
+) -> ScheduleCommitTestContext {
+    let ctx =
+        ScheduleCommitTestContext::try_new_random_keys(ncommittees).unwrap();
+    ctx.init_committees().unwrap();
+    ctx.resize_committees(PATTERN_DATA_LEN).unwrap();
+    ctx.delegate_committees(None).unwrap();
+    ctx
+}
+
+fn fill_and_schedule_commit(
+    ctx: &ScheduleCommitTestContext,
+    patterns: &[DataPattern],
+    undelegate: bool,
+) -> Signature {
+    let ScheduleCommitTestContextFields {
+        payer,
+        committees,
+        commitment,
+        ephem_client,
+        ephem_blockhash,
+        ..
+    } = ctx.fields();
+
+    let players = committees
+        .iter()
+        .map(|(player, _)| player.pubkey())
+        .collect::<Vec<_>>();
+    let pdas = committees.iter().map(|(_, pda)| *pda).collect::<Vec<_>>();
+    let schedule_commit_ix = if undelegate {
+        schedule_commit_and_undelegate_cpi_instruction
+    } else {
+        schedule_commit_cpi_instruction
+    };
+
+    let mut ixs = pdas
+        .iter()
+        .zip(patterns)
+        .map(|(pda, pattern)| fill_pattern_instruction(*pda, *pattern))
+        .collect::<Vec<Instruction>>();
+    ixs.push(schedule_commit_ix(
+        payer.pubkey(),
+        pubkey_from_magic_program(magic_program::id()),
+        pubkey_from_magic_program(magic_program::MAGIC_CONTEXT_PUBKEY),
+        &players,
+        &pdas,
+    ));
+
+    let tx = Transaction::new_signed_with_payer(
+        &ixs,
+        Some(&payer.pubkey()),
+        &[&payer],
+        *ephem_blockhash,
+    );
+    let sig = *tx.get_signature();
+    let res = ephem_client
+        .send_and_confirm_transaction_with_spinner_and_config(
+            &tx,
+            *commitment,
+            RpcSendTransactionConfig {
+                skip_preflight: true,
+                ..Default::default()
+            },
+        );
+    info!("{} '{:?}'", sig, res);
+    res.unwrap();
+    sig
+}
+
+fn assert_chain_data_follows_pattern(
+    ctx: &ScheduleCommitTestContext,
+    patterns: &[DataPattern],
+) {
+    for ((_, pda), pattern) in ctx.committees.iter().zip(patterns) {
+        let data = ctx.fetch_chain_account_data(*pda).unwrap();
+        assert_eq!(
+            data.len(),
+            MainAccount::SIZE + PATTERN_DATA_LEN as usize,
+            "size of {} changed",
+            pda
+        );
+        assert_eq!(
+            pattern.find_mismatch(&data[MainAccount::SIZE..]),
+            None,
+            "committed data of {} doesn't match {:?}",
+            pda,
+            pattern
+        );
+    }
+}
+
+#[test]
+fn test_committing_resized_accounts_with_patterned_data() {
+    run_test!({
+        let ctx = get_context_with_delegated_resized_committees(1);
+        let patterns = [DataPattern::Pseudorandom(42)];
+
+        let sig = fill_and_schedule_commit(&ctx, &patterns, false);
+        ctx.wait_for_commit_confirmed(sig).unwrap();
+
+        assert_chain_data_follows_pattern(&ctx, &patterns);
+    });
+}
+
+#[test]
+fn test_committing_and_undelegating_resized_accounts_with_patterned_data() {
+    run_test!({
+        let ctx = get_context_with_delegated_resized_committees(1);
+        let patterns = [DataPattern::Increasing(7)];
+
+        let sig = fill_and_schedule_commit(&ctx, &patterns, true);
+        ctx.wait_for_commit_confirmed(sig).unwrap();
+        for (_, pda) in &ctx.committees {
+            ctx.wait_for_undelegation(*pda).unwrap();
+        }
+
+        assert_chain_data_follows_pattern(&ctx, &patterns);
+
+        // The program can verify the data once it owns the accounts again
+        let ScheduleCommitTestContextFields {
+            payer, commitment, ..
+        } = ctx.fields();
+        let chain_client = ctx.try_chain_client().unwrap();
+        let ixs = ctx
+            .committees
+            .iter()
+            .zip(patterns)
+            .map(|((_, pda), pattern)| {
+                verify_pattern_instruction(*pda, pattern)
+            })
+            .collect::<Vec<_>>();
+        let tx = Transaction::new_signed_with_payer(
+            &ixs,
+            Some(&payer.pubkey()),
+            &[&payer],
+            chain_client.get_latest_blockhash().unwrap(),
+        );
+        chain_client
+            .send_and_confirm_transaction_with_spinner_and_config(
+                &tx,
+                *commitment,
+                RpcSendTransactionConfig {
+                    skip_preflight: true,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+    });
+}
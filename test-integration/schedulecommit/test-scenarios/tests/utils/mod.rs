@@ -173,6 +173,48 @@ pub fn assert_account_was_undelegated_on_chain(
     assert_eq!(owner, new_owner, "new owner");
 }
 
+#[allow(dead_code)] // used in 02_commit_and_undelegate.rs
+pub fn assert_committee_accounts_were_not_committed_nor_undelegated(
+    ctx: &ScheduleCommitTestContext,
+) {
+    // Give the validator enough time to realize a commit in case it was
+    // wrongly scheduled
+    ctx.wait_for_delta_slot_ephem(5).unwrap();
+
+    let id = program_schedulecommit::id();
+    for (_, pda) in &ctx.committees {
+        let chain_owner = ctx.fetch_chain_account_owner(*pda).unwrap();
+        assert_eq!(
+            chain_owner, DELEGATION_PROGRAM_ID,
+            "pda ({}) still owned by delegation program on chain",
+            pda
+        );
+        let ephem_owner = ctx.fetch_ephem_account_owner(*pda).unwrap();
+        assert_eq!(
+            ephem_owner, id,
+            "pda ({}) still owned by program in ephemeral",
+            pda
+        );
+
+        // Neither the base layer nor the ephemeral account may reflect the
+        // modification of the failed transaction, i.e. the count remains the
+        // one set when the committees were initialized
+        let chain_data = ctx.fetch_chain_account_data(*pda).unwrap();
+        let ephem_data = ctx.fetch_ephem_account_data(*pda).unwrap();
+        assert_eq!(
+            chain_data, ephem_data,
+            "pda ({}) data on chain matches ephemeral data",
+            pda
+        );
+        let chain_account = MainAccount::try_decode(&chain_data).unwrap();
+        assert_eq!(
+            chain_account.count, 0,
+            "pda ({}) count was not committed to chain",
+            pda
+        );
+    }
+}
+
 #[allow(dead_code)] // used in 02_commit_and_undelegate.rs
 pub fn assert_tx_failed_with_instruction_error(
     tx_result: Result<Signature, solana_rpc_client_api::client_error::Error>,
@@ -1,4 +1,5 @@
 use integration_test_tools::conversions::pubkey_from_magic_program;
+use magicblock_commit_errors::ScheduleCommitError;
 use magicblock_core::magic_program;
 use program_schedulecommit::api::schedule_commit_cpi_instruction;
 use schedulecommit_client::{
@@ -13,7 +14,7 @@ use solana_sdk::{
 };
 
 use crate::utils::{
-    create_nested_schedule_cpis_instruction,
+    assert_schedule_commit_error, create_nested_schedule_cpis_instruction,
     create_sibling_non_cpi_instruction,
     create_sibling_schedule_cpis_instruction,
 };
@@ -21,12 +22,6 @@ mod utils;
 
 const _PROGRAM_ADDR: &str = "9hgprgZiRWmy8KkfvUuaVkDGrqo9GzeXMohwq6BazgUY";
 
-const PROGRAM_ID_NOT_FOUND: &str =
-    "ScheduleCommit ERR: failed to find parent program id";
-const INVALID_ACCOUNT_OWNER: &str = "Invalid account owner";
-const NEEDS_TO_BE_OWNED_BY_INVOKING_PROGRAM: &str =
-    "needs to be owned by the invoking program";
-
 fn prepare_ctx_with_account_to_commit() -> ScheduleCommitTestContext {
     let ctx = if std::env::var("FIXED_KP").is_ok() {
         ScheduleCommitTestContext::try_new(2)
@@ -104,7 +99,15 @@ fn test_schedule_commit_directly_with_single_ix() {
                 ..Default::default()
             },
         );
-    ctx.assert_ephemeral_transaction_error(sig, &res, PROGRAM_ID_NOT_FOUND);
+    ctx.assert_ephemeral_transaction_error(
+        sig,
+        &res,
+        ScheduleCommitError::ParentProgramNotFound.name(),
+    );
+    assert_schedule_commit_error(
+        &res,
+        ScheduleCommitError::ParentProgramNotFound,
+    );
 }
 
 #[test]
@@ -164,7 +167,15 @@ fn test_schedule_commit_directly_with_commit_ix_sandwiched() {
                 ..Default::default()
             },
         );
-    ctx.assert_ephemeral_transaction_error(sig, &res, PROGRAM_ID_NOT_FOUND);
+    ctx.assert_ephemeral_transaction_error(
+        sig,
+        &res,
+        ScheduleCommitError::ParentProgramNotFound.name(),
+    );
+    assert_schedule_commit_error(
+        &res,
+        ScheduleCommitError::ParentProgramNotFound,
+    );
 }
 
 #[test]
@@ -210,11 +221,14 @@ fn test_schedule_commit_via_direct_and_indirect_cpi_of_other_program() {
             },
         );
 
-    ctx.assert_ephemeral_transaction_error(sig, &res, INVALID_ACCOUNT_OWNER);
     ctx.assert_ephemeral_transaction_error(
         sig,
         &res,
-        NEEDS_TO_BE_OWNED_BY_INVOKING_PROGRAM,
+        ScheduleCommitError::AccountNotOwnedByInvoker.name(),
+    );
+    assert_schedule_commit_error(
+        &res,
+        ScheduleCommitError::AccountNotOwnedByInvoker,
     );
 }
 
@@ -274,10 +288,13 @@ fn test_schedule_commit_via_direct_and_from_other_program_indirect_cpi_including
             },
         );
 
-    ctx.assert_ephemeral_transaction_error(sig, &res, INVALID_ACCOUNT_OWNER);
     ctx.assert_ephemeral_transaction_error(
         sig,
         &res,
-        NEEDS_TO_BE_OWNED_BY_INVOKING_PROGRAM,
+        ScheduleCommitError::AccountNotOwnedByInvoker.name(),
+    );
+    assert_schedule_commit_error(
+        &res,
+        ScheduleCommitError::AccountNotOwnedByInvoker,
     );
 }
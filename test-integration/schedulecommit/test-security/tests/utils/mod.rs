@@ -1,9 +1,15 @@
 use integration_test_tools::conversions::pubkey_from_magic_program;
+use magicblock_commit_errors::ScheduleCommitError;
 use magicblock_core::magic_program;
 use program_schedulecommit_security::ScheduleCommitSecurityInstruction;
+use solana_rpc_client_api::client_error::{
+    Error as ClientError, ErrorKind as ClientErrorKind,
+};
 use solana_sdk::{
-    instruction::{AccountMeta, Instruction},
+    instruction::{AccountMeta, Instruction, InstructionError},
     pubkey::Pubkey,
+    signature::Signature,
+    transaction::TransactionError,
 };
 
 /// Attempts to commit the PDAs twice as follows:
@@ -84,3 +90,30 @@ pub fn create_sibling_non_cpi_instruction(payer: Pubkey) -> Instruction {
         account_metas,
     )
 }
+
+/// Asserts that the transaction failed with the [expected] schedule commit
+/// error, matching it by name instead of by the code of the custom error.
+pub fn assert_schedule_commit_error(
+    res: &Result<Signature, ClientError>,
+    expected: ScheduleCommitError,
+) {
+    let code = match res {
+        Err(ClientError {
+            kind:
+                ClientErrorKind::TransactionError(
+                    TransactionError::InstructionError(
+                        _,
+                        InstructionError::Custom(code),
+                    ),
+                ),
+            ..
+        }) => *code,
+        _ => panic!("Expected {} error, but got {:?}", expected, res),
+    };
+    assert_eq!(
+        ScheduleCommitError::from_code(code).map(|err| err.name()),
+        Some(expected.name()),
+        "Unexpected custom error code {}",
+        code
+    );
+}
@@ -0,0 +1,232 @@
+use integration_test_tools::IntegrationTestContext;
+use solana_rpc_client::rpc_client::RpcClient;
+use solana_sdk::{
+    compute_budget::ComputeBudgetInstruction,
+    ed25519_program,
+    instruction::Instruction,
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    secp256k1_instruction::new_secp256k1_instruction,
+    signature::{Keypair, Signature},
+    signer::Signer,
+    transaction::Transaction,
+};
+
+// MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr
+const MEMO_PROGRAM_PK: Pubkey = Pubkey::new_from_array([
+    5, 74, 83, 90, 153, 41, 33, 6, 77, 36, 232, 113, 96, 218, 56, 124, 124, 53,
+    181, 221, 188, 146, 187, 129, 228, 31, 168, 64, 65, 5, 68, 141,
+]);
+
+// -----------------
+// Helpers
+// -----------------
+fn init_payer(ctx: &IntegrationTestContext) -> Keypair {
+    let payer = Keypair::new();
+    ctx.airdrop_chain(&payer.pubkey(), LAMPORTS_PER_SOL)
+        .expect("failed to airdrop to on-chain account");
+    payer
+}
+
+/// Sends the transaction with preflight enabled, so that transactions which
+/// are rejected before execution fail the same way as failing ones.
+fn send_with_preflight(
+    rpc_client: &RpcClient,
+    ixs: &[Instruction],
+    payer: &Keypair,
+) -> Result<Signature, String> {
+    let blockhash = rpc_client
+        .get_latest_blockhash()
+        .map_err(|err| err.to_string())?;
+    let tx = Transaction::new_signed_with_payer(
+        ixs,
+        Some(&payer.pubkey()),
+        &[payer],
+        blockhash,
+    );
+    rpc_client
+        .send_and_confirm_transaction(&tx)
+        .map_err(|err| err.to_string())
+}
+
+/// Runs the same instructions on chain and in the ephemeral and asserts that
+/// both either succeed or fail.
+fn assert_parity(
+    ctx: &IntegrationTestContext,
+    payer: &Keypair,
+    ixs: &[Instruction],
+    expect_success: bool,
+) {
+    let chain_res =
+        send_with_preflight(ctx.try_chain_client().unwrap(), ixs, payer);
+    let ephem_res = send_with_preflight(&ctx.ephem_client, ixs, payer);
+    assert_eq!(
+        chain_res.is_ok(),
+        expect_success,
+        "chain result: {:?}",
+        chain_res
+    );
+    assert_eq!(
+        ephem_res.is_ok(),
+        chain_res.is_ok(),
+        "ephem diverged from chain, chain: {:?}, ephem: {:?}",
+        chain_res,
+        ephem_res
+    );
+}
+
+fn memo_ix(memo: &[u8]) -> Instruction {
+    Instruction::new_with_bytes(MEMO_PROGRAM_PK, memo, vec![])
+}
+
+/// Builds an ed25519 precompile instruction verifying a single signature
+/// whose pubkey, signature and message are all contained in its data.
+fn ed25519_ix(signer: &Keypair, message: &[u8]) -> Instruction {
+    const OFFSETS_START: u16 = 2;
+    const OFFSETS_SIZE: u16 = 14;
+    const PUBKEY_SIZE: u16 = 32;
+    const SIGNATURE_SIZE: u16 = 64;
+
+    let signature = signer.sign_message(message);
+    let public_key_offset = OFFSETS_START + OFFSETS_SIZE;
+    let signature_offset = public_key_offset + PUBKEY_SIZE;
+    let message_data_offset = signature_offset + SIGNATURE_SIZE;
+
+    let mut data = vec![1u8, 0u8];
+    for value in [
+        signature_offset,
+        u16::MAX,
+        public_key_offset,
+        u16::MAX,
+        message_data_offset,
+        message.len() as u16,
+        u16::MAX,
+    ] {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+    data.extend_from_slice(signer.pubkey().as_ref());
+    data.extend_from_slice(signature.as_ref());
+    data.extend_from_slice(message);
+
+    Instruction::new_with_bytes(ed25519_program::ID, &data, vec![])
+}
+
+fn secp256k1_ix(message: &[u8]) -> Instruction {
+    let secret_key = libsecp256k1::SecretKey::parse(&[7u8; 32]).unwrap();
+    new_secp256k1_instruction(&secret_key, message)
+}
+
+fn tamper_last_byte(mut ix: Instruction) -> Instruction {
+    let last = ix.data.last_mut().unwrap();
+    *last = last.wrapping_add(1);
+    ix
+}
+
+// -----------------
+// Compute Budget
+// -----------------
+#[test]
+fn compute_budget_limit_and_price() {
+    let ctx = IntegrationTestContext::try_new().unwrap();
+    let payer = init_payer(&ctx);
+    assert_parity(
+        &ctx,
+        &payer,
+        &[
+            ComputeBudgetInstruction::set_compute_unit_limit(50_000),
+            ComputeBudgetInstruction::set_compute_unit_price(1),
+            memo_ix(b"compute budget"),
+        ],
+        true,
+    );
+}
+
+#[test]
+fn compute_budget_duplicate_instruction() {
+    let ctx = IntegrationTestContext::try_new().unwrap();
+    let payer = init_payer(&ctx);
+    assert_parity(
+        &ctx,
+        &payer,
+        &[
+            ComputeBudgetInstruction::set_compute_unit_limit(50_000),
+            ComputeBudgetInstruction::set_compute_unit_limit(60_000),
+        ],
+        false,
+    );
+}
+
+#[test]
+fn compute_budget_limit_exceeded() {
+    let ctx = IntegrationTestContext::try_new().unwrap();
+    let payer = init_payer(&ctx);
+    assert_parity(
+        &ctx,
+        &payer,
+        &[
+            ComputeBudgetInstruction::set_compute_unit_limit(100),
+            memo_ix(b"not enough compute units to log this memo"),
+        ],
+        false,
+    );
+}
+
+// -----------------
+// Memo
+// -----------------
+#[test]
+fn memo_valid_utf8() {
+    let ctx = IntegrationTestContext::try_new().unwrap();
+    let payer = init_payer(&ctx);
+    assert_parity(&ctx, &payer, &[memo_ix("parity ✓".as_bytes())], true);
+}
+
+#[test]
+fn memo_invalid_utf8() {
+    let ctx = IntegrationTestContext::try_new().unwrap();
+    let payer = init_payer(&ctx);
+    assert_parity(&ctx, &payer, &[memo_ix(&[0xf0, 0x28, 0x8c, 0xbc])], false);
+}
+
+// -----------------
+// Precompiles
+// -----------------
+#[test]
+fn ed25519_valid_signature() {
+    let ctx = IntegrationTestContext::try_new().unwrap();
+    let payer = init_payer(&ctx);
+    let signer = Keypair::new();
+    assert_parity(&ctx, &payer, &[ed25519_ix(&signer, b"ed25519")], true);
+}
+
+#[test]
+fn ed25519_invalid_signature() {
+    let ctx = IntegrationTestContext::try_new().unwrap();
+    let payer = init_payer(&ctx);
+    let signer = Keypair::new();
+    assert_parity(
+        &ctx,
+        &payer,
+        &[tamper_last_byte(ed25519_ix(&signer, b"ed25519"))],
+        false,
+    );
+}
+
+#[test]
+fn secp256k1_valid_signature() {
+    let ctx = IntegrationTestContext::try_new().unwrap();
+    let payer = init_payer(&ctx);
+    assert_parity(&ctx, &payer, &[secp256k1_ix(b"secp256k1")], true);
+}
+
+#[test]
+fn secp256k1_invalid_signature() {
+    let ctx = IntegrationTestContext::try_new().unwrap();
+    let payer = init_payer(&ctx);
+    assert_parity(
+        &ctx,
+        &payer,
+        &[tamper_last_byte(secp256k1_ix(b"secp256k1"))],
+        false,
+    );
+}
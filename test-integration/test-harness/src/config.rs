@@ -0,0 +1,144 @@
+use std::path::PathBuf;
+
+use integration_test_tools::{
+    devnet_fixture::DevnetFixture, toml_to_args::ProgramLoader,
+};
+use magicblock_config::{
+    AccountsConfig, EphemeralConfig, LifecycleMode, ProgramConfig,
+};
+use solana_sdk::{
+    native_token::LAMPORTS_PER_SOL, pubkey::Pubkey, signature::Keypair,
+};
+
+/// Env var pointing to the ephemeral validator binary used when
+/// [EphemeralTestConfig::validator_bin] is not set.
+pub const VALIDATOR_BIN_ENV: &str = "MAGICBLOCK_VALIDATOR_BIN";
+/// Name of the ephemeral validator binary built by this repository, looked up
+/// in the `PATH` when neither the config nor [VALIDATOR_BIN_ENV] provide one.
+pub const DEFAULT_VALIDATOR_BIN: &str = "rpc";
+
+// -----------------
+// EphemeralTestConfig
+// -----------------
+pub struct EphemeralTestConfig {
+    /// Config of the ephemeral validator.
+    /// Its remote is always pointed at the chain validator started alongside.
+    pub ephem: EphemeralConfig,
+    pub chain: ChainValidatorConfig,
+    /// Identity of the ephemeral validator, it is funded on chain before the
+    /// ephemeral validator starts so that it can pay for commits.
+    pub validator_keypair: Keypair,
+    pub validator_airdrop_lamports: u64,
+    /// Path to the ephemeral validator binary.
+    pub validator_bin: Option<PathBuf>,
+}
+
+impl Default for EphemeralTestConfig {
+    fn default() -> Self {
+        Self {
+            ephem: EphemeralConfig {
+                accounts: AccountsConfig {
+                    lifecycle: LifecycleMode::Ephemeral,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            chain: ChainValidatorConfig::default(),
+            validator_keypair: Keypair::new(),
+            validator_airdrop_lamports: 100 * LAMPORTS_PER_SOL,
+            validator_bin: None,
+        }
+    }
+}
+
+impl EphemeralTestConfig {
+    /// Loads the provided program into both the chain and the ephemeral
+    /// validator.
+    pub fn with_program(mut self, id: Pubkey, path: impl Into<String>) -> Self {
+        let program = ProgramConfig {
+            id,
+            path: path.into(),
+        };
+        self.chain.programs.push(program.clone());
+        self.ephem.programs.push(program);
+        self
+    }
+
+    pub(crate) fn resolve_validator_bin(&self) -> PathBuf {
+        self.validator_bin.clone().unwrap_or_else(|| {
+            std::env::var(VALIDATOR_BIN_ENV)
+                .map(PathBuf::from)
+                .unwrap_or_else(|_| PathBuf::from(DEFAULT_VALIDATOR_BIN))
+        })
+    }
+}
+
+// -----------------
+// ChainValidatorConfig
+// -----------------
+/// Config of the `solana-test-validator` which acts as the chain the
+/// ephemeral validator clones accounts from and commits to.
+pub struct ChainValidatorConfig {
+    pub port: u16,
+    /// Programs deployed at genesis, the path of each is an absolute path to
+    /// the program's `.so` file.
+    pub programs: Vec<ProgramConfig>,
+    pub program_loader: ProgramLoader,
+    /// Accounts, delegated accounts and further programs loaded at genesis.
+    pub fixture: DevnetFixture,
+    /// Accounts and programs cloned from the provided cluster at genesis.
+    pub clone: Vec<Pubkey>,
+    pub clone_url: Option<String>,
+}
+
+impl Default for ChainValidatorConfig {
+    fn default() -> Self {
+        Self {
+            port: 7799,
+            programs: vec![],
+            program_loader: ProgramLoader::default(),
+            fixture: DevnetFixture::default(),
+            clone: vec![],
+            clone_url: None,
+        }
+    }
+}
+
+impl ChainValidatorConfig {
+    pub fn url(&self) -> String {
+        format!("http://localhost:{}", self.port)
+    }
+
+    pub(crate) fn to_args(&self) -> Vec<String> {
+        let mut args = vec![
+            "--log".to_string(),
+            "--rpc-port".to_string(),
+            self.port.to_string(),
+            "-r".to_string(),
+            "--limit-ledger-size".to_string(),
+            "10000".to_string(),
+        ];
+        for program in &self.programs {
+            if self.program_loader == ProgramLoader::UpgradeableProgram {
+                args.push("--upgradeable-program".to_string());
+            } else {
+                args.push("--bpf-program".to_string());
+            }
+            args.push(program.id.to_string());
+            args.push(program.path.clone());
+            if self.program_loader == ProgramLoader::UpgradeableProgram {
+                args.push("none".to_string());
+            }
+        }
+        args.extend(self.fixture.to_args());
+        if let Some(url) = &self.clone_url {
+            for pubkey in &self.clone {
+                args.push("--clone".to_string());
+                args.push(pubkey.to_string());
+            }
+            args.push("--url".to_string());
+            args.push(url.clone());
+        }
+        args
+    }
+}
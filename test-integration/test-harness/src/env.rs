@@ -0,0 +1,372 @@
+use std::{
+    fmt, fs,
+    process::{self, Child},
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use borsh::BorshDeserialize;
+use integration_test_tools::{
+    scheduled_commits::ScheduledCommitResult,
+    validator::{start_test_validator_with_args, wait_for_validator},
+    IntegrationTestContext,
+};
+use magicblock_config::RemoteConfig;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    account::Account,
+    commitment_config::CommitmentConfig,
+    instruction::Instruction,
+    pubkey,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature},
+    signer::Signer,
+    transaction::Transaction,
+};
+use tempfile::TempDir;
+
+use crate::config::EphemeralTestConfig;
+
+pub const DELEGATION_PROGRAM_ID: Pubkey =
+    pubkey!("DELeGGvXpWV2fqJUhqcF5ZSYMS4JTLjteaAMARRSaeSh");
+
+// Allow transactions to take up to 20 seconds to confirm
+const MAX_UNCONFIRMED_COUNT: u64 = 40;
+const MILLIS_UNTIL_RECONFIRM: u64 = 500;
+
+// -----------------
+// EphemeralTestEnv
+// -----------------
+/// A chain validator (`solana-test-validator`) and an ephemeral validator
+/// that clones from and commits to it, both running as child processes.
+/// The validators are killed when the env is dropped.
+///
+/// ```ignore
+/// let env = EphemeralTestEnv::start(
+///     EphemeralTestConfig::default().with_program(PROGRAM_ID, PROGRAM_PATH),
+/// )
+/// .await?;
+/// env.airdrop_chain(&payer.pubkey(), LAMPORTS_PER_SOL).await?;
+/// env.delegate(create_delegate_ix(payer.pubkey()), &payer, &pda).await?;
+/// let sig = env.send_and_confirm_ephem(&[commit_ix], &payer, &[]).await?;
+/// let res = env.verify_commit::<MyState>(sig).await?;
+/// ```
+pub struct EphemeralTestEnv {
+    ctx: Arc<IntegrationTestContext>,
+    chain_client: RpcClient,
+    ephem_client: RpcClient,
+    validator_identity: Pubkey,
+    chain_validator: Child,
+    ephem_validator: Child,
+    _config_dir: TempDir,
+}
+
+impl EphemeralTestEnv {
+    /// Starts the chain validator, funds the ephemeral validator's identity
+    /// on it and then starts the ephemeral validator.
+    /// Resolves once both validators accept RPC requests.
+    pub async fn start(config: EphemeralTestConfig) -> Result<Self> {
+        tokio::task::spawn_blocking(move || Self::start_blocking(config))
+            .await
+            .context("Validator startup task panicked")?
+    }
+
+    fn start_blocking(config: EphemeralTestConfig) -> Result<Self> {
+        let EphemeralTestConfig {
+            ephem,
+            chain,
+            validator_keypair,
+            validator_airdrop_lamports,
+            ..
+        } = &config;
+        let validator_bin = config.resolve_validator_bin();
+        let config_dir =
+            tempfile::tempdir().context("Failed to create config dir")?;
+        let chain_url = chain.url();
+        let ephem_url = format!("http://localhost:{}", ephem.rpc.port);
+
+        let mut ephem = ephem.clone();
+        ephem.accounts.remote = RemoteConfig::Custom(
+            chain_url.as_str().try_into().context("Invalid chain url")?,
+        );
+        let config_path = config_dir.path().join("ephem.toml");
+        fs::write(&config_path, ephem.to_string())
+            .context("Failed to write ephemeral validator config")?;
+
+        // 1. Chain
+        let mut chain_validator = start_test_validator_with_args(
+            chain.to_args(),
+            chain.port,
+            config_dir.path(),
+            "CHAIN",
+        )
+        .ok_or_else(|| anyhow!("Chain validator failed to start"))?;
+
+        // 2. Fund ephemeral validator identity on chain
+        let validator_identity = validator_keypair.pubkey();
+        if let Err(err) = IntegrationTestContext::airdrop(
+            &solana_rpc_client::rpc_client::RpcClient::new(chain_url.clone()),
+            &validator_identity,
+            *validator_airdrop_lamports,
+            CommitmentConfig::confirmed(),
+        ) {
+            kill_validator(&mut chain_validator, "chain");
+            return Err(err);
+        }
+
+        // 3. Ephemeral
+        let mut command = process::Command::new(&validator_bin);
+        command
+            .arg(&config_path)
+            .env("VALIDATOR_KEYPAIR", validator_keypair.to_base58_string())
+            .env("RUST_LOG_STYLE", "EPHEM");
+        eprintln!("Starting ephemeral validator with {:?}", command);
+        let ephem_validator = match command.spawn().with_context(|| {
+            format!("Failed to start ephemeral validator {:?}", validator_bin)
+        }) {
+            Ok(child) => wait_for_validator(child, ephem.rpc.port),
+            Err(err) => {
+                kill_validator(&mut chain_validator, "chain");
+                return Err(err);
+            }
+        };
+        let Some(mut ephem_validator) = ephem_validator else {
+            kill_validator(&mut chain_validator, "chain");
+            bail!("Ephemeral validator failed to start");
+        };
+
+        let ctx = match IntegrationTestContext::try_new_with_urls(
+            &chain_url, &ephem_url,
+        ) {
+            Ok(ctx) => ctx,
+            Err(err) => {
+                kill_validator(&mut ephem_validator, "ephemeral");
+                kill_validator(&mut chain_validator, "chain");
+                return Err(err);
+            }
+        };
+
+        let commitment = ctx.commitment;
+        Ok(Self {
+            ctx: Arc::new(ctx),
+            chain_client: RpcClient::new_with_commitment(chain_url, commitment),
+            ephem_client: RpcClient::new_with_commitment(ephem_url, commitment),
+            validator_identity,
+            chain_validator,
+            ephem_validator,
+            _config_dir: config_dir,
+        })
+    }
+
+    /// Blocking context connected to both validators providing more
+    /// fine grained helpers.
+    pub fn ctx(&self) -> &IntegrationTestContext {
+        &self.ctx
+    }
+
+    pub fn chain_client(&self) -> &RpcClient {
+        &self.chain_client
+    }
+
+    pub fn ephem_client(&self) -> &RpcClient {
+        &self.ephem_client
+    }
+
+    pub fn validator_identity(&self) -> &Pubkey {
+        &self.validator_identity
+    }
+
+    // -----------------
+    // Airdrop
+    // -----------------
+    pub async fn airdrop_chain(
+        &self,
+        pubkey: &Pubkey,
+        lamports: u64,
+    ) -> Result<Signature> {
+        airdrop(&self.chain_client, pubkey, lamports).await
+    }
+
+    pub async fn airdrop_ephem(
+        &self,
+        pubkey: &Pubkey,
+        lamports: u64,
+    ) -> Result<Signature> {
+        airdrop(&self.ephem_client, pubkey, lamports).await
+    }
+
+    // -----------------
+    // Transactions
+    // -----------------
+    pub async fn send_and_confirm_chain(
+        &self,
+        ixs: &[Instruction],
+        payer: &Keypair,
+        signers: &[&Keypair],
+    ) -> Result<Signature> {
+        send_and_confirm(&self.chain_client, ixs, payer, signers).await
+    }
+
+    pub async fn send_and_confirm_ephem(
+        &self,
+        ixs: &[Instruction],
+        payer: &Keypair,
+        signers: &[&Keypair],
+    ) -> Result<Signature> {
+        send_and_confirm(&self.ephem_client, ixs, payer, signers).await
+    }
+
+    // -----------------
+    // Accounts
+    // -----------------
+    pub async fn fetch_chain_account(
+        &self,
+        pubkey: &Pubkey,
+    ) -> Result<Account> {
+        self.chain_client
+            .get_account(pubkey)
+            .await
+            .with_context(|| {
+                format!("Failed to fetch chain account '{}'", pubkey)
+            })
+    }
+
+    pub async fn fetch_ephem_account(
+        &self,
+        pubkey: &Pubkey,
+    ) -> Result<Account> {
+        self.ephem_client
+            .get_account(pubkey)
+            .await
+            .with_context(|| {
+                format!("Failed to fetch ephemeral account '{}'", pubkey)
+            })
+    }
+
+    // -----------------
+    // Delegation
+    // -----------------
+    /// Runs the program specific delegate instruction on chain and verifies
+    /// that the account is now owned by the delegation program.
+    pub async fn delegate(
+        &self,
+        delegate_ix: Instruction,
+        payer: &Keypair,
+        delegated_account: &Pubkey,
+    ) -> Result<Signature> {
+        let sig = self
+            .send_and_confirm_chain(&[delegate_ix], payer, &[])
+            .await?;
+        let owner = self.fetch_chain_account(delegated_account).await?.owner;
+        if owner != DELEGATION_PROGRAM_ID {
+            bail!(
+                "Account '{}' is owned by '{}' after delegation",
+                delegated_account,
+                owner
+            );
+        }
+        Ok(sig)
+    }
+
+    // -----------------
+    // Commits
+    // -----------------
+    /// Finds the commit scheduled by the ephemeral transaction with the
+    /// provided signature and verifies that all of its transactions were
+    /// confirmed on chain.
+    /// Returns the committed accounts deserialized as `T`.
+    pub async fn verify_commit<T>(
+        &self,
+        sig: Signature,
+    ) -> Result<ScheduledCommitResult<T>>
+    where
+        T: fmt::Debug + BorshDeserialize + PartialEq + Eq + Send + 'static,
+    {
+        let ctx = self.ctx.clone();
+        tokio::task::spawn_blocking(move || {
+            let res = ctx.fetch_schedule_commit_result::<T>(sig)?;
+            res.confirm_commit_transactions_on_chain(&ctx)?;
+            Ok(res)
+        })
+        .await
+        .context("Commit verification task panicked")?
+    }
+}
+
+impl Drop for EphemeralTestEnv {
+    fn drop(&mut self) {
+        kill_validator(&mut self.ephem_validator, "ephemeral");
+        kill_validator(&mut self.chain_validator, "chain");
+    }
+}
+
+fn kill_validator(validator: &mut Child, label: &str) {
+    if let Err(err) = validator.kill() {
+        eprintln!("ERR: Failed to kill {} validator: {:?}", label, err);
+    }
+}
+
+// -----------------
+// Async RPC Helpers
+// -----------------
+async fn airdrop(
+    rpc_client: &RpcClient,
+    pubkey: &Pubkey,
+    lamports: u64,
+) -> Result<Signature> {
+    let sig = rpc_client
+        .request_airdrop(pubkey, lamports)
+        .await
+        .with_context(|| format!("Failed to airdrop to '{}'", pubkey))?;
+    confirm(rpc_client, &sig).await.with_context(|| {
+        format!("Failed to confirm airdrop to '{}'", pubkey)
+    })?;
+    Ok(sig)
+}
+
+async fn send_and_confirm(
+    rpc_client: &RpcClient,
+    ixs: &[Instruction],
+    payer: &Keypair,
+    signers: &[&Keypair],
+) -> Result<Signature> {
+    let blockhash = rpc_client
+        .get_latest_blockhash()
+        .await
+        .context("Failed to get latest blockhash")?;
+    let mut all_signers = vec![payer];
+    all_signers.extend_from_slice(signers);
+    let tx = Transaction::new_signed_with_payer(
+        ixs,
+        Some(&payer.pubkey()),
+        &all_signers,
+        blockhash,
+    );
+    let sig = rpc_client
+        .send_transaction(&tx)
+        .await
+        .context("Failed to send transaction")?;
+    confirm(rpc_client, &sig).await?;
+    Ok(sig)
+}
+
+async fn confirm(rpc_client: &RpcClient, sig: &Signature) -> Result<()> {
+    for _ in 0..MAX_UNCONFIRMED_COUNT {
+        let status = rpc_client
+            .get_signature_status_with_commitment(sig, rpc_client.commitment())
+            .await
+            .with_context(|| format!("Failed to get status of '{}'", sig))?;
+        match status {
+            Some(Ok(())) => return Ok(()),
+            Some(Err(err)) => bail!("Transaction '{}' failed: {:?}", sig, err),
+            None => {
+                tokio::time::sleep(Duration::from_millis(
+                    MILLIS_UNTIL_RECONFIRM,
+                ))
+                .await
+            }
+        }
+    }
+    bail!("Transaction '{}' was not confirmed within timeout", sig)
+}
@@ -0,0 +1,12 @@
+//! Starts a chain and an ephemeral validator for integration tests of
+//! programs that delegate accounts to an ephemeral validator.
+//! See [EphemeralTestEnv] for an example.
+mod config;
+mod env;
+
+pub use config::*;
+pub use env::*;
+pub use integration_test_tools::{
+    scheduled_commits::ScheduledCommitResult, toml_to_args::ProgramLoader,
+    IntegrationTestContext,
+};
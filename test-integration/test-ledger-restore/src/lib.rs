@@ -56,6 +56,7 @@ pub fn start_validator_with_config(
         config_path,
         root_dir,
         workspace_dir,
+        output_dir: None,
     };
     (
         default_tmpdir,
@@ -108,6 +109,7 @@ pub fn setup_offline_validator(
         ledger: LedgerConfig {
             reset,
             path: Some(ledger_path.display().to_string()),
+            ..Default::default()
         },
         accounts: accounts_config.clone(),
         programs,
@@ -146,6 +148,7 @@ pub fn setup_validator_with_local_remote(
         ledger: LedgerConfig {
             reset,
             path: Some(ledger_path.display().to_string()),
+            ..Default::default()
         },
         accounts: accounts_config.clone(),
         programs,
@@ -0,0 +1,285 @@
+use cleanass::assert_eq;
+use std::{path::Path, process::Child};
+
+use integration_test_tools::{
+    expect, tmpdir::resolve_tmp_dir, IntegrationTestContext,
+};
+use program_flexi_counter::instruction::{
+    create_add_and_schedule_commit_ix, create_add_counter_ix, create_add_ix,
+};
+use program_flexi_counter::{
+    instruction::{create_delegate_ix, create_init_ix},
+    state::FlexiCounter,
+};
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    instruction::Instruction,
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature},
+    signer::Signer,
+};
+use test_ledger_restore::{
+    cleanup, confirm_tx_with_payer_chain, confirm_tx_with_payer_ephem,
+    fetch_counter_ephem, get_programs_with_flexi_counter,
+    send_tx_with_payer_ephem, setup_validator_with_local_remote,
+    wait_for_ledger_persist, TMP_DIR_LEDGER,
+};
+const COUNTER_MAIN: &str = "Main Counter";
+const COUNTER_READONLY: &str = "Readonly Counter";
+/// Transactions sent right before the validator is killed
+const WORKLOAD_TXS: u8 = 12;
+/// The readonly counters cloned during the workload and one cloned after
+/// the validator recovered
+const READONLY_COUNTERS: usize = WORKLOAD_TXS as usize / 4 + 1;
+fn payer_keypair() -> Keypair {
+    Keypair::new()
+}
+
+// In this test we kill the validator in the middle of a workload instead of
+// waiting for the ledger to be persisted first, like a crash would.
+//
+// The workload adds to a delegated counter, schedules commits of it and adds
+// the counts of readonly counters to it. Each readonly counter is cloned
+// when it is first used which stores its data as data mod in the ledger.
+//
+// We then restart the validator against the same ledger and verify that the
+// state it recovered is exactly the one of the transactions it recovered,
+// i.e. none of them was applied partially and each readonly counter was
+// cloned with the data it was cloned with originally.
+// Pending commits must not be sent again while the ledger is replayed.
+//
+// Finally we clone another readonly counter after the recovery and restart
+// once more to verify that its data mod does not clash with the ones that
+// were recovered.
+
+#[test]
+fn restore_ledger_after_crash_mid_workload() {
+    let (_, ledger_path) = resolve_tmp_dir(TMP_DIR_LEDGER);
+    let payer_main = payer_keypair();
+    let payers_readonly = (0..READONLY_COUNTERS)
+        .map(|_| payer_keypair())
+        .collect::<Vec<_>>();
+
+    let workload = write(&ledger_path, &payer_main, &payers_readonly);
+
+    let (mut validator, expected) =
+        recover(&ledger_path, &payer_main, &payers_readonly, workload);
+    validator.kill().unwrap();
+
+    let mut validator = read(&ledger_path, &payer_main, expected);
+    validator.kill().unwrap();
+}
+
+/// A transaction of the workload and the count it adds to the main counter
+struct WorkloadTx {
+    signature: Signature,
+    count: u64,
+}
+
+struct Workload {
+    /// The state of the main counter before the workload started
+    counter: FlexiCounter,
+    txs: Vec<WorkloadTx>,
+    /// Commit transactions of the main counter on chain when we crashed
+    chain_commits: usize,
+}
+
+fn readonly_count(idx: usize) -> u8 {
+    10 + idx as u8
+}
+
+fn write(
+    ledger_path: &Path,
+    payer_main: &Keypair,
+    payers_readonly: &[Keypair],
+) -> Workload {
+    let programs = get_programs_with_flexi_counter();
+
+    let (_, mut validator, ctx) =
+        setup_validator_with_local_remote(ledger_path, Some(programs), true);
+
+    // Init main counter on chain and delegate it to the ephemeral
+    expect!(
+        ctx.airdrop_chain(&payer_main.pubkey(), LAMPORTS_PER_SOL),
+        validator
+    );
+    confirm_tx_with_payer_chain(
+        create_init_ix(payer_main.pubkey(), COUNTER_MAIN.to_string()),
+        payer_main,
+        &mut validator,
+    );
+    confirm_tx_with_payer_chain(
+        create_delegate_ix(payer_main.pubkey()),
+        payer_main,
+        &mut validator,
+    );
+
+    // Init readonly counters on chain, each with a different count
+    for (idx, payer) in payers_readonly.iter().enumerate() {
+        expect!(
+            ctx.airdrop_chain(&payer.pubkey(), LAMPORTS_PER_SOL),
+            validator
+        );
+        confirm_tx_with_payer_chain(
+            create_init_ix(payer.pubkey(), COUNTER_READONLY.to_string()),
+            payer,
+            &mut validator,
+        );
+        confirm_tx_with_payer_chain(
+            create_add_ix(payer.pubkey(), readonly_count(idx)),
+            payer,
+            &mut validator,
+        );
+    }
+
+    // Make sure the state before the workload is persisted
+    confirm_tx_with_payer_ephem(
+        create_add_ix(payer_main.pubkey(), 1),
+        payer_main,
+        &mut validator,
+    );
+    wait_for_ledger_persist(&mut validator);
+    let counter = fetch_counter_ephem(&payer_main.pubkey(), &mut validator);
+
+    // Send the workload without waiting for any of it to be confirmed.
+    // The counts differ per transaction such that none of them are the same.
+    let txs = (1..=WORKLOAD_TXS)
+        .map(|i| {
+            let (ix, count): (Instruction, u64) = match i % 4 {
+                0 => {
+                    let idx = usize::from(i / 4 - 1);
+                    (
+                        create_add_counter_ix(
+                            payer_main.pubkey(),
+                            payers_readonly[idx].pubkey(),
+                        ),
+                        readonly_count(idx).into(),
+                    )
+                }
+                2 => (
+                    create_add_and_schedule_commit_ix(
+                        payer_main.pubkey(),
+                        i,
+                        false,
+                    ),
+                    i.into(),
+                ),
+                _ => (create_add_ix(payer_main.pubkey(), i), i.into()),
+            };
+            let signature =
+                send_tx_with_payer_ephem(ix, payer_main, &mut validator);
+            WorkloadTx { signature, count }
+        })
+        .collect::<Vec<_>>();
+
+    // Crash without waiting for the workload or the ledger to be persisted
+    validator.kill().unwrap();
+
+    let chain_commits =
+        count_chain_commits(&ctx, &payer_main.pubkey()).unwrap();
+    Workload {
+        counter,
+        txs,
+        chain_commits,
+    }
+}
+
+fn recover(
+    ledger_path: &Path,
+    payer_main: &Keypair,
+    payers_readonly: &[Keypair],
+    workload: Workload,
+) -> (Child, FlexiCounter) {
+    let programs = get_programs_with_flexi_counter();
+
+    let (_, mut validator, ctx) =
+        setup_validator_with_local_remote(ledger_path, Some(programs), false);
+
+    // The recovered state needs to match the transactions that were
+    // recovered exactly
+    let recovered = workload
+        .txs
+        .iter()
+        .filter(|tx| {
+            let status = expect!(
+                ctx.ephem_client
+                    .get_signature_status_with_commitment_and_history(
+                        &tx.signature,
+                        CommitmentConfig::confirmed(),
+                        true,
+                    ),
+                validator
+            );
+            matches!(status, Some(Ok(())))
+        })
+        .collect::<Vec<_>>();
+    eprintln!(
+        "Recovered {} of {} workload transactions",
+        recovered.len(),
+        workload.txs.len()
+    );
+    let mut expected = FlexiCounter {
+        count: workload.counter.count
+            + recovered.iter().map(|tx| tx.count).sum::<u64>(),
+        updates: workload.counter.updates + recovered.len() as u64,
+        label: COUNTER_MAIN.to_string(),
+    };
+    let counter = fetch_counter_ephem(&payer_main.pubkey(), &mut validator);
+    assert_eq!(counter, expected, cleanup(&mut validator));
+
+    // Ensure we did not send commits again during ledger replay
+    let chain_commits =
+        expect!(count_chain_commits(&ctx, &payer_main.pubkey()), validator);
+    assert_eq!(
+        chain_commits, workload.chain_commits,
+        cleanup(&mut validator)
+    );
+
+    // Clone another readonly counter now that we recovered
+    let idx = READONLY_COUNTERS - 1;
+    confirm_tx_with_payer_ephem(
+        create_add_counter_ix(
+            payer_main.pubkey(),
+            payers_readonly[idx].pubkey(),
+        ),
+        payer_main,
+        &mut validator,
+    );
+    expected.count += u64::from(readonly_count(idx));
+    expected.updates += 1;
+    let counter = fetch_counter_ephem(&payer_main.pubkey(), &mut validator);
+    assert_eq!(counter, expected, cleanup(&mut validator));
+
+    wait_for_ledger_persist(&mut validator);
+    (validator, expected)
+}
+
+fn read(
+    ledger_path: &Path,
+    payer_main: &Keypair,
+    expected: FlexiCounter,
+) -> Child {
+    let programs = get_programs_with_flexi_counter();
+
+    let (_, mut validator, _) =
+        setup_validator_with_local_remote(ledger_path, Some(programs), false);
+
+    let counter = fetch_counter_ephem(&payer_main.pubkey(), &mut validator);
+    assert_eq!(counter, expected, cleanup(&mut validator));
+
+    validator
+}
+
+/// Counts the transactions of the main counter on chain once the commits
+/// that were already sent landed
+fn count_chain_commits(
+    ctx: &IntegrationTestContext,
+    payer: &Pubkey,
+) -> anyhow::Result<usize> {
+    let (pda, _) = FlexiCounter::pda(payer);
+    for _ in 0..3 {
+        ctx.wait_for_next_slot_chain()?;
+    }
+    Ok(ctx.get_signaturestats_for_address_chain(&pda)?.len())
+}
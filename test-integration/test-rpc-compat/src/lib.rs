@@ -0,0 +1,170 @@
+use std::{env, fs, path::PathBuf};
+
+use serde_json::{json, Map, Value};
+use solana_rpc_client::rpc_client::RpcClient;
+use solana_rpc_client_api::{
+    client_error::ErrorKind,
+    request::{RpcError, RpcRequest},
+};
+
+/// Fields whose presence legitimately differs between the ephemeral validator
+/// and solana-test-validator and which clients don't depend on.
+/// Paths are joined with `.` and array elements are addressed via `[]`.
+const IGNORED_PATHS: &[&str] = &["context.apiVersion", "feature-set"];
+
+/// Issues a raw JSON RPC request and returns its `result`.
+/// RPC errors are returned as `{ "error": { "code": <code> } }` since that is
+/// the part of an error clients match on.
+pub fn rpc_call(
+    client: &RpcClient,
+    method: &'static str,
+    params: Value,
+) -> Value {
+    match client.send::<Value>(RpcRequest::Custom { method }, params) {
+        Ok(result) => result,
+        Err(err) => match err.kind() {
+            ErrorKind::RpcError(RpcError::RpcResponseError {
+                code, ..
+            }) => {
+                json!({ "error": { "code": code } })
+            }
+            _ => panic!("'{}' request failed: {:?}", method, err),
+        },
+    }
+}
+
+/// Replaces every value with the name of its type, keeping the structure,
+/// i.e. the field names, intact.
+/// Arrays are reduced to the schema of their first element since the
+/// number of elements depends on the state of the validator.
+pub fn json_schema(value: &Value) -> Value {
+    match value {
+        Value::Null => json!("null"),
+        Value::Bool(_) => json!("bool"),
+        Value::Number(n) if n.is_f64() => json!("float"),
+        Value::Number(_) => json!("integer"),
+        Value::String(_) => json!("string"),
+        Value::Array(values) => {
+            Value::Array(values.first().map(json_schema).into_iter().collect())
+        }
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, value)| (key.clone(), json_schema(value)))
+                .collect::<Map<_, _>>(),
+        ),
+    }
+}
+
+/// Collects the places where the [actual] schema is not compatible with the
+/// [expected] one.
+/// Every field of [expected] needs to be present in [actual] with the same
+/// type, additional fields in [actual] are fine since clients ignore them.
+/// A `null` on either side is compatible with any type since optional fields
+/// are `null` depending on the state, i.e. a `blockTime` that isn't known yet.
+pub fn schema_divergences(expected: &Value, actual: &Value) -> Vec<String> {
+    let mut divergences = vec![];
+    collect_divergences(expected, actual, "", &mut divergences);
+    divergences
+}
+
+fn collect_divergences(
+    expected: &Value,
+    actual: &Value,
+    path: &str,
+    divergences: &mut Vec<String>,
+) {
+    if IGNORED_PATHS.contains(&path) {
+        return;
+    }
+    let null = json!("null");
+    if expected == &null || actual == &null {
+        return;
+    }
+    match (expected, actual) {
+        (Value::Object(expected), Value::Object(actual)) => {
+            for (key, expected_value) in expected {
+                let path = join_path(path, key);
+                match actual.get(key) {
+                    Some(actual_value) => collect_divergences(
+                        expected_value,
+                        actual_value,
+                        &path,
+                        divergences,
+                    ),
+                    None if IGNORED_PATHS.contains(&path.as_str()) => {}
+                    None => divergences.push(format!("{}: missing", path)),
+                }
+            }
+        }
+        (Value::Array(expected), Value::Array(actual)) => {
+            // An empty array is compatible with any element type
+            if let (Some(expected), Some(actual)) =
+                (expected.first(), actual.first())
+            {
+                let path = join_path(path, "[]");
+                collect_divergences(expected, actual, &path, divergences);
+            }
+        }
+        // Clients parse integers and floats the same way
+        (Value::String(expected), Value::String(actual))
+            if is_number(expected) && is_number(actual) => {}
+        _ if expected == actual => {}
+        _ => divergences.push(format!(
+            "{}: expected {}, got {}",
+            if path.is_empty() { "<root>" } else { path },
+            expected,
+            actual
+        )),
+    }
+}
+
+fn is_number(schema: &str) -> bool {
+    schema == "integer" || schema == "float"
+}
+
+fn join_path(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+// -----------------
+// Golden Files
+// -----------------
+fn golden_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("golden")
+        .join(format!("{}.json", name))
+}
+
+/// Compares the [schema] against the one stored in the golden file of the
+/// given [name] and returns a description of the differences, if any.
+/// The golden file is written if it doesn't exist yet or if the
+/// `UPDATE_GOLDEN` env var is set, i.e. after an intentional change of a
+/// response.
+pub fn check_golden(name: &str, schema: &Value) -> Option<String> {
+    let path = golden_path(name);
+    let update = env::var("UPDATE_GOLDEN").is_ok();
+    let serialized = serde_json::to_string_pretty(schema).unwrap() + "\n";
+    if update || !path.exists() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, serialized).unwrap_or_else(|err| {
+            panic!("Failed to write golden file {:?}: {:?}", path, err)
+        });
+        return None;
+    }
+    let golden = fs::read_to_string(&path).unwrap_or_else(|err| {
+        panic!("Failed to read golden file {:?}: {:?}", path, err)
+    });
+    (golden != serialized).then(|| {
+        format!(
+            "{} changed, rerun with UPDATE_GOLDEN=1 if this is intended\n\
+            golden: {}\nactual: {}",
+            path.display(),
+            golden.trim_end(),
+            serialized.trim_end()
+        )
+    })
+}
@@ -0,0 +1,179 @@
+use integration_test_tools::IntegrationTestContext;
+use serde_json::{json, Value};
+use solana_rpc_client::rpc_client::RpcClient;
+use solana_sdk::{
+    compute_budget::ComputeBudgetInstruction, native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey, signature::Keypair, signer::Signer,
+    transaction::Transaction,
+};
+use test_rpc_compat::{
+    check_golden, json_schema, rpc_call, schema_divergences,
+};
+
+struct RpcCase {
+    name: &'static str,
+    method: &'static str,
+    chain_params: Value,
+    ephem_params: Value,
+}
+
+impl RpcCase {
+    fn new(name: &'static str, method: &'static str, params: Value) -> Self {
+        Self {
+            name,
+            method,
+            chain_params: params.clone(),
+            ephem_params: params,
+        }
+    }
+
+    /// For requests referencing state that differs between both validators,
+    /// i.e. the signature of a transaction sent to each of them.
+    fn with_params(
+        name: &'static str,
+        method: &'static str,
+        chain_params: Value,
+        ephem_params: Value,
+    ) -> Self {
+        Self {
+            name,
+            method,
+            chain_params,
+            ephem_params,
+        }
+    }
+}
+
+// -----------------
+// Helpers
+// -----------------
+fn send_noop_transaction(
+    ctx: &IntegrationTestContext,
+    rpc_client: &RpcClient,
+    payer: &Keypair,
+) -> String {
+    let mut tx = Transaction::new_with_payer(
+        &[ComputeBudgetInstruction::set_compute_unit_limit(200_000)],
+        Some(&payer.pubkey()),
+    );
+    let (sig, confirmed) =
+        IntegrationTestContext::send_and_confirm_transaction(
+            rpc_client,
+            &mut tx,
+            &[payer],
+            ctx.commitment,
+        )
+        .unwrap();
+    assert!(confirmed, "transaction {} was not confirmed", sig);
+    sig.to_string()
+}
+
+fn rpc_cases(
+    ctx: &IntegrationTestContext,
+    payer: &Pubkey,
+    chain_sig: &str,
+    ephem_sig: &str,
+) -> Vec<RpcCase> {
+    let commitment = json!({ "commitment": ctx.commitment.commitment });
+    let payer = payer.to_string();
+    let missing = Pubkey::new_unique().to_string();
+    let base64 = json!({
+        "encoding": "base64",
+        "commitment": ctx.commitment.commitment,
+    });
+    let tx_config = json!({
+        "encoding": "json",
+        "commitment": ctx.commitment.commitment,
+        "maxSupportedTransactionVersion": 0,
+    });
+    vec![
+        RpcCase::new("get_version", "getVersion", json!([])),
+        RpcCase::new("get_health", "getHealth", json!([])),
+        RpcCase::new("get_identity", "getIdentity", json!([])),
+        RpcCase::new("get_slot", "getSlot", json!([commitment])),
+        RpcCase::new("get_block_height", "getBlockHeight", json!([commitment])),
+        RpcCase::new("get_epoch_info", "getEpochInfo", json!([commitment])),
+        RpcCase::new("get_genesis_hash", "getGenesisHash", json!([])),
+        RpcCase::new(
+            "get_latest_blockhash",
+            "getLatestBlockhash",
+            json!([commitment]),
+        ),
+        RpcCase::new(
+            "get_minimum_balance_for_rent_exemption",
+            "getMinimumBalanceForRentExemption",
+            json!([128]),
+        ),
+        RpcCase::new("get_balance", "getBalance", json!([payer, commitment])),
+        RpcCase::new(
+            "get_account_info",
+            "getAccountInfo",
+            json!([payer, base64]),
+        ),
+        RpcCase::new(
+            "get_account_info_missing",
+            "getAccountInfo",
+            json!([missing, base64]),
+        ),
+        RpcCase::new(
+            "get_multiple_accounts",
+            "getMultipleAccounts",
+            json!([[payer, missing], base64]),
+        ),
+        RpcCase::with_params(
+            "get_signature_statuses",
+            "getSignatureStatuses",
+            json!([[chain_sig], { "searchTransactionHistory": true }]),
+            json!([[ephem_sig], { "searchTransactionHistory": true }]),
+        ),
+        RpcCase::with_params(
+            "get_transaction",
+            "getTransaction",
+            json!([chain_sig, tx_config]),
+            json!([ephem_sig, tx_config]),
+        ),
+        RpcCase::new(
+            "get_signatures_for_address",
+            "getSignaturesForAddress",
+            json!([payer, commitment]),
+        ),
+    ]
+}
+
+#[test]
+fn test_rpc_responses_match_solana_test_validator() {
+    let ctx = IntegrationTestContext::try_new().unwrap();
+    let chain_client = ctx.try_chain_client().unwrap();
+
+    // The payer is cloned into the ephemeral when it pays for the transaction
+    let payer = Keypair::new();
+    ctx.airdrop_chain(&payer.pubkey(), LAMPORTS_PER_SOL)
+        .expect("failed to airdrop to on-chain account");
+    let chain_sig = send_noop_transaction(&ctx, chain_client, &payer);
+    let ephem_sig = send_noop_transaction(&ctx, &ctx.ephem_client, &payer);
+
+    let mut failures = vec![];
+    for case in rpc_cases(&ctx, &payer.pubkey(), &chain_sig, &ephem_sig) {
+        let chain_schema = json_schema(&rpc_call(
+            chain_client,
+            case.method,
+            case.chain_params,
+        ));
+        let ephem_schema = json_schema(&rpc_call(
+            &ctx.ephem_client,
+            case.method,
+            case.ephem_params,
+        ));
+        for divergence in schema_divergences(&chain_schema, &ephem_schema) {
+            failures.push(format!("{}: {}", case.name, divergence));
+        }
+        if let Some(diff) = check_golden(case.name, &ephem_schema) {
+            failures.push(format!("{}: {}", case.name, diff));
+        }
+    }
+    assert!(
+        failures.is_empty(),
+        "RPC responses diverge:\n{}",
+        failures.join("\n")
+    );
+}
@@ -1,48 +1,113 @@
 use integration_test_tools::{
-    toml_to_args::{config_to_args, rpc_port_from_config, ProgramLoader},
+    devnet_fixture::DevnetFixture,
+    toml_to_args::ProgramLoader,
     validator::{
-        resolve_workspace_dir, start_magic_block_validator_with_config,
-        wait_for_validator, TestRunnerPaths,
+        resolve_workspace_dir,
+        start_isolated_magic_block_validator_with_config,
+        start_isolated_test_validator_with_config,
+        start_magic_block_validator_with_config,
+        start_test_validator_with_config, IsolatedCluster,
+        IsolatedValidatorPair, TestRunnerPaths,
     },
 };
 use std::{
     error::Error,
     io,
     path::Path,
-    process::{self, Output},
+    process::{self, Child, Output},
+    thread,
 };
 use teepee::Teepee;
-use test_runner::cleanup::{cleanup_devnet_only, cleanup_validators};
+use test_runner::{
+    artifacts::SuiteArtifacts,
+    cleanup::{
+        cleanup_devnet_only, cleanup_isolated_validators, cleanup_validators,
+    },
+};
+
+type SuiteResult = Result<Vec<Output>, Box<dyn Error + Send + Sync>>;
+
+/// The ledger restore test which kills the validator mid-workload, it runs
+/// separately from the other ledger restore tests
+const CRASH_RECOVERY_TEST: &str = "restore_ledger_after_crash_mid_workload";
 
 pub fn main() {
     let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
 
-    let Ok((security_output, scenarios_output)) =
-        run_schedule_commit_tests(&manifest_dir)
-    else {
+    // Only runs the crash recovery scenario when CRASH_RECOVERY_ONLY is set
+    if std::env::var("CRASH_RECOVERY_ONLY").is_ok() {
+        if let Ok(output) = run_crash_recovery_tests(&manifest_dir) {
+            assert_cargo_tests_passed(output);
+        }
         return;
-    };
+    }
 
-    let Ok(issues_frequent_commits_output) =
-        run_issues_frequent_commmits_tests(&manifest_dir)
-    else {
-        return;
+    // Each suite runs against its own validator pair on separate ports when
+    // PARALLEL is set, otherwise all of them share the default ports
+    let suite_outputs = if std::env::var("PARALLEL").is_ok() {
+        run_suites_in_parallel(&manifest_dir)
+    } else {
+        run_suites_serially(&manifest_dir)
     };
-    let Ok(cloning_output) = run_cloning_tests(&manifest_dir) else {
+    let Some(suite_outputs) = suite_outputs else {
         return;
     };
 
+    // The ledger tests start their ephem validator on the default ports and
+    // kill all validators when done, so they always run last
     let Ok(restore_ledger_output) = run_restore_ledger_tests(&manifest_dir)
     else {
         return;
     };
+    let Ok(crash_recovery_output) = run_crash_recovery_tests(&manifest_dir)
+    else {
+        return;
+    };
 
     // Assert that all tests passed
-    assert_cargo_tests_passed(security_output);
-    assert_cargo_tests_passed(scenarios_output);
-    assert_cargo_tests_passed(cloning_output);
-    assert_cargo_tests_passed(issues_frequent_commits_output);
+    for output in suite_outputs {
+        assert_cargo_tests_passed(output);
+    }
     assert_cargo_tests_passed(restore_ledger_output);
+    assert_cargo_tests_passed(crash_recovery_output);
+}
+
+const SUITES: [fn(&str, Option<&IsolatedValidatorPair>) -> SuiteResult; 4] = [
+    run_schedule_commit_tests,
+    run_issues_frequent_commmits_tests,
+    run_cloning_tests,
+    run_rpc_compat_tests,
+];
+
+fn run_suites_serially(manifest_dir: &str) -> Option<Vec<Output>> {
+    let mut outputs = vec![];
+    for run_suite in SUITES {
+        outputs.extend(run_suite(manifest_dir, None).ok()?);
+    }
+    Some(outputs)
+}
+
+fn run_suites_in_parallel(manifest_dir: &str) -> Option<Vec<Output>> {
+    let results = thread::scope(|scope| {
+        let handles = SUITES
+            .map(|run_suite| {
+                scope.spawn(move || {
+                    let pair = IsolatedValidatorPair::allocate();
+                    run_suite(manifest_dir, Some(&pair))
+                })
+            })
+            .into_iter()
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().expect("Test suite panicked"))
+            .collect::<Vec<_>>()
+    });
+    let mut outputs = vec![];
+    for result in results {
+        outputs.extend(result.ok()?);
+    }
+    Some(outputs)
 }
 
 // -----------------
@@ -52,11 +117,15 @@ fn run_restore_ledger_tests(
     manifest_dir: &str,
 ) -> Result<Output, Box<dyn Error>> {
     eprintln!("======== RUNNING RESTORE LEDGER TESTS ========");
+    let mut artifacts =
+        SuiteArtifacts::create(&resolve_workspace_dir(), "restore-ledger");
     // The ledger tests manage their own ephem validator so all we start up here
     // is devnet
     let mut devnet_validator = match start_validator(
         "restore-ledger-conf.devnet.toml",
         ValidatorCluster::Chain(None),
+        None,
+        &artifacts,
     ) {
         Some(validator) => validator,
         None => {
@@ -69,7 +138,14 @@ fn run_restore_ledger_tests(
         "Running restore ledger tests in {}",
         test_restore_ledger_dir
     );
-    let output = match run_test(test_restore_ledger_dir, Default::default()) {
+    let output = match run_test(
+        test_restore_ledger_dir,
+        RunTestConfig {
+            skip: Some(CRASH_RECOVERY_TEST),
+            ..Default::default()
+        },
+        None,
+    ) {
         Ok(output) => output,
         Err(err) => {
             eprintln!("Failed to run restore ledger tests: {:?}", err);
@@ -78,20 +154,71 @@ fn run_restore_ledger_tests(
         }
     };
     cleanup_devnet_only(&mut devnet_validator);
+    artifacts.add_test_output("restore-ledger", &output);
+    artifacts.complete();
+    Ok(output)
+}
+
+/// Kills the ephem validator in the middle of a workload including pending
+/// commits and data mods and verifies that it recovers its state from the
+/// ledger once it is restarted.
+fn run_crash_recovery_tests(
+    manifest_dir: &str,
+) -> Result<Output, Box<dyn Error>> {
+    eprintln!("======== RUNNING CRASH RECOVERY TESTS ========");
+    let mut artifacts =
+        SuiteArtifacts::create(&resolve_workspace_dir(), "crash-recovery");
+    // Like the ledger tests this test manages its own ephem validator
+    let mut devnet_validator = match start_validator(
+        "restore-ledger-conf.devnet.toml",
+        ValidatorCluster::Chain(None),
+        None,
+        &artifacts,
+    ) {
+        Some(validator) => validator,
+        None => {
+            panic!("Failed to start devnet validator properly");
+        }
+    };
+    let test_restore_ledger_dir =
+        format!("{}/../{}", manifest_dir, "test-ledger-restore");
+    let output = match run_test(
+        test_restore_ledger_dir,
+        RunTestConfig {
+            test: Some(CRASH_RECOVERY_TEST),
+            ..Default::default()
+        },
+        None,
+    ) {
+        Ok(output) => output,
+        Err(err) => {
+            eprintln!("Failed to run crash recovery tests: {:?}", err);
+            cleanup_devnet_only(&mut devnet_validator);
+            return Err(err.into());
+        }
+    };
+    cleanup_devnet_only(&mut devnet_validator);
+    artifacts.add_test_output("crash-recovery", &output);
+    artifacts.complete();
     Ok(output)
 }
 
 fn run_schedule_commit_tests(
     manifest_dir: &str,
-) -> Result<(Output, Output), Box<dyn Error>> {
+    pair: Option<&IsolatedValidatorPair>,
+) -> SuiteResult {
     eprintln!(
         "======== Starting DEVNET Validator for Scenarios + Security ========"
     );
+    let mut artifacts =
+        SuiteArtifacts::create(&resolve_workspace_dir(), "schedulecommit");
 
     // Start validators via `cargo run --release  -- <config>
     let mut devnet_validator = match start_validator(
         "schedulecommit-conf.devnet.toml",
         ValidatorCluster::Chain(None),
+        pair,
+        &artifacts,
     ) {
         Some(validator) => validator,
         None => {
@@ -107,6 +234,8 @@ fn run_schedule_commit_tests(
     let mut ephem_validator = match start_validator(
         "schedulecommit-conf.ephem.toml",
         ValidatorCluster::Ephem,
+        pair,
+        &artifacts,
     ) {
         Some(validator) => validator,
         None => {
@@ -122,11 +251,11 @@ fn run_schedule_commit_tests(
         format!("{}/../{}", manifest_dir, "schedulecommit/test-security");
     eprintln!("Running security tests in {}", test_security_dir);
     let test_security_output =
-        match run_test(test_security_dir, Default::default()) {
+        match run_test(test_security_dir, Default::default(), pair) {
             Ok(output) => output,
             Err(err) => {
                 eprintln!("Failed to run security: {:?}", err);
-                cleanup_validators(&mut ephem_validator, &mut devnet_validator);
+                cleanup(&mut ephem_validator, &mut devnet_validator, pair);
                 return Err(err.into());
             }
         };
@@ -135,26 +264,36 @@ fn run_schedule_commit_tests(
     let test_scenarios_dir =
         format!("{}/../{}", manifest_dir, "schedulecommit/test-scenarios");
     let test_scenarios_output =
-        match run_test(test_scenarios_dir, Default::default()) {
+        match run_test(test_scenarios_dir, Default::default(), pair) {
             Ok(output) => output,
             Err(err) => {
                 eprintln!("Failed to run scenarios: {:?}", err);
-                cleanup_validators(&mut ephem_validator, &mut devnet_validator);
+                cleanup(&mut ephem_validator, &mut devnet_validator, pair);
                 return Err(err.into());
             }
         };
 
-    cleanup_validators(&mut ephem_validator, &mut devnet_validator);
-    Ok((test_security_output, test_scenarios_output))
+    cleanup(&mut ephem_validator, &mut devnet_validator, pair);
+    artifacts.add_test_output("security", &test_security_output);
+    artifacts.add_test_output("scenarios", &test_scenarios_output);
+    artifacts.complete();
+    Ok(vec![test_security_output, test_scenarios_output])
 }
 
 fn run_issues_frequent_commmits_tests(
     manifest_dir: &str,
-) -> Result<Output, Box<dyn Error>> {
+    pair: Option<&IsolatedValidatorPair>,
+) -> SuiteResult {
     eprintln!("======== RUNNING ISSUES TESTS - Frequent Commits ========");
+    let mut artifacts = SuiteArtifacts::create(
+        &resolve_workspace_dir(),
+        "issues-frequent-commits",
+    );
     let mut devnet_validator = match start_validator(
         "schedulecommit-conf.devnet.toml",
         ValidatorCluster::Chain(None),
+        pair,
+        &artifacts,
     ) {
         Some(validator) => validator,
         None => {
@@ -164,6 +303,8 @@ fn run_issues_frequent_commmits_tests(
     let mut ephem_validator = match start_validator(
         "schedulecommit-conf.ephem.frequent-commits.toml",
         ValidatorCluster::Ephem,
+        pair,
+        &artifacts,
     ) {
         Some(validator) => validator,
         None => {
@@ -180,23 +321,33 @@ fn run_issues_frequent_commmits_tests(
             package: Some("test-issues"),
             test: Some("test_frequent_commits_do_not_run_when_no_accounts_need_to_be_committed"),
         },
+        pair,
     ) {
         Ok(output) => output,
         Err(err) => {
             eprintln!("Failed to run issues: {:?}", err);
-            cleanup_validators(&mut ephem_validator, &mut devnet_validator);
+            cleanup(&mut ephem_validator, &mut devnet_validator, pair);
             return Err(err.into());
         }
     };
-    cleanup_validators(&mut ephem_validator, &mut devnet_validator);
-    Ok(test_output)
+    cleanup(&mut ephem_validator, &mut devnet_validator, pair);
+    artifacts.add_test_output("issues", &test_output);
+    artifacts.complete();
+    Ok(vec![test_output])
 }
 
-fn run_cloning_tests(manifest_dir: &str) -> Result<Output, Box<dyn Error>> {
+fn run_cloning_tests(
+    manifest_dir: &str,
+    pair: Option<&IsolatedValidatorPair>,
+) -> SuiteResult {
     eprintln!("======== RUNNING CLONING TESTS ========");
+    let mut artifacts =
+        SuiteArtifacts::create(&resolve_workspace_dir(), "cloning");
     let mut devnet_validator = match start_validator(
         "cloning-conf.devnet.toml",
         ValidatorCluster::Chain(Some(ProgramLoader::BpfProgram)),
+        pair,
+        &artifacts,
     ) {
         Some(validator) => validator,
         None => {
@@ -206,6 +357,8 @@ fn run_cloning_tests(manifest_dir: &str) -> Result<Output, Box<dyn Error>> {
     let mut ephem_validator = match start_validator(
         "cloning-conf.ephem.toml",
         ValidatorCluster::Ephem,
+        pair,
+        &artifacts,
     ) {
         Some(validator) => validator,
         None => {
@@ -217,16 +370,67 @@ fn run_cloning_tests(manifest_dir: &str) -> Result<Output, Box<dyn Error>> {
     };
     let test_cloning_dir = format!("{}/../{}", manifest_dir, "test-cloning");
     eprintln!("Running cloning tests in {}", test_cloning_dir);
-    let output = match run_test(test_cloning_dir, Default::default()) {
+    let output = match run_test(test_cloning_dir, Default::default(), pair) {
         Ok(output) => output,
         Err(err) => {
             eprintln!("Failed to run cloning tests: {:?}", err);
-            cleanup_validators(&mut ephem_validator, &mut devnet_validator);
+            cleanup(&mut ephem_validator, &mut devnet_validator, pair);
             return Err(err.into());
         }
     };
-    cleanup_validators(&mut ephem_validator, &mut devnet_validator);
-    Ok(output)
+    cleanup(&mut ephem_validator, &mut devnet_validator, pair);
+    artifacts.add_test_output("cloning", &output);
+    artifacts.complete();
+    Ok(vec![output])
+}
+
+fn run_rpc_compat_tests(
+    manifest_dir: &str,
+    pair: Option<&IsolatedValidatorPair>,
+) -> SuiteResult {
+    eprintln!("======== RUNNING RPC COMPAT TESTS ========");
+    let mut artifacts =
+        SuiteArtifacts::create(&resolve_workspace_dir(), "rpc_compat");
+    let mut devnet_validator = match start_validator(
+        "cloning-conf.devnet.toml",
+        ValidatorCluster::Chain(None),
+        pair,
+        &artifacts,
+    ) {
+        Some(validator) => validator,
+        None => {
+            panic!("Failed to start devnet validator properly");
+        }
+    };
+    let mut ephem_validator = match start_validator(
+        "cloning-conf.ephem.toml",
+        ValidatorCluster::Ephem,
+        pair,
+        &artifacts,
+    ) {
+        Some(validator) => validator,
+        None => {
+            devnet_validator
+                .kill()
+                .expect("Failed to kill devnet validator");
+            panic!("Failed to start ephemeral validator properly");
+        }
+    };
+    let test_rpc_compat_dir =
+        format!("{}/../{}", manifest_dir, "test-rpc-compat");
+    eprintln!("Running rpc compat tests in {}", test_rpc_compat_dir);
+    let output = match run_test(test_rpc_compat_dir, Default::default(), pair) {
+        Ok(output) => output,
+        Err(err) => {
+            eprintln!("Failed to run rpc compat tests: {:?}", err);
+            cleanup(&mut ephem_validator, &mut devnet_validator, pair);
+            return Err(err.into());
+        }
+    };
+    cleanup(&mut ephem_validator, &mut devnet_validator, pair);
+    artifacts.add_test_output("rpc_compat", &output);
+    artifacts.complete();
+    Ok(vec![output])
 }
 
 // -----------------
@@ -250,14 +454,21 @@ fn assert_cargo_tests_passed(output: process::Output) {
 #[derive(Default)]
 struct RunTestConfig<'a> {
     package: Option<&'a str>,
+    /// Only runs the tests whose name contains this
     test: Option<&'a str>,
+    /// Skips the tests whose name contains this
+    skip: Option<&'a str>,
 }
 
 fn run_test(
     manifest_dir: String,
     config: RunTestConfig,
+    pair: Option<&IsolatedValidatorPair>,
 ) -> io::Result<process::Output> {
     let mut cmd = process::Command::new("cargo");
+    if let Some(pair) = pair {
+        cmd.envs(pair.test_envs());
+    }
     cmd.env(
         "RUST_LOG",
         std::env::var("RUST_LOG").unwrap_or_else(|_| "info".to_string()),
@@ -267,9 +478,13 @@ fn run_test(
         cmd.arg("-p").arg(package);
     }
     if let Some(test) = config.test {
-        cmd.arg(format!("'{}'", test));
+        // Passed as is since no shell is involved that would unquote it
+        cmd.arg(test);
     }
     cmd.arg("--").arg("--test-threads=1").arg("--nocapture");
+    if let Some(skip) = config.skip {
+        cmd.arg("--skip").arg(skip);
+    }
     cmd.current_dir(manifest_dir.clone());
     Teepee::new(cmd).output()
 }
@@ -277,7 +492,10 @@ fn run_test(
 // -----------------
 // Validator Startup
 // -----------------
-fn resolve_paths(config_file: &str) -> TestRunnerPaths {
+fn resolve_paths(
+    config_file: &str,
+    artifacts: &SuiteArtifacts,
+) -> TestRunnerPaths {
     let workspace_dir = resolve_workspace_dir();
     let root_dir = Path::new(&workspace_dir)
         .join("..")
@@ -290,6 +508,7 @@ fn resolve_paths(config_file: &str) -> TestRunnerPaths {
         config_path,
         root_dir,
         workspace_dir,
+        output_dir: Some(artifacts.dir().to_path_buf()),
     }
 }
 
@@ -310,10 +529,20 @@ impl ValidatorCluster {
 fn start_validator(
     config_file: &str,
     cluster: ValidatorCluster,
+    pair: Option<&IsolatedValidatorPair>,
+    artifacts: &SuiteArtifacts,
 ) -> Option<process::Child> {
     let log_suffix = cluster.log_suffix();
-    let test_runner_paths = resolve_paths(config_file);
+    let test_runner_paths = resolve_paths(config_file, artifacts);
 
+    if let Some(pair) = pair {
+        return start_isolated_validator(
+            &test_runner_paths,
+            cluster,
+            log_suffix,
+            pair,
+        );
+    }
     match cluster {
         ValidatorCluster::Chain(program_loader)
             if std::env::var("FORCE_MAGIC_BLOCK_VALIDATOR").is_err() =>
@@ -332,54 +561,56 @@ fn start_validator(
     }
 }
 
-fn start_test_validator_with_config(
+fn start_isolated_validator(
     test_runner_paths: &TestRunnerPaths,
-    program_loader: Option<ProgramLoader>,
+    cluster: ValidatorCluster,
     log_suffix: &str,
+    pair: &IsolatedValidatorPair,
 ) -> Option<process::Child> {
-    let TestRunnerPaths {
-        config_path,
-        root_dir,
-        workspace_dir,
-    } = test_runner_paths;
-
-    let port = rpc_port_from_config(config_path);
-    let mut args = config_to_args(config_path, program_loader);
-
-    let accounts_dir = workspace_dir.join("configs").join("accounts");
-    let accounts = [
-        (
-            "mAGicPQYBMvcYveUZA5F5UNNwyHvfYh5xkLS2Fr1mev",
-            "validator-authority.json",
-        ),
-        (
-            "LUzidNSiPNjYNkxZcUm5hYHwnWPwsUfh2US1cpWwaBm",
-            "luzid-authority.json",
-        ),
-    ];
-
-    let account_args = accounts
-        .iter()
-        .flat_map(|(account, file)| {
-            let account_path = accounts_dir.join(file).canonicalize().unwrap();
-            vec![
-                "--account".to_string(),
-                account.to_string(),
-                account_path.to_str().unwrap().to_string(),
-            ]
-        })
-        .collect::<Vec<_>>();
-
-    args.extend(account_args);
-
-    let mut command = process::Command::new("solana-test-validator");
-    command
-        .args(args)
-        .env("RUST_LOG", "solana=warn")
-        .env("RUST_LOG_STYLE", log_suffix)
-        .current_dir(root_dir);
+    match cluster {
+        ValidatorCluster::Chain(program_loader)
+            if std::env::var("FORCE_MAGIC_BLOCK_VALIDATOR").is_err() =>
+        {
+            let fixture = DevnetFixture::with_default_accounts(
+                &test_runner_paths.workspace_dir,
+            );
+            start_isolated_test_validator_with_config(
+                test_runner_paths,
+                program_loader,
+                log_suffix,
+                &fixture,
+                pair,
+            )
+        }
+        ValidatorCluster::Chain(_) => {
+            start_isolated_magic_block_validator_with_config(
+                test_runner_paths,
+                log_suffix,
+                false,
+                pair,
+                IsolatedCluster::Chain,
+            )
+        }
+        ValidatorCluster::Ephem => {
+            start_isolated_magic_block_validator_with_config(
+                test_runner_paths,
+                log_suffix,
+                false,
+                pair,
+                IsolatedCluster::Ephem,
+            )
+        }
+    }
+}
 
-    eprintln!("Starting test validator with {:?}", command);
-    let validator = command.spawn().expect("Failed to start validator");
-    wait_for_validator(validator, port)
+fn cleanup(
+    ephem_validator: &mut Child,
+    devnet_validator: &mut Child,
+    pair: Option<&IsolatedValidatorPair>,
+) {
+    if pair.is_some() {
+        cleanup_isolated_validators(ephem_validator, devnet_validator);
+    } else {
+        cleanup_validators(ephem_validator, devnet_validator);
+    }
 }
@@ -0,0 +1,83 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Output,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Env var overriding the dir into which the artifacts of failed suites are
+/// written, defaults to `test-integration/target/test-artifacts`.
+pub const TEST_ARTIFACTS_DIR: &str = "TEST_ARTIFACTS_DIR";
+
+/// Collects the logs and ledgers of the validators a suite runs against
+/// together with the output of its tests into a timestamped dir.
+///
+/// The dir is removed when the suite passed and kept when any of its tests
+/// failed or the suite didn't complete, i.e. due to a validator failing to
+/// start.
+pub struct SuiteArtifacts {
+    dir: PathBuf,
+    completed: bool,
+    failed: bool,
+}
+
+impl SuiteArtifacts {
+    pub fn create(workspace_dir: &Path, suite: &str) -> Self {
+        let artifacts_dir = std::env::var(TEST_ARTIFACTS_DIR)
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| {
+                workspace_dir.join("target").join("test-artifacts")
+            });
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_millis();
+        let dir = artifacts_dir.join(format!("{}-{}", timestamp, suite));
+        fs::create_dir_all(&dir).unwrap_or_else(|err| {
+            panic!("Failed to create artifacts dir {:?}: {:?}", dir, err)
+        });
+        Self {
+            dir,
+            completed: false,
+            failed: false,
+        }
+    }
+
+    /// Dir into which the validators of the suite write their logs and
+    /// ledgers.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Writes the stdout and stderr of `cargo test` into the artifacts.
+    pub fn add_test_output(&mut self, test: &str, output: &Output) {
+        self.failed |= !output.status.success();
+        for (ext, content) in
+            [("stdout", &output.stdout), ("stderr", &output.stderr)]
+        {
+            let path = self.dir.join(format!("test-{}.{}.log", test, ext));
+            if let Err(err) = fs::write(&path, content) {
+                eprintln!("Failed to write {:?}: {:?}", path, err);
+            }
+        }
+    }
+
+    /// Marks that all tests of the suite ran and their validators were
+    /// stopped.
+    pub fn complete(&mut self) {
+        self.completed = true;
+    }
+}
+
+impl Drop for SuiteArtifacts {
+    fn drop(&mut self) {
+        if self.completed && !self.failed {
+            let _ = fs::remove_dir_all(&self.dir);
+        } else {
+            eprintln!(
+                "======== Kept artifacts of failed suite in {} ========",
+                self.dir.display()
+            );
+        }
+    }
+}
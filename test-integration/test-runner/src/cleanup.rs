@@ -1,11 +1,27 @@
 use std::process::{self, Child};
 
-pub fn cleanup_validators(ephem_validator: &mut Child, devnet_validator: &mut Child) {
+pub fn cleanup_validators(
+    ephem_validator: &mut Child,
+    devnet_validator: &mut Child,
+) {
     cleanup_validator(ephem_validator, "ephemeral");
     cleanup_validator(devnet_validator, "devnet");
     kill_validators();
 }
 
+/// Kills only the provided validators, so that validators that other suites
+/// run against in parallel keep running.
+pub fn cleanup_isolated_validators(
+    ephem_validator: &mut Child,
+    devnet_validator: &mut Child,
+) {
+    cleanup_validator(ephem_validator, "ephemeral");
+    cleanup_validator(devnet_validator, "devnet");
+    // Reap the processes so that their ports are released
+    let _ = ephem_validator.wait();
+    let _ = devnet_validator.wait();
+}
+
 pub fn cleanup_devnet_only(devnet_validator: &mut Child) {
     cleanup_validator(devnet_validator, "devnet");
     kill_validators();
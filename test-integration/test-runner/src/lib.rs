@@ -1 +1,2 @@
+pub mod artifacts;
 pub mod cleanup;
@@ -0,0 +1,193 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use dlp::pda::delegation_record_pda_from_delegated_account;
+use serde::Serialize;
+use solana_account_decoder::{UiAccount, UiAccountEncoding};
+use solana_sdk::{account::Account, pubkey::Pubkey};
+use tempfile::{tempdir, TempDir};
+
+use crate::{toml_to_args::ProgramLoader, DELEGATION_PROGRAM_ID};
+
+/// Accounts that every chain validator started by the test runner needs.
+const DEFAULT_ACCOUNTS: [(&str, &str); 2] = [
+    (
+        "mAGicPQYBMvcYveUZA5F5UNNwyHvfYh5xkLS2Fr1mev",
+        "validator-authority.json",
+    ),
+    (
+        "LUzidNSiPNjYNkxZcUm5hYHwnWPwsUfh2US1cpWwaBm",
+        "luzid-authority.json",
+    ),
+];
+
+/// Same format as produced by `solana account --output json` which is what
+/// `solana-test-validator --account` expects.
+#[derive(Serialize)]
+struct AccountFile {
+    pubkey: String,
+    account: UiAccount,
+}
+
+struct FixtureProgram {
+    id: Pubkey,
+    path: PathBuf,
+    loader: ProgramLoader,
+}
+
+// -----------------
+// DevnetFixture
+// -----------------
+/// Accounts and programs that are preloaded into the chain validator
+/// (`solana-test-validator`) at genesis.
+///
+/// Accounts provided in memory are written to a temporary dir which is
+/// removed when the fixture is dropped. The validator reads them while
+/// creating its genesis, so the fixture only needs to outlive its startup.
+///
+/// ```ignore
+/// let fixture = DevnetFixture::with_default_accounts(&workspace_dir)
+///     .with_account(payer.pubkey(), &payer_account)
+///     .with_delegated_account(pda, pda_account, delegation_record);
+/// let args = fixture.to_args();
+/// ```
+pub struct DevnetFixture {
+    accounts_dir: TempDir,
+    accounts: Vec<(Pubkey, PathBuf)>,
+    programs: Vec<FixtureProgram>,
+}
+
+impl Default for DevnetFixture {
+    fn default() -> Self {
+        Self {
+            accounts_dir: tempdir()
+                .expect("Failed to create fixture accounts dir"),
+            accounts: vec![],
+            programs: vec![],
+        }
+    }
+}
+
+impl DevnetFixture {
+    /// Fixture including the validator and luzid authorities found in
+    /// `configs/accounts` of the provided workspace.
+    pub fn with_default_accounts(workspace_dir: &Path) -> Self {
+        let accounts_dir = workspace_dir.join("configs").join("accounts");
+        DEFAULT_ACCOUNTS.iter().fold(
+            Self::default(),
+            |fixture, (pubkey, file)| {
+                fixture.with_account_file(
+                    pubkey.parse().unwrap(),
+                    accounts_dir.join(file),
+                )
+            },
+        )
+    }
+
+    /// Loads the account from a JSON file as produced by
+    /// `solana account --output json`.
+    pub fn with_account_file(
+        mut self,
+        pubkey: Pubkey,
+        path: impl AsRef<Path>,
+    ) -> Self {
+        let path = path.as_ref().canonicalize().unwrap_or_else(|err| {
+            panic!(
+                "Failed to resolve account file {:?}: {:?}",
+                path.as_ref(),
+                err
+            )
+        });
+        self.accounts.push((pubkey, path));
+        self
+    }
+
+    pub fn with_account(mut self, pubkey: Pubkey, account: &Account) -> Self {
+        let path = self.accounts_dir.path().join(format!("{}.json", pubkey));
+        let account_file = AccountFile {
+            pubkey: pubkey.to_string(),
+            account: UiAccount::encode(
+                &pubkey,
+                account,
+                UiAccountEncoding::Base64,
+                None,
+                None,
+            ),
+        };
+        let json = serde_json::to_string_pretty(&account_file)
+            .expect("Failed to serialize fixture account");
+        fs::write(&path, json).unwrap_or_else(|err| {
+            panic!("Failed to write account file {:?}: {:?}", path, err)
+        });
+        self.accounts.push((pubkey, path));
+        self
+    }
+
+    /// Loads the account as delegated, i.e. owned by the delegation program,
+    /// together with its delegation record.
+    /// The [delegation_record] is stored at the record PDA derived from the
+    /// [pubkey] and is owned by the delegation program as well.
+    pub fn with_delegated_account(
+        self,
+        pubkey: Pubkey,
+        account: Account,
+        delegation_record: Account,
+    ) -> Self {
+        let delegation_record_pubkey =
+            delegation_record_pda_from_delegated_account(&pubkey);
+        self.with_account(
+            pubkey,
+            &Account {
+                owner: DELEGATION_PROGRAM_ID,
+                ..account
+            },
+        )
+        .with_account(
+            delegation_record_pubkey,
+            &Account {
+                owner: DELEGATION_PROGRAM_ID,
+                ..delegation_record
+            },
+        )
+    }
+
+    /// Deploys the program found at the provided `.so` [path].
+    pub fn with_program(
+        mut self,
+        id: Pubkey,
+        path: impl AsRef<Path>,
+        loader: ProgramLoader,
+    ) -> Self {
+        let path = path.as_ref().canonicalize().unwrap_or_else(|err| {
+            panic!("Failed to resolve program {:?}: {:?}", path.as_ref(), err)
+        });
+        self.programs.push(FixtureProgram { id, path, loader });
+        self
+    }
+
+    /// Args passed to `solana-test-validator` in order to preload the
+    /// accounts and programs of this fixture.
+    pub fn to_args(&self) -> Vec<String> {
+        let mut args = vec![];
+        for FixtureProgram { id, path, loader } in &self.programs {
+            if *loader == ProgramLoader::UpgradeableProgram {
+                args.push("--upgradeable-program".to_string());
+            } else {
+                args.push("--bpf-program".to_string());
+            }
+            args.push(id.to_string());
+            args.push(path.to_str().unwrap().to_string());
+            if *loader == ProgramLoader::UpgradeableProgram {
+                args.push("none".to_string());
+            }
+        }
+        for (pubkey, path) in &self.accounts {
+            args.push("--account".to_string());
+            args.push(pubkey.to_string());
+            args.push(path.to_str().unwrap().to_string());
+        }
+        args
+    }
+}
@@ -1,4 +1,9 @@
-use std::{str::FromStr, thread::sleep, time::Duration};
+use std::{
+    str::FromStr,
+    sync::OnceLock,
+    thread::sleep,
+    time::{Duration, Instant},
+};
 
 use anyhow::{Context, Result};
 use solana_rpc_client::rpc_client::{
@@ -16,6 +21,7 @@ use solana_sdk::{
     clock::Slot,
     commitment_config::CommitmentConfig,
     hash::Hash,
+    pubkey,
     pubkey::Pubkey,
     signature::{Keypair, Signature},
     transaction::{Transaction, TransactionError},
@@ -24,6 +30,18 @@ use solana_sdk::{
 const URL_CHAIN: &str = "http://localhost:7799";
 const URL_EPHEM: &str = "http://localhost:8899";
 
+pub const DELEGATION_PROGRAM_ID: Pubkey =
+    pubkey!("DELeGGvXpWV2fqJUhqcF5ZSYMS4JTLjteaAMARRSaeSh");
+
+/// Max time the `wait_for_*` helpers wait for a condition to be met.
+pub const WAIT_TIMEOUT: Duration = Duration::from_secs(20);
+const WAIT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Env var overriding the URL of the chain validator the tests connect to.
+pub const CHAIN_URL_ENV: &str = "CHAIN_URL";
+/// Env var overriding the URL of the ephemeral validator the tests connect to.
+pub const EPHEM_URL_ENV: &str = "EPHEM_URL";
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct TransactionStatusWithSignature {
     pub signature: String,
@@ -72,16 +90,18 @@ impl IntegrationTestContext {
     }
 
     pub fn try_new() -> Result<Self> {
+        Self::try_new_with_urls(Self::url_chain(), Self::url_ephem())
+    }
+
+    /// Same as [Self::try_new] but connects to validators that don't listen
+    /// on the default ports.
+    pub fn try_new_with_urls(url_chain: &str, url_ephem: &str) -> Result<Self> {
         let commitment = CommitmentConfig::confirmed();
 
-        let chain_client = RpcClient::new_with_commitment(
-            Self::url_chain().to_string(),
-            commitment,
-        );
-        let ephem_client = RpcClient::new_with_commitment(
-            Self::url_ephem().to_string(),
-            commitment,
-        );
+        let chain_client =
+            RpcClient::new_with_commitment(url_chain.to_string(), commitment);
+        let ephem_client =
+            RpcClient::new_with_commitment(url_ephem.to_string(), commitment);
         let validator_identity = chain_client.get_identity()?;
         let chain_blockhash = chain_client.get_latest_blockhash()?;
         let ephem_blockhash = ephem_client.get_latest_blockhash()?;
@@ -616,6 +636,86 @@ impl IntegrationTestContext {
         Ok(slot)
     }
 
+    // -----------------
+    // Waiting
+    // -----------------
+    /// Waits until the chain account is no longer owned by the delegation
+    /// program and returns it.
+    pub fn wait_for_undelegation(&self, pubkey: Pubkey) -> Result<Account> {
+        Self::poll_until(
+            WAIT_TIMEOUT,
+            || format!("undelegation of '{}'", pubkey),
+            || {
+                Ok(self
+                    .fetch_chain_account(pubkey)
+                    .ok()
+                    .filter(|account| account.owner != DELEGATION_PROGRAM_ID))
+            },
+        )
+    }
+
+    /// Waits until the chain account is owned by [owner] and returns it.
+    pub fn wait_for_account_owner_chain(
+        &self,
+        pubkey: Pubkey,
+        owner: Pubkey,
+    ) -> Result<Account> {
+        self.try_chain_client()?;
+        Self::poll_until(
+            WAIT_TIMEOUT,
+            || format!("chain account '{}' to be owned by '{}'", pubkey, owner),
+            || {
+                Ok(self
+                    .fetch_chain_account(pubkey)
+                    .ok()
+                    .filter(|account| account.owner == owner))
+            },
+        )
+    }
+
+    /// Waits until the ephemeral account is owned by [owner] and returns it.
+    pub fn wait_for_account_owner_ephem(
+        &self,
+        pubkey: Pubkey,
+        owner: Pubkey,
+    ) -> Result<Account> {
+        Self::poll_until(
+            WAIT_TIMEOUT,
+            || format!("ephem account '{}' to be owned by '{}'", pubkey, owner),
+            || {
+                Ok(self
+                    .fetch_ephem_account(pubkey)
+                    .ok()
+                    .filter(|account| account.owner == owner))
+            },
+        )
+    }
+
+    /// Calls [check] every [WAIT_POLL_INTERVAL] until it returns a value and
+    /// fails with the [description] of what we waited for once the [timeout]
+    /// passed.
+    /// Errors returned by [check] are returned right away.
+    pub fn poll_until<T>(
+        timeout: Duration,
+        description: impl Fn() -> String,
+        mut check: impl FnMut() -> Result<Option<T>>,
+    ) -> Result<T> {
+        let start = Instant::now();
+        loop {
+            if let Some(value) = check()? {
+                return Ok(value);
+            }
+            if start.elapsed() >= timeout {
+                anyhow::bail!(
+                    "Timed out after {:?} waiting for {}",
+                    timeout,
+                    description()
+                );
+            }
+            sleep(WAIT_POLL_INTERVAL);
+        }
+    }
+
     // -----------------
     // Blockhash
     // -----------------
@@ -641,9 +741,15 @@ impl IntegrationTestContext {
     // RPC Clients
     // -----------------
     pub fn url_ephem() -> &'static str {
-        URL_EPHEM
+        static URL: OnceLock<String> = OnceLock::new();
+        URL.get_or_init(|| url_from_env(EPHEM_URL_ENV, URL_EPHEM))
     }
     pub fn url_chain() -> &'static str {
-        URL_CHAIN
+        static URL: OnceLock<String> = OnceLock::new();
+        URL.get_or_init(|| url_from_env(CHAIN_URL_ENV, URL_CHAIN))
     }
 }
+
+fn url_from_env(env_var: &str, default_url: &str) -> String {
+    std::env::var(env_var).unwrap_or_else(|_| default_url.to_string())
+}
@@ -1,4 +1,5 @@
 pub mod conversions;
+pub mod devnet_fixture;
 mod integration_test_context;
 mod run_test;
 pub mod scheduled_commits;
@@ -7,5 +8,8 @@ pub mod workspace_paths;
 
 pub mod toml_to_args;
 pub mod validator;
-pub use integration_test_context::IntegrationTestContext;
+pub use integration_test_context::{
+    IntegrationTestContext, CHAIN_URL_ENV, DELEGATION_PROGRAM_ID,
+    EPHEM_URL_ENV, WAIT_TIMEOUT,
+};
 pub use run_test::*;
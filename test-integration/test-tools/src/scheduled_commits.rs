@@ -5,7 +5,7 @@ use std::{collections::HashMap, fmt};
 use borsh::BorshDeserialize;
 use solana_sdk::{pubkey::Pubkey, signature::Signature};
 
-use crate::IntegrationTestContext;
+use crate::{integration_test_context::WAIT_TIMEOUT, IntegrationTestContext};
 
 // -----------------
 // Log Extractors
@@ -106,26 +106,66 @@ where
         &self,
         ctx: &IntegrationTestContext,
     ) -> Result<()> {
-        for sig in &self.sigs {
-            let confirmed =
-                ctx.confirm_transaction_chain(sig).with_context(|| {
-                    format!(
-                        "Transaction with sig {:?} confirmation on chain failed",
-                        sig
-                    )
-                })?;
-            if !confirmed {
-                bail!(
-                    "Transaction {:?} not confirmed on chain within timeout",
+        confirm_commit_transactions_on_chain(ctx, &self.sigs)
+    }
+}
+
+fn confirm_commit_transactions_on_chain(
+    ctx: &IntegrationTestContext,
+    sigs: &[Signature],
+) -> Result<()> {
+    for sig in sigs {
+        let confirmed =
+            ctx.confirm_transaction_chain(sig).with_context(|| {
+                format!(
+                    "Transaction with sig {:?} confirmation on chain failed",
                     sig
-                );
-            }
+                )
+            })?;
+        if !confirmed {
+            bail!(
+                "Transaction {:?} not confirmed on chain within timeout",
+                sig
+            );
         }
-        Ok(())
     }
+    Ok(())
 }
 
 impl IntegrationTestContext {
+    /// Waits until the commits scheduled by the ephemeral transaction [sig]
+    /// were sent and confirmed on chain and returns their signatures.
+    pub fn wait_for_commit_confirmed(
+        &self,
+        sig: Signature,
+    ) -> Result<Vec<Signature>> {
+        let scheduled_commit_sent_sig = Self::poll_until(
+            WAIT_TIMEOUT,
+            || format!("commit scheduled by {:?} to be sent", sig),
+            || {
+                Ok(self.fetch_ephemeral_logs(sig).and_then(|logs| {
+                    extract_scheduled_commit_sent_signature_from_logs(&logs)
+                }))
+            },
+        )?;
+        let sigs = Self::poll_until(
+            WAIT_TIMEOUT,
+            || {
+                format!(
+                    "logs of scheduled commit sent {:?}",
+                    scheduled_commit_sent_sig
+                )
+            },
+            || {
+                Ok(self
+                    .fetch_ephemeral_logs(scheduled_commit_sent_sig)
+                    .map(|logs| extract_sent_commit_info_from_logs(&logs).2))
+            },
+        )?;
+        confirm_commit_transactions_on_chain(self, &sigs)?;
+        Ok(sigs)
+    }
+
     pub fn fetch_schedule_commit_result<T>(
         &self,
         sig: Signature,
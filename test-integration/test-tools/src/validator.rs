@@ -1,12 +1,19 @@
 use std::{
-    net::TcpStream,
+    fs::File,
+    net::{TcpListener, TcpStream},
     path::{Path, PathBuf},
     process::{self, Child},
+    sync::atomic::{AtomicU16, Ordering},
     thread::sleep,
     time::Duration,
 };
 
-use crate::toml_to_args::rpc_port_from_config;
+use tempfile::{tempdir, TempDir};
+
+use crate::{
+    devnet_fixture::DevnetFixture,
+    toml_to_args::{config_to_args, rpc_port_from_config, ProgramLoader},
+};
 
 pub fn start_magic_block_validator_with_config(
     test_runner_paths: &TestRunnerPaths,
@@ -43,6 +50,11 @@ pub fn start_magic_block_validator_with_config(
         .arg(config_path)
         .env("RUST_LOG_STYLE", log_suffix)
         .current_dir(root_dir);
+    capture_magic_block_validator_output(
+        &mut command,
+        test_runner_paths,
+        log_suffix,
+    );
 
     eprintln!("Starting validator with {:?}", command);
 
@@ -50,6 +62,199 @@ pub fn start_magic_block_validator_with_config(
     wait_for_validator(validator, port)
 }
 
+/// Same as [start_magic_block_validator_with_config] but overrides the ports,
+/// ledger and remote of the config with the ones of the [pair], so that it
+/// doesn't interfere with validators of other pairs.
+/// The validator binary is run directly instead of via `cargo run` so that
+/// killing the returned child kills the validator.
+pub fn start_isolated_magic_block_validator_with_config(
+    test_runner_paths: &TestRunnerPaths,
+    log_suffix: &str,
+    release: bool,
+    pair: &IsolatedValidatorPair,
+    cluster: IsolatedCluster,
+) -> Option<process::Child> {
+    let TestRunnerPaths {
+        config_path,
+        root_dir,
+        ..
+    } = test_runner_paths;
+
+    let mut build = process::Command::new("cargo");
+    build.arg("build").arg("--bin").arg("rpc");
+    if release {
+        build.arg("--release");
+    }
+    let build_res = build.current_dir(root_dir.clone()).output();
+    if build_res.map_or(true, |output| !output.status.success()) {
+        eprintln!("Failed to build validator");
+        return None;
+    }
+
+    let profile = if release { "release" } else { "debug" };
+    let mut command = process::Command::new(
+        root_dir.join("target").join(profile).join("rpc"),
+    );
+    command
+        .arg(config_path)
+        .envs(pair.magic_block_validator_envs(cluster))
+        .env("RUST_LOG_STYLE", log_suffix)
+        .current_dir(root_dir);
+    capture_magic_block_validator_output(
+        &mut command,
+        test_runner_paths,
+        log_suffix,
+    );
+
+    eprintln!("Starting isolated validator with {:?}", command);
+
+    let validator = command.spawn().expect("Failed to start validator");
+    wait_for_validator(validator, pair.rpc_port(cluster))
+}
+
+pub fn start_test_validator_with_config(
+    test_runner_paths: &TestRunnerPaths,
+    program_loader: Option<ProgramLoader>,
+    log_suffix: &str,
+) -> Option<process::Child> {
+    let fixture =
+        DevnetFixture::with_default_accounts(&test_runner_paths.workspace_dir);
+    start_test_validator_with_fixture(
+        test_runner_paths,
+        program_loader,
+        log_suffix,
+        &fixture,
+    )
+}
+
+/// Same as [start_test_validator_with_config] but preloads the accounts and
+/// programs of the provided [fixture] instead of the default accounts.
+pub fn start_test_validator_with_fixture(
+    test_runner_paths: &TestRunnerPaths,
+    program_loader: Option<ProgramLoader>,
+    log_suffix: &str,
+    fixture: &DevnetFixture,
+) -> Option<process::Child> {
+    let port = rpc_port_from_config(&test_runner_paths.config_path);
+    let args = test_validator_args(test_runner_paths, program_loader, fixture);
+    spawn_test_validator(args, port, test_runner_paths, log_suffix)
+}
+
+/// Same as [start_test_validator_with_fixture] but listens on the chain ports
+/// of the [pair] and writes its ledger into the dir of the [pair].
+pub fn start_isolated_test_validator_with_config(
+    test_runner_paths: &TestRunnerPaths,
+    program_loader: Option<ProgramLoader>,
+    log_suffix: &str,
+    fixture: &DevnetFixture,
+    pair: &IsolatedValidatorPair,
+) -> Option<process::Child> {
+    let mut args =
+        test_validator_args(test_runner_paths, program_loader, fixture);
+    // The last occurrence of an arg wins, so these override the config
+    args.extend(pair.test_validator_args());
+    spawn_test_validator(args, pair.chain_rpc, test_runner_paths, log_suffix)
+}
+
+fn test_validator_args(
+    test_runner_paths: &TestRunnerPaths,
+    program_loader: Option<ProgramLoader>,
+    fixture: &DevnetFixture,
+) -> Vec<String> {
+    let mut args =
+        config_to_args(&test_runner_paths.config_path, program_loader);
+    args.extend(fixture.to_args());
+    args
+}
+
+/// Starts a `solana-test-validator` with the provided args and waits until
+/// its RPC listens on the provided port.
+pub fn start_test_validator_with_args(
+    args: Vec<String>,
+    port: u16,
+    root_dir: &Path,
+    log_suffix: &str,
+) -> Option<process::Child> {
+    let mut command = test_validator_command(args, root_dir, log_suffix);
+    eprintln!("Starting test validator with {:?}", command);
+    let validator = command.spawn().expect("Failed to start validator");
+    wait_for_validator(validator, port)
+}
+
+fn spawn_test_validator(
+    mut args: Vec<String>,
+    port: u16,
+    test_runner_paths: &TestRunnerPaths,
+    log_suffix: &str,
+) -> Option<process::Child> {
+    if let Some(output_dir) = &test_runner_paths.output_dir {
+        // The last occurrence of an arg wins, so this overrides the ledger
+        // of the config and isolated pair
+        args.push("--ledger".to_string());
+        args.push(
+            ledger_output_path(output_dir, log_suffix)
+                .to_str()
+                .unwrap()
+                .to_string(),
+        );
+    }
+    let mut command =
+        test_validator_command(args, &test_runner_paths.root_dir, log_suffix);
+    if let Some(output_dir) = &test_runner_paths.output_dir {
+        redirect_output(&mut command, output_dir, log_suffix);
+    }
+    eprintln!("Starting test validator with {:?}", command);
+    let validator = command.spawn().expect("Failed to start validator");
+    wait_for_validator(validator, port)
+}
+
+fn test_validator_command(
+    args: Vec<String>,
+    root_dir: &Path,
+    log_suffix: &str,
+) -> process::Command {
+    let mut command = process::Command::new("solana-test-validator");
+    command
+        .args(args)
+        .env("RUST_LOG", "solana=warn")
+        .env("RUST_LOG_STYLE", log_suffix)
+        .current_dir(root_dir);
+    command
+}
+
+/// Writes the ledger and logs of the validator into the
+/// [TestRunnerPaths::output_dir] if one is set.
+fn capture_magic_block_validator_output(
+    command: &mut process::Command,
+    test_runner_paths: &TestRunnerPaths,
+    log_suffix: &str,
+) {
+    if let Some(output_dir) = &test_runner_paths.output_dir {
+        command.env("LEDGER_PATH", ledger_output_path(output_dir, log_suffix));
+        redirect_output(command, output_dir, log_suffix);
+    }
+}
+
+fn ledger_output_path(output_dir: &Path, log_suffix: &str) -> PathBuf {
+    output_dir.join(format!("{}-ledger", log_suffix.to_lowercase()))
+}
+
+/// Redirects stdout and stderr of the validator into one log file inside the
+/// [output_dir] which survives the validator being killed.
+fn redirect_output(
+    command: &mut process::Command,
+    output_dir: &Path,
+    log_suffix: &str,
+) {
+    let log_path =
+        output_dir.join(format!("{}.log", log_suffix.to_lowercase()));
+    let stdout = File::create(&log_path).unwrap_or_else(|err| {
+        panic!("Failed to create log file {:?}: {:?}", log_path, err)
+    });
+    let stderr = stdout.try_clone().expect("Failed to clone log file");
+    command.stdout(stdout).stderr(stderr);
+}
+
 pub fn wait_for_validator(mut validator: Child, port: u16) -> Option<Child> {
     let mut count = 0;
     loop {
@@ -67,11 +272,168 @@ pub fn wait_for_validator(mut validator: Child, port: u16) -> Option<Child> {
     }
 }
 
+// -----------------
+// Isolated Validators
+// -----------------
+const ISOLATED_PORTS_START: u16 = 20_000;
+const ISOLATED_PORTS_BLOCK_SIZE: u16 = 64;
+const ISOLATED_DYNAMIC_PORTS_OFFSET: u16 = 16;
+
+static NEXT_ISOLATED_PORTS_BLOCK: AtomicU16 = AtomicU16::new(0);
+
+/// The validator of an [IsolatedValidatorPair].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IsolatedCluster {
+    Chain,
+    Ephem,
+}
+
+/// Ports and ledger dirs of a chain and ephemeral validator that don't
+/// clash with the ones of any other pair, which allows running test suites
+/// against separate validators in parallel.
+/// The pubsub of each validator listens on its RPC port + 1.
+#[derive(Debug)]
+pub struct IsolatedValidatorPair {
+    pub chain_rpc: u16,
+    pub chain_faucet: u16,
+    pub chain_gossip: u16,
+    pub chain_geyser_grpc: u16,
+    pub chain_metrics: u16,
+    pub chain_dynamic_ports: (u16, u16),
+    pub ephem_rpc: u16,
+    pub ephem_geyser_grpc: u16,
+    pub ephem_metrics: u16,
+    ledgers_dir: TempDir,
+}
+
+impl IsolatedValidatorPair {
+    /// Allocates the next block of ports which are all free to bind.
+    pub fn allocate() -> Self {
+        let base = loop {
+            let block =
+                NEXT_ISOLATED_PORTS_BLOCK.fetch_add(1, Ordering::SeqCst);
+            let base = block
+                .checked_mul(ISOLATED_PORTS_BLOCK_SIZE)
+                .and_then(|offset| offset.checked_add(ISOLATED_PORTS_START))
+                .filter(|base| {
+                    base.checked_add(ISOLATED_PORTS_BLOCK_SIZE).is_some()
+                })
+                .expect("Ran out of ports for isolated validators");
+            let all_free = (base..base + ISOLATED_PORTS_BLOCK_SIZE)
+                .all(|port| TcpListener::bind(("0.0.0.0", port)).is_ok());
+            if all_free {
+                break base;
+            }
+        };
+        Self {
+            chain_rpc: base,
+            chain_faucet: base + 2,
+            chain_gossip: base + 3,
+            chain_geyser_grpc: base + 4,
+            chain_metrics: base + 5,
+            ephem_rpc: base + 6,
+            ephem_geyser_grpc: base + 8,
+            ephem_metrics: base + 9,
+            chain_dynamic_ports: (
+                base + ISOLATED_DYNAMIC_PORTS_OFFSET,
+                base + ISOLATED_PORTS_BLOCK_SIZE - 1,
+            ),
+            ledgers_dir: tempdir().expect("Failed to create ledgers dir"),
+        }
+    }
+
+    pub fn rpc_port(&self, cluster: IsolatedCluster) -> u16 {
+        match cluster {
+            IsolatedCluster::Chain => self.chain_rpc,
+            IsolatedCluster::Ephem => self.ephem_rpc,
+        }
+    }
+
+    pub fn chain_url(&self) -> String {
+        format!("http://localhost:{}", self.chain_rpc)
+    }
+
+    pub fn ephem_url(&self) -> String {
+        format!("http://localhost:{}", self.ephem_rpc)
+    }
+
+    pub fn ledger_path(&self, cluster: IsolatedCluster) -> PathBuf {
+        match cluster {
+            IsolatedCluster::Chain => self.ledgers_dir.path().join("chain"),
+            IsolatedCluster::Ephem => self.ledgers_dir.path().join("ephem"),
+        }
+    }
+
+    /// Env vars which make an [IntegrationTestContext] of a test process
+    /// connect to the validators of this pair.
+    ///
+    /// [IntegrationTestContext]: crate::IntegrationTestContext
+    pub fn test_envs(&self) -> Vec<(&'static str, String)> {
+        vec![
+            (crate::CHAIN_URL_ENV, self.chain_url()),
+            (crate::EPHEM_URL_ENV, self.ephem_url()),
+        ]
+    }
+
+    fn test_validator_args(&self) -> Vec<String> {
+        let (dynamic_start, dynamic_end) = self.chain_dynamic_ports;
+        vec![
+            "--rpc-port".to_string(),
+            self.chain_rpc.to_string(),
+            "--faucet-port".to_string(),
+            self.chain_faucet.to_string(),
+            "--gossip-port".to_string(),
+            self.chain_gossip.to_string(),
+            "--dynamic-port-range".to_string(),
+            format!("{}-{}", dynamic_start, dynamic_end),
+            "--ledger".to_string(),
+            self.ledger_path(IsolatedCluster::Chain)
+                .to_str()
+                .unwrap()
+                .to_string(),
+        ]
+    }
+
+    fn magic_block_validator_envs(
+        &self,
+        cluster: IsolatedCluster,
+    ) -> Vec<(&'static str, String)> {
+        let (rpc, geyser_grpc, metrics) = match cluster {
+            IsolatedCluster::Chain => {
+                (self.chain_rpc, self.chain_geyser_grpc, self.chain_metrics)
+            }
+            IsolatedCluster::Ephem => {
+                (self.ephem_rpc, self.ephem_geyser_grpc, self.ephem_metrics)
+            }
+        };
+        let mut envs = vec![
+            ("RPC_PORT", rpc.to_string()),
+            ("GEYSER_GRPC_PORT", geyser_grpc.to_string()),
+            ("METRICS_PORT", metrics.to_string()),
+            (
+                "LEDGER_PATH",
+                self.ledger_path(cluster).to_str().unwrap().to_string(),
+            ),
+        ];
+        if cluster == IsolatedCluster::Ephem {
+            envs.push(("ACCOUNTS_REMOTE", self.chain_url()));
+            envs.push((
+                "ACCOUNTS_REMOTE_WS",
+                format!("ws://localhost:{}", self.chain_rpc + 1),
+            ));
+        }
+        envs
+    }
+}
+
 /// Directories
 pub struct TestRunnerPaths {
     pub config_path: PathBuf,
     pub root_dir: PathBuf,
     pub workspace_dir: PathBuf,
+    /// If set the validator writes its logs and ledger into this dir instead
+    /// of the console and the ledger dir of its config
+    pub output_dir: Option<PathBuf>,
 }
 
 pub fn resolve_workspace_dir() -> PathBuf {
@@ -2,9 +2,14 @@ use std::sync::Arc;
 
 use magicblock_accounts_db::accounts_update_notifier_interface::AccountsUpdateNotifier;
 use magicblock_bank::{
-    bank::Bank, slot_status_notifier_interface::SlotStatusNotifierArc,
+    bank::Bank,
+    blockhash_expiry::BlockhashExpiry,
+    genesis_utils::create_genesis_config_with_leader_and_fees,
+    slot_status_notifier_interface::{
+        SlotStatusNotifierArc, SlotStatusNotifiers,
+    },
     transaction_logs::TransactionLogCollectorFilter,
-    EPHEM_DEFAULT_MILLIS_PER_SLOT,
+    DEFAULT_TRANSACTION_EXPIRATION_MILLIS, EPHEM_DEFAULT_MILLIS_PER_SLOT,
 };
 use solana_sdk::{genesis_config::GenesisConfig, pubkey::Pubkey};
 use solana_svm::runtime_config::RuntimeConfig;
@@ -22,6 +27,10 @@ pub fn bank_for_tests_with_identity(
 ) -> Bank {
     let runtime_config = Arc::new(RuntimeConfig::default());
     let accounts_paths = vec![];
+    let slot_status_notifiers = SlotStatusNotifiers::default();
+    if let Some(slot_status_notifier) = slot_status_notifier {
+        slot_status_notifiers.register("test", slot_status_notifier);
+    }
     let bank = Bank::new(
         genesis_config,
         runtime_config,
@@ -30,9 +39,12 @@ pub fn bank_for_tests_with_identity(
         false,
         accounts_paths,
         accounts_update_notifier,
-        slot_status_notifier,
+        slot_status_notifiers,
         millis_per_slot,
+        DEFAULT_TRANSACTION_EXPIRATION_MILLIS,
+        BlockhashExpiry::default(),
         identity_id,
+        Default::default(),
     );
     bank.transaction_log_collector_config
         .write()
@@ -54,3 +66,14 @@ pub fn bank_for_tests(
         Pubkey::new_unique(),
     )
 }
+
+/// Creates a bank from a genesis config with fees and a random leader, which
+/// is the setup most tests need.
+pub fn bank_with_fees_for_tests() -> Bank {
+    let genesis_config = create_genesis_config_with_leader_and_fees(
+        u64::MAX,
+        &Pubkey::new_unique(),
+    )
+    .genesis_config;
+    bank_for_tests(&genesis_config, None, None)
+}
@@ -1,18 +1,12 @@
 use std::{collections::HashMap, sync::Arc};
 
 use magicblock_accounts_db::transaction_results::TransactionResults;
-use magicblock_bank::{
-    bank::{Bank, TransactionExecutionRecordingOpts},
-    genesis_utils::create_genesis_config_with_leader_and_fees,
-};
+use magicblock_bank::bank::{Bank, TransactionExecutionRecordingOpts};
 use solana_program_runtime::timings::ExecuteTimings;
-use solana_sdk::{
-    pubkey::Pubkey,
-    transaction::{SanitizedTransaction, Transaction},
-};
+use solana_sdk::transaction::{SanitizedTransaction, Transaction};
 
 use crate::{
-    bank::bank_for_tests,
+    bank::bank_with_fees_for_tests,
     traits::{TransactionsProcessor, TransactionsProcessorProcessResult},
 };
 
@@ -29,13 +23,7 @@ impl BankTransactionsProcessor {
 
 impl Default for BankTransactionsProcessor {
     fn default() -> Self {
-        let genesis_config = create_genesis_config_with_leader_and_fees(
-            u64::MAX,
-            &Pubkey::new_unique(),
-        )
-        .genesis_config;
-        let bank = Arc::new(bank_for_tests(&genesis_config, None, None));
-        Self::new(bank)
+        Self::new(Arc::new(bank_with_fees_for_tests()))
     }
 }
 
@@ -10,16 +10,16 @@ use std::{
 use log::*;
 use magicblock_bank::bank::Bank;
 use magicblock_core::traits::PersistsAccountModData;
-use magicblock_program::{init_persister, validator};
 use solana_sdk::native_token::LAMPORTS_PER_SOL;
 
-use crate::account::fund_account;
+use crate::{account::fund_account, bank::bank_with_fees_for_tests};
 
 fn ensure_funded_validator(bank: &Bank) {
-    validator::generate_validator_authority_if_needed();
+    let context = bank.validator_context();
+    context.generate_validator_authority_if_needed();
     fund_account(
         bank,
-        &validator::validator_authority_id(),
+        &context.validator_authority_id(),
         LAMPORTS_PER_SOL * 1_000,
     );
 }
@@ -58,9 +58,19 @@ impl PersistsAccountModData for PersisterStub {
     }
 }
 
+/// Funds the authority of the validator context owned by the [bank] and
+/// marks the validator as running.
 pub fn init_started_validator(bank: &Bank) {
     ensure_funded_validator(bank);
     let stub = Arc::new(PersisterStub::default());
-    init_persister(stub);
-    validator::ensure_started_up();
+    let context = bank.validator_context();
+    context.data_mods().init_persister(stub);
+    context.ensure_started_up();
+}
+
+/// Creates a bank with fees whose validator is funded and marked as running.
+pub fn bank_with_started_validator() -> Arc<Bank> {
+    let bank = Arc::new(bank_with_fees_for_tests());
+    init_started_validator(&bank);
+    bank
 }
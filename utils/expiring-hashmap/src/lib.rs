@@ -1,6 +1,6 @@
 use std::{
     collections::{HashMap, VecDeque},
-    sync::{Arc, RwLock},
+    sync::{Arc, PoisonError, RwLock},
 };
 
 #[derive(Debug, Clone)]
@@ -38,13 +38,13 @@ where
     pub fn get(&self, key: &K) -> Option<V> {
         self.0
             .read()
-            .expect("RwLock poisoned")
+            .unwrap_or_else(PoisonError::into_inner)
             .get(key)
             .map(|e| e.value.clone())
     }
 
     pub fn len(&self) -> usize {
-        self.0.read().expect("RwLock poisoned").len()
+        self.0.read().unwrap_or_else(PoisonError::into_inner).len()
     }
 
     pub fn is_empty(&self) -> bool {
@@ -100,7 +100,7 @@ where
 
         // 2. Remove entries that expired unless they were updated more recently
         let n_keys_to_drain = {
-            let vec = self.vec.read().expect("RwLock vec poisoned");
+            let vec = self.vec.read().unwrap_or_else(PoisonError::into_inner);
             let mut n = 0;
             // Find all keys up to the first one that isn't expired yet
             while let Some(ts_entry) = vec.get(n) {
@@ -117,7 +117,7 @@ where
             Some(
                 self.vec
                     .write()
-                    .expect("RwLock vec poisoned")
+                    .unwrap_or_else(PoisonError::into_inner)
                     .drain(0..n_keys_to_drain)
                     .map(|e| e.key)
                     .collect::<Vec<_>>(),
@@ -138,14 +138,14 @@ where
     fn vec_push(&self, key: TimestampedKey<K>) {
         self.vec
             .write()
-            .expect("RwLock vec poisoned")
+            .unwrap_or_else(PoisonError::into_inner)
             .push_back(key);
     }
 
     fn map_decrease_count_and_maybe_remove(&self, keys: &[K]) {
         // If a particular entry was updated multiple times it is present in our timestamp buffer
         // at multiple indexes. We want to remove it only once we find the last of those.
-        let map = &mut self.map.write().expect("RwLock map poisoned");
+        let map = &mut self.map.write().unwrap_or_else(PoisonError::into_inner);
         for key in keys {
             let remove = if let Some(entry) = map.get_mut(key) {
                 entry.count -= 1;
@@ -164,12 +164,12 @@ where
     fn map_contains_key(&self, key: &K) -> bool {
         self.map
             .read()
-            .expect("RwLock map poisoned")
+            .unwrap_or_else(PoisonError::into_inner)
             .contains_key(key)
     }
 
     fn map_insert_or_increase_count(&self, key: &K, value: V) {
-        let map = &mut self.map.write().expect("RwLock map poisoned");
+        let map = &mut self.map.write().unwrap_or_else(PoisonError::into_inner);
         if let Some(entry) = map.get_mut(key) {
             entry.count += 1;
             entry.value = value;
@@ -180,7 +180,10 @@ where
     }
 
     fn map_len(&self) -> usize {
-        self.map.read().expect("RwLock map poisoned").len()
+        self.map
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len()
     }
 
     /// Check if the map contains the given key.
@@ -192,7 +195,7 @@ where
     pub fn get_cloned(&self, key: &K) -> Option<V> {
         self.map
             .read()
-            .expect("RwLock map poisoned")
+            .unwrap_or_else(PoisonError::into_inner)
             .get(key)
             .map(|entry| entry.value.clone())
     }